@@ -0,0 +1,102 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tpscube_core::{
+    scramble_2x2x2_sourced, scramble_3x3x3_sourced, scramble_4x4x4_fast, Cube, Cube2x2x2,
+    Cube2x2x2Faces, Cube3x3x3, Cube3x3x3Faces, Cube4x4x4, Cube4x4x4Faces, InitialCubeState, Move,
+    SeededRandomSource,
+};
+
+fn move_benchmark(c: &mut Criterion) {
+    let mut rng = SeededRandomSource::new(0);
+    let moves_2x2x2 = scramble_2x2x2_sourced(&mut rng);
+    let moves_3x3x3 = scramble_3x3x3_sourced(&mut rng);
+    let moves_4x4x4 = scramble_4x4x4_fast();
+
+    let mut group = c.benchmark_group("do_moves");
+    bench_moves::<Cube2x2x2>(&mut group, "2x2x2", &moves_2x2x2);
+    bench_moves::<Cube2x2x2Faces>(&mut group, "2x2x2_faces", &moves_2x2x2);
+    bench_moves::<Cube3x3x3>(&mut group, "3x3x3", &moves_3x3x3);
+    bench_moves::<Cube3x3x3Faces>(&mut group, "3x3x3_faces", &moves_3x3x3);
+    bench_moves::<Cube4x4x4>(&mut group, "4x4x4", &moves_4x4x4);
+    bench_moves::<Cube4x4x4Faces>(&mut group, "4x4x4_faces", &moves_4x4x4);
+    group.finish();
+}
+
+fn bench_moves<T: Cube + InitialCubeState>(
+    group: &mut criterion::BenchmarkGroup<criterion::measurement::WallTime>,
+    name: &str,
+    moves: &[Move],
+) {
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            let mut cube = T::new();
+            cube.do_moves(black_box(moves));
+            cube
+        })
+    });
+}
+
+fn conversion_benchmark(c: &mut Criterion) {
+    let mut rng = SeededRandomSource::new(0);
+    let cube_2x2x2 = Cube2x2x2::sourced_random(&mut rng);
+    let faces_2x2x2 = cube_2x2x2.as_faces();
+    let cube_3x3x3 = Cube3x3x3::sourced_random(&mut rng);
+    let faces_3x3x3 = cube_3x3x3.as_faces();
+    let cube_4x4x4 = Cube4x4x4::sourced_random(&mut rng);
+    let faces_4x4x4 = cube_4x4x4.as_faces();
+
+    let mut group = c.benchmark_group("convert");
+    group.bench_function("2x2x2_as_faces", |b| {
+        b.iter(|| black_box(&cube_2x2x2).as_faces())
+    });
+    group.bench_function("2x2x2_as_pieces", |b| {
+        b.iter(|| black_box(&faces_2x2x2).as_pieces())
+    });
+    group.bench_function("3x3x3_as_faces", |b| {
+        b.iter(|| black_box(&cube_3x3x3).as_faces())
+    });
+    group.bench_function("3x3x3_as_pieces", |b| {
+        b.iter(|| black_box(&faces_3x3x3).as_pieces())
+    });
+    group.bench_function("4x4x4_as_faces", |b| {
+        b.iter(|| black_box(&cube_4x4x4).as_faces())
+    });
+    group.bench_function("4x4x4_as_pieces", |b| {
+        b.iter(|| black_box(&faces_4x4x4).as_pieces())
+    });
+    group.finish();
+}
+
+fn index_benchmark(c: &mut Criterion) {
+    let mut rng = SeededRandomSource::new(0);
+    let cube_2x2x2 = Cube2x2x2::sourced_random(&mut rng);
+    let cube_3x3x3 = Cube3x3x3::sourced_random(&mut rng);
+
+    let mut group = c.benchmark_group("index");
+    group.bench_function("2x2x2_corner_orientation", |b| {
+        b.iter(|| black_box(&cube_2x2x2).corner_orientation_index())
+    });
+    group.bench_function("2x2x2_corner_permutation", |b| {
+        b.iter(|| black_box(&cube_2x2x2).corner_permutation_index())
+    });
+    group.bench_function("3x3x3_corner_orientation", |b| {
+        b.iter(|| black_box(&cube_3x3x3).corner_orientation_index())
+    });
+    group.bench_function("3x3x3_corner_permutation", |b| {
+        b.iter(|| black_box(&cube_3x3x3).corner_permutation_index())
+    });
+    group.bench_function("3x3x3_edge_orientation", |b| {
+        b.iter(|| black_box(&cube_3x3x3).edge_orientation_index())
+    });
+    group.bench_function("3x3x3_equatorial_edge_slice", |b| {
+        b.iter(|| black_box(&cube_3x3x3).equatorial_edge_slice_index())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    move_benchmark,
+    conversion_benchmark,
+    index_benchmark
+);
+criterion_main!(benches);
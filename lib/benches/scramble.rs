@@ -0,0 +1,50 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tpscube_core::{
+    scramble_2x2x2_sourced, scramble_3x3x3_batch_sourced, scramble_3x3x3_fast,
+    scramble_3x3x3_sourced, scramble_4x4x4, SeededRandomSource,
+};
+
+const BATCH_SIZES: [usize; 3] = [5, 35, 100];
+
+fn scramble_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scramble_3x3x3");
+    group.sample_size(10);
+
+    for size in BATCH_SIZES.iter() {
+        group.bench_with_input(BenchmarkId::new("sequential", size), size, |b, size| {
+            let mut rng = SeededRandomSource::new(0);
+            b.iter(|| {
+                (0..*size)
+                    .map(|_| scramble_3x3x3_sourced(&mut rng))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", size), size, |b, size| {
+            let mut rng = SeededRandomSource::new(0);
+            b.iter(|| scramble_3x3x3_batch_sourced(&mut rng, *size))
+        });
+    }
+
+    group.finish();
+}
+
+fn puzzle_scramble_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scramble");
+    group.sample_size(10);
+
+    group.bench_function("2x2x2", |b| {
+        let mut rng = SeededRandomSource::new(0);
+        b.iter(|| scramble_2x2x2_sourced(&mut rng))
+    });
+    group.bench_function("3x3x3", |b| {
+        let mut rng = SeededRandomSource::new(0);
+        b.iter(|| scramble_3x3x3_sourced(&mut rng))
+    });
+    group.bench_function("3x3x3_fast", |b| b.iter(scramble_3x3x3_fast));
+    group.bench_function("4x4x4", |b| b.iter(scramble_4x4x4));
+
+    group.finish();
+}
+
+criterion_group!(benches, scramble_benchmark, puzzle_scramble_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::time::Duration;
+use tpscube_core::{
+    Cube, Cube2x2x2, Cube3x3x3, InitialCubeState, SeededRandomSource, SolverOptions,
+};
+
+#[cfg(feature = "large-tables")]
+use tpscube_core::{prepare_large_solver_tables, set_solver_table_size, SolverTableSize};
+
+const SOLVE_COUNT: usize = 10;
+
+/// Solver table variants to benchmark. Build with `--features large-tables`
+/// to compare the standard tables against the large tables.
+#[cfg(not(feature = "large-tables"))]
+fn table_variants() -> Vec<(&'static str, Box<dyn Fn()>)> {
+    vec![("standard", Box::new(|| {}))]
+}
+
+#[cfg(feature = "large-tables")]
+fn table_variants() -> Vec<(&'static str, Box<dyn Fn()>)> {
+    prepare_large_solver_tables().unwrap();
+    vec![
+        (
+            "standard",
+            Box::new(|| set_solver_table_size(SolverTableSize::Standard)),
+        ),
+        (
+            "large",
+            Box::new(|| set_solver_table_size(SolverTableSize::Large)),
+        ),
+    ]
+}
+
+fn random_cubes<T: InitialCubeState>() -> Vec<T> {
+    let mut rng = SeededRandomSource::new(0);
+    (0..SOLVE_COUNT)
+        .map(|_| T::sourced_random(&mut rng))
+        .collect()
+}
+
+fn solve_benchmark(c: &mut Criterion) {
+    let cubes_2x2x2: Vec<Cube2x2x2> = random_cubes();
+    let cubes_3x3x3: Vec<Cube3x3x3> = random_cubes();
+
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(10);
+
+    group.bench_function("2x2x2", |b| {
+        b.iter(|| {
+            for cube in &cubes_2x2x2 {
+                cube.solve();
+            }
+        })
+    });
+
+    let options = [
+        ("fast", SolverOptions::fast()),
+        (
+            "200ms",
+            SolverOptions::default().with_max_time(Duration::from_millis(200)),
+        ),
+        ("optimal", SolverOptions::default()),
+    ];
+    for (table_name, select_tables) in table_variants() {
+        select_tables();
+        for (options_name, options) in options.iter() {
+            group.bench_with_input(
+                BenchmarkId::new(format!("3x3x3_{}", options_name), table_name),
+                options,
+                |b, options| {
+                    b.iter(|| {
+                        for cube in &cubes_3x3x3 {
+                            cube.solve_with_options(options);
+                        }
+                    })
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, solve_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tpscube_core::{
+    decode_gan_timer_message, decode_gan_v1_cube_state, decode_gan_v2_message, decode_giiker_move,
+    decode_gocube_cube_state, decode_gocube_moves, decode_gocube_orientation, decode_moyu_turns,
+};
+
+fuzz_target!(|data: &[u8]| {
+    // Every decoder must return an error for malformed packets, never panic
+    let _ = decode_gan_timer_message(data);
+    let _ = decode_gan_v1_cube_state(data);
+    let _ = decode_gan_v2_message(data);
+    let _ = decode_giiker_move(data);
+    let _ = decode_gocube_cube_state(data);
+    let _ = decode_gocube_moves(data);
+    let _ = decode_gocube_orientation(data);
+    let _ = decode_moyu_turns(data);
+});
@@ -0,0 +1,23 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::convert::TryFrom;
+use tpscube_core::{Color, Cube3x3x3Faces};
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 6 * 9 {
+        return;
+    }
+    let mut colors = [Color::White; 6 * 9];
+    for (color, byte) in colors.iter_mut().zip(data.iter()) {
+        *color = match Color::try_from(*byte) {
+            Ok(color) => color,
+            Err(_) => return,
+        };
+    }
+
+    // Any state that is accepted must convert back to the same colors
+    let faces = Cube3x3x3Faces::from_colors(colors);
+    if let Ok(pieces) = faces.try_as_pieces() {
+        assert_eq!(pieces.as_faces(), faces);
+    }
+});
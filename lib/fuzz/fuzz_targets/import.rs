@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tpscube_core::parse_import;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(contents) = std::str::from_utf8(data) {
+        let _ = parse_import(contents.to_string());
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use tpscube_core::{parse_move_string, parse_timed_move_string};
+
+fuzz_target!(|data: &str| {
+    let _ = parse_move_string(data);
+    let _ = parse_timed_move_string(data);
+});
@@ -0,0 +1,236 @@
+use crate::history::History;
+use crate::request::{
+    AccountDevice, AccountOperation, AccountRequest, AccountResponse, SyncRequest,
+};
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+#[cfg(feature = "native-storage")]
+use crate::sync::post_native;
+#[cfg(feature = "web-storage")]
+use crate::sync::{post_web, spawn_future};
+
+/// Account management for the sync key of a history. Creating an account or
+/// logging in registers this device with the sync server and stores the
+/// returned token locally. The token is required to list the devices using
+/// the account, revoke them, or wipe the data stored on the server.
+pub struct Account {
+    sync_key: String,
+    device_id: String,
+    device_name: String,
+    token: Option<String>,
+    devices: Vec<AccountDevice>,
+    current_operation: Option<Arc<Mutex<AccountRequestOperation>>>,
+    last_error: Option<String>,
+}
+
+struct AccountRequestOperation {
+    request: AccountRequest,
+    response: Option<Result<AccountResponse>>,
+}
+
+impl Account {
+    /// Loads the account state of this device for the current sync key of
+    /// the history. Devices are listed to others under `device_name`.
+    pub fn load(history: &mut History, device_name: &str) -> Self {
+        // The device ID is generated once and kept across sync key changes
+        let device_id = match history.setting_as_string("account_device_id") {
+            Some(id) => id,
+            None => {
+                let id = Uuid::new_v4().to_simple().to_string();
+                let _ = history.set_string_setting("account_device_id", &id);
+                id
+            }
+        };
+
+        // Tokens are only valid for the sync key they were issued for
+        let sync_key = history.sync_key().to_string();
+        let token = match history.setting_as_string("account_sync_key") {
+            Some(key) if key == sync_key => history
+                .setting_as_string("account_token")
+                .filter(|token| token.len() != 0),
+            _ => None,
+        };
+
+        Self {
+            sync_key,
+            device_id,
+            device_name: device_name.into(),
+            token,
+            devices: Vec::new(),
+            current_operation: None,
+            last_error: None,
+        }
+    }
+
+    pub fn sync_key(&self) -> &str {
+        &self.sync_key
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    pub fn logged_in(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Devices using the account, as of the last completed operation
+    pub fn devices(&self) -> &[AccountDevice] {
+        &self.devices
+    }
+
+    /// Creates an account for the sync key and logs in this device
+    pub fn create(&mut self) -> bool {
+        self.start(AccountOperation::Create)
+    }
+
+    /// Logs in this device to the existing account for the sync key
+    pub fn login(&mut self) -> bool {
+        self.start(AccountOperation::Login)
+    }
+
+    pub fn list_devices(&mut self) -> bool {
+        self.start(AccountOperation::ListDevices)
+    }
+
+    /// Revokes the token of a device. Revoking this device logs it out.
+    pub fn revoke_device(&mut self, device_id: &str) -> bool {
+        self.start(AccountOperation::RevokeDevice(device_id.into()))
+    }
+
+    /// Deletes all synced data and devices of the account from the server
+    pub fn wipe_data(&mut self) -> bool {
+        self.start(AccountOperation::WipeData)
+    }
+
+    /// Starts an account operation. Returns false if another operation is
+    /// already in progress, or if the operation requires logging in first.
+    fn start(&mut self, operation: AccountOperation) -> bool {
+        if self.current_operation.is_some() {
+            return false;
+        }
+        let token = match operation {
+            AccountOperation::Create | AccountOperation::Login => None,
+            _ => match &self.token {
+                Some(token) => Some(token.clone()),
+                None => {
+                    self.last_error = Some("Not logged in".into());
+                    return false;
+                }
+            },
+        };
+
+        let request = AccountRequest {
+            sync_key: self.sync_key.clone(),
+            device_id: self.device_id.clone(),
+            device_name: self.device_name.clone(),
+            token,
+            operation,
+        };
+        self.current_operation = Some(AccountRequestOperation::new(request));
+        true
+    }
+
+    /// Checks for completion of an operation. Returns the operation that
+    /// completed successfully, if any. Tokens are stored in the history's
+    /// local settings, and wiping data moves the history to a new sync key
+    /// so that solves on this device are not uploaded again.
+    pub fn check_operation(&mut self, history: &mut History) -> Option<AccountOperation> {
+        let operation = self.current_operation.clone()?;
+        let operation = operation.lock().unwrap();
+        if !operation.done() {
+            return None;
+        }
+        self.current_operation = None;
+
+        let response = match operation.response().as_ref().unwrap() {
+            Ok(response) => response,
+            Err(error) => {
+                self.last_error = Some(error.to_string());
+                return None;
+            }
+        };
+        self.last_error = None;
+        self.devices = response.devices.clone();
+
+        let completed = operation.request.operation.clone();
+        match &completed {
+            AccountOperation::Create | AccountOperation::Login => {
+                if let Some(token) = &response.token {
+                    self.set_token(history, Some(token.clone()));
+                }
+            }
+            AccountOperation::RevokeDevice(device) if device == &self.device_id => {
+                self.set_token(history, None);
+            }
+            AccountOperation::WipeData => {
+                self.set_token(history, None);
+                let _ = history.set_sync_key(&SyncRequest::new_sync_key());
+                self.sync_key = history.sync_key().to_string();
+            }
+            _ => (),
+        }
+        Some(completed)
+    }
+
+    fn set_token(&mut self, history: &mut History, token: Option<String>) {
+        let _ = history.set_string_setting("account_sync_key", &self.sync_key);
+        let _ = history.set_string_setting("account_token", token.as_deref().unwrap_or(""));
+        self.token = token;
+    }
+
+    pub fn operation_in_progress(&self) -> bool {
+        self.current_operation.is_some()
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+}
+
+impl AccountRequestOperation {
+    fn new(request: AccountRequest) -> Arc<Mutex<Self>> {
+        let operation = Arc::new(Mutex::new(Self {
+            request,
+            response: None,
+        }));
+
+        let operation_copy = operation.clone();
+
+        #[cfg(feature = "native-storage")]
+        std::thread::spawn(move || {
+            let result = Self::execute(&operation_copy);
+            operation_copy.lock().unwrap().response = Some(result);
+        });
+
+        #[cfg(feature = "web-storage")]
+        spawn_future(async move {
+            let result = Self::execute(&operation_copy).await;
+            operation_copy.lock().unwrap().response = Some(result);
+        });
+
+        operation
+    }
+
+    #[cfg(feature = "native-storage")]
+    fn execute(operation: &Arc<Mutex<Self>>) -> Result<AccountResponse> {
+        let request = operation.lock().unwrap().request.serialize()?.to_string();
+        Ok(AccountResponse::deserialize(post_native(request)?)?)
+    }
+
+    #[cfg(feature = "web-storage")]
+    async fn execute(operation: &Arc<Mutex<Self>>) -> Result<AccountResponse> {
+        let request = operation.lock().unwrap().request.serialize()?.to_string();
+        Ok(AccountResponse::deserialize(post_web(request).await?)?)
+    }
+
+    fn done(&self) -> bool {
+        self.response.is_some()
+    }
+
+    fn response(&self) -> &Option<Result<AccountResponse>> {
+        &self.response
+    }
+}
@@ -1,6 +1,8 @@
 use crate::action_generated;
+use crate::analysis::CFOPSkips;
 use crate::common::{Move, Penalty, Solve, SolveType, TimedMove};
-use anyhow::{anyhow, Result};
+use crate::error::{Error, ErrorKind};
+use anyhow::anyhow;
 use chrono::{Local, TimeZone};
 use flatbuffers::{FlatBufferBuilder, WIPOffset};
 use std::convert::{TryFrom, TryInto};
@@ -10,6 +12,19 @@ use uuid::Uuid;
 use crate::index_generated;
 #[cfg(feature = "storage")]
 use crate::storage::{DeferredStorage, Storage};
+#[cfg(feature = "storage")]
+use anyhow::Result;
+#[cfg(feature = "storage")]
+use std::collections::HashSet;
+
+/// Version of the serialized action list and index formats. Data written
+/// before versions were stored reads as version 0.
+pub(crate) const ACTION_FORMAT_VERSION: u32 = 1;
+
+/// Upgrades for actions read from an older format version. The entry at
+/// index `n` upgrades actions from version `n` to version `n + 1`.
+const ACTION_MIGRATIONS: [fn(Vec<StoredAction>) -> Vec<StoredAction>;
+    ACTION_FORMAT_VERSION as usize] = [migrate_unversioned];
 
 #[cfg(feature = "storage")]
 const TARGET_BUNDLE_SIZE: usize = 65536;
@@ -24,6 +39,14 @@ pub enum Action {
     MergeSessions(String, String),
     RenameSession(String, Option<String>),
     DeleteSolve(String),
+    AddParticipant(String, String, String),
+    RemoveParticipant(String, String),
+    ChangeParticipant(String, Option<String>),
+    TagSolve(String, String),
+    UntagSolve(String, String),
+    SetPreference(String, String),
+    SetSessionGoal(String, String, String),
+    AttemptGroup(String, String, Vec<String>),
 }
 
 #[derive(Clone, Debug)]
@@ -59,6 +82,7 @@ struct ActionBundle {
     id: String,
     actions: Vec<StoredAction>,
     present_in_index: bool,
+    version: u32,
 }
 
 impl StoredAction {
@@ -107,6 +131,10 @@ impl StoredAction {
                     .device
                     .as_ref()
                     .map(|device| builder.create_string(&device));
+                let participant = solve
+                    .participant
+                    .as_ref()
+                    .map(|participant| builder.create_string(&participant));
                 let moves = solve.moves.as_ref().map(|moves| {
                     let mut move_list = Vec::new();
                     for mv in moves {
@@ -117,6 +145,16 @@ impl StoredAction {
                     }
                     builder.create_vector(&move_list)
                 });
+                let skips = solve.skips.map(|skips| {
+                    action_generated::SolveSkips::create(
+                        builder,
+                        &action_generated::SolveSkipsArgs {
+                            f2l_pairs: skips.f2l_pairs as u8,
+                            oll: skips.oll,
+                            pll: skips.pll,
+                        },
+                    )
+                });
                 let mut solve_builder = action_generated::NewSolveActionBuilder::new(builder);
                 solve_builder.add_id(id);
                 solve_builder.add_solve_type(solve.solve_type as u8);
@@ -134,6 +172,12 @@ impl StoredAction {
                 if let Some(moves) = moves {
                     solve_builder.add_moves(moves);
                 }
+                if let Some(participant) = participant {
+                    solve_builder.add_participant(participant);
+                }
+                if let Some(skips) = skips {
+                    solve_builder.add_skips(skips);
+                }
 
                 (
                     solve_builder.finish().as_union_value(),
@@ -229,6 +273,134 @@ impl StoredAction {
 
                 (action, action_generated::ActionContents::DeleteSolveAction)
             }
+            Action::AddParticipant(session, participant, name) => {
+                let session = Some(builder.create_string(&session));
+                let participant = Some(builder.create_string(&participant));
+                let name = Some(builder.create_string(&name));
+                let action = action_generated::AddParticipantAction::create(
+                    builder,
+                    &action_generated::AddParticipantActionArgs {
+                        session,
+                        participant,
+                        name,
+                    },
+                )
+                .as_union_value();
+
+                (
+                    action,
+                    action_generated::ActionContents::AddParticipantAction,
+                )
+            }
+            Action::RemoveParticipant(session, participant) => {
+                let session = Some(builder.create_string(&session));
+                let participant = Some(builder.create_string(&participant));
+                let action = action_generated::RemoveParticipantAction::create(
+                    builder,
+                    &action_generated::RemoveParticipantActionArgs {
+                        session,
+                        participant,
+                    },
+                )
+                .as_union_value();
+
+                (
+                    action,
+                    action_generated::ActionContents::RemoveParticipantAction,
+                )
+            }
+            Action::ChangeParticipant(solve, participant) => {
+                let solve = builder.create_string(&solve);
+                let participant = participant
+                    .as_ref()
+                    .map(|participant| builder.create_string(&participant));
+                let mut change_builder =
+                    action_generated::ChangeParticipantActionBuilder::new(builder);
+                change_builder.add_solve(solve);
+                if let Some(participant) = participant {
+                    change_builder.add_participant(participant);
+                }
+
+                (
+                    change_builder.finish().as_union_value(),
+                    action_generated::ActionContents::ChangeParticipantAction,
+                )
+            }
+            Action::TagSolve(solve, tag) => {
+                let solve = Some(builder.create_string(&solve));
+                let tag = Some(builder.create_string(&tag));
+                let action = action_generated::TagSolveAction::create(
+                    builder,
+                    &action_generated::TagSolveActionArgs { solve, tag },
+                )
+                .as_union_value();
+
+                (action, action_generated::ActionContents::TagSolveAction)
+            }
+            Action::UntagSolve(solve, tag) => {
+                let solve = Some(builder.create_string(&solve));
+                let tag = Some(builder.create_string(&tag));
+                let action = action_generated::UntagSolveAction::create(
+                    builder,
+                    &action_generated::UntagSolveActionArgs { solve, tag },
+                )
+                .as_union_value();
+
+                (action, action_generated::ActionContents::UntagSolveAction)
+            }
+            Action::SetPreference(name, value) => {
+                let name = Some(builder.create_string(&name));
+                let value = Some(builder.create_string(&value));
+                let action = action_generated::SetPreferenceAction::create(
+                    builder,
+                    &action_generated::SetPreferenceActionArgs { name, value },
+                )
+                .as_union_value();
+
+                (
+                    action,
+                    action_generated::ActionContents::SetPreferenceAction,
+                )
+            }
+            Action::SetSessionGoal(session, kind, value) => {
+                let session = Some(builder.create_string(&session));
+                let kind = Some(builder.create_string(&kind));
+                let value = Some(builder.create_string(&value));
+                let action = action_generated::SetSessionGoalAction::create(
+                    builder,
+                    &action_generated::SetSessionGoalActionArgs {
+                        session,
+                        kind,
+                        value,
+                    },
+                )
+                .as_union_value();
+
+                (
+                    action,
+                    action_generated::ActionContents::SetSessionGoalAction,
+                )
+            }
+            Action::AttemptGroup(group, format, solves) => {
+                let group = Some(builder.create_string(&group));
+                let format = Some(builder.create_string(&format));
+                let solves: Vec<_> = solves
+                    .iter()
+                    .map(|solve| builder.create_string(solve))
+                    .collect();
+                let solves = Some(builder.create_vector(&solves));
+                let action = action_generated::AttemptGroupAction::create(
+                    builder,
+                    &action_generated::AttemptGroupActionArgs {
+                        group,
+                        format,
+                        solves,
+                    },
+                )
+                .as_union_value();
+
+                (action, action_generated::ActionContents::AttemptGroupAction)
+            }
         };
 
         let id = builder.create_string(&self.id);
@@ -254,7 +426,10 @@ impl StoredAction {
         let actions = Some(builder.create_vector(&serialized));
         let actions = action_generated::ActionList::create(
             &mut builder,
-            &action_generated::ActionListArgs { actions },
+            &action_generated::ActionListArgs {
+                actions,
+                version: ACTION_FORMAT_VERSION,
+            },
         );
         builder.finish(actions, None);
 
@@ -317,6 +492,10 @@ impl StoredAction {
                     Some(device) => Some(device.to_string()),
                     None => None,
                 };
+                let participant = match action.participant() {
+                    Some(participant) => Some(participant.to_string()),
+                    None => None,
+                };
                 let moves = match action.moves() {
                     Some(moves) => {
                         let mut result = Vec::new();
@@ -331,6 +510,11 @@ impl StoredAction {
                     }
                     None => None,
                 };
+                let skips = action.skips().map(|skips| CFOPSkips {
+                    f2l_pairs: skips.f2l_pairs() as usize,
+                    oll: skips.oll(),
+                    pll: skips.pll(),
+                });
                 Some(Self {
                     id,
                     action: Action::NewSolve(Solve {
@@ -342,7 +526,9 @@ impl StoredAction {
                         time,
                         penalty,
                         device,
+                        participant,
                         moves,
+                        skips,
                     }),
                 })
             }
@@ -437,12 +623,177 @@ impl StoredAction {
                     action: Action::DeleteSolve(solve),
                 })
             }
+            action_generated::ActionContents::AddParticipantAction => {
+                let action = match action.contents_as_add_participant_action() {
+                    Some(action) => action,
+                    None => return None,
+                };
+                let session = match action.session() {
+                    Some(session) => session.to_string(),
+                    None => return None,
+                };
+                let participant = match action.participant() {
+                    Some(participant) => participant.to_string(),
+                    None => return None,
+                };
+                let name = match action.name() {
+                    Some(name) => name.to_string(),
+                    None => return None,
+                };
+                Some(Self {
+                    id,
+                    action: Action::AddParticipant(session, participant, name),
+                })
+            }
+            action_generated::ActionContents::RemoveParticipantAction => {
+                let action = match action.contents_as_remove_participant_action() {
+                    Some(action) => action,
+                    None => return None,
+                };
+                let session = match action.session() {
+                    Some(session) => session.to_string(),
+                    None => return None,
+                };
+                let participant = match action.participant() {
+                    Some(participant) => participant.to_string(),
+                    None => return None,
+                };
+                Some(Self {
+                    id,
+                    action: Action::RemoveParticipant(session, participant),
+                })
+            }
+            action_generated::ActionContents::ChangeParticipantAction => {
+                let action = match action.contents_as_change_participant_action() {
+                    Some(action) => action,
+                    None => return None,
+                };
+                let solve = match action.solve() {
+                    Some(solve) => solve.to_string(),
+                    None => return None,
+                };
+                let participant = action
+                    .participant()
+                    .map(|participant| participant.to_string());
+                Some(Self {
+                    id,
+                    action: Action::ChangeParticipant(solve, participant),
+                })
+            }
+            action_generated::ActionContents::TagSolveAction => {
+                let action = match action.contents_as_tag_solve_action() {
+                    Some(action) => action,
+                    None => return None,
+                };
+                let solve = match action.solve() {
+                    Some(solve) => solve.to_string(),
+                    None => return None,
+                };
+                let tag = match action.tag() {
+                    Some(tag) => tag.to_string(),
+                    None => return None,
+                };
+                Some(Self {
+                    id,
+                    action: Action::TagSolve(solve, tag),
+                })
+            }
+            action_generated::ActionContents::UntagSolveAction => {
+                let action = match action.contents_as_untag_solve_action() {
+                    Some(action) => action,
+                    None => return None,
+                };
+                let solve = match action.solve() {
+                    Some(solve) => solve.to_string(),
+                    None => return None,
+                };
+                let tag = match action.tag() {
+                    Some(tag) => tag.to_string(),
+                    None => return None,
+                };
+                Some(Self {
+                    id,
+                    action: Action::UntagSolve(solve, tag),
+                })
+            }
+            action_generated::ActionContents::SetPreferenceAction => {
+                let action = match action.contents_as_set_preference_action() {
+                    Some(action) => action,
+                    None => return None,
+                };
+                let name = match action.name() {
+                    Some(name) => name.to_string(),
+                    None => return None,
+                };
+                let value = match action.value() {
+                    Some(value) => value.to_string(),
+                    None => return None,
+                };
+                Some(Self {
+                    id,
+                    action: Action::SetPreference(name, value),
+                })
+            }
+            action_generated::ActionContents::SetSessionGoalAction => {
+                let action = match action.contents_as_set_session_goal_action() {
+                    Some(action) => action,
+                    None => return None,
+                };
+                let session = match action.session() {
+                    Some(session) => session.to_string(),
+                    None => return None,
+                };
+                let kind = match action.kind() {
+                    Some(kind) => kind.to_string(),
+                    None => return None,
+                };
+                let value = match action.value() {
+                    Some(value) => value.to_string(),
+                    None => return None,
+                };
+                Some(Self {
+                    id,
+                    action: Action::SetSessionGoal(session, kind, value),
+                })
+            }
+            action_generated::ActionContents::AttemptGroupAction => {
+                let action = match action.contents_as_attempt_group_action() {
+                    Some(action) => action,
+                    None => return None,
+                };
+                let group = match action.group() {
+                    Some(group) => group.to_string(),
+                    None => return None,
+                };
+                let format = match action.format() {
+                    Some(format) => format.to_string(),
+                    None => return None,
+                };
+                let solves = match action.solves() {
+                    Some(solves) => solves.iter().map(|solve| solve.to_string()).collect(),
+                    None => Vec::new(),
+                };
+                Some(Self {
+                    id,
+                    action: Action::AttemptGroup(group, format, solves),
+                })
+            }
             _ => None,
         }
     }
 
-    pub fn deserialize_list(data: &[u8]) -> Result<Vec<Self>> {
-        let action_list = action_generated::root_as_action_list(data)?;
+    pub fn deserialize_list(data: &[u8]) -> crate::Result<Vec<Self>> {
+        Ok(Self::deserialize_versioned_list(data)?.0)
+    }
+
+    /// Deserializes a list of actions, upgrading them to the current format
+    /// version. Also returns the version the list was written with. Lists from
+    /// a newer version are refused so that they are never rewritten in an older
+    /// format.
+    pub(crate) fn deserialize_versioned_list(data: &[u8]) -> crate::Result<(Vec<Self>, u32)> {
+        let action_list = action_generated::root_as_action_list(data).parse_error()?;
+        let version = action_list.version();
+        check_format_version(version)?;
         if let Some(action_list) = action_list.actions() {
             let mut actions = Vec::new();
             for action in action_list.iter() {
@@ -450,9 +801,12 @@ impl StoredAction {
                     actions.push(action);
                 }
             }
-            Ok(actions)
+            for migration in &ACTION_MIGRATIONS[version as usize..] {
+                actions = migration(actions);
+            }
+            Ok((actions, version))
         } else {
-            Err(anyhow!("Actions not present in list"))
+            Err(Error::Parse(anyhow!("Actions not present in list")))
         }
     }
 }
@@ -470,6 +824,7 @@ impl ActionList {
     pub async fn load(storage: &Storage, name: &'static str) -> Result<Self> {
         if let Some(data) = storage.get(name).await? {
             let index = index_generated::root_as_action_list_index(&data)?;
+            check_format_version(index.version())?;
             if let Some(lists) = index.lists() {
                 // Load each bundle referenced in the index
                 let mut bundles = Vec::new();
@@ -560,7 +915,10 @@ impl ActionList {
         let lists = builder.create_vector_of_strings(&lists);
         let index = crate::index_generated::ActionListIndex::create(
             &mut builder,
-            &crate::index_generated::ActionListIndexArgs { lists: Some(lists) },
+            &crate::index_generated::ActionListIndexArgs {
+                lists: Some(lists),
+                version: ACTION_FORMAT_VERSION,
+            },
         );
         builder.finish(index, None);
 
@@ -681,6 +1039,56 @@ impl ActionList {
         }
     }
 
+    /// Rewrites bundles that were loaded from an older format version, along
+    /// with the index, so that migrations only run once
+    pub fn upgrade(&mut self, storage: &DeferredStorage) {
+        let mut upgraded = false;
+        for bundle in self
+            .archive
+            .iter_mut()
+            .chain(std::iter::once(&mut self.current))
+        {
+            if bundle.present_in_index && bundle.version < ACTION_FORMAT_VERSION {
+                diagnostic!(
+                    info,
+                    list = self.name,
+                    from_version = bundle.version,
+                    "Upgrading action bundle"
+                );
+                bundle.save(storage);
+                bundle.version = ACTION_FORMAT_VERSION;
+                upgraded = true;
+            }
+        }
+        if upgraded {
+            self.save_index(storage);
+            storage.flush();
+        }
+    }
+
+    /// Rewrites the list without the actions with the given IDs. The new
+    /// bundles are written before the old ones are deleted. Returns the
+    /// number of actions removed.
+    pub fn remove_actions(&mut self, ids: &HashSet<String>, storage: &DeferredStorage) -> usize {
+        let mut new_list = ActionList::empty(self.name);
+        let mut removed = 0;
+        for action in self.iter() {
+            if ids.contains(&action.id) {
+                removed += 1;
+            } else {
+                new_list.push(action.clone());
+            }
+        }
+        if removed == 0 {
+            return 0;
+        }
+
+        new_list.commit(storage, true);
+        self.delete_bundles(storage);
+        *self = new_list;
+        removed
+    }
+
     pub fn delete_bundles(&self, storage: &DeferredStorage) {
         for archive in &self.archive {
             archive.delete(storage);
@@ -762,22 +1170,25 @@ impl ActionBundle {
             id: Uuid::new_v4().to_simple().to_string(),
             actions: Vec::new(),
             present_in_index: false,
+            version: ACTION_FORMAT_VERSION,
         }
     }
 
     async fn load(storage: &Storage, id: &str) -> Result<Self> {
         if let Some(data) = storage.get(id).await? {
-            let actions = StoredAction::deserialize_list(&data)?;
+            let (actions, version) = StoredAction::deserialize_versioned_list(&data)?;
             Ok(Self {
                 id: id.to_string(),
                 actions,
                 present_in_index: true,
+                version,
             })
         } else {
             Ok(Self {
                 id: id.to_string(),
                 actions: Vec::new(),
                 present_in_index: true,
+                version: ACTION_FORMAT_VERSION,
             })
         }
     }
@@ -792,3 +1203,21 @@ impl ActionBundle {
         storage.delete(&self.id)
     }
 }
+
+fn check_format_version(version: u32) -> crate::Result<()> {
+    if version > ACTION_FORMAT_VERSION {
+        Err(Error::Storage(anyhow!(
+            "Solve history was written by a newer version of TPS Cube (format version {}, \
+                this version supports up to {}). Update TPS Cube to open it.",
+            version,
+            ACTION_FORMAT_VERSION
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Lists without a version have the same layout as version 1
+fn migrate_unversioned(actions: Vec<StoredAction>) -> Vec<StoredAction> {
+    actions
+}
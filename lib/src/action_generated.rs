@@ -104,10 +104,10 @@ pub struct PenaltyUnionTableOffset {}
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
 pub const ENUM_MIN_ACTION_CONTENTS: u8 = 0;
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
-pub const ENUM_MAX_ACTION_CONTENTS: u8 = 6;
+pub const ENUM_MAX_ACTION_CONTENTS: u8 = 14;
 #[deprecated(since = "2.0.0", note = "Use associated constants instead. This will no longer be generated in 2021.")]
 #[allow(non_camel_case_types)]
-pub const ENUM_VALUES_ACTION_CONTENTS: [ActionContents; 7] = [
+pub const ENUM_VALUES_ACTION_CONTENTS: [ActionContents; 15] = [
   ActionContents::NONE,
   ActionContents::NewSolveAction,
   ActionContents::PenaltyAction,
@@ -115,6 +115,14 @@ pub const ENUM_VALUES_ACTION_CONTENTS: [ActionContents; 7] = [
   ActionContents::MergeSessionsAction,
   ActionContents::RenameSessionAction,
   ActionContents::DeleteSolveAction,
+  ActionContents::AddParticipantAction,
+  ActionContents::RemoveParticipantAction,
+  ActionContents::ChangeParticipantAction,
+  ActionContents::TagSolveAction,
+  ActionContents::UntagSolveAction,
+  ActionContents::SetPreferenceAction,
+  ActionContents::SetSessionGoalAction,
+  ActionContents::AttemptGroupAction,
 ];
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -129,9 +137,17 @@ impl ActionContents {
   pub const MergeSessionsAction: Self = Self(4);
   pub const RenameSessionAction: Self = Self(5);
   pub const DeleteSolveAction: Self = Self(6);
+  pub const AddParticipantAction: Self = Self(7);
+  pub const RemoveParticipantAction: Self = Self(8);
+  pub const ChangeParticipantAction: Self = Self(9);
+  pub const TagSolveAction: Self = Self(10);
+  pub const UntagSolveAction: Self = Self(11);
+  pub const SetPreferenceAction: Self = Self(12);
+  pub const SetSessionGoalAction: Self = Self(13);
+  pub const AttemptGroupAction: Self = Self(14);
 
   pub const ENUM_MIN: u8 = 0;
-  pub const ENUM_MAX: u8 = 6;
+  pub const ENUM_MAX: u8 = 14;
   pub const ENUM_VALUES: &'static [Self] = &[
     Self::NONE,
     Self::NewSolveAction,
@@ -140,6 +156,14 @@ impl ActionContents {
     Self::MergeSessionsAction,
     Self::RenameSessionAction,
     Self::DeleteSolveAction,
+    Self::AddParticipantAction,
+    Self::RemoveParticipantAction,
+    Self::ChangeParticipantAction,
+    Self::TagSolveAction,
+    Self::UntagSolveAction,
+    Self::SetPreferenceAction,
+    Self::SetSessionGoalAction,
+    Self::AttemptGroupAction,
   ];
   /// Returns the variant's name or "" if unknown.
   pub fn variant_name(self) -> Option<&'static str> {
@@ -151,6 +175,14 @@ impl ActionContents {
       Self::MergeSessionsAction => Some("MergeSessionsAction"),
       Self::RenameSessionAction => Some("RenameSessionAction"),
       Self::DeleteSolveAction => Some("DeleteSolveAction"),
+      Self::AddParticipantAction => Some("AddParticipantAction"),
+      Self::RemoveParticipantAction => Some("RemoveParticipantAction"),
+      Self::ChangeParticipantAction => Some("ChangeParticipantAction"),
+      Self::TagSolveAction => Some("TagSolveAction"),
+      Self::UntagSolveAction => Some("UntagSolveAction"),
+      Self::SetPreferenceAction => Some("SetPreferenceAction"),
+      Self::SetSessionGoalAction => Some("SetSessionGoalAction"),
+      Self::AttemptGroupAction => Some("AttemptGroupAction"),
       _ => None,
     }
   }
@@ -502,6 +534,125 @@ impl std::fmt::Debug for DNFPenalty<'_> {
       ds.finish()
   }
 }
+pub enum SolveSkipsOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct SolveSkips<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for SolveSkips<'a> {
+    type Inner = SolveSkips<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> SolveSkips<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        SolveSkips { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args SolveSkipsArgs) -> flatbuffers::WIPOffset<SolveSkips<'bldr>> {
+      let mut builder = SolveSkipsBuilder::new(_fbb);
+      builder.add_pll(args.pll);
+      builder.add_oll(args.oll);
+      builder.add_f2l_pairs(args.f2l_pairs);
+      builder.finish()
+    }
+
+    pub const VT_F2L_PAIRS: flatbuffers::VOffsetT = 4;
+    pub const VT_OLL: flatbuffers::VOffsetT = 6;
+    pub const VT_PLL: flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub fn f2l_pairs(&self) -> u8 {
+    self._tab.get::<u8>(SolveSkips::VT_F2L_PAIRS, Some(0)).unwrap()
+  }
+  #[inline]
+  pub fn oll(&self) -> bool {
+    self._tab.get::<bool>(SolveSkips::VT_OLL, Some(false)).unwrap()
+  }
+  #[inline]
+  pub fn pll(&self) -> bool {
+    self._tab.get::<bool>(SolveSkips::VT_PLL, Some(false)).unwrap()
+  }
+}
+
+impl flatbuffers::Verifiable for SolveSkips<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<u8>(&"f2l_pairs", Self::VT_F2L_PAIRS, false)?
+     .visit_field::<bool>(&"oll", Self::VT_OLL, false)?
+     .visit_field::<bool>(&"pll", Self::VT_PLL, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct SolveSkipsArgs {
+    pub f2l_pairs: u8,
+    pub oll: bool,
+    pub pll: bool,
+}
+impl<'a> Default for SolveSkipsArgs {
+    #[inline]
+    fn default() -> Self {
+        SolveSkipsArgs {
+            f2l_pairs: 0,
+            oll: false,
+            pll: false,
+        }
+    }
+}
+pub struct SolveSkipsBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> SolveSkipsBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_f2l_pairs(&mut self, f2l_pairs: u8) {
+    self.fbb_.push_slot::<u8>(SolveSkips::VT_F2L_PAIRS, f2l_pairs, 0);
+  }
+  #[inline]
+  pub fn add_oll(&mut self, oll: bool) {
+    self.fbb_.push_slot::<bool>(SolveSkips::VT_OLL, oll, false);
+  }
+  #[inline]
+  pub fn add_pll(&mut self, pll: bool) {
+    self.fbb_.push_slot::<bool>(SolveSkips::VT_PLL, pll, false);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> SolveSkipsBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    SolveSkipsBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<SolveSkips<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for SolveSkips<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("SolveSkips");
+      ds.field("f2l_pairs", &self.f2l_pairs());
+      ds.field("oll", &self.oll());
+      ds.field("pll", &self.pll());
+      ds.finish()
+  }
+}
 pub enum NewSolveActionOffset {}
 #[derive(Copy, Clone, PartialEq)]
 
@@ -528,6 +679,8 @@ impl<'a> NewSolveAction<'a> {
         args: &'args NewSolveActionArgs<'args>) -> flatbuffers::WIPOffset<NewSolveAction<'bldr>> {
       let mut builder = NewSolveActionBuilder::new(_fbb);
       builder.add_created(args.created);
+      if let Some(x) = args.skips { builder.add_skips(x); }
+      if let Some(x) = args.participant { builder.add_participant(x); }
       if let Some(x) = args.moves { builder.add_moves(x); }
       if let Some(x) = args.device { builder.add_device(x); }
       if let Some(x) = args.penalty { builder.add_penalty(x); }
@@ -550,6 +703,8 @@ impl<'a> NewSolveAction<'a> {
     pub const VT_PENALTY: flatbuffers::VOffsetT = 18;
     pub const VT_DEVICE: flatbuffers::VOffsetT = 20;
     pub const VT_MOVES: flatbuffers::VOffsetT = 22;
+    pub const VT_PARTICIPANT: flatbuffers::VOffsetT = 24;
+    pub const VT_SKIPS: flatbuffers::VOffsetT = 26;
 
   #[inline]
   pub fn id(&self) -> Option<&'a str> {
@@ -592,6 +747,14 @@ impl<'a> NewSolveAction<'a> {
     self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, TimedMove>>>(NewSolveAction::VT_MOVES, None).map(|v| v.safe_slice())
   }
   #[inline]
+  pub fn participant(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(NewSolveAction::VT_PARTICIPANT, None)
+  }
+  #[inline]
+  pub fn skips(&self) -> Option<SolveSkips<'a>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<SolveSkips>>(NewSolveAction::VT_SKIPS, None)
+  }
+  #[inline]
   #[allow(non_snake_case)]
   pub fn penalty_as_time_penalty(&self) -> Option<TimePenalty<'a>> {
     if self.penalty_type() == Penalty::TimePenalty {
@@ -635,6 +798,8 @@ impl flatbuffers::Verifiable for NewSolveAction<'_> {
      })?
      .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"device", Self::VT_DEVICE, false)?
      .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, TimedMove>>>(&"moves", Self::VT_MOVES, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"participant", Self::VT_PARTICIPANT, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<SolveSkips>>(&"skips", Self::VT_SKIPS, false)?
      .finish();
     Ok(())
   }
@@ -650,6 +815,8 @@ pub struct NewSolveActionArgs<'a> {
     pub penalty: Option<flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>>,
     pub device: Option<flatbuffers::WIPOffset<&'a str>>,
     pub moves: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, TimedMove>>>,
+    pub participant: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub skips: Option<flatbuffers::WIPOffset<SolveSkips<'a>>>,
 }
 impl<'a> Default for NewSolveActionArgs<'a> {
     #[inline]
@@ -665,6 +832,8 @@ impl<'a> Default for NewSolveActionArgs<'a> {
             penalty: None,
             device: None,
             moves: None,
+            participant: None,
+            skips: None,
         }
     }
 }
@@ -714,6 +883,14 @@ impl<'a: 'b, 'b> NewSolveActionBuilder<'a, 'b> {
     self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(NewSolveAction::VT_MOVES, moves);
   }
   #[inline]
+  pub fn add_participant(&mut self, participant: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(NewSolveAction::VT_PARTICIPANT, participant);
+  }
+  #[inline]
+  pub fn add_skips(&mut self, skips: flatbuffers::WIPOffset<SolveSkips<'b >>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<SolveSkips>>(NewSolveAction::VT_SKIPS, skips);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> NewSolveActionBuilder<'a, 'b> {
     let start = _fbb.start_table();
     NewSolveActionBuilder {
@@ -760,6 +937,8 @@ impl std::fmt::Debug for NewSolveAction<'_> {
       };
       ds.field("device", &self.device());
       ds.field("moves", &self.moves());
+      ds.field("participant", &self.participant());
+      ds.field("skips", &self.skips());
       ds.finish()
   }
 }
@@ -1332,189 +1511,1159 @@ impl std::fmt::Debug for DeleteSolveAction<'_> {
       ds.finish()
   }
 }
-pub enum ActionOffset {}
+pub enum AddParticipantActionOffset {}
 #[derive(Copy, Clone, PartialEq)]
 
-pub struct Action<'a> {
+pub struct AddParticipantAction<'a> {
   pub _tab: flatbuffers::Table<'a>,
 }
 
-impl<'a> flatbuffers::Follow<'a> for Action<'a> {
-    type Inner = Action<'a>;
+impl<'a> flatbuffers::Follow<'a> for AddParticipantAction<'a> {
+    type Inner = AddParticipantAction<'a>;
     #[inline]
     fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
         Self { _tab: flatbuffers::Table { buf, loc } }
     }
 }
 
-impl<'a> Action<'a> {
+impl<'a> AddParticipantAction<'a> {
     #[inline]
     pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
-        Action { _tab: table }
+        AddParticipantAction { _tab: table }
     }
     #[allow(unused_mut)]
     pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
         _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
-        args: &'args ActionArgs<'args>) -> flatbuffers::WIPOffset<Action<'bldr>> {
-      let mut builder = ActionBuilder::new(_fbb);
-      if let Some(x) = args.contents { builder.add_contents(x); }
-      if let Some(x) = args.id { builder.add_id(x); }
-      builder.add_contents_type(args.contents_type);
+        args: &'args AddParticipantActionArgs<'a>) -> flatbuffers::WIPOffset<AddParticipantAction<'bldr>> {
+      let mut builder = AddParticipantActionBuilder::new(_fbb);
+      if let Some(x) = args.name { builder.add_name(x); }
+      if let Some(x) = args.participant { builder.add_participant(x); }
+      if let Some(x) = args.session { builder.add_session(x); }
       builder.finish()
     }
 
-    pub const VT_ID: flatbuffers::VOffsetT = 4;
-    pub const VT_CONTENTS_TYPE: flatbuffers::VOffsetT = 6;
-    pub const VT_CONTENTS: flatbuffers::VOffsetT = 8;
+    pub const VT_SESSION: flatbuffers::VOffsetT = 4;
+    pub const VT_PARTICIPANT: flatbuffers::VOffsetT = 6;
+    pub const VT_NAME: flatbuffers::VOffsetT = 8;
 
   #[inline]
-  pub fn id(&self) -> Option<&'a str> {
-    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(Action::VT_ID, None)
+  pub fn session(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(AddParticipantAction::VT_SESSION, None)
   }
   #[inline]
-  pub fn contents_type(&self) -> ActionContents {
-    self._tab.get::<ActionContents>(Action::VT_CONTENTS_TYPE, Some(ActionContents::NONE)).unwrap()
+  pub fn participant(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(AddParticipantAction::VT_PARTICIPANT, None)
   }
   #[inline]
-  pub fn contents(&self) -> Option<flatbuffers::Table<'a>> {
-    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Table<'a>>>(Action::VT_CONTENTS, None)
+  pub fn name(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(AddParticipantAction::VT_NAME, None)
   }
+}
+
+impl flatbuffers::Verifiable for AddParticipantAction<'_> {
   #[inline]
-  #[allow(non_snake_case)]
-  pub fn contents_as_new_solve_action(&self) -> Option<NewSolveAction<'a>> {
-    if self.contents_type() == ActionContents::NewSolveAction {
-      self.contents().map(NewSolveAction::init_from_table)
-    } else {
-      None
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"session", Self::VT_SESSION, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"participant", Self::VT_PARTICIPANT, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"name", Self::VT_NAME, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct AddParticipantActionArgs<'a> {
+    pub session: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub participant: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub name: Option<flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for AddParticipantActionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        AddParticipantActionArgs {
+            session: None,
+            participant: None,
+            name: None,
+        }
     }
+}
+pub struct AddParticipantActionBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> AddParticipantActionBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_session(&mut self, session: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(AddParticipantAction::VT_SESSION, session);
   }
-
   #[inline]
-  #[allow(non_snake_case)]
-  pub fn contents_as_penalty_action(&self) -> Option<PenaltyAction<'a>> {
-    if self.contents_type() == ActionContents::PenaltyAction {
-      self.contents().map(PenaltyAction::init_from_table)
-    } else {
-      None
-    }
+  pub fn add_participant(&mut self, participant: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(AddParticipantAction::VT_PARTICIPANT, participant);
   }
-
   #[inline]
-  #[allow(non_snake_case)]
-  pub fn contents_as_change_session_action(&self) -> Option<ChangeSessionAction<'a>> {
-    if self.contents_type() == ActionContents::ChangeSessionAction {
-      self.contents().map(ChangeSessionAction::init_from_table)
-    } else {
-      None
-    }
+  pub fn add_name(&mut self, name: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(AddParticipantAction::VT_NAME, name);
   }
-
   #[inline]
-  #[allow(non_snake_case)]
-  pub fn contents_as_merge_sessions_action(&self) -> Option<MergeSessionsAction<'a>> {
-    if self.contents_type() == ActionContents::MergeSessionsAction {
-      self.contents().map(MergeSessionsAction::init_from_table)
-    } else {
-      None
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> AddParticipantActionBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    AddParticipantActionBuilder {
+      fbb_: _fbb,
+      start_: start,
     }
   }
-
   #[inline]
-  #[allow(non_snake_case)]
-  pub fn contents_as_rename_session_action(&self) -> Option<RenameSessionAction<'a>> {
-    if self.contents_type() == ActionContents::RenameSessionAction {
-      self.contents().map(RenameSessionAction::init_from_table)
-    } else {
-      None
-    }
+  pub fn finish(self) -> flatbuffers::WIPOffset<AddParticipantAction<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
   }
+}
 
-  #[inline]
-  #[allow(non_snake_case)]
-  pub fn contents_as_delete_solve_action(&self) -> Option<DeleteSolveAction<'a>> {
-    if self.contents_type() == ActionContents::DeleteSolveAction {
-      self.contents().map(DeleteSolveAction::init_from_table)
-    } else {
-      None
-    }
+impl std::fmt::Debug for AddParticipantAction<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("AddParticipantAction");
+      ds.field("session", &self.session());
+      ds.field("participant", &self.participant());
+      ds.field("name", &self.name());
+      ds.finish()
   }
+}
+pub enum RemoveParticipantActionOffset {}
+#[derive(Copy, Clone, PartialEq)]
 
+pub struct RemoveParticipantAction<'a> {
+  pub _tab: flatbuffers::Table<'a>,
 }
 
-impl flatbuffers::Verifiable for Action<'_> {
+impl<'a> flatbuffers::Follow<'a> for RemoveParticipantAction<'a> {
+    type Inner = RemoveParticipantAction<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> RemoveParticipantAction<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        RemoveParticipantAction { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args RemoveParticipantActionArgs<'a>) -> flatbuffers::WIPOffset<RemoveParticipantAction<'bldr>> {
+      let mut builder = RemoveParticipantActionBuilder::new(_fbb);
+      if let Some(x) = args.participant { builder.add_participant(x); }
+      if let Some(x) = args.session { builder.add_session(x); }
+      builder.finish()
+    }
+
+    pub const VT_SESSION: flatbuffers::VOffsetT = 4;
+    pub const VT_PARTICIPANT: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn session(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(RemoveParticipantAction::VT_SESSION, None)
+  }
+  #[inline]
+  pub fn participant(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(RemoveParticipantAction::VT_PARTICIPANT, None)
+  }
+}
+
+impl flatbuffers::Verifiable for RemoveParticipantAction<'_> {
   #[inline]
   fn run_verifier(
     v: &mut flatbuffers::Verifier, pos: usize
   ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
     use self::flatbuffers::Verifiable;
     v.visit_table(pos)?
-     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"id", Self::VT_ID, false)?
-     .visit_union::<ActionContents, _>(&"contents_type", Self::VT_CONTENTS_TYPE, &"contents", Self::VT_CONTENTS, false, |key, v, pos| {
-        match key {
-          ActionContents::NewSolveAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<NewSolveAction>>("ActionContents::NewSolveAction", pos),
-          ActionContents::PenaltyAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<PenaltyAction>>("ActionContents::PenaltyAction", pos),
-          ActionContents::ChangeSessionAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<ChangeSessionAction>>("ActionContents::ChangeSessionAction", pos),
-          ActionContents::MergeSessionsAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<MergeSessionsAction>>("ActionContents::MergeSessionsAction", pos),
-          ActionContents::RenameSessionAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<RenameSessionAction>>("ActionContents::RenameSessionAction", pos),
-          ActionContents::DeleteSolveAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<DeleteSolveAction>>("ActionContents::DeleteSolveAction", pos),
-          _ => Ok(()),
-        }
-     })?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"session", Self::VT_SESSION, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"participant", Self::VT_PARTICIPANT, false)?
      .finish();
     Ok(())
   }
 }
-pub struct ActionArgs<'a> {
-    pub id: Option<flatbuffers::WIPOffset<&'a str>>,
-    pub contents_type: ActionContents,
-    pub contents: Option<flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>>,
+pub struct RemoveParticipantActionArgs<'a> {
+    pub session: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub participant: Option<flatbuffers::WIPOffset<&'a str>>,
 }
-impl<'a> Default for ActionArgs<'a> {
+impl<'a> Default for RemoveParticipantActionArgs<'a> {
     #[inline]
     fn default() -> Self {
-        ActionArgs {
-            id: None,
-            contents_type: ActionContents::NONE,
-            contents: None,
+        RemoveParticipantActionArgs {
+            session: None,
+            participant: None,
         }
     }
 }
-pub struct ActionBuilder<'a: 'b, 'b> {
+pub struct RemoveParticipantActionBuilder<'a: 'b, 'b> {
   fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
   start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
 }
-impl<'a: 'b, 'b> ActionBuilder<'a, 'b> {
-  #[inline]
-  pub fn add_id(&mut self, id: flatbuffers::WIPOffset<&'b  str>) {
-    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Action::VT_ID, id);
-  }
+impl<'a: 'b, 'b> RemoveParticipantActionBuilder<'a, 'b> {
   #[inline]
-  pub fn add_contents_type(&mut self, contents_type: ActionContents) {
-    self.fbb_.push_slot::<ActionContents>(Action::VT_CONTENTS_TYPE, contents_type, ActionContents::NONE);
+  pub fn add_session(&mut self, session: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(RemoveParticipantAction::VT_SESSION, session);
   }
   #[inline]
-  pub fn add_contents(&mut self, contents: flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>) {
-    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Action::VT_CONTENTS, contents);
+  pub fn add_participant(&mut self, participant: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(RemoveParticipantAction::VT_PARTICIPANT, participant);
   }
   #[inline]
-  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> ActionBuilder<'a, 'b> {
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> RemoveParticipantActionBuilder<'a, 'b> {
     let start = _fbb.start_table();
-    ActionBuilder {
+    RemoveParticipantActionBuilder {
       fbb_: _fbb,
       start_: start,
     }
   }
   #[inline]
-  pub fn finish(self) -> flatbuffers::WIPOffset<Action<'a>> {
+  pub fn finish(self) -> flatbuffers::WIPOffset<RemoveParticipantAction<'a>> {
     let o = self.fbb_.end_table(self.start_);
     flatbuffers::WIPOffset::new(o.value())
   }
 }
 
-impl std::fmt::Debug for Action<'_> {
+impl std::fmt::Debug for RemoveParticipantAction<'_> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let mut ds = f.debug_struct("Action");
-      ds.field("id", &self.id());
+    let mut ds = f.debug_struct("RemoveParticipantAction");
+      ds.field("session", &self.session());
+      ds.field("participant", &self.participant());
+      ds.finish()
+  }
+}
+pub enum ChangeParticipantActionOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct ChangeParticipantAction<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for ChangeParticipantAction<'a> {
+    type Inner = ChangeParticipantAction<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> ChangeParticipantAction<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        ChangeParticipantAction { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args ChangeParticipantActionArgs<'a>) -> flatbuffers::WIPOffset<ChangeParticipantAction<'bldr>> {
+      let mut builder = ChangeParticipantActionBuilder::new(_fbb);
+      if let Some(x) = args.participant { builder.add_participant(x); }
+      if let Some(x) = args.solve { builder.add_solve(x); }
+      builder.finish()
+    }
+
+    pub const VT_SOLVE: flatbuffers::VOffsetT = 4;
+    pub const VT_PARTICIPANT: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn solve(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(ChangeParticipantAction::VT_SOLVE, None)
+  }
+  #[inline]
+  pub fn participant(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(ChangeParticipantAction::VT_PARTICIPANT, None)
+  }
+}
+
+impl flatbuffers::Verifiable for ChangeParticipantAction<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"solve", Self::VT_SOLVE, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"participant", Self::VT_PARTICIPANT, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ChangeParticipantActionArgs<'a> {
+    pub solve: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub participant: Option<flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for ChangeParticipantActionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        ChangeParticipantActionArgs {
+            solve: None,
+            participant: None,
+        }
+    }
+}
+pub struct ChangeParticipantActionBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> ChangeParticipantActionBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_solve(&mut self, solve: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ChangeParticipantAction::VT_SOLVE, solve);
+  }
+  #[inline]
+  pub fn add_participant(&mut self, participant: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ChangeParticipantAction::VT_PARTICIPANT, participant);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> ChangeParticipantActionBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    ChangeParticipantActionBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<ChangeParticipantAction<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for ChangeParticipantAction<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("ChangeParticipantAction");
+      ds.field("solve", &self.solve());
+      ds.field("participant", &self.participant());
+      ds.finish()
+  }
+}
+pub enum TagSolveActionOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct TagSolveAction<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for TagSolveAction<'a> {
+    type Inner = TagSolveAction<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> TagSolveAction<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        TagSolveAction { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args TagSolveActionArgs<'a>) -> flatbuffers::WIPOffset<TagSolveAction<'bldr>> {
+      let mut builder = TagSolveActionBuilder::new(_fbb);
+      if let Some(x) = args.tag { builder.add_tag(x); }
+      if let Some(x) = args.solve { builder.add_solve(x); }
+      builder.finish()
+    }
+
+    pub const VT_SOLVE: flatbuffers::VOffsetT = 4;
+    pub const VT_TAG: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn solve(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(TagSolveAction::VT_SOLVE, None)
+  }
+  #[inline]
+  pub fn tag(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(TagSolveAction::VT_TAG, None)
+  }
+}
+
+impl flatbuffers::Verifiable for TagSolveAction<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"solve", Self::VT_SOLVE, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"tag", Self::VT_TAG, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct TagSolveActionArgs<'a> {
+    pub solve: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub tag: Option<flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for TagSolveActionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        TagSolveActionArgs {
+            solve: None,
+            tag: None,
+        }
+    }
+}
+pub struct TagSolveActionBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> TagSolveActionBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_solve(&mut self, solve: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(TagSolveAction::VT_SOLVE, solve);
+  }
+  #[inline]
+  pub fn add_tag(&mut self, tag: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(TagSolveAction::VT_TAG, tag);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> TagSolveActionBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    TagSolveActionBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<TagSolveAction<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for TagSolveAction<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("TagSolveAction");
+      ds.field("solve", &self.solve());
+      ds.field("tag", &self.tag());
+      ds.finish()
+  }
+}
+pub enum UntagSolveActionOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct UntagSolveAction<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for UntagSolveAction<'a> {
+    type Inner = UntagSolveAction<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> UntagSolveAction<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        UntagSolveAction { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args UntagSolveActionArgs<'a>) -> flatbuffers::WIPOffset<UntagSolveAction<'bldr>> {
+      let mut builder = UntagSolveActionBuilder::new(_fbb);
+      if let Some(x) = args.tag { builder.add_tag(x); }
+      if let Some(x) = args.solve { builder.add_solve(x); }
+      builder.finish()
+    }
+
+    pub const VT_SOLVE: flatbuffers::VOffsetT = 4;
+    pub const VT_TAG: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn solve(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(UntagSolveAction::VT_SOLVE, None)
+  }
+  #[inline]
+  pub fn tag(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(UntagSolveAction::VT_TAG, None)
+  }
+}
+
+impl flatbuffers::Verifiable for UntagSolveAction<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"solve", Self::VT_SOLVE, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"tag", Self::VT_TAG, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct UntagSolveActionArgs<'a> {
+    pub solve: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub tag: Option<flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for UntagSolveActionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        UntagSolveActionArgs {
+            solve: None,
+            tag: None,
+        }
+    }
+}
+pub struct UntagSolveActionBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> UntagSolveActionBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_solve(&mut self, solve: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(UntagSolveAction::VT_SOLVE, solve);
+  }
+  #[inline]
+  pub fn add_tag(&mut self, tag: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(UntagSolveAction::VT_TAG, tag);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> UntagSolveActionBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    UntagSolveActionBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<UntagSolveAction<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for UntagSolveAction<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("UntagSolveAction");
+      ds.field("solve", &self.solve());
+      ds.field("tag", &self.tag());
+      ds.finish()
+  }
+}
+pub enum SetPreferenceActionOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct SetPreferenceAction<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for SetPreferenceAction<'a> {
+    type Inner = SetPreferenceAction<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> SetPreferenceAction<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        SetPreferenceAction { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args SetPreferenceActionArgs<'a>) -> flatbuffers::WIPOffset<SetPreferenceAction<'bldr>> {
+      let mut builder = SetPreferenceActionBuilder::new(_fbb);
+      if let Some(x) = args.value { builder.add_value(x); }
+      if let Some(x) = args.name { builder.add_name(x); }
+      builder.finish()
+    }
+
+    pub const VT_NAME: flatbuffers::VOffsetT = 4;
+    pub const VT_VALUE: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn name(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(SetPreferenceAction::VT_NAME, None)
+  }
+  #[inline]
+  pub fn value(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(SetPreferenceAction::VT_VALUE, None)
+  }
+}
+
+impl flatbuffers::Verifiable for SetPreferenceAction<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"name", Self::VT_NAME, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"value", Self::VT_VALUE, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct SetPreferenceActionArgs<'a> {
+    pub name: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub value: Option<flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for SetPreferenceActionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        SetPreferenceActionArgs {
+            name: None,
+            value: None,
+        }
+    }
+}
+pub struct SetPreferenceActionBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> SetPreferenceActionBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_name(&mut self, name: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(SetPreferenceAction::VT_NAME, name);
+  }
+  #[inline]
+  pub fn add_value(&mut self, value: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(SetPreferenceAction::VT_VALUE, value);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> SetPreferenceActionBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    SetPreferenceActionBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<SetPreferenceAction<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for SetPreferenceAction<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("SetPreferenceAction");
+      ds.field("name", &self.name());
+      ds.field("value", &self.value());
+      ds.finish()
+  }
+}
+pub enum SetSessionGoalActionOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct SetSessionGoalAction<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for SetSessionGoalAction<'a> {
+    type Inner = SetSessionGoalAction<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> SetSessionGoalAction<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        SetSessionGoalAction { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args SetSessionGoalActionArgs<'a>) -> flatbuffers::WIPOffset<SetSessionGoalAction<'bldr>> {
+      let mut builder = SetSessionGoalActionBuilder::new(_fbb);
+      if let Some(x) = args.value { builder.add_value(x); }
+      if let Some(x) = args.kind { builder.add_kind(x); }
+      if let Some(x) = args.session { builder.add_session(x); }
+      builder.finish()
+    }
+
+    pub const VT_SESSION: flatbuffers::VOffsetT = 4;
+    pub const VT_KIND: flatbuffers::VOffsetT = 6;
+    pub const VT_VALUE: flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub fn session(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(SetSessionGoalAction::VT_SESSION, None)
+  }
+  #[inline]
+  pub fn kind(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(SetSessionGoalAction::VT_KIND, None)
+  }
+  #[inline]
+  pub fn value(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(SetSessionGoalAction::VT_VALUE, None)
+  }
+}
+
+impl flatbuffers::Verifiable for SetSessionGoalAction<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"session", Self::VT_SESSION, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"kind", Self::VT_KIND, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"value", Self::VT_VALUE, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct SetSessionGoalActionArgs<'a> {
+    pub session: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub kind: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub value: Option<flatbuffers::WIPOffset<&'a str>>,
+}
+impl<'a> Default for SetSessionGoalActionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        SetSessionGoalActionArgs {
+            session: None,
+            kind: None,
+            value: None,
+        }
+    }
+}
+pub struct SetSessionGoalActionBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> SetSessionGoalActionBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_session(&mut self, session: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(SetSessionGoalAction::VT_SESSION, session);
+  }
+  #[inline]
+  pub fn add_kind(&mut self, kind: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(SetSessionGoalAction::VT_KIND, kind);
+  }
+  #[inline]
+  pub fn add_value(&mut self, value: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(SetSessionGoalAction::VT_VALUE, value);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> SetSessionGoalActionBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    SetSessionGoalActionBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<SetSessionGoalAction<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for SetSessionGoalAction<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("SetSessionGoalAction");
+      ds.field("session", &self.session());
+      ds.field("kind", &self.kind());
+      ds.field("value", &self.value());
+      ds.finish()
+  }
+}
+pub enum AttemptGroupActionOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct AttemptGroupAction<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for AttemptGroupAction<'a> {
+    type Inner = AttemptGroupAction<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> AttemptGroupAction<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        AttemptGroupAction { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args AttemptGroupActionArgs<'args>) -> flatbuffers::WIPOffset<AttemptGroupAction<'bldr>> {
+      let mut builder = AttemptGroupActionBuilder::new(_fbb);
+      if let Some(x) = args.solves { builder.add_solves(x); }
+      if let Some(x) = args.format { builder.add_format(x); }
+      if let Some(x) = args.group { builder.add_group(x); }
+      builder.finish()
+    }
+
+    pub const VT_GROUP: flatbuffers::VOffsetT = 4;
+    pub const VT_FORMAT: flatbuffers::VOffsetT = 6;
+    pub const VT_SOLVES: flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub fn group(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(AttemptGroupAction::VT_GROUP, None)
+  }
+  #[inline]
+  pub fn format(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(AttemptGroupAction::VT_FORMAT, None)
+  }
+  #[inline]
+  pub fn solves(&self) -> Option<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<&'a str>>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<&'a str>>>>(AttemptGroupAction::VT_SOLVES, None)
+  }
+}
+
+impl flatbuffers::Verifiable for AttemptGroupAction<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"group", Self::VT_GROUP, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"format", Self::VT_FORMAT, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<&'_ str>>>>(&"solves", Self::VT_SOLVES, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct AttemptGroupActionArgs<'a> {
+    pub group: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub format: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub solves: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<&'a str>>>>,
+}
+impl<'a> Default for AttemptGroupActionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        AttemptGroupActionArgs {
+            group: None,
+            format: None,
+            solves: None,
+        }
+    }
+}
+pub struct AttemptGroupActionBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> AttemptGroupActionBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_group(&mut self, group: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(AttemptGroupAction::VT_GROUP, group);
+  }
+  #[inline]
+  pub fn add_format(&mut self, format: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(AttemptGroupAction::VT_FORMAT, format);
+  }
+  #[inline]
+  pub fn add_solves(&mut self, solves: flatbuffers::WIPOffset<flatbuffers::Vector<'b , flatbuffers::ForwardsUOffset<&'b  str>>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(AttemptGroupAction::VT_SOLVES, solves);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> AttemptGroupActionBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    AttemptGroupActionBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<AttemptGroupAction<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for AttemptGroupAction<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("AttemptGroupAction");
+      ds.field("group", &self.group());
+      ds.field("format", &self.format());
+      ds.field("solves", &self.solves());
+      ds.finish()
+  }
+}
+pub enum ActionOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct Action<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for Action<'a> {
+    type Inner = Action<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> Action<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        Action { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args ActionArgs<'args>) -> flatbuffers::WIPOffset<Action<'bldr>> {
+      let mut builder = ActionBuilder::new(_fbb);
+      if let Some(x) = args.contents { builder.add_contents(x); }
+      if let Some(x) = args.id { builder.add_id(x); }
+      builder.add_contents_type(args.contents_type);
+      builder.finish()
+    }
+
+    pub const VT_ID: flatbuffers::VOffsetT = 4;
+    pub const VT_CONTENTS_TYPE: flatbuffers::VOffsetT = 6;
+    pub const VT_CONTENTS: flatbuffers::VOffsetT = 8;
+
+  #[inline]
+  pub fn id(&self) -> Option<&'a str> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(Action::VT_ID, None)
+  }
+  #[inline]
+  pub fn contents_type(&self) -> ActionContents {
+    self._tab.get::<ActionContents>(Action::VT_CONTENTS_TYPE, Some(ActionContents::NONE)).unwrap()
+  }
+  #[inline]
+  pub fn contents(&self) -> Option<flatbuffers::Table<'a>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Table<'a>>>(Action::VT_CONTENTS, None)
+  }
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_new_solve_action(&self) -> Option<NewSolveAction<'a>> {
+    if self.contents_type() == ActionContents::NewSolveAction {
+      self.contents().map(NewSolveAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_penalty_action(&self) -> Option<PenaltyAction<'a>> {
+    if self.contents_type() == ActionContents::PenaltyAction {
+      self.contents().map(PenaltyAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_change_session_action(&self) -> Option<ChangeSessionAction<'a>> {
+    if self.contents_type() == ActionContents::ChangeSessionAction {
+      self.contents().map(ChangeSessionAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_merge_sessions_action(&self) -> Option<MergeSessionsAction<'a>> {
+    if self.contents_type() == ActionContents::MergeSessionsAction {
+      self.contents().map(MergeSessionsAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_rename_session_action(&self) -> Option<RenameSessionAction<'a>> {
+    if self.contents_type() == ActionContents::RenameSessionAction {
+      self.contents().map(RenameSessionAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_delete_solve_action(&self) -> Option<DeleteSolveAction<'a>> {
+    if self.contents_type() == ActionContents::DeleteSolveAction {
+      self.contents().map(DeleteSolveAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_add_participant_action(&self) -> Option<AddParticipantAction<'a>> {
+    if self.contents_type() == ActionContents::AddParticipantAction {
+      self.contents().map(AddParticipantAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_remove_participant_action(&self) -> Option<RemoveParticipantAction<'a>> {
+    if self.contents_type() == ActionContents::RemoveParticipantAction {
+      self.contents().map(RemoveParticipantAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_change_participant_action(&self) -> Option<ChangeParticipantAction<'a>> {
+    if self.contents_type() == ActionContents::ChangeParticipantAction {
+      self.contents().map(ChangeParticipantAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_tag_solve_action(&self) -> Option<TagSolveAction<'a>> {
+    if self.contents_type() == ActionContents::TagSolveAction {
+      self.contents().map(TagSolveAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_untag_solve_action(&self) -> Option<UntagSolveAction<'a>> {
+    if self.contents_type() == ActionContents::UntagSolveAction {
+      self.contents().map(UntagSolveAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_set_preference_action(&self) -> Option<SetPreferenceAction<'a>> {
+    if self.contents_type() == ActionContents::SetPreferenceAction {
+      self.contents().map(SetPreferenceAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_set_session_goal_action(&self) -> Option<SetSessionGoalAction<'a>> {
+    if self.contents_type() == ActionContents::SetSessionGoalAction {
+      self.contents().map(SetSessionGoalAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+  #[inline]
+  #[allow(non_snake_case)]
+  pub fn contents_as_attempt_group_action(&self) -> Option<AttemptGroupAction<'a>> {
+    if self.contents_type() == ActionContents::AttemptGroupAction {
+      self.contents().map(AttemptGroupAction::init_from_table)
+    } else {
+      None
+    }
+  }
+
+}
+
+impl flatbuffers::Verifiable for Action<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>(&"id", Self::VT_ID, false)?
+     .visit_union::<ActionContents, _>(&"contents_type", Self::VT_CONTENTS_TYPE, &"contents", Self::VT_CONTENTS, false, |key, v, pos| {
+        match key {
+          ActionContents::NewSolveAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<NewSolveAction>>("ActionContents::NewSolveAction", pos),
+          ActionContents::PenaltyAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<PenaltyAction>>("ActionContents::PenaltyAction", pos),
+          ActionContents::ChangeSessionAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<ChangeSessionAction>>("ActionContents::ChangeSessionAction", pos),
+          ActionContents::MergeSessionsAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<MergeSessionsAction>>("ActionContents::MergeSessionsAction", pos),
+          ActionContents::RenameSessionAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<RenameSessionAction>>("ActionContents::RenameSessionAction", pos),
+          ActionContents::DeleteSolveAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<DeleteSolveAction>>("ActionContents::DeleteSolveAction", pos),
+          ActionContents::AddParticipantAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<AddParticipantAction>>("ActionContents::AddParticipantAction", pos),
+          ActionContents::RemoveParticipantAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<RemoveParticipantAction>>("ActionContents::RemoveParticipantAction", pos),
+          ActionContents::ChangeParticipantAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<ChangeParticipantAction>>("ActionContents::ChangeParticipantAction", pos),
+          ActionContents::TagSolveAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<TagSolveAction>>("ActionContents::TagSolveAction", pos),
+          ActionContents::UntagSolveAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<UntagSolveAction>>("ActionContents::UntagSolveAction", pos),
+          ActionContents::SetPreferenceAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<SetPreferenceAction>>("ActionContents::SetPreferenceAction", pos),
+          ActionContents::SetSessionGoalAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<SetSessionGoalAction>>("ActionContents::SetSessionGoalAction", pos),
+          ActionContents::AttemptGroupAction => v.verify_union_variant::<flatbuffers::ForwardsUOffset<AttemptGroupAction>>("ActionContents::AttemptGroupAction", pos),
+          _ => Ok(()),
+        }
+     })?
+     .finish();
+    Ok(())
+  }
+}
+pub struct ActionArgs<'a> {
+    pub id: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub contents_type: ActionContents,
+    pub contents: Option<flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>>,
+}
+impl<'a> Default for ActionArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        ActionArgs {
+            id: None,
+            contents_type: ActionContents::NONE,
+            contents: None,
+        }
+    }
+}
+pub struct ActionBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> ActionBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_id(&mut self, id: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Action::VT_ID, id);
+  }
+  #[inline]
+  pub fn add_contents_type(&mut self, contents_type: ActionContents) {
+    self.fbb_.push_slot::<ActionContents>(Action::VT_CONTENTS_TYPE, contents_type, ActionContents::NONE);
+  }
+  #[inline]
+  pub fn add_contents(&mut self, contents: flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(Action::VT_CONTENTS, contents);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> ActionBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    ActionBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<Action<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for Action<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("Action");
+      ds.field("id", &self.id());
       ds.field("contents_type", &self.contents_type());
       match self.contents_type() {
         ActionContents::NewSolveAction => {
@@ -1559,6 +2708,62 @@ impl std::fmt::Debug for Action<'_> {
             ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
           }
         },
+        ActionContents::AddParticipantAction => {
+          if let Some(x) = self.contents_as_add_participant_action() {
+            ds.field("contents", &x)
+          } else {
+            ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ActionContents::RemoveParticipantAction => {
+          if let Some(x) = self.contents_as_remove_participant_action() {
+            ds.field("contents", &x)
+          } else {
+            ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ActionContents::ChangeParticipantAction => {
+          if let Some(x) = self.contents_as_change_participant_action() {
+            ds.field("contents", &x)
+          } else {
+            ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ActionContents::TagSolveAction => {
+          if let Some(x) = self.contents_as_tag_solve_action() {
+            ds.field("contents", &x)
+          } else {
+            ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ActionContents::UntagSolveAction => {
+          if let Some(x) = self.contents_as_untag_solve_action() {
+            ds.field("contents", &x)
+          } else {
+            ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ActionContents::SetPreferenceAction => {
+          if let Some(x) = self.contents_as_set_preference_action() {
+            ds.field("contents", &x)
+          } else {
+            ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ActionContents::SetSessionGoalAction => {
+          if let Some(x) = self.contents_as_set_session_goal_action() {
+            ds.field("contents", &x)
+          } else {
+            ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
+        ActionContents::AttemptGroupAction => {
+          if let Some(x) = self.contents_as_attempt_group_action() {
+            ds.field("contents", &x)
+          } else {
+            ds.field("contents", &"InvalidFlatbuffer: Union discriminant does not match value.")
+          }
+        },
         _ => {
           let x: Option<()> = None;
           ds.field("contents", &x)
@@ -1592,16 +2797,22 @@ impl<'a> ActionList<'a> {
         _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
         args: &'args ActionListArgs<'args>) -> flatbuffers::WIPOffset<ActionList<'bldr>> {
       let mut builder = ActionListBuilder::new(_fbb);
+      builder.add_version(args.version);
       if let Some(x) = args.actions { builder.add_actions(x); }
       builder.finish()
     }
 
     pub const VT_ACTIONS: flatbuffers::VOffsetT = 4;
+    pub const VT_VERSION: flatbuffers::VOffsetT = 6;
 
   #[inline]
   pub fn actions(&self) -> Option<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<Action<'a>>>> {
     self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<Action>>>>(ActionList::VT_ACTIONS, None)
   }
+  #[inline]
+  pub fn version(&self) -> u32 {
+    self._tab.get::<u32>(ActionList::VT_VERSION, Some(0)).unwrap()
+  }
 }
 
 impl flatbuffers::Verifiable for ActionList<'_> {
@@ -1612,18 +2823,21 @@ impl flatbuffers::Verifiable for ActionList<'_> {
     use self::flatbuffers::Verifiable;
     v.visit_table(pos)?
      .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<Action>>>>(&"actions", Self::VT_ACTIONS, false)?
+     .visit_field::<u32>(&"version", Self::VT_VERSION, false)?
      .finish();
     Ok(())
   }
 }
 pub struct ActionListArgs<'a> {
     pub actions: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<Action<'a>>>>>,
+    pub version: u32,
 }
 impl<'a> Default for ActionListArgs<'a> {
     #[inline]
     fn default() -> Self {
         ActionListArgs {
             actions: None,
+            version: 0,
         }
     }
 }
@@ -1637,6 +2851,10 @@ impl<'a: 'b, 'b> ActionListBuilder<'a, 'b> {
     self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ActionList::VT_ACTIONS, actions);
   }
   #[inline]
+  pub fn add_version(&mut self, version: u32) {
+    self.fbb_.push_slot::<u32>(ActionList::VT_VERSION, version, 0);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> ActionListBuilder<'a, 'b> {
     let start = _fbb.start_table();
     ActionListBuilder {
@@ -1655,6 +2873,7 @@ impl std::fmt::Debug for ActionList<'_> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let mut ds = f.debug_struct("ActionList");
       ds.field("actions", &self.actions());
+      ds.field("version", &self.version());
       ds.finish()
   }
 }
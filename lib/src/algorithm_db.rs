@@ -0,0 +1,491 @@
+use crate::analysis::{AlgorithmExecution, CFOPAnalysis};
+use crate::case_schedule::LastLayerCase;
+use crate::common::{parse_move_string, CubeFace, Move, MoveSequence, ScrambleOrientation};
+use crate::error::ErrorKind;
+use crate::history::History;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Local setting holding the algorithms of each case
+const ALGORITHM_DATABASE_SETTING: &str = "algorithm_database";
+
+/// Algorithm stored for a last layer case
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredAlgorithm {
+    /// Moves of the algorithm in standard notation
+    pub moves: String,
+}
+
+/// Algorithms known by the user for each last layer case. A case can have
+/// more than one algorithm, such as when the user is deciding between two
+/// algorithms. The database is kept in local storage with `load` and `save`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct AlgorithmDatabase {
+    cases: BTreeMap<String, Vec<StoredAlgorithm>>,
+}
+
+/// Format of an algorithm sheet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlgorithmSheetFormat {
+    /// Comma separated values with the case name in the first column and one
+    /// or more algorithms in the following columns. A header row is skipped.
+    CSV,
+    /// Anki notes exported as plain text, with the case name on the front of
+    /// the card and the algorithms on the back. Line breaks on the back
+    /// separate algorithms.
+    Anki,
+    /// Plain text lists with one `case: algorithm` per line, or AlgDb style
+    /// exports where a `case:` line is followed by its algorithms on the
+    /// following lines. Lines starting with `//` are comments.
+    Text,
+}
+
+/// What to do with an imported algorithm for a case that already has
+/// different algorithms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlgorithmConflict {
+    /// Keep the existing algorithms and skip the imported one
+    KeepExisting,
+    /// Replace the existing algorithms with the ones from the sheet
+    Replace,
+    /// Add the imported algorithm alongside the existing algorithms
+    AddAlternative,
+}
+
+/// Summary of an algorithm sheet import
+#[derive(Clone, Debug, Default)]
+pub struct AlgorithmImportResult {
+    pub added: usize,
+    /// Number of cases whose existing algorithms were replaced
+    pub replaced_cases: usize,
+    /// Algorithms that were already in the database
+    pub duplicates: usize,
+    /// Algorithms skipped because their case already had other algorithms
+    pub conflicts: usize,
+    /// Lines that could not be imported, with the reason
+    pub errors: Vec<String>,
+}
+
+/// Times of the executions of a stored algorithm
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AlgorithmTimeStats {
+    pub count: usize,
+    pub total_recognition_time: u64,
+    pub total_execution_time: u64,
+    pub best_execution_time: Option<u32>,
+}
+
+/// Execution statistics of each stored algorithm of a case, so that
+/// algorithms for the same case can be compared. Executions are matched to
+/// the stored algorithms with `AlgorithmDatabase::identify`.
+#[derive(Clone, Default)]
+pub struct AlgorithmComparison {
+    stats: BTreeMap<String, Vec<AlgorithmTimeStats>>,
+    /// Executions of each case that did not match any stored algorithm
+    unmatched: BTreeMap<String, usize>,
+}
+
+impl StoredAlgorithm {
+    pub fn moves(&self) -> Vec<Move> {
+        parse_move_string(&self.moves).unwrap_or_default()
+    }
+}
+
+impl AlgorithmSheetFormat {
+    /// Guesses the format of an algorithm sheet from its contents
+    pub fn detect(contents: &str) -> Self {
+        let first_line = contents
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| line.len() != 0 && !line.starts_with("//"))
+            .unwrap_or("");
+        if first_line.contains('\t') {
+            AlgorithmSheetFormat::Anki
+        } else if first_line.contains(',') {
+            AlgorithmSheetFormat::CSV
+        } else {
+            AlgorithmSheetFormat::Text
+        }
+    }
+
+    /// Splits a sheet into its case names and algorithms, along with the line
+    /// number of each entry
+    fn entries(&self, contents: &str) -> Vec<(usize, String, String)> {
+        let mut entries = Vec::new();
+        let mut current_case: Option<String> = None;
+        for (idx, line) in contents.lines().enumerate() {
+            let line_number = idx + 1;
+            let line = line.trim();
+            if line.len() == 0 || line.starts_with("//") {
+                continue;
+            }
+            match self {
+                // Newer versions of Anki start exports with lines such as
+                // `#separator:tab`
+                AlgorithmSheetFormat::Anki
+                    if line.starts_with('#')
+                        && line[1..].starts_with(|ch: char| ch.is_ascii_alphabetic()) =>
+                {
+                    continue
+                }
+                AlgorithmSheetFormat::CSV | AlgorithmSheetFormat::Anki => {
+                    let fields = if *self == AlgorithmSheetFormat::CSV {
+                        csv_fields(line)
+                    } else {
+                        line.split('\t').map(|field| field.to_string()).collect()
+                    };
+                    let mut fields = fields.into_iter();
+                    let case = match fields.next() {
+                        Some(case) => strip_html(&case),
+                        None => continue,
+                    };
+                    // Skip a header row, which does not name a case
+                    if line_number == 1 && LastLayerCase::from_name(&case).is_none() {
+                        continue;
+                    }
+                    for field in fields {
+                        let field = field
+                            .replace("<br />", "<br>")
+                            .replace("<br/>", "<br>")
+                            .replace("</div>", "<br>");
+                        for alg in field.split("<br>").map(strip_html) {
+                            if alg.trim().len() != 0 {
+                                entries.push((line_number, case.clone(), alg));
+                            }
+                        }
+                    }
+                }
+                AlgorithmSheetFormat::Text => {
+                    if let Some(case) = line.strip_suffix(':') {
+                        current_case = Some(case.to_string());
+                    } else if let Some((case, alg)) = line.split_once(':') {
+                        entries.push((line_number, case.to_string(), alg.to_string()));
+                    } else if let Some(case) = &current_case {
+                        entries.push((line_number, case.clone(), line.to_string()));
+                    } else {
+                        entries.push((line_number, line.to_string(), String::new()));
+                    }
+                }
+            }
+        }
+        entries
+    }
+}
+
+impl AlgorithmDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the database from local storage
+    pub fn load(history: &History) -> Self {
+        history
+            .setting_as_string(ALGORITHM_DATABASE_SETTING)
+            .and_then(|database| serde_json::from_str(&database).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, history: &mut History) -> crate::Result<()> {
+        let database = serde_json::to_string(self).storage_error()?;
+        history.set_string_setting(ALGORITHM_DATABASE_SETTING, &database)
+    }
+
+    pub fn algorithms(&self, case: LastLayerCase) -> &[StoredAlgorithm] {
+        match self.cases.get(&case.key()) {
+            Some(algorithms) => algorithms,
+            None => &[],
+        }
+    }
+
+    /// Cases that have at least one algorithm
+    pub fn cases(&self) -> Vec<LastLayerCase> {
+        self.cases
+            .iter()
+            .filter(|(_, algorithms)| algorithms.len() != 0)
+            .filter_map(|(key, _)| LastLayerCase::from_key(key))
+            .collect()
+    }
+
+    /// Adds an algorithm to a case. Returns false if the case already has the
+    /// same algorithm.
+    pub fn add(&mut self, case: LastLayerCase, moves: &[Move]) -> bool {
+        let algorithm = StoredAlgorithm {
+            moves: moves.to_vec().to_string(),
+        };
+        let algorithms = self.cases.entry(case.key()).or_default();
+        if algorithms.contains(&algorithm) {
+            return false;
+        }
+        algorithms.push(algorithm);
+        true
+    }
+
+    /// Finds which stored algorithm of a case was executed. The algorithm may
+    /// be executed in any cube orientation, and the adjustments of the last
+    /// layer before and after it are ignored. Mirrored or inverted executions
+    /// do not match.
+    pub fn identify(&self, case: LastLayerCase, execution: &AlgorithmExecution) -> Option<usize> {
+        if execution.algorithm.len() == 0 {
+            return None;
+        }
+        let orientations = ScrambleOrientation::all();
+        self.algorithms(case).iter().position(|algorithm| {
+            let moves = algorithm.moves();
+            if moves.len() < execution.algorithm.len() {
+                return false;
+            }
+            orientations.iter().any(|orientation| {
+                // Stored algorithms are written with the last layer on top
+                let last_layer = orientation.face(CubeFace::Top.color());
+                AlgorithmExecution::new(&orientation.apply(&moves), last_layer).algorithm
+                    == execution.algorithm
+            })
+        })
+    }
+
+    pub fn remove(&mut self, case: LastLayerCase, idx: usize) {
+        if let Some(algorithms) = self.cases.get_mut(&case.key()) {
+            if idx < algorithms.len() {
+                algorithms.remove(idx);
+            }
+        }
+    }
+
+    /// Imports the algorithms from an algorithm sheet. Each algorithm must
+    /// name a known OLL or PLL case and consist of face turns that can be
+    /// parsed with `parse_move_string`. Parentheses used to group moves are
+    /// ignored. Lines that fail to parse are reported in the result and do
+    /// not stop the import.
+    pub fn import(
+        &mut self,
+        contents: &str,
+        format: AlgorithmSheetFormat,
+        conflict: AlgorithmConflict,
+    ) -> AlgorithmImportResult {
+        let mut result = AlgorithmImportResult::default();
+
+        // Existing algorithms are compared against the database as it was
+        // before the import, so that a sheet with several algorithms for a
+        // case does not conflict with itself
+        let existing = self.cases.clone();
+        let mut replaced = Vec::new();
+        for (line, case_name, alg) in format.entries(contents) {
+            let case = match LastLayerCase::from_name(&case_name) {
+                Some(case) => case,
+                None => {
+                    result.errors.push(format!(
+                        "Line {}: unknown case '{}'",
+                        line,
+                        case_name.trim()
+                    ));
+                    continue;
+                }
+            };
+            let alg = alg.replace(|ch: char| "()[]".contains(ch), " ");
+            let moves = match parse_move_string(alg.trim()) {
+                Ok(moves) if moves.len() != 0 => moves,
+                Ok(_) => {
+                    result.errors.push(format!(
+                        "Line {}: no algorithm for {}",
+                        line,
+                        case.to_string()
+                    ));
+                    continue;
+                }
+                Err(error) => {
+                    result.errors.push(format!("Line {}: {}", line, error));
+                    continue;
+                }
+            };
+
+            let key = case.key();
+            let algorithm = StoredAlgorithm {
+                moves: moves.to_string(),
+            };
+            match existing.get(&key) {
+                Some(algorithms) if algorithms.contains(&algorithm) => {
+                    result.duplicates += 1;
+                    continue;
+                }
+                Some(algorithms) if algorithms.len() != 0 => match conflict {
+                    AlgorithmConflict::KeepExisting => {
+                        result.conflicts += 1;
+                        continue;
+                    }
+                    AlgorithmConflict::Replace => {
+                        if !replaced.contains(&key) {
+                            self.cases.insert(key.clone(), Vec::new());
+                            replaced.push(key);
+                            result.replaced_cases += 1;
+                        }
+                    }
+                    AlgorithmConflict::AddAlternative => (),
+                },
+                _ => (),
+            }
+
+            if self.add(case, &moves) {
+                result.added += 1;
+            } else {
+                result.duplicates += 1;
+            }
+        }
+        result
+    }
+}
+
+impl AlgorithmTimeStats {
+    fn add(&mut self, recognition_time: u32, execution_time: u32) {
+        self.count += 1;
+        self.total_recognition_time += recognition_time as u64;
+        self.total_execution_time += execution_time as u64;
+        self.best_execution_time = Some(
+            self.best_execution_time
+                .map(|best| best.min(execution_time))
+                .unwrap_or(execution_time),
+        );
+    }
+
+    fn average(&self, total: u64) -> Option<u32> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(((total + self.count as u64 / 2) / self.count as u64) as u32)
+        }
+    }
+
+    pub fn average_recognition_time(&self) -> Option<u32> {
+        self.average(self.total_recognition_time)
+    }
+
+    pub fn average_execution_time(&self) -> Option<u32> {
+        self.average(self.total_execution_time)
+    }
+}
+
+impl AlgorithmComparison {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the last layer algorithms of an analyzed solve to the statistics
+    pub fn add_analysis(&mut self, database: &AlgorithmDatabase, cfop: &CFOPAnalysis) {
+        for oll in &cfop.oll {
+            self.add(
+                database,
+                LastLayerCase::OLL(oll.performed_algorithm),
+                &oll.execution,
+                oll.recognition_time,
+                oll.execution_time,
+            );
+        }
+        for pll in &cfop.pll {
+            self.add(
+                database,
+                LastLayerCase::PLL(pll.performed_algorithm),
+                &pll.execution,
+                pll.recognition_time,
+                pll.execution_time,
+            );
+        }
+    }
+
+    fn add(
+        &mut self,
+        database: &AlgorithmDatabase,
+        case: LastLayerCase,
+        execution: &AlgorithmExecution,
+        recognition_time: u32,
+        execution_time: u32,
+    ) {
+        let algorithm_count = database.algorithms(case).len();
+        if algorithm_count == 0 {
+            return;
+        }
+        match database.identify(case, execution) {
+            Some(idx) => {
+                let stats = self.stats.entry(case.key()).or_default();
+                if stats.len() < algorithm_count {
+                    stats.resize(algorithm_count, AlgorithmTimeStats::default());
+                }
+                stats[idx].add(recognition_time, execution_time);
+            }
+            None => *self.unmatched.entry(case.key()).or_insert(0) += 1,
+        }
+    }
+
+    /// Statistics of the stored algorithm of a case with the given index
+    pub fn stats(&self, case: LastLayerCase, idx: usize) -> AlgorithmTimeStats {
+        self.stats
+            .get(&case.key())
+            .and_then(|stats| stats.get(idx))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Number of executions of a case that did not match a stored algorithm
+    pub fn unmatched_count(&self, case: LastLayerCase) -> usize {
+        self.unmatched.get(&case.key()).cloned().unwrap_or(0)
+    }
+
+    /// Index of the stored algorithm of a case with the fastest average
+    /// execution. Each algorithm being compared must have been executed at
+    /// least `min_count` times, and there must be at least two of them.
+    pub fn fastest(&self, case: LastLayerCase, min_count: usize) -> Option<usize> {
+        let stats = self.stats.get(&case.key())?;
+        let candidates: Vec<(usize, u32)> = stats
+            .iter()
+            .enumerate()
+            .filter(|(_, stats)| stats.count >= min_count.max(1))
+            .filter_map(|(idx, stats)| Some((idx, stats.average_execution_time()?)))
+            .collect();
+        if candidates.len() < 2 {
+            return None;
+        }
+        candidates
+            .iter()
+            .min_by_key(|(_, time)| *time)
+            .map(|(idx, _)| *idx)
+    }
+}
+
+/// Splits a line of comma separated values, allowing quoted fields
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Removes HTML tags and common entities from a field of an Anki export
+fn strip_html(text: &str) -> String {
+    let mut result = String::new();
+    let mut in_tag = false;
+    for ch in text.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => result.push(ch),
+            _ => (),
+        }
+    }
+    result
+        .replace("&nbsp;", " ")
+        .replace("&#x27;", "'")
+        .replace("&#39;", "'")
+        .trim()
+        .to_string()
+}
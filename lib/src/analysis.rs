@@ -1,16 +1,39 @@
+mod blind;
 mod cfop;
-
-use crate::{Cube, Cube3x3x3, InitialCubeState, Solve, TimedMove};
-
+mod custom;
+mod fingertrick;
+mod lookahead;
+mod parity;
+mod reconstruction;
+mod session;
+mod similar;
+mod tps;
+mod warning;
+mod what_if;
+
+use crate::{Cube, Cube3x3x3, InitialCubeState, Solve, SolveType, TimedMove};
+
+pub use blind::{BlindAnalysis, BlindCycleAnalysis, BlindPieceType, BlindSwapAlgorithm};
 pub use cfop::{
-    CFOPAnalysis, CFOPPartialAnalysis, CFOPProgress, CrossAnalysis, F2LPairAnalysis,
-    FinalAlignmentAnalysis, OLLAlgorithm, OLLAnalysis, PLLAlgorithm, PLLAnalysis,
+    AlgorithmExecution, AlgorithmHand, CFOPAnalysis, CFOPPartialAnalysis, CFOPProgress, CFOPSkips,
+    CrossAnalysis, F2LPairAnalysis, FinalAlignmentAnalysis, OLLAlgorithm, OLLAnalysis,
+    PLLAlgorithm, PLLAnalysis, SkippedStep, StepBoundary,
 };
+pub use custom::{CustomAnalysis, CustomMethod, CustomStep, CustomStepAnalysis, StepPredicate};
+pub use fingertrick::{FingertrickAnalysis, Regrip, Trigger, TriggerSegment};
+pub use lookahead::{LookaheadAnalysis, TransitionPause};
+pub use parity::{ParityAlgorithm, ParityAnalysis, ParityType};
+pub use session::{AUFStats, PLLCaseStats, SessionAnalysis};
+pub use similar::{find_similar_solves, SimilarSolve, SimilarityCriteria, SolveProfile};
+pub use tps::{StepTPS, TPSCurve, TPSSample};
+pub use warning::AnalysisWarning;
+pub use what_if::WhatIfAnalysis;
 
 #[derive(Clone)]
 pub enum Analysis {
     Unsuccessful,
     CFOP(CFOPAnalysis),
+    Blind(BlindAnalysis),
 }
 
 #[derive(Clone)]
@@ -68,12 +91,39 @@ impl Analysis {
         }
     }
 
+    /// Analyzes a solve of the given type. Blind solves are checked for
+    /// target cycles first, and fall back to CFOP if none are found. Other
+    /// solves are never analyzed as blind solves.
+    pub fn analyze_with_solve_type(solve: &CubeWithSolution, solve_type: SolveType) -> Self {
+        if solve_type == SolveType::Blind3x3x3 {
+            if let Some(blind) = BlindAnalysis::analyze(solve) {
+                return Analysis::Blind(blind);
+            }
+        }
+        Self::analyze(solve)
+    }
+
+    /// Steps that were skipped in the solve. Only CFOP solves have steps that
+    /// can be skipped.
+    pub fn skips(&self) -> Option<CFOPSkips> {
+        match self {
+            Analysis::CFOP(cfop) => Some(cfop.skips()),
+            _ => None,
+        }
+    }
+
     pub fn successful(&self) -> bool {
         match self {
             Analysis::Unsuccessful => false,
             _ => true,
         }
     }
+
+    /// Problems found while analyzing the solve, such as steps that could not
+    /// be detected or moves recorded after the cube was solved
+    pub fn warnings(&self, solve: &CubeWithSolution) -> Vec<AnalysisWarning> {
+        AnalysisWarning::check(solve, self)
+    }
 }
 
 impl Default for Analysis {
@@ -115,6 +165,7 @@ impl AnalysisSummary for Analysis {
         match self {
             Analysis::Unsuccessful => Vec::new(),
             Analysis::CFOP(analysis) => analysis.step_summary(),
+            Analysis::Blind(analysis) => analysis.step_summary(),
         }
     }
 
@@ -122,6 +173,7 @@ impl AnalysisSummary for Analysis {
         match self {
             Analysis::Unsuccessful => Vec::new(),
             Analysis::CFOP(analysis) => analysis.detailed_step_summary(),
+            Analysis::Blind(analysis) => analysis.detailed_step_summary(),
         }
     }
 }
@@ -152,7 +204,7 @@ impl SolveAnalysis for Solve {
     fn analyze(&self) -> Analysis {
         let solve: Option<CubeWithSolution> = self.into();
         if let Some(solve) = solve {
-            Analysis::analyze(&solve)
+            Analysis::analyze_with_solve_type(&solve, self.solve_type)
         } else {
             Analysis::Unsuccessful
         }
@@ -0,0 +1,453 @@
+use crate::{
+    AnalysisStepSummary, AnalysisSubstepTime, AnalysisSummary, Cube, Cube3x3x3, CubeFace,
+    CubeWithSolution, InitialCubeState, Move, MoveSequence, TimedMove,
+};
+
+/// Minimum number of targets for a solve to be analyzed as a blind solve
+const MIN_TARGETS: usize = 2;
+
+/// Minimum fraction of turns that must be part of a target cycle for a solve
+/// to be analyzed as a blind solve
+const MIN_CYCLE_COVERAGE: f32 = 0.5;
+
+/// Modified Y permutation used by Old Pochmann to swap the ULB and RDF corners
+const OP_CORNER_SWAP: &[Move] = &[
+    Move::R,
+    Move::Up,
+    Move::Rp,
+    Move::Up,
+    Move::R,
+    Move::U,
+    Move::Rp,
+    Move::Fp,
+    Move::R,
+    Move::U,
+    Move::Rp,
+    Move::Up,
+    Move::Rp,
+    Move::F,
+    Move::R,
+];
+
+/// T permutation used by Old Pochmann to swap the UR and UL edges
+const OP_EDGE_SWAP: &[Move] = &[
+    Move::R,
+    Move::U,
+    Move::Rp,
+    Move::Up,
+    Move::Rp,
+    Move::F,
+    Move::R2,
+    Move::Up,
+    Move::Rp,
+    Move::Up,
+    Move::R,
+    Move::U,
+    Move::Rp,
+    Move::Fp,
+];
+
+/// Type of piece solved by a target cycle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlindPieceType {
+    Corner,
+    Edge,
+}
+
+/// Algorithm used to swap the buffer piece with a target
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlindSwapAlgorithm {
+    /// Old Pochmann corners, using a modified Y permutation
+    OldPochmannCorner,
+    /// Old Pochmann edges, using a T permutation
+    OldPochmannEdge,
+    /// M2 edges
+    M2,
+}
+
+/// Analysis of a single target in a blind solve. Each target is solved with
+/// setup moves that bring the target to the swap position, the swap algorithm,
+/// and the inverse of the setup moves.
+#[derive(Clone)]
+pub struct BlindCycleAnalysis {
+    pub piece: BlindPieceType,
+    pub algorithm: BlindSwapAlgorithm,
+    /// Time between the end of the previous target and the start of this one,
+    /// which is spent recalling the next target from memory
+    pub recognition_time: u32,
+    pub execution_time: u32,
+    /// Move index of the first setup move
+    pub start_move_index: usize,
+    pub setup_moves: Vec<Move>,
+    /// All moves performed, including setup and undo moves
+    pub moves: Vec<Move>,
+}
+
+/// Analysis of a blind solve using Old Pochmann or M2 style methods
+#[derive(Clone)]
+pub struct BlindAnalysis {
+    /// Time spent memorizing before the first move
+    pub memo_time: u32,
+    /// Time from the first move to the end of the solve
+    pub execution_time: u32,
+    pub cycles: Vec<BlindCycleAnalysis>,
+    /// Number of moves that are not part of a target, such as parity
+    /// algorithms or corrections
+    pub other_move_count: usize,
+}
+
+/// Quarter turn from a solution. Half turns are split into two quarter turns
+/// because smart cubes report them that way.
+#[derive(Clone, Copy)]
+struct QuarterTurn {
+    face: CubeFace,
+    direction: i32,
+    move_index: usize,
+}
+
+impl QuarterTurn {
+    fn to_move(&self) -> Move {
+        Move::from_face_and_rotation(self.face, self.direction).unwrap()
+    }
+
+    /// Rewrites the turn for a cube held rotated by x2 from its centers. An M2
+    /// turns the centers along with the slice, so after an M2 the face the
+    /// solver sees on top is the bottom face as reported by a smart cube.
+    fn rotate_x2(&mut self) {
+        match self.face {
+            CubeFace::Right | CubeFace::Left => (),
+            face => self.face = face.opposite(),
+        }
+    }
+}
+
+struct Swap {
+    algorithm: BlindSwapAlgorithm,
+    start: usize,
+    end: usize,
+}
+
+impl BlindSwapAlgorithm {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            BlindSwapAlgorithm::OldPochmannCorner => "OP",
+            BlindSwapAlgorithm::OldPochmannEdge => "OP",
+            BlindSwapAlgorithm::M2 => "M2",
+        }
+    }
+
+    pub fn piece(&self) -> BlindPieceType {
+        match self {
+            BlindSwapAlgorithm::OldPochmannCorner => BlindPieceType::Corner,
+            BlindSwapAlgorithm::OldPochmannEdge | BlindSwapAlgorithm::M2 => BlindPieceType::Edge,
+        }
+    }
+
+    /// Checks for this algorithm at the given turn, returning the number of
+    /// turns it spans if found
+    fn matches(&self, turns: &[QuarterTurn]) -> Option<usize> {
+        match self {
+            BlindSwapAlgorithm::OldPochmannCorner => Self::matches_sequence(OP_CORNER_SWAP, turns),
+            BlindSwapAlgorithm::OldPochmannEdge => Self::matches_sequence(OP_EDGE_SWAP, turns),
+            BlindSwapAlgorithm::M2 => {
+                // Smart cubes have fixed centers, so an M2 is reported as R2 and L2
+                // turns in any order
+                let turns = turns.get(0..4)?;
+                let mut right = 0;
+                let mut left = 0;
+                for turn in turns {
+                    match turn.face {
+                        CubeFace::Right => right += turn.direction,
+                        CubeFace::Left => left += turn.direction,
+                        _ => return None,
+                    }
+                }
+                if right.rem_euclid(4) == 2 && left.rem_euclid(4) == 2 {
+                    Some(4)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn matches_sequence(algorithm: &[Move], turns: &[QuarterTurn]) -> Option<usize> {
+        let mut idx = 0;
+        for mv in algorithm {
+            if mv.rotation() == 2 {
+                // Half turns can be performed in either direction
+                let first = turns.get(idx)?;
+                let second = turns.get(idx + 1)?;
+                if first.face != mv.face()
+                    || second.face != mv.face()
+                    || first.direction != second.direction
+                {
+                    return None;
+                }
+                idx += 2;
+            } else {
+                let turn = turns.get(idx)?;
+                if turn.face != mv.face() || turn.direction != mv.rotation() {
+                    return None;
+                }
+                idx += 1;
+            }
+        }
+        Some(idx)
+    }
+}
+
+impl BlindAnalysis {
+    pub fn analyze(solve: &CubeWithSolution) -> Option<Self> {
+        let solution = &solve.solution;
+        let mut turns = Self::quarter_turns(solution);
+        if turns.len() == 0 {
+            return None;
+        }
+
+        // Find the swap algorithms
+        let mut swaps = Vec::new();
+        let mut idx = 0;
+        while idx < turns.len() {
+            let found = [
+                BlindSwapAlgorithm::OldPochmannCorner,
+                BlindSwapAlgorithm::OldPochmannEdge,
+                BlindSwapAlgorithm::M2,
+            ]
+            .iter()
+            .find_map(|algorithm| {
+                algorithm
+                    .matches(&turns[idx..])
+                    .map(|len| (*algorithm, len))
+            });
+            if let Some((algorithm, len)) = found {
+                // Each M2 changes the orientation the cube is held in relative
+                // to its centers. Track it so that the turns that follow are
+                // in the orientation the solver sees.
+                if algorithm == BlindSwapAlgorithm::M2 {
+                    for turn in &mut turns[idx + len..] {
+                        turn.rotate_x2();
+                    }
+                }
+                swaps.push(Swap {
+                    algorithm,
+                    start: idx,
+                    end: idx + len,
+                });
+                idx += len;
+            } else {
+                idx += 1;
+            }
+        }
+        if swaps.len() < MIN_TARGETS {
+            return None;
+        }
+
+        // Find the setup moves around each swap. The setup moves are the longest
+        // sequence before the swap that is undone by the moves after it.
+        let memo_time = solution[0].time();
+        let mut cycles = Vec::new();
+        let mut prev_end = 0;
+        let mut prev_end_time = memo_time;
+        let mut cycle_turns = 0;
+        for (swap_idx, swap) in swaps.iter().enumerate() {
+            let next_start = swaps
+                .get(swap_idx + 1)
+                .map(|next| next.start)
+                .unwrap_or(turns.len());
+            let max_setup = (swap.start - prev_end).min(next_start - swap.end);
+            let setup_len = (0..=max_setup)
+                .rev()
+                .find(|len| {
+                    let mut cube = Cube3x3x3::new();
+                    for turn in &turns[swap.start - len..swap.start] {
+                        cube.do_move(turn.to_move());
+                    }
+                    for turn in &turns[swap.end..swap.end + len] {
+                        cube.do_move(turn.to_move());
+                    }
+                    cube.is_solved()
+                })
+                .unwrap_or(0);
+
+            let first = &turns[swap.start - setup_len];
+            let last = &turns[swap.end + setup_len - 1];
+            let start_time = solution[first.move_index].time();
+            let end_time = solution[last.move_index].time();
+            cycles.push(BlindCycleAnalysis {
+                piece: swap.algorithm.piece(),
+                algorithm: swap.algorithm,
+                recognition_time: start_time.saturating_sub(prev_end_time),
+                execution_time: end_time.saturating_sub(start_time),
+                start_move_index: first.move_index,
+                setup_moves: Self::moves(solution, &turns[swap.start - setup_len..swap.start]),
+                moves: Self::moves(
+                    solution,
+                    &turns[swap.start - setup_len..swap.end + setup_len],
+                ),
+            });
+
+            cycle_turns += swap.end - swap.start + setup_len * 2;
+            prev_end = swap.end + setup_len;
+            prev_end_time = end_time;
+        }
+
+        if (cycle_turns as f32) < turns.len() as f32 * MIN_CYCLE_COVERAGE {
+            return None;
+        }
+
+        let cycle_moves: usize = cycles.iter().map(|cycle| cycle.moves.len()).sum();
+        Some(BlindAnalysis {
+            memo_time,
+            execution_time: solution[solution.len() - 1].time() - memo_time,
+            cycles,
+            other_move_count: solution.len().saturating_sub(cycle_moves),
+        })
+    }
+
+    /// Number of targets solved with the given piece type
+    pub fn target_count(&self, piece: BlindPieceType) -> usize {
+        self.cycles
+            .iter()
+            .filter(|cycle| cycle.piece == piece)
+            .count()
+    }
+
+    pub fn corner_targets(&self) -> usize {
+        self.target_count(BlindPieceType::Corner)
+    }
+
+    pub fn edge_targets(&self) -> usize {
+        self.target_count(BlindPieceType::Edge)
+    }
+
+    fn quarter_turns(solution: &[TimedMove]) -> Vec<QuarterTurn> {
+        let mut result = Vec::new();
+        for (move_index, mv) in solution.iter().enumerate() {
+            let mv = mv.move_();
+            let (count, direction) = match mv.rotation() {
+                2 => (2, 1),
+                rotation => (1, rotation),
+            };
+            for _ in 0..count {
+                result.push(QuarterTurn {
+                    face: mv.face(),
+                    direction,
+                    move_index,
+                });
+            }
+        }
+        result
+    }
+
+    /// Gets the moves of the solution that the given turns are part of, in
+    /// the orientation the solver sees
+    fn moves(solution: &[TimedMove], turns: &[QuarterTurn]) -> Vec<Move> {
+        let mut result = Vec::new();
+        let mut last_index = None;
+        for turn in turns {
+            if last_index != Some(turn.move_index) {
+                let rotation = solution[turn.move_index].move_().rotation();
+                result.push(Move::from_face_and_rotation(turn.face, rotation).unwrap());
+                last_index = Some(turn.move_index);
+            }
+        }
+        result
+    }
+
+    fn piece_summary(
+        &self,
+        cycles: &[&BlindCycleAnalysis],
+        major_step_index: usize,
+    ) -> AnalysisStepSummary {
+        let (name, short_name) = match cycles[0].piece {
+            BlindPieceType::Corner => ("Corners", "Corners"),
+            BlindPieceType::Edge => ("Edges", "Edges"),
+        };
+        let mut substeps = Vec::new();
+        for cycle in cycles {
+            substeps.push(AnalysisSubstepTime::Recognition(cycle.recognition_time));
+            substeps.push(AnalysisSubstepTime::Execution(cycle.execution_time));
+        }
+        AnalysisStepSummary {
+            name: name.into(),
+            short_name: short_name.into(),
+            major_step_index,
+            algorithm: Some(cycles[0].algorithm.to_str().into()),
+            recognition_time: cycles.iter().map(|cycle| cycle.recognition_time).sum(),
+            execution_time: cycles.iter().map(|cycle| cycle.execution_time).sum(),
+            substeps,
+            move_count: cycles.iter().map(|cycle| cycle.moves.len()).sum(),
+        }
+    }
+
+    fn memo_summary(&self) -> AnalysisStepSummary {
+        AnalysisStepSummary {
+            name: "Memo".into(),
+            short_name: "Memo".into(),
+            major_step_index: 0,
+            algorithm: None,
+            recognition_time: self.memo_time,
+            execution_time: 0,
+            substeps: vec![AnalysisSubstepTime::Recognition(self.memo_time)],
+            move_count: 0,
+        }
+    }
+}
+
+impl AnalysisSummary for BlindAnalysis {
+    fn step_summary(&self) -> Vec<AnalysisStepSummary> {
+        let mut result = vec![self.memo_summary()];
+
+        // Group consecutive targets of the same piece type, which is usually
+        // edges followed by corners
+        let mut group: Vec<&BlindCycleAnalysis> = Vec::new();
+        for cycle in &self.cycles {
+            if let Some(first) = group.first() {
+                if first.piece != cycle.piece {
+                    result.push(self.piece_summary(&group, result.len()));
+                    group.clear();
+                }
+            }
+            group.push(cycle);
+        }
+        if group.len() > 0 {
+            result.push(self.piece_summary(&group, result.len()));
+        }
+        result
+    }
+
+    fn detailed_step_summary(&self) -> Vec<AnalysisStepSummary> {
+        let mut result = vec![self.memo_summary()];
+        let mut major_step_index = 0;
+        let mut last_piece = None;
+        for cycle in &self.cycles {
+            if last_piece != Some(cycle.piece) {
+                major_step_index += 1;
+                last_piece = Some(cycle.piece);
+            }
+            let (name, short_name) = match cycle.piece {
+                BlindPieceType::Corner => ("Corner Target", "Corner"),
+                BlindPieceType::Edge => ("Edge Target", "Edge"),
+            };
+            result.push(AnalysisStepSummary {
+                name: name.into(),
+                short_name: short_name.into(),
+                major_step_index,
+                algorithm: if cycle.setup_moves.len() > 0 {
+                    Some(cycle.setup_moves.to_string())
+                } else {
+                    None
+                },
+                recognition_time: cycle.recognition_time,
+                execution_time: cycle.execution_time,
+                substeps: vec![
+                    AnalysisSubstepTime::Recognition(cycle.recognition_time),
+                    AnalysisSubstepTime::Execution(cycle.execution_time),
+                ],
+                move_count: cycle.moves.len(),
+            });
+        }
+        result
+    }
+}
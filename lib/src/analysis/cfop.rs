@@ -4,9 +4,10 @@ use crate::tables::analysis::{
 use crate::tables::table3x3x3::CUBE3_EDGE_ADJACENCY;
 use crate::{
     cube3x3x3::FaceRowOrColumn, AnalysisStepSummary, AnalysisSubstepTime, AnalysisSummary, Color,
-    Cube, Cube3x3x3Faces, CubeFace, CubeWithSolution, InitialCubeState, Move, MoveSequence,
-    PartialAnalysis, PartialAnalysisMethod, TimedMove,
+    Cube, Cube3x3x3, Cube3x3x3Faces, CubeFace, CubeWithSolution, InitialCubeState, Move,
+    MoveSequence, PartialAnalysis, PartialAnalysisMethod, TimedMove,
 };
+use serde::{Deserialize, Serialize};
 
 /// Analysis of a full solve using CFOP method. Both one-look and two-look
 /// are fully supported automatically.
@@ -46,6 +47,9 @@ pub struct CrossAnalysis {
     pub color: Color,
     /// Time spent solving the first layer cross
     pub time: u32,
+    /// Number of F2L pairs that were solved along with the cross. If this is
+    /// nonzero, the cross was an extended cross (XCross, XXCross, etc.)
+    pub extended_pairs: usize,
     /// Moves performed
     pub moves: Vec<Move>,
 }
@@ -84,6 +88,9 @@ pub struct OLLAnalysis {
     pub start_move_index: usize,
     /// Moves performed
     pub moves: Vec<Move>,
+    /// How the algorithm was executed, including the hand used and any
+    /// adjustment of the last layer before and after the algorithm
+    pub execution: AlgorithmExecution,
 }
 
 /// OLL algorithm used during solve. Two-look algorithms are named and
@@ -121,6 +128,9 @@ pub struct PLLAnalysis {
     pub start_move_index: usize,
     /// Moves performed
     pub moves: Vec<Move>,
+    /// How the algorithm was executed, including the hand used and any
+    /// adjustment of the last layer before and after the algorithm
+    pub execution: AlgorithmExecution,
 }
 
 /// PLL algorithm used during solve.
@@ -149,6 +159,32 @@ pub enum PLLAlgorithm {
     Z,
 }
 
+/// Hand used to execute a last layer algorithm. This is determined from which
+/// side of the last layer the side face turns were performed on, with the
+/// cube held in the standard orientation for that last layer face.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlgorithmHand {
+    Right,
+    Left,
+    /// Equal number of turns on both sides, or no turns on either side
+    Mixed,
+}
+
+/// Exact moves used to execute a last layer algorithm, split into the
+/// adjustments of the last layer (AUF) and the algorithm itself. This allows
+/// comparison against the algorithm the solver intended to use, as mirrored
+/// and inverse algorithms solve the same cases with different moves.
+#[derive(Clone)]
+pub struct AlgorithmExecution {
+    /// Last layer adjustment performed before the algorithm
+    pub pre_auf: Option<Move>,
+    /// Moves of the algorithm, without the last layer adjustments
+    pub algorithm: Vec<Move>,
+    /// Last layer adjustment performed after the algorithm
+    pub post_auf: Option<Move>,
+    pub hand: AlgorithmHand,
+}
+
 /// Analysis of the final alignment of the last layer. This is after the PLL algorithm
 /// is completed and one or more face rotations are needed to finish the solve. These
 /// fields may be zero if the cube was solved directly after the PLL algorithm.
@@ -162,6 +198,38 @@ pub struct FinalAlignmentAnalysis {
     pub moves: Vec<Move>,
 }
 
+/// State of the cube at the end of a step of a CFOP solve
+#[derive(Clone)]
+pub struct StepBoundary {
+    /// Short name of the step that ends at this boundary
+    pub step: &'static str,
+    /// Number of moves of the solution performed before the boundary
+    pub move_index: usize,
+    /// Cube state after the step
+    pub state: Cube3x3x3,
+}
+
+/// Steps of a CFOP solve that were skipped because an earlier step happened
+/// to solve them as well. Pairs solved deliberately with an extended cross
+/// are not counted as skips.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CFOPSkips {
+    /// Number of F2L pairs that were solved without any moves of their own
+    pub f2l_pairs: usize,
+    pub oll: bool,
+    pub pll: bool,
+}
+
+/// Step that can be skipped in a CFOP solve, used to filter solves
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkippedStep {
+    F2LPair,
+    OLL,
+    PLL,
+    /// Any of the other steps
+    Any,
+}
+
 /// State of the cube as it's being solved with CFOP method
 #[derive(Clone, PartialEq, Eq)]
 pub enum CFOPProgress {
@@ -204,9 +272,184 @@ impl CFOPAnalysis {
     pub fn analyze(solve: &CubeWithSolution) -> Option<Self> {
         CFOPPartialAnalysis::analyze(solve).into()
     }
+
+    /// Returns the cube state at the end of each step of the solve, in solve
+    /// order. Steps that were skipped do not have a boundary.
+    pub fn step_boundaries(&self, initial_state: &Cube3x3x3) -> Vec<StepBoundary> {
+        let mut steps: Vec<(&'static str, &[Move])> = vec![("Cross", self.cross.moves.as_slice())];
+        for pair in &self.f2l_pairs {
+            steps.push(("Pair", pair.moves.as_slice()));
+        }
+        for oll in &self.oll {
+            steps.push(("OLL", oll.moves.as_slice()));
+        }
+        for pll in &self.pll {
+            steps.push(("PLL", pll.moves.as_slice()));
+        }
+        if self.alignment.moves.len() != 0 {
+            steps.push(("Align", self.alignment.moves.as_slice()));
+        }
+
+        let mut cube = initial_state.clone();
+        let mut move_index = 0;
+        let mut result = Vec::new();
+        for (step, moves) in steps {
+            cube.do_moves(moves);
+            move_index += moves.len();
+            result.push(StepBoundary {
+                step,
+                move_index,
+                state: cube.clone(),
+            });
+        }
+        result
+    }
+
+    pub fn skips(&self) -> CFOPSkips {
+        CFOPSkips {
+            f2l_pairs: 4usize
+                .saturating_sub(self.cross.extended_pairs)
+                .saturating_sub(self.f2l_pairs.len()),
+            oll: self.oll.len() == 0,
+            pll: self.pll.len() == 0,
+        }
+    }
+}
+
+impl CFOPSkips {
+    pub fn any(&self) -> bool {
+        self.f2l_pairs > 0 || self.oll || self.pll
+    }
+
+    pub fn contains(&self, step: SkippedStep) -> bool {
+        match step {
+            SkippedStep::F2LPair => self.f2l_pairs > 0,
+            SkippedStep::OLL => self.oll,
+            SkippedStep::PLL => self.pll,
+            SkippedStep::Any => self.any(),
+        }
+    }
+}
+
+impl SkippedStep {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            SkippedStep::F2LPair => "F2L skip",
+            SkippedStep::OLL => "OLL skip",
+            SkippedStep::PLL => "PLL skip",
+            SkippedStep::Any => "Any skip",
+        }
+    }
+}
+
+impl AlgorithmHand {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            AlgorithmHand::Right => "Right",
+            AlgorithmHand::Left => "Left",
+            AlgorithmHand::Mixed => "Mixed",
+        }
+    }
+}
+
+impl AlgorithmExecution {
+    pub fn new(moves: &[Move], last_layer: CubeFace) -> Self {
+        // Combine turns of the last layer at the start and end of the moves into
+        // a single adjustment on each side
+        let is_auf = |mv: &Move| mv.face() == last_layer && mv.width() == 1;
+        let pre_count = moves.iter().take_while(|mv| is_auf(mv)).count();
+        let post_count = moves[pre_count..]
+            .iter()
+            .rev()
+            .take_while(|mv| is_auf(mv))
+            .count();
+        let algorithm = moves[pre_count..moves.len() - post_count].to_vec();
+        let combine = |moves: &[Move]| {
+            let rotation: i32 = moves.iter().map(|mv| mv.rotation()).sum();
+            Move::from_face_and_rotation(last_layer, rotation)
+        };
+
+        // Determine the hand from the side faces as seen with the last layer
+        // rotated to the top using the smallest cube rotation
+        let (right, left) = match last_layer {
+            CubeFace::Top | CubeFace::Front | CubeFace::Back => (CubeFace::Right, CubeFace::Left),
+            CubeFace::Bottom => (CubeFace::Left, CubeFace::Right),
+            CubeFace::Right => (CubeFace::Bottom, CubeFace::Top),
+            CubeFace::Left => (CubeFace::Top, CubeFace::Bottom),
+        };
+        let right_count = algorithm.iter().filter(|mv| mv.face() == right).count();
+        let left_count = algorithm.iter().filter(|mv| mv.face() == left).count();
+        let hand = if right_count > left_count {
+            AlgorithmHand::Right
+        } else if left_count > right_count {
+            AlgorithmHand::Left
+        } else {
+            AlgorithmHand::Mixed
+        };
+
+        Self {
+            pre_auf: combine(&moves[..pre_count]),
+            post_auf: combine(&moves[moves.len() - post_count..]),
+            algorithm,
+            hand,
+        }
+    }
+
+    /// Number of last layer adjustments made before and after the algorithm
+    pub fn auf_count(&self) -> usize {
+        self.pre_auf.iter().count() + self.post_auf.iter().count()
+    }
+
+    /// Returns the name of an algorithm with the hand used to perform it, if it
+    /// was not performed with the usual right hand
+    pub fn label(&self, name: &str) -> String {
+        match self.hand {
+            AlgorithmHand::Right => name.into(),
+            hand => format!("{} ({})", name, hand.to_str()),
+        }
+    }
+}
+
+impl ToString for AlgorithmExecution {
+    /// Returns the moves performed with the last layer adjustments in parenthesis
+    fn to_string(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(auf) = self.pre_auf {
+            parts.push(format!("({})", auf.to_string()));
+        }
+        if self.algorithm.len() != 0 {
+            parts.push(self.algorithm.to_string());
+        }
+        if let Some(auf) = self.post_auf {
+            parts.push(format!("({})", auf.to_string()));
+        }
+        parts.join(" ")
+    }
+}
+
+impl CrossAnalysis {
+    /// Returns true if one or more F2L pairs were solved during the cross
+    pub fn is_extended(&self) -> bool {
+        self.extended_pairs > 0
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self.extended_pairs {
+            0 => "Cross",
+            1 => "XCross",
+            2 => "XXCross",
+            3 => "XXXCross",
+            _ => "XXXXCross",
+        }
+    }
 }
 
 impl OLLAlgorithm {
+    /// All 57 OLL cases, in order of their standard number
+    pub fn all() -> Vec<Self> {
+        (0..CUBE3_OLL_CASES.len()).map(Self::from_index).collect()
+    }
+
     fn from_index(idx: usize) -> Self {
         match idx + 1 {
             21 => OLLAlgorithm::H,
@@ -344,6 +587,11 @@ impl OLLAlgorithm {
 }
 
 impl PLLAlgorithm {
+    /// All 21 PLL cases
+    pub fn all() -> Vec<Self> {
+        (0..CUBE3_PLL_CASES.len()).map(Self::from_index).collect()
+    }
+
     fn from_index(idx: usize) -> Self {
         match idx {
             0 => PLLAlgorithm::Aa,
@@ -586,6 +834,7 @@ impl AnalysisData {
                     self.cross_analysis = Some(CrossAnalysis {
                         color: self.cross_color,
                         time: self.time - self.state_start_time,
+                        extended_pairs: self.f2l_pair_count(),
                         moves: self.state_moves.clone(),
                     });
                     self.new_state(CFOPProgress::F2LPair(0));
@@ -640,6 +889,10 @@ impl AnalysisData {
                                     - recognition_time,
                                 start_move_index: self.state_start_index,
                                 moves: self.state_moves.clone(),
+                                execution: AlgorithmExecution::new(
+                                    &self.state_moves,
+                                    self.cross_face.opposite(),
+                                ),
                             });
                         }
 
@@ -684,6 +937,10 @@ impl AnalysisData {
                                     - recognition_time,
                                 start_move_index: self.state_start_index,
                                 moves: self.state_moves.clone(),
+                                execution: AlgorithmExecution::new(
+                                    &self.state_moves,
+                                    self.cross_face.opposite(),
+                                ),
                             });
                         }
                         self.new_state(CFOPProgress::OLL(new_one_look_algorithm));
@@ -708,6 +965,10 @@ impl AnalysisData {
                                     - recognition_time,
                                 start_move_index: self.state_start_index,
                                 moves: self.state_moves.clone(),
+                                execution: AlgorithmExecution::new(
+                                    &self.state_moves,
+                                    self.cross_face.opposite(),
+                                ),
                             });
                         }
                         self.new_state(CFOPProgress::FinalAlignment);
@@ -742,6 +1003,10 @@ impl AnalysisData {
                                     - recognition_time,
                                 start_move_index: self.state_start_index,
                                 moves: self.state_moves.clone(),
+                                execution: AlgorithmExecution::new(
+                                    &self.state_moves,
+                                    self.cross_face.opposite(),
+                                ),
                             });
                         }
                         self.new_state(CFOPProgress::PLL(new_one_look_algorithm));
@@ -875,8 +1140,8 @@ impl<T: CFOPAnalysisStages> AnalysisSummary for T {
         let mut result = Vec::new();
         if let Some(cross) = self.cross() {
             result.push(AnalysisStepSummary {
-                name: "Cross".into(),
-                short_name: "Cross".into(),
+                name: cross.to_str().into(),
+                short_name: cross.to_str().into(),
                 major_step_index: 0,
                 algorithm: None,
                 recognition_time: 0,
@@ -1003,8 +1268,8 @@ impl<T: CFOPAnalysisStages> AnalysisSummary for T {
         let mut result = Vec::new();
         if let Some(cross) = self.cross() {
             result.push(AnalysisStepSummary {
-                name: format!("{} Cross", cross.color.to_str()),
-                short_name: "Cross".into(),
+                name: format!("{} {}", cross.color.to_str(), cross.to_str()),
+                short_name: cross.to_str().into(),
                 major_step_index: 0,
                 algorithm: None,
                 recognition_time: 0,
@@ -1033,7 +1298,7 @@ impl<T: CFOPAnalysisStages> AnalysisSummary for T {
                 name: "OLL".into(),
                 short_name: "OLL".into(),
                 major_step_index: 2,
-                algorithm: Some(oll.performed_algorithm.to_string()),
+                algorithm: Some(oll.execution.label(&oll.performed_algorithm.to_string())),
                 recognition_time: oll.recognition_time,
                 execution_time: oll.execution_time,
                 substeps: vec![
@@ -1048,7 +1313,7 @@ impl<T: CFOPAnalysisStages> AnalysisSummary for T {
                 name: "PLL".into(),
                 short_name: "PLL".into(),
                 major_step_index: 3,
-                algorithm: Some(pll.performed_algorithm.to_str().into()),
+                algorithm: Some(pll.execution.label(pll.performed_algorithm.to_str())),
                 recognition_time: pll.recognition_time,
                 execution_time: pll.execution_time,
                 substeps: vec![
@@ -1144,8 +1409,9 @@ impl std::fmt::Display for CFOPPartialAnalysis {
         if let Some(cross) = &self.cross {
             write!(
                 f,
-                "{} cross: {} moves in {}ms\n",
+                "{} {}: {} moves in {}ms\n",
                 cross.color.to_str(),
+                cross.to_str().to_lowercase(),
                 cross.moves.len(),
                 cross.time
             )?;
@@ -0,0 +1,373 @@
+use crate::{
+    AnalysisStepSummary, AnalysisSubstepTime, AnalysisSummary, Corner, Cube, Cube3x3x3, CubeFace,
+    CubeWithSolution, Edge3x3x3, Move,
+};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Condition on the cube state that marks the end of a step in a custom
+/// analysis method. Pieces are identified by their solved positions, which
+/// do not change during a solve as smart cubes only report face turns.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StepPredicate {
+    /// The given corners and edges are in their solved positions with the
+    /// correct orientation
+    PiecesSolved {
+        corners: Vec<Corner>,
+        edges: Vec<Edge3x3x3>,
+    },
+    /// Every one of the conditions is met
+    All(Vec<StepPredicate>),
+    /// At least one of the conditions is met
+    Any(Vec<StepPredicate>),
+    /// The entire cube is solved
+    Solved,
+}
+
+/// Named step of a custom analysis method
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomStep {
+    pub name: String,
+    /// Short name of the step for display in compact views
+    pub short_name: String,
+    /// Condition that is met once the step is complete
+    pub predicate: StepPredicate,
+}
+
+/// Solving method defined as an ordered list of steps. Each step ends at the
+/// first move where its condition is met, so methods the analysis does not
+/// know about can be split into steps by describing the pieces each step
+/// solves.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomMethod {
+    pub name: String,
+    pub steps: Vec<CustomStep>,
+}
+
+/// Analysis of a single step of a custom method
+#[derive(Clone)]
+pub struct CustomStepAnalysis {
+    pub name: String,
+    pub short_name: String,
+    /// Time spent recognizing the state
+    pub recognition_time: u32,
+    /// Time spent executing the step
+    pub execution_time: u32,
+    /// Move index of the start of the step
+    pub start_move_index: usize,
+    /// Moves performed
+    pub moves: Vec<Move>,
+}
+
+/// Analysis of a solve split into the steps of a custom method. Steps that
+/// were complete before any of their moves were made have no moves and zero
+/// time.
+#[derive(Clone)]
+pub struct CustomAnalysis {
+    pub method: String,
+    /// Steps that were completed, in order. If the solve did not complete
+    /// every step, the remaining steps are not included.
+    pub steps: Vec<CustomStepAnalysis>,
+}
+
+const ALL_CORNERS: [Corner; 8] = [
+    Corner::URF,
+    Corner::UFL,
+    Corner::ULB,
+    Corner::UBR,
+    Corner::DFR,
+    Corner::DLF,
+    Corner::DBL,
+    Corner::DRB,
+];
+
+const ALL_FACES: [CubeFace; 6] = [
+    CubeFace::Top,
+    CubeFace::Front,
+    CubeFace::Right,
+    CubeFace::Back,
+    CubeFace::Left,
+    CubeFace::Bottom,
+];
+
+fn corner_faces(corner: Corner) -> [CubeFace; 3] {
+    match corner {
+        Corner::URF => [CubeFace::Top, CubeFace::Right, CubeFace::Front],
+        Corner::UFL => [CubeFace::Top, CubeFace::Front, CubeFace::Left],
+        Corner::ULB => [CubeFace::Top, CubeFace::Left, CubeFace::Back],
+        Corner::UBR => [CubeFace::Top, CubeFace::Back, CubeFace::Right],
+        Corner::DFR => [CubeFace::Bottom, CubeFace::Front, CubeFace::Right],
+        Corner::DLF => [CubeFace::Bottom, CubeFace::Left, CubeFace::Front],
+        Corner::DBL => [CubeFace::Bottom, CubeFace::Back, CubeFace::Left],
+        Corner::DRB => [CubeFace::Bottom, CubeFace::Right, CubeFace::Back],
+    }
+}
+
+fn edge_faces(edge: Edge3x3x3) -> [CubeFace; 2] {
+    match edge {
+        Edge3x3x3::UR => [CubeFace::Top, CubeFace::Right],
+        Edge3x3x3::UF => [CubeFace::Top, CubeFace::Front],
+        Edge3x3x3::UL => [CubeFace::Top, CubeFace::Left],
+        Edge3x3x3::UB => [CubeFace::Top, CubeFace::Back],
+        Edge3x3x3::DR => [CubeFace::Bottom, CubeFace::Right],
+        Edge3x3x3::DF => [CubeFace::Bottom, CubeFace::Front],
+        Edge3x3x3::DL => [CubeFace::Bottom, CubeFace::Left],
+        Edge3x3x3::DB => [CubeFace::Bottom, CubeFace::Back],
+        Edge3x3x3::FR => [CubeFace::Front, CubeFace::Right],
+        Edge3x3x3::FL => [CubeFace::Front, CubeFace::Left],
+        Edge3x3x3::BL => [CubeFace::Back, CubeFace::Left],
+        Edge3x3x3::BR => [CubeFace::Back, CubeFace::Right],
+    }
+}
+
+fn all_edges() -> impl Iterator<Item = Edge3x3x3> {
+    (0..12).map(|idx| Edge3x3x3::try_from(idx).unwrap())
+}
+
+/// Edges that have both of their faces in the given set of faces
+fn edges_within(faces: &[CubeFace]) -> Vec<Edge3x3x3> {
+    all_edges()
+        .filter(|edge| edge_faces(*edge).iter().all(|face| faces.contains(face)))
+        .collect()
+}
+
+impl StepPredicate {
+    /// The given pieces are solved
+    pub fn pieces(corners: &[Corner], edges: &[Edge3x3x3]) -> Self {
+        StepPredicate::PiecesSolved {
+            corners: corners.to_vec(),
+            edges: edges.to_vec(),
+        }
+    }
+
+    /// Cross on the given face
+    pub fn cross(face: CubeFace) -> Self {
+        let edges: Vec<Edge3x3x3> = all_edges()
+            .filter(|edge| edge_faces(*edge).contains(&face))
+            .collect();
+        Self::pieces(&[], &edges)
+    }
+
+    /// First two layers solved with the cross on the given face. If
+    /// `missing_pair` is given, that F2L slot is allowed to be unsolved.
+    pub fn f2l(face: CubeFace, missing_pair: Option<Corner>) -> Self {
+        let mut corners = Vec::new();
+        let mut edges: Vec<Edge3x3x3> = all_edges()
+            .filter(|edge| edge_faces(*edge).contains(&face))
+            .collect();
+        for corner in ALL_CORNERS.iter() {
+            let faces = corner_faces(*corner);
+            if !faces.contains(&face) || Some(*corner) == missing_pair {
+                continue;
+            }
+            corners.push(*corner);
+
+            // Pair edge is the edge between the corner's two side faces
+            let sides: Vec<CubeFace> = faces.iter().cloned().filter(|f| *f != face).collect();
+            edges.extend(edges_within(&sides));
+        }
+        Self::pieces(&corners, &edges)
+    }
+
+    /// A 2x2x2 block is solved in any corner of the cube
+    pub fn any_2x2x2_block() -> Self {
+        StepPredicate::Any(
+            ALL_CORNERS
+                .iter()
+                .map(|corner| Self::pieces(&[*corner], &edges_within(&corner_faces(*corner))))
+                .collect(),
+        )
+    }
+
+    /// A 2x2x3 block is solved along any edge of the cube
+    pub fn any_2x2x3_block() -> Self {
+        StepPredicate::Any(
+            all_edges()
+                .map(|spine| {
+                    let spine_faces = edge_faces(spine);
+                    let corners: Vec<Corner> = ALL_CORNERS
+                        .iter()
+                        .cloned()
+                        .filter(|corner| {
+                            spine_faces
+                                .iter()
+                                .all(|face| corner_faces(*corner).contains(face))
+                        })
+                        .collect();
+                    let mut faces = Vec::new();
+                    for corner in &corners {
+                        for face in corner_faces(*corner).iter() {
+                            if !faces.contains(face) {
+                                faces.push(*face);
+                            }
+                        }
+                    }
+                    Self::pieces(&corners, &edges_within(&faces))
+                })
+                .collect(),
+        )
+    }
+
+    /// First two layers with one pair remaining, on any face and in any slot
+    pub fn any_f2l_minus_one() -> Self {
+        let mut options = Vec::new();
+        for face in ALL_FACES.iter() {
+            for corner in ALL_CORNERS.iter() {
+                if corner_faces(*corner).contains(face) {
+                    options.push(Self::f2l(*face, Some(*corner)));
+                }
+            }
+        }
+        StepPredicate::Any(options)
+    }
+
+    /// First two layers solved on any face
+    pub fn any_f2l() -> Self {
+        StepPredicate::Any(
+            ALL_FACES
+                .iter()
+                .map(|face| Self::f2l(*face, None))
+                .collect(),
+        )
+    }
+
+    pub fn is_met(&self, cube: &Cube3x3x3) -> bool {
+        match self {
+            StepPredicate::PiecesSolved { corners, edges } => {
+                corners.iter().all(|corner| {
+                    let piece = cube.corner_piece(*corner);
+                    piece.piece == *corner && piece.orientation == 0
+                }) && edges.iter().all(|edge| {
+                    let piece = cube.edge_piece(*edge);
+                    piece.piece == *edge && piece.orientation == 0
+                })
+            }
+            StepPredicate::All(predicates) => predicates.iter().all(|pred| pred.is_met(cube)),
+            StepPredicate::Any(predicates) => predicates.iter().any(|pred| pred.is_met(cube)),
+            StepPredicate::Solved => cube.is_solved(),
+        }
+    }
+}
+
+impl CustomStep {
+    pub fn new(name: &str, short_name: &str, predicate: StepPredicate) -> Self {
+        Self {
+            name: name.into(),
+            short_name: short_name.into(),
+            predicate,
+        }
+    }
+}
+
+impl CustomMethod {
+    /// Block building method starting with a 2x2x2 block, expanding it to a
+    /// 2x2x3 block, and then finishing the first two layers before the last
+    /// layer. The steps are generic enough to describe Petrus and similar
+    /// methods without hard coding their edge orientation steps.
+    pub fn block_building() -> Self {
+        Self {
+            name: "Block building".into(),
+            steps: vec![
+                CustomStep::new("2x2x2 Block", "2x2x2", StepPredicate::any_2x2x2_block()),
+                CustomStep::new("2x2x3 Block", "2x2x3", StepPredicate::any_2x2x3_block()),
+                CustomStep::new("F2L-1", "F2L-1", StepPredicate::any_f2l_minus_one()),
+                CustomStep::new("F2L", "F2L", StepPredicate::any_f2l()),
+                CustomStep::new("Last Layer", "LL", StepPredicate::Solved),
+            ],
+        }
+    }
+}
+
+impl CustomAnalysis {
+    /// Splits a solve into the steps of a custom method. Returns `None` if
+    /// the method has no steps.
+    pub fn analyze(method: &CustomMethod, solve: &CubeWithSolution) -> Option<Self> {
+        if method.steps.len() == 0 {
+            return None;
+        }
+
+        let mut cube = solve.initial_state.clone();
+        let mut steps: Vec<CustomStepAnalysis> = Vec::new();
+        let mut start_index = 0;
+        let mut start_time = 0;
+        for move_index in 0..=solve.solution.len() {
+            let mut time = 0;
+            if move_index > 0 {
+                let mv = &solve.solution[move_index - 1];
+                cube.do_move(mv.move_());
+                time = mv.time();
+            }
+
+            // A move can complete more than one step, in which case the later
+            // steps are recorded with no moves
+            while let Some(step) = method.steps.get(steps.len()) {
+                if !step.predicate.is_met(&cube) {
+                    break;
+                }
+                let moves: Vec<Move> = solve.solution[start_index..move_index]
+                    .iter()
+                    .map(|mv| mv.move_())
+                    .collect();
+
+                // The first step has no recognition time, as inspection is not
+                // part of the solve
+                let recognition_time = if steps.len() == 0 || moves.len() == 0 {
+                    0
+                } else {
+                    solve.solution[start_index]
+                        .time()
+                        .saturating_sub(start_time)
+                };
+                steps.push(CustomStepAnalysis {
+                    name: step.name.clone(),
+                    short_name: step.short_name.clone(),
+                    recognition_time,
+                    execution_time: time.saturating_sub(start_time) - recognition_time,
+                    start_move_index: start_index,
+                    moves,
+                });
+                start_index = move_index;
+                start_time = time;
+            }
+            if steps.len() == method.steps.len() {
+                break;
+            }
+        }
+
+        Some(Self {
+            method: method.name.clone(),
+            steps,
+        })
+    }
+
+    /// Returns true if every step of the method was completed
+    pub fn is_complete(&self, method: &CustomMethod) -> bool {
+        self.steps.len() == method.steps.len()
+    }
+}
+
+impl AnalysisSummary for CustomAnalysis {
+    fn step_summary(&self) -> Vec<AnalysisStepSummary> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(idx, step)| AnalysisStepSummary {
+                name: step.name.clone(),
+                short_name: step.short_name.clone(),
+                major_step_index: idx,
+                algorithm: None,
+                recognition_time: step.recognition_time,
+                execution_time: step.execution_time,
+                substeps: vec![
+                    AnalysisSubstepTime::Recognition(step.recognition_time),
+                    AnalysisSubstepTime::Execution(step.execution_time),
+                ],
+                move_count: step.moves.len(),
+            })
+            .collect()
+    }
+
+    fn detailed_step_summary(&self) -> Vec<AnalysisStepSummary> {
+        self.step_summary()
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{AlgorithmHand, CFOPAnalysis, CubeFace, Move, TimedMove};
+
+/// Shortest pause between moves of an algorithm that is flagged as a regrip
+const MIN_REGRIP_PAUSE: u32 = 150;
+
+/// Pauses longer than this multiple of the typical pause within the
+/// algorithm are flagged as regrips
+const REGRIP_PAUSE_FACTOR: u32 = 3;
+
+/// Common group of moves that is performed as a single fingertrick. Moves are
+/// given with the last layer on top.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    /// R U R' U'
+    Sexy,
+    /// R' F R F'
+    Sledgehammer,
+    /// F R' F' R
+    Hedgeslammer,
+    /// R U R'
+    RightInsert,
+    /// R U' R'
+    RightPair,
+    /// R U2 R'
+    RightDouble,
+    /// L' U' L U
+    LeftSexy,
+    /// L F' L' F
+    LeftSledgehammer,
+    /// L' U' L
+    LeftInsert,
+    /// L' U L
+    LeftPair,
+    /// L' U2 L
+    LeftDouble,
+}
+
+/// Trigger found within the moves of an algorithm
+#[derive(Clone, Copy, Debug)]
+pub struct TriggerSegment {
+    pub trigger: Trigger,
+    /// Index of the first move of the trigger within the algorithm
+    pub start_move_index: usize,
+    pub move_count: usize,
+}
+
+/// Pause within an algorithm that is long enough to be a regrip
+#[derive(Clone, Copy, Debug)]
+pub struct Regrip {
+    /// Index of the move after the pause within the algorithm
+    pub move_index: usize,
+    pub pause: u32,
+}
+
+/// Fingertrick annotations of a last layer algorithm. Triggers are matched
+/// for both hands, so that left handed executions of an algorithm are also
+/// labeled.
+#[derive(Clone, Debug)]
+pub struct FingertrickAnalysis {
+    /// Move index of the start of the algorithm within the solve
+    pub start_move_index: usize,
+    pub moves: Vec<Move>,
+    pub triggers: Vec<TriggerSegment>,
+    pub regrips: Vec<Regrip>,
+}
+
+const TRIGGERS: &[(Trigger, &[Move])] = &[
+    (Trigger::Sexy, &[Move::R, Move::U, Move::Rp, Move::Up]),
+    (
+        Trigger::Sledgehammer,
+        &[Move::Rp, Move::F, Move::R, Move::Fp],
+    ),
+    (
+        Trigger::Hedgeslammer,
+        &[Move::F, Move::Rp, Move::Fp, Move::R],
+    ),
+    (Trigger::LeftSexy, &[Move::Lp, Move::Up, Move::L, Move::U]),
+    (
+        Trigger::LeftSledgehammer,
+        &[Move::L, Move::Fp, Move::Lp, Move::F],
+    ),
+    (Trigger::RightInsert, &[Move::R, Move::U, Move::Rp]),
+    (Trigger::RightPair, &[Move::R, Move::Up, Move::Rp]),
+    (Trigger::RightDouble, &[Move::R, Move::U2, Move::Rp]),
+    (Trigger::LeftInsert, &[Move::Lp, Move::Up, Move::L]),
+    (Trigger::LeftPair, &[Move::Lp, Move::U, Move::L]),
+    (Trigger::LeftDouble, &[Move::Lp, Move::U2, Move::L]),
+];
+
+impl FingertrickAnalysis {
+    /// Annotates the moves of an algorithm performed on the given last layer
+    /// face. `moves` must contain only the moves of the algorithm, with times
+    /// relative to any point in the solve.
+    pub fn analyze(moves: &[TimedMove], last_layer: CubeFace, start_move_index: usize) -> Self {
+        // Look at the moves with the last layer on top, so that triggers can
+        // be matched regardless of the cross color
+        let oriented: Vec<Option<Move>> = moves
+            .iter()
+            .map(|mv| {
+                let mv = mv.move_();
+                if mv.width() != 1 {
+                    return None;
+                }
+                Move::from_face_and_rotation(
+                    Self::face_with_last_layer_on_top(mv.face(), last_layer),
+                    mv.rotation(),
+                )
+            })
+            .collect();
+
+        // Greedily match the longest trigger at each position
+        let mut triggers = Vec::new();
+        let mut idx = 0;
+        while idx < oriented.len() {
+            let found = TRIGGERS.iter().find(|(_, pattern)| {
+                idx + pattern.len() <= oriented.len()
+                    && pattern
+                        .iter()
+                        .zip(&oriented[idx..])
+                        .all(|(expected, actual)| Some(*expected) == *actual)
+            });
+            if let Some((trigger, pattern)) = found {
+                triggers.push(TriggerSegment {
+                    trigger: *trigger,
+                    start_move_index: idx,
+                    move_count: pattern.len(),
+                });
+                idx += pattern.len();
+            } else {
+                idx += 1;
+            }
+        }
+
+        // Regrips show up as pauses that are much longer than the typical
+        // pause between moves of the algorithm
+        let pauses: Vec<u32> = moves
+            .windows(2)
+            .map(|pair| pair[1].time().saturating_sub(pair[0].time()))
+            .collect();
+        let mut regrips = Vec::new();
+        if pauses.len() != 0 {
+            let mut sorted = pauses.clone();
+            sorted.sort_unstable();
+            let threshold = (sorted[sorted.len() / 2] * REGRIP_PAUSE_FACTOR).max(MIN_REGRIP_PAUSE);
+            for (idx, pause) in pauses.iter().enumerate() {
+                if *pause >= threshold {
+                    regrips.push(Regrip {
+                        move_index: idx + 1,
+                        pause: *pause,
+                    });
+                }
+            }
+        }
+
+        Self {
+            start_move_index,
+            moves: moves.iter().map(|mv| mv.move_()).collect(),
+            triggers,
+            regrips,
+        }
+    }
+
+    /// Face that `face` is on when the cube is rotated to place the last
+    /// layer on top. This uses the same rotations as `AlgorithmExecution`.
+    fn face_with_last_layer_on_top(face: CubeFace, last_layer: CubeFace) -> CubeFace {
+        let rotation: [CubeFace; 6] = match last_layer {
+            CubeFace::Top => return face,
+            // Indexed by face: Top, Front, Right, Back, Left, Bottom
+            CubeFace::Bottom => [
+                CubeFace::Bottom,
+                CubeFace::Front,
+                CubeFace::Left,
+                CubeFace::Back,
+                CubeFace::Right,
+                CubeFace::Top,
+            ],
+            CubeFace::Right => [
+                CubeFace::Left,
+                CubeFace::Front,
+                CubeFace::Top,
+                CubeFace::Back,
+                CubeFace::Bottom,
+                CubeFace::Right,
+            ],
+            CubeFace::Left => [
+                CubeFace::Right,
+                CubeFace::Front,
+                CubeFace::Bottom,
+                CubeFace::Back,
+                CubeFace::Top,
+                CubeFace::Left,
+            ],
+            CubeFace::Front => [
+                CubeFace::Back,
+                CubeFace::Top,
+                CubeFace::Right,
+                CubeFace::Bottom,
+                CubeFace::Left,
+                CubeFace::Front,
+            ],
+            CubeFace::Back => [
+                CubeFace::Front,
+                CubeFace::Bottom,
+                CubeFace::Right,
+                CubeFace::Top,
+                CubeFace::Left,
+                CubeFace::Back,
+            ],
+        };
+        rotation[face as u8 as usize]
+    }
+
+    /// Hand that performed most of the triggers, if any were found
+    pub fn trigger_hand(&self) -> Option<AlgorithmHand> {
+        let left = self
+            .triggers
+            .iter()
+            .filter(|segment| segment.trigger.is_left_handed())
+            .count();
+        let right = self.triggers.len() - left;
+        if self.triggers.len() == 0 {
+            None
+        } else if right > left {
+            Some(AlgorithmHand::Right)
+        } else if left > right {
+            Some(AlgorithmHand::Left)
+        } else {
+            Some(AlgorithmHand::Mixed)
+        }
+    }
+
+    /// Describes the triggers and regrips of the algorithm, one per line
+    pub fn description(&self) -> String {
+        let mut lines = Vec::new();
+        for segment in &self.triggers {
+            let moves: Vec<String> = self.moves
+                [segment.start_move_index..segment.start_move_index + segment.move_count]
+                .iter()
+                .map(|mv| mv.to_string())
+                .collect();
+            lines.push(format!("{}: {}", segment.trigger.to_str(), moves.join(" ")));
+        }
+        for regrip in &self.regrips {
+            lines.push(format!(
+                "Regrip before {} (move {}, {:.2}s pause)",
+                self.moves[regrip.move_index].to_string(),
+                regrip.move_index + 1,
+                regrip.pause as f32 / 1000.0
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Trigger {
+    pub fn is_left_handed(&self) -> bool {
+        match self {
+            Trigger::LeftSexy
+            | Trigger::LeftSledgehammer
+            | Trigger::LeftInsert
+            | Trigger::LeftPair
+            | Trigger::LeftDouble => true,
+            _ => false,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Trigger::Sexy => "Sexy move",
+            Trigger::Sledgehammer => "Sledgehammer",
+            Trigger::Hedgeslammer => "Hedgeslammer",
+            Trigger::RightInsert => "Right insert",
+            Trigger::RightPair => "Right pair",
+            Trigger::RightDouble => "Right double",
+            Trigger::LeftSexy => "Left sexy move",
+            Trigger::LeftSledgehammer => "Left sledgehammer",
+            Trigger::LeftInsert => "Left insert",
+            Trigger::LeftPair => "Left pair",
+            Trigger::LeftDouble => "Left double",
+        }
+    }
+}
+
+impl CFOPAnalysis {
+    /// Fingertrick annotations of each OLL and PLL algorithm of the solve, in
+    /// solve order. The solution must be the one that was analyzed.
+    pub fn last_layer_fingertricks(&self, solution: &[TimedMove]) -> Vec<FingertrickAnalysis> {
+        let last_layer = self.cross.color.face().opposite();
+        let mut result = Vec::new();
+        let ranges = self
+            .oll
+            .iter()
+            .map(|oll| (oll.start_move_index, oll.moves.len()))
+            .chain(
+                self.pll
+                    .iter()
+                    .map(|pll| (pll.start_move_index, pll.moves.len())),
+            );
+        for (start, count) in ranges {
+            if count == 0 || start + count > solution.len() {
+                continue;
+            }
+            result.push(FingertrickAnalysis::analyze(
+                &solution[start..start + count],
+                last_layer,
+                start,
+            ));
+        }
+        result
+    }
+}
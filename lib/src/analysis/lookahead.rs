@@ -0,0 +1,85 @@
+use crate::{CFOPAnalysis, TimedMove};
+
+/// Pause between the last move of one step of a solve and the first move of
+/// the next step. Long pauses at these transitions indicate that the solver
+/// was not looking ahead to the next step while executing the previous one.
+#[derive(Clone)]
+pub struct TransitionPause {
+    /// Short name of the step that begins after the pause
+    pub step: &'static str,
+    /// Move index of the first move after the pause
+    pub move_index: usize,
+    /// Length of the pause
+    pub time: u32,
+}
+
+/// Analysis of the pauses at step and F2L pair boundaries within a solve
+#[derive(Clone)]
+pub struct LookaheadAnalysis {
+    /// Pauses at each step transition, in solve order
+    pub pauses: Vec<TransitionPause>,
+    /// Time from the start of the solve to the last move
+    pub total_time: u32,
+}
+
+impl LookaheadAnalysis {
+    pub fn analyze(analysis: &CFOPAnalysis, solution: &[TimedMove]) -> Self {
+        let mut boundaries = Vec::new();
+        for pair in &analysis.f2l_pairs {
+            boundaries.push(("Pair", pair.start_move_index));
+        }
+        for oll in &analysis.oll {
+            boundaries.push(("OLL", oll.start_move_index));
+        }
+        for pll in &analysis.pll {
+            boundaries.push(("PLL", pll.start_move_index));
+        }
+        if analysis.alignment.moves.len() != 0 {
+            boundaries.push(("Align", analysis.alignment.start_move_index));
+        }
+
+        // The pause is the gap between the first move of the step and the move
+        // before it. The cross is excluded, as the time before it is inspection.
+        let mut pauses = Vec::new();
+        for (step, move_index) in boundaries {
+            if move_index == 0 || move_index >= solution.len() {
+                continue;
+            }
+            let prev = solution[move_index - 1].time();
+            let next = solution[move_index].time();
+            pauses.push(TransitionPause {
+                step,
+                move_index,
+                time: next.saturating_sub(prev),
+            });
+        }
+
+        Self {
+            pauses,
+            total_time: solution.last().map(|mv| mv.time()).unwrap_or(0),
+        }
+    }
+
+    pub fn total_pause_time(&self) -> u32 {
+        self.pauses.iter().fold(0, |sum, pause| sum + pause.time)
+    }
+
+    pub fn longest_pause(&self) -> Option<&TransitionPause> {
+        self.pauses.iter().max_by_key(|pause| pause.time)
+    }
+
+    /// Lookahead score from 0 to 100. This is the percentage of the solve that
+    /// was spent outside of pauses at step transitions, so a perfectly fluid
+    /// solve with no pauses has a score of 100.
+    pub fn score(&self) -> f32 {
+        lookahead_score(self.total_pause_time() as u64, self.total_time as u64)
+    }
+}
+
+pub(crate) fn lookahead_score(pause_time: u64, total_time: u64) -> f32 {
+    if total_time == 0 {
+        100.0
+    } else {
+        100.0 - (pause_time.min(total_time) as f32 * 100.0 / total_time as f32)
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{Cube, Cube4x4x4, TimedMove};
+
+/// Maximum number of outer layer moves between the wide moves of a single
+/// parity algorithm, and after the last wide move before the cube is reduced
+const MAX_OUTER_MOVE_GAP: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParityType {
+    OLL,
+    PLL,
+}
+
+/// Execution of a parity algorithm within a 4x4x4 solve
+#[derive(Clone)]
+pub struct ParityAlgorithm {
+    pub parity: ParityType,
+    /// Index of the first move of the algorithm in the solution
+    pub start_move_index: usize,
+    /// Index after the last move of the algorithm in the solution
+    pub end_move_index: usize,
+    /// Time between the previous move and the start of the algorithm
+    pub recognition_time: u32,
+    /// Time from the first move to the last move of the algorithm
+    pub execution_time: u32,
+}
+
+/// Analysis of parity occurrence in a 4x4x4 solve. Parity algorithms are found
+/// by looking for groups of wide moves that start and end on a reduced cube
+/// and change its parity state.
+#[derive(Clone)]
+pub struct ParityAnalysis {
+    pub algorithms: Vec<ParityAlgorithm>,
+}
+
+impl ParityAnalysis {
+    pub fn analyze(initial_state: &Cube4x4x4, solution: &[TimedMove]) -> Self {
+        // Get the cube state before each move of the solution
+        let mut states = Vec::with_capacity(solution.len() + 1);
+        let mut cube = initial_state.clone();
+        states.push(cube.clone());
+        for mv in solution {
+            cube.do_move(mv.move_());
+            states.push(cube.clone());
+        }
+
+        // Group wide moves that are close together into candidate algorithms
+        let wide_moves: Vec<usize> = solution
+            .iter()
+            .enumerate()
+            .filter(|(_, mv)| mv.move_().width() > 1)
+            .map(|(i, _)| i)
+            .collect();
+        let mut groups: Vec<(usize, usize)> = Vec::new();
+        for i in wide_moves {
+            match groups.last_mut() {
+                Some((_, last)) if i - *last <= MAX_OUTER_MOVE_GAP + 1 => *last = i,
+                _ => groups.push((i, i)),
+            }
+        }
+
+        let mut algorithms = Vec::new();
+        for (first, last) in groups {
+            // Parity can only be determined on a reduced cube, so the group must
+            // start on one and end on one shortly after the last wide move
+            let before = &states[first];
+            if !before.is_reduced() {
+                continue;
+            }
+            let end = match (last + 1..=(last + 1 + MAX_OUTER_MOVE_GAP).min(solution.len()))
+                .find(|i| states[*i].is_reduced())
+            {
+                Some(end) => end,
+                None => continue,
+            };
+            let after = &states[end];
+
+            let parity = if before.oll_parity() != after.oll_parity() {
+                ParityType::OLL
+            } else if before.pll_parity() != after.pll_parity() {
+                ParityType::PLL
+            } else {
+                continue;
+            };
+
+            let start_time = solution[first].time();
+            let recognition_time = if first == 0 {
+                0
+            } else {
+                start_time.saturating_sub(solution[first - 1].time())
+            };
+            algorithms.push(ParityAlgorithm {
+                parity,
+                start_move_index: first,
+                end_move_index: end,
+                recognition_time,
+                execution_time: solution[end - 1].time().saturating_sub(start_time),
+            });
+        }
+
+        Self { algorithms }
+    }
+
+    pub fn oll_parity(&self) -> bool {
+        self.algorithms
+            .iter()
+            .any(|alg| alg.parity == ParityType::OLL)
+    }
+
+    pub fn pll_parity(&self) -> bool {
+        self.algorithms
+            .iter()
+            .any(|alg| alg.parity == ParityType::PLL)
+    }
+
+    /// Total time spent recognizing and executing parity algorithms
+    pub fn time_cost(&self) -> u32 {
+        self.algorithms.iter().fold(0, |sum, alg| {
+            sum + alg.recognition_time + alg.execution_time
+        })
+    }
+}
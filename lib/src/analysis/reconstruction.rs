@@ -0,0 +1,84 @@
+use crate::{Analysis, AnalysisSummary, CubeWithSolution, MoveSequence, Solve, TimedMove};
+
+impl CubeWithSolution {
+    /// Formats the solution as reconstruction text. Each step of the analysis
+    /// is placed on its own line with a comment giving the step name, time
+    /// and move count. Moves that are not part of a step, such as moves of a
+    /// solve that could not be analyzed, are listed on a final line.
+    pub fn reconstruction_text(&self, analysis: &Analysis) -> String {
+        let mut lines = Vec::new();
+        let mut remaining: &[TimedMove] = &self.solution;
+        for step in analysis.detailed_step_summary() {
+            if step.move_count == 0 || step.move_count > remaining.len() {
+                continue;
+            }
+            let (moves, rest) = remaining.split_at(step.move_count);
+            remaining = rest;
+
+            let name = match &step.algorithm {
+                Some(algorithm) => format!("{} ({})", step.name, algorithm),
+                None => step.name.clone(),
+            };
+            lines.push(format!(
+                "{} // {}, {}, {}",
+                move_string(moves),
+                name,
+                seconds_string(step.recognition_time + step.execution_time),
+                move_count_string(step.move_count)
+            ));
+        }
+        if remaining.len() != 0 {
+            lines.push(move_string(remaining));
+        }
+
+        let time = self.solution.last().map(|mv| mv.time()).unwrap_or(0);
+        let mut summary = format!(
+            "// {}, {}",
+            seconds_string(time),
+            move_count_string(self.solution.len())
+        );
+        if time > 0 {
+            summary += &format!(
+                ", {:.2} TPS",
+                self.solution.len() as f32 * 1000.0 / time as f32
+            );
+        }
+        lines.push(summary);
+        lines.join("\n")
+    }
+}
+
+impl Solve {
+    /// Reconstruction of the solve for sharing, with the scramble followed by
+    /// the moves of each step. Returns `None` if no moves were recorded.
+    pub fn reconstruction_text(&self) -> Option<String> {
+        let solve: Option<CubeWithSolution> = self.into();
+        let solve = solve?;
+        if solve.solution.len() == 0 {
+            return None;
+        }
+        let analysis = Analysis::analyze_with_solve_type(&solve, self.solve_type);
+        Some(format!(
+            "{} // Scramble\n\n{}",
+            self.scramble.to_string(),
+            solve.reconstruction_text(&analysis)
+        ))
+    }
+}
+
+fn move_string(moves: &[TimedMove]) -> String {
+    let moves: Vec<String> = moves.iter().map(|mv| mv.move_().to_string()).collect();
+    moves.join(" ")
+}
+
+fn move_count_string(count: usize) -> String {
+    if count == 1 {
+        "1 move".into()
+    } else {
+        format!("{} moves", count)
+    }
+}
+
+fn seconds_string(time: u32) -> String {
+    format!("{:.2}s", time as f32 / 1000.0)
+}
@@ -0,0 +1,280 @@
+use crate::analysis::lookahead::lookahead_score;
+use crate::{
+    Analysis, AnalysisSummary, CubeWithSolution, LookaheadAnalysis, OLLAlgorithm, PLLAlgorithm,
+    SkippedStep, Solve, TimedMove,
+};
+use std::collections::HashMap;
+
+/// Aggregate analysis statistics across a set of solves, such as the solves
+/// in a session. Only solves with move data that could be successfully
+/// analyzed are counted.
+#[derive(Clone, Default)]
+pub struct SessionAnalysis {
+    /// Number of solves that were successfully analyzed
+    pub analyzed_count: usize,
+    /// Number of analyzed solves where one or more F2L pairs were solved
+    /// along with the cross
+    pub xcross_count: usize,
+    /// Number of analyzed solves where one or more F2L pairs were skipped
+    pub f2l_skip_count: usize,
+    /// Number of analyzed solves where OLL was skipped
+    pub oll_skip_count: usize,
+    /// Number of analyzed solves where PLL was skipped
+    pub pll_skip_count: usize,
+    /// Number of analyzed solves where any step was skipped
+    pub any_skip_count: usize,
+    /// Total time spent in pauses at step transitions
+    pub total_pause_time: u64,
+    /// Total time of all analyzed solves
+    pub total_time: u64,
+    /// Total time of each major step and number of solves that contained
+    /// the step, indexed by the major step index of the step summary
+    pub step_totals: Vec<(u64, usize)>,
+    /// Last layer adjustments for each OLL case that was performed
+    pub oll_aufs: HashMap<OLLAlgorithm, AUFStats>,
+    /// Last layer adjustments for each PLL case that was performed. The final
+    /// alignment of the last layer counts towards the last PLL of the solve.
+    pub pll_aufs: HashMap<PLLAlgorithm, AUFStats>,
+    /// Time spent on each PLL case that was performed
+    pub pll_times: HashMap<PLLAlgorithm, PLLCaseStats>,
+}
+
+/// Count of last layer adjustments (AUFs) made around an algorithm
+#[derive(Clone, Copy, Default)]
+pub struct AUFStats {
+    /// Number of times the algorithm was performed
+    pub count: usize,
+    /// Number of times the last layer was adjusted before the algorithm
+    pub pre_aufs: usize,
+    /// Number of times the last layer was adjusted after the algorithm
+    pub post_aufs: usize,
+}
+
+/// Time spent on a PLL case across the analyzed solves
+#[derive(Clone, Copy, Default)]
+pub struct PLLCaseStats {
+    /// Number of times the case was performed
+    pub count: usize,
+    pub total_recognition_time: u64,
+    pub total_execution_time: u64,
+    /// Longest recognition and execution time of a single occurrence
+    pub worst_time: u32,
+}
+
+impl AUFStats {
+    fn add(&mut self, pre_auf: bool, post_auf: bool) {
+        self.count += 1;
+        if pre_auf {
+            self.pre_aufs += 1;
+        }
+        if post_auf {
+            self.post_aufs += 1;
+        }
+    }
+
+    /// Average number of adjustments made each time the algorithm was performed
+    pub fn average_aufs(&self) -> f32 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.pre_aufs + self.post_aufs) as f32 / self.count as f32
+        }
+    }
+}
+
+impl PLLCaseStats {
+    fn add(&mut self, recognition_time: u32, execution_time: u32) {
+        self.count += 1;
+        self.total_recognition_time += recognition_time as u64;
+        self.total_execution_time += execution_time as u64;
+        self.worst_time = self.worst_time.max(recognition_time + execution_time);
+    }
+
+    fn average(&self, total: u64) -> u32 {
+        if self.count == 0 {
+            0
+        } else {
+            ((total + self.count as u64 / 2) / self.count as u64) as u32
+        }
+    }
+
+    pub fn average_recognition_time(&self) -> u32 {
+        self.average(self.total_recognition_time)
+    }
+
+    pub fn average_execution_time(&self) -> u32 {
+        self.average(self.total_execution_time)
+    }
+
+    pub fn average_time(&self) -> u32 {
+        self.average(self.total_recognition_time + self.total_execution_time)
+    }
+
+    /// Total time spent on the case across all occurrences
+    pub fn total_time(&self) -> u64 {
+        self.total_recognition_time + self.total_execution_time
+    }
+}
+
+impl SessionAnalysis {
+    pub fn analyze(solves: &[Solve]) -> Self {
+        let mut result = Self::default();
+        for solve in solves {
+            if !solve.solve_type.is_3x3x3() {
+                continue;
+            }
+            let solve_type = solve.solve_type;
+            let solve: Option<CubeWithSolution> = solve.into();
+            if let Some(solve) = solve {
+                let analysis = Analysis::analyze_with_solve_type(&solve, solve_type);
+                result.add_analysis(&analysis, &solve.solution);
+            }
+        }
+        result
+    }
+
+    /// Adds the analysis of a single solve to the statistics. Analyses that
+    /// were unsuccessful are ignored.
+    pub fn add_analysis(&mut self, analysis: &Analysis, solution: &[TimedMove]) {
+        match analysis {
+            // Step statistics are only gathered for CFOP solves, the steps of
+            // other methods are not comparable
+            Analysis::Unsuccessful | Analysis::Blind(_) => (),
+            Analysis::CFOP(cfop) => {
+                self.analyzed_count += 1;
+                if cfop.cross.is_extended() {
+                    self.xcross_count += 1;
+                }
+
+                let skips = cfop.skips();
+                if skips.f2l_pairs > 0 {
+                    self.f2l_skip_count += 1;
+                }
+                if skips.oll {
+                    self.oll_skip_count += 1;
+                }
+                if skips.pll {
+                    self.pll_skip_count += 1;
+                }
+                if skips.any() {
+                    self.any_skip_count += 1;
+                }
+
+                let lookahead = LookaheadAnalysis::analyze(cfop, solution);
+                self.total_pause_time += lookahead.total_pause_time() as u64;
+                self.total_time += lookahead.total_time as u64;
+
+                let mut steps_in_solve = Vec::new();
+                for step in analysis.step_summary() {
+                    if self.step_totals.len() <= step.major_step_index {
+                        self.step_totals.resize(step.major_step_index + 1, (0, 0));
+                    }
+                    let total = &mut self.step_totals[step.major_step_index];
+                    total.0 += (step.recognition_time + step.execution_time) as u64;
+                    if !steps_in_solve.contains(&step.major_step_index) {
+                        steps_in_solve.push(step.major_step_index);
+                        total.1 += 1;
+                    }
+                }
+
+                for oll in &cfop.oll {
+                    self.oll_aufs
+                        .entry(oll.performed_algorithm)
+                        .or_default()
+                        .add(
+                            oll.execution.pre_auf.is_some(),
+                            oll.execution.post_auf.is_some(),
+                        );
+                }
+                for (idx, pll) in cfop.pll.iter().enumerate() {
+                    let final_alignment =
+                        idx + 1 == cfop.pll.len() && cfop.alignment.moves.len() != 0;
+                    self.pll_aufs
+                        .entry(pll.performed_algorithm)
+                        .or_default()
+                        .add(
+                            pll.execution.pre_auf.is_some(),
+                            pll.execution.post_auf.is_some() || final_alignment,
+                        );
+                    self.pll_times
+                        .entry(pll.performed_algorithm)
+                        .or_default()
+                        .add(pll.recognition_time, pll.execution_time);
+                }
+            }
+        }
+    }
+
+    /// Fraction of analyzed solves that had an extended cross, from 0 to 1
+    pub fn xcross_rate(&self) -> Option<f32> {
+        if self.analyzed_count == 0 {
+            None
+        } else {
+            Some(self.xcross_count as f32 / self.analyzed_count as f32)
+        }
+    }
+
+    /// Fraction of analyzed solves where the given step was skipped, from 0 to 1
+    pub fn skip_rate(&self, step: SkippedStep) -> Option<f32> {
+        if self.analyzed_count == 0 {
+            return None;
+        }
+        let count = match step {
+            SkippedStep::F2LPair => self.f2l_skip_count,
+            SkippedStep::OLL => self.oll_skip_count,
+            SkippedStep::PLL => self.pll_skip_count,
+            SkippedStep::Any => self.any_skip_count,
+        };
+        Some(count as f32 / self.analyzed_count as f32)
+    }
+
+    /// Average time of solves for a major step. Steps that occur multiple times
+    /// in a solve, such as F2L pairs, are averaged per solve. Solves where the
+    /// step was skipped are not counted.
+    pub fn average_step_time(&self, major_step_index: usize) -> Option<u32> {
+        match self.step_totals.get(major_step_index) {
+            Some((total, count)) if *count > 0 => {
+                Some(((*total + *count as u64 / 2) / *count as u64) as u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Average number of last layer adjustments made per OLL and PLL algorithm
+    /// across all analyzed solves
+    pub fn average_aufs(&self) -> Option<f32> {
+        let mut count = 0;
+        let mut aufs = 0;
+        for stats in self.oll_aufs.values().chain(self.pll_aufs.values()) {
+            count += stats.count;
+            aufs += stats.pre_aufs + stats.post_aufs;
+        }
+        if count == 0 {
+            None
+        } else {
+            Some(aufs as f32 / count as f32)
+        }
+    }
+
+    /// Lookahead score across all analyzed solves, from 0 to 100. See
+    /// `LookaheadAnalysis::score` for details.
+    pub fn lookahead_score(&self) -> Option<f32> {
+        if self.analyzed_count == 0 {
+            None
+        } else {
+            Some(lookahead_score(self.total_pause_time, self.total_time))
+        }
+    }
+
+    /// Time spent on each PLL case, with the cases that cost the most total
+    /// time first
+    pub fn pll_case_breakdown(&self) -> Vec<(PLLAlgorithm, PLLCaseStats)> {
+        let mut result: Vec<(PLLAlgorithm, PLLCaseStats)> = self
+            .pll_times
+            .iter()
+            .map(|(algorithm, stats)| (*algorithm, *stats))
+            .collect();
+        result.sort_by(|a, b| b.1.total_time().cmp(&a.1.total_time()).then(a.0.cmp(&b.0)));
+        result
+    }
+}
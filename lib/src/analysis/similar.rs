@@ -0,0 +1,169 @@
+use crate::{
+    Analysis, AnalysisSummary, CFOPAnalysis, OLLAlgorithm, PLLAlgorithm, Solve, SolveAnalysis,
+};
+use std::cmp::Ordering;
+
+/// Largest difference in cross length, in moves, for solves to be considered
+/// to have a similar scramble difficulty
+const MAX_DIFFICULTY_DISTANCE: f32 = 1.0;
+
+/// How solves are compared when searching for similar solves
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SimilarityCriteria {
+    /// Solves that started the last layer with the same OLL case
+    OLLCase,
+    /// Solves that had the same PLL case
+    PLLCase,
+    /// Solves with a cross of about the same length and the same number of
+    /// extended pairs. There is no cross solver, so the cross that was
+    /// actually solved is used as a measure of how hard the scramble was.
+    ScrambleDifficulty,
+    /// Solves that spent a similar share of their time on each step
+    StepProfile,
+}
+
+/// Features of a CFOP solve used to compare it to other solves
+#[derive(Clone, PartialEq)]
+pub struct SolveProfile {
+    /// First OLL case of the solve, or `None` if OLL was skipped
+    pub oll_case: Option<OLLAlgorithm>,
+    /// First PLL case of the solve, or `None` if PLL was skipped
+    pub pll_case: Option<PLLAlgorithm>,
+    pub cross_moves: usize,
+    pub extended_pairs: usize,
+    /// Fraction of the solve time spent on each major step, indexed by the
+    /// major step index of the step summary
+    pub step_fractions: Vec<f32>,
+}
+
+/// Solve found by `find_similar_solves`
+#[derive(Clone)]
+pub struct SimilarSolve<'a> {
+    pub solve: &'a Solve,
+    /// How different the solve is from the one searched for, where zero is
+    /// the closest match. Distances are only comparable between solves found
+    /// with the same criteria.
+    pub distance: f32,
+}
+
+impl SolveProfile {
+    pub fn new(cfop: &CFOPAnalysis) -> Self {
+        let mut step_times: Vec<u32> = Vec::new();
+        for step in cfop.step_summary() {
+            if step_times.len() <= step.major_step_index {
+                step_times.resize(step.major_step_index + 1, 0);
+            }
+            step_times[step.major_step_index] += step.recognition_time + step.execution_time;
+        }
+        let total: u32 = step_times.iter().sum();
+        let step_fractions = step_times
+            .iter()
+            .map(|time| {
+                if total == 0 {
+                    0.0
+                } else {
+                    *time as f32 / total as f32
+                }
+            })
+            .collect();
+
+        Self {
+            oll_case: cfop.oll.first().map(|oll| oll.one_look_algorithm),
+            pll_case: cfop.pll.first().map(|pll| pll.one_look_algorithm),
+            cross_moves: cfop.cross.moves.len(),
+            extended_pairs: cfop.cross.extended_pairs,
+            step_fractions,
+        }
+    }
+
+    /// Analyzes a solve to get its profile. Returns `None` if the solve does
+    /// not have move data or was not solved with CFOP.
+    pub fn from_solve(solve: &Solve) -> Option<Self> {
+        if !solve.solve_type.is_3x3x3() {
+            return None;
+        }
+        match solve.analyze() {
+            Analysis::CFOP(cfop) => Some(Self::new(&cfop)),
+            _ => None,
+        }
+    }
+
+    /// Distance between two profiles for the given criteria. Returns `None`
+    /// if the solves are not similar at all, such as solves with different
+    /// last layer cases.
+    pub fn distance(&self, other: &SolveProfile, criteria: SimilarityCriteria) -> Option<f32> {
+        match criteria {
+            SimilarityCriteria::OLLCase => match (self.oll_case, other.oll_case) {
+                (Some(case), Some(other_case)) if case == other_case => Some(0.0),
+                _ => None,
+            },
+            SimilarityCriteria::PLLCase => match (self.pll_case, other.pll_case) {
+                (Some(case), Some(other_case)) if case == other_case => Some(0.0),
+                _ => None,
+            },
+            SimilarityCriteria::ScrambleDifficulty => {
+                if self.extended_pairs != other.extended_pairs {
+                    return None;
+                }
+                let distance = (self.cross_moves as f32 - other.cross_moves as f32).abs();
+                if distance <= MAX_DIFFICULTY_DISTANCE {
+                    Some(distance)
+                } else {
+                    None
+                }
+            }
+            SimilarityCriteria::StepProfile => {
+                if self.step_fractions.len() != other.step_fractions.len() {
+                    return None;
+                }
+                Some(
+                    self.step_fractions
+                        .iter()
+                        .zip(other.step_fractions.iter())
+                        .map(|(fraction, other_fraction)| (fraction - other_fraction).abs())
+                        .sum(),
+                )
+            }
+        }
+    }
+}
+
+/// Searches a set of solves, such as the solves of every session, for solves
+/// similar to `target`. Only solves of the same puzzle type with move data
+/// are considered. The closest matches are returned first, with ties broken
+/// by the newest solve. Every solve is analyzed, so this should only be used
+/// on request and not every frame.
+pub fn find_similar_solves<'a, I: Iterator<Item = &'a Solve>>(
+    target: &Solve,
+    solves: I,
+    criteria: SimilarityCriteria,
+    limit: usize,
+) -> Vec<SimilarSolve<'a>> {
+    let profile = match SolveProfile::from_solve(target) {
+        Some(profile) => profile,
+        None => return Vec::new(),
+    };
+
+    let mut result = Vec::new();
+    for solve in solves {
+        if solve.id == target.id || solve.solve_type != target.solve_type || solve.moves.is_none() {
+            continue;
+        }
+        let distance = match SolveProfile::from_solve(solve) {
+            Some(other) => profile.distance(&other, criteria),
+            None => None,
+        };
+        if let Some(distance) = distance {
+            result.push(SimilarSolve { solve, distance });
+        }
+    }
+
+    result.sort_by(|a, b| {
+        a.distance
+            .partial_cmp(&b.distance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.solve.created.cmp(&a.solve.created))
+    });
+    result.truncate(limit);
+    result
+}
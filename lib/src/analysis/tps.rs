@@ -0,0 +1,101 @@
+use crate::{AnalysisStepSummary, TimedMove};
+
+/// Length of the trailing window used for rolling TPS, in milliseconds
+const TPS_ROLLING_WINDOW: u32 = 1000;
+
+/// Highest instantaneous TPS reported. Moves registered at nearly the same
+/// time, such as double turns on some smart cubes, would otherwise produce
+/// meaningless spikes.
+const MAX_INSTANTANEOUS_TPS: f32 = 20.0;
+
+/// Turns per second at the time of a move
+#[derive(Clone, Copy, Debug)]
+pub struct TPSSample {
+    /// Time of the move from the start of the solve
+    pub time: u32,
+    /// TPS from the pause since the previous move
+    pub instantaneous: f32,
+    /// TPS over the trailing rolling window
+    pub rolling: f32,
+}
+
+/// Average TPS of a step of the solve
+#[derive(Clone, Debug)]
+pub struct StepTPS {
+    pub name: String,
+    pub major_step_index: usize,
+    pub start_time: u32,
+    pub end_time: u32,
+    pub tps: f32,
+}
+
+/// Turns per second over the course of a single solve, with the average TPS
+/// of each analyzed step for overlaying on the curve.
+#[derive(Clone, Debug)]
+pub struct TPSCurve {
+    pub samples: Vec<TPSSample>,
+    pub steps: Vec<StepTPS>,
+}
+
+impl TPSCurve {
+    /// Computes the TPS curve of a solution. The step summary of the solve's
+    /// analysis is used for the step overlays and may be empty.
+    pub fn new(solution: &[TimedMove], summary: &[AnalysisStepSummary]) -> Self {
+        let mut samples = Vec::new();
+        let mut window_start = 0;
+        let mut prev_time = 0;
+        for (idx, mv) in solution.iter().enumerate() {
+            let time = mv.time();
+            let gap = time.saturating_sub(prev_time);
+            let instantaneous = if gap == 0 {
+                MAX_INSTANTANEOUS_TPS
+            } else {
+                (1000.0 / gap as f32).min(MAX_INSTANTANEOUS_TPS)
+            };
+            prev_time = time;
+
+            // Count the moves within the trailing window. Early in the solve
+            // the window is shortened to the time elapsed so far.
+            while solution[window_start].time() + TPS_ROLLING_WINDOW <= time {
+                window_start += 1;
+            }
+            let window = TPS_ROLLING_WINDOW.min(time).max(1);
+            let rolling = (idx + 1 - window_start) as f32 * 1000.0 / window as f32;
+
+            samples.push(TPSSample {
+                time,
+                instantaneous,
+                rolling: rolling.min(MAX_INSTANTANEOUS_TPS),
+            });
+        }
+
+        let mut steps = Vec::new();
+        let mut start_time = 0;
+        for step in summary {
+            let duration = step.recognition_time + step.execution_time;
+            steps.push(StepTPS {
+                name: step.short_name.clone(),
+                major_step_index: step.major_step_index,
+                start_time,
+                end_time: start_time + duration,
+                tps: if duration == 0 {
+                    0.0
+                } else {
+                    step.move_count as f32 * 1000.0 / duration as f32
+                },
+            });
+            start_time += duration;
+        }
+
+        Self { samples, steps }
+    }
+
+    /// Highest TPS in the curve or the step overlays
+    pub fn max_tps(&self) -> f32 {
+        self.samples
+            .iter()
+            .map(|sample| sample.rolling)
+            .chain(self.steps.iter().map(|step| step.tps))
+            .fold(0.0, f32::max)
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{Analysis, CFOPPartialAnalysis, CFOPProgress, Cube, CubeWithSolution};
+
+/// Problem found while analyzing a solve. Warnings explain why a solve could
+/// not be broken down into steps, or why the step summary may not match what
+/// was actually performed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnalysisWarning {
+    /// No moves were recorded for the solve
+    NoMoves,
+    /// The recorded moves do not solve the cube. This usually means the smart
+    /// cube lost track of its state during the solve.
+    NotSolved,
+    /// Moves were recorded after the cube was solved. The number of extra
+    /// moves is given.
+    MovesAfterSolved(usize),
+    /// No first layer cross was found in the solve
+    CrossNotDetected,
+    /// The cross was found but the first two layers were never completed
+    F2LNotDetected,
+    /// The first two layers were found but the last layer was not solved
+    /// with recognized algorithms
+    LastLayerNotDetected,
+}
+
+impl AnalysisWarning {
+    /// Checks a solve and its analysis for problems
+    pub(crate) fn check(solve: &CubeWithSolution, analysis: &Analysis) -> Vec<Self> {
+        if solve.solution.len() == 0 {
+            return vec![AnalysisWarning::NoMoves];
+        }
+
+        let mut warnings = Vec::new();
+
+        // Find the first point where the cube is solved
+        let mut cube = solve.initial_state.clone();
+        let mut solved_move_count = None;
+        for (idx, mv) in solve.solution.iter().enumerate() {
+            cube.do_move(mv.move_());
+            if solved_move_count.is_none() && cube.is_solved() {
+                solved_move_count = Some(idx + 1);
+            }
+        }
+        match solved_move_count {
+            None => warnings.push(AnalysisWarning::NotSolved),
+            Some(count) if count < solve.solution.len() => {
+                warnings.push(AnalysisWarning::MovesAfterSolved(
+                    solve.solution.len() - count,
+                ));
+            }
+            _ => (),
+        }
+
+        // If no method matched the solve, report how far CFOP analysis got
+        if !analysis.successful() {
+            match CFOPPartialAnalysis::analyze(solve).progress {
+                CFOPProgress::Initial => warnings.push(AnalysisWarning::CrossNotDetected),
+                CFOPProgress::F2LPair(_) => warnings.push(AnalysisWarning::F2LNotDetected),
+                CFOPProgress::OLL(_) | CFOPProgress::PLL(_) => {
+                    warnings.push(AnalysisWarning::LastLayerNotDetected)
+                }
+                CFOPProgress::FinalAlignment | CFOPProgress::Solved => (),
+            }
+        }
+
+        warnings
+    }
+}
+
+impl std::fmt::Display for AnalysisWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AnalysisWarning::NoMoves => write!(f, "No moves recorded"),
+            AnalysisWarning::NotSolved => write!(f, "Moves do not solve the cube"),
+            AnalysisWarning::MovesAfterSolved(1) => write!(f, "1 move after solved state"),
+            AnalysisWarning::MovesAfterSolved(count) => {
+                write!(f, "{} moves after solved state", count)
+            }
+            AnalysisWarning::CrossNotDetected => write!(f, "Cross not detected"),
+            AnalysisWarning::F2LNotDetected => write!(f, "First two layers not detected"),
+            AnalysisWarning::LastLayerNotDetected => write!(f, "Last layer not detected"),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{Analysis, AnalysisSummary, TimedMove};
+
+/// Hypothetical recomputation of a solve's time. This can be used to show how
+/// much time was lost to poor lookahead or to a slow step of the solve.
+#[derive(Clone, Copy)]
+pub struct WhatIfAnalysis {
+    /// Actual time of the solve from the start to the last move
+    pub actual_time: u32,
+    /// Time the solve would have taken under the hypothetical conditions
+    pub hypothetical_time: u32,
+}
+
+impl WhatIfAnalysis {
+    /// Recomputes the solve time with every pause between moves limited to
+    /// at most `max_pause` milliseconds.
+    pub fn without_pauses(solution: &[TimedMove], max_pause: u32) -> Self {
+        let mut prev_time = 0;
+        let mut hypothetical_time = 0;
+        for mv in solution {
+            let gap = mv.time().saturating_sub(prev_time);
+            hypothetical_time += gap.min(max_pause);
+            prev_time = mv.time();
+        }
+
+        Self {
+            actual_time: prev_time,
+            hypothetical_time,
+        }
+    }
+
+    /// Recomputes the solve time with all steps matching `major_step_index`
+    /// (as given in the step summary) replaced by a single step taking
+    /// `step_time` milliseconds, such as the solver's average for that step.
+    /// Returns `None` if the analysis was unsuccessful or the step was not
+    /// present in the solve.
+    pub fn with_step_time(
+        analysis: &Analysis,
+        major_step_index: usize,
+        step_time: u32,
+    ) -> Option<Self> {
+        let mut actual_time = 0;
+        let mut other_steps_time = 0;
+        let mut found = false;
+        for step in analysis.step_summary() {
+            let time = step.recognition_time + step.execution_time;
+            actual_time += time;
+            if step.major_step_index == major_step_index {
+                found = true;
+            } else {
+                other_steps_time += time;
+            }
+        }
+
+        if found {
+            Some(Self {
+                actual_time,
+                hypothetical_time: other_steps_time + step_time,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Time that would have been saved under the hypothetical conditions. This
+    /// is negative if the hypothetical solve is slower.
+    pub fn time_saved(&self) -> i64 {
+        self.actual_time as i64 - self.hypothetical_time as i64
+    }
+}
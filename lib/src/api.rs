@@ -0,0 +1,418 @@
+use crate::error::ErrorKind;
+use crate::lan_sync::DeadlineStream;
+use crate::{
+    parse_move_string, History, Penalty, PracticeStreak, Solve, SolveInput, SolveList, SolveType,
+    SyncStatus, SESSION_STATS_AVERAGE_SIZES,
+};
+use chrono::Local;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, ErrorKind as IoErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+
+#[cfg(not(feature = "no_solver"))]
+use crate::scramble_provider::{GeneratedScrambles, ScrambleProvider};
+#[cfg(not(feature = "no_solver"))]
+use crate::MoveSequence;
+
+/// TCP port used by the HTTP API by default
+pub const API_SERVER_PORT: u16 = 47864;
+
+/// Largest request body accepted by the HTTP API
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+/// Largest size of the request line and headers together
+const MAX_HEADER_SIZE: u64 = 16 * 1024;
+/// Longest time a client connection can take in total, from accepting it to
+/// the end of the response. Connections are handled one at a time, so this
+/// is kept short to stop a slow client from holding up every other client.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Name recorded as the input device of solves submitted over the API
+const API_DEVICE_NAME: &str = "HTTP API";
+
+/// Serves the solve history over HTTP for headless operation, such as kiosk
+/// setups or integrations with other programs. Requests and responses are
+/// JSON. The API has no authentication, so it should only be bound to the
+/// loopback address or a trusted network. Requests from web pages, which
+/// carry an `Origin` header, are rejected so that a site open in a browser
+/// can't use the API, and `POST` bodies must be sent as `application/json`.
+///
+/// - `GET /scramble?type=3x3x3` generates a scramble
+/// - `POST /solves` adds a solve to the current session of its puzzle. The
+///   body holds the `time` in milliseconds and optionally the `type`, the
+///   `penalty` in milliseconds or `"dnf"`, and the `scramble`.
+/// - `GET /stats?type=3x3x3` gives statistics for the current session, along
+///   with the practice streak across all puzzles
+/// - `POST /sync` starts a sync and `GET /sync` gives the sync status
+///
+/// Puzzle types use the names from `SolveType::to_string`, and default to
+/// 3x3x3.
+pub struct ApiServer {
+    listener: TcpListener,
+}
+
+/// Headers of an API request that are used by the server
+#[derive(Default)]
+pub(crate) struct ApiRequestHeaders {
+    pub content_length: usize,
+    pub content_type: Option<String>,
+    pub origin: Option<String>,
+}
+
+/// Response to an API request
+pub(crate) struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    /// Errors are sent in the same form as bad request messages from the
+    /// sync server
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": true, "message": message }),
+        }
+    }
+}
+
+impl ApiServer {
+    pub fn bind(address: SocketAddr) -> crate::Result<Self> {
+        let listener = TcpListener::bind(address).network_error()?;
+        listener.set_nonblocking(true).network_error()?;
+        Ok(Self { listener })
+    }
+
+    pub fn local_address(&self) -> crate::Result<SocketAddr> {
+        self.listener.local_addr().network_error()
+    }
+
+    /// Serves requests until an error occurs. Requests are handled one at a
+    /// time on the calling thread, which must be running within the async
+    /// runtime used for storage. Clients that do not finish their request
+    /// and receive the response within `CONNECTION_TIMEOUT` are disconnected.
+    /// Scheduled syncs are run while waiting for requests.
+    pub fn run(&self, history: &mut History) -> crate::Result<()> {
+        loop {
+            history.retry_sync_if_due();
+            history.check_sync_status();
+
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    // A failed connection only affects that client
+                    let _ = Self::handle_connection(history, stream);
+                }
+                Err(error) if error.kind() == IoErrorKind::WouldBlock => {
+                    std::thread::sleep(ACCEPT_POLL_INTERVAL)
+                }
+                Err(error) => return Err(error).network_error(),
+            }
+        }
+    }
+
+    fn handle_connection(history: &mut History, stream: TcpStream) -> anyhow::Result<()> {
+        stream.set_nonblocking(false)?;
+        let mut stream = DeadlineStream::new(stream, CONNECTION_TIMEOUT);
+
+        // Read the request line and headers, which together must fit within
+        // `MAX_HEADER_SIZE` so that a client can't send an endless line
+        let mut reader = BufReader::new(&mut stream);
+        let mut header_reader = (&mut reader).take(MAX_HEADER_SIZE);
+        let mut request_line = String::new();
+        header_reader.read_line(&mut request_line)?;
+        let mut headers = ApiRequestHeaders::default();
+        loop {
+            let mut line = String::new();
+            if header_reader.read_line(&mut line)? == 0 || line.trim().len() == 0 {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                let name = name.trim();
+                let value = value.trim();
+                if name.eq_ignore_ascii_case("content-length") {
+                    headers.content_length = value.parse()?;
+                } else if name.eq_ignore_ascii_case("content-type") {
+                    headers.content_type = Some(value.into());
+                } else if name.eq_ignore_ascii_case("origin") {
+                    headers.origin = Some(value.into());
+                }
+            }
+        }
+
+        let headers_too_large = header_reader.limit() == 0;
+
+        let mut parts = request_line.split_whitespace();
+        let response = match (parts.next(), parts.next()) {
+            _ if headers_too_large => ApiResponse::error(431, "Request headers too large"),
+            (Some(method), Some(target)) => match Self::check_headers(method, &headers) {
+                Some(response) => response,
+                None => {
+                    let mut body = vec![0; headers.content_length];
+                    reader.read_exact(&mut body)?;
+                    Self::handle(history, method, target, &String::from_utf8_lossy(&body))
+                }
+            },
+            _ => ApiResponse::error(400, "Malformed request"),
+        };
+
+        let body = response.body.to_string();
+        let reason = match response.status {
+            200 => "OK",
+            400 => "Bad Request",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            415 => "Unsupported Media Type",
+            431 => "Request Header Fields Too Large",
+            _ => "Error",
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+            Connection: close\r\n\r\n{}",
+            response.status,
+            reason,
+            body.len(),
+            body
+        )?;
+        Ok(())
+    }
+
+    /// Checks the headers of a request before its body is read, and gives the
+    /// response if the request is rejected. Browsers send an `Origin` header
+    /// with requests made by web pages. Requiring JSON bodies as well means a
+    /// page can't send a body at all without the browser first asking the
+    /// server for permission, which it never gives.
+    pub(crate) fn check_headers(method: &str, headers: &ApiRequestHeaders) -> Option<ApiResponse> {
+        if headers.origin.is_some() {
+            return Some(ApiResponse::error(
+                403,
+                "Requests from web pages are not allowed",
+            ));
+        }
+        if headers.content_length > MAX_BODY_SIZE {
+            return Some(ApiResponse::error(413, "Request too large"));
+        }
+        let is_json = headers.content_type.as_ref().map_or(false, |content_type| {
+            content_type
+                .split(';')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .eq_ignore_ascii_case("application/json")
+        });
+        if method == "POST"
+            && (headers.content_length > 0 || headers.content_type.is_some())
+            && !is_json
+        {
+            return Some(ApiResponse::error(415, "Request body must be JSON"));
+        }
+        None
+    }
+
+    /// Handles a single request. `target` is the request path with its query
+    /// string.
+    pub(crate) fn handle(
+        history: &mut History,
+        method: &str,
+        target: &str,
+        body: &str,
+    ) -> ApiResponse {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let solve_type = match query_parameter(query, "type") {
+            Some(name) => match SolveType::from_str(&name) {
+                Some(solve_type) => solve_type,
+                None => return ApiResponse::error(400, "Unknown puzzle type"),
+            },
+            None => SolveType::Standard3x3x3,
+        };
+
+        match (method, path.trim_end_matches('/')) {
+            ("GET", "/scramble") => Self::scramble(solve_type),
+            ("POST", "/solves") => match serde_json::from_str(body) {
+                Ok(body) => Self::new_solve(history, body),
+                Err(_) => ApiResponse::error(400, "Invalid JSON"),
+            },
+            ("GET", "/stats") => Self::stats(history, solve_type),
+            ("GET", "/sync") => Self::sync_status(history),
+            ("POST", "/sync") => {
+                let started = history.start_sync();
+                ApiResponse::ok(json!({ "started": started }))
+            }
+            (_, "/scramble") | (_, "/solves") | (_, "/stats") | (_, "/sync") => {
+                ApiResponse::error(405, "Method not allowed")
+            }
+            _ => ApiResponse::error(404, "Not found"),
+        }
+    }
+
+    #[cfg(not(feature = "no_solver"))]
+    fn scramble(solve_type: SolveType) -> ApiResponse {
+        match GeneratedScrambles.next_scramble(solve_type) {
+            Some(scramble) => ApiResponse::ok(json!({
+                "type": solve_type.to_string(),
+                "scramble": scramble.to_string(),
+            })),
+            None => ApiResponse::error(400, "Scrambles are not available for this puzzle"),
+        }
+    }
+
+    #[cfg(feature = "no_solver")]
+    fn scramble(_solve_type: SolveType) -> ApiResponse {
+        ApiResponse::error(404, "Scramble generation is not available in this build")
+    }
+
+    fn new_solve(history: &mut History, body: Value) -> ApiResponse {
+        let solve_type = match body.get("type").and_then(|name| name.as_str()) {
+            Some(name) => match SolveType::from_str(name) {
+                Some(solve_type) => solve_type,
+                None => return ApiResponse::error(400, "Unknown puzzle type"),
+            },
+            None => SolveType::Standard3x3x3,
+        };
+        let time = match body.get("time").and_then(|time| time.as_u64()) {
+            Some(time) if time <= u32::MAX as u64 => time as u32,
+            _ => return ApiResponse::error(400, "Solve time in milliseconds is required"),
+        };
+        let penalty = match body.get("penalty") {
+            None | Some(Value::Null) => Penalty::None,
+            Some(Value::String(penalty)) if penalty.eq_ignore_ascii_case("dnf") => Penalty::DNF,
+            Some(penalty) => match penalty.as_u64() {
+                Some(0) => Penalty::None,
+                Some(penalty) if penalty <= u32::MAX as u64 => Penalty::Time(penalty as u32),
+                _ => return ApiResponse::error(400, "Invalid penalty"),
+            },
+        };
+        let scramble = match body.get("scramble").and_then(|scramble| scramble.as_str()) {
+            Some(scramble) => match parse_move_string(scramble) {
+                Ok(scramble) => scramble,
+                Err(_) => return ApiResponse::error(400, "Invalid scramble"),
+            },
+            None => Vec::new(),
+        };
+
+        // Solves go into the current session of the puzzle, as with solves
+        // from the timer
+        let session = history.select_solve_type(solve_type);
+        let id = Solve::new_id();
+        history.new_solve(Solve {
+            id: id.clone(),
+            solve_type,
+            session: session.clone(),
+            scramble,
+            created: Local::now(),
+            time,
+            penalty,
+            device: Some(SolveInput::Named(API_DEVICE_NAME.into()).to_device_string()),
+            participant: history.next_participant(),
+            moves: None,
+            skips: None,
+        });
+        history.local_commit();
+        ApiResponse::ok(json!({ "id": id, "session": session }))
+    }
+
+    fn stats(history: &mut History, solve_type: SolveType) -> ApiResponse {
+        let streak = Self::streak(history);
+        let session = match history.solve_type_session(solve_type) {
+            Some(session) => session,
+            None => {
+                return ApiResponse::ok(json!({
+                    "type": solve_type.to_string(),
+                    "session": Value::Null,
+                    "solve_count": 0,
+                    "streak": streak,
+                }))
+            }
+        };
+        let session_id = session.id().to_string();
+        let name = session.name().clone();
+        let solves = session.to_vec(history);
+        let stats = match history.session_stats(&session_id) {
+            Some(stats) => stats,
+            None => return ApiResponse::error(404, "Session not found"),
+        };
+
+        let mut result = json!({
+            "type": solve_type.to_string(),
+            "session": session_id,
+            "name": name,
+            "solve_count": stats.solve_count(),
+            "average": stats.average(),
+            "best": stats.best(&solves).map(|best| best.time),
+            "streak": streak,
+        });
+        let fields = result.as_object_mut().unwrap();
+        for size in &SESSION_STATS_AVERAGE_SIZES {
+            fields.insert(
+                format!("ao{}", size),
+                json!(solves
+                    .as_slice()
+                    .last_average(*size)
+                    .map(|average| average.time)),
+            );
+            fields.insert(
+                format!("best_ao{}", size),
+                json!(stats
+                    .best_average(*size, &solves)
+                    .map(|average| average.time)),
+            );
+        }
+        ApiResponse::ok(result)
+    }
+
+    fn streak(history: &mut History) -> Value {
+        let streak = PracticeStreak::for_history(history);
+        json!({
+            "current": streak.current,
+            "longest": streak.longest,
+            "today_solves": streak.today_solves,
+            "daily_goal": streak.daily_goal,
+            "at_risk": streak.at_risk(),
+        })
+    }
+
+    fn sync_status(history: &mut History) -> ApiResponse {
+        let (status, message) = match history.check_sync_status() {
+            SyncStatus::NotSynced => ("not_synced", None),
+            SyncStatus::SyncPending => ("pending", None),
+            SyncStatus::SyncFailed(message) => ("failed", Some(message)),
+            SyncStatus::SyncComplete => ("complete", None),
+        };
+        ApiResponse::ok(json!({
+            "status": status,
+            "message": message,
+            "pending_actions": history.local_action_count(),
+        }))
+    }
+}
+
+/// Gets a parameter from a URL query string, decoding `+` and percent
+/// escapes so that puzzle names with spaces can be given
+fn query_parameter(query: &str, name: &str) -> Option<String> {
+    let value = query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| *key == name)?
+        .1;
+    let mut bytes = Vec::new();
+    let mut chars = value.bytes();
+    while let Some(ch) = chars.next() {
+        match ch {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = chars.by_ref().take(2).collect();
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            _ => bytes.push(ch),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
@@ -0,0 +1,175 @@
+use crate::common::Solve;
+
+/// Time limit in milliseconds above which official results are rounded to the
+/// nearest second instead of the nearest hundredth (WCA regulation 9f2)
+const WHOLE_SECOND_ROUNDING_LIMIT: u32 = 10 * 60 * 1000;
+
+/// Competition format for a formal attempt made from a fixed number of
+/// consecutive solves
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttemptFormat {
+    BestOf1,
+    BestOf2,
+    BestOf3,
+    MeanOf3,
+    AverageOf5,
+}
+
+/// Official result of a formal attempt. Times are in milliseconds and `None`
+/// is a DNF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttemptResult {
+    pub format: AttemptFormat,
+    /// Best single of the attempt
+    pub best: Option<u32>,
+    /// Mean or average of the attempt. Always `None` for best of N formats.
+    pub average: Option<u32>,
+}
+
+/// Solves grouped into a formal attempt, ordered by the time they were done
+#[derive(Clone, Debug)]
+pub struct AttemptGroup {
+    pub id: String,
+    pub format: AttemptFormat,
+    pub solves: Vec<Solve>,
+}
+
+impl AttemptFormat {
+    pub const ALL: &'static [AttemptFormat] = &[
+        AttemptFormat::BestOf1,
+        AttemptFormat::BestOf2,
+        AttemptFormat::BestOf3,
+        AttemptFormat::MeanOf3,
+        AttemptFormat::AverageOf5,
+    ];
+
+    /// Name of the format as stored in attempt group actions
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttemptFormat::BestOf1 => "bo1",
+            AttemptFormat::BestOf2 => "bo2",
+            AttemptFormat::BestOf3 => "bo3",
+            AttemptFormat::MeanOf3 => "mo3",
+            AttemptFormat::AverageOf5 => "ao5",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|format| format.name() == name)
+            .copied()
+    }
+
+    /// Number of solves that make up an attempt in this format
+    pub fn solve_count(&self) -> usize {
+        match self {
+            AttemptFormat::BestOf1 => 1,
+            AttemptFormat::BestOf2 => 2,
+            AttemptFormat::BestOf3 | AttemptFormat::MeanOf3 => 3,
+            AttemptFormat::AverageOf5 => 5,
+        }
+    }
+
+    /// Returns true if the attempt is ranked by its mean or average instead
+    /// of its best single
+    pub fn has_average(&self) -> bool {
+        match self {
+            AttemptFormat::MeanOf3 | AttemptFormat::AverageOf5 => true,
+            _ => false,
+        }
+    }
+
+    /// Computes the official result for the given solve times using WCA rules.
+    /// Singles are truncated to the hundredth and means and averages are
+    /// rounded to the hundredth, with results over ten minutes rounded to the
+    /// nearest second. A mean of 3 is a DNF if any solve is a DNF, and an
+    /// average of 5 is a DNF if more than one solve is a DNF. Returns `None` if
+    /// the number of times does not match the format.
+    pub fn result(&self, times: &[Option<u32>]) -> Option<AttemptResult> {
+        if times.len() != self.solve_count() {
+            return None;
+        }
+
+        let mut times: Vec<Option<u32>> = times.iter().map(|time| time.map(wca_single)).collect();
+        let best = times.iter().filter_map(|time| *time).min();
+
+        let counted = match self {
+            AttemptFormat::MeanOf3 => &times[..],
+            AttemptFormat::AverageOf5 => {
+                // DNF sorts as the worst time
+                times.sort_unstable_by_key(|time| time.unwrap_or(u32::MAX));
+                &times[1..4]
+            }
+            _ => &[],
+        };
+        let average = if counted.len() != 0 {
+            counted
+                .iter()
+                .copied()
+                .collect::<Option<Vec<u32>>>()
+                .map(|counted| {
+                    let sum: u32 = counted.iter().sum();
+                    wca_average(sum, counted.len() as u32)
+                })
+        } else {
+            None
+        };
+
+        Some(AttemptResult {
+            format: *self,
+            best,
+            average,
+        })
+    }
+}
+
+impl ToString for AttemptFormat {
+    fn to_string(&self) -> String {
+        match self {
+            AttemptFormat::BestOf1 => "Best of 1".into(),
+            AttemptFormat::BestOf2 => "Best of 2".into(),
+            AttemptFormat::BestOf3 => "Best of 3".into(),
+            AttemptFormat::MeanOf3 => "Mean of 3".into(),
+            AttemptFormat::AverageOf5 => "Average of 5".into(),
+        }
+    }
+}
+
+impl AttemptResult {
+    /// Result used for ranking the attempt: the mean or average if the format
+    /// has one, otherwise the best single
+    pub fn ranked(&self) -> Option<u32> {
+        if self.format.has_average() {
+            self.average
+        } else {
+            self.best
+        }
+    }
+}
+
+impl AttemptGroup {
+    /// Official result of the attempt. Returns `None` if the group does not
+    /// have the number of solves that the format requires.
+    pub fn result(&self) -> Option<AttemptResult> {
+        let times: Vec<Option<u32>> = self.solves.iter().map(|solve| solve.final_time()).collect();
+        self.format.result(&times)
+    }
+}
+
+fn wca_single(time: u32) -> u32 {
+    if time < WHOLE_SECOND_ROUNDING_LIMIT {
+        time - time % 10
+    } else {
+        (time + 500) / 1000 * 1000
+    }
+}
+
+fn wca_average(sum: u32, count: u32) -> u32 {
+    let unit = if sum / count < WHOLE_SECOND_ROUNDING_LIMIT {
+        10
+    } else {
+        1000
+    };
+    (sum + count * unit / 2) / (count * unit) * unit
+}
@@ -0,0 +1,345 @@
+use crate::common::{Color, Cube, CubeFace, Move};
+use crate::rand::{RandomSource, StandardRandomSource};
+use std::collections::BTreeMap;
+
+#[cfg(not(feature = "no_solver"))]
+use crate::common::SolverOptions;
+
+/// Smallest cube size that uses random move scrambles
+pub const MIN_BIG_CUBE_SIZE: usize = 5;
+
+/// Largest cube size with scrambles that can be stored as `Move`s, which turn
+/// at most three layers
+pub const MAX_STORED_BIG_CUBE_SIZE: usize = 7;
+
+const FACES: [CubeFace; 6] = [
+    CubeFace::Top,
+    CubeFace::Front,
+    CubeFace::Right,
+    CubeFace::Back,
+    CubeFace::Left,
+    CubeFace::Bottom,
+];
+
+/// Outward normal, rightward and downward directions of each face as it is
+/// shown in a flattened cube diagram. Indexed by `CubeFace`.
+const FACE_DIRECTIONS: [[[i32; 3]; 3]; 6] = [
+    [[0, 1, 0], [1, 0, 0], [0, 0, 1]],
+    [[0, 0, 1], [1, 0, 0], [0, -1, 0]],
+    [[1, 0, 0], [0, 0, -1], [0, -1, 0]],
+    [[0, 0, -1], [-1, 0, 0], [0, -1, 0]],
+    [[-1, 0, 0], [0, 0, 1], [0, -1, 0]],
+    [[0, -1, 0], [1, 0, 0], [0, 0, -1]],
+];
+
+/// Sticker model of a cube of any size. There is no solver for cubes larger
+/// than 4x4x4, so this only tracks the colors to show the scrambled state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigCube {
+    size: usize,
+    /// Colors of each face in order of the `CubeFace` enumeration. Each face
+    /// is stored from top to bottom in row major order, with columns left to
+    /// right.
+    state: Vec<Color>,
+}
+
+/// Move on a cube larger than 4x4x4. Turns the given number of layers from a
+/// face, so a width of 1 is an outer face turn and a width of 2 is a wide turn.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BigCubeMove {
+    face: CubeFace,
+    width: usize,
+    rotation: i32,
+}
+
+impl BigCubeMove {
+    /// Creates a move with the rotation given in 90 degree clockwise turns
+    pub fn new(face: CubeFace, width: usize, rotation: i32) -> Self {
+        Self {
+            face,
+            width,
+            rotation,
+        }
+    }
+
+    pub fn face(&self) -> CubeFace {
+        self.face
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the face rotation amount in number of 90 degree clockwise rotations
+    pub fn rotation(&self) -> i32 {
+        self.rotation
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            face: self.face,
+            width: self.width,
+            rotation: if self.rotation == 2 {
+                2
+            } else {
+                -self.rotation
+            },
+        }
+    }
+
+    /// Converts to a `Move` that can be stored with a solve. Returns `None` for
+    /// moves of more than three layers.
+    pub fn to_move(&self) -> Option<Move> {
+        Move::from_face_and_rotation_wide(self.face, self.rotation, self.width)
+    }
+}
+
+impl ToString for BigCubeMove {
+    fn to_string(&self) -> String {
+        let face = match self.face {
+            CubeFace::Top => "U",
+            CubeFace::Front => "F",
+            CubeFace::Right => "R",
+            CubeFace::Back => "B",
+            CubeFace::Left => "L",
+            CubeFace::Bottom => "D",
+        };
+        let suffix = match self.rotation {
+            2 | -2 => "2",
+            -1 | 3 => "'",
+            _ => "",
+        };
+        match self.width {
+            1 => format!("{}{}", face, suffix),
+            2 => format!("{}w{}", face, suffix),
+            // Moves of three or more layers use the "3Rw" notation
+            width => format!("{}{}w{}", width, face, suffix),
+        }
+    }
+}
+
+impl BigCube {
+    /// Creates a solved cube with the given number of layers along each side
+    pub fn new(size: usize) -> Self {
+        let mut state = Vec::with_capacity(6 * size * size);
+        for face in &FACES {
+            state.extend(std::iter::repeat(face.color()).take(size * size));
+        }
+        Self { size, state }
+    }
+
+    pub fn color(&self, face: CubeFace, row: usize, col: usize) -> Color {
+        self.state[self.idx(face, row, col)]
+    }
+
+    /// Performs a move of any width
+    pub fn do_big_move(&mut self, mv: BigCubeMove) {
+        self.turn(mv.face, mv.width, mv.rotation);
+    }
+
+    /// Performs a sequence of moves of any width
+    pub fn do_big_moves(&mut self, seq: &[BigCubeMove]) {
+        for mv in seq {
+            self.do_big_move(*mv);
+        }
+    }
+
+    fn idx(&self, face: CubeFace, row: usize, col: usize) -> usize {
+        (face as u8 as usize * self.size + row) * self.size + col
+    }
+
+    /// Position of the center of a sticker and the direction it is facing.
+    /// Positions are doubled so that they are always integers, which puts the
+    /// faces at a distance of `size` from the center of the cube.
+    fn sticker_position(&self, face: CubeFace, row: usize, col: usize) -> ([i32; 3], [i32; 3]) {
+        let [normal, right, down] = FACE_DIRECTIONS[face as u8 as usize];
+        let size = self.size as i32;
+        let x = 2 * col as i32 - (size - 1);
+        let y = 2 * row as i32 - (size - 1);
+        let mut pos = [0; 3];
+        for i in 0..3 {
+            pos[i] = normal[i] * size + right[i] * x + down[i] * y;
+        }
+        (pos, normal)
+    }
+
+    /// Index of the sticker at a position given by `sticker_position`
+    fn sticker_idx(&self, pos: [i32; 3], normal: [i32; 3]) -> usize {
+        let face = FACES
+            .iter()
+            .find(|face| FACE_DIRECTIONS[**face as u8 as usize][0] == normal)
+            .unwrap();
+        let [_, right, down] = FACE_DIRECTIONS[*face as u8 as usize];
+        let size = self.size as i32;
+        let col = (dot(pos, right) + size - 1) / 2;
+        let row = (dot(pos, down) + size - 1) / 2;
+        self.idx(*face, row as usize, col as usize)
+    }
+
+    /// Turns the given number of layers from a face by a number of 90 degree
+    /// clockwise rotations
+    fn turn(&mut self, face: CubeFace, width: usize, rotation: i32) {
+        let axis = FACE_DIRECTIONS[face as u8 as usize][0];
+        let size = self.size as i32;
+        // Stickers on the opposite face only move when the whole cube turns
+        let min_distance = if width >= self.size {
+            -size
+        } else {
+            size + 1 - 2 * width as i32
+        };
+
+        let mut state = self.state.clone();
+        for sticker_face in &FACES {
+            for row in 0..self.size {
+                for col in 0..self.size {
+                    let (mut pos, mut normal) = self.sticker_position(*sticker_face, row, col);
+                    if dot(pos, axis) < min_distance {
+                        continue;
+                    }
+                    for _ in 0..rotation.rem_euclid(4) {
+                        pos = rotate_clockwise(pos, axis);
+                        normal = rotate_clockwise(normal, axis);
+                    }
+                    state[self.sticker_idx(pos, normal)] =
+                        self.state[self.idx(*sticker_face, row, col)];
+                }
+            }
+        }
+        self.state = state;
+    }
+}
+
+impl Cube for BigCube {
+    fn is_solved(&self) -> bool {
+        let face_size = self.size * self.size;
+        self.state
+            .chunks(face_size)
+            .all(|face| face.iter().all(|color| *color == face[0]))
+    }
+
+    fn do_move(&mut self, mv: Move) {
+        self.turn(mv.face(), mv.width(), mv.rotation());
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn colors(&self) -> BTreeMap<CubeFace, Vec<Vec<Color>>> {
+        let mut result = BTreeMap::new();
+        for face in &FACES {
+            let mut rows = Vec::new();
+            for row in 0..self.size {
+                let mut cols = Vec::new();
+                for col in 0..self.size {
+                    cols.push(self.color(*face, row, col));
+                }
+                rows.push(cols);
+            }
+            result.insert(*face, rows);
+        }
+        result
+    }
+
+    #[cfg(not(feature = "no_solver"))]
+    fn solve_with_options(&self, _options: &SolverOptions) -> Option<Vec<Move>> {
+        None
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.size);
+    }
+
+    fn dyn_clone(&self) -> Box<dyn Cube> {
+        Box::new(self.clone())
+    }
+}
+
+fn dot(a: [i32; 3], b: [i32; 3]) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Rotates a vector 90 degrees clockwise when looking at the origin from the
+/// direction of `axis`
+fn rotate_clockwise(v: [i32; 3], axis: [i32; 3]) -> [i32; 3] {
+    let cross = [
+        axis[1] * v[2] - axis[2] * v[1],
+        axis[2] * v[0] - axis[0] * v[2],
+        axis[0] * v[1] - axis[1] * v[0],
+    ];
+    let along = dot(axis, v);
+    [
+        axis[0] * along - cross[0],
+        axis[1] * along - cross[1],
+        axis[2] * along - cross[2],
+    ]
+}
+
+/// Number of moves in a random move scramble for a cube of the given size.
+/// This is 60 moves for 5x5x5, 80 for 6x6x6 and 100 for 7x7x7, matching the
+/// regulation scramble lengths, with larger cubes continuing the progression.
+pub fn big_cube_scramble_length(size: usize) -> usize {
+    20 * (size.max(MIN_BIG_CUBE_SIZE) - 2)
+}
+
+/// Generates a random move scramble for a cube of the given size. Sizes below
+/// 5x5x5 are scrambled as a 5x5x5.
+pub fn scramble_big_cube(size: usize) -> Vec<BigCubeMove> {
+    scramble_big_cube_sourced(size, &mut StandardRandomSource)
+}
+
+/// Generates a random move scramble for a cube of the given size using the
+/// given source of randomness
+pub fn scramble_big_cube_sourced<T: RandomSource>(size: usize, rng: &mut T) -> Vec<BigCubeMove> {
+    let size = size.max(MIN_BIG_CUBE_SIZE);
+    let max_width = size / 2;
+    let length = big_cube_scramble_length(size);
+
+    let mut result: Vec<BigCubeMove> = Vec::with_capacity(length);
+    // Layers turned since the last move on a different axis. Moves on the same
+    // axis commute, so turning any of these layers again could cancel.
+    let mut axis_layers: Vec<(CubeFace, usize)> = Vec::new();
+    while result.len() < length {
+        let face = FACES[rng.next(6) as usize];
+        let width = rng.next(max_width as u32) as usize + 1;
+
+        // On even cubes, turning half of the cube from opposite faces only
+        // differs by a cube rotation, so only use one face of each pair
+        if size % 2 == 0 && width == max_width && face as u8 >= CubeFace::Back as u8 {
+            continue;
+        }
+
+        if let Some(last) = result.last() {
+            if last.face != face && last.face != face.opposite() {
+                axis_layers.clear();
+            }
+        }
+        if axis_layers.contains(&(face, width)) {
+            continue;
+        }
+        axis_layers.push((face, width));
+
+        let rotation = match rng.next(3) {
+            0 => 1,
+            1 => 2,
+            _ => -1,
+        };
+        result.push(BigCubeMove::new(face, width, rotation));
+    }
+    result
+}
+
+/// Generates a random move scramble for a cube of the given size as moves that
+/// can be stored with a solve. Sizes are limited to `MAX_STORED_BIG_CUBE_SIZE`.
+pub fn scramble_big_cube_moves(size: usize) -> Vec<Move> {
+    scramble_big_cube_moves_sourced(size, &mut StandardRandomSource)
+}
+
+/// Generates a random move scramble for a cube of the given size as moves that
+/// can be stored with a solve, using the given source of randomness
+pub fn scramble_big_cube_moves_sourced<T: RandomSource>(size: usize, rng: &mut T) -> Vec<Move> {
+    scramble_big_cube_sourced(size.min(MAX_STORED_BIG_CUBE_SIZE), rng)
+        .iter()
+        .map(|mv| mv.to_move().unwrap())
+        .collect()
+}
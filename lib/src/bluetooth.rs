@@ -2,21 +2,28 @@ mod gan;
 mod giiker;
 mod gocube;
 mod moyu;
+mod peripheral;
 
-use crate::common::TimedMove;
+use crate::common::{CubeOrientation, TimedMove};
 use crate::cube3x3x3::Cube3x3x3;
+use crate::error::Error;
+use crate::timeline::MoveTimeline;
 use anyhow::{anyhow, Result};
 use btleplug::api::{BDAddr, Central, Peripheral};
 use gan::gan_cube_connect;
 use giiker::giiker_connect;
 use gocube::gocube_connect;
 use moyu::moyu_connect;
+use peripheral::CubePeripheral;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+#[cfg(test)]
+pub(crate) use peripheral::MockPeripheral;
+
 #[cfg(target_os = "linux")]
 use btleplug::bluez::{adapter::Adapter, manager::Manager};
 #[cfg(target_os = "macos")]
@@ -25,6 +32,7 @@ use btleplug::corebluetooth::manager::Manager;
 use btleplug::winrtble::{adapter::Adapter, manager::Manager};
 
 pub(crate) trait BluetoothCubeDevice: Send {
+    fn device_info(&self) -> DeviceInfo;
     fn cube_state(&self) -> Cube3x3x3;
     fn battery_percentage(&self) -> Option<u32>;
     fn battery_charging(&self) -> Option<bool>;
@@ -35,6 +43,7 @@ pub(crate) trait BluetoothCubeDevice: Send {
     fn timer_only(&self) -> bool {
         false
     }
+    fn set_timer_start_latency(&self, _latency: u32) {}
 
     fn estimated_clock_ratio(&self) -> f64 {
         1.0
@@ -44,6 +53,64 @@ pub(crate) trait BluetoothCubeDevice: Send {
     }
 }
 
+/// Sets up the backend for a cube type on an already connected device. Each
+/// backend determines the protocol version from the device's characteristics.
+pub(crate) fn connect_cube<P: CubePeripheral + 'static>(
+    cube_type: BluetoothCubeType,
+    device: P,
+    move_listener: Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>,
+) -> Result<Box<dyn BluetoothCubeDevice>> {
+    match cube_type {
+        BluetoothCubeType::GAN => gan_cube_connect(device, move_listener),
+        BluetoothCubeType::GoCube => gocube_connect(device, move_listener),
+        BluetoothCubeType::Giiker => giiker_connect(device, move_listener),
+        BluetoothCubeType::MoYu => moyu_connect(device, move_listener),
+    }
+}
+
+/// Hardware details reported by a connected device. Fields that the device
+/// does not report are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Name of the protocol used to communicate with the device
+    pub protocol: String,
+    pub firmware_version: Option<String>,
+    pub hardware_version: Option<String>,
+    /// Unique identifier of the device, formatted as hex bytes
+    pub device_id: Option<String>,
+    pub features: DeviceFeatures,
+}
+
+/// Capabilities supported by a device's protocol
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeviceFeatures {
+    /// Cube state can be read from the device on connection
+    pub cube_state: bool,
+    /// Cube state stored on the device can be reset to solved
+    pub reset_cube_state: bool,
+    pub battery: bool,
+    pub orientation: bool,
+    pub timer: bool,
+}
+
+impl DeviceInfo {
+    pub(crate) fn new(protocol: &str, features: DeviceFeatures) -> Self {
+        Self {
+            protocol: protocol.into(),
+            features,
+            ..Default::default()
+        }
+    }
+
+    /// Formats a device identifier as colon separated hex bytes
+    pub(crate) fn format_id(id: &[u8]) -> String {
+        id.iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<String>>()
+            .join(":")
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct AvailableDevice {
     pub address: BDAddr,
@@ -87,11 +154,16 @@ pub enum BluetoothCubeState {
 #[derive(Clone)]
 pub enum BluetoothCubeEvent {
     Move(Vec<TimedMove>, Cube3x3x3),
+    Orientation(CubeOrientation),
     HandsOnTimer,
     TimerStartCancel,
     TimerReady,
     TimerStarted,
+    TimerRunning(u32),
     TimerFinished(u32),
+    /// A notification from the device could not be decoded. The device is
+    /// marked as desynced when this is sent.
+    Error(String),
 }
 
 pub struct BluetoothCube {
@@ -101,6 +173,7 @@ pub struct BluetoothCube {
     connected_device: Arc<Mutex<Option<Box<dyn BluetoothCubeDevice>>>>,
     connected_name: Arc<Mutex<Option<String>>>,
     battery: Arc<Mutex<(Option<u32>, Option<bool>)>>,
+    clock_ratio: Arc<Mutex<Option<f64>>>,
     listeners: Arc<Mutex<HashMap<MoveListenerHandle, Box<dyn Fn(BluetoothCubeEvent) + Send>>>>,
     next_listener_id: AtomicU64,
     error: Arc<Mutex<Option<String>>>,
@@ -119,6 +192,7 @@ impl BluetoothCube {
         let connected_device = Arc::new(Mutex::new(None));
         let connected_name = Arc::new(Mutex::new(None));
         let battery = Arc::new(Mutex::new((None, None)));
+        let clock_ratio = Arc::new(Mutex::new(None));
         let listeners = Arc::new(Mutex::new(HashMap::new()));
         let error = Arc::new(Mutex::new(None));
 
@@ -128,6 +202,7 @@ impl BluetoothCube {
         let connected_device_copy = connected_device.clone();
         let connected_name_copy = connected_name.clone();
         let battery_copy = battery.clone();
+        let clock_ratio_copy = clock_ratio.clone();
         let listeners_copy = listeners.clone();
         let error_copy = error.clone();
         std::thread::spawn(move || {
@@ -138,6 +213,7 @@ impl BluetoothCube {
                 connected_device_copy,
                 connected_name_copy,
                 battery_copy,
+                clock_ratio_copy,
                 listeners_copy,
             ) {
                 Err(error) => {
@@ -155,6 +231,7 @@ impl BluetoothCube {
             connected_device,
             connected_name,
             battery,
+            clock_ratio,
             listeners,
             next_listener_id: AtomicU64::new(0),
             error,
@@ -168,6 +245,7 @@ impl BluetoothCube {
         connected_device: Arc<Mutex<Option<Box<dyn BluetoothCubeDevice>>>>,
         connected_name: Arc<Mutex<Option<String>>>,
         battery: Arc<Mutex<(Option<u32>, Option<bool>)>>,
+        clock_ratio: Arc<Mutex<Option<f64>>>,
         listeners: Arc<Mutex<HashMap<MoveListenerHandle, Box<dyn Fn(BluetoothCubeEvent) + Send>>>>,
     ) -> Result<()> {
         let manager = Manager::new()?;
@@ -188,135 +266,40 @@ impl BluetoothCube {
                         let listeners_copy = listeners.clone();
 
                         // Set up time calibration state
-                        struct TimeCalibrationState {
-                            start_time: Option<Instant>,
-                            last_move_time: Option<Instant>,
-                            current_duration: Duration,
-                            total_raw_ticks: u64,
-                            total_real_ticks: u64,
-                            clock_ratio: f64,
-                            clock_ratio_range: (f64, f64),
-                        }
-                        let calibration_state = Arc::new(Mutex::new(TimeCalibrationState {
-                            start_time: None,
-                            last_move_time: None,
-                            current_duration: Duration::from_secs(0),
-                            total_raw_ticks: 0,
-                            total_real_ticks: 0,
-                            clock_ratio: 1.0,
-                            clock_ratio_range: (0.98, 1.02),
-                        }));
-                        let init_calibration_state = calibration_state.clone();
-
-                        let _ = Self::connect_handler(
+                        let timeline = Arc::new(Mutex::new(MoveTimeline::new(1.0, (0.98, 1.02))));
+                        let init_timeline = timeline.clone();
+                        let init_clock_ratio = clock_ratio.clone();
+                        let learned_clock_ratio = clock_ratio.clone();
+
+                        let result = Self::connect_handler(
                             state.clone(),
                             connected_device.clone(),
                             connected_name.clone(),
                             battery.clone(),
                             device,
                             Box::new(move |cube| {
-                                init_calibration_state.lock().unwrap().clock_ratio =
-                                    cube.estimated_clock_ratio();
-                                init_calibration_state.lock().unwrap().clock_ratio_range =
-                                    cube.clock_ratio_range();
+                                // Start from the ratio learned in a previous connection
+                                // if there is one, otherwise use the cube's estimate
+                                let ratio = match *init_clock_ratio.lock().unwrap() {
+                                    Some(ratio) => ratio,
+                                    None => cube.estimated_clock_ratio(),
+                                };
+                                *init_timeline.lock().unwrap() =
+                                    MoveTimeline::new(ratio, cube.clock_ratio_range());
                             }),
                             Box::new(move |event| {
                                 match event {
                                     BluetoothCubeEvent::Move(moves, state) => {
+                                        diagnostic!(trace, count = moves.len(), "Received moves");
+
                                         // We can't use the move timing data directly. Some cubes have very
                                         // uncalibrated clocks and we must adjust the timing to match real
                                         // time, with the host device as the reference source.
-                                        let mut calibration_state =
-                                            calibration_state.lock().unwrap();
-                                        let now = Instant::now();
-                                        let mut last_duration = calibration_state.current_duration;
-
-                                        // Check length of time since last move
-                                        let mut calibration_reset = false;
-                                        if let Some(last_move_time) =
-                                            calibration_state.last_move_time
-                                        {
-                                            let delta = now - last_move_time;
-                                            if delta.as_secs() > 30 {
-                                                // More than 30 seconds between moves, don't adjust clock ratio to
-                                                // avoid issues with the range of the encodings of some cubes.
-                                                // Adjust timestamp using real time.
-                                                calibration_reset = true;
-                                                calibration_state.current_duration += delta;
-                                            }
-                                        }
-
-                                        // Go through the move list and adjust the timing information
-                                        let mut adjusted_moves = Vec::new();
-                                        let mut new_raw_ticks = 0;
-                                        for raw_move in moves {
-                                            let mv = raw_move.move_();
-                                            let raw_time = raw_move.time();
-
-                                            if !calibration_reset {
-                                                new_raw_ticks += raw_time;
-
-                                                // Adjust delta using clock ratio. This will be adjusted
-                                                // over time to be calibrated to real time.
-                                                let adjusted_delta = Duration::from_nanos(
-                                                    ((raw_time as u64 * 1_000_000) as f64
-                                                        / calibration_state.clock_ratio)
-                                                        as u64,
-                                                );
-                                                calibration_state.current_duration +=
-                                                    adjusted_delta;
-                                            }
-
-                                            // Add adjusted timing information to new move list
-                                            let adjusted_time =
-                                                (calibration_state.current_duration).as_millis()
-                                                    - last_duration.as_millis();
-                                            last_duration = calibration_state.current_duration;
-                                            adjusted_moves
-                                                .push(TimedMove::new(mv, adjusted_time as u32));
-                                        }
-
-                                        // Update calibration state
-                                        if let Some(start_time_deref) = calibration_state.start_time
-                                        {
-                                            if calibration_reset {
-                                                // Calibration is being reset because of too much time
-                                                // between moves. Measure from this move forward.
-                                                calibration_state.start_time = Some(now);
-                                                calibration_state.total_raw_ticks = 0;
-                                                calibration_state.total_real_ticks = 0;
-                                            } else {
-                                                // Update the calibration with the number of milliseconds
-                                                // reported in the raw data and the number of milliseconds
-                                                // that have actually passed.
-                                                calibration_state.total_raw_ticks +=
-                                                    new_raw_ticks as u64;
-                                                calibration_state.total_real_ticks =
-                                                    (now - start_time_deref).as_millis() as u64;
-
-                                                // Compute ratio between raw time and real time
-                                                let computed_clock_ratio = (calibration_state
-                                                    .total_raw_ticks
-                                                    as f64)
-                                                    / (calibration_state.total_real_ticks as f64);
-
-                                                // Clamp ratio to a range for sanity check
-                                                calibration_state.clock_ratio =
-                                                    computed_clock_ratio
-                                                        .max(
-                                                            (calibration_state.clock_ratio_range).0,
-                                                        )
-                                                        .min(
-                                                            (calibration_state.clock_ratio_range).1,
-                                                        );
-                                            }
-                                        } else {
-                                            // First move, record start time
-                                            calibration_state.start_time = Some(now);
-                                        }
-
-                                        // Keep track of last move's real time
-                                        calibration_state.last_move_time = Some(now);
+                                        let mut timeline = timeline.lock().unwrap();
+                                        let adjusted_moves =
+                                            timeline.add_moves(&moves, Instant::now());
+                                        *learned_clock_ratio.lock().unwrap() =
+                                            Some(timeline.clock_ratio());
 
                                         // Notify clients of the move information
                                         for listener in listeners_copy.lock().unwrap().iter() {
@@ -327,6 +310,11 @@ impl BluetoothCube {
                                         }
                                     }
                                     event => {
+                                        #[cfg(feature = "diagnostics")]
+                                        if let BluetoothCubeEvent::Error(error) = &event {
+                                            diagnostic!(warn, "Cube desynced: {}", error);
+                                        }
+
                                         // Notify clients of the event
                                         for listener in listeners_copy.lock().unwrap().iter() {
                                             listener.1(event.clone());
@@ -335,6 +323,11 @@ impl BluetoothCube {
                                 }
                             }),
                         );
+                        #[cfg(feature = "diagnostics")]
+                        if let Err(error) = &result {
+                            diagnostic!(warn, "Connection failed: {}", error);
+                        }
+                        let _ = result;
                     }
                 }
             }
@@ -383,19 +376,22 @@ impl BluetoothCube {
             return Err(anyhow!("Cube name missing"));
         };
 
+        let _span = diagnostic_span!("bluetooth", cube_type = ?cube_type);
+        diagnostic!(
+            info,
+            address = %peripheral.address(),
+            "Connecting to {}",
+            name.as_deref().unwrap_or("")
+        );
         *state.lock().unwrap() = BluetoothCubeState::Connecting;
 
         // Connect to the cube
         peripheral.connect()?;
 
-        let cube = match cube_type {
-            BluetoothCubeType::GAN => gan_cube_connect(peripheral, move_listener)?,
-            BluetoothCubeType::GoCube => gocube_connect(peripheral, move_listener)?,
-            BluetoothCubeType::Giiker => giiker_connect(peripheral, move_listener)?,
-            BluetoothCubeType::MoYu => moyu_connect(peripheral, move_listener)?,
-        };
+        let cube = connect_cube(cube_type, peripheral, move_listener)?;
 
         init(cube.as_ref());
+        diagnostic!(info, "Connected");
 
         *connected_device.lock().unwrap() = Some(cube);
         *connected_name.lock().unwrap() = name;
@@ -415,6 +411,7 @@ impl BluetoothCube {
             }
         }
 
+        diagnostic!(info, "Disconnected");
         *state.lock().unwrap() = BluetoothCubeState::Discovering;
         *connected_device.lock().unwrap() = None;
         *connected_name.lock().unwrap() = None;
@@ -423,29 +420,54 @@ impl BluetoothCube {
         Ok(())
     }
 
-    fn check_for_error(&self) -> Result<()> {
+    fn check_for_error(&self) -> crate::Result<()> {
         match self.error.lock().unwrap().deref() {
-            Some(error) => Err(anyhow!("{}", error)),
+            Some(error) => Err(Error::Bluetooth(anyhow!("{}", error))),
             None => Ok(()),
         }
     }
 
-    pub fn state(&self) -> Result<BluetoothCubeState> {
+    pub fn state(&self) -> crate::Result<BluetoothCubeState> {
         self.check_for_error()?;
         Ok(*self.state.lock().unwrap())
     }
 
-    pub fn available_devices(&self) -> Result<Vec<AvailableDevice>> {
+    pub fn available_devices(&self) -> crate::Result<Vec<AvailableDevice>> {
         self.check_for_error()?;
         Ok(self.discovered_devices.lock().unwrap().clone())
     }
 
-    pub fn connect(&self, address: BDAddr) -> Result<()> {
+    pub fn connect(&self, address: BDAddr) -> crate::Result<()> {
         self.check_for_error()?;
         *self.to_connect.lock().unwrap() = Some(address);
         Ok(())
     }
 
+    /// Address of the connected device, if there is one
+    pub fn connected_address(&self) -> crate::Result<Option<BDAddr>> {
+        self.check_for_error()?;
+        if self.connected_device.lock().unwrap().is_some() {
+            Ok(self.to_connect.lock().unwrap().clone())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Sets the clock ratio to start from when the next device connects, such
+    /// as one learned during a previous connection to the same device. If not
+    /// set, a default for the type of device is used.
+    pub fn set_clock_ratio(&self, ratio: Option<f64>) {
+        *self.clock_ratio.lock().unwrap() = ratio;
+    }
+
+    /// Ratio between the clock of the connected device and real time. This
+    /// starts from the ratio given to `set_clock_ratio` and is updated as the
+    /// timing of moves is calibrated. Returns `None` if neither has happened.
+    pub fn clock_ratio(&self) -> crate::Result<Option<f64>> {
+        self.check_for_error()?;
+        Ok(*self.clock_ratio.lock().unwrap())
+    }
+
     pub fn disconnect(&self) {
         match self.connected_device.lock().unwrap().deref() {
             Some(device) => device.disconnect(),
@@ -456,49 +478,71 @@ impl BluetoothCube {
         *self.connected_device.lock().unwrap() = None;
     }
 
-    pub fn name(&self) -> Result<Option<String>> {
+    pub fn name(&self) -> crate::Result<Option<String>> {
         self.check_for_error()?;
         Ok(self.connected_name.lock().unwrap().clone())
     }
 
-    pub fn timer_only(&self) -> Result<bool> {
+    pub fn device_info(&self) -> crate::Result<DeviceInfo> {
+        self.check_for_error()?;
+        match self.connected_device.lock().unwrap().deref() {
+            Some(device) => Ok(device.device_info()),
+            None => Err(Error::Bluetooth(anyhow!("Cube not connected"))),
+        }
+    }
+
+    pub fn timer_only(&self) -> crate::Result<bool> {
         self.check_for_error()?;
         match self.connected_device.lock().unwrap().deref() {
             Some(device) => Ok(device.timer_only()),
-            None => Err(anyhow!("Cube not connected")),
+            None => Err(Error::Bluetooth(anyhow!("Cube not connected"))),
+        }
+    }
+
+    /// Sets the latency in milliseconds between a hardware timer starting and
+    /// the start notification being received. This is added to the running time
+    /// reported while the timer is running, and does not affect final times.
+    pub fn set_timer_start_latency(&self, latency: u32) -> crate::Result<()> {
+        self.check_for_error()?;
+        match self.connected_device.lock().unwrap().deref() {
+            Some(device) => {
+                device.set_timer_start_latency(latency);
+                Ok(())
+            }
+            None => Err(Error::Bluetooth(anyhow!("Cube not connected"))),
         }
     }
 
-    pub fn cube_state(&self) -> Result<Cube3x3x3> {
+    pub fn cube_state(&self) -> crate::Result<Cube3x3x3> {
         self.check_for_error()?;
         match self.connected_device.lock().unwrap().deref() {
             Some(device) => Ok(device.cube_state()),
-            None => Err(anyhow!("Cube not connected")),
+            None => Err(Error::Bluetooth(anyhow!("Cube not connected"))),
         }
     }
 
-    pub fn battery_percentage(&self) -> Result<Option<u32>> {
+    pub fn battery_percentage(&self) -> crate::Result<Option<u32>> {
         self.check_for_error()?;
         Ok(self.battery.lock().unwrap().0)
     }
 
-    pub fn battery_charging(&self) -> Result<Option<bool>> {
+    pub fn battery_charging(&self) -> crate::Result<Option<bool>> {
         self.check_for_error()?;
         Ok(self.battery.lock().unwrap().1)
     }
 
-    pub fn reset_cube_state(&self) -> Result<()> {
+    pub fn reset_cube_state(&self) -> crate::Result<()> {
         self.check_for_error()?;
         match self.connected_device.lock().unwrap().deref() {
             Some(device) => {
                 device.reset_cube_state();
                 Ok(())
             }
-            None => Err(anyhow!("Cube not connected")),
+            None => Err(Error::Bluetooth(anyhow!("Cube not connected"))),
         }
     }
 
-    pub fn synced(&self) -> Result<bool> {
+    pub fn synced(&self) -> crate::Result<bool> {
         self.check_for_error()?;
         Ok(*self.state.lock().unwrap() == BluetoothCubeState::Connected)
     }
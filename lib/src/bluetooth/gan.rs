@@ -1,21 +1,22 @@
-use crate::bluetooth::{BluetoothCubeDevice, BluetoothCubeEvent};
-use crate::common::{
-    Color, Corner, CornerPiece, Cube, CubeFace, InitialCubeState, Move, TimedMove,
+use crate::bluetooth::peripheral::CubePeripheral;
+use crate::bluetooth::{BluetoothCubeDevice, BluetoothCubeEvent, DeviceFeatures, DeviceInfo};
+use crate::common::{Cube, InitialCubeState, Move, TimedMove};
+use crate::cube3x3x3::Cube3x3x3;
+use crate::packet::{
+    decode_gan_timer_message, decode_gan_v1_cube_state, decode_gan_v2_message, GanTimerMessage,
+    GanV2Message, GAN_V2_MOVE_HISTORY,
 };
-use crate::cube3x3x3::{Cube3x3x3, Cube3x3x3Faces, Edge3x3x3, EdgePiece3x3x3};
 use aes::{
     cipher::generic_array::GenericArray,
     cipher::{BlockDecrypt, BlockEncrypt},
     Aes128, Block, NewBlockCipher,
 };
 use anyhow::{anyhow, Result};
-use btleplug::api::{Characteristic, Peripheral, WriteType};
-use std::collections::HashSet;
-use std::convert::{TryFrom, TryInto};
-use std::iter::FromIterator;
+use btleplug::api::{Characteristic, WriteType};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 struct GANCubeVersion1Characteristics {
@@ -27,7 +28,7 @@ struct GANCubeVersion1Characteristics {
     battery: Characteristic,
 }
 
-struct GANCubeVersion1<P: Peripheral + 'static> {
+struct GANCubeVersion1<P: CubePeripheral + 'static> {
     device: P,
     state: Mutex<Cube3x3x3>,
     battery_percentage: Mutex<u32>,
@@ -37,13 +38,14 @@ struct GANCubeVersion1<P: Peripheral + 'static> {
     characteristics: GANCubeVersion1Characteristics,
     cipher: GANCubeVersion1Cipher,
     move_listener: Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>,
+    info: DeviceInfo,
 }
 
 struct GANCubeVersion1Cipher {
     device_key: [u8; 16],
 }
 
-struct GANCubeVersion2<P: Peripheral + 'static> {
+struct GANCubeVersion2<P: CubePeripheral + 'static> {
     device: P,
     state: Arc<Mutex<Cube3x3x3>>,
     battery_percentage: Arc<Mutex<Option<u32>>>,
@@ -51,6 +53,7 @@ struct GANCubeVersion2<P: Peripheral + 'static> {
     synced: Arc<Mutex<bool>>,
     write: Characteristic,
     cipher: GANCubeVersion2Cipher,
+    info: DeviceInfo,
 }
 
 #[derive(Clone)]
@@ -59,11 +62,15 @@ struct GANCubeVersion2Cipher {
     device_iv: [u8; 16],
 }
 
-struct GANSmartTimer<P: Peripheral + 'static> {
+struct GANSmartTimer<P: CubePeripheral + 'static> {
     device: P,
+    move_listener: Arc<Mutex<Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>>>,
+    running_since: Arc<Mutex<Option<Instant>>>,
+    start_latency: AtomicU32,
+    info: DeviceInfo,
 }
 
-impl<P: Peripheral> GANCubeVersion1<P> {
+impl<P: CubePeripheral> GANCubeVersion1<P> {
     const LAST_MOVE_COUNT_OFFSET: usize = 12;
     const LAST_MOVE_LIST_OFFSET: usize = 13;
 
@@ -72,6 +79,7 @@ impl<P: Peripheral> GANCubeVersion1<P> {
         characteristics: GANCubeVersion1Characteristics,
         move_listener: Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>,
         minor_version: u8,
+        firmware_version: String,
     ) -> Result<Self> {
         // Read device identifier, this is used to derive the key
         let device_id = device.read(&characteristics.hardware)?;
@@ -102,7 +110,7 @@ impl<P: Peripheral> GANCubeVersion1<P> {
             return Err(anyhow!("Cube state is invalid"));
         }
         let state = cipher.decrypt(&state)?;
-        let state = Self::decode_cube_state(&state)?;
+        let state = decode_gan_v1_cube_state(&state)?;
         let state = Mutex::new(state);
 
         // Get the initial move count
@@ -122,6 +130,20 @@ impl<P: Peripheral> GANCubeVersion1<P> {
         let battery_percentage = battery[7];
         let battery_charging = battery[6] != 0;
 
+        let info = DeviceInfo {
+            firmware_version: Some(firmware_version),
+            device_id: Some(DeviceInfo::format_id(&device_id[0..6])),
+            ..DeviceInfo::new(
+                "GAN version 1",
+                DeviceFeatures {
+                    cube_state: true,
+                    reset_cube_state: true,
+                    battery: true,
+                    ..Default::default()
+                },
+            )
+        };
+
         Ok(GANCubeVersion1 {
             device,
             state,
@@ -132,60 +154,10 @@ impl<P: Peripheral> GANCubeVersion1<P> {
             characteristics,
             cipher,
             move_listener,
+            info,
         })
     }
 
-    fn decode_cube_state(data: &[u8]) -> Result<Cube3x3x3> {
-        const FACES: [CubeFace; 6] = [
-            CubeFace::Top,
-            CubeFace::Right,
-            CubeFace::Front,
-            CubeFace::Bottom,
-            CubeFace::Left,
-            CubeFace::Back,
-        ];
-        const COLORS: [Color; 6] = [
-            Color::White,
-            Color::Red,
-            Color::Green,
-            Color::Yellow,
-            Color::Orange,
-            Color::Blue,
-        ];
-        let mut state: [Color; 6 * 9] = [Color::White; 6 * 9];
-
-        for face in 0..6 {
-            // Find face index in our representation using mapping
-            let target_face_idx = FACES[face] as u8 as usize;
-            state[target_face_idx * 9 + 1 * 3 + 1] = COLORS[face];
-
-            // Decode face's data from buffer
-            let face_data = ((data[(face * 3) ^ 1] as u32) << 16)
-                | ((data[((face * 3) + 1) ^ 1] as u32) << 8)
-                | data[((face * 3) + 2) ^ 1] as u32;
-
-            // Place colors into the cube state array
-            let mut offset = target_face_idx * 9;
-            for i in 0..8 {
-                if i == 4 {
-                    // Skip center, not represented in data
-                    offset += 1;
-                }
-
-                let color_idx = (face_data >> (3 * (7 - i))) & 7;
-                if color_idx >= 6 {
-                    return Err(anyhow!("Invalid cube state"));
-                }
-
-                state[offset] = COLORS[color_idx as usize];
-                offset += 1;
-            }
-        }
-
-        // Create cube state and convert to normal format
-        Ok(Cube3x3x3Faces::from_colors(state).as_pieces())
-    }
-
     fn move_poll(&self) -> Result<()> {
         if !*self.synced.lock().unwrap() {
             // Not synced, do not try to poll moves
@@ -280,7 +252,11 @@ impl<P: Peripheral> GANCubeVersion1<P> {
     }
 }
 
-impl<P: Peripheral> BluetoothCubeDevice for GANCubeVersion1<P> {
+impl<P: CubePeripheral> BluetoothCubeDevice for GANCubeVersion1<P> {
+    fn device_info(&self) -> DeviceInfo {
+        self.info.clone()
+    }
+
     fn cube_state(&self) -> Cube3x3x3 {
         self.state.lock().unwrap().clone()
     }
@@ -313,8 +289,13 @@ impl<P: Peripheral> BluetoothCubeDevice for GANCubeVersion1<P> {
     }
 
     fn update(&self) {
-        if let Err(_) = self.move_poll() {
-            *self.synced.lock().unwrap() = false;
+        if let Err(error) = self.move_poll() {
+            // Only report the first error, polling fails until the state is reset
+            let mut synced = self.synced.lock().unwrap();
+            if *synced {
+                *synced = false;
+                (self.move_listener)(BluetoothCubeEvent::Error(error.to_string()));
+            }
         }
     }
 
@@ -363,8 +344,7 @@ impl GANCubeVersion1Cipher {
     }
 }
 
-impl<P: Peripheral> GANCubeVersion2<P> {
-    const CUBE_MOVES_MESSAGE: u8 = 2;
+impl<P: CubePeripheral> GANCubeVersion2<P> {
     const CUBE_STATE_MESSAGE: u8 = 4;
     const BATTERY_STATE_MESSAGE: u8 = 9;
     const RESET_CUBE_STATE_MESSAGE: u8 = 10;
@@ -379,8 +359,7 @@ impl<P: Peripheral> GANCubeVersion2<P> {
     ) -> Result<Self> {
         // Derive keys. These are based on a 6 byte device identifier found in the
         // manufacturer data.
-        let device_key: [u8; 6] = if let Some(data) = device.properties().manufacturer_data.get(&1)
-        {
+        let device_key: [u8; 6] = if let Some(data) = device.manufacturer_data(1) {
             if data.len() >= 9 {
                 let mut result = [0; 6];
                 result.copy_from_slice(&data[3..9]);
@@ -426,165 +405,97 @@ impl<P: Peripheral> GANCubeVersion2<P> {
         let synced_copy = synced.clone();
 
         device.on_notification(Box::new(move |value| {
-            if let Ok(value) = cipher_copy.decrypt(&value.value) {
-                let message_type = Self::extract_bits(&value, 0, 4) as u8;
-                match message_type {
-                    Self::CUBE_MOVES_MESSAGE => {
-                        let current_move_count = Self::extract_bits(&value, 4, 8) as u8;
-
-                        // If we haven't received a cube state message yet, we can't know what
-                        // the curent cube state is. Ignore moves until the cube state message
-                        // is received. If there has been a cube state message, we will have
-                        // a last move count and we can continue.
-                        let mut last_move_count_option = last_move_count.lock().unwrap();
-                        if let Some(last_move_count) = *last_move_count_option {
-                            // Check number of moves since last message.
-                            let move_count =
-                                current_move_count.wrapping_sub(last_move_count) as usize;
-                            if move_count > 7 {
-                                // There are too many moves since the last message. Our cube
-                                // state is out of sync. Let the client know and reset the
-                                // last move count such that we don't parse any more move
-                                // messages, since they aren't valid anymore.
-                                *synced_copy.lock().unwrap() = false;
-                                *last_move_count_option = None;
-                                return;
-                            }
-
-                            // Gather the moves
-                            let mut moves = Vec::with_capacity(move_count);
-                            for j in 0..move_count {
-                                // Build move list in reverse order. In the packet the moves
-                                // are from the latest move to the oldest move, but the callback
-                                // should take the moves in the order they happened.
-                                let i = (move_count - 1) - j;
-
-                                // Decode move data
-                                let move_num = Self::extract_bits(&value, 12 + i * 5, 5) as usize;
-                                let move_time = Self::extract_bits(&value, 12 + 7 * 5 + i * 16, 16);
-                                const MOVES: &[Move] = &[
-                                    Move::U,
-                                    Move::Up,
-                                    Move::R,
-                                    Move::Rp,
-                                    Move::F,
-                                    Move::Fp,
-                                    Move::D,
-                                    Move::Dp,
-                                    Move::L,
-                                    Move::Lp,
-                                    Move::B,
-                                    Move::Bp,
-                                ];
-                                if move_num >= MOVES.len() {
+            diagnostic!(trace, packet = ?value.value, "Received notification");
+
+            let message = match cipher_copy
+                .decrypt(&value.value)
+                .and_then(|value| decode_gan_v2_message(&value))
+            {
+                Ok(message) => message,
+                Err(error) => {
+                    *synced_copy.lock().unwrap() = false;
+                    move_listener(BluetoothCubeEvent::Error(error.to_string()));
+                    return;
+                }
+            };
+            match message {
+                GanV2Message::Moves {
+                    move_count: current_move_count,
+                    moves: move_history,
+                } => {
+                    // If we haven't received a cube state message yet, we can't know what
+                    // the curent cube state is. Ignore moves until the cube state message
+                    // is received. If there has been a cube state message, we will have
+                    // a last move count and we can continue.
+                    let mut last_move_count_option = last_move_count.lock().unwrap();
+                    if let Some(last_move_count) = *last_move_count_option {
+                        // Check number of moves since last message.
+                        let move_count = current_move_count.wrapping_sub(last_move_count) as usize;
+                        if move_count > GAN_V2_MOVE_HISTORY {
+                            // There are too many moves since the last message. Our cube
+                            // state is out of sync. Let the client know and reset the
+                            // last move count such that we don't parse any more move
+                            // messages, since they aren't valid anymore.
+                            *synced_copy.lock().unwrap() = false;
+                            *last_move_count_option = None;
+                            move_listener(BluetoothCubeEvent::Error(
+                                "Too many moves since last update".into(),
+                            ));
+                            return;
+                        }
+
+                        // Gather the moves
+                        let mut moves = Vec::with_capacity(move_count);
+                        for j in 0..move_count {
+                            // Build move list in reverse order. In the packet the moves
+                            // are from the latest move to the oldest move, but the callback
+                            // should take the moves in the order they happened.
+                            let i = (move_count - 1) - j;
+                            let mv = match &move_history[i] {
+                                Some(mv) => mv.clone(),
+                                None => {
                                     // Bad move data. Cube is now desynced.
                                     *synced_copy.lock().unwrap() = false;
                                     *last_move_count_option = None;
+                                    move_listener(BluetoothCubeEvent::Error(
+                                        "Invalid move in move message".into(),
+                                    ));
                                     return;
                                 }
-                                let mv = MOVES[move_num];
-                                moves.push(TimedMove::new(mv, move_time));
-
-                                // Apply move to the cube state.
-                                state_copy.lock().unwrap().do_move(mv);
-                            }
-
-                            *last_move_count_option = Some(current_move_count);
-
-                            if moves.len() != 0 {
-                                // Let clients know there is a new move
-                                move_listener(BluetoothCubeEvent::Move(
-                                    moves,
-                                    state_copy.lock().unwrap().clone(),
-                                ));
-                            }
-                        }
-                    }
-                    Self::CUBE_STATE_MESSAGE => {
-                        *last_move_count.lock().unwrap() =
-                            Some(Self::extract_bits(&value, 4, 8) as u8);
-
-                        // Set up corner and edge state
-                        let mut corners = [0; 8];
-                        let mut corner_twist = [0; 8];
-                        let mut corners_left: HashSet<u32> =
-                            HashSet::from_iter((&[0, 1, 2, 3, 4, 5, 6, 7]).iter().cloned());
-                        let mut edges = [0; 12];
-                        let mut edge_parity = [0; 12];
-                        let mut edges_left: HashSet<u32> = HashSet::from_iter(
-                            (&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]).iter().cloned(),
-                        );
-                        let mut total_corner_twist = 0;
-                        let mut total_edge_parity = 0;
-
-                        // Decode corners. There are only 7 in the packet because the
-                        // last one is implicit (the one missing).
-                        for i in 0..7 {
-                            corners[i] = Self::extract_bits(&value, 12 + i * 3, 3);
-                            corner_twist[i] = Self::extract_bits(&value, 33 + i * 2, 2);
-                            total_corner_twist += corner_twist[i];
-                            if !corners_left.remove(&corners[i]) || corner_twist[i] >= 3 {
-                                return;
-                            }
-                        }
+                            };
 
-                        // Decode edges. There are only 11 in the packet because the
-                        // last one is implicit (the one missing).
-                        for i in 0..11 {
-                            edges[i] = Self::extract_bits(&value, 47 + i * 4, 4);
-                            edge_parity[i] = Self::extract_bits(&value, 91 + i, 1);
-                            total_edge_parity += edge_parity[i];
-                            if !edges_left.remove(&edges[i]) || edge_parity[i] >= 2 {
-                                return;
-                            }
+                            // Apply move to the cube state.
+                            state_copy.lock().unwrap().do_move(mv.move_());
+                            moves.push(mv);
                         }
 
-                        // Add in the missing corner and edge based on the last one
-                        // left. There will always be exactly one left since we
-                        // already verified each corner and edge was unique.
-                        corners[7] = *corners_left.iter().next().unwrap();
-                        edges[11] = *edges_left.iter().next().unwrap();
-
-                        // Compute the corner twist and edge parity of the last corner
-                        // and edge piece. The corner twist must be a multiple of 3 and
-                        // the edge parity must be even.
-                        corner_twist[7] = (3 - total_corner_twist % 3) % 3;
-                        edge_parity[11] = total_edge_parity & 1;
-
-                        // Create cube state. Our representation of the cube state matches
-                        // the one used in the packet. We have already verified the data
-                        // is valid so we can unwrap the conversions with panic.
-                        let mut corner_pieces = Vec::with_capacity(8);
-                        let mut edge_pieces = Vec::with_capacity(12);
-                        for i in 0..8 {
-                            corner_pieces.push(CornerPiece {
-                                piece: Corner::try_from(corners[i] as u8).unwrap(),
-                                orientation: corner_twist[i] as u8,
-                            });
-                        }
-                        for i in 0..12 {
-                            edge_pieces.push(EdgePiece3x3x3 {
-                                piece: Edge3x3x3::try_from(edges[i] as u8).unwrap(),
-                                orientation: edge_parity[i] as u8,
-                            });
-                        }
+                        *last_move_count_option = Some(current_move_count);
 
-                        let cube = Cube3x3x3::from_corners_and_edges(
-                            corner_pieces.try_into().unwrap(),
-                            edge_pieces.try_into().unwrap(),
-                        );
-
-                        *state_copy.lock().unwrap() = cube;
-                        *state_set_copy.lock().unwrap() = true;
-                    }
-                    Self::BATTERY_STATE_MESSAGE => {
-                        *battery_charging_copy.lock().unwrap() =
-                            Some(Self::extract_bits(&value, 4, 4) != 0);
-                        *battery_percentage_copy.lock().unwrap() =
-                            Some(Self::extract_bits(&value, 8, 8));
+                        if moves.len() != 0 {
+                            // Let clients know there is a new move
+                            move_listener(BluetoothCubeEvent::Move(
+                                moves,
+                                state_copy.lock().unwrap().clone(),
+                            ));
+                        }
                     }
-                    _ => (),
                 }
+                GanV2Message::CubeState { move_count, state } => {
+                    *last_move_count.lock().unwrap() = Some(move_count);
+                    *state_copy.lock().unwrap() = state;
+                    *state_set_copy.lock().unwrap() = true;
+                }
+                GanV2Message::Battery {
+                    charging,
+                    percentage,
+                } => {
+                    *battery_charging_copy.lock().unwrap() = Some(charging);
+                    *battery_percentage_copy.lock().unwrap() = Some(percentage);
+                }
+                GanV2Message::Orientation(orientation) => {
+                    move_listener(BluetoothCubeEvent::Orientation(orientation));
+                }
+                GanV2Message::Unknown => (),
             }
         }));
         device.subscribe(&read)?;
@@ -623,20 +534,21 @@ impl<P: Peripheral> GANCubeVersion2<P> {
             synced,
             cipher,
             write,
+            info: DeviceInfo {
+                device_id: Some(DeviceInfo::format_id(&device_key)),
+                ..DeviceInfo::new(
+                    "GAN version 2",
+                    DeviceFeatures {
+                        cube_state: true,
+                        reset_cube_state: true,
+                        battery: true,
+                        orientation: true,
+                        timer: false,
+                    },
+                )
+            },
         })
     }
-
-    fn extract_bits(data: &[u8], start: usize, count: usize) -> u32 {
-        let mut result = 0;
-        for i in 0..count {
-            let bit = start + i;
-            result <<= 1;
-            if data[bit / 8] & (1 << (7 - (bit % 8))) != 0 {
-                result |= 1;
-            }
-        }
-        result
-    }
 }
 
 impl GANCubeVersion2Cipher {
@@ -705,7 +617,11 @@ impl GANCubeVersion2Cipher {
     }
 }
 
-impl<P: Peripheral> BluetoothCubeDevice for GANCubeVersion2<P> {
+impl<P: CubePeripheral> BluetoothCubeDevice for GANCubeVersion2<P> {
+    fn device_info(&self) -> DeviceInfo {
+        self.info.clone()
+    }
+
     fn cube_state(&self) -> Cube3x3x3 {
         self.state.lock().unwrap().clone()
     }
@@ -759,40 +675,75 @@ impl<P: Peripheral> BluetoothCubeDevice for GANCubeVersion2<P> {
     }
 }
 
-impl<P: Peripheral> GANSmartTimer<P> {
+impl<P: CubePeripheral> GANSmartTimer<P> {
     pub fn new(
         device: P,
         updates: Characteristic,
         move_listener: Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>,
+        firmware_version: Option<String>,
     ) -> Result<Self> {
+        let move_listener = Arc::new(Mutex::new(move_listener));
+        let running_since = Arc::new(Mutex::new(None));
+
+        let move_listener_copy = move_listener.clone();
+        let running_since_copy = running_since.clone();
         device.on_notification(Box::new(move |value| {
-            if value.value.len() >= 4 {
-                match value.value[3] {
-                    1 => move_listener(BluetoothCubeEvent::TimerReady),
-                    2 => move_listener(BluetoothCubeEvent::TimerStartCancel),
-                    3 => move_listener(BluetoothCubeEvent::TimerStarted),
-                    4 => {
-                        if value.value.len() >= 8 {
-                            let min = value.value[4] as u32;
-                            let sec = value.value[5] as u32;
-                            let msec = ((value.value[7] as u32) << 8) | (value.value[6] as u32);
-                            move_listener(BluetoothCubeEvent::TimerFinished(
-                                min * 60000 + sec * 1000 + msec,
-                            ));
-                        }
-                    }
-                    6 => move_listener(BluetoothCubeEvent::HandsOnTimer),
-                    _ => (),
+            diagnostic!(trace, packet = ?value.value, "Received notification");
+
+            let message = match decode_gan_timer_message(&value.value) {
+                Ok(message) => message,
+                Err(_error) => {
+                    // Timers have no state to desync, so drop invalid packets
+                    diagnostic!(warn, "Invalid timer packet: {}", _error);
+                    return;
                 }
+            };
+
+            let move_listener = move_listener_copy.lock().unwrap();
+            match message {
+                GanTimerMessage::GetSet => move_listener(BluetoothCubeEvent::TimerReady),
+                GanTimerMessage::HandsOff | GanTimerMessage::Idle => {
+                    *running_since_copy.lock().unwrap() = None;
+                    move_listener(BluetoothCubeEvent::TimerStartCancel);
+                }
+                GanTimerMessage::Running => {
+                    *running_since_copy.lock().unwrap() = Some(Instant::now());
+                    move_listener(BluetoothCubeEvent::TimerStarted);
+                }
+                GanTimerMessage::Stopped(time) => {
+                    *running_since_copy.lock().unwrap() = None;
+                    move_listener(BluetoothCubeEvent::TimerFinished(time));
+                }
+                GanTimerMessage::HandsOn => move_listener(BluetoothCubeEvent::HandsOnTimer),
+                GanTimerMessage::Finished | GanTimerMessage::Unknown => (),
             }
         }));
         device.subscribe(&updates)?;
 
-        Ok(GANSmartTimer { device })
+        Ok(GANSmartTimer {
+            device,
+            move_listener,
+            running_since,
+            start_latency: AtomicU32::new(0),
+            info: DeviceInfo {
+                firmware_version,
+                ..DeviceInfo::new(
+                    "GAN smart timer",
+                    DeviceFeatures {
+                        timer: true,
+                        ..Default::default()
+                    },
+                )
+            },
+        })
     }
 }
 
-impl<P: Peripheral> BluetoothCubeDevice for GANSmartTimer<P> {
+impl<P: CubePeripheral> BluetoothCubeDevice for GANSmartTimer<P> {
+    fn device_info(&self) -> DeviceInfo {
+        self.info.clone()
+    }
+
     fn timer_only(&self) -> bool {
         true
     }
@@ -810,7 +761,22 @@ impl<P: Peripheral> BluetoothCubeDevice for GANSmartTimer<P> {
     }
 
     fn reset_cube_state(&self) {}
-    fn update(&self) {}
+
+    fn update(&self) {
+        // The timer does not send updates while running, so poll the time since
+        // the start packet to mirror the running display. The start packet arrives
+        // after the timer has started, so add the configured start latency.
+        let running_since = *self.running_since.lock().unwrap();
+        if let Some(start) = running_since {
+            let time = (Instant::now() - start).as_millis() as u32
+                + self.start_latency.load(Ordering::SeqCst);
+            (self.move_listener.lock().unwrap())(BluetoothCubeEvent::TimerRunning(time));
+        }
+    }
+
+    fn set_timer_start_latency(&self, latency: u32) {
+        self.start_latency.store(latency, Ordering::SeqCst);
+    }
 
     fn synced(&self) -> bool {
         true
@@ -821,7 +787,7 @@ impl<P: Peripheral> BluetoothCubeDevice for GANSmartTimer<P> {
     }
 }
 
-pub(crate) fn gan_cube_connect<P: Peripheral + 'static>(
+pub(crate) fn gan_cube_connect<P: CubePeripheral + 'static>(
     device: P,
     move_listener: Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>,
 ) -> Result<Box<dyn BluetoothCubeDevice>> {
@@ -895,18 +861,21 @@ pub(crate) fn gan_cube_connect<P: Peripheral + 'static>(
         }
         let major = version[0];
         let minor = version[1];
+        let firmware_version = format!("{}.{}.{}", major, minor, version[2]);
         if major == 1 && minor <= 1 {
             Ok(Box::new(GANCubeVersion1::new(
                 device,
                 characteristics,
                 move_listener,
                 minor,
+                firmware_version,
             )?))
         } else if major == 2 && minor == 0 {
             Ok(Box::new(GANSmartTimer::new(
                 device,
                 characteristics.last_moves,
                 move_listener,
+                Some(firmware_version),
             )?))
         } else {
             Err(anyhow!(
@@ -922,6 +891,24 @@ pub(crate) fn gan_cube_connect<P: Peripheral + 'static>(
             v2_write.unwrap(),
             move_listener,
         )?))
+    } else if v1_last_moves.is_some() && v1_timing.is_none() && v1_battery.is_none() {
+        // Newer smart timers such as the GAN Halo only have the timer state
+        // characteristic, and may not report a version
+        let firmware_version = match &v1_version {
+            Some(characteristic) => match device.read(characteristic) {
+                Ok(version) if version.len() >= 3 => {
+                    Some(format!("{}.{}.{}", version[0], version[1], version[2]))
+                }
+                _ => None,
+            },
+            None => None,
+        };
+        Ok(Box::new(GANSmartTimer::new(
+            device,
+            v1_last_moves.unwrap(),
+            move_listener,
+            firmware_version,
+        )?))
     } else {
         Err(anyhow!("Unrecognized GAN cube version"))
     }
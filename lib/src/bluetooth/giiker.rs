@@ -1,26 +1,23 @@
-use crate::bluetooth::{BluetoothCubeDevice, BluetoothCubeEvent};
-use crate::common::{Cube, InitialCubeState, Move, TimedMove};
+use crate::bluetooth::peripheral::CubePeripheral;
+use crate::bluetooth::{BluetoothCubeDevice, BluetoothCubeEvent, DeviceFeatures, DeviceInfo};
+use crate::common::{Cube, InitialCubeState, TimedMove};
 use crate::cube3x3x3::Cube3x3x3;
+use crate::packet::decode_giiker_move;
+use crate::timeline::DeviceClock;
 use anyhow::{anyhow, Result};
-use btleplug::api::{Characteristic, Peripheral};
+use btleplug::api::Characteristic;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use uuid::Uuid;
 
-struct GiikerCube<P: Peripheral + 'static> {
+struct GiikerCube<P: CubePeripheral + 'static> {
     device: P,
     state: Arc<Mutex<Cube3x3x3>>,
     synced: Arc<Mutex<bool>>,
 }
 
-impl<P: Peripheral + 'static> GiikerCube<P> {
-    const KEY_STREAM: &'static [u8] = &[
-        0xb0, 0x51, 0x68, 0xe0, 0x56, 0x89, 0xed, 0x77, 0x26, 0x1a, 0xc1, 0xa1, 0xd2, 0x7e, 0x96,
-        0x51, 0x5d, 0x0d, 0xec, 0xf9, 0x59, 0xeb, 0x58, 0x18, 0x71, 0x51, 0xd6, 0x83, 0x82, 0xc7,
-        0x02, 0xa9, 0x27, 0xa5, 0xab, 0x29,
-    ];
-
+impl<P: CubePeripheral + 'static> GiikerCube<P> {
     pub fn new(
         device: P,
         move_data: Characteristic,
@@ -38,12 +35,16 @@ impl<P: Peripheral + 'static> GiikerCube<P> {
         let synced = Arc::new(Mutex::new(true));
         let synced_copy = synced.clone();
         let start_time = Mutex::new(Instant::now());
-        let last_move_time = Mutex::new(0);
+        let clock = Mutex::new(DeviceClock::host());
 
         device.on_notification(Box::new(move |value| {
-            let mut value = value.value.clone();
-            if value.len() < 20 {
+            diagnostic!(trace, packet = ?value.value, "Received notification");
+
+            if value.value.len() < 20 {
                 *synced_copy.lock().unwrap() = false;
+                move_listener(BluetoothCubeEvent::Error(
+                    "Giiker move packet is too short".into(),
+                ));
                 return;
             }
 
@@ -53,39 +54,11 @@ impl<P: Peripheral + 'static> GiikerCube<P> {
                 return;
             }
 
-            // Check for encoded packets
-            if value[18] == 0xa7 {
-                let key_offset_a = (value[19] >> 4) as usize;
-                let key_offset_b = (value[19] & 0xf) as usize;
-                for i in 0..18 {
-                    value[i] = value[i].wrapping_add(
-                        Self::KEY_STREAM[i + key_offset_a]
-                            .wrapping_add(Self::KEY_STREAM[i + key_offset_b]),
-                    );
-                }
-            }
-
-            let mv = match value[16] {
-                0x11 => Move::B,
-                0x12 => Move::B2,
-                0x13 => Move::Bp,
-                0x21 => Move::D,
-                0x22 => Move::D2,
-                0x23 => Move::Dp,
-                0x31 => Move::L,
-                0x32 => Move::L2,
-                0x33 => Move::Lp,
-                0x41 => Move::U,
-                0x42 => Move::U2,
-                0x43 => Move::Up,
-                0x51 => Move::R,
-                0x52 => Move::R2,
-                0x53 => Move::Rp,
-                0x61 => Move::F,
-                0x62 => Move::F2,
-                0x63 => Move::Fp,
-                _ => {
+            let mv = match decode_giiker_move(&value.value) {
+                Ok(mv) => mv,
+                Err(error) => {
                     *synced_copy.lock().unwrap() = false;
+                    move_listener(BluetoothCubeEvent::Error(error.to_string()));
                     return;
                 }
             };
@@ -96,12 +69,11 @@ impl<P: Peripheral + 'static> GiikerCube<P> {
             // Get time since last move. Keep computation relative to start time so
             // that rounding errors don't cause errors in the total time.
             let current_time = (Instant::now() - *start_time.lock().unwrap()).as_millis();
-            let move_time = current_time - *last_move_time.lock().unwrap();
-            *last_move_time.lock().unwrap() = current_time;
+            let move_time = clock.lock().unwrap().elapsed(current_time as u64);
 
             // Let clients know there is a new move
             move_listener(BluetoothCubeEvent::Move(
-                vec![TimedMove::new(mv, move_time as u32)],
+                vec![TimedMove::new(mv, move_time)],
                 state_copy.lock().unwrap().clone(),
             ));
         }));
@@ -115,7 +87,12 @@ impl<P: Peripheral + 'static> GiikerCube<P> {
     }
 }
 
-impl<P: Peripheral + 'static> BluetoothCubeDevice for GiikerCube<P> {
+impl<P: CubePeripheral + 'static> BluetoothCubeDevice for GiikerCube<P> {
+    fn device_info(&self) -> DeviceInfo {
+        // Writes are not supported, so only moves are available
+        DeviceInfo::new("Giiker", DeviceFeatures::default())
+    }
+
     fn cube_state(&self) -> Cube3x3x3 {
         self.state.lock().unwrap().clone()
     }
@@ -144,7 +121,7 @@ impl<P: Peripheral + 'static> BluetoothCubeDevice for GiikerCube<P> {
     }
 }
 
-pub(crate) fn giiker_connect<P: Peripheral + 'static>(
+pub(crate) fn giiker_connect<P: CubePeripheral + 'static>(
     device: P,
     move_listener: Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>,
 ) -> Result<Box<dyn BluetoothCubeDevice>> {
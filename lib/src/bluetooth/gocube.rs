@@ -1,14 +1,17 @@
-use crate::bluetooth::{BluetoothCubeDevice, BluetoothCubeEvent};
-use crate::common::{Color, Cube, CubeFace, InitialCubeState, Move, TimedMove};
-use crate::cube3x3x3::{Cube3x3x3, Cube3x3x3Faces};
+use crate::bluetooth::peripheral::CubePeripheral;
+use crate::bluetooth::{BluetoothCubeDevice, BluetoothCubeEvent, DeviceFeatures, DeviceInfo};
+use crate::common::{Cube, InitialCubeState, TimedMove};
+use crate::cube3x3x3::Cube3x3x3;
+use crate::packet::{decode_gocube_cube_state, decode_gocube_moves, decode_gocube_orientation};
+use crate::timeline::DeviceClock;
 use anyhow::{anyhow, Result};
-use btleplug::api::{Characteristic, Peripheral, WriteType};
+use btleplug::api::{Characteristic, WriteType};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 
-struct GoCube<P: Peripheral + 'static> {
+struct GoCube<P: CubePeripheral + 'static> {
     device: P,
     state: Arc<Mutex<Cube3x3x3>>,
     battery_percentage: Arc<Mutex<Option<u32>>>,
@@ -16,15 +19,16 @@ struct GoCube<P: Peripheral + 'static> {
     write: Characteristic,
 }
 
-impl<P: Peripheral + 'static> GoCube<P> {
+impl<P: CubePeripheral + 'static> GoCube<P> {
     const ROTATE_MESSAGE: u8 = 0x01;
     const STATE_MESSAGE: u8 = 0x02;
+    const ORIENTATION_MESSAGE: u8 = 0x03;
     const BATTERY_MESSAGE: u8 = 0x05;
 
     const REQUEST_BATTERY_MESSAGE: u8 = 0x32;
     const REQUEST_STATE_MESSAGE: u8 = 0x33;
     const RESET_STATE_MESSAGE: u8 = 0x35;
-    const DISABLE_ORIENTATION_MESSAGE: u8 = 0x37;
+    const ENABLE_ORIENTATION_MESSAGE: u8 = 0x38;
 
     const CUBE_STATE_TIMEOUT_MS: usize = 2000;
 
@@ -44,58 +48,45 @@ impl<P: Peripheral + 'static> GoCube<P> {
         let battery_percentage_copy = battery_percentage.clone();
         let synced_copy = synced.clone();
         let start_time = Mutex::new(Instant::now());
-        let last_move_time = Mutex::new(0);
+        let clock = Mutex::new(DeviceClock::host());
 
         device.on_notification(Box::new(move |value| {
-            if value.value.len() < 4 {
+            diagnostic!(trace, packet = ?value.value, "Received notification");
+
+            let desync = |error: String| {
                 *synced_copy.lock().unwrap() = false;
+                move_listener(BluetoothCubeEvent::Error(error));
+            };
+
+            if value.value.len() < 4 || value.value[1] < 4 {
+                desync("GoCube message is too short".into());
                 return;
             }
             if value.value.len() < value.value[1] as usize {
-                *synced_copy.lock().unwrap() = false;
-                return;
-            }
-            if value.value[1] < 4 {
-                *synced_copy.lock().unwrap() = false;
+                desync("GoCube message is truncated".into());
                 return;
             }
 
             match value.value[2] {
                 Self::ROTATE_MESSAGE => {
                     let count = (value.value[1] as usize - 4) / 2;
-                    let mut moves = Vec::new();
-                    for i in 0..count {
-                        let move_idx = value.value[3 + i * 2] as usize;
-                        let mv = match move_idx {
-                            0 => Move::B,
-                            1 => Move::Bp,
-                            2 => Move::F,
-                            3 => Move::Fp,
-                            4 => Move::U,
-                            5 => Move::Up,
-                            6 => Move::D,
-                            7 => Move::Dp,
-                            8 => Move::R,
-                            9 => Move::Rp,
-                            0xa => Move::L,
-                            0xb => Move::Lp,
-                            _ => {
-                                *synced_copy.lock().unwrap() = false;
-                                return;
-                            }
-                        };
-
-                        // Apply move to the cube state.
-                        state_copy.lock().unwrap().do_move(mv);
-
-                        moves.push(mv);
+                    let moves = match decode_gocube_moves(&value.value[3..3 + count * 2]) {
+                        Ok(moves) => moves,
+                        Err(error) => {
+                            desync(error.to_string());
+                            return;
+                        }
+                    };
+
+                    // Apply moves to the cube state.
+                    for mv in &moves {
+                        state_copy.lock().unwrap().do_move(*mv);
                     }
 
                     // Get time since last move. Keep computation relative to start time so
                     // that rounding errors don't cause errors in the total time.
                     let current_time = (Instant::now() - *start_time.lock().unwrap()).as_millis();
-                    let move_time = (current_time - *last_move_time.lock().unwrap()) as u32;
-                    *last_move_time.lock().unwrap() = current_time;
+                    let move_time = clock.lock().unwrap().elapsed(current_time as u64);
 
                     let mut timed_moves = Vec::new();
                     for (idx, mv) in moves.iter().enumerate() {
@@ -110,15 +101,23 @@ impl<P: Peripheral + 'static> GoCube<P> {
                 }
                 Self::STATE_MESSAGE => {
                     if value.value.len() < 64 {
-                        *synced_copy.lock().unwrap() = false;
+                        desync("GoCube cube state is too short".into());
                         return;
                     }
 
-                    if let Ok(state) = Self::decode_cube_state(&value.value) {
-                        *state_copy.lock().unwrap() = state;
-                        *state_set_copy.lock().unwrap() = true;
-                    } else {
-                        *synced_copy.lock().unwrap() = false;
+                    match decode_gocube_cube_state(&value.value) {
+                        Ok(state) => {
+                            *state_copy.lock().unwrap() = state;
+                            *state_set_copy.lock().unwrap() = true;
+                        }
+                        Err(error) => desync(error.to_string()),
+                    }
+                }
+                Self::ORIENTATION_MESSAGE => {
+                    if let Some(orientation) =
+                        decode_gocube_orientation(&value.value[3..value.value[1] as usize - 1])
+                    {
+                        move_listener(BluetoothCubeEvent::Orientation(orientation));
                     }
                 }
                 Self::BATTERY_MESSAGE => {
@@ -129,10 +128,11 @@ impl<P: Peripheral + 'static> GoCube<P> {
         }));
         device.subscribe(&read)?;
 
-        // Turn off orientation messages
+        // Turn on orientation messages, these are used to detect when the cube
+        // has been set down to arm the timer
         device.write(
             &write,
-            &[Self::DISABLE_ORIENTATION_MESSAGE],
+            &[Self::ENABLE_ORIENTATION_MESSAGE],
             WriteType::WithResponse,
         )?;
 
@@ -172,60 +172,22 @@ impl<P: Peripheral + 'static> GoCube<P> {
             write,
         })
     }
+}
 
-    fn decode_cube_state(data: &[u8]) -> Result<Cube3x3x3> {
-        const FACES: [CubeFace; 6] = [
-            CubeFace::Back,
-            CubeFace::Front,
-            CubeFace::Top,
-            CubeFace::Bottom,
-            CubeFace::Right,
-            CubeFace::Left,
-        ];
-        const COLORS: [Color; 6] = [
-            Color::Blue,
-            Color::Green,
-            Color::White,
-            Color::Yellow,
-            Color::Red,
-            Color::Orange,
-        ];
-        const ORDER: [usize; 8] = [
-            0 * 3 + 0,
-            0 * 3 + 1,
-            0 * 3 + 2,
-            1 * 3 + 2,
-            2 * 3 + 2,
-            2 * 3 + 1,
-            2 * 3 + 0,
-            1 * 3 + 0,
-        ];
-        const ORDER_OFFSET: [usize; 6] = [0, 0, 6, 2, 0, 0];
-        let mut state: [Color; 6 * 9] = [Color::White; 6 * 9];
-
-        for face in 0..6 {
-            // Find face index in our representation using mapping
-            let target_face_idx = FACES[face] as u8 as usize;
-
-            // Place colors into the cube state array
-            let offset = target_face_idx * 9;
-            state[offset + 1 * 3 + 1] = COLORS[face];
-            for i in 0..8 {
-                let color_idx = data[4 + face * 9 + i];
-                if color_idx >= 6 {
-                    return Err(anyhow!("Invalid cube state"));
-                }
-
-                state[offset + ORDER[(i + ORDER_OFFSET[face]) % 8]] = COLORS[color_idx as usize];
-            }
-        }
-
-        // Create cube state and convert to normal format
-        Ok(Cube3x3x3Faces::from_colors(state).as_pieces())
+impl<P: CubePeripheral> BluetoothCubeDevice for GoCube<P> {
+    fn device_info(&self) -> DeviceInfo {
+        DeviceInfo::new(
+            "GoCube",
+            DeviceFeatures {
+                cube_state: true,
+                reset_cube_state: true,
+                battery: true,
+                orientation: true,
+                timer: false,
+            },
+        )
     }
-}
 
-impl<P: Peripheral> BluetoothCubeDevice for GoCube<P> {
     fn cube_state(&self) -> Cube3x3x3 {
         self.state.lock().unwrap().clone()
     }
@@ -258,7 +220,7 @@ impl<P: Peripheral> BluetoothCubeDevice for GoCube<P> {
     }
 }
 
-pub(crate) fn gocube_connect<P: Peripheral + 'static>(
+pub(crate) fn gocube_connect<P: CubePeripheral + 'static>(
     device: P,
     move_listener: Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>,
 ) -> Result<Box<dyn BluetoothCubeDevice>> {
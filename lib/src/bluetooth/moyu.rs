@@ -1,28 +1,21 @@
-use crate::bluetooth::{BluetoothCubeDevice, BluetoothCubeEvent};
-use crate::common::{Cube, CubeFace, InitialCubeState, Move, TimedMove};
+use crate::bluetooth::peripheral::CubePeripheral;
+use crate::bluetooth::{BluetoothCubeDevice, BluetoothCubeEvent, DeviceFeatures, DeviceInfo};
+use crate::common::{Cube, InitialCubeState};
 use crate::cube3x3x3::Cube3x3x3;
+use crate::packet::{decode_moyu_turns, MoYuMoveTracker};
 use anyhow::{anyhow, Result};
-use btleplug::api::{Characteristic, Peripheral};
+use btleplug::api::Characteristic;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
-struct MoYuCube<P: Peripheral + 'static> {
+struct MoYuCube<P: CubePeripheral + 'static> {
     device: P,
     state: Arc<Mutex<Cube3x3x3>>,
     synced: Arc<Mutex<bool>>,
 }
 
-impl<P: Peripheral + 'static> MoYuCube<P> {
-    const FACES: [CubeFace; 6] = [
-        CubeFace::Bottom,
-        CubeFace::Left,
-        CubeFace::Back,
-        CubeFace::Right,
-        CubeFace::Front,
-        CubeFace::Top,
-    ];
-
+impl<P: CubePeripheral + 'static> MoYuCube<P> {
     pub fn new(
         device: P,
         turn: Characteristic,
@@ -35,62 +28,28 @@ impl<P: Peripheral + 'static> MoYuCube<P> {
 
         let state_copy = state.clone();
         let synced_copy = synced.clone();
-        let mut last_move_time = None;
         let turn_uuid = turn.uuid.clone();
-        let mut face_rotations: [i8; 6] = [0, 0, 0, 0, 0, 0];
+        let mut tracker = MoYuMoveTracker::new();
 
         device.on_notification(Box::new(move |value| {
-            if value.uuid == turn_uuid {
-                // Get count of turn reports and check lengths
-                if value.value.len() < 1 {
-                    *synced_copy.lock().unwrap() = false;
-                    return;
-                }
-                let count = value.value[0];
-                if value.value.len() < 1 + count as usize * 6 {
-                    *synced_copy.lock().unwrap() = false;
-                    return;
-                }
+            diagnostic!(trace, packet = ?value.value, "Received notification");
 
-                // Parse each turn report
-                for i in 0..count {
-                    let offset = 1 + i as usize * 6;
-                    let turn = &value.value[offset..offset + 6];
-                    let timestamp = (((turn[1] as u32) << 24)
-                        | ((turn[0] as u32) << 16)
-                        | ((turn[3] as u32) << 8)
-                        | (turn[2] as u32)) as f64
-                        / 65536.0;
-                    let face = turn[4];
-                    let direction = turn[5] as i8 / 36;
-
-                    // Decode face rotation into moves
-                    let old_rotation = face_rotations[face as usize];
-                    let new_rotation = old_rotation + direction;
-                    face_rotations[face as usize] = (new_rotation + 9) % 9;
-                    let mv = if old_rotation >= 5 && new_rotation <= 4 {
-                        Some(Move::from_face_and_rotation(Self::FACES[face as usize], -1).unwrap())
-                    } else if old_rotation <= 4 && new_rotation >= 5 {
-                        Some(Move::from_face_and_rotation(Self::FACES[face as usize], 1).unwrap())
-                    } else {
-                        None
-                    };
-
-                    if let Some(mv) = mv {
-                        // There was a move, get time since last move
-                        let prev_move_time = if let Some(time) = last_move_time {
-                            time
-                        } else {
-                            timestamp
-                        };
-                        let time_passed = timestamp - prev_move_time;
-                        let time_passed_ms = (time_passed * 1000.0) as u32;
-                        last_move_time = Some(prev_move_time + time_passed_ms as f64 / 1000.0);
+            if value.uuid == turn_uuid {
+                let turns = match decode_moyu_turns(&value.value) {
+                    Ok(turns) => turns,
+                    Err(error) => {
+                        *synced_copy.lock().unwrap() = false;
+                        move_listener(BluetoothCubeEvent::Error(error.to_string()));
+                        return;
+                    }
+                };
 
+                for turn in turns {
+                    if let Some(mv) = tracker.turn(&turn) {
                         // Report the new move
-                        state_copy.lock().unwrap().do_move(mv);
+                        state_copy.lock().unwrap().do_move(mv.move_());
                         move_listener(BluetoothCubeEvent::Move(
-                            vec![TimedMove::new(mv, time_passed_ms)],
+                            vec![mv],
                             state_copy.lock().unwrap().clone(),
                         ));
                     }
@@ -112,7 +71,11 @@ impl<P: Peripheral + 'static> MoYuCube<P> {
     }
 }
 
-impl<P: Peripheral> BluetoothCubeDevice for MoYuCube<P> {
+impl<P: CubePeripheral> BluetoothCubeDevice for MoYuCube<P> {
+    fn device_info(&self) -> DeviceInfo {
+        DeviceInfo::new("MoYu", DeviceFeatures::default())
+    }
+
     fn cube_state(&self) -> Cube3x3x3 {
         self.state.lock().unwrap().clone()
     }
@@ -138,7 +101,7 @@ impl<P: Peripheral> BluetoothCubeDevice for MoYuCube<P> {
     }
 }
 
-pub(crate) fn moyu_connect<P: Peripheral + 'static>(
+pub(crate) fn moyu_connect<P: CubePeripheral + 'static>(
     device: P,
     move_listener: Box<dyn Fn(BluetoothCubeEvent) + Send + 'static>,
 ) -> Result<Box<dyn BluetoothCubeDevice>> {
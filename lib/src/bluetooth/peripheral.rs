@@ -0,0 +1,201 @@
+use anyhow::Result;
+use btleplug::api::{Characteristic, Peripheral, WriteType};
+use uuid::Uuid;
+
+/// Notification received from a characteristic the device is subscribed to
+pub(crate) struct Notification {
+    pub uuid: Uuid,
+    pub value: Vec<u8>,
+}
+
+pub(crate) type NotificationHandler = Box<dyn FnMut(Notification) + Send>;
+
+/// Operations the cube backends need from a connected Bluetooth device. This
+/// is implemented for every `btleplug` peripheral, and exists so that the
+/// backends can be driven by a scripted device in tests.
+pub(crate) trait CubePeripheral: Send + Sync {
+    fn discover_characteristics(&self) -> Result<Vec<Characteristic>>;
+    fn read(&self, characteristic: &Characteristic) -> Result<Vec<u8>>;
+    fn write(
+        &self,
+        characteristic: &Characteristic,
+        data: &[u8],
+        write_type: WriteType,
+    ) -> Result<()>;
+    fn subscribe(&self, characteristic: &Characteristic) -> Result<()>;
+    fn on_notification(&self, handler: NotificationHandler);
+    /// Manufacturer specific data from the advertisement with the given
+    /// company identifier
+    fn manufacturer_data(&self, id: u16) -> Option<Vec<u8>>;
+    fn disconnect(&self) -> Result<()>;
+}
+
+impl<P: Peripheral> CubePeripheral for P {
+    fn discover_characteristics(&self) -> Result<Vec<Characteristic>> {
+        Ok(Peripheral::discover_characteristics(self)?)
+    }
+
+    fn read(&self, characteristic: &Characteristic) -> Result<Vec<u8>> {
+        Ok(Peripheral::read(self, characteristic)?)
+    }
+
+    fn write(
+        &self,
+        characteristic: &Characteristic,
+        data: &[u8],
+        write_type: WriteType,
+    ) -> Result<()> {
+        Ok(Peripheral::write(self, characteristic, data, write_type)?)
+    }
+
+    fn subscribe(&self, characteristic: &Characteristic) -> Result<()> {
+        Ok(Peripheral::subscribe(self, characteristic)?)
+    }
+
+    fn on_notification(&self, mut handler: NotificationHandler) {
+        Peripheral::on_notification(
+            self,
+            Box::new(move |value| {
+                handler(Notification {
+                    uuid: value.uuid,
+                    value: value.value,
+                })
+            }),
+        );
+    }
+
+    fn manufacturer_data(&self, id: u16) -> Option<Vec<u8>> {
+        self.properties().manufacturer_data.get(&id).cloned()
+    }
+
+    fn disconnect(&self) -> Result<()> {
+        Ok(Peripheral::disconnect(self)?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) use mock::MockPeripheral;
+
+#[cfg(test)]
+mod mock {
+    use super::{CubePeripheral, Notification, NotificationHandler};
+    use anyhow::{anyhow, Result};
+    use btleplug::api::{Characteristic, WriteType};
+    use std::collections::{HashMap, VecDeque};
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+
+    /// Scripted device for testing the cube backends. Reads return queued
+    /// responses, writes are recorded, and notifications are delivered with
+    /// `notify`. Clones share the same device.
+    #[derive(Clone, Default)]
+    pub(crate) struct MockPeripheral {
+        characteristics: Vec<Characteristic>,
+        manufacturer_data: HashMap<u16, Vec<u8>>,
+        reads: Arc<Mutex<HashMap<Uuid, VecDeque<Vec<u8>>>>>,
+        writes: Arc<Mutex<Vec<(Uuid, Vec<u8>)>>>,
+        subscriptions: Arc<Mutex<Vec<Uuid>>>,
+        handler: Arc<Mutex<Option<NotificationHandler>>>,
+        disconnected: Arc<Mutex<bool>>,
+    }
+
+    impl MockPeripheral {
+        /// Creates a device with characteristics for each of the given UUIDs
+        pub fn new(uuids: &[&str]) -> Self {
+            Self {
+                characteristics: uuids
+                    .iter()
+                    .map(|uuid| Characteristic {
+                        uuid: Uuid::from_str(uuid).unwrap(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }
+        }
+
+        pub fn with_manufacturer_data(mut self, id: u16, data: &[u8]) -> Self {
+            self.manufacturer_data.insert(id, data.to_vec());
+            self
+        }
+
+        /// Queues a response for the next read of a characteristic
+        pub fn queue_read(&self, uuid: &str, data: &[u8]) {
+            self.reads
+                .lock()
+                .unwrap()
+                .entry(Uuid::from_str(uuid).unwrap())
+                .or_default()
+                .push_back(data.to_vec());
+        }
+
+        /// Delivers a notification to the registered handler
+        pub fn notify(&self, uuid: &str, data: &[u8]) {
+            if let Some(handler) = self.handler.lock().unwrap().as_mut() {
+                handler(Notification {
+                    uuid: Uuid::from_str(uuid).unwrap(),
+                    value: data.to_vec(),
+                });
+            }
+        }
+
+        pub fn writes(&self) -> Vec<(Uuid, Vec<u8>)> {
+            self.writes.lock().unwrap().clone()
+        }
+
+        pub fn subscriptions(&self) -> Vec<Uuid> {
+            self.subscriptions.lock().unwrap().clone()
+        }
+
+        pub fn disconnected(&self) -> bool {
+            *self.disconnected.lock().unwrap()
+        }
+    }
+
+    impl CubePeripheral for MockPeripheral {
+        fn discover_characteristics(&self) -> Result<Vec<Characteristic>> {
+            Ok(self.characteristics.clone())
+        }
+
+        fn read(&self, characteristic: &Characteristic) -> Result<Vec<u8>> {
+            self.reads
+                .lock()
+                .unwrap()
+                .get_mut(&characteristic.uuid)
+                .and_then(|reads| reads.pop_front())
+                .ok_or_else(|| anyhow!("No read queued for {}", characteristic.uuid))
+        }
+
+        fn write(
+            &self,
+            characteristic: &Characteristic,
+            data: &[u8],
+            _write_type: WriteType,
+        ) -> Result<()> {
+            self.writes
+                .lock()
+                .unwrap()
+                .push((characteristic.uuid, data.to_vec()));
+            Ok(())
+        }
+
+        fn subscribe(&self, characteristic: &Characteristic) -> Result<()> {
+            self.subscriptions.lock().unwrap().push(characteristic.uuid);
+            Ok(())
+        }
+
+        fn on_notification(&self, handler: NotificationHandler) {
+            *self.handler.lock().unwrap() = Some(handler);
+        }
+
+        fn manufacturer_data(&self, id: u16) -> Option<Vec<u8>> {
+            self.manufacturer_data.get(&id).cloned()
+        }
+
+        fn disconnect(&self) -> Result<()> {
+            *self.disconnected.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use crate::error::ErrorKind;
+use crate::history::History;
+use crate::rand::RandomSource;
+use crate::{CFOPAnalysis, OLLAlgorithm, PLLAlgorithm};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Local setting holding the scheduling state of each case
+const CASE_SCHEDULE_SETTING: &str = "case_schedule";
+
+/// Ease factor of a case that has not been reviewed yet
+const INITIAL_EASE: f32 = 2.5;
+/// Lowest ease factor, so that hard cases still move further out over time
+const MIN_EASE: f32 = 1.3;
+
+/// Recognition times, in milliseconds, at or below which a correct review is
+/// graded 5, 4 and 3. Slower recognition is graded 2, which counts as a lapse.
+const RECOGNITION_GRADES: [u32; 3] = [800, 1500, 2500];
+
+/// Selection weight of a case that is due now, and the most extra weight
+/// given to a case for being overdue
+const DUE_WEIGHT: u32 = 8;
+const MAX_OVERDUE_WEIGHT: u32 = 16;
+
+/// Last layer case that can be drilled in the trainer
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LastLayerCase {
+    OLL(OLLAlgorithm),
+    PLL(PLLAlgorithm),
+}
+
+/// Scheduling state of a single case, following the SM-2 algorithm. Intervals
+/// are measured in reviews across all cases instead of days, so that cases
+/// come back within a practice session.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CaseSchedule {
+    pub ease: f32,
+    /// Number of reviews until the case is due again
+    pub interval: u64,
+    /// Number of correct reviews in a row
+    pub repetitions: u32,
+    /// Review count at which the case is due
+    pub due: u64,
+}
+
+/// Result of drilling a case once
+#[derive(Clone, Copy, Debug)]
+pub struct CaseReview {
+    /// Time taken to recognize the case, in milliseconds
+    pub recognition_time: u32,
+    /// True if the case was solved incorrectly or with more than one
+    /// algorithm
+    pub failed: bool,
+}
+
+/// Spaced repetition scheduler for last layer cases. Cases that are failed or
+/// recognized slowly are due again soon, and cases that are known well are
+/// shown less and less often. The schedule is kept in local storage with
+/// `load` and `save`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CaseScheduler {
+    cases: BTreeMap<String, CaseSchedule>,
+    /// Total number of reviews, used as the clock for scheduling
+    reviews: u64,
+}
+
+impl LastLayerCase {
+    pub fn all_oll() -> Vec<Self> {
+        OLLAlgorithm::all()
+            .into_iter()
+            .map(LastLayerCase::OLL)
+            .collect()
+    }
+
+    pub fn all_pll() -> Vec<Self> {
+        PLLAlgorithm::all()
+            .into_iter()
+            .map(LastLayerCase::PLL)
+            .collect()
+    }
+
+    pub fn to_string(&self) -> String {
+        match self {
+            LastLayerCase::OLL(oll) => format!("OLL {}", oll.to_string()),
+            LastLayerCase::PLL(pll) => format!("{} Perm", pll.to_str()),
+        }
+    }
+
+    /// Parses a case name as written in algorithm sheets, such as "OLL 27",
+    /// "#27", "Sune", "Ua Perm" or "PLL T". Case is ignored. Names shared by
+    /// OLL and PLL cases, such as "H" and "T", are taken to be PLL cases
+    /// unless prefixed with "OLL".
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name
+            .trim()
+            .to_lowercase()
+            .replace(|ch: char| ch == '-' || ch == '_', " ");
+        let mut words: Vec<&str> = name.split_whitespace().collect();
+        let mut pll = false;
+        if let Some(last) = words.last() {
+            if *last == "perm" || *last == "permutation" {
+                words.pop();
+                pll = true;
+            }
+        }
+        let (set, name) = match words.split_first() {
+            Some((first, rest)) if *first == "oll" || *first == "pll" => {
+                (Some(*first), rest.concat())
+            }
+            Some((first, rest)) if first.starts_with("oll") || first.starts_with("pll") => (
+                Some(&first[..3]),
+                format!("{}{}", &first[3..], rest.concat()),
+            ),
+            _ => (None, words.concat()),
+        };
+        let name = name.trim_start_matches('#');
+
+        let oll = || match name.parse::<u8>() {
+            Ok(number) => OLLAlgorithm::all()
+                .into_iter()
+                .find(|oll| oll.as_number() == number),
+            Err(_) => OLLAlgorithm::all()
+                .into_iter()
+                .find(|oll| oll.is_cross() && oll.to_string().to_lowercase() == name),
+        };
+        let pll_case = || {
+            PLLAlgorithm::all()
+                .into_iter()
+                .find(|pll| pll.to_str().to_lowercase() == name)
+        };
+        match set {
+            Some("oll") if !pll => oll().map(LastLayerCase::OLL),
+            Some("pll") => pll_case().map(LastLayerCase::PLL),
+            None if pll => pll_case().map(LastLayerCase::PLL),
+            None => pll_case()
+                .map(LastLayerCase::PLL)
+                .or_else(|| oll().map(LastLayerCase::OLL)),
+            _ => None,
+        }
+    }
+
+    /// Name used to store the case. OLL cases are stored by number so that
+    /// the two-look names do not collide with PLL names.
+    pub(crate) fn key(&self) -> String {
+        match self {
+            LastLayerCase::OLL(oll) => format!("oll_{}", oll.as_number()),
+            LastLayerCase::PLL(pll) => format!("pll_{}", pll.to_str()),
+        }
+    }
+
+    pub(crate) fn from_key(key: &str) -> Option<Self> {
+        match key.split_once('_')? {
+            ("oll", number) => {
+                let number: u8 = number.parse().ok()?;
+                OLLAlgorithm::all()
+                    .into_iter()
+                    .find(|oll| oll.as_number() == number)
+                    .map(LastLayerCase::OLL)
+            }
+            ("pll", name) => PLLAlgorithm::all()
+                .into_iter()
+                .find(|pll| pll.to_str() == name)
+                .map(LastLayerCase::PLL),
+            _ => None,
+        }
+    }
+}
+
+impl CaseSchedule {
+    fn new() -> Self {
+        Self {
+            ease: INITIAL_EASE,
+            interval: 0,
+            repetitions: 0,
+            due: 0,
+        }
+    }
+
+    /// Updates the schedule with a review graded from 0 to 5, where 3 and
+    /// above is a correct answer
+    fn review(&mut self, quality: u32, now: u64) {
+        let quality = quality.min(5);
+        if quality < 3 {
+            self.repetitions = 0;
+            self.interval = 1;
+        } else {
+            self.repetitions += 1;
+            self.interval = match self.repetitions {
+                1 => 1,
+                2 => 6,
+                _ => (self.interval as f32 * self.ease).round() as u64,
+            };
+        }
+        let miss = (5 - quality) as f32;
+        self.ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+        self.due = now + self.interval;
+    }
+}
+
+impl CaseReview {
+    /// SM-2 grade of the review, from 0 to 5
+    pub fn quality(&self) -> u32 {
+        if self.failed {
+            return 0;
+        }
+        match RECOGNITION_GRADES
+            .iter()
+            .position(|limit| self.recognition_time <= *limit)
+        {
+            Some(idx) => 5 - idx as u32,
+            None => 2,
+        }
+    }
+}
+
+impl CaseScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the schedule from local storage. A missing or unreadable schedule
+    /// starts over with every case new.
+    pub fn load(history: &History) -> Self {
+        history
+            .setting_as_string(CASE_SCHEDULE_SETTING)
+            .and_then(|schedule| serde_json::from_str(&schedule).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, history: &mut History) -> crate::Result<()> {
+        let schedule = serde_json::to_string(self).storage_error()?;
+        history.set_string_setting(CASE_SCHEDULE_SETTING, &schedule)
+    }
+
+    /// Scheduling state of a case, or `None` if it has not been reviewed
+    pub fn schedule(&self, case: LastLayerCase) -> Option<&CaseSchedule> {
+        self.cases.get(&case.key())
+    }
+
+    /// Number of reviews until the case is due, or zero if it is due now
+    pub fn reviews_until_due(&self, case: LastLayerCase) -> u64 {
+        match self.schedule(case) {
+            Some(schedule) => schedule.due.saturating_sub(self.reviews),
+            None => 0,
+        }
+    }
+
+    pub fn review(&mut self, case: LastLayerCase, review: CaseReview) {
+        self.reviews += 1;
+        let now = self.reviews;
+        self.cases
+            .entry(case.key())
+            .or_insert_with(CaseSchedule::new)
+            .review(review.quality(), now);
+    }
+
+    /// Reviews each last layer case of a solve. Cases that were solved with
+    /// more than one algorithm count as failed.
+    pub fn review_analysis(&mut self, cfop: &CFOPAnalysis) {
+        if let Some(oll) = cfop.oll.first() {
+            self.review(
+                LastLayerCase::OLL(oll.one_look_algorithm),
+                CaseReview {
+                    recognition_time: oll.recognition_time,
+                    failed: cfop.oll.len() > 1,
+                },
+            );
+        }
+        if let Some(pll) = cfop.pll.first() {
+            self.review(
+                LastLayerCase::PLL(pll.one_look_algorithm),
+                CaseReview {
+                    recognition_time: pll.recognition_time,
+                    failed: cfop.pll.len() > 1,
+                },
+            );
+        }
+    }
+
+    /// Chooses the next case to drill from the given cases. Cases are chosen
+    /// at random, weighted so that new, due and overdue cases are much more
+    /// likely than cases that are not due yet.
+    pub fn next_case<T: RandomSource>(
+        &self,
+        rng: &mut T,
+        cases: &[LastLayerCase],
+    ) -> Option<LastLayerCase> {
+        let weights: Vec<u32> = cases.iter().map(|case| self.weight(*case)).collect();
+        let total: u32 = weights.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let mut choice = rng.next(total);
+        for (case, weight) in cases.iter().zip(weights) {
+            if choice < weight {
+                return Some(*case);
+            }
+            choice -= weight;
+        }
+        None
+    }
+
+    fn weight(&self, case: LastLayerCase) -> u32 {
+        match self.schedule(case) {
+            Some(schedule) if schedule.due > self.reviews => 1,
+            Some(schedule) => {
+                let overdue = (self.reviews - schedule.due).min(MAX_OVERDUE_WEIGHT as u64);
+                DUE_WEIGHT + overdue as u32
+            }
+            None => DUE_WEIGHT,
+        }
+    }
+}
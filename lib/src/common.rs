@@ -1,11 +1,19 @@
+use crate::analysis::CFOPSkips;
+use crate::big_cube::BigCube;
+use crate::cube2x2x2::Cube2x2x2;
+use crate::cube3x3x3::Cube3x3x3;
+use crate::error::{Error, ErrorKind};
 use crate::rand::{RandomSource, StandardRandomSource};
-use anyhow::{anyhow, Result};
+use crate::stats::RollingAverage;
+use anyhow::anyhow;
 use chrono::{DateTime, Local};
 use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::str::FromStr;
+use std::time::Duration;
 use uuid::Uuid;
 
 #[cfg(not(feature = "no_solver"))]
@@ -24,7 +32,7 @@ pub enum Color {
 }
 
 #[repr(u8)]
-#[derive(Debug, PartialEq, Eq, Clone, Copy, TryFromPrimitive)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, TryFromPrimitive, Serialize, Deserialize)]
 /// Identification of a corner piece. Names come from the faces of the cube this corner
 /// belongs to on a solved cube.
 pub enum Corner {
@@ -54,6 +62,15 @@ pub(crate) struct CornerOrientationPruneTable;
 pub(crate) struct CornerPermutationPruneTable;
 
 impl Color {
+    pub const ALL: &'static [Color] = &[
+        Color::White,
+        Color::Green,
+        Color::Red,
+        Color::Blue,
+        Color::Orange,
+        Color::Yellow,
+    ];
+
     pub fn face(&self) -> CubeFace {
         match self {
             Color::White => CubeFace::Top,
@@ -161,11 +178,95 @@ pub enum Move {
     Dw = 33,
     Dwp = 34,
     Dw2 = 35,
+    U3w = 36,
+    U3wp = 37,
+    U3w2 = 38,
+    F3w = 39,
+    F3wp = 40,
+    F3w2 = 41,
+    R3w = 42,
+    R3wp = 43,
+    R3w2 = 44,
+    B3w = 45,
+    B3wp = 46,
+    B3w2 = 47,
+    L3w = 48,
+    L3wp = 49,
+    L3w2 = 50,
+    D3w = 51,
+    D3wp = 52,
+    D3w2 = 53,
+}
+
+/// How wide moves are written in move strings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WideMoveNotation {
+    /// Face letter followed by "w", as in "Rw"
+    Suffix,
+    /// Lowercase face letter, as in "r"
+    Lowercase,
+}
+
+/// How counterclockwise moves are written in move strings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrimeNotation {
+    /// Apostrophe after the move, as in "R'"
+    Apostrophe,
+    /// Three clockwise quarter turns, as in "R3"
+    Three,
+}
+
+/// Notation used when rendering moves for display or export. Parsing always
+/// accepts every supported notation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MoveNotation {
+    pub wide: WideMoveNotation,
+    pub prime: PrimeNotation,
+}
+
+/// Orientation the cube is held in while applying a scramble, given by the
+/// colors of the top and front faces. Scrambles are generated with white on
+/// top and green in front, and are rewritten to turn the same colors when the
+/// cube is held in another orientation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScrambleOrientation {
+    top: Color,
+    front: Color,
 }
 
 #[derive(Clone, Debug)]
 pub struct TimedMove(Move, u32);
 
+/// Orientation of a smart cube as reported by its gyroscope. This is a unit
+/// quaternion in a reference frame where the Z axis is vertical.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubeOrientation {
+    w: f32,
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+/// Input that produced a solve. This is stored in the `device` field of the
+/// solve, so that solves recorded by older versions, which only stored the
+/// name of a Bluetooth device, are still understood.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveInput {
+    Keyboard,
+    /// Time typed in after the solve
+    Manual,
+    SmartCube {
+        id: String,
+        name: String,
+    },
+    ExternalTimer {
+        id: String,
+        name: String,
+    },
+    /// Device known only by name, as recorded by older versions or imports
+    Named(String),
+}
+
 #[derive(Clone, Debug)]
 pub struct Solve {
     pub id: String,
@@ -176,7 +277,10 @@ pub struct Solve {
     pub time: u32,
     pub penalty: Penalty,
     pub device: Option<String>,
+    pub participant: Option<String>,
     pub moves: Option<Vec<TimedMove>>,
+    /// Steps skipped in the solve, recorded when the solve is added
+    pub skips: Option<CFOPSkips>,
 }
 
 impl Solve {
@@ -184,6 +288,13 @@ impl Solve {
         Uuid::new_v4().to_simple().to_string()
     }
 
+    /// Input that produced the solve, if it was recorded
+    pub fn input(&self) -> Option<SolveInput> {
+        self.device
+            .as_deref()
+            .map(|device| SolveInput::from_device_string(device))
+    }
+
     pub fn final_time(&self) -> Option<u32> {
         match self.penalty {
             Penalty::None => Some(self.time),
@@ -193,6 +304,60 @@ impl Solve {
     }
 }
 
+impl SolveInput {
+    pub fn from_device_string(device: &str) -> Self {
+        match device {
+            "keyboard" => return SolveInput::Keyboard,
+            "manual" => return SolveInput::Manual,
+            _ => (),
+        }
+        let mut parts = device.splitn(3, '|');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some("cube"), Some(id), Some(name)) => SolveInput::SmartCube {
+                id: id.into(),
+                name: name.into(),
+            },
+            (Some("timer"), Some(id), Some(name)) => SolveInput::ExternalTimer {
+                id: id.into(),
+                name: name.into(),
+            },
+            _ => SolveInput::Named(device.into()),
+        }
+    }
+
+    pub fn to_device_string(&self) -> String {
+        match self {
+            SolveInput::Keyboard => "keyboard".into(),
+            SolveInput::Manual => "manual".into(),
+            SolveInput::SmartCube { id, name } => format!("cube|{}|{}", id, name),
+            SolveInput::ExternalTimer { id, name } => format!("timer|{}|{}", id, name),
+            SolveInput::Named(name) => name.clone(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            SolveInput::Keyboard => "Keyboard",
+            SolveInput::Manual => "Manual entry",
+            SolveInput::SmartCube { name, .. }
+            | SolveInput::ExternalTimer { name, .. }
+            | SolveInput::Named(name) => name,
+        }
+    }
+
+    /// Returns true if both inputs are the same source. Devices are compared
+    /// by ID, as their names can change between connections.
+    pub fn same_source(&self, other: &SolveInput) -> bool {
+        match (self, other) {
+            (SolveInput::SmartCube { id, .. }, SolveInput::SmartCube { id: other, .. })
+            | (SolveInput::ExternalTimer { id, .. }, SolveInput::ExternalTimer { id: other, .. }) => {
+                id == other
+            }
+            _ => self == other,
+        }
+    }
+}
+
 #[cfg(not(feature = "no_solver"))]
 impl CornerOrientationMoveTable {
     pub fn get(idx: u16, mv: Move) -> u16 {
@@ -266,6 +431,19 @@ pub struct Average {
     pub time: u32,
 }
 
+/// Number of solves removed from each end of an average of `count` solves.
+/// Following the WCA regulations and csTimer, 5% of the solves are removed
+/// from each end, rounded up, so that an ao5 or ao12 removes the single best
+/// and worst solves, an ao50 removes 3 and an ao100 removes 5. Lists of less
+/// than 5 solves are a plain mean and do not remove any solves.
+pub fn average_trim_count(count: usize) -> usize {
+    if count >= 5 {
+        (count + 19) / 20
+    } else {
+        0
+    }
+}
+
 pub trait ListAverage {
     fn average(&self) -> Option<u32>;
 }
@@ -300,17 +478,13 @@ impl ListAverage for &[Option<u32>] {
         });
 
         // Remove the best and worst time(s) as appropriate for the size of the set.
-        // If there are less than 5 values, use an arithmetic mean and do not
-        // eliminate any values.
-        let to_remove = if sorted.len() >= 5 {
-            (sorted.len() + 39) / 40
-        } else {
-            0
-        };
+        let to_remove = average_trim_count(sorted.len());
         let solves = &sorted[to_remove..sorted.len() - to_remove];
 
-        // Sum the solves that are not removed. If there is a DNF in this set, the
-        // entire average is invalid.
+        // Sum the solves that are not removed. DNFs are sorted as the slowest
+        // times, so they are only removed if there are no more of them than the
+        // number of trimmed solves. If there is a DNF left in this set, the
+        // entire average is a DNF.
         let sum = solves.iter().fold(Some(0), |sum, time| {
             if let Some(sum) = sum {
                 if let Some(time) = time {
@@ -380,29 +554,18 @@ impl SolveList for &[Solve] {
     }
 
     fn best_average(&self, count: usize) -> Option<Average> {
-        self.windows(count)
-            .fold(None, |best, solves| {
-                if let Some(time) = solves.average() {
-                    if let Some((best_solves, best_time)) = best {
-                        if time < best_time {
-                            Some((solves, time))
-                        } else {
-                            Some((best_solves, best_time))
-                        }
-                    } else {
-                        Some((solves, time))
-                    }
-                } else {
-                    best
-                }
-            })
-            .map(|best| {
-                let (solves, time) = best;
-                Average {
-                    solves: solves.to_vec(),
-                    time,
-                }
-            })
+        // Compute the averages incrementally so that large histories and large
+        // averages do not need to sort every window
+        let mut average = RollingAverage::new(count);
+        for solve in self.iter() {
+            average.push(solve.final_time());
+        }
+        let time = average.best()?;
+        let end = average.best_end()?;
+        Some(Average {
+            solves: self[end + 1 - count..=end].to_vec(),
+            time,
+        })
     }
 }
 
@@ -414,23 +577,25 @@ pub enum Penalty {
 }
 
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq, TryFromPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
 pub enum SolveType {
     Standard3x3x3 = 0,
     OneHanded3x3x3 = 1,
     Blind3x3x3 = 2,
     Standard2x2x2 = 3,
     /*Standard4x4x4 = 4,
-    Blind4x4x4 = 5,
+    Blind4x4x4 = 5,*/
     Standard5x5x5 = 6,
-    Blind5x5x5 = 7,
+    /*Blind5x5x5 = 7,*/
     Standard6x6x6 = 8,
     Standard7x7x7 = 9,
-    Pyraminx = 10,
+    /*Pyraminx = 10,
     Megaminx = 11,
     Skewb = 12,
     Square1 = 13,
     Clock = 14,*/
+    Void3x3x3 = 15,
+    Bandaged3x3x3 = 16,
 }
 
 impl SolveType {
@@ -441,20 +606,45 @@ impl SolveType {
             "3x3x3 Blind" => Some(SolveType::Blind3x3x3),
             "2x2x2" => Some(SolveType::Standard2x2x2),
             /*"4x4x4" => Some(SolveType::Standard4x4x4),
-            "4x4x4 Blind" => Some(SolveType::Blind4x4x4),
+            "4x4x4 Blind" => Some(SolveType::Blind4x4x4),*/
             "5x5x5" => Some(SolveType::Standard5x5x5),
-            "5x5x5 Blind" => Some(SolveType::Blind5x5x5),
+            /*"5x5x5 Blind" => Some(SolveType::Blind5x5x5),*/
             "6x6x6" => Some(SolveType::Standard6x6x6),
             "7x7x7" => Some(SolveType::Standard7x7x7),
-            "Pyraminx" => Some(SolveType::Pyraminx),
+            /*"Pyraminx" => Some(SolveType::Pyraminx),
             "Megaminx" => Some(SolveType::Megaminx),
             "Skewb" => Some(SolveType::Skewb),
             "Square-1" => Some(SolveType::Square1),
             "Clock" => Some(SolveType::Clock),*/
+            "3x3x3 Void" => Some(SolveType::Void3x3x3),
+            "3x3x3 Bandaged" => Some(SolveType::Bandaged3x3x3),
             _ => None,
         }
     }
 
+    /// Event identifier used by the World Cube Association for official
+    /// results. Returns `None` for puzzles that are not official events.
+    pub fn wca_event(&self) -> Option<&'static str> {
+        match self {
+            SolveType::Standard3x3x3 => Some("333"),
+            SolveType::OneHanded3x3x3 => Some("333oh"),
+            SolveType::Blind3x3x3 => Some("333bf"),
+            SolveType::Standard2x2x2 => Some("222"),
+            /*SolveType::Standard4x4x4 => Some("444"),
+            SolveType::Blind4x4x4 => Some("444bf"),*/
+            SolveType::Standard5x5x5 => Some("555"),
+            /*SolveType::Blind5x5x5 => Some("555bf"),*/
+            SolveType::Standard6x6x6 => Some("666"),
+            SolveType::Standard7x7x7 => Some("777"),
+            /*SolveType::Pyraminx => Some("pyram"),
+            SolveType::Megaminx => Some("minx"),
+            SolveType::Skewb => Some("skewb"),
+            SolveType::Square1 => Some("sq1"),
+            SolveType::Clock => Some("clock"),*/
+            SolveType::Void3x3x3 | SolveType::Bandaged3x3x3 => None,
+        }
+    }
+
     pub fn is_3x3x3(&self) -> bool {
         match self {
             SolveType::Standard3x3x3 => true,
@@ -463,6 +653,30 @@ impl SolveType {
             _ => false,
         }
     }
+
+    /// Number of layers along each side of the cube used by this solve type
+    pub fn cube_size(&self) -> usize {
+        match self {
+            SolveType::Standard2x2x2 => 2,
+            SolveType::Standard3x3x3
+            | SolveType::OneHanded3x3x3
+            | SolveType::Blind3x3x3
+            | SolveType::Void3x3x3
+            | SolveType::Bandaged3x3x3 => 3,
+            SolveType::Standard5x5x5 => 5,
+            SolveType::Standard6x6x6 => 6,
+            SolveType::Standard7x7x7 => 7,
+        }
+    }
+
+    /// Creates a solved cube of the puzzle used by this solve type
+    pub fn new_cube(&self) -> Box<dyn Cube> {
+        match self.cube_size() {
+            2 => Box::new(Cube2x2x2::new()),
+            3 => Box::new(Cube3x3x3::new()),
+            size => Box::new(BigCube::new(size)),
+        }
+    }
 }
 
 impl ToString for SolveType {
@@ -473,16 +687,18 @@ impl ToString for SolveType {
             SolveType::Blind3x3x3 => "3x3x3 Blind".into(),
             SolveType::Standard2x2x2 => "2x2x2".into(),
             /*SolveType::Standard4x4x4 => "4x4x4".into(),
-            SolveType::Blind4x4x4 => "4x4x4 Blind".into(),
+            SolveType::Blind4x4x4 => "4x4x4 Blind".into(),*/
             SolveType::Standard5x5x5 => "5x5x5".into(),
-            SolveType::Blind5x5x5 => "5x5x5 Blind".into(),
+            /*SolveType::Blind5x5x5 => "5x5x5 Blind".into(),*/
             SolveType::Standard6x6x6 => "6x6x6".into(),
             SolveType::Standard7x7x7 => "7x7x7".into(),
-            SolveType::Pyraminx => "Pyraminx".into(),
+            /*SolveType::Pyraminx => "Pyraminx".into(),
             SolveType::Megaminx => "Megaminx".into(),
             SolveType::Skewb => "Skewb".into(),
             SolveType::Square1 => "Square-1".into(),
             SolveType::Clock => "Clock".into(),*/
+            SolveType::Void3x3x3 => "3x3x3 Void".into(),
+            SolveType::Bandaged3x3x3 => "3x3x3 Bandaged".into(),
         }
     }
 }
@@ -607,6 +823,62 @@ impl Move {
                     _ => None,
                 },
             },
+            3 => match face {
+                CubeFace::Top => match rotation {
+                    -3 => Some(Move::U3w),
+                    -2 => Some(Move::U3w2),
+                    -1 => Some(Move::U3wp),
+                    1 => Some(Move::U3w),
+                    2 => Some(Move::U3w2),
+                    3 => Some(Move::U3wp),
+                    _ => None,
+                },
+                CubeFace::Front => match rotation {
+                    -3 => Some(Move::F3w),
+                    -2 => Some(Move::F3w2),
+                    -1 => Some(Move::F3wp),
+                    1 => Some(Move::F3w),
+                    2 => Some(Move::F3w2),
+                    3 => Some(Move::F3wp),
+                    _ => None,
+                },
+                CubeFace::Right => match rotation {
+                    -3 => Some(Move::R3w),
+                    -2 => Some(Move::R3w2),
+                    -1 => Some(Move::R3wp),
+                    1 => Some(Move::R3w),
+                    2 => Some(Move::R3w2),
+                    3 => Some(Move::R3wp),
+                    _ => None,
+                },
+                CubeFace::Back => match rotation {
+                    -3 => Some(Move::B3w),
+                    -2 => Some(Move::B3w2),
+                    -1 => Some(Move::B3wp),
+                    1 => Some(Move::B3w),
+                    2 => Some(Move::B3w2),
+                    3 => Some(Move::B3wp),
+                    _ => None,
+                },
+                CubeFace::Left => match rotation {
+                    -3 => Some(Move::L3w),
+                    -2 => Some(Move::L3w2),
+                    -1 => Some(Move::L3wp),
+                    1 => Some(Move::L3w),
+                    2 => Some(Move::L3w2),
+                    3 => Some(Move::L3wp),
+                    _ => None,
+                },
+                CubeFace::Bottom => match rotation {
+                    -3 => Some(Move::D3w),
+                    -2 => Some(Move::D3w2),
+                    -1 => Some(Move::D3wp),
+                    1 => Some(Move::D3w),
+                    2 => Some(Move::D3w2),
+                    3 => Some(Move::D3wp),
+                    _ => None,
+                },
+            },
             _ => None,
         }
     }
@@ -656,12 +928,60 @@ impl Move {
 
     pub const fn face(&self) -> CubeFace {
         match self {
-            Move::U | Move::Up | Move::U2 | Move::Uw | Move::Uwp | Move::Uw2 => CubeFace::Top,
-            Move::F | Move::Fp | Move::F2 | Move::Fw | Move::Fwp | Move::Fw2 => CubeFace::Front,
-            Move::R | Move::Rp | Move::R2 | Move::Rw | Move::Rwp | Move::Rw2 => CubeFace::Right,
-            Move::B | Move::Bp | Move::B2 | Move::Bw | Move::Bwp | Move::Bw2 => CubeFace::Back,
-            Move::L | Move::Lp | Move::L2 | Move::Lw | Move::Lwp | Move::Lw2 => CubeFace::Left,
-            Move::D | Move::Dp | Move::D2 | Move::Dw | Move::Dwp | Move::Dw2 => CubeFace::Bottom,
+            Move::U
+            | Move::Up
+            | Move::U2
+            | Move::Uw
+            | Move::Uwp
+            | Move::Uw2
+            | Move::U3w
+            | Move::U3wp
+            | Move::U3w2 => CubeFace::Top,
+            Move::F
+            | Move::Fp
+            | Move::F2
+            | Move::Fw
+            | Move::Fwp
+            | Move::Fw2
+            | Move::F3w
+            | Move::F3wp
+            | Move::F3w2 => CubeFace::Front,
+            Move::R
+            | Move::Rp
+            | Move::R2
+            | Move::Rw
+            | Move::Rwp
+            | Move::Rw2
+            | Move::R3w
+            | Move::R3wp
+            | Move::R3w2 => CubeFace::Right,
+            Move::B
+            | Move::Bp
+            | Move::B2
+            | Move::Bw
+            | Move::Bwp
+            | Move::Bw2
+            | Move::B3w
+            | Move::B3wp
+            | Move::B3w2 => CubeFace::Back,
+            Move::L
+            | Move::Lp
+            | Move::L2
+            | Move::Lw
+            | Move::Lwp
+            | Move::Lw2
+            | Move::L3w
+            | Move::L3wp
+            | Move::L3w2 => CubeFace::Left,
+            Move::D
+            | Move::Dp
+            | Move::D2
+            | Move::Dw
+            | Move::Dwp
+            | Move::Dw2
+            | Move::D3w
+            | Move::D3wp
+            | Move::D3w2 => CubeFace::Bottom,
         }
     }
 
@@ -679,7 +999,13 @@ impl Move {
             | Move::Rw
             | Move::Bw
             | Move::Lw
-            | Move::Dw => 1,
+            | Move::Dw
+            | Move::U3w
+            | Move::F3w
+            | Move::R3w
+            | Move::B3w
+            | Move::L3w
+            | Move::D3w => 1,
             Move::Up
             | Move::Fp
             | Move::Rp
@@ -691,7 +1017,13 @@ impl Move {
             | Move::Rwp
             | Move::Bwp
             | Move::Lwp
-            | Move::Dwp => -1,
+            | Move::Dwp
+            | Move::U3wp
+            | Move::F3wp
+            | Move::R3wp
+            | Move::B3wp
+            | Move::L3wp
+            | Move::D3wp => -1,
             Move::U2
             | Move::F2
             | Move::R2
@@ -703,7 +1035,13 @@ impl Move {
             | Move::Rw2
             | Move::Bw2
             | Move::Lw2
-            | Move::Dw2 => 2,
+            | Move::Dw2
+            | Move::U3w2
+            | Move::F3w2
+            | Move::R3w2
+            | Move::B3w2
+            | Move::L3w2
+            | Move::D3w2 => 2,
         }
     }
 
@@ -746,6 +1084,24 @@ impl Move {
             | Move::Bw2
             | Move::Lw2
             | Move::Dw2 => 2,
+            Move::U3w
+            | Move::F3w
+            | Move::R3w
+            | Move::B3w
+            | Move::L3w
+            | Move::D3w
+            | Move::U3wp
+            | Move::F3wp
+            | Move::R3wp
+            | Move::B3wp
+            | Move::L3wp
+            | Move::D3wp
+            | Move::U3w2
+            | Move::F3w2
+            | Move::R3w2
+            | Move::B3w2
+            | Move::L3w2
+            | Move::D3w2 => 3,
         }
     }
 
@@ -787,10 +1143,38 @@ impl Move {
             Move::Dw => Move::Dwp,
             Move::Dwp => Move::Dw,
             Move::Dw2 => Move::Dw2,
+            Move::U3w => Move::U3wp,
+            Move::U3wp => Move::U3w,
+            Move::U3w2 => Move::U3w2,
+            Move::F3w => Move::F3wp,
+            Move::F3wp => Move::F3w,
+            Move::F3w2 => Move::F3w2,
+            Move::R3w => Move::R3wp,
+            Move::R3wp => Move::R3w,
+            Move::R3w2 => Move::R3w2,
+            Move::B3w => Move::B3wp,
+            Move::B3wp => Move::B3w,
+            Move::B3w2 => Move::B3w2,
+            Move::L3w => Move::L3wp,
+            Move::L3wp => Move::L3w,
+            Move::L3w2 => Move::L3w2,
+            Move::D3w => Move::D3wp,
+            Move::D3wp => Move::D3w,
+            Move::D3w2 => Move::D3w2,
         }
     }
 
     pub fn from_str(string: &str) -> Option<Self> {
+        // Counterclockwise moves can also be written as three clockwise turns
+        if let Some(base) = string
+            .strip_suffix('3')
+            .or_else(|| string.strip_suffix('\u{2019}'))
+        {
+            return Self::from_str(base)
+                .filter(|mv| mv.rotation() == 1)
+                .map(|mv| mv.inverse());
+        }
+
         match string {
             "U" => Some(Move::U),
             "U'" => Some(Move::Up),
@@ -828,9 +1212,240 @@ impl Move {
             "Dw" | "d" => Some(Move::Dw),
             "Dw'" | "d'" => Some(Move::Dwp),
             "Dw2" | "d2" => Some(Move::Dw2),
+            "3Uw" | "3u" => Some(Move::U3w),
+            "3Uw'" | "3u'" => Some(Move::U3wp),
+            "3Uw2" | "3u2" => Some(Move::U3w2),
+            "3Fw" | "3f" => Some(Move::F3w),
+            "3Fw'" | "3f'" => Some(Move::F3wp),
+            "3Fw2" | "3f2" => Some(Move::F3w2),
+            "3Rw" | "3r" => Some(Move::R3w),
+            "3Rw'" | "3r'" => Some(Move::R3wp),
+            "3Rw2" | "3r2" => Some(Move::R3w2),
+            "3Bw" | "3b" => Some(Move::B3w),
+            "3Bw'" | "3b'" => Some(Move::B3wp),
+            "3Bw2" | "3b2" => Some(Move::B3w2),
+            "3Lw" | "3l" => Some(Move::L3w),
+            "3Lw'" | "3l'" => Some(Move::L3wp),
+            "3Lw2" | "3l2" => Some(Move::L3w2),
+            "3Dw" | "3d" => Some(Move::D3w),
+            "3Dw'" | "3d'" => Some(Move::D3wp),
+            "3Dw2" | "3d2" => Some(Move::D3w2),
+            _ => None,
+        }
+    }
+}
+
+impl Move {
+    /// Returns the string for this move in the given notation
+    pub fn to_string_with(&self, notation: MoveNotation) -> String {
+        let mut result = self.to_string();
+        if notation.wide == WideMoveNotation::Lowercase && self.width() > 1 {
+            result = result.replacen('w', "", 1).to_lowercase();
+        }
+        if notation.prime == PrimeNotation::Three && result.ends_with('\'') {
+            result.pop();
+            result.push('3');
+        }
+        result
+    }
+}
+
+impl WideMoveNotation {
+    pub const ALL: &'static [WideMoveNotation] =
+        &[WideMoveNotation::Suffix, WideMoveNotation::Lowercase];
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "suffix" => Some(WideMoveNotation::Suffix),
+            "lowercase" => Some(WideMoveNotation::Lowercase),
             _ => None,
         }
     }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            WideMoveNotation::Suffix => "suffix",
+            WideMoveNotation::Lowercase => "lowercase",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WideMoveNotation::Suffix => "Rw",
+            WideMoveNotation::Lowercase => "r",
+        }
+    }
+}
+
+impl Default for WideMoveNotation {
+    fn default() -> Self {
+        WideMoveNotation::Suffix
+    }
+}
+
+impl PrimeNotation {
+    pub const ALL: &'static [PrimeNotation] = &[PrimeNotation::Apostrophe, PrimeNotation::Three];
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "apostrophe" => Some(PrimeNotation::Apostrophe),
+            "three" => Some(PrimeNotation::Three),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            PrimeNotation::Apostrophe => "apostrophe",
+            PrimeNotation::Three => "three",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PrimeNotation::Apostrophe => "R'",
+            PrimeNotation::Three => "R3",
+        }
+    }
+}
+
+impl Default for PrimeNotation {
+    fn default() -> Self {
+        PrimeNotation::Apostrophe
+    }
+}
+
+impl ScrambleOrientation {
+    /// Creates an orientation from the colors of the top and front faces.
+    /// Returns `None` if the colors are the same or on opposite faces.
+    pub fn new(top: Color, front: Color) -> Option<Self> {
+        if top == front || top.face().opposite() == front.face() {
+            None
+        } else {
+            Some(Self { top, front })
+        }
+    }
+
+    /// All 24 orientations of the cube
+    pub fn all() -> Vec<Self> {
+        let mut result = Vec::new();
+        for top in Color::ALL {
+            for front in Color::ALL {
+                if let Some(orientation) = Self::new(*top, *front) {
+                    result.push(orientation);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn top(&self) -> Color {
+        self.top
+    }
+
+    pub fn front(&self) -> Color {
+        self.front
+    }
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        let mut colors = string.split('-').map(|name| {
+            Color::ALL
+                .iter()
+                .find(|color| color.to_str().to_lowercase() == name)
+                .cloned()
+        });
+        let top = colors.next()??;
+        let front = colors.next()??;
+        if colors.next().is_some() {
+            return None;
+        }
+        Self::new(top, front)
+    }
+
+    pub fn to_str(&self) -> String {
+        format!(
+            "{}-{}",
+            self.top.to_str().to_lowercase(),
+            self.front.to_str().to_lowercase()
+        )
+    }
+
+    pub fn name(&self) -> String {
+        format!(
+            "{} top, {} front",
+            self.top.to_str(),
+            self.front.to_str().to_lowercase()
+        )
+    }
+
+    /// Face that holds the center of the given color in this orientation
+    pub fn face(&self, color: Color) -> CubeFace {
+        // Express the direction of the color's face in the standard orientation
+        // in terms of the axes of this orientation
+        let top = Self::axis(self.top.face());
+        let front = Self::axis(self.front.face());
+        let right = [
+            top[1] * front[2] - top[2] * front[1],
+            top[2] * front[0] - top[0] * front[2],
+            top[0] * front[1] - top[1] * front[0],
+        ];
+        let dir = Self::axis(color.face());
+        let dot = |axis: [i32; 3]| dir[0] * axis[0] + dir[1] * axis[1] + dir[2] * axis[2];
+        match (dot(right), dot(top), dot(front)) {
+            (_, 1, _) => CubeFace::Top,
+            (_, -1, _) => CubeFace::Bottom,
+            (_, _, 1) => CubeFace::Front,
+            (_, _, -1) => CubeFace::Back,
+            (1, _, _) => CubeFace::Right,
+            _ => CubeFace::Left,
+        }
+    }
+
+    /// Color of the center on the given face in this orientation
+    pub fn color(&self, face: CubeFace) -> Color {
+        *Color::ALL
+            .iter()
+            .find(|color| self.face(**color) == face)
+            .unwrap()
+    }
+
+    /// Rewrites moves generated for the standard orientation so that they
+    /// turn the same colors when the cube is held in this orientation
+    pub fn apply(&self, moves: &[Move]) -> Vec<Move> {
+        moves
+            .iter()
+            .map(|mv| {
+                Move::from_face_and_rotation_wide(
+                    self.face(mv.face().color()),
+                    mv.rotation(),
+                    mv.width(),
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    /// Direction of a face in the standard orientation, with X to the right,
+    /// Y up and Z towards the front
+    fn axis(face: CubeFace) -> [i32; 3] {
+        match face {
+            CubeFace::Top => [0, 1, 0],
+            CubeFace::Front => [0, 0, 1],
+            CubeFace::Right => [1, 0, 0],
+            CubeFace::Back => [0, 0, -1],
+            CubeFace::Left => [-1, 0, 0],
+            CubeFace::Bottom => [0, -1, 0],
+        }
+    }
+}
+
+impl Default for ScrambleOrientation {
+    fn default() -> Self {
+        Self {
+            top: Color::White,
+            front: Color::Green,
+        }
+    }
 }
 
 impl ToString for Move {
@@ -872,6 +1487,24 @@ impl ToString for Move {
             Move::Dw => "Dw".into(),
             Move::Dwp => "Dw'".into(),
             Move::Dw2 => "Dw2".into(),
+            Move::U3w => "3Uw".into(),
+            Move::U3wp => "3Uw'".into(),
+            Move::U3w2 => "3Uw2".into(),
+            Move::F3w => "3Fw".into(),
+            Move::F3wp => "3Fw'".into(),
+            Move::F3w2 => "3Fw2".into(),
+            Move::R3w => "3Rw".into(),
+            Move::R3wp => "3Rw'".into(),
+            Move::R3w2 => "3Rw2".into(),
+            Move::B3w => "3Bw".into(),
+            Move::B3wp => "3Bw'".into(),
+            Move::B3w2 => "3Bw2".into(),
+            Move::L3w => "3Lw".into(),
+            Move::L3wp => "3Lw'".into(),
+            Move::L3w2 => "3Lw2".into(),
+            Move::D3w => "3Dw".into(),
+            Move::D3wp => "3Dw'".into(),
+            Move::D3w2 => "3Dw2".into(),
         }
     }
 }
@@ -890,6 +1523,54 @@ impl TimedMove {
     }
 }
 
+impl CubeOrientation {
+    /// Creates an orientation from a quaternion, which does not need to be
+    /// normalized.
+    pub fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        let length = (w * w + x * x + y * y + z * z).sqrt();
+        if length == 0.0 || !length.is_finite() {
+            return Self::identity();
+        }
+        Self {
+            w: w / length,
+            x: x / length,
+            y: y / length,
+            z: z / length,
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self {
+            w: 1.0,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        }
+    }
+
+    /// Angle in degrees of the rotation between two orientations
+    pub fn angle_to(&self, other: &CubeOrientation) -> f32 {
+        let dot = self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z;
+        2.0 * dot.abs().min(1.0).acos().to_degrees()
+    }
+
+    /// Angle in degrees between vertical and the closest axis of the cube. This
+    /// is zero when the cube is resting flat on one of its faces.
+    pub fn tilt(&self) -> f32 {
+        let (w, x, y, z) = (self.w, self.x, self.y, self.z);
+        // Vertical components of each of the cube's rotated axes
+        let vertical = [
+            2.0 * (x * z - y * w),
+            2.0 * (y * z + x * w),
+            1.0 - 2.0 * (x * x + y * y),
+        ];
+        let closest = vertical
+            .iter()
+            .fold(0.0f32, |closest, component| closest.max(component.abs()));
+        closest.min(1.0).acos().to_degrees()
+    }
+}
+
 /// Operations on sequences of cube moves
 pub trait MoveSequence: Sized {
     /// Returns the inverse of this move sequence (undoing all moves)
@@ -897,6 +1578,10 @@ pub trait MoveSequence: Sized {
 
     /// Returns the human-readable string for this move sequence
     fn to_string(&self) -> String;
+
+    /// Returns the human-readable string for this move sequence in the
+    /// given notation
+    fn to_string_with(&self, notation: MoveNotation) -> String;
 }
 
 impl MoveSequence for Vec<Move> {
@@ -907,6 +1592,10 @@ impl MoveSequence for Vec<Move> {
     fn to_string(&self) -> String {
         self.as_slice().to_string()
     }
+
+    fn to_string_with(&self, notation: MoveNotation) -> String {
+        self.as_slice().to_string_with(notation)
+    }
 }
 
 impl MoveSequence for &[Move] {
@@ -918,6 +1607,11 @@ impl MoveSequence for &[Move] {
         let moves: Vec<String> = self.iter().map(|mv| mv.to_string()).collect();
         moves.join(" ")
     }
+
+    fn to_string_with(&self, notation: MoveNotation) -> String {
+        let moves: Vec<String> = self.iter().map(|mv| mv.to_string_with(notation)).collect();
+        moves.join(" ")
+    }
 }
 
 /// Operations on sequences of cube moves with timing information
@@ -955,6 +1649,50 @@ pub trait InitialCubeState: Sized {
     }
 }
 
+/// Options for the cube solvers, trading off solution length against the time
+/// spent searching. The default options search for the shortest solution the
+/// solver can find, however long that takes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolverOptions {
+    /// Once this much time has passed, return the best solution found so far.
+    /// If no solution has been found yet, the search continues until the
+    /// first solution is found.
+    pub max_time: Option<Duration>,
+    /// Only accept solutions with at most this many moves. If there is no
+    /// such solution, the solve fails.
+    pub max_length: Option<usize>,
+    /// Stop searching as soon as a solution with at most this many moves is
+    /// found.
+    pub target_length: Option<usize>,
+}
+
+impl SolverOptions {
+    /// Options that return the first solution found, which is what
+    /// `Cube::solve_fast` uses
+    pub fn fast() -> Self {
+        Self {
+            max_time: None,
+            max_length: None,
+            target_length: Some(usize::MAX),
+        }
+    }
+
+    pub fn with_max_time(mut self, max_time: Duration) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn with_target_length(mut self, target_length: usize) -> Self {
+        self.target_length = Some(target_length);
+        self
+    }
+}
+
 pub trait Cube {
     /// Determines if this cube is in the solved state
     fn is_solved(&self) -> bool;
@@ -974,12 +1712,31 @@ pub trait Cube {
 
     /// Finds an efficient solution to this cube state
     #[cfg(not(feature = "no_solver"))]
-    fn solve(&self) -> Option<Vec<Move>>;
+    fn solve(&self) -> Option<Vec<Move>> {
+        self.solve_with_options(&SolverOptions::default())
+    }
 
     /// Finds any solution to this cube state. Likely has many more moves than the
     /// result of `solve`.
     #[cfg(not(feature = "no_solver"))]
-    fn solve_fast(&self) -> Option<Vec<Move>>;
+    fn solve_fast(&self) -> Option<Vec<Move>> {
+        self.solve_with_options(&SolverOptions::fast())
+    }
+
+    /// Finds a solution to this cube state, using the given options to decide
+    /// how long to search for a shorter solution
+    #[cfg(not(feature = "no_solver"))]
+    fn solve_with_options(&self, options: &SolverOptions) -> Option<Vec<Move>>;
+
+    /// Finds an efficient solution to this cube state, reporting why there is
+    /// no solution instead of returning `None`. Missing or invalid solver
+    /// tables are reported as `Error::Solver`.
+    #[cfg(not(feature = "no_solver"))]
+    fn try_solve(&self) -> crate::Result<Vec<Move>> {
+        crate::tables::ensure_solver_tables()?;
+        self.solve()
+            .ok_or_else(|| Error::Solver(anyhow!("No solution found for cube state")))
+    }
 
     fn reset(&mut self);
     fn dyn_clone(&self) -> Box<dyn Cube>;
@@ -1020,19 +1777,92 @@ pub trait FaceRotation {
     }
 }
 
-pub fn parse_move_string(string: &str) -> Result<Vec<Move>> {
+pub fn parse_move_string(string: &str) -> crate::Result<Vec<Move>> {
     let mut moves = Vec::new();
     for move_str in string.split(' ') {
         if move_str.len() == 0 {
             continue;
         }
-        let mv = Move::from_str(move_str).ok_or_else(|| anyhow!("Invalid move '{}'", move_str))?;
+        let mv = Move::from_str(move_str)
+            .ok_or_else(|| Error::Parse(anyhow!("Invalid move '{}'", move_str)))?;
         moves.push(mv);
     }
     Ok(moves)
 }
 
-pub fn parse_timed_move_string(string: &str) -> Result<Vec<TimedMove>> {
+/// Parses a solve time such as `12.34` or `1:02.345` into milliseconds
+pub fn parse_solve_time(string: &str) -> crate::Result<u32> {
+    let invalid = || Error::Parse(anyhow!("Invalid time '{}'", string));
+    let (minutes, seconds) = match string.rfind(':') {
+        Some(idx) => (
+            u32::from_str(&string[..idx]).map_err(|_| invalid())?,
+            &string[idx + 1..],
+        ),
+        None => (0, string),
+    };
+    let (whole, fraction) = match seconds.find('.') {
+        Some(idx) => (&seconds[..idx], &seconds[idx + 1..]),
+        None => (seconds, ""),
+    };
+    if whole.len() == 0 || fraction.len() > 3 || !fraction.chars().all(|ch| ch.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let whole = u32::from_str(whole).map_err(|_| invalid())?;
+    if minutes > 0 && whole >= 60 {
+        return Err(invalid());
+    }
+    let fraction = format!("{:0<3}", fraction);
+    let ms = u32::from_str(&fraction).map_err(|_| invalid())?;
+    Ok((minutes * 60 + whole) * 1000 + ms)
+}
+
+/// Parses a list of solve times, such as handwritten results or times copied
+/// from another timer. Times are separated by whitespace, commas, or
+/// semicolons. A time followed by `+` or `+2` has a two second penalty, and
+/// `DNF` or `DNF(12.34)` is a DNF. Times in parentheses, as used for the
+/// dropped times of an average, are accepted.
+pub fn parse_time_list(string: &str) -> crate::Result<Vec<(u32, Penalty)>> {
+    let mut times: Vec<(u32, Penalty)> = Vec::new();
+    for token in string.split(|ch: char| ch.is_whitespace() || ch == ',' || ch == ';') {
+        if token.len() == 0 {
+            continue;
+        }
+
+        // Penalty written separately from the time applies to the previous time
+        if token == "+" || token == "+2" {
+            match times.last_mut() {
+                Some((_, penalty @ Penalty::None)) => *penalty = Penalty::Time(2000),
+                _ => return Err(Error::Parse(anyhow!("Penalty '{}' without a time", token))),
+            }
+            continue;
+        }
+
+        let token = token.trim_start_matches('(').trim_end_matches(')');
+        let upper = token.to_uppercase();
+        if upper.starts_with("DNF") {
+            let time = upper[3..].trim_start_matches('(');
+            let time = if time.len() == 0 {
+                0
+            } else {
+                parse_solve_time(time)?
+            };
+            times.push((time, Penalty::DNF));
+            continue;
+        }
+
+        let (time, penalty) = if let Some(time) = token.strip_suffix("+2") {
+            (time, Penalty::Time(2000))
+        } else if let Some(time) = token.strip_suffix('+') {
+            (time, Penalty::Time(2000))
+        } else {
+            (token, Penalty::None)
+        };
+        times.push((parse_solve_time(time)?, penalty));
+    }
+    Ok(times)
+}
+
+pub fn parse_timed_move_string(string: &str) -> crate::Result<Vec<TimedMove>> {
     let mut moves = Vec::new();
     for move_str in string.split(' ') {
         if move_str.len() == 0 {
@@ -1041,12 +1871,13 @@ pub fn parse_timed_move_string(string: &str) -> Result<Vec<TimedMove>> {
         let mut move_iter = move_str.split('@');
         let mv_str = move_iter
             .next()
-            .ok_or_else(|| anyhow!("Invalid move '{}'", move_str))?;
+            .ok_or_else(|| Error::Parse(anyhow!("Invalid move '{}'", move_str)))?;
         let time_str = move_iter
             .next()
-            .ok_or_else(|| anyhow!("Invalid move '{}'", move_str))?;
-        let mv = Move::from_str(mv_str).ok_or_else(|| anyhow!("Invalid move '{}'"))?;
-        let time = u32::from_str(time_str)?;
+            .ok_or_else(|| Error::Parse(anyhow!("Invalid move '{}'", move_str)))?;
+        let mv = Move::from_str(mv_str)
+            .ok_or_else(|| Error::Parse(anyhow!("Invalid move '{}'", mv_str)))?;
+        let time = u32::from_str(time_str).parse_error()?;
         moves.push(TimedMove(mv, time));
     }
     Ok(moves)
@@ -0,0 +1,148 @@
+use crate::action::{Action, ActionList, StoredAction};
+use std::collections::{HashMap, HashSet};
+
+/// Space used by one kind of stored action
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StorageCategory {
+    pub actions: usize,
+    /// Serialized size in bytes, before database compression
+    pub bytes: usize,
+}
+
+/// Size of the solve history, broken down by the kind of action stored
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StorageUsage {
+    /// New solves, including their move data
+    pub solves: StorageCategory,
+    /// Penalties, deletions, tags and other changes to existing solves
+    pub solve_edits: StorageCategory,
+    /// Session names, merges, participants, goals and attempt groups
+    pub sessions: StorageCategory,
+    pub preferences: StorageCategory,
+    /// Actions that would be removed by compacting the history. These are
+    /// also counted in the categories above.
+    pub superseded: StorageCategory,
+}
+
+impl StorageUsage {
+    pub fn total(&self) -> StorageCategory {
+        StorageCategory {
+            actions: self.solves.actions
+                + self.solve_edits.actions
+                + self.sessions.actions
+                + self.preferences.actions,
+            bytes: self.solves.bytes
+                + self.solve_edits.bytes
+                + self.sessions.bytes
+                + self.preferences.bytes,
+        }
+    }
+
+    pub(crate) fn add_list(&mut self, list: &ActionList) {
+        let superseded = superseded_actions(list);
+        let mut solves = Vec::new();
+        let mut solve_edits = Vec::new();
+        let mut sessions = Vec::new();
+        let mut preferences = Vec::new();
+        let mut removed = Vec::new();
+        for action in list {
+            match &action.action {
+                Action::NewSolve(_) => solves.push(action.clone()),
+                Action::Penalty(_, _)
+                | Action::DeleteSolve(_)
+                | Action::ChangeSession(_, _)
+                | Action::ChangeParticipant(_, _)
+                | Action::TagSolve(_, _)
+                | Action::UntagSolve(_, _) => solve_edits.push(action.clone()),
+                Action::RenameSession(_, _)
+                | Action::MergeSessions(_, _)
+                | Action::AddParticipant(_, _, _)
+                | Action::RemoveParticipant(_, _)
+                | Action::SetSessionGoal(_, _, _)
+                | Action::AttemptGroup(_, _, _) => sessions.push(action.clone()),
+                Action::SetPreference(_, _) => preferences.push(action.clone()),
+            }
+            if superseded.contains(&action.id) {
+                removed.push(action.clone());
+            }
+        }
+
+        Self::add_actions(&mut self.solves, &solves);
+        Self::add_actions(&mut self.solve_edits, &solve_edits);
+        Self::add_actions(&mut self.sessions, &sessions);
+        Self::add_actions(&mut self.preferences, &preferences);
+        Self::add_actions(&mut self.superseded, &removed);
+    }
+
+    fn add_actions(category: &mut StorageCategory, actions: &[StoredAction]) {
+        if actions.len() != 0 {
+            category.actions += actions.len();
+            category.bytes += StoredAction::serialize_list(actions).len();
+        }
+    }
+}
+
+/// Finds the actions of a list that can be removed without changing the
+/// resulting history. These are changes that are overwritten by a later
+/// action in the same list, such as earlier values of a preference, and all
+/// actions of solves that were both created and deleted within the list.
+/// Actions are only compared within a list, as actions in other lists may
+/// not have been applied yet on other devices.
+pub(crate) fn superseded_actions(list: &ActionList) -> HashSet<String> {
+    let mut result = HashSet::new();
+
+    // Only the last action for each of these keys has an effect
+    let mut seen = HashSet::new();
+    let actions: Vec<&StoredAction> = list.iter().collect();
+    for action in actions.iter().rev() {
+        let key = match &action.action {
+            Action::Penalty(solve, _) => ("penalty", solve.as_str(), ""),
+            Action::ChangeParticipant(solve, _) => ("participant", solve.as_str(), ""),
+            Action::TagSolve(solve, tag) | Action::UntagSolve(solve, tag) => {
+                ("tag", solve.as_str(), tag.as_str())
+            }
+            Action::RenameSession(session, _) => ("name", session.as_str(), ""),
+            Action::SetSessionGoal(session, kind, _) => ("goal", session.as_str(), kind.as_str()),
+            Action::SetPreference(name, _) => ("preference", name.as_str(), ""),
+            _ => continue,
+        };
+        if !seen.insert(key) {
+            result.insert(action.id.clone());
+        }
+    }
+
+    // Solves that were created and deleted within this list leave nothing
+    // behind, so every action on them can be removed. Only solves that end
+    // up deleted are included, in case a solve was restored.
+    let mut deleted: HashMap<&str, bool> = HashMap::new();
+    for action in &actions {
+        match &action.action {
+            Action::NewSolve(solve) => {
+                deleted.insert(solve.id.as_str(), false);
+            }
+            Action::DeleteSolve(solve) => {
+                if let Some(deleted) = deleted.get_mut(solve.as_str()) {
+                    *deleted = true;
+                }
+            }
+            _ => (),
+        }
+    }
+    for action in &actions {
+        let solve = match &action.action {
+            Action::NewSolve(solve) => solve.id.as_str(),
+            Action::Penalty(solve, _)
+            | Action::DeleteSolve(solve)
+            | Action::ChangeSession(solve, _)
+            | Action::ChangeParticipant(solve, _)
+            | Action::TagSolve(solve, _)
+            | Action::UntagSolve(solve, _) => solve.as_str(),
+            _ => continue,
+        };
+        if deleted.get(solve) == Some(&true) {
+            result.insert(action.id.clone());
+        }
+    }
+
+    result
+}
@@ -0,0 +1,134 @@
+use crate::attempt::{AttemptFormat, AttemptResult};
+use crate::common::{Move, SolveType};
+use crate::history::History;
+use crate::report::optional_time_string;
+
+#[cfg(not(feature = "no_solver"))]
+use crate::rand::StandardRandomSource;
+#[cfg(not(feature = "no_solver"))]
+use crate::scramble_sheet::generate_scrambles;
+
+/// A simulated competition round. Scrambles for the whole round are fixed
+/// when the round starts, as they would be at a competition, and the solves
+/// are grouped into a formal attempt once the round is complete.
+#[derive(Clone, Debug)]
+pub struct CompetitionRound {
+    solve_type: SolveType,
+    format: AttemptFormat,
+    scrambles: Vec<Vec<Move>>,
+    solves: Vec<String>,
+}
+
+impl CompetitionRound {
+    /// Starts a new round in the official format of the event, with newly
+    /// generated scrambles
+    #[cfg(not(feature = "no_solver"))]
+    pub fn new(solve_type: SolveType) -> Self {
+        let format = round_format(solve_type);
+        let scrambles =
+            generate_scrambles(solve_type, format.solve_count(), &mut StandardRandomSource);
+        Self {
+            solve_type,
+            format,
+            scrambles,
+            solves: Vec::new(),
+        }
+    }
+
+    /// Starts a new round with the given scrambles, such as scrambles from a
+    /// scramble sheet. Returns `None` if the number of scrambles does not
+    /// match the format.
+    pub fn with_scrambles(
+        solve_type: SolveType,
+        format: AttemptFormat,
+        scrambles: Vec<Vec<Move>>,
+    ) -> Option<Self> {
+        if scrambles.len() != format.solve_count() {
+            return None;
+        }
+        Some(Self {
+            solve_type,
+            format,
+            scrambles,
+            solves: Vec::new(),
+        })
+    }
+
+    pub fn solve_type(&self) -> SolveType {
+        self.solve_type
+    }
+
+    pub fn format(&self) -> AttemptFormat {
+        self.format
+    }
+
+    /// Number of solves completed in this round
+    pub fn solve_count(&self) -> usize {
+        self.solves.len()
+    }
+
+    /// Scramble for the next solve of the round, or `None` if the round is
+    /// complete
+    pub fn current_scramble(&self) -> Option<&[Move]> {
+        self.scrambles
+            .get(self.solves.len())
+            .map(|scramble| scramble.as_slice())
+    }
+
+    /// Records a solve done with the current scramble
+    pub fn add_solve(&mut self, solve_id: String) {
+        if !self.is_complete() {
+            self.solves.push(solve_id);
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.solves.len() >= self.format.solve_count()
+    }
+
+    /// Groups the solves of a complete round into an attempt, and adds the
+    /// round result to the note of the session. Returns the result of the
+    /// round, or `None` if the round is not complete or the solves could not
+    /// be grouped.
+    pub fn finish(&self, history: &mut History) -> Option<AttemptResult> {
+        if !self.is_complete() {
+            return None;
+        }
+        history.group_attempt(self.format, &self.solves)?;
+        let group = history.solve_attempt_group(&self.solves[0])?;
+        let times: Vec<Option<u32>> = group
+            .solves
+            .iter()
+            .map(|solve| solve.final_time())
+            .collect();
+        let result = self.format.result(&times)?;
+
+        let times: Vec<String> = times
+            .iter()
+            .map(|time| optional_time_string(*time))
+            .collect();
+        let line = format!(
+            "Competition round ({}): {} ({})",
+            self.format.to_string(),
+            optional_time_string(result.ranked()),
+            times.join(", ")
+        );
+        let session = group.solves[0].session.clone();
+        let note = match history.session_note(&session) {
+            Some(note) => format!("{}\n{}", note, line),
+            None => line,
+        };
+        history.set_session_note(session, note);
+        Some(result)
+    }
+}
+
+/// Format of a round of an event in WCA competitions
+#[cfg(not(feature = "no_solver"))]
+fn round_format(solve_type: SolveType) -> AttemptFormat {
+    match solve_type {
+        SolveType::Blind3x3x3 => AttemptFormat::BestOf3,
+        SolveType::Standard6x6x6 | SolveType::Standard7x7x7 => AttemptFormat::MeanOf3,
+        _ => AttemptFormat::AverageOf5,
+    }
+}
@@ -10,6 +10,8 @@ use crate::common::{
     CornerOrientationMoveTable, CornerOrientationPruneTable, CornerPermutationMoveTable,
     CornerPermutationPruneTable, MoveSequence,
 };
+#[cfg(not(feature = "no_solver"))]
+use crate::SolverOptions;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 /// A 2x2x2 cube represented in piece format (optimal for computational algorithms).
@@ -61,11 +63,14 @@ struct Solver {
 
 #[cfg(not(feature = "no_solver"))]
 impl Solver {
-    fn new(cube: &Cube2x2x2) -> Self {
+    fn new(cube: &Cube2x2x2, options: &SolverOptions) -> Self {
         Self {
             initial_state: cube.clone(),
             moves: Vec::new(),
-            max_moves: Cube2x2x2::MAX_SOLUTION_MOVES,
+            max_moves: options
+                .max_length
+                .unwrap_or(Cube2x2x2::MAX_SOLUTION_MOVES)
+                .min(Cube2x2x2::MAX_SOLUTION_MOVES),
             solution: None,
         }
     }
@@ -283,14 +288,12 @@ impl Cube for Cube2x2x2 {
         self.as_faces().colors()
     }
 
+    /// The 2x2x2 solver always finds the shortest solution first, so only the
+    /// maximum length option has any effect
     #[cfg(not(feature = "no_solver"))]
-    fn solve(&self) -> Option<Vec<Move>> {
-        Solver::new(self).solve()
-    }
-
-    #[cfg(not(feature = "no_solver"))]
-    fn solve_fast(&self) -> Option<Vec<Move>> {
-        Solver::new(self).solve()
+    fn solve_with_options(&self, options: &SolverOptions) -> Option<Vec<Move>> {
+        crate::tables::ensure_solver_tables().ok()?;
+        Solver::new(self, options).solve()
     }
 
     fn reset(&mut self) {
@@ -484,13 +487,8 @@ impl Cube for Cube2x2x2Faces {
     }
 
     #[cfg(not(feature = "no_solver"))]
-    fn solve(&self) -> Option<Vec<Move>> {
-        self.as_pieces().solve()
-    }
-
-    #[cfg(not(feature = "no_solver"))]
-    fn solve_fast(&self) -> Option<Vec<Move>> {
-        self.as_pieces().solve_fast()
+    fn solve_with_options(&self, options: &SolverOptions) -> Option<Vec<Move>> {
+        self.as_pieces().solve_with_options(options)
     }
 
     fn reset(&mut self) {
@@ -546,3 +544,11 @@ pub fn scramble_2x2x2() -> Vec<Move> {
     let solution = state.solve().unwrap();
     solution.inverse()
 }
+
+/// Generates a random scramble using the given source of randomness
+#[cfg(not(feature = "no_solver"))]
+pub fn scramble_2x2x2_sourced<T: RandomSource>(rng: &mut T) -> Vec<Move> {
+    let state = Cube2x2x2::sourced_random(rng);
+    let solution = state.solve().unwrap();
+    solution.inverse()
+}
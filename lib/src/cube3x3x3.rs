@@ -1,18 +1,32 @@
+use crate::error::{Error, Result};
+use crate::tables::table3x3x3::CUBE3_PIECE_MOVES;
 use crate::{
     Color, Corner, CornerPiece, Cube, CubeFace, FaceRotation, InitialCubeState, Move, RandomSource,
     RotationDirection,
 };
+use anyhow::anyhow;
 use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
 #[cfg(not(feature = "no_solver"))]
 use crate::common::{CornerOrientationMoveTable, CornerPermutationMoveTable, MoveSequence};
 #[cfg(not(feature = "no_solver"))]
+use crate::{SolverOptions, StandardRandomSource};
+#[cfg(not(feature = "no_solver"))]
+use instant::Instant;
+#[cfg(not(feature = "no_solver"))]
 use std::convert::TryInto;
 
+#[cfg(all(not(feature = "no_solver"), not(target_arch = "wasm32")))]
+use rayon::prelude::*;
+
+#[cfg(all(not(feature = "no_solver"), feature = "large-tables"))]
+use crate::tables::large::{active_large_tables, u_edge_index, u_edge_move, LargePhase2PruneTable};
+
 #[repr(u8)]
-#[derive(Debug, PartialEq, Eq, Clone, Copy, TryFromPrimitive)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, TryFromPrimitive, Serialize, Deserialize)]
 /// Identification of an edge piece. Names come from the faces of the cube this edge
 /// belongs to on a solved cube.
 pub enum Edge3x3x3 {
@@ -212,6 +226,75 @@ const fn n_choose_k(n: usize, k: usize) -> usize {
     result
 }
 
+/// Edge permutation of a 3x3x3 cube without orientations. The solver only needs
+/// the edge permutation to find the phase 2 coordinates at the end of phase 1,
+/// so it tracks this instead of moving full piece arrays.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct PackedEdges3x3x3 {
+    pieces: [u8; 12],
+}
+
+impl PackedEdges3x3x3 {
+    pub(crate) fn new(cube: &Cube3x3x3) -> Self {
+        let mut pieces = [0; 12];
+        for i in 0..12 {
+            pieces[i] = cube.edges[i].piece as u8;
+        }
+        Self { pieces }
+    }
+
+    #[cfg(not(feature = "no_solver"))]
+    pub(crate) fn do_move(&mut self, mv: Move) {
+        let table = &CUBE3_PIECE_MOVES[mv as u8 as usize % Move::count_3x3x3()];
+        let old_pieces = self.pieces;
+        for i in 0..12 {
+            self.pieces[i] = old_pieces[table.edges[i].0 as usize];
+        }
+    }
+
+    /// Gets the piece at a given edge position
+    #[cfg(all(not(feature = "no_solver"), feature = "large-tables"))]
+    pub(crate) fn piece(&self, edge: usize) -> u8 {
+        self.pieces[edge]
+    }
+
+    fn phase_2_edge_permutation_index(&self) -> u16 {
+        let mut result = 0;
+        for i in 0..7 {
+            // Get index in set of remaining options by checking how many of the entries
+            // are greater than this one (which is the index in the sorted list of
+            // remaining options)
+            let mut cur = 0;
+            for j in i + 1..8 {
+                if self.pieces[i] > self.pieces[j] {
+                    cur += 1;
+                }
+            }
+            result = (result + cur) * (7 - i as u16);
+        }
+        result
+    }
+
+    fn phase_2_equatorial_edge_permutation_index(&self) -> u16 {
+        let mut result = 0;
+        for i in 0..3 {
+            // Get index in set of remaining options by checking how many of the entries
+            // are greater than this one (which is the index in the sorted list of
+            // remaining options)
+            let mut cur = 0;
+            for j in i + 1..4 {
+                if self.pieces[i + Edge3x3x3::FR as u8 as usize]
+                    > self.pieces[j + Edge3x3x3::FR as u8 as usize]
+                {
+                    cur += 1;
+                }
+            }
+            result = (result + cur) * (3 - i as u16);
+        }
+        result
+    }
+}
+
 #[cfg(not(feature = "no_solver"))]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 struct Phase1IndexCube {
@@ -257,15 +340,19 @@ struct Phase2IndexCube {
     corner_permutation: u16,
     edge_permutation: u16,
     equatorial_edge_permutation: u16,
+    #[cfg(feature = "large-tables")]
+    u_edges: u16,
 }
 
 #[cfg(not(feature = "no_solver"))]
 impl Phase2IndexCube {
-    fn new(phase_1: &Phase1IndexCube, pieces: &Cube3x3x3) -> Self {
+    fn new(phase_1: &Phase1IndexCube, edges: &PackedEdges3x3x3) -> Self {
         Self {
             corner_permutation: phase_1.corner_permutation,
-            edge_permutation: pieces.phase_2_edge_permutation_index(),
-            equatorial_edge_permutation: pieces.phase_2_equatorial_edge_permutation_index(),
+            edge_permutation: edges.phase_2_edge_permutation_index(),
+            equatorial_edge_permutation: edges.phase_2_equatorial_edge_permutation_index(),
+            #[cfg(feature = "large-tables")]
+            u_edges: u_edge_index(edges),
         }
     }
 
@@ -277,6 +364,8 @@ impl Phase2IndexCube {
                 self.equatorial_edge_permutation,
                 mv,
             ),
+            #[cfg(feature = "large-tables")]
+            u_edges: u_edge_move(self.u_edges, mv),
         }
     }
 
@@ -290,21 +379,41 @@ impl Phase2IndexCube {
 #[cfg(not(feature = "no_solver"))]
 struct Solver {
     initial_state: Cube3x3x3,
+    initial_edges: PackedEdges3x3x3,
     moves: Vec<Move>,
-    optimal: bool,
     max_moves: usize,
+    max_length: usize,
+    target_length: usize,
+    deadline: Option<Instant>,
+    timed_out: bool,
+    nodes: usize,
     best_solution: Option<Vec<Move>>,
+    #[cfg(feature = "large-tables")]
+    large_tables: Option<&'static LargePhase2PruneTable>,
 }
 
 #[cfg(not(feature = "no_solver"))]
 impl Solver {
-    fn new(cube: &Cube3x3x3, optimal: bool) -> Self {
+    const TIME_CHECK_INTERVAL: usize = 256;
+
+    fn new(cube: &Cube3x3x3, options: &SolverOptions) -> Self {
+        let max_length = options
+            .max_length
+            .unwrap_or(Cube3x3x3::MAX_SOLUTION_MOVES)
+            .min(Cube3x3x3::MAX_SOLUTION_MOVES);
         Self {
             initial_state: cube.clone(),
+            initial_edges: PackedEdges3x3x3::new(cube),
             moves: Vec::new(),
-            optimal,
-            max_moves: Cube3x3x3::MAX_SOLUTION_MOVES,
+            max_moves: max_length + 1,
+            max_length,
+            target_length: options.target_length.unwrap_or(0),
+            deadline: options.max_time.map(|max_time| Instant::now() + max_time),
+            timed_out: false,
+            nodes: 0,
             best_solution: None,
+            #[cfg(feature = "large-tables")]
+            large_tables: active_large_tables(),
         }
     }
 
@@ -335,11 +444,11 @@ impl Solver {
                 if depth == 1 {
                     // Translate cube state into phase 2 index form
                     self.moves.push(*mv);
-                    let mut pieces = self.initial_state.clone();
+                    let mut edges = self.initial_edges;
                     for mv in &self.moves {
-                        pieces.do_move(*mv);
+                        edges.do_move(*mv);
                     }
-                    let cube = Phase2IndexCube::new(&new_cube, &pieces);
+                    let cube = Phase2IndexCube::new(&new_cube, &edges);
 
                     // Search for phase 2 solution using iterative deepening. Do not go beyond the maximum
                     // number of moves for the whole solve.
@@ -352,7 +461,7 @@ impl Solver {
                     }
 
                     self.moves.pop();
-                    if !self.optimal && self.best_solution.is_some() {
+                    if self.finished() {
                         break;
                     }
                 }
@@ -398,7 +507,7 @@ impl Solver {
             self.search_phase_1(new_cube, depth - 1);
             self.moves.pop();
 
-            if !self.optimal && self.best_solution.is_some() {
+            if self.finished() {
                 break;
             }
             if self.moves.len() + 1 >= self.max_moves {
@@ -411,8 +520,9 @@ impl Solver {
         // Check for solution
         if cube.is_phase_solved() {
             // Cube is solved, update best solution and stop this search path
-            if self.best_solution.is_none()
-                || self.moves.len() < self.best_solution.as_ref().unwrap().len()
+            if self.moves.len() <= self.max_length
+                && (self.best_solution.is_none()
+                    || self.moves.len() < self.best_solution.as_ref().unwrap().len())
             {
                 self.best_solution = Some(self.moves.clone());
                 self.max_moves = self.moves.len() - 1;
@@ -437,6 +547,12 @@ impl Solver {
         {
             return false;
         }
+        #[cfg(feature = "large-tables")]
+        if let Some(tables) = self.large_tables {
+            if tables.distance(cube.corner_permutation, cube.u_edges) > depth {
+                return false;
+            }
+        }
 
         // Need to go deeper. Iterate through the possible moves.
         let possible_moves = if self.moves.len() == 0 {
@@ -463,6 +579,29 @@ impl Solver {
         false
     }
 
+    /// Checks if the search should stop, either because a solution at the
+    /// target length has been found, or because time has run out and there is
+    /// a solution to return
+    fn finished(&mut self) -> bool {
+        let best_len = match &self.best_solution {
+            Some(solution) => solution.len(),
+            None => return false,
+        };
+        if best_len <= self.target_length {
+            return true;
+        }
+
+        // Checking the time is slow compared to a search step, so only check
+        // it every so often
+        if let Some(deadline) = self.deadline {
+            self.nodes += 1;
+            if !self.timed_out && self.nodes % Self::TIME_CHECK_INTERVAL == 0 {
+                self.timed_out = Instant::now() >= deadline;
+            }
+        }
+        self.timed_out
+    }
+
     fn solve(mut self) -> Option<Vec<Move>> {
         // If already solved, solution is zero moves
         if self.initial_state.is_solved() {
@@ -473,7 +612,7 @@ impl Solver {
 
         if cube.is_phase_solved() {
             // Phase 1 is already solved, translate cube state into phase 2 index form
-            let cube = Phase2IndexCube::new(&cube, &self.initial_state);
+            let cube = Phase2IndexCube::new(&cube, &self.initial_edges);
 
             // Search for phase 2 solution using iterative deepening. Do not go beyond the maximum
             // number of moves for the whole solve.
@@ -486,7 +625,10 @@ impl Solver {
             }
         } else {
             let mut depth = 1;
-            while depth <= Cube3x3x3::MAX_PHASE_1_MOVES && depth <= self.max_moves {
+            while depth <= Cube3x3x3::MAX_PHASE_1_MOVES
+                && depth <= self.max_moves
+                && !self.finished()
+            {
                 self.search_phase_1(cube, depth);
                 depth += 1;
             }
@@ -572,20 +714,7 @@ impl Cube3x3x3 {
     /// This is the phase 2 edge permutation index, which does not include the edges
     /// in the equatorial slice (significantly reducing the count).
     pub fn phase_2_edge_permutation_index(&self) -> u16 {
-        let mut result = 0;
-        for i in 0..7 {
-            // Get index in set of remaining options by checking how many of the entries
-            // are greater than this one (which is the index in the sorted list of
-            // remaining options)
-            let mut cur = 0;
-            for j in i + 1..8 {
-                if self.edges[i].piece as u8 > self.edges[j].piece as u8 {
-                    cur += 1;
-                }
-            }
-            result = (result + cur) * (7 - i as u16);
-        }
-        result
+        PackedEdges3x3x3::new(self).phase_2_edge_permutation_index()
     }
 
     /// Find the positions of the edge pieces that belong in the equatorial slice. For this
@@ -622,22 +751,7 @@ impl Cube3x3x3 {
     /// decreases in base, with the digits representing the index of the choice in the
     /// remaining possible choices).
     pub fn phase_2_equatorial_edge_permutation_index(&self) -> u16 {
-        let mut result = 0;
-        for i in 0..3 {
-            // Get index in set of remaining options by checking how many of the entries
-            // are greater than this one (which is the index in the sorted list of
-            // remaining options)
-            let mut cur = 0;
-            for j in i + 1..4 {
-                if self.edges[i + Edge3x3x3::FR as u8 as usize].piece as u8
-                    > self.edges[j + Edge3x3x3::FR as u8 as usize].piece as u8
-                {
-                    cur += 1;
-                }
-            }
-            result = (result + cur) * (3 - i as u16);
-        }
-        result
+        PackedEdges3x3x3::new(self).phase_2_equatorial_edge_permutation_index()
     }
 
     /// Gets this cube state in face color format
@@ -799,7 +913,28 @@ impl Cube for Cube3x3x3 {
     }
 
     fn do_move(&mut self, mv: Move) {
-        self.rotate_counted(mv.face(), mv.rotation());
+        // Apply the whole move at once with the piece movement table instead of
+        // rotating the face one quarter turn at a time. Wide moves are the same
+        // as face moves on a 3x3x3.
+        let table = &CUBE3_PIECE_MOVES[mv as u8 as usize % Move::count_3x3x3()];
+        let old_corners = self.corners;
+        let old_edges = self.edges;
+        for i in 0..8 {
+            let (src, orientation) = table.corners[i];
+            let piece = old_corners[src as usize];
+            self.corners[i] = CornerPiece {
+                piece: piece.piece,
+                orientation: (piece.orientation + orientation) % 3,
+            };
+        }
+        for i in 0..12 {
+            let (src, orientation) = table.edges[i];
+            let piece = old_edges[src as usize];
+            self.edges[i] = EdgePiece3x3x3 {
+                piece: piece.piece,
+                orientation: piece.orientation ^ orientation,
+            };
+        }
     }
 
     fn size(&self) -> usize {
@@ -811,13 +946,9 @@ impl Cube for Cube3x3x3 {
     }
 
     #[cfg(not(feature = "no_solver"))]
-    fn solve(&self) -> Option<Vec<Move>> {
-        Solver::new(self, true).solve()
-    }
-
-    #[cfg(not(feature = "no_solver"))]
-    fn solve_fast(&self) -> Option<Vec<Move>> {
-        Solver::new(self, false).solve()
+    fn solve_with_options(&self, options: &SolverOptions) -> Option<Vec<Move>> {
+        crate::tables::ensure_solver_tables().ok()?;
+        Solver::new(self, options).solve()
     }
 
     fn reset(&mut self) {
@@ -859,6 +990,12 @@ impl Cube3x3x3Faces {
         self.state[Self::idx(face, row, col)]
     }
 
+    /// Sets the color for a given place on the cube, for entering a cube state by hand.
+    /// Use `try_as_pieces` to check that the resulting state is valid.
+    pub fn set_color(&mut self, face: CubeFace, row: usize, col: usize, color: Color) {
+        self.state[Self::idx(face, row, col)] = color;
+    }
+
     /// Gets the color for a given place on the cube. For a given `face`, the `row` and
     /// `col` represent the zero-indexed position on the face to be accessed.
     pub(crate) fn color_by_idx(&self, idx: usize) -> Color {
@@ -947,6 +1084,78 @@ impl Cube3x3x3Faces {
 
         pieces
     }
+
+    /// Gets this cube state in piece format, returning an error if the colors
+    /// do not describe a cube that can be reached by turning the faces.
+    pub fn try_as_pieces(&self) -> Result<Cube3x3x3> {
+        // Check sticker counts first, as this is the most common mistake when a
+        // state is entered by hand
+        let mut counts = [0; 6];
+        for color in self.state.iter() {
+            counts[*color as u8 as usize] += 1;
+        }
+        if let Some(color) = (0..6).find(|color| counts[*color] != 9) {
+            return Err(Error::Parse(anyhow!(
+                "Cube state has {} {} stickers instead of 9",
+                counts[color],
+                Color::try_from(color as u8)
+                    .unwrap()
+                    .to_str()
+                    .to_lowercase()
+            )));
+        }
+
+        let pieces = self.as_pieces();
+
+        // Pieces that could not be found are left unchanged by `as_pieces`, so
+        // converting back will not match. This also checks the centers.
+        if pieces.as_faces() != *self {
+            return Err(Error::Parse(anyhow!("Cube state contains invalid pieces")));
+        }
+
+        // Each piece must appear exactly once
+        let mut corners_seen = [false; 8];
+        for corner in &pieces.corners {
+            if std::mem::replace(&mut corners_seen[corner.piece as u8 as usize], true) {
+                return Err(Error::Parse(anyhow!(
+                    "Cube state contains duplicate corners"
+                )));
+            }
+        }
+        let mut edges_seen = [false; 12];
+        for edge in &pieces.edges {
+            if std::mem::replace(&mut edges_seen[edge.piece as u8 as usize], true) {
+                return Err(Error::Parse(anyhow!("Cube state contains duplicate edges")));
+            }
+        }
+
+        // Check that the state is solvable
+        let twist: u32 = pieces.corners.iter().map(|c| c.orientation as u32).sum();
+        if twist % 3 != 0 {
+            return Err(Error::Parse(anyhow!("Cube state has a twisted corner")));
+        }
+        let flip: u32 = pieces.edges.iter().map(|e| e.orientation as u32).sum();
+        if flip % 2 != 0 {
+            return Err(Error::Parse(anyhow!("Cube state has a flipped edge")));
+        }
+        let corner_parity = permutation_parity(pieces.corners.iter().map(|c| c.piece as u8));
+        let edge_parity = permutation_parity(pieces.edges.iter().map(|e| e.piece as u8));
+        if corner_parity != edge_parity {
+            return Err(Error::Parse(anyhow!("Cube state has swapped pieces")));
+        }
+
+        Ok(pieces)
+    }
+}
+
+/// Returns true if the permutation is odd. Counts inversions, which is fine
+/// for the small number of pieces on a cube.
+fn permutation_parity(pieces: impl Iterator<Item = u8> + Clone) -> bool {
+    let mut inversions = 0;
+    for (i, a) in pieces.clone().enumerate() {
+        inversions += pieces.clone().skip(i + 1).filter(|b| *b < a).count();
+    }
+    inversions % 2 != 0
 }
 
 impl FaceRotation for Cube3x3x3Faces {
@@ -1055,13 +1264,8 @@ impl Cube for Cube3x3x3Faces {
     }
 
     #[cfg(not(feature = "no_solver"))]
-    fn solve(&self) -> Option<Vec<Move>> {
-        self.as_pieces().solve()
-    }
-
-    #[cfg(not(feature = "no_solver"))]
-    fn solve_fast(&self) -> Option<Vec<Move>> {
-        self.as_pieces().solve_fast()
+    fn solve_with_options(&self, options: &SolverOptions) -> Option<Vec<Move>> {
+        self.as_pieces().solve_with_options(options)
     }
 
     fn reset(&mut self) {
@@ -1118,6 +1322,49 @@ pub fn scramble_3x3x3() -> Vec<Move> {
     solution.inverse()
 }
 
+/// Generates a random scramble using the given source of randomness
+#[cfg(not(feature = "no_solver"))]
+pub fn scramble_3x3x3_sourced<T: RandomSource>(rng: &mut T) -> Vec<Move> {
+    let state = Cube3x3x3::sourced_random(rng);
+    let solution = state.solve().unwrap();
+    solution.inverse()
+}
+
+/// Generates a batch of random scrambles. The random states are generated in
+/// order and then solved in parallel, so the result is the same as calling
+/// `scramble_3x3x3` repeatedly, only faster.
+#[cfg(not(feature = "no_solver"))]
+pub fn scramble_3x3x3_batch(count: usize) -> Vec<Vec<Move>> {
+    scramble_3x3x3_batch_sourced(&mut StandardRandomSource, count)
+}
+
+/// Generates a batch of random scrambles using the given source of randomness.
+/// The scrambles are the same as calling `scramble_3x3x3_sourced` `count` times
+/// with the same source.
+#[cfg(not(feature = "no_solver"))]
+pub fn scramble_3x3x3_batch_sourced<T: RandomSource>(rng: &mut T, count: usize) -> Vec<Vec<Move>> {
+    let states: Vec<Cube3x3x3> = (0..count).map(|_| Cube3x3x3::sourced_random(rng)).collect();
+
+    // Threads are not available on the web, solve the states in order there
+    #[cfg(not(target_arch = "wasm32"))]
+    let states = states.into_par_iter();
+    #[cfg(target_arch = "wasm32")]
+    let states = states.into_iter();
+
+    states
+        .map(|state| state.solve().unwrap().inverse())
+        .collect()
+}
+
+/// Generates a scramble that sets up the given state from a solved cube, such
+/// as a specific case for a trainer. Returns an error if the state cannot be
+/// reached by turning the faces.
+#[cfg(not(feature = "no_solver"))]
+pub fn scramble_to_state(target: &Cube3x3x3) -> Result<Vec<Move>> {
+    let target = target.as_faces().try_as_pieces()?;
+    Ok(target.try_solve()?.inverse())
+}
+
 /// Generates a random scramble very fast, but with more moves required than normal
 #[cfg(not(feature = "no_solver"))]
 pub fn scramble_3x3x3_fast() -> Vec<Move> {
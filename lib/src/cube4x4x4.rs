@@ -8,6 +8,8 @@ use std::convert::TryFrom;
 
 #[cfg(not(feature = "no_solver"))]
 use crate::common::MoveSequence;
+#[cfg(not(feature = "no_solver"))]
+use crate::SolverOptions;
 
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, TryFromPrimitive)]
@@ -103,6 +105,64 @@ impl Cube4x4x4 {
         result & 2 != 0
     }
 
+    /// Checks if the cube is reduced to a 3x3x3, with all centers solved and all
+    /// edge pieces paired together. The centers and edges do not need to be in
+    /// their final positions.
+    pub fn is_reduced(&self) -> bool {
+        let faces = self.as_faces();
+        for face in &[
+            CubeFace::Top,
+            CubeFace::Front,
+            CubeFace::Right,
+            CubeFace::Back,
+            CubeFace::Left,
+            CubeFace::Bottom,
+        ] {
+            let center = faces.color(*face, 1, 1);
+            if faces.color(*face, 1, 2) != center
+                || faces.color(*face, 2, 1) != center
+                || faces.color(*face, 2, 2) != center
+            {
+                return false;
+            }
+
+            // Each edge is checked on both of its faces, so only one side
+            // needs to be checked per face
+            if faces.color(*face, 0, 1) != faces.color(*face, 0, 2)
+                || faces.color(*face, 3, 1) != faces.color(*face, 3, 2)
+                || faces.color(*face, 1, 0) != faces.color(*face, 2, 0)
+                || faces.color(*face, 1, 3) != faces.color(*face, 2, 3)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Checks for PLL parity on a reduced cube. PLL parity exists if the paired
+    /// edges are in a permutation that can't be reached with 3x3x3 moves, which
+    /// is the case when the parity of the corners, paired edges, and center faces
+    /// don't match. The result is only meaningful if `is_reduced` is true.
+    pub fn pll_parity(&self) -> bool {
+        let mut corners = [0; 8];
+        for (i, corner) in self.corners.iter().enumerate() {
+            corners[i] = corner.piece as u8 as usize;
+        }
+
+        let mut edges = [0; 12];
+        for (i, edge) in edges.iter_mut().enumerate() {
+            *edge = self.edges[i * 2].piece as u8 as usize / 2;
+        }
+
+        let mut centers = [0; 6];
+        for (i, center) in centers.iter_mut().enumerate() {
+            let face = CubeFace::try_from(i as u8).unwrap();
+            *center = self.center_color(face, 0, 0).face() as u8 as usize;
+        }
+
+        permutation_parity(&corners) ^ permutation_parity(&edges) ^ permutation_parity(&centers)
+    }
+
     /// Gets this cube state in face color format
     pub fn as_faces(&self) -> Cube4x4x4Faces {
         let mut faces = Cube4x4x4Faces::new();
@@ -348,12 +408,7 @@ impl Cube for Cube4x4x4 {
     }
 
     #[cfg(not(feature = "no_solver"))]
-    fn solve(&self) -> Option<Vec<Move>> {
-        unimplemented!()
-    }
-
-    #[cfg(not(feature = "no_solver"))]
-    fn solve_fast(&self) -> Option<Vec<Move>> {
+    fn solve_with_options(&self, _options: &SolverOptions) -> Option<Vec<Move>> {
         unimplemented!()
     }
 
@@ -661,13 +716,8 @@ impl Cube for Cube4x4x4Faces {
     }
 
     #[cfg(not(feature = "no_solver"))]
-    fn solve(&self) -> Option<Vec<Move>> {
-        self.as_pieces().solve()
-    }
-
-    #[cfg(not(feature = "no_solver"))]
-    fn solve_fast(&self) -> Option<Vec<Move>> {
-        self.as_pieces().solve_fast()
+    fn solve_with_options(&self, options: &SolverOptions) -> Option<Vec<Move>> {
+        self.as_pieces().solve_with_options(options)
     }
 
     fn reset(&mut self) {
@@ -731,3 +781,26 @@ pub fn scramble_4x4x4_fast() -> Vec<Move> {
     let solution = state.solve_fast().unwrap();
     solution.inverse()
 }
+
+/// Returns true if the permutation is odd
+fn permutation_parity(perm: &[usize]) -> bool {
+    let mut visited = [false; 24];
+    let mut parity = false;
+    for start in 0..perm.len() {
+        if visited[start] {
+            continue;
+        }
+        // A cycle of length n is made of n - 1 swaps
+        let mut i = start;
+        let mut length = 0;
+        while !visited[i] {
+            visited[i] = true;
+            i = perm[i];
+            length += 1;
+        }
+        if length % 2 == 0 {
+            parity = !parity;
+        }
+    }
+    parity
+}
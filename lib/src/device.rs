@@ -0,0 +1,40 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// Information remembered about a Bluetooth cube or timer that has been
+/// connected on this device. Known devices are stored locally and are not
+/// synchronized, since device identifiers differ between hosts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KnownDevice {
+    /// Identifier of the device, which is its Bluetooth address
+    pub id: String,
+    pub name: String,
+    /// Time the device was last connected, in milliseconds since the epoch
+    pub last_seen: i64,
+    /// Battery percentage last reported by the device
+    pub battery: Option<u32>,
+    /// Ratio between the device's clock and real time learned during the
+    /// last connection. Restoring it avoids recalibrating on every connect.
+    pub clock_ratio: Option<f64>,
+}
+
+impl KnownDevice {
+    pub fn new(id: String, name: String) -> Self {
+        Self {
+            id,
+            name,
+            last_seen: Local::now().timestamp_millis(),
+            battery: None,
+            clock_ratio: None,
+        }
+    }
+
+    pub fn last_seen(&self) -> DateTime<Local> {
+        Local.timestamp_millis(self.last_seen)
+    }
+
+    /// Marks the device as seen now
+    pub fn seen(&mut self) {
+        self.last_seen = Local::now().timestamp_millis();
+    }
+}
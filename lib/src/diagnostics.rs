@@ -0,0 +1,237 @@
+// Diagnostic events are emitted through `tracing` when the `diagnostics` feature is
+// enabled. Without the feature the macros expand to nothing, so call sites must not
+// rely on them to use values that are otherwise unused. The instrumented modules are
+// all optional, so the macros may be unused in some feature combinations.
+
+/// Emits a diagnostic event at the given level, for example
+/// `diagnostic!(debug, moves = count, "Received moves")`.
+#[allow(unused_macros)]
+macro_rules! diagnostic {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "diagnostics")]
+        tracing::$level!($($arg)+);
+    };
+}
+
+/// Enters a diagnostic span for the rest of the enclosing scope. The returned
+/// guard must be kept alive, for example `let _span = diagnostic_span!("sync");`.
+#[allow(unused_macros)]
+macro_rules! diagnostic_span {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "diagnostics")]
+        let span = tracing::debug_span!($($arg)+).entered();
+        #[cfg(not(feature = "diagnostics"))]
+        let span = crate::diagnostics::DisabledSpan;
+        span
+    }};
+}
+
+#[cfg(not(feature = "diagnostics"))]
+#[allow(dead_code)]
+pub(crate) struct DisabledSpan;
+
+#[cfg(feature = "diagnostics")]
+pub use log::{install_diagnostic_log, DiagnosticEntry, DiagnosticLevel, DiagnosticLog};
+
+#[cfg(feature = "diagnostics")]
+mod log {
+    use chrono::{DateTime, Local};
+    use std::collections::VecDeque;
+    use std::fmt::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Level, Metadata, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Severity of a diagnostic entry
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum DiagnosticLevel {
+        Trace,
+        Debug,
+        Info,
+        Warning,
+        Error,
+    }
+
+    /// A single event captured by the diagnostic log
+    #[derive(Clone, Debug)]
+    pub struct DiagnosticEntry {
+        pub time: DateTime<Local>,
+        pub level: DiagnosticLevel,
+        /// Module that emitted the event, without the crate prefix
+        pub target: String,
+        /// Names of the spans the event was emitted in, outermost first
+        pub spans: Vec<String>,
+        pub message: String,
+    }
+
+    /// Keeps the most recent diagnostic events in memory so that they can be
+    /// shown to the user. Clones share the same entries.
+    #[derive(Clone)]
+    pub struct DiagnosticLog {
+        entries: Arc<Mutex<VecDeque<DiagnosticEntry>>>,
+        capacity: usize,
+        min_level: DiagnosticLevel,
+    }
+
+    struct DiagnosticLayer {
+        log: DiagnosticLog,
+    }
+
+    #[derive(Default)]
+    struct MessageVisitor {
+        message: String,
+        fields: String,
+    }
+
+    /// Installs a global `tracing` subscriber that records events from this
+    /// crate into a `DiagnosticLog`. Only the most recent `capacity` events at
+    /// or above `min_level` are kept. Returns `None` if a global subscriber has
+    /// already been installed.
+    pub fn install_diagnostic_log(
+        capacity: usize,
+        min_level: DiagnosticLevel,
+    ) -> Option<DiagnosticLog> {
+        let log = DiagnosticLog {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+            min_level,
+        };
+        let subscriber = tracing_subscriber::registry().with(DiagnosticLayer { log: log.clone() });
+        tracing::subscriber::set_global_default(subscriber).ok()?;
+        Some(log)
+    }
+
+    impl DiagnosticLevel {
+        fn from_level(level: &Level) -> Self {
+            match *level {
+                Level::TRACE => DiagnosticLevel::Trace,
+                Level::DEBUG => DiagnosticLevel::Debug,
+                Level::INFO => DiagnosticLevel::Info,
+                Level::WARN => DiagnosticLevel::Warning,
+                _ => DiagnosticLevel::Error,
+            }
+        }
+
+        pub fn name(&self) -> &'static str {
+            match self {
+                DiagnosticLevel::Trace => "TRACE",
+                DiagnosticLevel::Debug => "DEBUG",
+                DiagnosticLevel::Info => "INFO",
+                DiagnosticLevel::Warning => "WARN",
+                DiagnosticLevel::Error => "ERROR",
+            }
+        }
+    }
+
+    impl DiagnosticLog {
+        /// Returns a copy of the captured entries, oldest first
+        pub fn entries(&self) -> Vec<DiagnosticEntry> {
+            self.entries.lock().unwrap().iter().cloned().collect()
+        }
+
+        pub fn clear(&self) {
+            self.entries.lock().unwrap().clear();
+        }
+
+        /// Formats the captured entries as plain text, one entry per line, for
+        /// copying into bug reports
+        pub fn to_text(&self) -> String {
+            let mut result = String::new();
+            for entry in self.entries.lock().unwrap().iter() {
+                let _ = writeln!(result, "{}", entry);
+            }
+            result
+        }
+
+        fn push(&self, entry: DiagnosticEntry) {
+            let mut entries = self.entries.lock().unwrap();
+            while entries.len() >= self.capacity.max(1) {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+
+    impl fmt::Display for DiagnosticEntry {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{} {:5} {}",
+                self.time.format("%H:%M:%S%.3f"),
+                self.level.name(),
+                self.target
+            )?;
+            for span in &self.spans {
+                write!(f, ":{}", span)?;
+            }
+            write!(f, ": {}", self.message)
+        }
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for DiagnosticLayer {
+        fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+            metadata
+                .target()
+                .starts_with(module_path!().split("::").next().unwrap())
+                && DiagnosticLevel::from_level(metadata.level()) >= self.log.min_level
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            let message = if visitor.fields.is_empty() {
+                visitor.message
+            } else if visitor.message.is_empty() {
+                visitor.fields
+            } else {
+                format!("{} ({})", visitor.message, visitor.fields)
+            };
+
+            // Collect the names of the active spans, outermost first
+            let mut spans = Vec::new();
+            let mut current = ctx.lookup_current();
+            while let Some(span) = current {
+                spans.push(span.name().to_string());
+                current = span.parent();
+            }
+            spans.reverse();
+
+            let metadata = event.metadata();
+            let target = metadata.target();
+            self.log.push(DiagnosticEntry {
+                time: Local::now(),
+                level: DiagnosticLevel::from_level(metadata.level()),
+                target: target
+                    .split_once("::")
+                    .map(|(_, module)| module)
+                    .unwrap_or(target)
+                    .to_string(),
+                spans,
+                message,
+            });
+        }
+    }
+
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                let _ = write!(self.message, "{:?}", value);
+            } else {
+                if !self.fields.is_empty() {
+                    self.fields.push_str(", ");
+                }
+                let _ = write!(self.fields, "{}={:?}", field.name(), value);
+            }
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "message" {
+                self.message.push_str(value);
+            } else {
+                self.record_debug(field, &value);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt;
+
+/// Errors returned by the public API, grouped by the kind of failure so that
+/// callers can decide how to present or recover from them. Internally errors
+/// are tracked with `anyhow`, which is kept as the underlying error so that
+/// its message and context are preserved.
+#[derive(Debug)]
+pub enum Error {
+    /// Communication with a Bluetooth device failed, or the device sent data
+    /// that could not be decoded
+    Bluetooth(anyhow::Error),
+    /// Reading or writing the solve history, settings or sync state failed
+    Storage(anyhow::Error),
+    /// A network socket could not be opened, or sending or receiving data
+    /// over it failed
+    Network(anyhow::Error),
+    /// The solver or its tables are not available
+    Solver(anyhow::Error),
+    /// Data could not be parsed or encoded, such as move strings, imported
+    /// solves, sync messages or images
+    Parse(anyhow::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Underlying error with the full context of the failure
+    pub fn inner(&self) -> &anyhow::Error {
+        match self {
+            Self::Bluetooth(error)
+            | Self::Storage(error)
+            | Self::Network(error)
+            | Self::Solver(error)
+            | Self::Parse(error) => error,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inner())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner().source()
+    }
+}
+
+/// Converts internal `anyhow` results into public results of a given kind.
+/// Some kinds are only used when optional features are enabled.
+#[allow(dead_code)]
+pub(crate) trait ErrorKind<T> {
+    fn bluetooth_error(self) -> Result<T>;
+    fn storage_error(self) -> Result<T>;
+    fn network_error(self) -> Result<T>;
+    fn solver_error(self) -> Result<T>;
+    fn parse_error(self) -> Result<T>;
+}
+
+impl<T, E: Into<anyhow::Error>> ErrorKind<T> for std::result::Result<T, E> {
+    fn bluetooth_error(self) -> Result<T> {
+        self.map_err(|error| Error::Bluetooth(error.into()))
+    }
+
+    fn storage_error(self) -> Result<T> {
+        self.map_err(|error| Error::Storage(error.into()))
+    }
+
+    fn network_error(self) -> Result<T> {
+        self.map_err(|error| Error::Network(error.into()))
+    }
+
+    fn solver_error(self) -> Result<T> {
+        self.map_err(|error| Error::Solver(error.into()))
+    }
+
+    fn parse_error(self) -> Result<T> {
+        self.map_err(|error| Error::Parse(error.into()))
+    }
+}
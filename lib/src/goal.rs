@@ -0,0 +1,208 @@
+use crate::common::{Solve, SolveList};
+use crate::history::History;
+use std::collections::HashMap;
+
+/// Target for a practice session. Goals are stored per session and are
+/// synchronized across devices using `SetSessionGoal` actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionGoal {
+    /// Best average of 12 in the session at or below the target time in
+    /// milliseconds
+    Average12(u32),
+    /// Number of solves in the session
+    SolveCount(usize),
+    /// Practice time in seconds, measured from the first solve of the session
+    /// to the most recent one
+    Duration(u32),
+}
+
+/// Progress of a session toward one of its goals
+#[derive(Clone, Debug)]
+pub struct GoalProgress {
+    pub goal: SessionGoal,
+    /// Current value for the goal: the best ao12 in milliseconds, the number
+    /// of solves, or the seconds of practice. `None` if there is no ao12 yet.
+    pub current: Option<u32>,
+    /// Fraction of the goal that has been reached, from 0 to 1
+    pub fraction: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GoalMilestoneKind {
+    Halfway,
+    Complete,
+}
+
+/// Event emitted when a session reaches a milestone toward one of its goals
+#[derive(Clone, Debug)]
+pub struct GoalMilestone {
+    pub session: String,
+    pub goal: SessionGoal,
+    pub kind: GoalMilestoneKind,
+}
+
+/// Watches the current session for progress toward its goals and reports
+/// milestones as they are reached. Milestones that were already reached when
+/// a session or goal is first seen are not reported again.
+pub struct GoalTracker {
+    session: Option<String>,
+    update_id: Option<u64>,
+    reached: HashMap<&'static str, (SessionGoal, Option<GoalMilestoneKind>)>,
+}
+
+impl SessionGoal {
+    /// Kind of goal as stored in goal actions. A session has at most one goal
+    /// of each kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SessionGoal::Average12(_) => "ao12",
+            SessionGoal::SolveCount(_) => "solve_count",
+            SessionGoal::Duration(_) => "duration",
+        }
+    }
+
+    /// Value of the goal as stored in goal actions
+    pub fn value(&self) -> String {
+        match self {
+            SessionGoal::Average12(time) => time.to_string(),
+            SessionGoal::SolveCount(count) => count.to_string(),
+            SessionGoal::Duration(seconds) => seconds.to_string(),
+        }
+    }
+
+    /// Parses a goal from its stored kind and value. Returns `None` if the
+    /// kind is not known or the value is not valid.
+    pub fn parse(kind: &str, value: &str) -> Option<Self> {
+        match kind {
+            "ao12" => value.parse().ok().map(SessionGoal::Average12),
+            "solve_count" => value.parse().ok().map(SessionGoal::SolveCount),
+            "duration" => value.parse().ok().map(SessionGoal::Duration),
+            _ => None,
+        }
+    }
+}
+
+impl GoalProgress {
+    pub fn complete(&self) -> bool {
+        self.fraction >= 1.0
+    }
+
+    /// Highest milestone reached for this goal. Average goals only have a
+    /// completion milestone, as there is no meaningful halfway point.
+    pub fn milestone(&self) -> Option<GoalMilestoneKind> {
+        if self.complete() {
+            Some(GoalMilestoneKind::Complete)
+        } else if self.fraction >= 0.5 {
+            match self.goal {
+                SessionGoal::Average12(_) => None,
+                _ => Some(GoalMilestoneKind::Halfway),
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl GoalTracker {
+    pub fn new() -> Self {
+        Self {
+            session: None,
+            update_id: None,
+            reached: HashMap::new(),
+        }
+    }
+
+    /// Computes progress for the goals of the given session
+    pub fn progress(history: &History, session_id: &str) -> Vec<GoalProgress> {
+        let goals = history.session_goals(session_id);
+        if goals.len() == 0 {
+            return Vec::new();
+        }
+
+        // Goals can be set before the first solve, when the session does not exist yet
+        let solves = match history.sessions().get(session_id) {
+            Some(session) => session.to_vec(history),
+            None => Vec::new(),
+        };
+        goal_progress(&goals, &solves)
+    }
+
+    /// Checks the current session for newly reached milestones. This should be
+    /// called whenever the history may have changed, and is cheap when the
+    /// session has not been updated.
+    pub fn update(&mut self, history: &History) -> Vec<GoalMilestone> {
+        let session_id = history.current_session();
+        let update_id = history
+            .sessions()
+            .get(session_id)
+            .map(|session| session.update_id());
+
+        let new_session = self.session.as_deref() != Some(session_id);
+        if !new_session && self.update_id == update_id {
+            return Vec::new();
+        }
+        if new_session {
+            self.session = Some(session_id.to_string());
+            self.reached.clear();
+        }
+        self.update_id = update_id;
+
+        let mut milestones = Vec::new();
+        let mut reached = HashMap::new();
+        for progress in Self::progress(history, session_id) {
+            let milestone = progress.milestone();
+            match self.reached.get(progress.goal.kind()) {
+                Some((goal, previous)) if *goal == progress.goal => {
+                    if milestone > *previous {
+                        milestones.push(GoalMilestone {
+                            session: session_id.to_string(),
+                            goal: progress.goal,
+                            kind: milestone.unwrap(),
+                        });
+                    }
+                }
+                _ => {
+                    // Newly seen goal, record its progress without reporting
+                    // milestones that were reached before it was set.
+                }
+            }
+            reached.insert(progress.goal.kind(), (progress.goal, milestone));
+        }
+        self.reached = reached;
+        milestones
+    }
+}
+
+/// Computes progress toward each of the goals given the solves of a session
+pub fn goal_progress(goals: &[SessionGoal], solves: &[Solve]) -> Vec<GoalProgress> {
+    goals
+        .iter()
+        .map(|goal| {
+            let (current, fraction) = match *goal {
+                SessionGoal::Average12(target) => match solves.best_average(12) {
+                    Some(best) if best.time <= target => (Some(best.time), 1.0),
+                    Some(best) => (Some(best.time), target as f32 / best.time as f32),
+                    None => (None, 0.0),
+                },
+                SessionGoal::SolveCount(target) => (
+                    Some(solves.len() as u32),
+                    solves.len() as f32 / target.max(1) as f32,
+                ),
+                SessionGoal::Duration(target) => {
+                    let seconds = match (solves.first(), solves.last()) {
+                        (Some(first), Some(last)) => {
+                            (last.created - first.created).num_seconds().max(0) as u32
+                        }
+                        _ => 0,
+                    };
+                    (Some(seconds), seconds as f32 / target.max(1) as f32)
+                }
+            };
+            GoalProgress {
+                goal: *goal,
+                current,
+                fraction: fraction.min(1.0),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,92 @@
+use crate::common::{Cube, Move};
+use crate::cube3x3x3::Cube3x3x3;
+
+/// Result of performing a move while following a guided solve
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuidedSolveProgress {
+    /// Move followed the solution, or was part of a half turn of the next move
+    OnTrack,
+    /// Move did not follow the solution and a new solution was computed from
+    /// the resulting state
+    Recomputed,
+    /// Cube is now solved
+    Solved,
+}
+
+/// Walks the user through solving a cube from an arbitrary state, one move at
+/// a time. Moves are verified as they are performed, and the solution is
+/// recomputed if the user deviates from it.
+pub struct GuidedSolve {
+    cube: Cube3x3x3,
+    solution: Vec<Move>,
+    recompute_count: usize,
+}
+
+impl GuidedSolve {
+    pub fn new(cube: Cube3x3x3) -> Self {
+        let solution = cube.solve_fast().unwrap_or_default();
+        Self {
+            cube,
+            solution,
+            recompute_count: 0,
+        }
+    }
+
+    /// Current state of the cube being guided
+    pub fn cube(&self) -> &Cube3x3x3 {
+        &self.cube
+    }
+
+    /// Next move the user should perform, or `None` if the cube is solved
+    pub fn next_move(&self) -> Option<Move> {
+        self.solution.first().copied()
+    }
+
+    /// Moves remaining in the solution, starting with the next move
+    pub fn remaining_moves(&self) -> &[Move] {
+        &self.solution
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.cube.is_solved()
+    }
+
+    /// Number of times the solution has been recomputed because the user
+    /// performed a different move
+    pub fn recompute_count(&self) -> usize {
+        self.recompute_count
+    }
+
+    pub fn do_move(&mut self, mv: Move) -> GuidedSolveProgress {
+        self.cube.do_move(mv);
+        if self.cube.is_solved() {
+            self.solution.clear();
+            return GuidedSolveProgress::Solved;
+        }
+
+        if let Some(expected) = self.next_move() {
+            if mv.face() == expected.face() {
+                // Smart cubes report half turns as two quarter turns, so a turn
+                // of the expected face only needs to complete part of the move.
+                // Turning the face too far is also fine, the remaining rotation
+                // becomes the next move.
+                match Move::from_face_and_rotation(
+                    expected.face(),
+                    expected.rotation() - mv.rotation(),
+                ) {
+                    Some(remaining) => self.solution[0] = remaining,
+                    None => {
+                        self.solution.remove(0);
+                    }
+                }
+                return GuidedSolveProgress::OnTrack;
+            }
+        }
+
+        diagnostic!(debug, mv = ?mv, "Move deviated from guided solve, recomputing");
+        self.solution = self.cube.solve_fast().unwrap_or_default();
+        self.recompute_count += 1;
+        GuidedSolveProgress::Recomputed
+    }
+}
+
@@ -1,11 +1,27 @@
 use crate::action::{Action, ActionList, StoredAction};
-use crate::common::{MoveSequence, Penalty, Solve, SolveType, TimedMoveSequence};
-use crate::import::ImportedSession;
-use crate::request::{SyncRequest, SyncResponse};
+use crate::analysis::{
+    find_similar_solves, CFOPSkips, SimilarSolve, SimilarityCriteria, SkippedStep, SolveAnalysis,
+};
+use crate::attempt::{AttemptFormat, AttemptGroup};
+use crate::common::{
+    parse_move_string, Move, MoveSequence, Penalty, Solve, SolveInput, SolveType, TimedMoveSequence,
+};
+use crate::compact::{superseded_actions, StorageUsage};
+use crate::device::KnownDevice;
+use crate::error::ErrorKind;
+use crate::goal::SessionGoal;
+use crate::hooks::{SolveCompletion, SolveHookHandle};
+use crate::import::HistoryImport;
+use crate::official::OfficialResults;
+use crate::preferences::{Preference, Preferences};
+use crate::request::{RetryAfter, SyncRequest, SyncResponse, BULK_UPLOAD_INTERVAL_MS};
+use crate::session_stats::{solve_fingerprint, SessionStats};
 use crate::storage::{DeferredStorage, Storage};
-use crate::sync::{SyncOperation, SyncStatus};
+use crate::sync::{SyncDetails, SyncEndpoint, SyncOperation, SyncStatus};
+use crate::variant::ShapeMod;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Local};
+use instant::Instant;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::cmp::Ordering;
@@ -14,12 +30,29 @@ use std::convert::TryInto;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
+#[cfg(not(feature = "no_solver"))]
+use crate::rand::StandardRandomSource;
+#[cfg(not(feature = "no_solver"))]
+use crate::scramble_sheet::generate_scrambles;
+
+#[cfg(feature = "native-storage")]
+use crate::merge::MergeSource;
 #[cfg(feature = "native-storage")]
 use dirs::data_local_dir;
 #[cfg(feature = "native-storage")]
+use std::net::SocketAddr;
+#[cfg(feature = "native-storage")]
 use std::path::Path;
 
 const UNSYNCED: u32 = 0;
+const SYNC_RETRY_INITIAL_DELAY_MS: u64 = 5_000;
+const SYNC_RETRY_MAX_DELAY_MS: u64 = 10 * 60 * 1000;
+const MAX_SKIPPED_SCRAMBLES: usize = 100;
+
+/// Key of the shape mod label in the per session goal values
+const SHAPE_MOD_SESSION_KEY: &str = "shape_mod";
+/// Key of the session note in the per session goal values
+const NOTE_SESSION_KEY: &str = "note";
 
 pub struct History {
     storage: DeferredStorage,
@@ -28,13 +61,73 @@ pub struct History {
     synced_actions: ActionList,
     sync_key: String,
     sync_id: u32,
+    sync_endpoint: SyncEndpoint,
     local_actions: ActionList,
     current_sync: Option<Arc<Mutex<SyncOperation>>>,
     last_sync_result: SyncStatus,
+    sync_details: SyncDetails,
+    sync_retries: u32,
+    sync_retry_at: Option<Instant>,
     current_session: String,
+    puzzle_sessions: HashMap<String, String>,
+    known_devices: HashMap<String, KnownDevice>,
+    sync_excluded_sessions: HashSet<String>,
+    official_results: Option<OfficialResults>,
+    skipped_scrambles: Vec<SkippedScramble>,
     update_id: u64,
     next_update_id: u64,
     settings: Settings,
+    settings_update_id: u64,
+    session_stats: HashMap<String, SessionStats>,
+    session_stats_modified: bool,
+    solve_skips: Mutex<HashMap<String, Option<CFOPSkips>>>,
+    change_listeners: HashMap<ChangeListenerHandle, Box<dyn Fn(HistoryChange) + Send>>,
+    next_listener_id: u64,
+    solve_hooks: HashMap<SolveHookHandle, Box<dyn Fn(&SolveCompletion) + Send>>,
+    in_memory: bool,
+}
+
+/// Change to the solve history, sent to the listeners registered with
+/// `History::register_change_listener`. Changes made on this device are
+/// reported as they happen. Changes received from other devices during a sync
+/// are combined into a single `Synced` change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HistoryChange {
+    SolveAdded {
+        solve: String,
+        session: String,
+    },
+    /// Penalty, participant or tags of a solve changed
+    SolveChanged {
+        solve: String,
+        session: String,
+    },
+    SolveMoved {
+        solve: String,
+        from_session: String,
+        to_session: String,
+    },
+    SolveDeleted {
+        solve: String,
+        session: String,
+    },
+    /// Name, goals or participants of a session changed
+    SessionChanged(String),
+    /// Solves of the `removed` session were moved into the `into` session
+    SessionsMerged {
+        into: String,
+        removed: String,
+    },
+    CurrentSessionChanged(String),
+    PreferencesChanged,
+    /// Changes from other devices were applied during a sync. Contains the
+    /// sessions that may have changed. Preferences may have changed as well.
+    Synced(Vec<String>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChangeListenerHandle {
+    id: u64,
 }
 
 #[derive(Clone, Copy)]
@@ -49,9 +142,20 @@ pub enum HistoryLoadProgress {
 struct SolveDatabase {
     solve_map: SolveMap,
     sessions: HashMap<String, Session>,
+    participants: HashMap<String, Vec<Participant>>,
+    tags: HashMap<String, BTreeSet<String>>,
+    goals: HashMap<String, BTreeMap<String, String>>,
+    attempt_groups: HashMap<String, StoredAttemptGroup>,
+    preferences: Preferences,
     actions: HashSet<String>,
 }
 
+#[derive(Clone)]
+struct StoredAttemptGroup {
+    format: String,
+    solves: Vec<String>,
+}
+
 #[derive(Clone)]
 struct SolveMap {
     solves: BTreeMap<SolveTimeAndId, Solve>,
@@ -65,6 +169,29 @@ pub struct Session {
     solve_type: SolveType,
     solves: BTreeSet<SolveTimeAndId>,
     update_id: u64,
+    fingerprint: u64,
+}
+
+/// Filter for selecting solves from the history. Fields that are `None`
+/// match every solve.
+#[derive(Clone, Default)]
+pub struct SolveQuery {
+    pub solve_type: Option<SolveType>,
+    pub session: Option<String>,
+    pub tag: Option<String>,
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+    pub skipped: Option<SkippedStep>,
+    pub input: Option<SolveInput>,
+}
+
+/// Person taking turns on a shared device within a session, such as during a
+/// relay or a group practice session. Solves are attributed to a participant
+/// using the `participant` field of the solve.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Participant {
+    id: String,
+    name: String,
 }
 
 #[derive(Clone)]
@@ -84,6 +211,13 @@ pub struct SessionSolveIterator<'a> {
     solve: std::collections::btree_set::Iter<'a, SolveTimeAndId>,
 }
 
+/// Scramble that was shown but skipped without being solved
+#[derive(Clone, Serialize, Deserialize)]
+struct SkippedScramble {
+    solve_type: String,
+    scramble: String,
+}
+
 #[derive(Serialize, Deserialize)]
 struct Settings {
     settings: HashMap<String, Vec<u8>>,
@@ -115,22 +249,25 @@ impl HistoryLoadProgress {
 
 impl History {
     #[cfg(feature = "native-storage")]
-    pub async fn open() -> Result<Self> {
+    pub async fn open() -> crate::Result<Self> {
         let progress = Arc::new(Mutex::new(HistoryLoadProgress::default()));
         Self::open_with_progress(progress).await
     }
 
     #[cfg(feature = "native-storage")]
-    pub async fn open_with_progress(progress: Arc<Mutex<HistoryLoadProgress>>) -> Result<Self> {
-        let mut path =
-            data_local_dir().ok_or_else(|| anyhow!("Local data directory not defined"))?;
+    pub async fn open_with_progress(
+        progress: Arc<Mutex<HistoryLoadProgress>>,
+    ) -> crate::Result<Self> {
+        let mut path = data_local_dir()
+            .ok_or_else(|| anyhow!("Local data directory not defined"))
+            .storage_error()?;
         path.push("tpscube");
         path.push("solves");
         Self::open_at_with_progress(path, progress).await
     }
 
     #[cfg(feature = "native-storage")]
-    pub async fn open_at<P: AsRef<Path>>(path: P) -> Result<Self> {
+    pub async fn open_at<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
         let progress = Arc::new(Mutex::new(HistoryLoadProgress::default()));
         Self::open_at_with_progress(path, progress).await
     }
@@ -139,20 +276,39 @@ impl History {
     pub async fn open_at_with_progress<P: AsRef<Path>>(
         path: P,
         progress: Arc<Mutex<HistoryLoadProgress>>,
-    ) -> Result<Self> {
+    ) -> crate::Result<Self> {
         // Open up the local database and read actions from it
-        Self::open_with_storage(Storage::open(path.as_ref())?, progress).await
+        let storage = Storage::open(path.as_ref()).storage_error()?;
+        Self::open_with_storage(storage, progress)
+            .await
+            .storage_error()
     }
 
     #[cfg(feature = "web-storage")]
-    pub async fn open() -> Result<Self> {
+    pub async fn open() -> crate::Result<Self> {
         let progress = Arc::new(Mutex::new(HistoryLoadProgress::default()));
         Self::open_with_progress(progress).await
     }
 
     #[cfg(feature = "web-storage")]
-    pub async fn open_with_progress(progress: Arc<Mutex<HistoryLoadProgress>>) -> Result<Self> {
-        Self::open_with_storage(Storage::new().await?, progress).await
+    pub async fn open_with_progress(
+        progress: Arc<Mutex<HistoryLoadProgress>>,
+    ) -> crate::Result<Self> {
+        let storage = Storage::new().await.storage_error()?;
+        Self::open_with_storage(storage, progress)
+            .await
+            .storage_error()
+    }
+
+    /// Opens an empty history that is only kept in memory, for guest sessions
+    /// on demo machines or borrowed devices. Nothing is written to disk and
+    /// syncing is disabled, so everything is discarded when the history is
+    /// closed.
+    pub async fn open_in_memory() -> crate::Result<Self> {
+        let progress = Arc::new(Mutex::new(HistoryLoadProgress::default()));
+        Self::open_with_storage(Storage::in_memory(), progress)
+            .await
+            .storage_error()
     }
 
     async fn open_with_storage(
@@ -176,6 +332,18 @@ impl History {
             None => None,
         };
 
+        // Sync goes to the cloud unless a device on the local network was chosen
+        #[cfg(feature = "native-storage")]
+        let sync_endpoint = match storage.get("lan_sync_peer").await? {
+            Some(peer) => String::from_utf8_lossy(&peer)
+                .parse()
+                .map(SyncEndpoint::Lan)
+                .unwrap_or(SyncEndpoint::Cloud),
+            None => SyncEndpoint::Cloud,
+        };
+        #[cfg(not(feature = "native-storage"))]
+        let sync_endpoint = SyncEndpoint::Cloud;
+
         let current_session = match storage.get("session").await? {
             Some(session) => String::from_utf8_lossy(&session).into_owned(),
             None => {
@@ -192,7 +360,72 @@ impl History {
             },
         };
 
+        // Last session used for each puzzle, keyed by solve type name. These only
+        // allow resuming sessions when switching puzzles, so discard them if they
+        // can't be read.
+        let puzzle_sessions = match storage.get("puzzle_sessions").await? {
+            Some(sessions) => {
+                serde_json::from_str(&String::from_utf8_lossy(&sessions)).unwrap_or_default()
+            }
+            None => HashMap::new(),
+        };
+
+        // Bluetooth devices that have been connected before, keyed by device
+        // identifier. These only restore device settings, so discard them if they
+        // can't be read.
+        let known_devices = match storage.get("known_devices").await? {
+            Some(devices) => {
+                serde_json::from_str(&String::from_utf8_lossy(&devices)).unwrap_or_default()
+            }
+            None => HashMap::new(),
+        };
+
+        // Sessions that are kept on this device only. This is a local preference
+        // and is not itself synced.
+        let sync_excluded_sessions = match storage.get("sync_excluded_sessions").await? {
+            Some(sessions) => {
+                serde_json::from_str(&String::from_utf8_lossy(&sessions)).unwrap_or_default()
+            }
+            None => HashSet::new(),
+        };
+
+        // Official results imported from the WCA are kept on this device only
+        let official_results = match storage.get("official_results").await? {
+            Some(results) => serde_json::from_str(&String::from_utf8_lossy(&results)).ok(),
+            None => None,
+        };
+
+        // Scrambles that were skipped without a solve, so that they can be served
+        // again later. Discard them if they can't be read.
+        let skipped_scrambles = match storage.get("skipped_scrambles").await? {
+            Some(scrambles) => {
+                serde_json::from_str(&String::from_utf8_lossy(&scrambles)).unwrap_or_default()
+            }
+            None => Vec::new(),
+        };
+
+        // Cached session statistics are only an optimization, discard them if they
+        // can't be read
+        let session_stats = match storage.get("session_stats").await? {
+            Some(stats) => {
+                serde_json::from_str(&String::from_utf8_lossy(&stats)).unwrap_or_default()
+            }
+            None => HashMap::new(),
+        };
+
+        // Skipped steps are now stored with each solve, older versions kept them
+        // in a separate cache that is no longer used
+        let stale_solve_skips = storage.get("solve_skips").await?.is_some();
+
+        let in_memory = matches!(storage, Storage::Memory(_));
         let storage = DeferredStorage::new(storage);
+        if stale_solve_skips {
+            storage.delete("solve_skips");
+        }
+
+        // Rewrite action bundles from older format versions in the current format
+        synced_actions.upgrade(&storage);
+        local_actions.upgrade(&storage);
 
         if sync_key.is_none() || sync_id.is_none() {
             // No valid sync information in the database, create new sync information
@@ -217,13 +450,30 @@ impl History {
             synced_actions,
             sync_key: sync_key.unwrap(),
             sync_id: sync_id.unwrap(),
+            sync_endpoint,
             local_actions,
             current_sync: None,
             last_sync_result: SyncStatus::NotSynced,
+            sync_details: SyncDetails::default(),
+            sync_retries: 0,
+            sync_retry_at: None,
             current_session,
+            puzzle_sessions,
+            known_devices,
+            sync_excluded_sessions,
+            official_results,
+            skipped_scrambles,
             update_id: 0,
             next_update_id: 1,
             settings,
+            settings_update_id: 0,
+            session_stats,
+            session_stats_modified: false,
+            solve_skips: Mutex::new(HashMap::new()),
+            change_listeners: HashMap::new(),
+            next_listener_id: 0,
+            solve_hooks: HashMap::new(),
+            in_memory,
         };
 
         // Resolve actions to create solve and session lists
@@ -254,6 +504,12 @@ impl History {
             }
         }
 
+        // If this device has synced before, upload actions left over from the last
+        // run as soon as the app checks for pending syncs
+        if result.sync_id != UNSYNCED && result.local_actions.has_actions() {
+            result.sync_retry_at = Some(Instant::now());
+        }
+
         Ok(result)
     }
 
@@ -279,13 +535,55 @@ impl History {
         &self.sync_key
     }
 
-    pub fn set_sync_key(&mut self, key: &str) -> Result<()> {
-        // Set the key and make sure that any in progress syncs do not complete
-        // on the new key.
+    pub fn set_sync_key(&mut self, key: &str) -> crate::Result<()> {
         self.sync_key = key.into();
+        self.restart_sync();
+        self.storage.put("sync_key", self.sync_key.as_bytes());
+        Ok(())
+    }
+
+    /// Device on the local network that is used for sync instead of the
+    /// cloud, if any
+    #[cfg(feature = "native-storage")]
+    pub fn lan_sync_peer(&self) -> Option<SocketAddr> {
+        match self.sync_endpoint {
+            SyncEndpoint::Lan(address) => Some(address),
+            SyncEndpoint::Cloud => None,
+        }
+    }
+
+    /// Syncs with a device on the local network instead of the cloud, or
+    /// goes back to cloud sync if `peer` is `None`. The other device has its
+    /// own sync state, so all actions are synced again.
+    #[cfg(feature = "native-storage")]
+    pub fn set_lan_sync_peer(&mut self, peer: Option<SocketAddr>) {
+        if self.lan_sync_peer() == peer {
+            return;
+        }
+        self.sync_endpoint = match peer {
+            Some(address) => SyncEndpoint::Lan(address),
+            None => SyncEndpoint::Cloud,
+        };
+        self.restart_sync();
+        self.storage.put(
+            "lan_sync_peer",
+            peer.map(|address| address.to_string())
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+    }
+
+    /// Resets the sync state after the sync key or endpoint changes, so that
+    /// every action is uploaded again on the next sync
+    fn restart_sync(&mut self) {
+        // Make sure that any in progress syncs do not complete on the new
+        // sync state
         self.sync_id = UNSYNCED;
         self.current_sync = None;
         self.last_sync_result = SyncStatus::NotSynced;
+        self.sync_details = SyncDetails::default();
+        self.sync_retries = 0;
+        self.sync_retry_at = None;
 
         // Move any synced actions to local so that they will be uploaded under
         // the new key.
@@ -296,13 +594,124 @@ impl History {
             self.synced_solves = SolveDatabase::new();
         }
 
-        self.storage.put("sync_key", self.sync_key.as_bytes());
         self.storage.put("sync_id", &self.sync_id.to_le_bytes());
+    }
 
-        Ok(())
+    /// Registers a function to be called whenever the solve history changes.
+    /// Listeners are called on the thread making the change, which is the
+    /// thread using the `History`.
+    pub fn register_change_listener<F: Fn(HistoryChange) + Send + 'static>(
+        &mut self,
+        func: F,
+    ) -> ChangeListenerHandle {
+        let handle = ChangeListenerHandle {
+            id: self.next_listener_id,
+        };
+        self.next_listener_id += 1;
+        self.change_listeners.insert(handle, Box::new(func));
+        handle
+    }
+
+    pub fn unregister_change_listener(&mut self, handle: ChangeListenerHandle) {
+        self.change_listeners.remove(&handle);
+    }
+
+    /// Registers a function to be called after each new solve is added, with
+    /// the solve and the current state of its session. This is an extension
+    /// point for sending results to other programs, such as stream overlays
+    /// or spreadsheets. Solves received during a sync or from an import are
+    /// not reported.
+    pub fn register_solve_hook<F: Fn(&SolveCompletion) + Send + 'static>(
+        &mut self,
+        func: F,
+    ) -> SolveHookHandle {
+        let handle = SolveHookHandle {
+            id: self.next_listener_id,
+        };
+        self.next_listener_id += 1;
+        self.solve_hooks.insert(handle, Box::new(func));
+        handle
+    }
+
+    pub fn unregister_solve_hook(&mut self, handle: SolveHookHandle) {
+        self.solve_hooks.remove(&handle);
+    }
+
+    fn notify_solve_hooks(&self, solve_id: &str) {
+        if self.solve_hooks.len() == 0 {
+            return;
+        }
+        let solve = match self.solve(solve_id) {
+            Some(solve) => solve.clone(),
+            None => return,
+        };
+        let (session_name, session_solves) = match self.sessions().get(&solve.session) {
+            Some(session) => (session.name().clone(), session.to_vec(self)),
+            None => (None, vec![solve.clone()]),
+        };
+        let completion = SolveCompletion::new(
+            solve,
+            session_name,
+            &session_solves,
+            self.preferences().move_notation(),
+        );
+        for hook in self.solve_hooks.values() {
+            hook(&completion);
+        }
+    }
+
+    fn notify_change(&self, change: HistoryChange) {
+        for listener in self.change_listeners.values() {
+            listener(change.clone());
+        }
+    }
+
+    /// Determines the change that an action will make. This must be called
+    /// before the action is resolved, as some changes depend on the state
+    /// before the action.
+    fn action_change(&self, action: &Action) -> Option<HistoryChange> {
+        let solve_session =
+            |solve_id: &str| self.solve(solve_id).map(|solve| solve.session.clone());
+        match action {
+            Action::NewSolve(solve) => Some(HistoryChange::SolveAdded {
+                solve: solve.id.clone(),
+                session: solve.session.clone(),
+            }),
+            Action::Penalty(solve_id, _)
+            | Action::ChangeParticipant(solve_id, _)
+            | Action::TagSolve(solve_id, _)
+            | Action::UntagSolve(solve_id, _) => Some(HistoryChange::SolveChanged {
+                solve: solve_id.clone(),
+                session: solve_session(solve_id)?,
+            }),
+            Action::ChangeSession(solve_id, session_id) => Some(HistoryChange::SolveMoved {
+                solve: solve_id.clone(),
+                from_session: solve_session(solve_id)?,
+                to_session: session_id.clone(),
+            }),
+            Action::DeleteSolve(solve_id) => Some(HistoryChange::SolveDeleted {
+                solve: solve_id.clone(),
+                session: solve_session(solve_id)?,
+            }),
+            Action::MergeSessions(first, second) => Some(HistoryChange::SessionsMerged {
+                into: first.clone(),
+                removed: second.clone(),
+            }),
+            Action::RenameSession(session_id, _)
+            | Action::AddParticipant(session_id, _, _)
+            | Action::RemoveParticipant(session_id, _)
+            | Action::SetSessionGoal(session_id, _, _) => {
+                Some(HistoryChange::SessionChanged(session_id.clone()))
+            }
+            Action::AttemptGroup(_, _, solves) => Some(HistoryChange::SessionChanged(
+                solve_session(solves.first()?)?,
+            )),
+            Action::SetPreference(_, _) => Some(HistoryChange::PreferencesChanged),
+        }
     }
 
     fn new_action(&mut self, action: StoredAction) {
+        let change = self.action_change(&action.action);
         if self
             .solves
             .resolve_action(&action, &mut self.next_update_id)
@@ -310,19 +719,72 @@ impl History {
             self.local_actions.push(action);
             self.update_id = self.next_update_id;
             self.next_update_id += 1;
+            if let Some(change) = change {
+                self.notify_change(change);
+            }
         }
     }
 
     pub fn new_solve(&mut self, solve: Solve) {
+        let id = solve.id.clone();
+        self.add_solve(solve);
+        self.notify_solve_hooks(&id);
+    }
+
+    /// Adds a solve without reporting it to the solve hooks, for solves that
+    /// were not just completed on this device
+    fn add_solve(&mut self, mut solve: Solve) {
+        // Record skipped steps along with the solve so that they can be queried
+        // without analyzing it again
+        if solve.skips.is_none() {
+            solve.skips = analyze_skips(&solve);
+        }
+
+        // A skipped scramble that has now been solved is no longer pending
+        let solve_type = solve.solve_type.to_string();
+        let scramble = solve.scramble.to_string();
+        let skipped_count = self.skipped_scrambles.len();
+        self.skipped_scrambles
+            .retain(|skipped| skipped.solve_type != solve_type || skipped.scramble != scramble);
+        if self.skipped_scrambles.len() != skipped_count {
+            self.save_skipped_scrambles();
+        }
+
         self.new_action(StoredAction::new(Action::NewSolve(solve)));
     }
 
+    /// Records a list of manually entered times into the current session, such
+    /// as times from a parsed time list. Each time gets a newly generated
+    /// scramble. Solves are spaced one second apart ending at the current
+    /// time, so that they keep the order they were entered in.
+    #[cfg(not(feature = "no_solver"))]
+    pub fn new_manual_solves(&mut self, solve_type: SolveType, times: &[(u32, Penalty)]) {
+        let scrambles = generate_scrambles(solve_type, times.len(), &mut StandardRandomSource);
+        let now = Local::now();
+        for (idx, ((time, penalty), scramble)) in times.iter().zip(scrambles).enumerate() {
+            self.new_solve(Solve {
+                id: Solve::new_id(),
+                solve_type,
+                session: self.current_session.clone(),
+                scramble,
+                created: now - chrono::Duration::seconds((times.len() - 1 - idx) as i64),
+                time: *time,
+                penalty: penalty.clone(),
+                device: Some(SolveInput::Manual.to_device_string()),
+                participant: self.next_participant(),
+                moves: None,
+                skips: None,
+            });
+        }
+    }
+
     pub fn new_session(&mut self) -> String {
         let session = Uuid::new_v4().to_simple().to_string();
         self.current_session = session.clone();
         self.storage.put("session", session.as_bytes());
         self.update_id = self.next_update_id;
         self.next_update_id += 1;
+        self.notify_change(HistoryChange::CurrentSessionChanged(session.clone()));
         session
     }
 
@@ -331,9 +793,118 @@ impl History {
     }
 
     pub fn set_current_session(&mut self, session: String) {
-        self.current_session = session;
+        self.current_session = session.clone();
         self.update_id = self.next_update_id;
         self.next_update_id += 1;
+        self.notify_change(HistoryChange::CurrentSessionChanged(session));
+    }
+
+    /// Session that will be resumed when switching to the given puzzle with
+    /// `select_solve_type`, if there is one
+    pub fn solve_type_session(&self, solve_type: SolveType) -> Option<&Session> {
+        if let Some(session) = self.solves.sessions.get(&self.current_session) {
+            if session.solve_type == solve_type {
+                return Some(session);
+            }
+        }
+        self.puzzle_sessions
+            .get(&solve_type.to_string())
+            .and_then(|session| self.solves.sessions.get(session))
+            .filter(|session| session.solve_type == solve_type)
+    }
+
+    /// Switches to a different puzzle. The current session is remembered for the
+    /// puzzle it holds, and the last session used for the new puzzle becomes the
+    /// current session again. If there is no session to resume, a new session is
+    /// started unless the current session is still empty. Returns the ID of the
+    /// current session.
+    pub fn select_solve_type(&mut self, solve_type: SolveType) -> String {
+        // Remember the current session for its puzzle. Empty sessions can hold any
+        // puzzle, so they are not remembered.
+        if let Some(session) = self.solves.sessions.get(&self.current_session) {
+            if session.solve_type == solve_type {
+                return self.current_session.clone();
+            }
+            self.puzzle_sessions
+                .insert(session.solve_type.to_string(), self.current_session.clone());
+            self.save_puzzle_sessions();
+        }
+
+        // Resume the last session for the new puzzle if it still exists and was not
+        // merged into a session of another puzzle
+        if let Some(session) = self.solve_type_session(solve_type) {
+            let session = session.id.clone();
+            if session != self.current_session {
+                self.set_current_session(session.clone());
+                self.storage.put("session", session.as_bytes());
+            }
+            return session;
+        }
+
+        if self.solves.sessions.contains_key(&self.current_session) {
+            self.new_session()
+        } else {
+            self.current_session.clone()
+        }
+    }
+
+    fn save_puzzle_sessions(&self) {
+        if let Ok(sessions) = serde_json::to_string(&self.puzzle_sessions) {
+            self.storage.put("puzzle_sessions", sessions.as_bytes());
+        }
+    }
+
+    /// Bluetooth devices that have been connected on this device, most
+    /// recently seen first
+    pub fn known_devices(&self) -> Vec<&KnownDevice> {
+        let mut devices: Vec<&KnownDevice> = self.known_devices.values().collect();
+        devices.sort_unstable_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        devices
+    }
+
+    pub fn known_device(&self, id: &str) -> Option<&KnownDevice> {
+        self.known_devices.get(id)
+    }
+
+    /// Adds or replaces the stored information for a Bluetooth device
+    pub fn update_known_device(&mut self, device: KnownDevice) {
+        self.known_devices.insert(device.id.clone(), device);
+        self.save_known_devices();
+    }
+
+    pub fn forget_known_device(&mut self, id: &str) {
+        if self.known_devices.remove(id).is_some() {
+            self.save_known_devices();
+        }
+    }
+
+    fn save_known_devices(&self) {
+        if let Ok(devices) = serde_json::to_string(&self.known_devices) {
+            self.storage.put("known_devices", devices.as_bytes());
+        }
+    }
+
+    /// Returns true if changes to the session are kept on this device and
+    /// not uploaded during sync
+    pub fn is_session_sync_excluded(&self, session_id: &str) -> bool {
+        self.sync_excluded_sessions.contains(session_id)
+    }
+
+    /// Sets whether changes to a session are kept on this device. Excluded
+    /// changes stay in the local action list and are uploaded if the session
+    /// is included again.
+    pub fn set_session_sync_excluded(&mut self, session_id: &str, excluded: bool) {
+        let changed = if excluded {
+            self.sync_excluded_sessions.insert(session_id.into())
+        } else {
+            self.sync_excluded_sessions.remove(session_id)
+        };
+        if changed {
+            if let Ok(sessions) = serde_json::to_string(&self.sync_excluded_sessions) {
+                self.storage
+                    .put("sync_excluded_sessions", sessions.as_bytes());
+            }
+        }
     }
 
     pub fn penalty(&mut self, solve_id: String, penalty: Penalty) {
@@ -363,10 +934,469 @@ impl History {
         self.new_action(StoredAction::new(Action::RenameSession(session_id, None)));
     }
 
+    /// Sets a goal for a session, replacing any existing goal of the same kind
+    pub fn set_session_goal(&mut self, session_id: String, goal: SessionGoal) {
+        self.new_action(StoredAction::new(Action::SetSessionGoal(
+            session_id,
+            goal.kind().into(),
+            goal.value(),
+        )));
+    }
+
+    /// Removes the goal of the given kind from a session
+    pub fn clear_session_goal(&mut self, session_id: String, kind: &str) {
+        self.new_action(StoredAction::new(Action::SetSessionGoal(
+            session_id,
+            kind.into(),
+            "".into(),
+        )));
+    }
+
+    /// Labels a session with the shape mod used for its solves. The label is
+    /// stored alongside the session goals, which versions that don't know
+    /// about shape mods ignore.
+    pub fn set_session_shape_mod(&mut self, session_id: String, shape_mod: Option<ShapeMod>) {
+        self.new_action(StoredAction::new(Action::SetSessionGoal(
+            session_id,
+            SHAPE_MOD_SESSION_KEY.into(),
+            shape_mod
+                .map(|shape_mod| shape_mod.to_str().into())
+                .unwrap_or_default(),
+        )));
+    }
+
+    /// Sets a free form note for a session. An empty note removes it. Like
+    /// shape mods, notes are stored alongside the session goals.
+    pub fn set_session_note(&mut self, session_id: String, note: String) {
+        self.new_action(StoredAction::new(Action::SetSessionGoal(
+            session_id,
+            NOTE_SESSION_KEY.into(),
+            note,
+        )));
+    }
+
     pub fn delete_solve(&mut self, solve_id: String) {
         self.new_action(StoredAction::new(Action::DeleteSolve(solve_id)));
     }
 
+    /// Adds a participant to a session and returns the new participant's ID
+    pub fn add_participant(&mut self, session_id: String, name: String) -> String {
+        let participant_id = Uuid::new_v4().to_simple().to_string();
+        self.new_action(StoredAction::new(Action::AddParticipant(
+            session_id,
+            participant_id.clone(),
+            name,
+        )));
+        participant_id
+    }
+
+    pub fn remove_participant(&mut self, session_id: String, participant_id: String) {
+        self.new_action(StoredAction::new(Action::RemoveParticipant(
+            session_id,
+            participant_id,
+        )));
+    }
+
+    pub fn change_participant(&mut self, solve_id: String, participant_id: Option<String>) {
+        self.new_action(StoredAction::new(Action::ChangeParticipant(
+            solve_id,
+            participant_id,
+        )));
+    }
+
+    pub fn participants(&self, session_id: &str) -> &[Participant] {
+        match self.solves.participants.get(session_id) {
+            Some(participants) => participants,
+            None => &[],
+        }
+    }
+
+    /// Gets the participant whose turn is next in the current session. Turns
+    /// alternate in the order that participants were added, starting after the
+    /// participant of the most recent solve.
+    pub fn next_participant(&self) -> Option<String> {
+        let participants = self.participants(&self.current_session);
+        if participants.len() == 0 {
+            return None;
+        }
+
+        let last_participant = self
+            .sessions()
+            .get(&self.current_session)
+            .and_then(|session| {
+                session
+                    .iter(self)
+                    .rev()
+                    .find_map(|solve| solve.participant.clone())
+            });
+        let index = match last_participant {
+            Some(last) => match participants
+                .iter()
+                .position(|participant| participant.id == last)
+            {
+                Some(index) => (index + 1) % participants.len(),
+                None => 0,
+            },
+            None => 0,
+        };
+        Some(participants[index].id.clone())
+    }
+
+    /// Gets the solves in a session attributed to a single participant, for
+    /// computing per-person statistics.
+    pub fn participant_solves(&self, session_id: &str, participant_id: &str) -> Vec<Solve> {
+        match self.sessions().get(session_id) {
+            Some(session) => session
+                .iter(self)
+                .filter(|solve| solve.participant.as_deref() == Some(participant_id))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn tag_solve(&mut self, solve_id: String, tag: String) {
+        self.new_action(StoredAction::new(Action::TagSolve(solve_id, tag)));
+    }
+
+    pub fn untag_solve(&mut self, solve_id: String, tag: String) {
+        self.new_action(StoredAction::new(Action::UntagSolve(solve_id, tag)));
+    }
+
+    /// Gets the tags attached to a solve, in alphabetical order
+    pub fn solve_tags(&self, solve_id: &str) -> Vec<String> {
+        match self.solves.tags.get(solve_id) {
+            Some(tags) => tags.iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Gets every tag that is attached to at least one solve
+    pub fn all_tags(&self) -> BTreeSet<String> {
+        self.solves.tags.values().flatten().cloned().collect()
+    }
+
+    /// Counts the solves that match a query
+    pub fn query_count(&self, query: &SolveQuery) -> usize {
+        self.iter()
+            .filter(|solve| query.matches(solve, self))
+            .count()
+    }
+
+    /// Gets a page of solves that match a query, starting with the most
+    /// recent solve. `offset` is the number of matching solves to skip and
+    /// `limit` is the maximum number of solves to return.
+    pub fn query(&self, query: &SolveQuery, offset: usize, limit: usize) -> Vec<&Solve> {
+        self.iter()
+            .rev()
+            .filter(|solve| query.matches(solve, self))
+            .skip(offset)
+            .take(limit)
+            .collect()
+    }
+
+    /// Finds solves from every session that are similar to the given solve,
+    /// so that it can be compared with how the same situation was handled
+    /// before. The closest matches are returned first. See
+    /// `find_similar_solves` for details.
+    pub fn similar_solves(
+        &self,
+        solve_id: &str,
+        criteria: SimilarityCriteria,
+        limit: usize,
+    ) -> Vec<SimilarSolve> {
+        match self.solve(solve_id) {
+            Some(solve) => find_similar_solves(solve, self.iter(), criteria, limit),
+            None => Vec::new(),
+        }
+    }
+
+    /// Inputs that solves of the given type were recorded with, most recently
+    /// used first. Devices are listed under their most recent name.
+    pub fn solve_inputs(&self, solve_type: SolveType) -> Vec<SolveInput> {
+        let mut inputs: Vec<SolveInput> = Vec::new();
+        for solve in self.solves.solve_map.solves.values().rev() {
+            if solve.solve_type != solve_type {
+                continue;
+            }
+            if let Some(input) = solve.input() {
+                if !inputs.iter().any(|existing| existing.same_source(&input)) {
+                    inputs.push(input);
+                }
+            }
+        }
+        inputs
+    }
+
+    /// Groups consecutive solves of a session into a formal attempt in the
+    /// given format, and returns the new group's ID. Returns `None` if the
+    /// solves do not form a valid attempt: the number of solves must match the
+    /// format, the solves must be consecutive solves of a single session, and
+    /// none of them can already be part of an attempt.
+    pub fn group_attempt(&mut self, format: AttemptFormat, solve_ids: &[String]) -> Option<String> {
+        if solve_ids.len() != format.solve_count() {
+            return None;
+        }
+        if solve_ids
+            .iter()
+            .any(|solve_id| self.solve_attempt_group(solve_id).is_some())
+        {
+            return None;
+        }
+
+        // Find the solves within their session and ensure there are no gaps
+        let session = self
+            .sessions()
+            .get(&self.solve(solve_ids.first()?)?.session)?;
+        let mut positions = Vec::new();
+        for (position, solve) in session.iter(self).enumerate() {
+            if solve_ids.contains(&solve.id) {
+                positions.push((position, solve.id.clone()));
+            }
+        }
+        if positions.len() != solve_ids.len()
+            || positions.last()?.0 - positions.first()?.0 + 1 != positions.len()
+        {
+            return None;
+        }
+
+        let group_id = Uuid::new_v4().to_simple().to_string();
+        self.new_action(StoredAction::new(Action::AttemptGroup(
+            group_id.clone(),
+            format.name().into(),
+            positions
+                .into_iter()
+                .map(|(_, solve_id)| solve_id)
+                .collect(),
+        )));
+        Some(group_id)
+    }
+
+    /// Removes an attempt group. The solves of the group are not changed.
+    pub fn ungroup_attempt(&mut self, group_id: String) {
+        let solves = match self.solves.attempt_groups.get(&group_id) {
+            Some(group) => group.solves.clone(),
+            None => return,
+        };
+        self.new_action(StoredAction::new(Action::AttemptGroup(
+            group_id,
+            "".into(),
+            solves,
+        )));
+    }
+
+    /// Gets the formal attempts of a session, oldest first. Attempts in
+    /// formats that are not understood by this version are not included.
+    pub fn attempt_groups(&self, session_id: &str) -> Vec<AttemptGroup> {
+        let mut groups: Vec<AttemptGroup> = self
+            .solves
+            .attempt_groups
+            .keys()
+            .filter_map(|group_id| self.attempt_group(group_id))
+            .filter(|group| group.solves[0].session == session_id)
+            .collect();
+        groups.sort_by(|a, b| a.solves[0].created.cmp(&b.solves[0].created));
+        groups
+    }
+
+    /// Gets the formal attempt that a solve is part of, if any
+    pub fn solve_attempt_group(&self, solve_id: &str) -> Option<AttemptGroup> {
+        let group_id = self
+            .solves
+            .attempt_groups
+            .iter()
+            .find(|(_, group)| group.solves.iter().any(|id| id == solve_id))?
+            .0;
+        self.attempt_group(group_id)
+    }
+
+    fn attempt_group(&self, group_id: &str) -> Option<AttemptGroup> {
+        let group = self.solves.attempt_groups.get(group_id)?;
+        let mut solves = group
+            .solves
+            .iter()
+            .map(|solve_id| self.solve(solve_id).cloned())
+            .collect::<Option<Vec<Solve>>>()?;
+        let format = AttemptFormat::from_name(&group.format)?;
+        if solves.len() != format.solve_count() {
+            return None;
+        }
+        solves.sort_by(|a, b| a.created.cmp(&b.created));
+        Some(AttemptGroup {
+            id: group_id.into(),
+            format,
+            solves,
+        })
+    }
+
+    /// Goals set for a session. Goals that are not understood by this version
+    /// are not included.
+    pub fn session_goals(&self, session_id: &str) -> Vec<SessionGoal> {
+        match self.solves.goals.get(session_id) {
+            Some(goals) => goals
+                .iter()
+                .filter_map(|(kind, value)| SessionGoal::parse(kind, value))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Shape mod that a session is labeled with, if any
+    pub fn session_shape_mod(&self, session_id: &str) -> Option<ShapeMod> {
+        self.solves
+            .goals
+            .get(session_id)?
+            .get(SHAPE_MOD_SESSION_KEY)
+            .and_then(|value| ShapeMod::from_str(value))
+    }
+
+    pub fn session_note(&self, session_id: &str) -> Option<String> {
+        self.solves
+            .goals
+            .get(session_id)?
+            .get(NOTE_SESSION_KEY)
+            .cloned()
+    }
+
+    /// Gets the preferences that are synchronized across devices
+    pub fn preferences(&self) -> &Preferences {
+        &self.solves.preferences
+    }
+
+    pub fn set_preference(&mut self, preference: Preference) {
+        self.new_action(StoredAction::new(Action::SetPreference(
+            preference.name().into(),
+            preference.value(),
+        )));
+    }
+
+    /// Statistics for a session. Statistics are cached and only recomputed
+    /// when the solves of the session change. Call `save_session_stats` to
+    /// keep newly computed statistics in storage.
+    pub fn session_stats(&mut self, session_id: &str) -> Option<SessionStats> {
+        let session = self.solves.sessions.get(session_id)?;
+        if let Some(stats) = self.session_stats.get(session_id) {
+            if stats.valid_for(session.fingerprint, session.len()) {
+                return Some(stats.clone());
+            }
+        }
+
+        let stats = SessionStats::compute(session.fingerprint, &session.to_vec(self));
+        self.session_stats
+            .insert(session_id.to_string(), stats.clone());
+        self.session_stats_modified = true;
+        Some(stats)
+    }
+
+    /// Writes session statistics to storage if any were recomputed
+    pub fn save_session_stats(&mut self) -> crate::Result<()> {
+        if !self.session_stats_modified {
+            return Ok(());
+        }
+
+        // Forget statistics of sessions that have been merged or emptied
+        let sessions = &self.solves.sessions;
+        self.session_stats.retain(|id, _| sessions.contains_key(id));
+
+        self.storage.put(
+            "session_stats",
+            serde_json::to_string(&self.session_stats)
+                .storage_error()?
+                .as_bytes(),
+        );
+        self.session_stats_modified = false;
+        Ok(())
+    }
+
+    /// Steps that were skipped in a solve. Returns `None` if the solve does not
+    /// have move data or was not solved with CFOP.
+    pub fn solve_skips(&self, solve_id: &str) -> Option<CFOPSkips> {
+        self.cached_solve_skips(self.solve(solve_id)?)
+    }
+
+    /// Skipped steps of a solve. Solves recorded before skips were stored with
+    /// the solve are analyzed once and the result is kept in memory.
+    fn cached_solve_skips(&self, solve: &Solve) -> Option<CFOPSkips> {
+        if solve.skips.is_some() {
+            return solve.skips;
+        }
+        let mut cache = self.solve_skips.lock().unwrap();
+        if let Some(skips) = cache.get(&solve.id) {
+            return *skips;
+        }
+        let skips = analyze_skips(solve);
+        cache.insert(solve.id.clone(), skips);
+        skips
+    }
+
+    /// Records a scramble that was shown but skipped without a solve, so that
+    /// it can be served again with `take_skipped_scramble`
+    pub fn record_skipped_scramble(&mut self, solve_type: SolveType, scramble: &[Move]) {
+        if scramble.len() == 0 {
+            return;
+        }
+        self.skipped_scrambles.push(SkippedScramble {
+            solve_type: solve_type.to_string(),
+            scramble: scramble.to_string(),
+        });
+        if self.skipped_scrambles.len() > MAX_SKIPPED_SCRAMBLES {
+            self.skipped_scrambles.remove(0);
+        }
+        self.save_skipped_scrambles();
+    }
+
+    pub fn skipped_scramble_count(&self, solve_type: SolveType) -> usize {
+        let solve_type = solve_type.to_string();
+        self.skipped_scrambles
+            .iter()
+            .filter(|skipped| skipped.solve_type == solve_type)
+            .count()
+    }
+
+    /// Removes and returns the oldest skipped scramble for a puzzle
+    pub fn take_skipped_scramble(&mut self, solve_type: SolveType) -> Option<Vec<Move>> {
+        let solve_type = solve_type.to_string();
+        let index = self
+            .skipped_scrambles
+            .iter()
+            .position(|skipped| skipped.solve_type == solve_type)?;
+        let skipped = self.skipped_scrambles.remove(index);
+        self.save_skipped_scrambles();
+        parse_move_string(&skipped.scramble).ok()
+    }
+
+    fn save_skipped_scrambles(&self) {
+        if let Ok(scrambles) = serde_json::to_string(&self.skipped_scrambles) {
+            self.storage.put("skipped_scrambles", scrambles.as_bytes());
+        }
+    }
+
+    /// Finds solves in a session that used the same scramble. Returns groups of
+    /// solve IDs in solve order, one group for each scramble used more than once.
+    pub fn reused_scrambles(&self, session_id: &str) -> Vec<Vec<String>> {
+        let session = match self.solves.sessions.get(session_id) {
+            Some(session) => session,
+            None => return Vec::new(),
+        };
+
+        let mut groups: Vec<Vec<String>> = Vec::new();
+        let mut scramble_groups: HashMap<String, usize> = HashMap::new();
+        for solve in session.iter(self) {
+            if solve.scramble.len() == 0 {
+                continue;
+            }
+            match scramble_groups.get(&solve.scramble.to_string()) {
+                Some(group) => groups[*group].push(solve.id.clone()),
+                None => {
+                    scramble_groups.insert(solve.scramble.to_string(), groups.len());
+                    groups.push(vec![solve.id.clone()]);
+                }
+            }
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
     pub fn local_commit(&mut self) {
         self.local_actions.commit(&self.storage, false);
     }
@@ -375,31 +1405,75 @@ impl History {
         self.local_actions.len()
     }
 
-    fn sync_request(&self) -> SyncRequest {
-        // Gather local actions for syncing
-        let actions: Vec<StoredAction> = self
-            .local_actions
-            .iter()
-            .map(|action| action.clone())
-            .collect();
+    /// Size of the stored history, by kind of action
+    pub fn storage_usage(&self) -> StorageUsage {
+        let mut usage = StorageUsage::default();
+        usage.add_list(&self.synced_actions);
+        usage.add_list(&self.local_actions);
+        usage
+    }
 
-        // Create the sync request with the current sync key and sync ID, along
-        // with the local actions that need to be uploaded.
-        SyncRequest {
-            sync_key: self.sync_key.clone(),
-            sync_id: self.sync_id,
-            upload: if actions.len() == 0 {
-                None
-            } else {
-                Some(actions)
-            },
+    /// Rewrites the stored action lists without the actions that no longer
+    /// affect the history, such as actions of deleted solves and preferences
+    /// that were changed again, then compacts the database. The resulting
+    /// history is unchanged. Returns the number of actions removed.
+    pub fn compact_storage(&mut self) -> crate::Result<usize> {
+        // An upload in progress refers to positions in the local action list
+        if self.current_sync.is_some() {
+            return Err(anyhow!("Cannot compact while a sync is in progress")).storage_error();
+        }
+
+        let synced = superseded_actions(&self.synced_actions);
+        let local = superseded_actions(&self.local_actions);
+        let removed = self.synced_actions.remove_actions(&synced, &self.storage)
+            + self.local_actions.remove_actions(&local, &self.storage);
+        if removed != 0 {
+            diagnostic!(info, count = removed, "Removed superseded actions");
+            self.storage.compact();
         }
+        Ok(removed)
+    }
+
+    pub(crate) fn sync_request(&self) -> SyncRequest {
+        // Create the sync request with the current sync key and sync ID, along
+        // with the local actions that need to be uploaded. Actions for sessions
+        // that are excluded from sync stay local.
+        SyncRequest::upload_excluding(
+            self.sync_key.clone(),
+            self.sync_id,
+            &self.local_actions,
+            &self.sync_excluded_sessions,
+            |solve_id| self.solve(solve_id).map(|solve| solve.session.clone()),
+        )
+    }
+
+    /// Returns true if the history is only kept in memory and is never
+    /// stored or synced
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory
     }
 
     pub fn start_sync(&mut self) -> bool {
+        // Guest sessions must not send their solves anywhere
+        if self.in_memory {
+            return false;
+        }
+
         // Do not start another sync if one is already running
         if self.current_sync.is_none() {
-            self.current_sync = Some(SyncOperation::new(self.sync_request()));
+            diagnostic!(
+                debug,
+                sync_id = self.sync_id,
+                upload = self.local_actions.len(),
+                "Starting sync"
+            );
+            self.sync_details = SyncDetails {
+                last_error: self.sync_details.last_error.take(),
+                last_complete: self.sync_details.last_complete,
+                ..SyncDetails::default()
+            };
+            self.sync_retry_at = None;
+            self.current_sync = Some(SyncOperation::new(self.sync_request(), self.sync_endpoint));
             true
         } else {
             false
@@ -410,31 +1484,85 @@ impl History {
         self.current_sync.is_some()
     }
 
+    /// Transfer statistics and errors for the current or most recent sync
+    pub fn sync_details(&self) -> SyncDetails {
+        let now = Instant::now();
+        SyncDetails {
+            pending_actions: self.local_actions.len(),
+            retries: self.sync_retries,
+            next_retry_ms: self.sync_retry_at.map(|at| {
+                if at > now {
+                    (at - now).as_millis() as u64
+                } else {
+                    0
+                }
+            }),
+            ..self.sync_details.clone()
+        }
+    }
+
+    /// Starts a sync if one is scheduled and due. Failed syncs are retried
+    /// with exponential backoff until a sync succeeds, so local actions are
+    /// uploaded once the connection is back. Call this periodically.
+    pub fn retry_sync_if_due(&mut self) -> bool {
+        match self.sync_retry_at {
+            Some(at) if at <= Instant::now() => {
+                diagnostic!(debug, retries = self.sync_retries, "Retrying sync");
+                self.start_sync()
+            }
+            _ => false,
+        }
+    }
+
     pub fn check_sync_status(&mut self) -> SyncStatus {
         match self.current_sync.clone() {
             Some(sync) => {
                 // There is a sync active, check for completion
                 let sync = sync.lock().unwrap();
                 if sync.done() {
-                    // Sync request is done, check response
+                    // Sync request is done, record transfer statistics and check response
+                    self.sync_details.requests += 1;
+                    self.sync_details.uploaded_bytes += sync.request_bytes();
+                    self.sync_details.downloaded_bytes += sync.response_bytes();
+                    self.sync_details.round_trip_ms = sync.round_trip_ms();
                     match &sync.response().as_ref().unwrap() {
                         Ok(response) => {
                             // Response is OK, process it now
+                            self.sync_details.uploaded_actions += response.uploaded;
+                            self.sync_details.downloaded_actions += response.new_actions.len();
                             self.current_sync = None;
-                            self.resolve_sync(response);
+                            self.resolve_sync(sync.request(), response);
 
                             // Response processing may have triggered another sync stage. Check
-                            // for another pending sync, or if there isn't one, return the status
-                            // of the completed sync.
-                            if self.current_sync.is_some() {
+                            // for another pending sync, or if there isn't one, the sync is
+                            // complete.
+                            if self.current_sync.is_some() || self.sync_retry_at.is_some() {
                                 SyncStatus::SyncPending
                             } else {
+                                self.sync_details.last_error = None;
+                                self.sync_details.last_complete = Some(Local::now());
+                                self.sync_retries = 0;
+                                self.last_sync_result = SyncStatus::SyncComplete;
                                 self.last_sync_result.clone()
                             }
                         }
                         Err(error) => {
                             // Sync failed, save failure message and return it
+                            diagnostic!(warn, "Sync failed: {}", error);
                             self.current_sync = None;
+                            self.sync_details.last_error = Some(format!("{:#}", error));
+
+                            // Schedule a retry, doubling the delay after each failure. If
+                            // the server asked for a specific delay, use that instead.
+                            let delay = match error.downcast_ref::<RetryAfter>() {
+                                Some(retry_after) => retry_after.delay_ms,
+                                None => SYNC_RETRY_INITIAL_DELAY_MS
+                                    .saturating_mul(1 << self.sync_retries.min(16))
+                                    .min(SYNC_RETRY_MAX_DELAY_MS),
+                            };
+                            self.sync_retries += 1;
+                            self.sync_retry_at =
+                                Some(Instant::now() + std::time::Duration::from_millis(delay));
                             self.last_sync_result = SyncStatus::SyncFailed(error.to_string());
                             self.last_sync_result.clone()
                         }
@@ -451,8 +1579,26 @@ impl History {
         }
     }
 
-    fn resolve_sync(&mut self, response: &SyncResponse) {
+    pub(crate) fn resolve_sync(&mut self, request: &SyncRequest, response: &SyncResponse) {
+        let _span = diagnostic_span!("resolve_sync", sync_id = response.new_sync_id);
+        diagnostic!(
+            info,
+            new_actions = response.new_actions.len(),
+            uploaded = response.uploaded,
+            more_actions = response.more_actions,
+            "Sync response received"
+        );
+
         if response.new_actions.len() != 0 || response.uploaded != 0 {
+            // Keep track of the state of the sessions before the sync so that listeners
+            // can be told which sessions changed
+            let session_update_ids: HashMap<String, u64> = self
+                .solves
+                .sessions
+                .iter()
+                .map(|(id, session)| (id.clone(), session.update_id))
+                .collect();
+
             // There are new actions, commit them to the synced state
             for action in &response.new_actions {
                 self.synced_solves
@@ -464,13 +1610,28 @@ impl History {
             if response.uploaded != 0 {
                 // Transfer completed local actions to synced state. Actions are only
                 // appended so even if new local actions have been added since the
-                // start of the sync, the uploaded ones will be in the same place.
+                // start of the sync, the uploaded ones will be in the same order.
+                // Actions excluded from the upload are kept in the local list.
+                let uploaded: HashSet<&str> = request
+                    .upload
+                    .iter()
+                    .flatten()
+                    .take(response.uploaded)
+                    .map(|action| action.id.as_str())
+                    .collect();
+                let mut remaining = uploaded.len();
+                let mut kept = Vec::new();
                 let mut local_iter = self.local_actions.iter();
-                for _ in 0..response.uploaded {
+                while remaining != 0 {
                     if let Some(action) = local_iter.next() {
-                        self.synced_solves
-                            .resolve_action(action, &mut self.next_update_id);
-                        self.synced_actions.push(action.clone());
+                        if uploaded.contains(action.id.as_str()) {
+                            self.synced_solves
+                                .resolve_action(action, &mut self.next_update_id);
+                            self.synced_actions.push(action.clone());
+                            remaining -= 1;
+                        } else {
+                            kept.push(action.clone());
+                        }
                     } else {
                         break;
                     }
@@ -478,8 +1639,20 @@ impl History {
                 self.synced_actions.commit(&self.storage, false);
 
                 // Remove completed local actions
-                let pos = local_iter.position();
-                self.local_actions.remove_before(pos, &self.storage);
+                if kept.len() == 0 {
+                    let pos = local_iter.position();
+                    self.local_actions.remove_before(pos, &self.storage);
+                } else {
+                    // Excluded actions were interleaved with the uploaded ones, so
+                    // rebuild the local action list without the uploaded actions
+                    let mut new_list = ActionList::empty("local");
+                    for action in kept.into_iter().chain(local_iter.cloned()) {
+                        new_list.push(action);
+                    }
+                    new_list.commit(&self.storage, true);
+                    self.local_actions.delete_bundles(&self.storage);
+                    self.local_actions = new_list;
+                }
             }
 
             // Resolve local actions on top of the synced state. If there are actions that
@@ -497,6 +1670,8 @@ impl History {
             }
 
             if has_rejected_actions {
+                diagnostic!(debug, "Removing local actions that no longer apply");
+
                 // If there were modifications to the local action list, reserialize the list
                 // and replace the existing list with the new one.
                 let mut new_list = ActionList::empty("local");
@@ -510,6 +1685,27 @@ impl History {
 
             self.update_id = self.next_update_id;
             self.next_update_id += 1;
+
+            // Resolving the local actions again touches the sessions they apply to, so
+            // sessions are only reported when there are changes from other devices or
+            // local actions were dropped.
+            if response.new_actions.len() != 0 || has_rejected_actions {
+                let mut sessions: Vec<String> = self
+                    .solves
+                    .sessions
+                    .iter()
+                    .filter(|(id, session)| session_update_ids.get(*id) != Some(&session.update_id))
+                    .map(|(id, _)| id.clone())
+                    .collect();
+                sessions.extend(
+                    session_update_ids
+                        .keys()
+                        .filter(|id| !self.solves.sessions.contains_key(*id))
+                        .cloned(),
+                );
+                sessions.sort();
+                self.notify_change(HistoryChange::Synced(sessions));
+            }
         }
 
         // Update sync ID and commit to local database if changed
@@ -522,12 +1718,25 @@ impl History {
             if (response.new_actions.len() != 0 || response.uploaded != 0)
                 && (self.local_actions.has_actions() || response.more_actions)
             {
-                self.current_sync = Some(SyncOperation::new(self.sync_request()));
+                if request.is_bulk_upload() {
+                    // Space out the requests of large uploads, such as after an import
+                    diagnostic!(debug, "Continuing bulk upload after delay");
+                    self.sync_retry_at = Some(
+                        Instant::now() + std::time::Duration::from_millis(BULK_UPLOAD_INTERVAL_MS),
+                    );
+                    self.last_sync_result = SyncStatus::SyncPending;
+                } else {
+                    diagnostic!(debug, "Continuing sync from new sync ID");
+                    self.current_sync =
+                        Some(SyncOperation::new(self.sync_request(), self.sync_endpoint));
+                }
             }
         }
     }
 
-    pub fn export(&self) -> Result<String> {
+    pub fn export(&self) -> crate::Result<String> {
+        let notation = self.preferences().move_notation();
+
         // Sort sessions by solve time
         let mut sessions: Vec<&Session> = self.solves.sessions.values().collect();
         sessions.sort_unstable(); // Sessions are always unique
@@ -544,7 +1753,7 @@ impl History {
                         Penalty::Time(time) => time,
                         Penalty::DNF => 0,
                     },
-                    "scramble": solve.scramble.to_string(),
+                    "scramble": solve.scramble.to_string_with(notation),
                     "time": solve.time,
                     "timestamp": solve.created.timestamp(),
                 });
@@ -554,16 +1763,29 @@ impl History {
                         .unwrap()
                         .insert("device".into(), json!(device));
                 }
+                if let Some(participant) = &solve.participant {
+                    value
+                        .as_object_mut()
+                        .unwrap()
+                        .insert("participant".into(), json!(participant));
+                }
                 if let Some(moves) = &solve.moves {
                     value
                         .as_object_mut()
                         .unwrap()
                         .insert("solve".into(), json!(moves.to_string()));
                 }
+                let tags = self.solve_tags(&solve.id);
+                if tags.len() != 0 {
+                    value
+                        .as_object_mut()
+                        .unwrap()
+                        .insert("tags".into(), json!(tags));
+                }
                 solve_list.push(value);
             }
             if solve_list.len() != 0 {
-                session_list.push(json!({
+                let mut value = json!({
                     "id": session.id,
                     "name": match &session.name {
                         Some(name) => &name,
@@ -571,50 +1793,96 @@ impl History {
                     },
                     "solves": solve_list,
                     "type": session.solve_type.to_string(),
-                }));
+                });
+                let participants = self.participants(&session.id);
+                if participants.len() != 0 {
+                    let participant_list: Vec<_> = participants
+                        .iter()
+                        .map(|participant| {
+                            json!({
+                                "id": participant.id,
+                                "name": participant.name,
+                            })
+                        })
+                        .collect();
+                    value
+                        .as_object_mut()
+                        .unwrap()
+                        .insert("participants".into(), json!(participant_list));
+                }
+                session_list.push(value);
             }
         }
 
-        Ok(serde_json::to_string_pretty(&json!({
+        serde_json::to_string_pretty(&json!({
             "sessions": session_list
-        }))?)
-    }
-
-    pub fn import(&mut self, contents: String) -> Result<String> {
-        // Import sessions and solves from the file contents
-        let sessions = ImportedSession::import(contents)?;
-
-        // Keep track of merge statistics
-        let file_sessions = sessions.len();
-        let mut file_solves = 0;
-        let mut changed_session_count = 0;
-        let mut new_session_count = 0;
-        let mut changed_solve_count = 0;
-        let mut new_solve_count = 0;
+        }))
+        .parse_error()
+    }
+
+    pub fn import(&mut self, contents: String) -> crate::Result<String> {
+        let mut import = HistoryImport::new(contents, false)?;
+        while !self.import_chunk(&mut import, usize::MAX) {}
+        Ok(import.summary())
+    }
+
+    /// Merges up to `max_solves` solves of an import into the history.
+    /// Returns true when the import is complete or was cancelled. Call this
+    /// repeatedly, such as once per frame, to import large backups without
+    /// blocking the application.
+    pub fn import_chunk(&mut self, import: &mut HistoryImport, max_solves: usize) -> bool {
+        let dry_run = import.dry_run;
+        let mut remaining = max_solves.max(1);
+        while !import.cancelled && import.session_index < import.sessions.len() && remaining > 0 {
+            let session = &import.sessions[import.session_index];
+
+            if import.solve_index == 0 {
+                import.session_existing = false;
+                import.session_changed = false;
+
+                // Check for existing session
+                if let Some(existing_session) = self.solves.sessions.get(&session.id) {
+                    import.session_existing = true;
+
+                    // If name has changed, perform rename
+                    if existing_session.name != session.name {
+                        import.session_changed = true;
+                        if !dry_run {
+                            if let Some(name) = &session.name {
+                                self.rename_session(session.id.clone(), name.clone());
+                            } else {
+                                self.default_session_name(session.id.clone());
+                            }
+                        }
+                    }
+                }
 
-        for session in sessions {
-            let mut existing = false;
-            let mut changed = false;
-
-            // Check for existing session
-            if let Some(existing_session) = self.solves.sessions.get_mut(&session.id) {
-                existing = true;
-
-                // If name has changed, perform rename
-                if existing_session.name != session.name {
-                    if let Some(name) = &session.name {
-                        self.rename_session(session.id.clone(), name.clone());
-                        changed = true;
-                    } else {
-                        self.default_session_name(session.id.clone());
-                        changed = true;
+                // Add any participants that are not already in the session
+                for (participant_id, name) in &session.participants {
+                    if !self
+                        .participants(&session.id)
+                        .iter()
+                        .any(|participant| &participant.id == participant_id)
+                    {
+                        import.session_changed = true;
+                        if !dry_run {
+                            self.new_action(StoredAction::new(Action::AddParticipant(
+                                session.id.clone(),
+                                participant_id.clone(),
+                                name.clone(),
+                            )));
+                        }
                     }
                 }
             }
 
             // Merge solves in session
-            file_solves += session.solves.len();
-            for solve in &session.solves {
+            while import.solve_index < session.solves.len() && remaining > 0 {
+                let solve = &session.solves[import.solve_index];
+                import.solve_index += 1;
+                import.merged_solves += 1;
+                remaining -= 1;
+
                 // Check for existing solve
                 if let Some(existing_solve) = self.solves.solve_map.solves.get(&SolveTimeAndId {
                     time: solve.created,
@@ -622,51 +1890,147 @@ impl History {
                 }) {
                     // Check for modified penalty
                     if existing_solve.penalty != solve.penalty {
-                        self.penalty(solve.id.clone(), solve.penalty.clone());
-                        changed_solve_count += 1;
-                        changed = true;
+                        if !dry_run {
+                            self.penalty(solve.id.clone(), solve.penalty.clone());
+                        }
+                        import.stats.changed_solves += 1;
+                        import.session_changed = true;
                     }
                 } else {
                     // New solve
-                    self.new_solve(solve.clone());
-                    new_solve_count += 1;
-                    changed = true;
+                    if !dry_run {
+                        self.add_solve(solve.clone());
+                    }
+                    import.stats.new_solves += 1;
+                    import.session_changed = true;
+                }
+            }
+            if import.solve_index < session.solves.len() {
+                // Continue this session in the next chunk
+                break;
+            }
+
+            // Add any tags that are not already on the solves
+            for (solve_id, tags) in &session.tags {
+                let existing_tags = self.solve_tags(solve_id);
+                for tag in tags {
+                    if !existing_tags.contains(tag) {
+                        import.session_changed = true;
+                        if !dry_run {
+                            self.tag_solve(solve_id.clone(), tag.clone());
+                        }
+                    }
                 }
             }
 
             // If there is a new session and it has a name, give it the name now
-            if !existing && changed {
+            if !import.session_existing && import.session_changed && !dry_run {
                 if let Some(name) = &session.name {
                     self.rename_session(session.id.clone(), name.clone());
                 }
             }
 
             // Update session merge statistics
-            if existing {
-                if changed {
-                    changed_session_count += 1;
+            if import.session_existing {
+                if import.session_changed {
+                    import.stats.changed_sessions += 1;
                 }
-            } else if changed {
-                new_session_count += 1;
+            } else if import.session_changed {
+                import.stats.new_sessions += 1;
             }
+
+            import.session_index += 1;
+            import.solve_index = 0;
+        }
+
+        if !dry_run {
+            self.local_commit();
+        }
+        import.is_complete()
+    }
+
+    /// Merges the history of another solve database into this one. Actions
+    /// that are already in this history, such as actions both databases
+    /// received from sync, are skipped, as are solves that already exist.
+    /// Preferences of the other database are not merged. Returns a summary of
+    /// the merge.
+    #[cfg(feature = "native-storage")]
+    pub fn merge_database(&mut self, source: MergeSource) -> String {
+        let existing: HashSet<String> = self
+            .synced_actions
+            .iter()
+            .chain(self.local_actions.iter())
+            .map(|action| action.id.clone())
+            .collect();
+
+        let mut new_solve_count = 0;
+        let mut change_count = 0;
+        let mut duplicate_count = 0;
+        for action in source.actions {
+            if existing.contains(&action.id) {
+                duplicate_count += 1;
+                continue;
+            }
+            match &action.action {
+                Action::NewSolve(solve) if self.solve(&solve.id).is_some() => {
+                    duplicate_count += 1;
+                    continue;
+                }
+                Action::NewSolve(_) => new_solve_count += 1,
+                Action::SetPreference(_, _) => continue,
+                _ => change_count += 1,
+            }
+            self.new_action(action);
         }
 
         self.local_commit();
 
-        // Import complete, return statistics about merge
-        Ok(format!(
-            "File contained {} solve(s) in {} session(s).\n\
-            {} session(s) added.\n\
-            {} session(s) modified.\n\
-            {} solve(s) added.\n\
-            {} solve(s) modified.",
-            file_solves,
-            file_sessions,
-            new_session_count,
-            changed_session_count,
-            new_solve_count,
-            changed_solve_count
-        ))
+        format!(
+            "{} solve(s) added.\n\
+            {} other change(s) merged.\n\
+            {} duplicate(s) skipped.",
+            new_solve_count, change_count, duplicate_count
+        )
+    }
+
+    /// Official results imported from the WCA, if any
+    pub fn official_results(&self) -> Option<&OfficialResults> {
+        self.official_results.as_ref()
+    }
+
+    /// Imports official results exported from the WCA, replacing any that were
+    /// imported before. These are kept on this device and are not synced.
+    pub fn import_official_results(&mut self, contents: &str) -> crate::Result<String> {
+        let results = OfficialResults::parse(contents).parse_error()?;
+        let message = format!(
+            "Imported official records for {} event(s){}.",
+            results.records.len(),
+            match (&results.name, &results.wca_id) {
+                (Some(name), Some(id)) => format!(" for {} ({})", name, id),
+                (None, Some(id)) => format!(" for {}", id),
+                _ => String::new(),
+            }
+        );
+        self.storage.put(
+            "official_results",
+            serde_json::to_string(&results).storage_error()?.as_bytes(),
+        );
+        self.official_results = Some(results);
+        self.touch();
+        Ok(message)
+    }
+
+    pub fn clear_official_results(&mut self) {
+        if self.official_results.take().is_some() {
+            self.storage.delete("official_results");
+            self.touch();
+        }
+    }
+
+    /// Marks the history as updated so that views showing it are refreshed
+    fn touch(&mut self) {
+        self.update_id = self.next_update_id;
+        self.next_update_id += 1;
     }
 
     pub fn auto_split_sessions(&mut self, max_gap_time: i64) -> usize {
@@ -746,24 +2110,33 @@ impl History {
         None
     }
 
-    pub fn set_setting(&mut self, name: &str, value: &[u8]) -> Result<()> {
+    /// Changes whenever a setting is changed, so that values derived from the
+    /// settings can be cached until the next change
+    pub fn settings_update_id(&self) -> u64 {
+        self.settings_update_id
+    }
+
+    pub fn set_setting(&mut self, name: &str, value: &[u8]) -> crate::Result<()> {
         self.settings.settings.insert(name.into(), value.to_vec());
+        self.settings_update_id += 1;
         self.storage.put(
             "settings",
-            serde_json::to_string(&self.settings)?.as_bytes(),
+            serde_json::to_string(&self.settings)
+                .storage_error()?
+                .as_bytes(),
         );
         Ok(())
     }
 
-    pub fn set_bool_setting(&mut self, name: &str, value: bool) -> Result<()> {
+    pub fn set_bool_setting(&mut self, name: &str, value: bool) -> crate::Result<()> {
         self.set_setting(name, &[if value { 1 } else { 0 }])
     }
 
-    pub fn set_string_setting(&mut self, name: &str, value: &str) -> Result<()> {
+    pub fn set_string_setting(&mut self, name: &str, value: &str) -> crate::Result<()> {
         self.set_setting(name, value.as_bytes())
     }
 
-    pub fn set_i64_setting(&mut self, name: &str, value: i64) -> Result<()> {
+    pub fn set_i64_setting(&mut self, name: &str, value: i64) -> crate::Result<()> {
         self.set_setting(name, &value.to_le_bytes())
     }
 
@@ -843,6 +2216,11 @@ impl SolveDatabase {
                 solve_times: HashMap::new(),
             },
             sessions: HashMap::new(),
+            participants: HashMap::new(),
+            tags: HashMap::new(),
+            goals: HashMap::new(),
+            attempt_groups: HashMap::new(),
+            preferences: Preferences::default(),
             actions: HashSet::new(),
         }
     }
@@ -856,6 +2234,7 @@ impl SolveDatabase {
         solve: SolveTimeAndId,
         solve_type: SolveType,
         session: &String,
+        fingerprint: u64,
         next_update_id: &mut u64,
     ) {
         let update_id = *next_update_id;
@@ -869,8 +2248,11 @@ impl SolveDatabase {
                 solve_type,
                 solves: BTreeSet::new(),
                 update_id,
+                fingerprint: 0,
             });
-        session.solves.insert(solve);
+        if session.solves.insert(solve) {
+            session.fingerprint ^= fingerprint;
+        }
         session.update_id = update_id;
     }
 
@@ -891,13 +2273,22 @@ impl SolveDatabase {
                     .solve_times
                     .insert(solve.id.clone(), solve.created);
                 self.solve_map.solves.insert(key.clone(), solve.clone());
-                self.add_solve_to_session(key, solve.solve_type, &solve.session, next_update_id);
+                self.add_solve_to_session(
+                    key,
+                    solve.solve_type,
+                    &solve.session,
+                    solve_fingerprint(&solve.id, &solve.penalty, solve.time),
+                    next_update_id,
+                );
                 true
             }
             Action::Penalty(solve, penalty) => match self.solve_map.solve_mut(solve) {
                 Some(solve) => {
+                    let old_fingerprint = solve_fingerprint(&solve.id, &solve.penalty, solve.time);
                     solve.penalty = penalty.clone();
                     if let Some(session) = self.sessions.get_mut(&solve.session) {
+                        session.fingerprint ^= old_fingerprint
+                            ^ solve_fingerprint(&solve.id, &solve.penalty, solve.time);
                         session.update_id = *next_update_id;
                         *next_update_id += 1;
                     }
@@ -912,9 +2303,12 @@ impl SolveDatabase {
                         time: solve.created.clone(),
                         id: solve.id.clone(),
                     };
+                    let fingerprint = solve_fingerprint(&solve.id, &solve.penalty, solve.time);
                     match self.sessions.get_mut(&solve.session) {
                         Some(session) => {
-                            session.solves.remove(&key);
+                            if session.solves.remove(&key) {
+                                session.fingerprint ^= fingerprint;
+                            }
                             session.update_id = *next_update_id;
                             *next_update_id += 1;
                         }
@@ -922,7 +2316,13 @@ impl SolveDatabase {
                     };
                     solve.session = session_id.clone();
                     let solve_type = solve.solve_type;
-                    self.add_solve_to_session(key, solve_type, session_id, next_update_id);
+                    self.add_solve_to_session(
+                        key,
+                        solve_type,
+                        session_id,
+                        fingerprint,
+                        next_update_id,
+                    );
                     true
                 }
                 None => false,
@@ -937,15 +2337,30 @@ impl SolveDatabase {
                         for solve in second_solves {
                             if let Some(solve) = self.solve_map.solves.get_mut(&solve) {
                                 solve.session = first.id.clone();
-                                first.solves.insert(SolveTimeAndId {
+                                if first.solves.insert(SolveTimeAndId {
                                     time: solve.created.clone(),
                                     id: solve.id.clone(),
-                                });
+                                }) {
+                                    first.fingerprint ^=
+                                        solve_fingerprint(&solve.id, &solve.penalty, solve.time);
+                                }
                             }
                         }
                         first.update_id = *next_update_id;
                         *next_update_id += 1;
+                        let first_id = first.id.clone();
                         self.sessions.remove(second);
+
+                        // Participants of the second session join the first session
+                        if let Some(second_participants) = self.participants.remove(second) {
+                            let participants =
+                                self.participants.entry(first_id).or_insert_with(Vec::new);
+                            for participant in second_participants {
+                                if !participants.contains(&participant) {
+                                    participants.push(participant);
+                                }
+                            }
+                        }
                         true
                     }
                     None => false,
@@ -966,9 +2381,12 @@ impl SolveDatabase {
                         time: solve.created.clone(),
                         id: solve_id.clone(),
                     };
+                    let fingerprint = solve_fingerprint(&solve.id, &solve.penalty, solve.time);
                     match self.sessions.get_mut(&solve.session) {
                         Some(session) => {
-                            session.solves.remove(&key);
+                            if session.solves.remove(&key) {
+                                session.fingerprint ^= fingerprint;
+                            }
                             session.update_id = *next_update_id;
                             *next_update_id += 1;
                         }
@@ -976,11 +2394,263 @@ impl SolveDatabase {
                     };
                     self.solve_map.solve_times.remove(&key.id);
                     self.solve_map.solves.remove(&key);
+                    self.tags.remove(solve_id);
+
+                    // An attempt is no longer complete once one of its solves is deleted
+                    self.attempt_groups
+                        .retain(|_, group| !group.solves.contains(solve_id));
                     true
                 }
                 None => false,
             },
+            Action::AddParticipant(session_id, participant_id, name) => {
+                let participants = self
+                    .participants
+                    .entry(session_id.clone())
+                    .or_insert_with(Vec::new);
+                if participants
+                    .iter()
+                    .any(|participant| &participant.id == participant_id)
+                {
+                    return false;
+                }
+                participants.push(Participant {
+                    id: participant_id.clone(),
+                    name: name.clone(),
+                });
+                self.touch_session(session_id, next_update_id);
+                true
+            }
+            Action::RemoveParticipant(session_id, participant_id) => {
+                let participants = match self.participants.get_mut(session_id) {
+                    Some(participants) => participants,
+                    None => return false,
+                };
+                match participants
+                    .iter()
+                    .position(|participant| &participant.id == participant_id)
+                {
+                    Some(index) => {
+                        participants.remove(index);
+                        self.touch_session(session_id, next_update_id);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Action::ChangeParticipant(solve_id, participant_id) => {
+                match self.solve_map.solve_mut(solve_id) {
+                    Some(solve) => {
+                        solve.participant = participant_id.clone();
+                        if let Some(session) = self.sessions.get_mut(&solve.session) {
+                            session.update_id = *next_update_id;
+                            *next_update_id += 1;
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Action::TagSolve(solve_id, tag) => {
+                let session = match self.solve_map.solve(solve_id) {
+                    Some(solve) => solve.session.clone(),
+                    None => return false,
+                };
+                if !self
+                    .tags
+                    .entry(solve_id.clone())
+                    .or_insert_with(BTreeSet::new)
+                    .insert(tag.clone())
+                {
+                    return false;
+                }
+                self.touch_session(&session, next_update_id);
+                true
+            }
+            Action::UntagSolve(solve_id, tag) => {
+                let session = match self.solve_map.solve(solve_id) {
+                    Some(solve) => solve.session.clone(),
+                    None => return false,
+                };
+                let removed = match self.tags.get_mut(solve_id) {
+                    Some(tags) => tags.remove(tag),
+                    None => false,
+                };
+                if !removed {
+                    return false;
+                }
+                self.touch_session(&session, next_update_id);
+                true
+            }
+            Action::SetPreference(name, value) => self.preferences.set(name, value),
+            Action::SetSessionGoal(session_id, kind, value) => {
+                // Goals are kept separately from the session so that they can be set
+                // before the first solve of a session
+                let goals = self
+                    .goals
+                    .entry(session_id.clone())
+                    .or_insert_with(BTreeMap::new);
+                let changed = if value.len() == 0 {
+                    goals.remove(kind).is_some()
+                } else if goals.get(kind) != Some(value) {
+                    goals.insert(kind.clone(), value.clone());
+                    true
+                } else {
+                    false
+                };
+                if changed {
+                    if let Some(session) = self.sessions.get_mut(session_id) {
+                        session.update_id = *next_update_id;
+                        *next_update_id += 1;
+                    }
+                }
+                changed
+            }
+            Action::AttemptGroup(group_id, format, solves) => {
+                let sessions: BTreeSet<String> = solves
+                    .iter()
+                    .filter_map(|solve_id| self.solve_map.solve(solve_id))
+                    .map(|solve| solve.session.clone())
+                    .collect();
+                if format.len() == 0 {
+                    if self.attempt_groups.remove(group_id).is_none() {
+                        return false;
+                    }
+                } else {
+                    if solves.len() == 0 || sessions.len() == 0 {
+                        return false;
+                    }
+
+                    // A solve can only be part of one attempt. If attempts on different
+                    // devices overlap, the most recent one is kept.
+                    self.attempt_groups.retain(|_, group| {
+                        !group
+                            .solves
+                            .iter()
+                            .any(|solve_id| solves.contains(solve_id))
+                    });
+                    self.attempt_groups.insert(
+                        group_id.clone(),
+                        StoredAttemptGroup {
+                            format: format.clone(),
+                            solves: solves.clone(),
+                        },
+                    );
+                }
+                for session in sessions {
+                    self.touch_session(&session, next_update_id);
+                }
+                true
+            }
+        }
+    }
+
+    fn touch_session(&mut self, session_id: &str, next_update_id: &mut u64) {
+        if let Some(session) = self.sessions.get_mut(session_id) {
+            session.update_id = *next_update_id;
+            *next_update_id += 1;
+        }
+    }
+}
+
+fn analyze_skips(solve: &Solve) -> Option<CFOPSkips> {
+    if solve.solve_type.is_3x3x3() {
+        solve.analyze().skips()
+    } else {
+        None
+    }
+}
+
+impl SolveQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn solve_type(mut self, solve_type: SolveType) -> Self {
+        self.solve_type = Some(solve_type);
+        self
+    }
+
+    pub fn session(mut self, session: String) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    pub fn tag(mut self, tag: String) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Only match solves where the given step was skipped
+    pub fn skipped(mut self, step: SkippedStep) -> Self {
+        self.skipped = Some(step);
+        self
+    }
+
+    /// Only match solves recorded with the given input
+    pub fn input(mut self, input: SolveInput) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Only match solves created in the given time range. The start of the
+    /// range is inclusive and the end is exclusive.
+    pub fn range(mut self, since: DateTime<Local>, until: DateTime<Local>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    pub fn matches(&self, solve: &Solve, history: &History) -> bool {
+        if let Some(solve_type) = self.solve_type {
+            if solve.solve_type != solve_type {
+                return false;
+            }
+        }
+        if let Some(session) = &self.session {
+            if &solve.session != session {
+                return false;
+            }
+        }
+        if let Some(since) = &self.since {
+            if &solve.created < since {
+                return false;
+            }
+        }
+        if let Some(until) = &self.until {
+            if &solve.created >= until {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            match history.solves.tags.get(&solve.id) {
+                Some(tags) if tags.contains(tag) => (),
+                _ => return false,
+            }
+        }
+        if let Some(step) = self.skipped {
+            match history.cached_solve_skips(solve) {
+                Some(skips) if skips.contains(step) => (),
+                _ => return false,
+            }
+        }
+        if let Some(input) = &self.input {
+            match solve.input() {
+                Some(solve_input) if solve_input.same_source(input) => (),
+                _ => return false,
+            }
         }
+        true
+    }
+}
+
+impl Participant {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
     }
 }
 
@@ -0,0 +1,80 @@
+use crate::common::TimedMoveSequence;
+use crate::{MoveNotation, MoveSequence, Penalty, Solve, SolveInput, SolveList};
+use serde_json::json;
+
+/// Completed solve passed to the hooks registered with
+/// `History::register_solve_hook`, along with the state of its session after
+/// the solve was added.
+#[derive(Clone, Debug)]
+pub struct SolveCompletion {
+    pub solve: Solve,
+    pub session_name: Option<String>,
+    /// Number of solves in the session, including this one
+    pub session_solve_count: usize,
+    /// Current averages of the session. These are `None` if there are not
+    /// enough solves or if the average is a DNF.
+    pub ao5: Option<u32>,
+    pub ao12: Option<u32>,
+    /// Best single of the session
+    pub best: Option<u32>,
+    pub(crate) notation: MoveNotation,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SolveHookHandle {
+    pub(crate) id: u64,
+}
+
+impl SolveCompletion {
+    pub(crate) fn new(
+        solve: Solve,
+        session_name: Option<String>,
+        session_solves: &[Solve],
+        notation: MoveNotation,
+    ) -> Self {
+        Self {
+            solve,
+            session_name,
+            session_solve_count: session_solves.len(),
+            ao5: session_solves.last_average(5).map(|average| average.time),
+            ao12: session_solves.last_average(12).map(|average| average.time),
+            best: session_solves.best().map(|best| best.time),
+            notation,
+        }
+    }
+
+    /// Serializes the solve as a single line of JSON for external tools. Times
+    /// are in milliseconds, and `final_time` is `null` for a DNF. The field
+    /// names of the solve match those used by `History::export`.
+    pub fn to_json(&self) -> String {
+        let solve = &self.solve;
+        json!({
+            "id": solve.id,
+            "type": solve.solve_type.to_string(),
+            "session": solve.session,
+            "session_name": self.session_name,
+            "time": solve.time,
+            "ok": if let Penalty::DNF = solve.penalty { false } else { true },
+            "penalty": match solve.penalty {
+                Penalty::None => 0,
+                Penalty::Time(time) => time,
+                Penalty::DNF => 0,
+            },
+            "final_time": solve.final_time(),
+            "scramble": solve.scramble.to_string_with(self.notation),
+            "timestamp": solve.created.timestamp(),
+            "device": solve.device,
+            "input": solve
+                .device
+                .as_ref()
+                .map(|device| SolveInput::from_device_string(device).name().to_string()),
+            "participant": solve.participant,
+            "solve": solve.moves.as_ref().map(|moves| moves.to_string()),
+            "session_solve_count": self.session_solve_count,
+            "ao5": self.ao5,
+            "ao12": self.ao12,
+            "best": self.best,
+        })
+        .to_string()
+    }
+}
@@ -2,13 +2,155 @@ use crate::common::{parse_move_string, parse_timed_move_string, Penalty, Solve,
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Local, TimeZone, Utc};
 use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::str::FromStr;
 use uuid::Uuid;
 
 pub(crate) struct ImportedSession {
     pub id: String,
     pub name: Option<String>,
+    pub participants: Vec<(String, String)>,
     pub solves: Vec<Solve>,
+    /// Tags attached to solves, keyed by solve ID
+    pub tags: HashMap<String, Vec<String>>,
+}
+
+/// Import of a solve backup that is merged into the history in chunks, so
+/// that large imports do not block the application. The backup is parsed
+/// when the import is created, which does not need the history and can be
+/// done in the background. In a dry run the history is not modified and the
+/// summary reports what the import would change.
+pub struct HistoryImport {
+    pub(crate) sessions: Vec<ImportedSession>,
+    pub(crate) dry_run: bool,
+    pub(crate) cancelled: bool,
+    /// Position of the next solve to merge
+    pub(crate) session_index: usize,
+    pub(crate) solve_index: usize,
+    /// Whether the session being merged already existed and was changed
+    pub(crate) session_existing: bool,
+    pub(crate) session_changed: bool,
+    pub(crate) merged_solves: usize,
+    pub(crate) total_solves: usize,
+    pub(crate) stats: ImportStats,
+}
+
+/// Changes made by an import
+#[derive(Clone, Copy, Default)]
+pub struct ImportStats {
+    pub file_sessions: usize,
+    pub file_solves: usize,
+    pub new_sessions: usize,
+    pub changed_sessions: usize,
+    pub new_solves: usize,
+    pub changed_solves: usize,
+}
+
+#[derive(Clone, Copy)]
+pub enum HistoryImportProgress {
+    MergeSolves(usize, usize),
+    Complete,
+    Cancelled,
+}
+
+impl HistoryImport {
+    pub fn new(contents: String, dry_run: bool) -> crate::Result<Self> {
+        let sessions = ImportedSession::import(contents).map_err(crate::Error::Parse)?;
+        let total_solves = sessions.iter().map(|session| session.solves.len()).sum();
+        Ok(Self {
+            stats: ImportStats {
+                file_sessions: sessions.len(),
+                file_solves: total_solves,
+                ..ImportStats::default()
+            },
+            sessions,
+            dry_run,
+            cancelled: false,
+            session_index: 0,
+            solve_index: 0,
+            session_existing: false,
+            session_changed: false,
+            merged_solves: 0,
+            total_solves,
+        })
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Stops the import before the next chunk. Solves that were already
+    /// merged are kept.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.cancelled || self.session_index >= self.sessions.len()
+    }
+
+    pub fn progress(&self) -> HistoryImportProgress {
+        if self.cancelled {
+            HistoryImportProgress::Cancelled
+        } else if self.session_index >= self.sessions.len() {
+            HistoryImportProgress::Complete
+        } else {
+            HistoryImportProgress::MergeSolves(self.merged_solves, self.total_solves)
+        }
+    }
+
+    pub fn stats(&self) -> ImportStats {
+        self.stats
+    }
+
+    /// Describes the changes made by the import, or the changes that would be
+    /// made for a dry run
+    pub fn summary(&self) -> String {
+        let (added, modified) = if self.dry_run {
+            ("would be added", "would be modified")
+        } else {
+            ("added", "modified")
+        };
+        let mut summary = format!(
+            "File contained {} solve(s) in {} session(s).\n\
+            {} session(s) {}.\n\
+            {} session(s) {}.\n\
+            {} solve(s) {}.\n\
+            {} solve(s) {}.",
+            self.stats.file_solves,
+            self.stats.file_sessions,
+            self.stats.new_sessions,
+            added,
+            self.stats.changed_sessions,
+            modified,
+            self.stats.new_solves,
+            added,
+            self.stats.changed_solves,
+            modified
+        );
+        if self.cancelled {
+            summary += &format!(
+                "\nCancelled after {} of {} solve(s).",
+                self.merged_solves, self.total_solves
+            );
+        }
+        summary
+    }
+}
+
+impl HistoryImportProgress {
+    pub fn approximate_percent_done(&self) -> f32 {
+        match self {
+            Self::MergeSolves(done, total) => {
+                if *total == 0 {
+                    100.0
+                } else {
+                    *done as f32 * 100.0 / *total as f32
+                }
+            }
+            Self::Complete | Self::Cancelled => 100.0,
+        }
+    }
 }
 
 impl ImportedSession {
@@ -69,8 +211,34 @@ impl ImportedSession {
                 )
             })?;
 
+            // Participant list is optional
+            let mut participants = Vec::new();
+            if let Some(participant_array) = session.get("participants") {
+                let participant_array = participant_array.as_array().ok_or_else(|| {
+                    anyhow!("Session '{}' participant list is not an array", session_id)
+                })?;
+                for participant in participant_array {
+                    let id = participant
+                        .get("id")
+                        .ok_or_else(|| {
+                            anyhow!("Participant in session '{}' has no id", session_id)
+                        })?
+                        .as_str()
+                        .ok_or_else(|| {
+                            anyhow!("Participant in session '{}' has invalid id", session_id)
+                        })?;
+                    let name = participant
+                        .get("name")
+                        .ok_or_else(|| anyhow!("Participant '{}' has no name", id))?
+                        .as_str()
+                        .ok_or_else(|| anyhow!("Participant '{}' has invalid name", id))?;
+                    participants.push((id.into(), name.into()));
+                }
+            }
+
             // Parse list of solves
             let mut solves = Vec::new();
+            let mut tags = HashMap::new();
             let solve_array = session
                 .get("solves")
                 .ok_or_else(|| anyhow!("Session '{}' is missing solve list", session_id))?
@@ -115,7 +283,10 @@ impl ImportedSession {
                     .ok_or_else(|| anyhow!("Solve '{}' has no timestamp", id))?
                     .as_i64()
                     .ok_or_else(|| anyhow!("Solve '{}' has invalid timestamp", id))?;
-                let timestamp = Local.timestamp(timestamp, 0);
+                let timestamp = Local
+                    .timestamp_opt(timestamp, 0)
+                    .single()
+                    .ok_or_else(|| anyhow!("Solve '{}' has invalid timestamp", id))?;
 
                 let scramble_string = solve
                     .get("scramble")
@@ -139,6 +310,24 @@ impl ImportedSession {
                     None
                 };
 
+                // Participant is optional
+                let participant = solve
+                    .get("participant")
+                    .and_then(|participant| participant.as_str())
+                    .map(|participant| participant.to_string());
+
+                // Tags are optional
+                if let Some(tag_array) = solve.get("tags").and_then(|tags| tags.as_array()) {
+                    let solve_tags: Vec<String> = tag_array
+                        .iter()
+                        .filter_map(|tag| tag.as_str())
+                        .map(|tag| tag.to_string())
+                        .collect();
+                    if solve_tags.len() != 0 {
+                        tags.insert(id.to_string(), solve_tags);
+                    }
+                }
+
                 // Move list is optional
                 let moves = if let Some(moves) = solve.get("solve") {
                     if let Some(moves) = moves.as_str() {
@@ -172,14 +361,18 @@ impl ImportedSession {
                         Penalty::DNF
                     },
                     device: device.map(|string| string.into()),
+                    participant,
                     moves,
+                    skips: None,
                 });
             }
 
             sessions.push(ImportedSession {
                 id: session_id.into(),
                 name: name.map(|string| string.into()),
+                participants,
                 solves,
+                tags,
             });
         }
 
@@ -280,7 +473,8 @@ impl ImportedSession {
                 let penalty = match penalty {
                     -1 => Penalty::DNF,
                     0 => Penalty::None,
-                    time => Penalty::Time(time as u32),
+                    time if time > 0 && time <= u32::MAX as i64 => Penalty::Time(time as u32),
+                    _ => return Err(anyhow!("Solve penalty is invalid")),
                 };
                 let time = time_array
                     .get(1)
@@ -304,7 +498,10 @@ impl ImportedSession {
                     .as_i64()
                     .ok_or_else(|| anyhow!("Timestamp is not an integer"))?;
                 let id = format!("cstimer:{}", timestamp);
-                let timestamp = Local.timestamp(timestamp, 0);
+                let timestamp = Local
+                    .timestamp_opt(timestamp, 0)
+                    .single()
+                    .ok_or_else(|| anyhow!("Timestamp is out of range"))?;
 
                 // Parse move sequence (optional)
                 let moves = if let Some(moves) = solve.get(4) {
@@ -332,7 +529,9 @@ impl ImportedSession {
                     time,
                     penalty,
                     device: None,
+                    participant: None,
                     moves,
+                    skips: None,
                 });
             }
 
@@ -340,7 +539,9 @@ impl ImportedSession {
                 sessions.push(ImportedSession {
                     id: session_id.clone(),
                     name: None,
+                    participants: Vec::new(),
                     solves,
+                    tags: HashMap::new(),
                 });
             }
         }
@@ -468,7 +669,9 @@ impl ImportedSession {
                 time,
                 penalty,
                 device: Some(device.into()),
+                participant: None,
                 moves: solution,
+                skips: None,
             });
         }
 
@@ -477,10 +680,21 @@ impl ImportedSession {
             sessions.push(ImportedSession {
                 id: session_id.clone(),
                 name: None,
+                participants: Vec::new(),
                 solves,
+                tags: HashMap::new(),
             });
         }
 
         Ok(sessions)
     }
 }
+
+/// Parses exported solve data without adding it to a history, returning the
+/// number of sessions found. This is used by the fuzz targets.
+#[cfg(feature = "fuzzing")]
+pub fn parse_import(contents: String) -> crate::Result<usize> {
+    Ok(ImportedSession::import(contents)
+        .map_err(crate::Error::Parse)?
+        .len())
+}
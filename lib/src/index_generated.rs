@@ -33,16 +33,22 @@ impl<'a> ActionListIndex<'a> {
         _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
         args: &'args ActionListIndexArgs<'args>) -> flatbuffers::WIPOffset<ActionListIndex<'bldr>> {
       let mut builder = ActionListIndexBuilder::new(_fbb);
+      builder.add_version(args.version);
       if let Some(x) = args.lists { builder.add_lists(x); }
       builder.finish()
     }
 
     pub const VT_LISTS: flatbuffers::VOffsetT = 4;
+    pub const VT_VERSION: flatbuffers::VOffsetT = 6;
 
   #[inline]
   pub fn lists(&self) -> Option<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<&'a str>>> {
     self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<&'a str>>>>(ActionListIndex::VT_LISTS, None)
   }
+  #[inline]
+  pub fn version(&self) -> u32 {
+    self._tab.get::<u32>(ActionListIndex::VT_VERSION, Some(0)).unwrap()
+  }
 }
 
 impl flatbuffers::Verifiable for ActionListIndex<'_> {
@@ -53,18 +59,21 @@ impl flatbuffers::Verifiable for ActionListIndex<'_> {
     use self::flatbuffers::Verifiable;
     v.visit_table(pos)?
      .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<&'_ str>>>>(&"lists", Self::VT_LISTS, false)?
+     .visit_field::<u32>(&"version", Self::VT_VERSION, false)?
      .finish();
     Ok(())
   }
 }
 pub struct ActionListIndexArgs<'a> {
     pub lists: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, flatbuffers::ForwardsUOffset<&'a str>>>>,
+    pub version: u32,
 }
 impl<'a> Default for ActionListIndexArgs<'a> {
     #[inline]
     fn default() -> Self {
         ActionListIndexArgs {
             lists: None,
+            version: 0,
         }
     }
 }
@@ -78,6 +87,10 @@ impl<'a: 'b, 'b> ActionListIndexBuilder<'a, 'b> {
     self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(ActionListIndex::VT_LISTS, lists);
   }
   #[inline]
+  pub fn add_version(&mut self, version: u32) {
+    self.fbb_.push_slot::<u32>(ActionListIndex::VT_VERSION, version, 0);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> ActionListIndexBuilder<'a, 'b> {
     let start = _fbb.start_table();
     ActionListIndexBuilder {
@@ -96,6 +109,7 @@ impl std::fmt::Debug for ActionListIndex<'_> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let mut ds = f.debug_struct("ActionListIndex");
       ds.field("lists", &self.lists());
+      ds.field("version", &self.version());
       ds.finish()
   }
 }
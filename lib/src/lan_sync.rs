@@ -0,0 +1,425 @@
+use crate::action::StoredAction;
+use crate::error::ErrorKind;
+use crate::request::{SyncRequest, SyncResponse, SYNC_API_VERSION};
+use anyhow::{anyhow, Result};
+use dirs::data_local_dir;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// mDNS service type advertised by devices hosting LAN sync
+pub const LAN_SYNC_SERVICE: &str = "_tpscube-sync._tcp.local.";
+/// TCP port used for LAN sync requests
+pub const LAN_SYNC_PORT: u16 = 47863;
+
+// Same limits as the cloud sync server, so that clients see the same
+// behavior for large syncs
+const MAX_ACTIONS_PER_BATCH: usize = 64;
+const MAX_BATCHES_PER_RESPONSE: usize = 256;
+
+/// Largest request accepted by a host. Requests upload at most
+/// `MAX_UPLOAD_ACTIONS_PER_REQUEST` actions, which is well under this size.
+const MAX_REQUEST_SIZE: usize = 4 * 1024 * 1024;
+/// Largest response accepted from a host, which can contain up to
+/// `MAX_BATCHES_PER_RESPONSE` batches of actions
+const MAX_RESPONSE_SIZE: usize = 64 * 1024 * 1024;
+/// Longest time a LAN sync connection can take in total, from connecting to
+/// the end of the response
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A device on the local network that is hosting sync
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LanSyncPeer {
+    pub name: String,
+    pub address: SocketAddr,
+}
+
+/// Hosts sync for other devices on the local network. The host takes the
+/// place of the cloud sync server: it keeps the log of synced actions and
+/// answers the same sync requests over TCP. The hosting device syncs to
+/// itself over the loopback address so that it follows the same rules as the
+/// other devices.
+pub struct LanSyncHost {
+    log: Arc<Mutex<LanSyncLog>>,
+    stop: Arc<AtomicBool>,
+    daemon: Option<ServiceDaemon>,
+}
+
+/// Finds devices hosting sync on the local network using mDNS
+pub struct LanSyncBrowser {
+    daemon: ServiceDaemon,
+    events: mdns_sd::Receiver<ServiceEvent>,
+    peers: BTreeMap<String, LanSyncPeer>,
+}
+
+/// Action log of a LAN sync host. Actions are stored in numbered batches,
+/// and each batch is appended to a file as it is stored.
+struct LanSyncLog {
+    sync_key: String,
+    path: PathBuf,
+    batches: BTreeMap<u32, Vec<StoredAction>>,
+}
+
+/// Connection that must complete before a deadline. Socket timeouts only
+/// apply to a single read or write, so they are shortened as the deadline
+/// approaches to keep a slow peer from holding the connection open.
+pub(crate) struct DeadlineStream {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl LanSyncLog {
+    fn open(sync_key: String) -> Result<Self> {
+        let mut path =
+            data_local_dir().ok_or_else(|| anyhow!("Local data directory not defined"))?;
+        path.push("tpscube");
+        std::fs::create_dir_all(&path)?;
+        path.push(format!("lan_sync_{}", sync_key));
+
+        // Each batch is stored as its sync ID and length followed by the
+        // serialized actions
+        let mut batches = BTreeMap::new();
+        if let Ok(mut file) = File::open(&path) {
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
+            let mut offset = 0;
+            while offset + 8 <= contents.len() {
+                let mut sync_id = [0; 4];
+                let mut len = [0; 4];
+                sync_id.copy_from_slice(&contents[offset..offset + 4]);
+                len.copy_from_slice(&contents[offset + 4..offset + 8]);
+                let start = offset + 8;
+                let end = start + u32::from_le_bytes(len) as usize;
+                if end > contents.len() {
+                    break;
+                }
+                batches.insert(
+                    u32::from_le_bytes(sync_id),
+                    StoredAction::deserialize_list(&contents[start..end])?,
+                );
+                offset = end;
+            }
+
+            if offset < contents.len() {
+                // The last batch was only partially written, so its upload was
+                // never acknowledged. Remove it so that new batches are appended
+                // directly after the last complete batch.
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)?
+                    .set_len(offset as u64)?;
+            }
+        }
+
+        Ok(Self {
+            sync_key,
+            path,
+            batches,
+        })
+    }
+
+    fn store(&mut self, sync_id: u32, actions: Vec<StoredAction>) -> Result<()> {
+        let data = StoredAction::serialize_list(&actions);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&sync_id.to_le_bytes())?;
+        file.write_all(&(data.len() as u32).to_le_bytes())?;
+        file.write_all(&data)?;
+        self.batches.insert(sync_id, actions);
+        Ok(())
+    }
+
+    /// Handles a sync request the same way as the cloud sync server
+    fn sync(&mut self, request: SyncRequest) -> Result<SyncResponse> {
+        if request.sync_key != self.sync_key {
+            return Err(anyhow!(
+                "Sync key does not match the sync key of the host device"
+            ));
+        }
+
+        // Get any new actions since the client's last sync
+        let mut sync_id = request.sync_id;
+        let mut new_actions = Vec::new();
+        let mut batches = self
+            .batches
+            .range((Bound::Excluded(request.sync_id), Bound::Unbounded));
+        for (id, actions) in batches.by_ref().take(MAX_BATCHES_PER_RESPONSE) {
+            sync_id = *id;
+            new_actions.extend(actions.iter().cloned());
+        }
+        let more_actions = batches.next().is_some();
+
+        // Store new actions from the client only if it is up to date, otherwise
+        // the client must resolve the new actions and upload again
+        let mut uploaded = 0;
+        if let Some(upload) = request.upload {
+            if new_actions.len() == 0 {
+                for chunk in upload.chunks(MAX_ACTIONS_PER_BATCH) {
+                    sync_id = sync_id
+                        .checked_add(1)
+                        .ok_or_else(|| anyhow!("Sync ID is out of range"))?;
+                    self.store(sync_id, chunk.to_vec())?;
+                    uploaded += chunk.len();
+                }
+            }
+        }
+
+        Ok(SyncResponse {
+            new_sync_id: sync_id,
+            new_actions,
+            more_actions,
+            uploaded,
+        })
+    }
+}
+
+impl DeadlineStream {
+    pub fn new(stream: TcpStream, timeout: Duration) -> Self {
+        Self {
+            stream,
+            deadline: Instant::now() + timeout,
+        }
+    }
+
+    fn remaining(&self) -> io::Result<Duration> {
+        match self.deadline.checked_duration_since(Instant::now()) {
+            Some(remaining) if remaining > Duration::from_millis(0) => Ok(remaining),
+            _ => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Connection timed out",
+            )),
+        }
+    }
+}
+
+impl Read for DeadlineStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.set_read_timeout(Some(self.remaining()?))?;
+        self.stream.read(buf)
+    }
+}
+
+impl Write for DeadlineStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.set_write_timeout(Some(self.remaining()?))?;
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+fn read_message(stream: &mut DeadlineStream, max_size: usize) -> Result<Value> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > max_size {
+        return Err(anyhow!("Message too large"));
+    }
+    let mut data = vec![0; len];
+    stream.read_exact(&mut data)?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+fn write_message(stream: &mut DeadlineStream, message: &str) -> Result<()> {
+    stream.write_all(&(message.len() as u32).to_be_bytes())?;
+    stream.write_all(message.as_bytes())?;
+    Ok(())
+}
+
+/// Handles a single sync request. Requests start with the sync key, as its
+/// length in one byte followed by the key itself, so that the host can turn
+/// away other devices before reading the rest of the request. The request and
+/// response are then sent as JSON prefixed with their length.
+fn handle_connection(log: &Mutex<LanSyncLog>, stream: TcpStream) -> Result<()> {
+    let mut stream = DeadlineStream::new(stream, CONNECTION_TIMEOUT);
+
+    let mut key_len = [0; 1];
+    stream.read_exact(&mut key_len)?;
+    let mut key = vec![0; key_len[0] as usize];
+    stream.read_exact(&mut key)?;
+    let key_matches = key == log.lock().unwrap().sync_key.as_bytes();
+
+    let response = if key_matches {
+        let request = read_message(&mut stream, MAX_REQUEST_SIZE)?;
+        match request
+            .get("api_version")
+            .and_then(|version| version.as_u64())
+        {
+            Some(SYNC_API_VERSION) => SyncRequest::deserialize(request)
+                .map_err(|error| anyhow!("{}", error))
+                .and_then(|request| log.lock().unwrap().sync(request))
+                .and_then(|response| Ok(response.serialize()?)),
+            _ => Err(anyhow!("API version mismatch, please update the client")),
+        }
+    } else {
+        Err(anyhow!(
+            "Sync key does not match the sync key of the host device"
+        ))
+    };
+    let response = match response {
+        Ok(response) => response,
+        // Errors are sent in the same form as bad request messages from the
+        // cloud sync server
+        Err(error) => json!({ "error": true, "message": error.to_string() }),
+    };
+    write_message(&mut stream, &response.to_string())
+}
+
+/// Sends a sync request to a LAN sync host and returns the response
+pub(crate) fn post_lan(address: SocketAddr, sync_key: &str, request: String) -> Result<Value> {
+    if sync_key.len() > u8::MAX as usize {
+        return Err(anyhow!("Sync key is too long"));
+    }
+    let mut stream = DeadlineStream::new(
+        TcpStream::connect_timeout(&address, CONNECTION_TIMEOUT)?,
+        CONNECTION_TIMEOUT,
+    );
+    stream.write_all(&[sync_key.len() as u8])?;
+    stream.write_all(sync_key.as_bytes())?;
+    write_message(&mut stream, &request)?;
+    let response = read_message(&mut stream, MAX_RESPONSE_SIZE)?;
+    if response.get("error").is_some() {
+        let message = response
+            .get("message")
+            .and_then(|message| message.as_str())
+            .unwrap_or("Bad request");
+        return Err(anyhow!("{}", message));
+    }
+    Ok(response)
+}
+
+impl LanSyncHost {
+    /// Starts hosting sync for devices using the given sync key, and
+    /// advertises the host on the local network under the given name
+    pub fn start(sync_key: &str, name: &str) -> crate::Result<Self> {
+        let log = LanSyncLog::open(sync_key.into()).storage_error()?;
+        let log = Arc::new(Mutex::new(log));
+        let listener = TcpListener::bind(("0.0.0.0", LAN_SYNC_PORT)).network_error()?;
+        listener.set_nonblocking(true).network_error()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_copy = stop.clone();
+        let log_copy = log.clone();
+        std::thread::spawn(move || {
+            while !stop_copy.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        // Requests are handled one at a time so that uploads
+                        // are applied in order. Each connection has a deadline,
+                        // so a slow device can't hold up the others for long.
+                        let _ = stream
+                            .set_nonblocking(false)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|_| handle_connection(&log_copy, stream));
+                    }
+                    Err(_) => std::thread::sleep(ACCEPT_POLL_INTERVAL),
+                }
+            }
+        });
+
+        // Advertising is best effort, devices can still connect by address if
+        // mDNS is not available on the network
+        let daemon = ServiceDaemon::new().ok();
+        if let Some(daemon) = &daemon {
+            let host_name = format!("{}.local.", name.replace(' ', "-"));
+            if let Ok(info) =
+                ServiceInfo::new(LAN_SYNC_SERVICE, name, &host_name, "", LAN_SYNC_PORT, None)
+            {
+                let _ = daemon.register(info.enable_addr_auto());
+            }
+        }
+
+        Ok(Self { log, stop, daemon })
+    }
+
+    /// Sync key of the devices that this host is syncing
+    pub fn sync_key(&self) -> String {
+        self.log.lock().unwrap().sync_key.clone()
+    }
+
+    /// Switches the host to a new sync key. The host keeps a separate log
+    /// for each sync key, and devices using the old key are turned away.
+    pub fn set_sync_key(&mut self, sync_key: &str) -> crate::Result<()> {
+        let log = LanSyncLog::open(sync_key.into()).storage_error()?;
+        *self.log.lock().unwrap() = log;
+        Ok(())
+    }
+
+    /// Address that the hosting device uses to sync with itself
+    pub fn local_address() -> SocketAddr {
+        SocketAddr::new(IpAddr::from([127, 0, 0, 1]), LAN_SYNC_PORT)
+    }
+}
+
+impl Drop for LanSyncHost {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(daemon) = self.daemon.take() {
+            let _ = daemon.shutdown();
+        }
+    }
+}
+
+impl LanSyncBrowser {
+    pub fn new() -> crate::Result<Self> {
+        let daemon =
+            ServiceDaemon::new().map_err(|error| crate::Error::Network(anyhow!("{}", error)))?;
+        let events = daemon
+            .browse(LAN_SYNC_SERVICE)
+            .map_err(|error| crate::Error::Network(anyhow!("{}", error)))?;
+        Ok(Self {
+            daemon,
+            events,
+            peers: BTreeMap::new(),
+        })
+    }
+
+    /// Hosts that have been found so far. Call this periodically to pick up
+    /// hosts as they appear and disappear.
+    pub fn peers(&mut self) -> Vec<LanSyncPeer> {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    if let Some(address) = info.get_addresses().iter().next() {
+                        let name = info
+                            .get_fullname()
+                            .trim_end_matches(LAN_SYNC_SERVICE)
+                            .trim_end_matches('.')
+                            .to_string();
+                        self.peers.insert(
+                            info.get_fullname().to_string(),
+                            LanSyncPeer {
+                                name,
+                                address: SocketAddr::new(IpAddr::V4(*address), info.get_port()),
+                            },
+                        );
+                    }
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    self.peers.remove(&fullname);
+                }
+                _ => (),
+            }
+        }
+        self.peers.values().cloned().collect()
+    }
+}
+
+impl Drop for LanSyncBrowser {
+    fn drop(&mut self) {
+        let _ = self.daemon.shutdown();
+    }
+}
@@ -1,75 +1,247 @@
+#[macro_use]
+mod diagnostics;
+
 mod action;
 mod analysis;
+mod attempt;
+mod big_cube;
 mod common;
 mod cube2x2x2;
 mod cube3x3x3;
 mod cube4x4x4;
+mod error;
+mod official;
+mod preferences;
+mod puzzle;
 mod rand;
 mod request;
+mod scramble_image;
+mod stats;
 mod tables;
+mod variant;
+
+#[cfg(not(feature = "no_solver"))]
+mod guided;
+#[cfg(not(feature = "no_solver"))]
+mod scramble_provider;
+#[cfg(not(feature = "no_solver"))]
+mod scramble_sheet;
 
+#[cfg(feature = "storage")]
+mod account;
+#[cfg(feature = "storage")]
+mod algorithm_db;
+#[cfg(feature = "native-storage")]
+mod api;
+#[cfg(feature = "storage")]
+mod case_schedule;
+#[cfg(feature = "storage")]
+mod compact;
+#[cfg(feature = "storage")]
+mod competition;
+#[cfg(feature = "storage")]
+mod device;
 #[cfg(feature = "storage")]
 mod future;
 #[cfg(feature = "storage")]
+mod goal;
+#[cfg(feature = "storage")]
 mod history;
 #[cfg(feature = "storage")]
+mod hooks;
+#[cfg(feature = "storage")]
 mod import;
+#[cfg(feature = "native-storage")]
+mod lan_sync;
+#[cfg(feature = "native-storage")]
+mod merge;
+#[cfg(feature = "storage")]
+mod race;
+#[cfg(feature = "storage")]
+mod report;
+#[cfg(feature = "storage")]
+mod session_stats;
 #[cfg(feature = "storage")]
 mod storage;
 #[cfg(feature = "storage")]
+mod streak;
+#[cfg(feature = "storage")]
 mod sync;
 
 #[cfg(feature = "bluetooth")]
 mod bluetooth;
+#[cfg(any(feature = "bluetooth", feature = "fuzzing"))]
+mod packet;
+#[cfg(any(feature = "bluetooth", feature = "fuzzing"))]
+mod timeline;
 
 #[allow(dead_code, unused_imports)]
 mod action_generated;
 #[allow(dead_code, unused_imports)]
 mod index_generated;
 
-pub use crate::rand::{RandomSource, SimpleSeededRandomSource, StandardRandomSource};
+pub use crate::rand::{
+    RandomSource, SeededRandomSource, SimpleSeededRandomSource, StandardRandomSource,
+};
 pub use action::{Action, StoredAction};
 pub use analysis::{
-    Analysis, AnalysisStepSummary, AnalysisSubstepTime, AnalysisSummary, CFOPAnalysis,
-    CFOPPartialAnalysis, CFOPProgress, CrossAnalysis, CubeWithSolution, F2LPairAnalysis,
-    FinalAlignmentAnalysis, OLLAlgorithm, OLLAnalysis, PLLAlgorithm, PLLAnalysis, PartialAnalysis,
-    PartialAnalysisMethod, SolveAnalysis,
+    find_similar_solves, AUFStats, AlgorithmExecution, AlgorithmHand, Analysis,
+    AnalysisStepSummary, AnalysisSubstepTime, AnalysisSummary, AnalysisWarning, BlindAnalysis,
+    BlindCycleAnalysis, BlindPieceType, BlindSwapAlgorithm, CFOPAnalysis, CFOPPartialAnalysis,
+    CFOPProgress, CFOPSkips, CrossAnalysis, CubeWithSolution, CustomAnalysis, CustomMethod,
+    CustomStep, CustomStepAnalysis, F2LPairAnalysis, FinalAlignmentAnalysis, FingertrickAnalysis,
+    LookaheadAnalysis, OLLAlgorithm, OLLAnalysis, PLLAlgorithm, PLLAnalysis, PLLCaseStats,
+    ParityAlgorithm, ParityAnalysis, ParityType, PartialAnalysis, PartialAnalysisMethod, Regrip,
+    SessionAnalysis, SimilarSolve, SimilarityCriteria, SkippedStep, SolveAnalysis, SolveProfile,
+    StepBoundary, StepPredicate, StepTPS, TPSCurve, TPSSample, TransitionPause, Trigger,
+    TriggerSegment, WhatIfAnalysis,
+};
+pub use attempt::{AttemptFormat, AttemptGroup, AttemptResult};
+pub use big_cube::{
+    big_cube_scramble_length, scramble_big_cube, scramble_big_cube_moves,
+    scramble_big_cube_moves_sourced, scramble_big_cube_sourced, BigCube, BigCubeMove,
+    MAX_STORED_BIG_CUBE_SIZE, MIN_BIG_CUBE_SIZE,
 };
 pub use common::{
-    parse_move_string, parse_timed_move_string, Average, BestSolve, Color, Corner, CornerPiece,
-    Cube, CubeFace, FaceRotation, InitialCubeState, ListAverage, Move, MoveSequence, Penalty,
-    RotationDirection, Solve, SolveList, SolveType, TimedMove,
+    average_trim_count, parse_move_string, parse_solve_time, parse_time_list,
+    parse_timed_move_string, Average, BestSolve, Color, Corner, CornerPiece, Cube, CubeFace,
+    CubeOrientation, FaceRotation, InitialCubeState, ListAverage, Move, MoveNotation, MoveSequence,
+    Penalty, PrimeNotation, RotationDirection, ScrambleOrientation, Solve, SolveInput, SolveList,
+    SolveType, SolverOptions, TimedMove, WideMoveNotation,
 };
 pub use cube2x2x2::{Cube2x2x2, Cube2x2x2Faces};
 pub use cube3x3x3::{Cube3x3x3, Cube3x3x3Faces, Edge3x3x3, EdgePiece3x3x3};
 pub use cube4x4x4::{Cube4x4x4, Cube4x4x4Faces, Edge4x4x4, EdgePiece4x4x4};
-pub use request::{SyncRequest, SyncResponse, SYNC_API_VERSION};
+pub use error::{Error, Result};
+pub use official::{OfficialRecord, OfficialResults};
+pub use preferences::{
+    AccentColor, ColorScheme, Preference, Preferences, ScrambleFontSize, SolvingTimeDisplay,
+    TimeRounding, DAILY_SOLVE_GOAL_CHOICES, DEFAULT_HOLD_DURATION, HOLD_DURATION_CHOICES,
+    INSPECTION_TIME,
+};
+pub use puzzle::{Puzzle, PuzzleMove};
+pub use request::{
+    AccountDevice, AccountOperation, AccountRequest, AccountResponse, RacePlayerResults,
+    RaceRequest, RaceResponse, RaceResult, RetryAfter, SyncRequest, SyncResponse,
+    BULK_UPLOAD_INTERVAL_MS, MAX_UPLOAD_ACTIONS_PER_REQUEST, SYNC_API_VERSION,
+};
+pub use scramble_image::ScrambleImage;
+pub use stats::{
+    detect_anomalies, detect_warmup, histogram, rolling_averages, rolling_standard_deviations,
+    standard_deviation, AnomalyKind, ImprovementEstimate, RollingAverage, SolveAnomaly,
+};
+pub use variant::{Bandage, BandagedPiece, ShapeMod, ShapeModDisplay, Variant3x3x3};
 
 #[cfg(feature = "storage")]
-pub use history::{History, HistoryLoadProgress, Session};
+pub use account::Account;
+#[cfg(feature = "storage")]
+pub use algorithm_db::{
+    AlgorithmComparison, AlgorithmConflict, AlgorithmDatabase, AlgorithmImportResult,
+    AlgorithmSheetFormat, AlgorithmTimeStats, StoredAlgorithm,
+};
+#[cfg(feature = "native-storage")]
+pub use api::{ApiServer, API_SERVER_PORT};
+#[cfg(feature = "storage")]
+pub use case_schedule::{CaseReview, CaseSchedule, CaseScheduler, LastLayerCase};
+#[cfg(feature = "storage")]
+pub use compact::{StorageCategory, StorageUsage};
+#[cfg(feature = "storage")]
+pub use competition::CompetitionRound;
+#[cfg(feature = "storage")]
+pub use device::KnownDevice;
+#[cfg(feature = "storage")]
+pub use goal::{
+    goal_progress, GoalMilestone, GoalMilestoneKind, GoalProgress, GoalTracker, SessionGoal,
+};
+#[cfg(feature = "storage")]
+pub use history::{
+    ChangeListenerHandle, History, HistoryChange, HistoryLoadProgress, Participant, Session,
+    SolveQuery,
+};
+#[cfg(feature = "storage")]
+pub use hooks::{SolveCompletion, SolveHookHandle};
+#[cfg(feature = "storage")]
+pub use import::{HistoryImport, HistoryImportProgress, ImportStats};
+#[cfg(feature = "native-storage")]
+pub use lan_sync::{LanSyncBrowser, LanSyncHost, LanSyncPeer, LAN_SYNC_PORT};
+#[cfg(feature = "native-storage")]
+pub use merge::MergeSource;
+#[cfg(feature = "storage")]
+pub use race::{Race, RaceScore};
+#[cfg(feature = "storage")]
+pub use report::{ReportFormat, SessionReport};
 #[cfg(feature = "storage")]
-pub use sync::SyncStatus;
+pub use session_stats::{SessionStats, SESSION_STATS_AVERAGE_SIZES};
+#[cfg(feature = "storage")]
+pub use streak::{PracticeStreak, StreakEvent, StreakTracker, STREAK_REMINDER_HOUR};
+#[cfg(feature = "storage")]
+pub use sync::{SyncDetails, SyncStatus};
 
 #[cfg(feature = "bluetooth")]
 pub use bluetooth::{
     AvailableDevice, BluetoothCube, BluetoothCubeEvent, BluetoothCubeState, BluetoothCubeType,
-    MoveListenerHandle,
+    DeviceFeatures, DeviceInfo, MoveListenerHandle,
+};
+
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::{install_diagnostic_log, DiagnosticEntry, DiagnosticLevel, DiagnosticLog};
+
+#[cfg(all(feature = "fuzzing", feature = "storage"))]
+pub use import::parse_import;
+#[cfg(feature = "fuzzing")]
+pub use packet::{
+    decode_gan_timer_message, decode_gan_v1_cube_state, decode_gan_v2_message, decode_giiker_move,
+    decode_gocube_cube_state, decode_gocube_moves, decode_gocube_orientation, decode_moyu_turns,
+    GanTimerMessage, GanV2Message, MoYuMoveTracker, MoYuTurn,
 };
+#[cfg(feature = "fuzzing")]
+pub use timeline::{DeviceClock, MoveTimeline};
 
 #[cfg(not(feature = "no_solver"))]
-pub use cube2x2x2::scramble_2x2x2;
+pub use cube2x2x2::{scramble_2x2x2, scramble_2x2x2_sourced};
 #[cfg(not(feature = "no_solver"))]
-pub use cube3x3x3::{scramble_3x3x3, scramble_3x3x3_fast};
+pub use cube3x3x3::{
+    scramble_3x3x3, scramble_3x3x3_batch, scramble_3x3x3_batch_sourced, scramble_3x3x3_fast,
+    scramble_3x3x3_sourced, scramble_to_state,
+};
 #[cfg(not(feature = "no_solver"))]
 pub use cube4x4x4::{scramble_4x4x4, scramble_4x4x4_fast};
+#[cfg(not(feature = "no_solver"))]
+pub use guided::{GuidedSolve, GuidedSolveProgress};
+#[cfg(not(feature = "no_solver"))]
+pub use scramble_provider::{GeneratedScrambles, ScrambleFile, ScrambleProvider};
+#[cfg(not(feature = "no_solver"))]
+pub use scramble_sheet::{
+    ScrambleSet, ScrambleSheet, STANDARD_EXTRA_SCRAMBLE_COUNT, STANDARD_SCRAMBLE_COUNT,
+};
+#[cfg(not(feature = "no_solver"))]
+pub use tables::ensure_solver_tables;
+#[cfg(all(
+    not(feature = "no_solver"),
+    feature = "external-tables",
+    not(target_arch = "wasm32")
+))]
+pub use tables::external::load_solver_tables;
+#[cfg(all(not(feature = "no_solver"), feature = "external-tables"))]
+pub use tables::external::{set_solver_table, solver_tables_loaded, SOLVER_TABLE_FILES};
+#[cfg(all(not(feature = "no_solver"), feature = "large-tables"))]
+pub use tables::large::{
+    prepare_large_solver_tables, set_solver_table_size, solver_table_size, SolverTableSize,
+};
+#[cfg(not(feature = "no_solver"))]
+pub use tables::memory::{solver_table_memory_total, solver_table_memory_usage, SolverTableMemory};
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        Cube, Cube2x2x2, Cube2x2x2Faces, Cube3x3x3, Cube3x3x3Faces, Cube4x4x4, Cube4x4x4Faces,
-        InitialCubeState, Move, MoveSequence, SimpleSeededRandomSource,
+        average_trim_count, rolling_averages, AttemptFormat, Color, Cube, Cube2x2x2,
+        Cube2x2x2Faces, Cube3x3x3, Cube3x3x3Faces, Cube4x4x4, Cube4x4x4Faces, CubeFace,
+        GuidedSolve, GuidedSolveProgress, InitialCubeState, ListAverage, Move, MoveSequence,
+        Penalty, SimpleSeededRandomSource, Solve, SolveList, SolveType, SolverOptions,
     };
+    use chrono::Local;
     use std::convert::TryFrom;
+    use std::time::Duration;
 
     fn basic_small_cube_movement<T: Cube + InitialCubeState + std::fmt::Display>() {
         let mut cube = T::new();
@@ -275,6 +447,106 @@ mod tests {
         basic_4x4x4_cube_movement::<Cube4x4x4>();
     }
 
+    fn puzzle_scramble_and_solve<P: crate::Puzzle>() {
+        use crate::PuzzleMove;
+
+        let scramble = P::scramble();
+        let parsed = P::Move::parse_sequence(&P::Move::sequence_to_string(&scramble));
+        assert_eq!(parsed, Some(scramble.clone()));
+
+        let mut puzzle = P::solved();
+        puzzle.do_moves(&scramble);
+        let solution = puzzle.solve().unwrap();
+        puzzle.do_moves(&solution);
+        assert!(puzzle.is_solved(), "puzzle not solved after solution");
+    }
+
+    #[test]
+    fn puzzle_trait() {
+        puzzle_scramble_and_solve::<Cube2x2x2>();
+        puzzle_scramble_and_solve::<Cube2x2x2Faces>();
+        puzzle_scramble_and_solve::<Cube3x3x3>();
+        puzzle_scramble_and_solve::<Cube3x3x3Faces>();
+    }
+
+    #[test]
+    fn bandaged_and_void_variants() {
+        use crate::{Bandage, BandagedPiece, Corner, Edge3x3x3, Variant3x3x3};
+
+        let block = Bandage::new(
+            BandagedPiece::Corner(Corner::URF),
+            BandagedPiece::Edge(Edge3x3x3::UR),
+        )
+        .unwrap();
+        assert!(Bandage::new(
+            BandagedPiece::Corner(Corner::URF),
+            BandagedPiece::Edge(Edge3x3x3::DL)
+        )
+        .is_none());
+        let variant = Variant3x3x3::Bandaged(vec![block]);
+
+        let mut cube = Cube3x3x3::new();
+        assert!(variant.is_move_legal(&cube, Move::R));
+        assert!(variant.is_move_legal(&cube, Move::U));
+        assert!(!variant.is_move_legal(&cube, Move::F));
+        assert!(variant.is_move_legal(&cube, Move::L));
+
+        // After R, the block is on the back of the R layer
+        cube.do_move(Move::R);
+        assert!(!variant.is_move_legal(&cube, Move::U));
+        assert!(variant.is_move_legal(&cube, Move::B));
+        assert!(variant.is_move_legal(&cube, Move::F));
+
+        // Scrambles must only use legal moves, and are undone by their inverse
+        // as bandaged cubes have no solver
+        let mut rng = SimpleSeededRandomSource::new();
+        let variant = Variant3x3x3::for_solve_type(SolveType::Bandaged3x3x3);
+        let mut cube = Cube3x3x3::new();
+        let scramble = variant.scramble_sourced(&mut rng);
+        for mv in &scramble {
+            assert!(
+                variant.is_move_legal(&cube, *mv),
+                "illegal move in scramble"
+            );
+            cube.do_move(*mv);
+        }
+        assert!(variant.solve(&cube).is_none());
+        for mv in scramble.inverse() {
+            assert!(variant.is_move_legal(&cube, mv), "illegal move in solution");
+            cube.do_move(mv);
+        }
+        assert!(cube.is_solved());
+        assert_eq!(
+            SolveType::from_str(&SolveType::Bandaged3x3x3.to_string()),
+            Some(SolveType::Bandaged3x3x3)
+        );
+        assert_eq!(
+            Variant3x3x3::for_solve_type(SolveType::Void3x3x3),
+            Variant3x3x3::Void
+        );
+
+        let mut cube = Cube3x3x3::new();
+        assert!(Variant3x3x3::Void.is_solved(&cube));
+        cube.do_move(Move::R);
+        assert!(!Variant3x3x3::Void.is_solved(&cube));
+    }
+
+    #[test]
+    fn mirror_blocks_display() {
+        use crate::ShapeMod;
+
+        let mut cube = Cube3x3x3::new();
+        let display = ShapeMod::MirrorBlocks.display(&cube);
+        assert_eq!(display.heights[&CubeFace::Top][0][2], 1.0);
+        assert_eq!(display.heights[&CubeFace::Front][0][2], 2.0);
+
+        // Front stickers move to the top with their height
+        cube.do_move(Move::R);
+        let display = ShapeMod::MirrorBlocks.display(&cube);
+        assert_eq!(display.heights[&CubeFace::Top][0][2], 2.0);
+        assert_eq!(display.heights[&CubeFace::Top][0][0], 1.0);
+    }
+
     #[test]
     fn oll_parity_4x4x4() {
         let mut cube = Cube4x4x4::new();
@@ -322,6 +594,50 @@ mod tests {
         assert!(!cube.oll_parity(), "oll parity after 2x parity algorithm");
     }
 
+    #[test]
+    fn parity_analysis_4x4x4() {
+        use crate::{ParityAnalysis, ParityType, TimedMove};
+
+        let oll_parity: &'static [Move] = &[
+            Move::Rwp,
+            Move::U2,
+            Move::Lw,
+            Move::F2,
+            Move::Lwp,
+            Move::F2,
+            Move::Rw2,
+            Move::U2,
+            Move::Rw,
+            Move::U2,
+            Move::Rwp,
+            Move::U2,
+            Move::F2,
+            Move::Rw2,
+            Move::F2,
+        ];
+        let mut initial = Cube4x4x4::new();
+        initial.do_moves(oll_parity);
+        assert!(initial.is_reduced(), "not reduced after parity algorithm");
+
+        // Solve with a few 3x3x3 moves before the parity algorithm
+        let mut solution = vec![TimedMove::new(Move::U, 0), TimedMove::new(Move::Up, 200)];
+        for (i, mv) in oll_parity.iter().enumerate() {
+            solution.push(TimedMove::new(*mv, 1000 + i as u32 * 100));
+        }
+
+        let analysis = ParityAnalysis::analyze(&initial, &solution);
+        assert_eq!(analysis.algorithms.len(), 1);
+        let alg = &analysis.algorithms[0];
+        assert_eq!(alg.parity, ParityType::OLL);
+        assert_eq!(alg.start_move_index, 2);
+        assert_eq!(alg.end_move_index, solution.len());
+        assert_eq!(alg.recognition_time, 800);
+        assert_eq!(alg.execution_time, 1400);
+        assert!(analysis.oll_parity());
+        assert!(!analysis.pll_parity());
+        assert_eq!(analysis.time_cost(), 2200);
+    }
+
     #[test]
     fn matching_2x2x2_formats() {
         for mv in &[Move::U, Move::L, Move::R, Move::D, Move::F, Move::B] {
@@ -427,6 +743,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_3x3x3_faces() {
+        let mut rng = SimpleSeededRandomSource::new();
+        let mut faces = Cube3x3x3Faces::new();
+        for _ in 0..100 {
+            faces.do_move(Move::sourced_random_3x3x3(&mut rng));
+        }
+        assert!(faces.try_as_pieces().is_ok());
+
+        let colors = |faces: &Cube3x3x3Faces| {
+            let mut colors = [Color::White; 6 * 9];
+            for face in 0..6 {
+                for idx in 0..9 {
+                    colors[face * 9 + idx] =
+                        faces.color(CubeFace::try_from(face as u8).unwrap(), idx / 3, idx % 3);
+                }
+            }
+            colors
+        };
+
+        // Swapping two stickers of different colors is never a valid cube
+        let mut swapped = colors(&faces);
+        let other = (1..9).find(|idx| swapped[*idx] != swapped[0]).unwrap();
+        swapped.swap(0, other);
+        assert!(Cube3x3x3Faces::from_colors(swapped)
+            .try_as_pieces()
+            .is_err());
+
+        // Painting a sticker leaves the wrong number of stickers of two colors
+        let mut painted = Cube3x3x3Faces::from_colors(colors(&faces));
+        let color = painted.color(CubeFace::Top, 0, 0);
+        painted.set_color(
+            CubeFace::Top,
+            0,
+            0,
+            Color::try_from((color as u8 + 1) % 6).unwrap(),
+        );
+        assert!(painted.try_as_pieces().is_err());
+
+        // Neither is a cube with the wrong centers
+        let mut centers = colors(&faces);
+        centers.swap(4, 9 + 4);
+        assert!(Cube3x3x3Faces::from_colors(centers)
+            .try_as_pieces()
+            .is_err());
+    }
+
     #[test]
     fn matching_4x4x4_formats() {
         for mv in 0..Move::count_4x4x4() {
@@ -559,4 +922,1665 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn solve_3x3x3_with_options() {
+        let mut rng = SimpleSeededRandomSource::new();
+        for _ in 0..10 {
+            let mut cube = Cube3x3x3::sourced_random(&mut rng);
+            let options = SolverOptions::default()
+                .with_max_time(Duration::from_millis(50))
+                .with_max_length(24);
+            let solution = cube.solve_with_options(&options).unwrap();
+            assert!(solution.len() <= 24);
+            cube.do_moves(&solution);
+            assert!(cube.is_solved());
+        }
+
+        for _ in 0..10 {
+            let mut cube = Cube3x3x3::sourced_random(&mut rng);
+            let options = SolverOptions::default().with_target_length(26);
+            let solution = cube.solve_with_options(&options).unwrap();
+            assert!(solution.len() <= 26);
+            cube.do_moves(&solution);
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn scramble_to_target_state() {
+        use crate::scramble_to_state;
+
+        let mut rng = SimpleSeededRandomSource::new();
+        for _ in 0..5 {
+            let target = Cube3x3x3::sourced_random(&mut rng);
+            let scramble = scramble_to_state(&target).unwrap();
+            let mut cube = Cube3x3x3::new();
+            cube.do_moves(&scramble);
+            assert_eq!(cube.as_faces(), target.as_faces());
+        }
+
+        // A single twisted corner can't be set up
+        let mut faces = Cube3x3x3Faces::new();
+        let top = faces.color(CubeFace::Top, 2, 2);
+        let front = faces.color(CubeFace::Front, 0, 2);
+        let right = faces.color(CubeFace::Right, 0, 0);
+        faces.set_color(CubeFace::Top, 2, 2, right);
+        faces.set_color(CubeFace::Front, 0, 2, top);
+        faces.set_color(CubeFace::Right, 0, 0, front);
+        assert!(scramble_to_state(&faces.as_pieces()).is_err());
+
+        let mut cube = Cube3x3x3::new();
+        cube.do_move(Move::R);
+        assert_eq!(cube.try_solve().unwrap(), vec![Move::Rp]);
+    }
+
+    #[cfg(feature = "external-tables")]
+    #[test]
+    fn external_table_sizes() {
+        use crate::{set_solver_table, Error, SOLVER_TABLE_FILES};
+
+        // Truncated tables are refused instead of being read out of bounds
+        assert!(matches!(
+            set_solver_table(SOLVER_TABLE_FILES[0], vec![0; 16]),
+            Err(Error::Solver(_))
+        ));
+        assert!(matches!(
+            set_solver_table("missing.bin", Vec::new()),
+            Err(Error::Solver(_))
+        ));
+    }
+
+    #[test]
+    fn seeded_scrambles() {
+        use crate::{
+            parse_move_string, scramble_2x2x2_sourced, scramble_3x3x3_sourced, RandomSource,
+            SeededRandomSource,
+        };
+
+        // Race scrambles must be identical on every client, so the seeded output
+        // must never change between versions or platforms
+        let mut rng = SeededRandomSource::new(0);
+        let values: Vec<u32> = (0..8).map(|_| rng.next(1000)).collect();
+        assert_eq!(values, vec![535, 700, 679, 444, 747, 90, 913, 940]);
+
+        let mut rng = SeededRandomSource::new(1);
+        assert_eq!(
+            scramble_3x3x3_sourced(&mut rng),
+            parse_move_string("D' B2 L2 D R2 D' F2 D U L' R F' R' U' F' L' D B2 F D2 B2").unwrap()
+        );
+        assert_eq!(
+            scramble_2x2x2_sourced(&mut rng),
+            parse_move_string("B' R2 F D2 L' F R F'").unwrap()
+        );
+    }
+
+    #[test]
+    fn big_cube_scramble() {
+        use crate::{scramble_big_cube_sourced, BigCubeMove};
+
+        let mut rng = SimpleSeededRandomSource::new();
+        for (size, length) in &[(5, 60), (6, 80), (7, 100)] {
+            let scramble = scramble_big_cube_sourced(*size, &mut rng);
+            assert_eq!(scramble.len(), *length);
+            for mv in &scramble {
+                assert!(mv.width() >= 1 && mv.width() <= size / 2);
+            }
+
+            // Moves on the same axis commute, so a layer must not be turned
+            // twice before a move on another axis
+            let mut axis_layers: Vec<(CubeFace, usize)> = Vec::new();
+            for (i, mv) in scramble.iter().enumerate() {
+                if i > 0 {
+                    let last = scramble[i - 1].face();
+                    if last != mv.face() && last != mv.face().opposite() {
+                        axis_layers.clear();
+                    }
+                }
+                assert!(
+                    !axis_layers.contains(&(mv.face(), mv.width())),
+                    "cancelling moves in {}x{}x{} scramble",
+                    size,
+                    size,
+                    size
+                );
+                axis_layers.push((mv.face(), mv.width()));
+            }
+        }
+
+        assert_eq!(BigCubeMove::new(CubeFace::Right, 1, 1).to_string(), "R");
+        assert_eq!(BigCubeMove::new(CubeFace::Front, 2, -1).to_string(), "Fw'");
+        assert_eq!(BigCubeMove::new(CubeFace::Top, 3, 2).to_string(), "3Uw2");
+    }
+
+    #[test]
+    fn big_cube_solve_types() {
+        use crate::{
+            parse_move_string, scramble_big_cube_moves_sourced, Action, BigCube, StoredAction,
+        };
+
+        // The sticker model turns the same way as the smaller cube models
+        let mut rng = SimpleSeededRandomSource::new();
+        let moves: Vec<Move> = (0..30)
+            .map(|_| Move::sourced_random_3x3x3(&mut rng))
+            .collect();
+        let mut expected = Cube3x3x3::new();
+        expected.do_moves(&moves);
+        let mut cube = BigCube::new(3);
+        cube.do_moves(&moves);
+        assert_eq!(cube.colors(), expected.colors());
+
+        for solve_type in &[
+            SolveType::Standard5x5x5,
+            SolveType::Standard6x6x6,
+            SolveType::Standard7x7x7,
+        ] {
+            assert_eq!(
+                SolveType::from_str(&solve_type.to_string()),
+                Some(*solve_type)
+            );
+
+            let scramble = scramble_big_cube_moves_sourced(solve_type.cube_size(), &mut rng);
+            assert_eq!(parse_move_string(&scramble.to_string()).unwrap(), scramble);
+            let mut cube = solve_type.new_cube();
+            cube.do_moves(&scramble);
+            assert_eq!(cube.size(), solve_type.cube_size());
+            assert!(!cube.is_solved());
+            cube.do_moves(&scramble.inverse());
+            assert!(cube.is_solved());
+
+            // Scrambles with moves of three layers are kept in storage
+            let mut big_solve = solve(60000, Penalty::None);
+            big_solve.solve_type = *solve_type;
+            big_solve.scramble = scramble.clone();
+            let data =
+                StoredAction::serialize_list(&[StoredAction::new(Action::NewSolve(big_solve))]);
+            match &StoredAction::deserialize_list(&data).unwrap()[0].action {
+                Action::NewSolve(loaded) => {
+                    assert_eq!(loaded.solve_type, *solve_type);
+                    assert_eq!(loaded.scramble, scramble);
+                }
+                _ => panic!("expected a solve"),
+            }
+        }
+    }
+
+    #[test]
+    fn blind_analysis() {
+        use crate::{parse_move_string, Analysis, BlindSwapAlgorithm, CubeWithSolution, TimedMove};
+
+        let op_swap = [
+            Move::R,
+            Move::Up,
+            Move::Rp,
+            Move::Up,
+            Move::R,
+            Move::U,
+            Move::Rp,
+            Move::Fp,
+            Move::R,
+            Move::U,
+            Move::Rp,
+            Move::Up,
+            Move::Rp,
+            Move::F,
+            Move::R,
+        ];
+
+        // Moves as a smart cube reports "M2 U R U' M2 U R' U'" for two M2 edge
+        // targets, followed by two Old Pochmann corner targets. Each M2 is sent
+        // as R2 and L2, and the cube is held rotated by x2 relative to its
+        // centers between the M2s, so the setup "U R U'" is reported as D R D'.
+        let mut solution = parse_move_string("R L R L D R D' R2 L2 U R' U'").unwrap();
+        solution.extend_from_slice(&[Move::D, Move::R]);
+        solution.extend_from_slice(&op_swap);
+        solution.extend_from_slice(&[Move::Rp, Move::Dp, Move::F]);
+        solution.extend_from_slice(&op_swap);
+        solution.push(Move::Fp);
+
+        let mut initial_state = Cube3x3x3::new();
+        initial_state.do_moves(&solution.inverse());
+        let solve = CubeWithSolution {
+            initial_state,
+            solution: solution
+                .iter()
+                .enumerate()
+                .map(|(i, mv)| TimedMove::new(*mv, 5000 + i as u32 * 200))
+                .collect(),
+        };
+
+        let blind = match Analysis::analyze_with_solve_type(&solve, SolveType::Blind3x3x3) {
+            Analysis::Blind(blind) => blind,
+            _ => panic!("solve not detected as blind"),
+        };
+        assert!(!matches!(
+            Analysis::analyze_with_solve_type(&solve, SolveType::Standard3x3x3),
+            Analysis::Blind(_)
+        ));
+        assert_eq!(blind.memo_time, 5000);
+        assert_eq!(blind.corner_targets(), 2);
+        assert_eq!(blind.edge_targets(), 2);
+        assert_eq!(blind.cycles[0].algorithm, BlindSwapAlgorithm::M2);
+        assert_eq!(blind.cycles[0].setup_moves, vec![]);
+        assert_eq!(blind.cycles[1].algorithm, BlindSwapAlgorithm::M2);
+        assert_eq!(
+            blind.cycles[1].setup_moves,
+            vec![Move::U, Move::R, Move::Up]
+        );
+        assert_eq!(
+            blind.cycles[1].moves,
+            parse_move_string("U R U' R2 L2 U R' U'").unwrap()
+        );
+        assert_eq!(blind.cycles[2].setup_moves, vec![Move::D, Move::R]);
+        assert_eq!(blind.cycles[3].setup_moves, vec![Move::F]);
+        assert_eq!(blind.other_move_count, 0);
+    }
+
+    #[test]
+    fn algorithm_execution() {
+        use crate::{AlgorithmExecution, AlgorithmHand};
+
+        // Left handed Sune with adjustments before and after
+        let moves = crate::parse_move_string("U U L' U' L U' L' U2 L U2").unwrap();
+        let execution = AlgorithmExecution::new(&moves, CubeFace::Top);
+        assert_eq!(execution.pre_auf, Some(Move::U2));
+        assert_eq!(execution.post_auf, Some(Move::U2));
+        assert_eq!(execution.algorithm.len(), 7);
+        assert_eq!(execution.hand, AlgorithmHand::Left);
+        assert_eq!(execution.to_string(), "(U2) L' U' L U' L' U2 L (U2)");
+        assert_eq!(execution.label("Sune"), "Sune (Left)");
+
+        // With yellow on top, the right hand turns the left face
+        let execution = AlgorithmExecution::new(&moves, CubeFace::Bottom);
+        assert_eq!(execution.pre_auf, None);
+        assert_eq!(execution.hand, AlgorithmHand::Right);
+    }
+
+    #[test]
+    fn custom_analysis() {
+        use crate::{
+            CubeWithSolution, CustomAnalysis, CustomMethod, CustomStep, StepPredicate, TimedMove,
+        };
+
+        let mut cube = Cube3x3x3::new();
+        cube.do_move(Move::R);
+        assert!(StepPredicate::any_f2l().is_met(&cube));
+        cube.do_move(Move::U);
+        assert!(StepPredicate::any_2x2x2_block().is_met(&cube));
+        assert!(StepPredicate::any_2x2x3_block().is_met(&cube));
+        assert!(!StepPredicate::any_f2l_minus_one().is_met(&cube));
+
+        let method = CustomMethod {
+            name: "Test".into(),
+            steps: vec![
+                CustomStep::new("Cross", "Cross", StepPredicate::cross(CubeFace::Bottom)),
+                CustomStep::new("Rest", "Rest", StepPredicate::Solved),
+            ],
+        };
+        let solve = CubeWithSolution {
+            initial_state: cube,
+            solution: vec![TimedMove::new(Move::Up, 500), TimedMove::new(Move::Rp, 800)],
+        };
+        let analysis = CustomAnalysis::analyze(&method, &solve).unwrap();
+        assert!(analysis.is_complete(&method));
+        assert_eq!(analysis.steps[0].moves, vec![Move::Up, Move::Rp]);
+        assert_eq!(analysis.steps[0].execution_time, 800);
+        assert_eq!(analysis.steps[1].moves.len(), 0);
+    }
+
+    #[test]
+    fn guided_solve() {
+        let mut rng = SimpleSeededRandomSource::new();
+        let mut guided = GuidedSolve::new(Cube3x3x3::sourced_random(&mut rng));
+
+        // A wrong move forces a new solution
+        let expected = guided.next_move().unwrap();
+        let wrong = if expected.face() == CubeFace::Top {
+            Move::D
+        } else {
+            Move::U
+        };
+        assert_eq!(guided.do_move(wrong), GuidedSolveProgress::Recomputed);
+        assert_eq!(guided.recompute_count(), 1);
+
+        // Follow the solution, performing half turns as two quarter turns the
+        // way a smart cube reports them
+        let mut moves = 0;
+        while let Some(mv) = guided.next_move() {
+            let mv = Move::from_face_and_rotation(mv.face(), mv.rotation().signum()).unwrap();
+            let progress = guided.do_move(mv);
+            moves += 1;
+            assert!(moves < 100, "guided solve did not finish");
+            if guided.is_solved() {
+                assert_eq!(progress, GuidedSolveProgress::Solved);
+            } else {
+                assert_eq!(progress, GuidedSolveProgress::OnTrack);
+            }
+        }
+        assert!(guided.is_solved());
+        assert_eq!(guided.recompute_count(), 1);
+    }
+
+    #[cfg(any(feature = "bluetooth", feature = "fuzzing"))]
+    #[test]
+    fn moyu_turn_packets() {
+        use crate::packet::{decode_moyu_turns, MoYuMoveTracker};
+
+        // Recorded turn notifications: a clockwise U reported in three steps,
+        // then a counterclockwise U reported in two steps
+        let packets: &[&[u8]] = &[
+            &[
+                3, 0x01, 0x00, 0x00, 0x00, 5, 108, 0x01, 0x00, 0x00, 0x80, 5, 108, 0x02, 0x00,
+                0x00, 0x00, 5, 108,
+            ],
+            &[
+                2, 0x02, 0x00, 0x00, 0x40, 5, 0x94, 0x02, 0x00, 0x00, 0x80, 5, 0x94,
+            ],
+        ];
+
+        let turns = decode_moyu_turns(packets[0]).unwrap();
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[1].timestamp, 1.5);
+        assert_eq!(turns[1].face, CubeFace::Top);
+        assert_eq!(turns[1].direction, 3);
+
+        let mut tracker = MoYuMoveTracker::new();
+        let moves: Vec<(Move, u32)> = packets
+            .iter()
+            .flat_map(|packet| decode_moyu_turns(packet).unwrap())
+            .filter_map(|turn| tracker.turn(&turn))
+            .map(|mv| (mv.move_(), mv.time()))
+            .collect();
+        assert_eq!(moves, vec![(Move::U, 0), (Move::Up, 1000)]);
+
+        // Malformed packets are errors instead of panics
+        assert!(decode_moyu_turns(&[]).is_err());
+        assert!(decode_moyu_turns(&[2, 0, 0, 0, 0, 5, 36]).is_err());
+        assert!(decode_moyu_turns(&[1, 0, 0, 0, 0, 6, 36]).is_err());
+    }
+
+    #[cfg(feature = "bluetooth")]
+    #[test]
+    fn gan_timer_packets() {
+        use crate::bluetooth::{
+            connect_cube, BluetoothCubeEvent, BluetoothCubeType, MockPeripheral,
+        };
+        use crate::packet::{decode_gan_timer_message, GanTimerMessage};
+        use std::sync::{Arc, Mutex};
+
+        const STOPPED: &[u8] = &[0xfe, 0x08, 0x01, 0x04, 0x01, 0x02, 0x59, 0x01, 0x64, 0x7e];
+        const HANDS_ON: &[u8] = &[0xfe, 0x04, 0x01, 0x06, 0xf8, 0x4e];
+        assert_eq!(
+            decode_gan_timer_message(STOPPED).unwrap(),
+            GanTimerMessage::Stopped(62345)
+        );
+        assert_eq!(
+            decode_gan_timer_message(HANDS_ON).unwrap(),
+            GanTimerMessage::HandsOn
+        );
+        assert!(decode_gan_timer_message(&[0xfe, 0x04, 0x01, 0x06, 0xf8, 0x4f]).is_err());
+        assert!(decode_gan_timer_message(&STOPPED[1..]).is_err());
+
+        // Halo timers only have the timer characteristics
+        const STATE: &str = "0000fff5-0000-1000-8000-00805f9b34fb";
+        let device = MockPeripheral::new(&[STATE, "0000fff2-0000-1000-8000-00805f9b34fb"]);
+        let times = Arc::new(Mutex::new(Vec::new()));
+        let times_copy = times.clone();
+        let timer = connect_cube(
+            BluetoothCubeType::GAN,
+            device.clone(),
+            Box::new(move |event| {
+                if let BluetoothCubeEvent::TimerFinished(time) = event {
+                    times_copy.lock().unwrap().push(time);
+                }
+            }),
+        )
+        .unwrap();
+        assert!(timer.timer_only());
+        assert_eq!(timer.device_info().firmware_version, None);
+        device.notify(STATE, HANDS_ON);
+        device.notify(STATE, &STOPPED[..9]);
+        device.notify(STATE, STOPPED);
+        assert_eq!(*times.lock().unwrap(), vec![62345]);
+    }
+
+    #[cfg(any(feature = "bluetooth", feature = "fuzzing"))]
+    #[test]
+    fn move_timeline() {
+        use crate::timeline::{DeviceClock, MoveTimeline};
+        use crate::TimedMove;
+        use std::time::Instant;
+
+        // 16-bit millisecond clock that wraps, with a packet arriving late
+        let mut clock = DeviceClock::new(16, 1000);
+        assert_eq!(clock.elapsed(65000), 0);
+        assert_eq!(clock.elapsed(200), 736);
+        assert_eq!(clock.elapsed(100), 0);
+        assert_eq!(clock.elapsed(300), 100);
+
+        // Fractions of a millisecond carry over
+        let mut clock = DeviceClock::new(32, 65536);
+        assert_eq!(clock.elapsed(0), 0);
+        assert_eq!(clock.elapsed(100), 1);
+        assert_eq!(clock.elapsed(200), 2);
+
+        // Cube clock running at twice real time
+        let start = Instant::now();
+        let mut timeline = MoveTimeline::new(2.0, (0.5, 2.0));
+        let moves = timeline.add_moves(&[TimedMove::new(Move::U, 0)], start);
+        assert_eq!(moves[0].time(), 0);
+        let moves = timeline.add_moves(
+            &[TimedMove::new(Move::R, 200)],
+            start + Duration::from_millis(100),
+        );
+        assert_eq!(moves[0].time(), 100);
+        assert_eq!(timeline.clock_ratio(), 2.0);
+
+        // Long pauses are measured with the host clock
+        let moves = timeline.add_moves(
+            &[TimedMove::new(Move::F, 5)],
+            start + Duration::from_millis(40100),
+        );
+        assert_eq!(moves[0].time(), 40000);
+
+        // Learned ratio stays within the range for the cube
+        let mut timeline = MoveTimeline::new(1.0, (0.98, 1.02));
+        timeline.add_moves(&[TimedMove::new(Move::U, 0)], start);
+        timeline.add_moves(
+            &[TimedMove::new(Move::R, 200)],
+            start + Duration::from_millis(100),
+        );
+        assert_eq!(timeline.clock_ratio(), 1.02);
+    }
+
+    #[cfg(feature = "bluetooth")]
+    #[test]
+    fn bluetooth_version_dispatch() {
+        use crate::bluetooth::{connect_cube, BluetoothCubeType, MockPeripheral};
+
+        const GAN_V1: &[&str] = &[
+            "00002a28-0000-1000-8000-00805f9b34fb",
+            "00002a23-0000-1000-8000-00805f9b34fb",
+            "0000fff2-0000-1000-8000-00805f9b34fb",
+            "0000fff5-0000-1000-8000-00805f9b34fb",
+            "0000fff6-0000-1000-8000-00805f9b34fb",
+            "0000fff7-0000-1000-8000-00805f9b34fb",
+        ];
+
+        // GAN version 2.0 devices are smart timers
+        let device = MockPeripheral::new(GAN_V1);
+        device.queue_read(GAN_V1[0], &[2, 0, 0]);
+        let cube = connect_cube(BluetoothCubeType::GAN, device.clone(), Box::new(|_| ())).unwrap();
+        assert!(cube.timer_only());
+        assert!(cube.device_info().features.timer);
+        assert_eq!(
+            cube.device_info().firmware_version.as_deref(),
+            Some("2.0.0")
+        );
+        assert_eq!(device.subscriptions().len(), 1);
+        cube.disconnect();
+        assert!(device.disconnected());
+
+        let device = MockPeripheral::new(GAN_V1);
+        device.queue_read(GAN_V1[0], &[3, 0, 0]);
+        assert!(connect_cube(BluetoothCubeType::GAN, device, Box::new(|_| ())).is_err());
+        assert!(connect_cube(
+            BluetoothCubeType::GAN,
+            MockPeripheral::new(&[]),
+            Box::new(|_| ())
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "bluetooth")]
+    #[test]
+    fn bluetooth_moyu_moves() {
+        use crate::bluetooth::{
+            connect_cube, BluetoothCubeEvent, BluetoothCubeType, MockPeripheral,
+        };
+        use std::sync::{Arc, Mutex};
+
+        const TURN: &str = "00001003-0000-1000-8000-00805f9b34fb";
+        let device = MockPeripheral::new(&[
+            TURN,
+            "00001004-0000-1000-8000-00805f9b34fb",
+            "00001002-0000-1000-8000-00805f9b34fb",
+        ]);
+        let moves = Arc::new(Mutex::new(Vec::new()));
+        let moves_copy = moves.clone();
+        let cube = connect_cube(
+            BluetoothCubeType::MoYu,
+            device.clone(),
+            Box::new(move |event| {
+                if let BluetoothCubeEvent::Move(timed_moves, _) = event {
+                    moves_copy
+                        .lock()
+                        .unwrap()
+                        .extend(timed_moves.iter().map(|mv| mv.move_()));
+                }
+            }),
+        )
+        .unwrap();
+        assert_eq!(device.subscriptions().len(), 3);
+
+        // A clockwise R turn reported in two steps
+        device.notify(TURN, &[2, 0, 0, 0, 0, 3, 108, 0, 0, 0, 0, 3, 108]);
+        assert_eq!(*moves.lock().unwrap(), vec![Move::R]);
+        let mut expected = Cube3x3x3::new();
+        expected.do_move(Move::R);
+        assert_eq!(cube.cube_state(), expected);
+
+        // Malformed packets desync the cube
+        assert!(cube.synced());
+        device.notify(TURN, &[1]);
+        assert!(!cube.synced());
+    }
+
+    #[test]
+    fn action_format_versions() {
+        use crate::action::ACTION_FORMAT_VERSION;
+        use crate::action_generated;
+        use crate::{Action, StoredAction};
+
+        let actions = vec![StoredAction::new(Action::RenameSession(
+            "test".into(),
+            Some("Name".into()),
+        ))];
+        let data = StoredAction::serialize_list(&actions);
+        let (loaded, version) = StoredAction::deserialize_versioned_list(&data).unwrap();
+        assert_eq!(version, ACTION_FORMAT_VERSION);
+        assert_eq!(loaded[0].id, actions[0].id);
+
+        let list_with_version = |version| {
+            let mut builder = flatbuffers::FlatBufferBuilder::new();
+            let actions =
+                builder.create_vector::<flatbuffers::WIPOffset<action_generated::Action>>(&[]);
+            let list = action_generated::ActionList::create(
+                &mut builder,
+                &action_generated::ActionListArgs {
+                    actions: Some(actions),
+                    version,
+                },
+            );
+            builder.finish(list, None);
+            builder.finished_data().to_vec()
+        };
+
+        // Lists written before versioning are upgraded, lists from newer versions
+        // are refused
+        let (loaded, version) =
+            StoredAction::deserialize_versioned_list(&list_with_version(0)).unwrap();
+        assert_eq!(version, 0);
+        assert!(loaded.is_empty());
+        assert!(
+            StoredAction::deserialize_list(&list_with_version(ACTION_FORMAT_VERSION + 1)).is_err()
+        );
+    }
+
+    #[test]
+    fn official_results() {
+        use crate::{OfficialResults, SolveType};
+
+        let person = r#"{
+            "person": {"wca_id": "2020TEST01", "name": "Test Person"},
+            "personal_records": {
+                "333": {"single": {"best": 912}, "average": {"best": 1103}},
+                "333bf": {"single": {"best": 6150}},
+                "333fm": {"single": {"best": 28}, "average": {"best": 3133}}
+            }
+        }"#;
+        let results = OfficialResults::parse(person).unwrap();
+        assert_eq!(results.wca_id.as_deref(), Some("2020TEST01"));
+        assert_eq!(results.records.len(), 2);
+        let record = results.record(SolveType::Standard3x3x3).unwrap();
+        assert_eq!(record.single, Some(9120));
+        assert_eq!(record.average, Some(11030));
+        let record = results.record(SolveType::Blind3x3x3).unwrap();
+        assert_eq!(record.average, None);
+        assert!(results.record(SolveType::OneHanded3x3x3).is_none());
+
+        // Result lists keep the best of each event, ignoring DNFs
+        let list = r#"[
+            {"wca_id": "2020TEST01", "event_id": "222", "best": 350, "average": -1},
+            {"wca_id": "2020TEST01", "event_id": "222", "best": 412, "average": 520}
+        ]"#;
+        let results = OfficialResults::parse(list).unwrap();
+        let record = results.record(SolveType::Standard2x2x2).unwrap();
+        assert_eq!(record.single, Some(3500));
+        assert_eq!(record.average, Some(5200));
+
+        assert!(OfficialResults::parse("42").is_err());
+    }
+
+    #[test]
+    fn sync_excluded_sessions() {
+        use crate::{Action, StoredAction, SyncRequest};
+        use std::collections::HashSet;
+
+        let mut private = solve(10000, Penalty::None);
+        private.session = "private".into();
+        let public = solve(12000, Penalty::None);
+        let private_id = private.id.clone();
+        let actions = vec![
+            StoredAction::new(Action::NewSolve(private)),
+            StoredAction::new(Action::NewSolve(public)),
+            StoredAction::new(Action::Penalty(private_id.clone(), Penalty::DNF)),
+            StoredAction::new(Action::RenameSession("private".into(), None)),
+            StoredAction::new(Action::SetPreference("theme".into(), "dark".into())),
+        ];
+        let solve_session = |id: &str| {
+            if id == private_id {
+                Some("private".to_string())
+            } else {
+                Some("test".to_string())
+            }
+        };
+
+        let request = SyncRequest::upload_excluding(
+            String::new(),
+            0,
+            &actions,
+            &HashSet::new(),
+            solve_session,
+        );
+        assert_eq!(request.upload.unwrap().len(), 5);
+
+        let excluded: HashSet<String> = ["private".to_string()].iter().cloned().collect();
+        let request =
+            SyncRequest::upload_excluding(String::new(), 0, &actions, &excluded, solve_session);
+        let upload = request.upload.unwrap();
+        assert_eq!(upload.len(), 2);
+        assert_eq!(upload[0].id, actions[1].id);
+        assert_eq!(upload[1].id, actions[4].id);
+
+        let request = SyncRequest::upload_excluding(
+            String::new(),
+            0,
+            &actions[..1],
+            &excluded,
+            solve_session,
+        );
+        assert!(request.upload.is_none());
+    }
+
+    fn average(times: &[Option<u32>]) -> Option<u32> {
+        times.average()
+    }
+
+    fn solve(time: u32, penalty: Penalty) -> Solve {
+        Solve {
+            id: Solve::new_id(),
+            solve_type: SolveType::Standard3x3x3,
+            session: "test".into(),
+            scramble: Vec::new(),
+            created: Local::now(),
+            time,
+            penalty,
+            device: None,
+            participant: None,
+            moves: None,
+            skips: None,
+        }
+    }
+
+    #[test]
+    fn average_trim_counts() {
+        assert_eq!(average_trim_count(1), 0);
+        assert_eq!(average_trim_count(3), 0);
+        assert_eq!(average_trim_count(4), 0);
+        assert_eq!(average_trim_count(5), 1);
+        assert_eq!(average_trim_count(12), 1);
+        assert_eq!(average_trim_count(20), 1);
+        assert_eq!(average_trim_count(21), 2);
+        assert_eq!(average_trim_count(50), 3);
+        assert_eq!(average_trim_count(100), 5);
+        assert_eq!(average_trim_count(1000), 50);
+    }
+
+    #[test]
+    fn mean_of_3() {
+        assert_eq!(average(&[]), None);
+        assert_eq!(
+            average(&[Some(10000), Some(11000), Some(12500)]),
+            Some(11167)
+        );
+        assert_eq!(average(&[Some(10000), None, Some(12500)]), None);
+    }
+
+    #[test]
+    fn average_of_5() {
+        // Best and worst are removed
+        assert_eq!(
+            average(&[
+                Some(12340),
+                Some(10000),
+                Some(15670),
+                Some(11110),
+                Some(13000)
+            ]),
+            Some(12150)
+        );
+
+        // A single DNF is the worst solve and is removed
+        assert_eq!(
+            average(&[Some(12340), Some(10000), None, Some(11110), Some(13000)]),
+            Some(12150)
+        );
+
+        // Two DNFs make the average a DNF
+        assert_eq!(
+            average(&[Some(12340), None, None, Some(11110), Some(13000)]),
+            None
+        );
+        assert_eq!(average(&[None, None, None, None, None]), None);
+
+        // Equal times
+        assert_eq!(
+            average(&[Some(9990), Some(9990), Some(9990), Some(9990), Some(9990)]),
+            Some(9990)
+        );
+    }
+
+    #[test]
+    fn average_of_12() {
+        let mut times: Vec<Option<u32>> = (0..12).map(|i| Some(10000 + i * 1000)).collect();
+        // 10 through 21 seconds, removing 10 and 21 leaves a mean of 15.5
+        assert_eq!(average(&times), Some(15500));
+
+        times[3] = None;
+        // 13 is now a DNF and removed as the worst, 10 removed as the best
+        assert_eq!(average(&times), Some(16300));
+
+        times[7] = None;
+        assert_eq!(average(&times), None);
+    }
+
+    #[test]
+    fn average_of_50_and_100() {
+        let mut times: Vec<Option<u32>> = (0..50).map(|i| Some(10000 + i * 100)).collect();
+        // Removing 3 from each end leaves 10.3 through 14.6 seconds
+        assert_eq!(average(&times), Some(12450));
+
+        // Up to 3 DNFs are trimmed
+        times[0] = None;
+        times[1] = None;
+        times[2] = None;
+        assert_eq!(average(&times), Some(12750));
+        times[3] = None;
+        assert_eq!(average(&times), None);
+
+        let mut times: Vec<Option<u32>> = (0..100).map(|i| Some(10000 + i * 100)).collect();
+        // Removing 5 from each end leaves 10.5 through 19.4 seconds
+        assert_eq!(average(&times), Some(14950));
+        for i in 0..5 {
+            times[i * 20] = None;
+        }
+        assert_eq!(average(&times), Some(15261));
+        times[99] = None;
+        assert_eq!(average(&times), None);
+    }
+
+    #[test]
+    fn average_rounding() {
+        assert_eq!(
+            average(&[
+                Some(10001),
+                Some(10002),
+                Some(10002),
+                Some(10003),
+                Some(10004)
+            ]),
+            Some(10002)
+        );
+        assert_eq!(
+            average(&[
+                Some(10000),
+                Some(10001),
+                Some(10002),
+                Some(10005),
+                Some(10009)
+            ]),
+            Some(10003)
+        );
+    }
+
+    #[test]
+    fn average_with_penalties() {
+        let solves = vec![
+            solve(10000, Penalty::None),
+            solve(11000, Penalty::Time(2000)),
+            solve(12000, Penalty::None),
+            solve(9000, Penalty::DNF),
+            solve(8000, Penalty::None),
+        ];
+        // Final times are 10, 13, 12, DNF, 8. Removing 8 and the DNF leaves
+        // a mean of 11.67.
+        assert_eq!(solves.as_slice().average(), Some(11667));
+        assert_eq!(
+            solves
+                .as_slice()
+                .last_average(5)
+                .map(|average| average.time),
+            Some(11667)
+        );
+        assert_eq!(solves.as_slice().best().map(|best| best.time), Some(8000));
+
+        let mut solves = solves;
+        solves[0].penalty = Penalty::DNF;
+        assert_eq!(solves.as_slice().average(), None);
+        assert!(solves.as_slice().best_average(5).is_none());
+    }
+
+    #[test]
+    fn attempt_results() {
+        // Singles are truncated and the average of 10.01, 12.34 and 11.11 is
+        // rounded to the hundredth
+        let result = AttemptFormat::AverageOf5
+            .result(&[Some(10019), None, Some(12345), Some(9999), Some(11111)])
+            .unwrap();
+        assert_eq!(result.best, Some(9990));
+        assert_eq!(result.average, Some(11150));
+        assert_eq!(result.ranked(), Some(11150));
+
+        let result = AttemptFormat::AverageOf5
+            .result(&[Some(10000), None, Some(12000), None, Some(11000)])
+            .unwrap();
+        assert_eq!(result.average, None);
+
+        let result = AttemptFormat::MeanOf3
+            .result(&[Some(10000), None, Some(12000)])
+            .unwrap();
+        assert_eq!(result.best, Some(10000));
+        assert_eq!(result.ranked(), None);
+
+        let result = AttemptFormat::BestOf3
+            .result(&[None, Some(12000), Some(11005)])
+            .unwrap();
+        assert_eq!(result.average, None);
+        assert_eq!(result.ranked(), Some(11000));
+
+        // Results over ten minutes are rounded to the nearest second
+        let result = AttemptFormat::MeanOf3
+            .result(&[Some(600400), Some(600500), Some(601000)])
+            .unwrap();
+        assert_eq!(result.best, Some(600000));
+        assert_eq!(result.average, Some(601000));
+
+        assert!(AttemptFormat::MeanOf3.result(&[Some(10000)]).is_none());
+    }
+
+    #[test]
+    fn incremental_rolling_averages() {
+        // Pseudo-random times with occasional DNFs and repeated values
+        let mut state: u32 = 12345;
+        let times: Vec<Option<u32>> = (0..400)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                let value = (state >> 8) % 20000;
+                if value % 23 == 0 {
+                    None
+                } else {
+                    Some(8000 + (value / 10) * 10)
+                }
+            })
+            .collect();
+
+        for size in &[1, 3, 5, 12, 50, 100] {
+            let rolling = rolling_averages(&times, *size);
+            for (idx, value) in rolling.iter().enumerate() {
+                let expected = if idx + 1 >= *size {
+                    average(&times[idx + 1 - size..=idx])
+                } else {
+                    None
+                };
+                assert_eq!(*value, expected, "ao{} mismatch at {}", size, idx);
+            }
+
+            // Best average must match a search over every window
+            let solves: Vec<Solve> = times
+                .iter()
+                .map(|time| match time {
+                    Some(time) => solve(*time, Penalty::None),
+                    None => solve(10000, Penalty::DNF),
+                })
+                .collect();
+            let expected = times
+                .windows(*size)
+                .enumerate()
+                .filter_map(|(idx, window)| average(window).map(|time| (time, idx)))
+                .fold(None, |best: Option<(u32, usize)>, (time, idx)| match best {
+                    Some((best_time, _)) if best_time <= time => best,
+                    _ => Some((time, idx)),
+                });
+            let best = solves.as_slice().best_average(*size);
+            assert_eq!(best.as_ref().map(|best| best.time), expected.map(|e| e.0));
+            if let (Some(best), Some((_, start))) = (best, expected) {
+                assert_eq!(best.solves[0].id, solves[start].id);
+                assert_eq!(best.solves.len(), *size);
+            }
+        }
+    }
+
+    #[test]
+    fn move_notation() {
+        use crate::{parse_move_string, MoveNotation, PrimeNotation, WideMoveNotation};
+
+        let moves = vec![Move::R, Move::Up, Move::Rwp, Move::Fw2];
+        assert_eq!(moves.to_string(), "R U' Rw' Fw2");
+        let alternate = MoveNotation {
+            wide: WideMoveNotation::Lowercase,
+            prime: PrimeNotation::Three,
+        };
+        let string = moves.to_string_with(alternate);
+        assert_eq!(string, "R U3 r3 f2");
+        assert_eq!(
+            moves.to_string_with(MoveNotation::default()),
+            moves.to_string()
+        );
+
+        // Every notation parses back to the same moves
+        assert_eq!(parse_move_string(&string).unwrap(), moves);
+        assert_eq!(parse_move_string("R U\u{2019} Rw3 f2").unwrap(), moves);
+        assert!(parse_move_string("R23").is_err());
+    }
+
+    #[test]
+    fn time_list() {
+        use crate::{parse_solve_time, parse_time_list};
+
+        assert_eq!(parse_solve_time("12.34").unwrap(), 12340);
+        assert_eq!(parse_solve_time("1:02.345").unwrap(), 62345);
+        assert_eq!(parse_solve_time("9").unwrap(), 9000);
+        assert!(parse_solve_time("1:75.00").is_err());
+        assert!(parse_solve_time("12.3456").is_err());
+
+        let times = parse_time_list("12.34, 15.02+\n(9.87) DNF DNF(20.1);11.00 +2").unwrap();
+        assert_eq!(
+            times,
+            vec![
+                (12340, Penalty::None),
+                (15020, Penalty::Time(2000)),
+                (9870, Penalty::None),
+                (0, Penalty::DNF),
+                (20100, Penalty::DNF),
+                (11000, Penalty::Time(2000)),
+            ]
+        );
+        assert!(parse_time_list("+2 12.34").is_err());
+        assert!(parse_time_list("12.34 abc").is_err());
+    }
+
+    #[test]
+    fn retry_after() {
+        use crate::RetryAfter;
+
+        assert_eq!(RetryAfter::from_header(Some("120")).delay_ms, 120_000);
+        assert_eq!(RetryAfter::from_header(Some(" 5 ")).delay_ms, 5_000);
+        assert_eq!(RetryAfter::from_header(Some("999999")).delay_ms, 3_600_000);
+        assert_eq!(
+            RetryAfter::from_header(Some("Wed, 21 Oct 2015 07:28:00 GMT")).delay_ms,
+            30_000
+        );
+        assert_eq!(RetryAfter::from_header(None).delay_ms, 30_000);
+    }
+
+    #[test]
+    fn solve_input() {
+        use crate::SolveInput;
+
+        let cube = SolveInput::SmartCube {
+            id: "AB:CD:EF".into(),
+            name: "GAN|356i".into(),
+        };
+        assert_eq!(
+            SolveInput::from_device_string(&cube.to_device_string()),
+            cube
+        );
+        assert_eq!(
+            SolveInput::from_device_string("keyboard"),
+            SolveInput::Keyboard
+        );
+        assert_eq!(
+            SolveInput::from_device_string("GAN-1234"),
+            SolveInput::Named("GAN-1234".into())
+        );
+
+        // Devices are matched by ID even if they were renamed
+        let renamed = SolveInput::SmartCube {
+            id: "AB:CD:EF".into(),
+            name: "Main cube".into(),
+        };
+        assert!(cube.same_source(&renamed));
+        assert!(!cube.same_source(&SolveInput::Keyboard));
+    }
+
+    #[test]
+    fn analysis_warnings() {
+        use crate::{Analysis, AnalysisWarning, CubeWithSolution, TimedMove};
+
+        let mut initial_state = Cube3x3x3::new();
+        initial_state.do_move(Move::R);
+        let solve = CubeWithSolution {
+            initial_state: initial_state.clone(),
+            solution: vec![
+                TimedMove::new(Move::Rp, 100),
+                TimedMove::new(Move::U, 200),
+                TimedMove::new(Move::Up, 300),
+            ],
+        };
+        assert!(Analysis::analyze(&solve)
+            .warnings(&solve)
+            .contains(&AnalysisWarning::MovesAfterSolved(2)));
+
+        let solve = CubeWithSolution {
+            initial_state,
+            solution: vec![TimedMove::new(Move::U, 100)],
+        };
+        let warnings = Analysis::analyze(&solve).warnings(&solve);
+        assert!(warnings.contains(&AnalysisWarning::NotSolved));
+    }
+
+    #[test]
+    fn reconstruction_text() {
+        use crate::TimedMove;
+
+        let solve = Solve {
+            id: Solve::new_id(),
+            solve_type: SolveType::Standard3x3x3,
+            session: String::new(),
+            scramble: vec![Move::R, Move::U],
+            created: Local::now(),
+            time: 1000,
+            penalty: Penalty::None,
+            device: None,
+            participant: None,
+            moves: Some(vec![
+                TimedMove::new(Move::Up, 400),
+                TimedMove::new(Move::Rp, 1000),
+            ]),
+            skips: None,
+        };
+        let text = solve.reconstruction_text().unwrap();
+        assert!(text.starts_with("R U // Scramble\n\n"));
+        assert!(text.ends_with("// 1.00s, 2 moves, 2.00 TPS"));
+    }
+
+    #[test]
+    fn fingertricks() {
+        use crate::{FingertrickAnalysis, TimedMove, Trigger};
+
+        // Sune performed with the last layer on the bottom face, with a pause
+        // before the second trigger
+        let moves = [
+            Move::L,
+            Move::D,
+            Move::Lp,
+            Move::D,
+            Move::L,
+            Move::D2,
+            Move::Lp,
+        ];
+        let mut timed = Vec::new();
+        for (idx, mv) in moves.iter().enumerate() {
+            let pause = if idx == 4 { 600 } else { 0 };
+            timed.push(TimedMove::new(*mv, idx as u32 * 100 + pause));
+        }
+        let analysis = FingertrickAnalysis::analyze(&timed, CubeFace::Bottom, 0);
+        let triggers: Vec<Trigger> = analysis.triggers.iter().map(|t| t.trigger).collect();
+        assert_eq!(triggers, vec![Trigger::RightInsert, Trigger::RightDouble]);
+        assert_eq!(analysis.regrips.len(), 1);
+        assert_eq!(analysis.regrips[0].move_index, 4);
+    }
+
+    #[test]
+    fn tps_curve() {
+        use crate::{TPSCurve, TimedMove};
+
+        let solution: Vec<TimedMove> = (1..=20)
+            .map(|idx| TimedMove::new(Move::U, idx * 250))
+            .collect();
+        let curve = TPSCurve::new(&solution, &[]);
+        assert_eq!(curve.samples.len(), 20);
+        assert!((curve.samples[10].instantaneous - 4.0).abs() < 0.01);
+        assert!((curve.samples[10].rolling - 4.0).abs() < 0.01);
+        assert_eq!(curve.steps.len(), 0);
+    }
+
+    #[test]
+    fn warmup() {
+        use crate::detect_warmup;
+
+        let mut times = vec![Some(14000), None, Some(12500), Some(10100), Some(11000)];
+        times.extend(
+            [10000, 10500, 9800, 10200, 9900, 10300, 10100]
+                .iter()
+                .map(|t| Some(*t)),
+        );
+        assert_eq!(detect_warmup(&times), 3);
+
+        // Too few solves to establish a pace
+        assert_eq!(detect_warmup(&times[..10]), 0);
+
+        // No slow solves at the start
+        let steady: Vec<Option<u32>> = times[5..].iter().chain(&times[5..]).cloned().collect();
+        assert_eq!(detect_warmup(&steady), 0);
+    }
+
+    #[test]
+    fn scramble_orientation() {
+        use crate::{parse_move_string, ScrambleOrientation};
+
+        let scramble = parse_move_string("R U F' D2 Lw").unwrap();
+        assert_eq!(ScrambleOrientation::default().apply(&scramble), scramble);
+
+        // Yellow on top is a z2 rotation, so left and right are also swapped
+        let flipped = ScrambleOrientation::new(Color::Yellow, Color::Green).unwrap();
+        assert_eq!(
+            flipped.apply(&scramble),
+            parse_move_string("L D F' U2 Rw").unwrap()
+        );
+
+        let all = ScrambleOrientation::all();
+        assert_eq!(all.len(), 24);
+        for orientation in all {
+            assert_eq!(orientation.face(orientation.top()), CubeFace::Top);
+            assert_eq!(orientation.face(orientation.front()), CubeFace::Front);
+            assert_eq!(
+                ScrambleOrientation::from_str(&orientation.to_str()),
+                Some(orientation)
+            );
+        }
+        assert!(ScrambleOrientation::new(Color::White, Color::Yellow).is_none());
+    }
+
+    #[test]
+    fn scramble_file() {
+        use crate::{ScrambleFile, ScrambleProvider};
+
+        let contents = "# Round 1\n1. R U R' U'\n2) F2 D\n\nL B'\n";
+        let mut file =
+            ScrambleFile::parse("Round 1".into(), SolveType::Standard3x3x3, contents).unwrap();
+        assert_eq!(file.remaining(SolveType::Standard3x3x3), Some(3));
+        assert_eq!(file.next_scramble(SolveType::Standard2x2x2), None);
+        assert_eq!(
+            file.next_scramble(SolveType::Standard3x3x3),
+            Some(vec![Move::R, Move::U, Move::Rp, Move::Up])
+        );
+        assert_eq!(
+            file.next_scramble(SolveType::Standard3x3x3),
+            Some(vec![Move::F2, Move::D])
+        );
+        assert_eq!(
+            file.next_scramble(SolveType::Standard3x3x3),
+            Some(vec![Move::L, Move::Bp])
+        );
+        assert_eq!(file.next_scramble(SolveType::Standard3x3x3), None);
+
+        assert!(ScrambleFile::parse("".into(), SolveType::Standard3x3x3, "1. R X").is_err());
+    }
+
+    #[cfg(feature = "native-storage")]
+    #[tokio::test]
+    async fn in_memory_history() {
+        use crate::History;
+
+        let mut history = History::open_in_memory().await.unwrap();
+        assert!(history.is_in_memory());
+        let solve = solve(12000, Penalty::None);
+        let id = solve.id.clone();
+        history.new_solve(solve);
+        assert_eq!(history.solve(&id).map(|solve| solve.time), Some(12000));
+
+        // Guest sessions never sync
+        assert!(!history.start_sync());
+        assert!(!history.sync_in_progress());
+
+        // A new in-memory history starts empty
+        let other = History::open_in_memory().await.unwrap();
+        assert!(other.solve(&id).is_none());
+    }
+
+    #[cfg(feature = "native-storage")]
+    #[tokio::test]
+    async fn solve_hooks() {
+        use crate::History;
+        use std::sync::{Arc, Mutex};
+
+        let mut history = History::open_in_memory().await.unwrap();
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let reported_copy = reported.clone();
+        let handle = history.register_solve_hook(move |completion| {
+            reported_copy.lock().unwrap().push(completion.to_json());
+        });
+
+        let session = history.current_session().to_string();
+        for time in &[10000, 11000, 12000, 13000, 14000] {
+            let mut solve = solve(*time, Penalty::None);
+            solve.session = session.clone();
+            history.new_solve(solve);
+        }
+        let solves: Vec<serde_json::Value> = reported
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|json| serde_json::from_str(json).unwrap())
+            .collect();
+        assert_eq!(solves.len(), 5);
+        assert_eq!(solves[4]["time"], 14000);
+        assert_eq!(solves[4]["session_solve_count"], 5);
+        assert_eq!(solves[4]["ao5"], 12000);
+        assert_eq!(solves[4]["best"], 10000);
+        assert!(solves[3]["ao5"].is_null());
+
+        history.unregister_solve_hook(handle);
+        history.new_solve(solve(15000, Penalty::None));
+        assert_eq!(reported.lock().unwrap().len(), 5);
+    }
+
+    #[cfg(feature = "native-storage")]
+    #[tokio::test]
+    async fn session_participants() {
+        use crate::{Action, History, StoredAction, SyncResponse};
+
+        // Participant actions and attributed solves are stored
+        let mut attributed = solve(10000, Penalty::None);
+        attributed.participant = Some("first".into());
+        let actions = vec![
+            StoredAction::new(Action::AddParticipant(
+                "test".into(),
+                "first".into(),
+                "First".into(),
+            )),
+            StoredAction::new(Action::RemoveParticipant("test".into(), "first".into())),
+            StoredAction::new(Action::ChangeParticipant("solve".into(), None)),
+            StoredAction::new(Action::NewSolve(attributed)),
+        ];
+        let loaded =
+            StoredAction::deserialize_list(&StoredAction::serialize_list(&actions)).unwrap();
+        assert_eq!(loaded.len(), 4);
+        assert!(matches!(&loaded[0].action,
+            Action::AddParticipant(session, id, name)
+                if session == "test" && id == "first" && name == "First"));
+        assert!(matches!(&loaded[1].action,
+            Action::RemoveParticipant(session, id) if session == "test" && id == "first"));
+        assert!(matches!(&loaded[2].action,
+            Action::ChangeParticipant(solve, None) if solve == "solve"));
+        assert!(matches!(&loaded[3].action,
+            Action::NewSolve(solve) if solve.participant.as_deref() == Some("first")));
+
+        // Turns alternate between participants in the order they were added
+        let mut history = History::open_in_memory().await.unwrap();
+        let session = history.current_session().to_string();
+        assert_eq!(history.next_participant(), None);
+        let first = history.add_participant(session.clone(), "First".into());
+        let second = history.add_participant(session.clone(), "Second".into());
+        let mut solve_ids = Vec::new();
+        for time in &[10000, 12000, 11000] {
+            let mut solve = solve(*time, Penalty::None);
+            solve.session = session.clone();
+            solve.participant = history.next_participant();
+            solve_ids.push(solve.id.clone());
+            history.new_solve(solve);
+        }
+        assert_eq!(history.participant_solves(&session, &first).len(), 2);
+        assert_eq!(history.participant_solves(&session, &second).len(), 1);
+        assert_eq!(history.next_participant(), Some(second.clone()));
+
+        history.change_participant(solve_ids[2].clone(), Some(second.clone()));
+        history.remove_participant(session.clone(), first.clone());
+        assert_eq!(history.participant_solves(&session, &second).len(), 2);
+        assert_eq!(history.participants(&session).len(), 1);
+        assert_eq!(history.next_participant(), Some(second.clone()));
+
+        // Other devices resolve the same participants after a sync
+        let request = history.sync_request();
+        let upload = request.upload.clone().unwrap();
+        let mut other = History::open_in_memory().await.unwrap();
+        let other_request = other.sync_request();
+        other.resolve_sync(
+            &other_request,
+            &SyncResponse {
+                new_sync_id: 1,
+                new_actions: upload.clone(),
+                more_actions: false,
+                uploaded: 0,
+            },
+        );
+        let participants = other.participants(&session);
+        assert_eq!(participants.len(), 1);
+        assert_eq!(participants[0].id(), second);
+        assert_eq!(participants[0].name(), "Second");
+        assert_eq!(
+            other.solve(&solve_ids[2]).unwrap().participant.as_deref(),
+            Some(second.as_str())
+        );
+
+        // Uploaded actions keep the same state on the device that made them
+        history.resolve_sync(
+            &request,
+            &SyncResponse {
+                new_sync_id: 1,
+                new_actions: Vec::new(),
+                more_actions: false,
+                uploaded: upload.len(),
+            },
+        );
+        assert_eq!(history.local_action_count(), 0);
+        assert_eq!(history.participants(&session).len(), 1);
+        assert_eq!(history.participant_solves(&session, &second).len(), 2);
+    }
+
+    #[cfg(feature = "native-storage")]
+    #[tokio::test]
+    async fn stored_solve_skips() {
+        use crate::{Action, CFOPSkips, History, SkippedStep, SolveQuery, StoredAction};
+
+        // Skips are stored with the solve, and solves without them load as before
+        let skips = CFOPSkips {
+            f2l_pairs: 1,
+            oll: true,
+            pll: false,
+        };
+        let mut skipped = solve(10000, Penalty::None);
+        skipped.skips = Some(skips);
+        let actions = vec![
+            StoredAction::new(Action::NewSolve(skipped.clone())),
+            StoredAction::new(Action::NewSolve(solve(12000, Penalty::None))),
+        ];
+        let loaded =
+            StoredAction::deserialize_list(&StoredAction::serialize_list(&actions)).unwrap();
+        assert!(matches!(&loaded[0].action,
+            Action::NewSolve(solve) if solve.skips == Some(skips)));
+        assert!(matches!(&loaded[1].action, Action::NewSolve(solve) if solve.skips.is_none()));
+
+        // Queries use the stored skips
+        let mut history = History::open_in_memory().await.unwrap();
+        history.new_solve(skipped.clone());
+        history.new_solve(solve(12000, Penalty::None));
+        let oll_skips = history.query(&SolveQuery::new().skipped(SkippedStep::OLL), 0, 10);
+        assert_eq!(oll_skips.len(), 1);
+        assert_eq!(oll_skips[0].id, skipped.id);
+        assert_eq!(
+            history.query_count(&SolveQuery::new().skipped(SkippedStep::PLL)),
+            0
+        );
+        assert_eq!(history.solve_skips(&skipped.id), Some(skips));
+    }
+
+    #[cfg(feature = "native-storage")]
+    #[tokio::test]
+    async fn api_requests() {
+        use crate::api::{ApiRequestHeaders, ApiServer};
+        use crate::History;
+
+        let mut history = History::open_in_memory().await.unwrap();
+        for time in &[10000, 11000, 12000, 13000, 14000] {
+            let body = format!("{{\"time\": {}, \"scramble\": \"R U R' U'\"}}", time);
+            let response = ApiServer::handle(&mut history, "POST", "/solves", &body);
+            assert_eq!(response.status, 200);
+        }
+        let response = ApiServer::handle(
+            &mut history,
+            "POST",
+            "/solves",
+            r#"{"type": "2x2x2", "time": 3000, "penalty": "dnf"}"#,
+        );
+        assert_eq!(response.status, 200);
+
+        let stats = ApiServer::handle(&mut history, "GET", "/stats?type=3x3x3", "").body;
+        assert_eq!(stats["solve_count"], 5);
+        assert_eq!(stats["ao5"], 12000);
+        assert_eq!(stats["best"], 10000);
+        let stats = ApiServer::handle(&mut history, "GET", "/stats?type=2x2x2", "").body;
+        assert_eq!(stats["solve_count"], 1);
+        assert!(stats["best"].is_null());
+
+        assert_eq!(
+            ApiServer::handle(&mut history, "POST", "/solves", r#"{"penalty": 2000}"#).status,
+            400
+        );
+        assert_eq!(
+            ApiServer::handle(&mut history, "GET", "/stats?type=3x3x3+OH", "").status,
+            200
+        );
+        assert_eq!(
+            ApiServer::handle(&mut history, "GET", "/stats?type=megaminx", "").status,
+            400
+        );
+        assert_eq!(
+            ApiServer::handle(&mut history, "GET", "/solves", "").status,
+            405
+        );
+        assert_eq!(
+            ApiServer::handle(&mut history, "GET", "/other", "").status,
+            404
+        );
+
+        // Requests from web pages and bodies that aren't JSON are rejected
+        // before the body is read
+        let headers = |content_type: Option<&str>, origin: Option<&str>| ApiRequestHeaders {
+            content_length: 16,
+            content_type: content_type.map(|value| value.into()),
+            origin: origin.map(|value| value.into()),
+        };
+        let json = Some("application/json; charset=utf-8");
+        assert!(ApiServer::check_headers("POST", &headers(json, None)).is_none());
+        assert_eq!(
+            ApiServer::check_headers("POST", &headers(json, Some("http://example.com")))
+                .map(|response| response.status),
+            Some(403)
+        );
+        assert_eq!(
+            ApiServer::check_headers("POST", &headers(Some("text/plain"), None))
+                .map(|response| response.status),
+            Some(415)
+        );
+        assert_eq!(
+            ApiServer::check_headers("POST", &headers(None, None)).map(|response| response.status),
+            Some(415)
+        );
+    }
+
+    #[test]
+    fn practice_streak() {
+        use crate::PracticeStreak;
+        use chrono::NaiveDate;
+
+        let day = |day| NaiveDate::from_ymd(2021, 6, day);
+        let dates = |days: &[(u32, usize)]| {
+            days.iter()
+                .flat_map(|(date, count)| std::iter::repeat(day(*date)).take(*count))
+                .collect::<Vec<_>>()
+        };
+
+        // Days below the goal break the streak
+        let solves = dates(&[(1, 5), (2, 5), (3, 5), (4, 2), (5, 5), (6, 5), (7, 3)]);
+        let streak = PracticeStreak::compute(solves.iter().cloned(), 5, day(7));
+        assert_eq!(streak.longest, 3);
+        assert_eq!(streak.current, 2);
+        assert_eq!(streak.today_solves, 3);
+        assert_eq!(streak.remaining_today(), 2);
+        assert!(streak.at_risk());
+
+        // Meeting the goal today extends the streak
+        let streak = PracticeStreak::compute(solves.iter().cloned(), 3, day(7));
+        assert_eq!(streak.current, 3);
+        assert!(streak.goal_met_today());
+        assert!(!streak.at_risk());
+
+        // A missed day ends the streak, and no goal means a single solve counts
+        let streak = PracticeStreak::compute(solves.iter().cloned(), 0, day(9));
+        assert_eq!(streak.current, 0);
+        assert_eq!(streak.longest, 7);
+        assert!(!streak.at_risk());
+    }
+
+    #[test]
+    fn similar_solve_profiles() {
+        use crate::{OLLAlgorithm, PLLAlgorithm, SimilarityCriteria, SolveProfile};
+
+        let profile = SolveProfile {
+            oll_case: Some(OLLAlgorithm::Sune),
+            pll_case: Some(PLLAlgorithm::Ua),
+            cross_moves: 6,
+            extended_pairs: 0,
+            step_fractions: vec![0.2, 0.5, 0.15, 0.15],
+        };
+        let other = SolveProfile {
+            oll_case: Some(OLLAlgorithm::Sune),
+            pll_case: None,
+            cross_moves: 7,
+            extended_pairs: 0,
+            step_fractions: vec![0.1, 0.6, 0.15, 0.15],
+        };
+        assert_eq!(
+            profile.distance(&other, SimilarityCriteria::OLLCase),
+            Some(0.0)
+        );
+        assert_eq!(profile.distance(&other, SimilarityCriteria::PLLCase), None);
+        assert_eq!(
+            profile.distance(&other, SimilarityCriteria::ScrambleDifficulty),
+            Some(1.0)
+        );
+        let distance = profile
+            .distance(&other, SimilarityCriteria::StepProfile)
+            .unwrap();
+        assert!((distance - 0.2).abs() < 0.001);
+
+        // An extended cross is not comparable to a plain cross
+        let xcross = SolveProfile {
+            extended_pairs: 1,
+            ..other.clone()
+        };
+        assert_eq!(
+            profile.distance(&xcross, SimilarityCriteria::ScrambleDifficulty),
+            None
+        );
+    }
+
+    #[cfg(feature = "storage")]
+    #[test]
+    fn case_schedule() {
+        use crate::{CaseReview, CaseScheduler, LastLayerCase, OLLAlgorithm, PLLAlgorithm};
+
+        let fast = CaseReview {
+            recognition_time: 500,
+            failed: false,
+        };
+        let slow = CaseReview {
+            recognition_time: 3000,
+            failed: false,
+        };
+        assert_eq!(fast.quality(), 5);
+        assert_eq!(slow.quality(), 2);
+
+        let cases = LastLayerCase::all_pll();
+        assert_eq!(cases.len(), 21);
+        assert_eq!(LastLayerCase::all_oll().len(), 57);
+
+        // Known cases move further out with each review, and a slow case
+        // comes right back
+        let mut scheduler = CaseScheduler::new();
+        let known = LastLayerCase::PLL(PLLAlgorithm::Ua);
+        let hard = LastLayerCase::PLL(PLLAlgorithm::Nb);
+        scheduler.review(known, fast);
+        scheduler.review(known, fast);
+        scheduler.review(known, fast);
+        scheduler.review(hard, slow);
+        assert!(scheduler.reviews_until_due(known) > 6);
+        assert_eq!(scheduler.reviews_until_due(hard), 1);
+        assert!(scheduler.schedule(hard).unwrap().ease < 2.5);
+        assert!(scheduler
+            .schedule(LastLayerCase::OLL(OLLAlgorithm::Sune))
+            .is_none());
+
+        // A case that is not due is rarely chosen
+        scheduler.review(LastLayerCase::OLL(OLLAlgorithm::Sune), fast);
+        assert_eq!(scheduler.reviews_until_due(hard), 0);
+        let mut rng = SimpleSeededRandomSource::new();
+        let known_count = (0..200)
+            .filter(|_| scheduler.next_case(&mut rng, &[known, hard]) == Some(known))
+            .count();
+        assert!(known_count < 50);
+    }
+
+    #[cfg(feature = "storage")]
+    #[test]
+    fn algorithm_sheet_import() {
+        use crate::{
+            AlgorithmConflict, AlgorithmDatabase, AlgorithmSheetFormat, LastLayerCase,
+            OLLAlgorithm, PLLAlgorithm,
+        };
+
+        assert!(LastLayerCase::from_name("Ua Perm") == Some(LastLayerCase::PLL(PLLAlgorithm::Ua)));
+        assert!(
+            LastLayerCase::from_name("OLL #27") == Some(LastLayerCase::OLL(OLLAlgorithm::Sune))
+        );
+        assert!(LastLayerCase::from_name("T") == Some(LastLayerCase::PLL(PLLAlgorithm::T)));
+        assert!(LastLayerCase::from_name("OLL T") == Some(LastLayerCase::OLL(OLLAlgorithm::T)));
+
+        let csv = "Case,Algorithm,Alternative\n\
+            Ua,\"R U' R U R U R U' R' U' R2\",\n\
+            Sune,R U R' U R U2 R',\"(R U2 R') U' R U' R'\"\n\
+            Xyz,R U\n\
+            Ub,R2 U M\n";
+        assert_eq!(AlgorithmSheetFormat::detect(csv), AlgorithmSheetFormat::CSV);
+        let mut database = AlgorithmDatabase::new();
+        let result = database.import(
+            csv,
+            AlgorithmSheetFormat::CSV,
+            AlgorithmConflict::KeepExisting,
+        );
+        assert_eq!(result.added, 3);
+        assert_eq!(result.errors.len(), 2);
+        let sune = LastLayerCase::OLL(OLLAlgorithm::Sune);
+        assert_eq!(database.algorithms(sune).len(), 2);
+        assert_eq!(database.algorithms(sune)[1].moves, "R U2 R' U' R U' R'");
+
+        // AlgDb style list with a conflicting algorithm and a duplicate
+        let text = "Ua Perm:\nR2 U R U R' U' R' U' R' U R'\n\
+            Sune: R U R' U R U2 R'\n";
+        assert_eq!(
+            AlgorithmSheetFormat::detect(text),
+            AlgorithmSheetFormat::Text
+        );
+        let result = database.import(
+            text,
+            AlgorithmSheetFormat::Text,
+            AlgorithmConflict::KeepExisting,
+        );
+        assert_eq!(
+            (result.added, result.conflicts, result.duplicates),
+            (0, 1, 1)
+        );
+        let result = database.import(text, AlgorithmSheetFormat::Text, AlgorithmConflict::Replace);
+        assert_eq!((result.added, result.replaced_cases), (1, 1));
+        let ua = LastLayerCase::PLL(PLLAlgorithm::Ua);
+        assert_eq!(database.algorithms(ua).len(), 1);
+        assert!(database.cases() == vec![sune, ua]);
+    }
+
+    #[cfg(feature = "storage")]
+    #[test]
+    fn identify_algorithm() {
+        use crate::{
+            parse_move_string, AlgorithmDatabase, AlgorithmExecution, LastLayerCase, OLLAlgorithm,
+        };
+
+        let sune = LastLayerCase::OLL(OLLAlgorithm::Sune);
+        let mut database = AlgorithmDatabase::new();
+        database.add(sune, &parse_move_string("R U2 R' U' R U' R'").unwrap());
+        database.add(sune, &parse_move_string("R U R' U R U2 R'").unwrap());
+
+        // Second algorithm performed with the last layer on the bottom face,
+        // with an adjustment first
+        let moves = parse_move_string("D' L D L' D L D2 L'").unwrap();
+        let execution = AlgorithmExecution::new(&moves, CubeFace::Bottom);
+        assert_eq!(database.identify(sune, &execution), Some(1));
+
+        // Mirrored execution does not match
+        let moves = parse_move_string("L' U' L U' L' U2 L").unwrap();
+        let execution = AlgorithmExecution::new(&moves, CubeFace::Top);
+        assert_eq!(database.identify(sune, &execution), None);
+    }
 }
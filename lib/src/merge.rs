@@ -0,0 +1,40 @@
+use crate::action::{ActionList, StoredAction};
+use crate::storage::Storage;
+use anyhow::anyhow;
+use std::path::Path;
+
+/// Actions read from another solve database, such as the database of a
+/// device that was used before sync was enabled. Merge it into the current
+/// history with `History::merge_database`.
+pub struct MergeSource {
+    pub(crate) actions: Vec<StoredAction>,
+}
+
+impl MergeSource {
+    /// Reads the synced and local actions of the solve database at `path`.
+    /// The database must not be open in another instance of the app.
+    pub async fn open<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        Self::open_internal(path.as_ref())
+            .await
+            .map_err(crate::Error::Storage)
+    }
+
+    async fn open_internal(path: &Path) -> anyhow::Result<Self> {
+        // Opening a database creates it if it is missing, so check for an
+        // existing database first
+        if !path.join("CURRENT").exists() {
+            return Err(anyhow!("Folder does not contain a solve database"));
+        }
+
+        let storage = Storage::open(path)?;
+        let synced = ActionList::load(&storage, "synced").await?;
+        let local = ActionList::load(&storage, "local").await?;
+        Ok(Self {
+            actions: synced.iter().chain(local.iter()).cloned().collect(),
+        })
+    }
+
+    pub fn action_count(&self) -> usize {
+        self.actions.len()
+    }
+}
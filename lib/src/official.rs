@@ -0,0 +1,141 @@
+use crate::common::SolveType;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Events where results are not times, such as move counts or multi-blind
+/// scores. These are skipped when importing.
+const UNTIMED_EVENTS: &[&str] = &["333fm", "333mbf", "333mbo"];
+
+/// Best official single and average for one event
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfficialRecord {
+    /// WCA event identifier, such as `333` or `333oh`
+    pub event: String,
+    pub single: Option<u32>,
+    pub average: Option<u32>,
+}
+
+/// Official competition results from the World Cube Association. These are
+/// stored separately from practice solves and are not part of the solve
+/// history.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfficialResults {
+    pub wca_id: Option<String>,
+    pub name: Option<String>,
+    pub records: Vec<OfficialRecord>,
+}
+
+impl OfficialResults {
+    /// Parses official results exported from the WCA API. Accepts either a
+    /// person (`/api/v0/persons/<WCA ID>`), which includes personal records,
+    /// or the list of a person's results (`/api/v0/persons/<WCA ID>/results`),
+    /// which is reduced to the best single and average of each event.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(contents)?;
+        match &value {
+            Value::Object(object) => Self::parse_person(object),
+            Value::Array(results) => Self::parse_results(results),
+            _ => Err(anyhow!("Official results must be an object or a list")),
+        }
+    }
+
+    fn parse_person(object: &Map<String, Value>) -> Result<Self> {
+        let person = object.get("person").and_then(|person| person.as_object());
+        let records = object
+            .get("personal_records")
+            .ok_or_else(|| anyhow!("Personal records missing"))?
+            .as_object()
+            .ok_or_else(|| anyhow!("Personal records are not an object"))?;
+
+        let mut result = Self {
+            wca_id: person.and_then(|person| string_field(person, "wca_id")),
+            name: person.and_then(|person| string_field(person, "name")),
+            records: Vec::new(),
+        };
+        for (event, record) in records {
+            let record = record
+                .as_object()
+                .ok_or_else(|| anyhow!("Personal record for '{}' is not an object", event))?;
+            let best = |kind: &str| {
+                record
+                    .get(kind)
+                    .and_then(|value| value.get("best"))
+                    .and_then(|best| best.as_i64())
+                    .and_then(result_time)
+            };
+            result.add(event, best("single"), best("average"));
+        }
+        Ok(result)
+    }
+
+    fn parse_results(results: &[Value]) -> Result<Self> {
+        let mut result = Self::default();
+        for entry in results {
+            let entry = entry
+                .as_object()
+                .ok_or_else(|| anyhow!("Result is not an object"))?;
+            let event = string_field(entry, "event_id")
+                .ok_or_else(|| anyhow!("Result does not have an event"))?;
+            if result.wca_id.is_none() {
+                result.wca_id = string_field(entry, "wca_id");
+            }
+            let time = |kind: &str| {
+                entry
+                    .get(kind)
+                    .and_then(|value| value.as_i64())
+                    .and_then(result_time)
+            };
+            result.add(&event, time("best"), time("average"));
+        }
+        Ok(result)
+    }
+
+    /// Adds results for an event, keeping the best single and average
+    fn add(&mut self, event: &str, single: Option<u32>, average: Option<u32>) {
+        if UNTIMED_EVENTS.contains(&event) {
+            return;
+        }
+        let best = |a: Option<u32>, b: Option<u32>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        match self.records.iter_mut().find(|record| record.event == event) {
+            Some(record) => {
+                record.single = best(record.single, single);
+                record.average = best(record.average, average);
+            }
+            None => {
+                if single.is_some() || average.is_some() {
+                    self.records.push(OfficialRecord {
+                        event: event.into(),
+                        single,
+                        average,
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn record(&self, solve_type: SolveType) -> Option<&OfficialRecord> {
+        let event = solve_type.wca_event()?;
+        self.records.iter().find(|record| record.event == event)
+    }
+}
+
+fn string_field(object: &Map<String, Value>, name: &str) -> Option<String> {
+    object
+        .get(name)
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string())
+}
+
+/// Converts a WCA result in centiseconds to milliseconds. Zero and negative
+/// values mean there is no result, a DNF or a DNS.
+fn result_time(centiseconds: i64) -> Option<u32> {
+    if centiseconds > 0 {
+        Some(centiseconds as u32 * 10)
+    } else {
+        None
+    }
+}
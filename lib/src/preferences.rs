@@ -0,0 +1,531 @@
+use crate::common::{
+    MoveNotation, PrimeNotation, ScrambleOrientation, SolveType, WideMoveNotation,
+};
+use std::collections::HashMap;
+
+/// Time in milliseconds the start key must be held before the timer is ready
+pub const DEFAULT_HOLD_DURATION: u32 = 300;
+
+/// Hold durations that are offered as choices, in milliseconds
+pub const HOLD_DURATION_CHOICES: [u32; 6] = [0, 100, 300, 500, 750, 1000];
+
+/// Daily solve goals that are offered as choices. Zero means that a single
+/// solve is enough for a day to count toward the practice streak.
+pub const DAILY_SOLVE_GOAL_CHOICES: [u32; 6] = [0, 5, 12, 25, 50, 100];
+
+/// Length of WCA inspection in milliseconds. Starting the solve up to two
+/// seconds after this results in a +2 penalty, and later is a DNF.
+pub const INSPECTION_TIME: u32 = 15000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrambleFontSize {
+    Small,
+    Medium,
+    Large,
+    ExtraLarge,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorScheme {
+    Dark,
+    Light,
+    HighContrast,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccentColor {
+    Blue,
+    Cyan,
+    Green,
+    Orange,
+    Yellow,
+    Magenta,
+}
+
+/// How solve times are rounded for display. Times are always stored with
+/// millisecond precision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeRounding {
+    /// Round to the nearest hundredth of a second
+    Round,
+    /// Truncate to hundredths of a second, as required by WCA regulations
+    Truncate,
+    /// Show all three decimal places
+    Milliseconds,
+}
+
+/// What the timer shows while a solve is in progress
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolvingTimeDisplay {
+    /// Running time with tenths of a second
+    Tenths,
+    /// Running time in whole seconds, like a Stackmat timer's rolling display
+    Seconds,
+    /// Running time is hidden until the solve is complete
+    Hidden,
+}
+
+/// A single typed preference value. Preferences are synchronized across
+/// devices using `SetPreference` actions, which store the preference as a
+/// name and a string value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preference {
+    HoldDuration(u32),
+    Inspection(bool),
+    ScrambleFontSize(ScrambleFontSize),
+    /// Color each move of the scramble by the color of the face it turns
+    ScrambleColors(bool),
+    /// Show a diagram of the scrambled state next to the scramble
+    ScramblePreview(bool),
+    /// Orientation the cube is held in while applying scrambles
+    ScrambleOrientation(ScrambleOrientation),
+    ColorScheme(ColorScheme),
+    AccentColor(AccentColor),
+    /// Puzzle to select when the application starts. If not set, the most
+    /// recently used puzzle on the device is selected.
+    DefaultPuzzle(Option<SolveType>),
+    WideMoveNotation(WideMoveNotation),
+    PrimeNotation(PrimeNotation),
+    TimeRounding(TimeRounding),
+    SolvingTimeDisplay(SolvingTimeDisplay),
+    /// Number of solves per day needed to keep the practice streak going
+    DailySolveGoal(u32),
+}
+
+/// Current values of all synchronized preferences. Preferences that have
+/// never been set return their default values.
+#[derive(Clone, Default)]
+pub struct Preferences {
+    values: HashMap<String, String>,
+}
+
+impl ScrambleFontSize {
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "small" => Some(ScrambleFontSize::Small),
+            "medium" => Some(ScrambleFontSize::Medium),
+            "large" => Some(ScrambleFontSize::Large),
+            "extra_large" => Some(ScrambleFontSize::ExtraLarge),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            ScrambleFontSize::Small => "small",
+            ScrambleFontSize::Medium => "medium",
+            ScrambleFontSize::Large => "large",
+            ScrambleFontSize::ExtraLarge => "extra_large",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScrambleFontSize::Small => "Small",
+            ScrambleFontSize::Medium => "Medium",
+            ScrambleFontSize::Large => "Large",
+            ScrambleFontSize::ExtraLarge => "Extra large",
+        }
+    }
+
+    /// Scale to apply to the scramble font relative to the default size
+    pub fn scale(&self) -> f32 {
+        match self {
+            ScrambleFontSize::Small => 0.8,
+            ScrambleFontSize::Medium => 1.0,
+            ScrambleFontSize::Large => 1.25,
+            ScrambleFontSize::ExtraLarge => 1.5,
+        }
+    }
+}
+
+impl Default for ScrambleFontSize {
+    fn default() -> Self {
+        ScrambleFontSize::Medium
+    }
+}
+
+impl ColorScheme {
+    pub const ALL: &'static [ColorScheme] = &[
+        ColorScheme::Dark,
+        ColorScheme::Light,
+        ColorScheme::HighContrast,
+    ];
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "dark" => Some(ColorScheme::Dark),
+            "light" => Some(ColorScheme::Light),
+            "high_contrast" => Some(ColorScheme::HighContrast),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            ColorScheme::Dark => "dark",
+            ColorScheme::Light => "light",
+            ColorScheme::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorScheme::Dark => "Dark",
+            ColorScheme::Light => "Light",
+            ColorScheme::HighContrast => "High contrast",
+        }
+    }
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme::Dark
+    }
+}
+
+impl AccentColor {
+    pub const ALL: &'static [AccentColor] = &[
+        AccentColor::Blue,
+        AccentColor::Cyan,
+        AccentColor::Green,
+        AccentColor::Orange,
+        AccentColor::Yellow,
+        AccentColor::Magenta,
+    ];
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "blue" => Some(AccentColor::Blue),
+            "cyan" => Some(AccentColor::Cyan),
+            "green" => Some(AccentColor::Green),
+            "orange" => Some(AccentColor::Orange),
+            "yellow" => Some(AccentColor::Yellow),
+            "magenta" => Some(AccentColor::Magenta),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            AccentColor::Blue => "blue",
+            AccentColor::Cyan => "cyan",
+            AccentColor::Green => "green",
+            AccentColor::Orange => "orange",
+            AccentColor::Yellow => "yellow",
+            AccentColor::Magenta => "magenta",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccentColor::Blue => "Blue",
+            AccentColor::Cyan => "Cyan",
+            AccentColor::Green => "Green",
+            AccentColor::Orange => "Orange",
+            AccentColor::Yellow => "Yellow",
+            AccentColor::Magenta => "Magenta",
+        }
+    }
+}
+
+impl Default for AccentColor {
+    fn default() -> Self {
+        AccentColor::Blue
+    }
+}
+
+impl TimeRounding {
+    pub const ALL: &'static [TimeRounding] = &[
+        TimeRounding::Round,
+        TimeRounding::Truncate,
+        TimeRounding::Milliseconds,
+    ];
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "round" => Some(TimeRounding::Round),
+            "truncate" => Some(TimeRounding::Truncate),
+            "milliseconds" => Some(TimeRounding::Milliseconds),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            TimeRounding::Round => "round",
+            TimeRounding::Truncate => "truncate",
+            TimeRounding::Milliseconds => "milliseconds",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimeRounding::Round => "Round to 0.01",
+            TimeRounding::Truncate => "Truncate to 0.01 (WCA)",
+            TimeRounding::Milliseconds => "Milliseconds",
+        }
+    }
+
+    /// Converts a time in milliseconds to the displayed precision. Returns
+    /// the time in hundredths of a second, or in milliseconds if all three
+    /// decimal places are shown.
+    pub fn apply(&self, time: u32) -> u32 {
+        match self {
+            TimeRounding::Round => (time + 5) / 10,
+            TimeRounding::Truncate => time / 10,
+            TimeRounding::Milliseconds => time,
+        }
+    }
+}
+
+impl Default for TimeRounding {
+    fn default() -> Self {
+        TimeRounding::Round
+    }
+}
+
+impl SolvingTimeDisplay {
+    pub const ALL: &'static [SolvingTimeDisplay] = &[
+        SolvingTimeDisplay::Tenths,
+        SolvingTimeDisplay::Seconds,
+        SolvingTimeDisplay::Hidden,
+    ];
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "tenths" => Some(SolvingTimeDisplay::Tenths),
+            "seconds" => Some(SolvingTimeDisplay::Seconds),
+            "hidden" => Some(SolvingTimeDisplay::Hidden),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            SolvingTimeDisplay::Tenths => "tenths",
+            SolvingTimeDisplay::Seconds => "seconds",
+            SolvingTimeDisplay::Hidden => "hidden",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolvingTimeDisplay::Tenths => "Tenths",
+            SolvingTimeDisplay::Seconds => "Whole seconds",
+            SolvingTimeDisplay::Hidden => "Hidden",
+        }
+    }
+}
+
+impl Default for SolvingTimeDisplay {
+    fn default() -> Self {
+        SolvingTimeDisplay::Tenths
+    }
+}
+
+impl Preference {
+    /// Name of the preference as stored in preference actions
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preference::HoldDuration(_) => "hold_duration",
+            Preference::Inspection(_) => "inspection",
+            Preference::ScrambleFontSize(_) => "scramble_font_size",
+            Preference::ScrambleColors(_) => "scramble_colors",
+            Preference::ScramblePreview(_) => "scramble_preview",
+            Preference::ScrambleOrientation(_) => "scramble_orientation",
+            Preference::ColorScheme(_) => "color_scheme",
+            Preference::AccentColor(_) => "accent_color",
+            Preference::DefaultPuzzle(_) => "default_puzzle",
+            Preference::WideMoveNotation(_) => "wide_move_notation",
+            Preference::PrimeNotation(_) => "prime_notation",
+            Preference::TimeRounding(_) => "time_rounding",
+            Preference::SolvingTimeDisplay(_) => "solving_time_display",
+            Preference::DailySolveGoal(_) => "daily_solve_goal",
+        }
+    }
+
+    /// Value of the preference as stored in preference actions
+    pub fn value(&self) -> String {
+        match self {
+            Preference::HoldDuration(duration) => duration.to_string(),
+            Preference::Inspection(enabled) => if *enabled { "on" } else { "off" }.into(),
+            Preference::ScrambleFontSize(size) => size.to_str().into(),
+            Preference::ScrambleColors(enabled) => if *enabled { "on" } else { "off" }.into(),
+            Preference::ScramblePreview(enabled) => if *enabled { "on" } else { "off" }.into(),
+            Preference::ScrambleOrientation(orientation) => orientation.to_str(),
+            Preference::ColorScheme(scheme) => scheme.to_str().into(),
+            Preference::AccentColor(color) => color.to_str().into(),
+            Preference::DefaultPuzzle(Some(solve_type)) => solve_type.to_string(),
+            Preference::DefaultPuzzle(None) => "".into(),
+            Preference::WideMoveNotation(notation) => notation.to_str().into(),
+            Preference::PrimeNotation(notation) => notation.to_str().into(),
+            Preference::TimeRounding(rounding) => rounding.to_str().into(),
+            Preference::SolvingTimeDisplay(display) => display.to_str().into(),
+            Preference::DailySolveGoal(goal) => goal.to_string(),
+        }
+    }
+
+    /// Parses a preference from its stored name and value. Returns `None` if
+    /// the preference is not known or the value is not valid, which can happen
+    /// when syncing with newer versions.
+    pub fn parse(name: &str, value: &str) -> Option<Self> {
+        match name {
+            "hold_duration" => value.parse().ok().map(Preference::HoldDuration),
+            "inspection" => match value {
+                "on" => Some(Preference::Inspection(true)),
+                "off" => Some(Preference::Inspection(false)),
+                _ => None,
+            },
+            "scramble_font_size" => {
+                ScrambleFontSize::from_str(value).map(Preference::ScrambleFontSize)
+            }
+            "scramble_colors" => match value {
+                "on" => Some(Preference::ScrambleColors(true)),
+                "off" => Some(Preference::ScrambleColors(false)),
+                _ => None,
+            },
+            "scramble_preview" => match value {
+                "on" => Some(Preference::ScramblePreview(true)),
+                "off" => Some(Preference::ScramblePreview(false)),
+                _ => None,
+            },
+            "scramble_orientation" => {
+                ScrambleOrientation::from_str(value).map(Preference::ScrambleOrientation)
+            }
+            "color_scheme" => ColorScheme::from_str(value).map(Preference::ColorScheme),
+            "accent_color" => AccentColor::from_str(value).map(Preference::AccentColor),
+            "default_puzzle" => match value {
+                "" => Some(Preference::DefaultPuzzle(None)),
+                _ => SolveType::from_str(value)
+                    .map(|solve_type| Preference::DefaultPuzzle(Some(solve_type))),
+            },
+            "wide_move_notation" => {
+                WideMoveNotation::from_str(value).map(Preference::WideMoveNotation)
+            }
+            "prime_notation" => PrimeNotation::from_str(value).map(Preference::PrimeNotation),
+            "time_rounding" => TimeRounding::from_str(value).map(Preference::TimeRounding),
+            "solving_time_display" => {
+                SolvingTimeDisplay::from_str(value).map(Preference::SolvingTimeDisplay)
+            }
+            "daily_solve_goal" => value.parse().ok().map(Preference::DailySolveGoal),
+            _ => None,
+        }
+    }
+}
+
+impl Preferences {
+    fn get(&self, name: &str) -> Option<Preference> {
+        self.values
+            .get(name)
+            .and_then(|value| Preference::parse(name, value))
+    }
+
+    /// Sets the stored value of a preference. Returns false if the value
+    /// did not change.
+    pub(crate) fn set(&mut self, name: &str, value: &str) -> bool {
+        if self.values.get(name).map(|existing| existing.as_str()) == Some(value) {
+            return false;
+        }
+        self.values.insert(name.into(), value.into());
+        true
+    }
+
+    pub fn hold_duration(&self) -> u32 {
+        match self.get("hold_duration") {
+            Some(Preference::HoldDuration(duration)) => duration,
+            _ => DEFAULT_HOLD_DURATION,
+        }
+    }
+
+    pub fn inspection(&self) -> bool {
+        match self.get("inspection") {
+            Some(Preference::Inspection(enabled)) => enabled,
+            _ => false,
+        }
+    }
+
+    pub fn scramble_font_size(&self) -> ScrambleFontSize {
+        match self.get("scramble_font_size") {
+            Some(Preference::ScrambleFontSize(size)) => size,
+            _ => ScrambleFontSize::default(),
+        }
+    }
+
+    pub fn scramble_colors(&self) -> bool {
+        match self.get("scramble_colors") {
+            Some(Preference::ScrambleColors(enabled)) => enabled,
+            _ => false,
+        }
+    }
+
+    pub fn scramble_preview(&self) -> bool {
+        match self.get("scramble_preview") {
+            Some(Preference::ScramblePreview(enabled)) => enabled,
+            _ => false,
+        }
+    }
+
+    pub fn scramble_orientation(&self) -> ScrambleOrientation {
+        match self.get("scramble_orientation") {
+            Some(Preference::ScrambleOrientation(orientation)) => orientation,
+            _ => ScrambleOrientation::default(),
+        }
+    }
+
+    pub fn color_scheme(&self) -> ColorScheme {
+        match self.get("color_scheme") {
+            Some(Preference::ColorScheme(scheme)) => scheme,
+            _ => ColorScheme::default(),
+        }
+    }
+
+    pub fn accent_color(&self) -> AccentColor {
+        match self.get("accent_color") {
+            Some(Preference::AccentColor(color)) => color,
+            _ => AccentColor::default(),
+        }
+    }
+
+    pub fn default_puzzle(&self) -> Option<SolveType> {
+        match self.get("default_puzzle") {
+            Some(Preference::DefaultPuzzle(solve_type)) => solve_type,
+            _ => None,
+        }
+    }
+
+    /// Notation to use when displaying and exporting moves
+    pub fn move_notation(&self) -> MoveNotation {
+        MoveNotation {
+            wide: match self.get("wide_move_notation") {
+                Some(Preference::WideMoveNotation(notation)) => notation,
+                _ => WideMoveNotation::default(),
+            },
+            prime: match self.get("prime_notation") {
+                Some(Preference::PrimeNotation(notation)) => notation,
+                _ => PrimeNotation::default(),
+            },
+        }
+    }
+
+    pub fn time_rounding(&self) -> TimeRounding {
+        match self.get("time_rounding") {
+            Some(Preference::TimeRounding(rounding)) => rounding,
+            _ => TimeRounding::default(),
+        }
+    }
+
+    pub fn solving_time_display(&self) -> SolvingTimeDisplay {
+        match self.get("solving_time_display") {
+            Some(Preference::SolvingTimeDisplay(display)) => display,
+            _ => SolvingTimeDisplay::default(),
+        }
+    }
+
+    pub fn daily_solve_goal(&self) -> u32 {
+        match self.get("daily_solve_goal") {
+            Some(Preference::DailySolveGoal(goal)) => goal,
+            _ => 0,
+        }
+    }
+}
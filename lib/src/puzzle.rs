@@ -0,0 +1,146 @@
+use crate::{
+    Color, Cube, Cube2x2x2, Cube2x2x2Faces, Cube3x3x3, Cube3x3x3Faces, CubeFace, InitialCubeState,
+    Move, RandomSource, StandardRandomSource,
+};
+use std::collections::BTreeMap;
+
+#[cfg(not(feature = "no_solver"))]
+use crate::MoveSequence;
+
+/// A move on any kind of puzzle
+pub trait PuzzleMove: Copy + Eq + std::fmt::Debug + ToString + Sized {
+    /// Gets the move that undoes this move
+    fn inverse(&self) -> Self;
+
+    /// Parses a single move in standard notation
+    fn from_str(string: &str) -> Option<Self>;
+
+    /// Parses a whitespace separated sequence of moves
+    fn parse_sequence(string: &str) -> Option<Vec<Self>> {
+        string.split_whitespace().map(Self::from_str).collect()
+    }
+
+    /// Formats a sequence of moves with spaces between each move
+    fn sequence_to_string(seq: &[Self]) -> String {
+        let moves: Vec<String> = seq.iter().map(|mv| mv.to_string()).collect();
+        moves.join(" ")
+    }
+}
+
+/// A puzzle that can be scrambled, solved, and rendered. Cubes with a solver
+/// implement this through the `Cube` trait, and other puzzles (such as the
+/// megaminx, clock, or square-1) can implement this directly with their own
+/// move type.
+pub trait Puzzle: Clone {
+    /// Type of the moves that can be performed on the puzzle
+    type Move: PuzzleMove;
+    /// Identifies a group of stickers that are rendered together
+    type Face: Copy + Ord;
+    /// Color of a single sticker
+    type Color: Copy + Eq;
+
+    /// Creates a new puzzle in the solved state
+    fn solved() -> Self;
+
+    /// Generates a random puzzle state with a given random number source
+    fn sourced_random<T: RandomSource>(rng: &mut T) -> Self;
+
+    /// Generates a random puzzle state
+    fn random() -> Self {
+        Self::sourced_random(&mut StandardRandomSource)
+    }
+
+    /// Determines if this puzzle is in the solved state
+    fn is_solved(&self) -> bool;
+
+    /// Perform a move on the puzzle
+    fn do_move(&mut self, mv: Self::Move);
+
+    /// Perform a sequence of moves on the puzzle
+    fn do_moves(&mut self, seq: &[Self::Move]) {
+        for mv in seq {
+            self.do_move(*mv);
+        }
+    }
+
+    /// Sticker colors for rendering, as rows of stickers for each face
+    fn render_colors(&self) -> BTreeMap<Self::Face, Vec<Vec<Self::Color>>>;
+
+    /// Finds a solution to this puzzle state
+    #[cfg(not(feature = "no_solver"))]
+    fn solve(&self) -> Option<Vec<Self::Move>>;
+
+    /// Generates a random scramble using the given source of randomness. By
+    /// default this solves a random state and inverts the solution, which is
+    /// how official scrambles are generated.
+    #[cfg(not(feature = "no_solver"))]
+    fn scramble_sourced<T: RandomSource>(rng: &mut T) -> Vec<Self::Move> {
+        let solution = Self::sourced_random(rng).solve().unwrap();
+        solution.iter().rev().map(|mv| mv.inverse()).collect()
+    }
+
+    /// Generates a random scramble
+    #[cfg(not(feature = "no_solver"))]
+    fn scramble() -> Vec<Self::Move> {
+        Self::scramble_sourced(&mut StandardRandomSource)
+    }
+}
+
+impl PuzzleMove for Move {
+    fn inverse(&self) -> Self {
+        Move::inverse(self)
+    }
+
+    fn from_str(string: &str) -> Option<Self> {
+        Move::from_str(string)
+    }
+}
+
+/// Implements `Puzzle` for cube types that have both a random state generator
+/// and a working solver. Cubes without a solver (such as the 4x4x4 and larger
+/// cubes) can't produce random state scrambles and must not implement it.
+macro_rules! cube_puzzle {
+    ($($cube:ty),*) => {
+        $(
+            impl Puzzle for $cube {
+                type Move = Move;
+                type Face = CubeFace;
+                type Color = Color;
+
+                fn solved() -> Self {
+                    <$cube as InitialCubeState>::new()
+                }
+
+                fn sourced_random<T: RandomSource>(rng: &mut T) -> Self {
+                    <$cube as InitialCubeState>::sourced_random(rng)
+                }
+
+                fn is_solved(&self) -> bool {
+                    Cube::is_solved(self)
+                }
+
+                fn do_move(&mut self, mv: Move) {
+                    Cube::do_move(self, mv)
+                }
+
+                fn render_colors(&self) -> BTreeMap<CubeFace, Vec<Vec<Color>>> {
+                    self.colors()
+                }
+
+                #[cfg(not(feature = "no_solver"))]
+                fn solve(&self) -> Option<Vec<Move>> {
+                    Cube::solve(self)
+                }
+
+                #[cfg(not(feature = "no_solver"))]
+                fn scramble_sourced<T: RandomSource>(rng: &mut T) -> Vec<Move> {
+                    Cube::solve(&<$cube as InitialCubeState>::sourced_random(rng))
+                        .unwrap()
+                        .inverse()
+                }
+            }
+        )*
+    };
+}
+
+cube_puzzle!(Cube2x2x2, Cube2x2x2Faces, Cube3x3x3, Cube3x3x3Faces);
@@ -0,0 +1,283 @@
+use crate::error::Error;
+use crate::request::{RaceRequest, RaceResponse, RaceResult, SyncRequest};
+use crate::{Move, SeededRandomSource, SolveType};
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[cfg(not(feature = "no_solver"))]
+use crate::{
+    scramble_2x2x2_sourced, scramble_3x3x3_sourced, scramble_big_cube_moves_sourced, Variant3x3x3,
+};
+
+#[cfg(feature = "native-storage")]
+use crate::sync::post_native;
+#[cfg(feature = "web-storage")]
+use crate::sync::{post_web, spawn_future};
+
+/// Head-to-head race between two or more players on different devices. All
+/// players in a race share a race key, which is used to seed scramble
+/// generation so that every player receives the same scrambles. Results are
+/// exchanged through the sync server.
+pub struct Race {
+    key: String,
+    player: String,
+    solve_type: SolveType,
+    rng: SeededRandomSource,
+    scrambles: Vec<Vec<Move>>,
+    results: BTreeMap<usize, Option<u32>>,
+    opponents: BTreeMap<String, BTreeMap<usize, Option<u32>>>,
+    current_update: Option<Arc<Mutex<RaceOperation>>>,
+    last_error: Option<String>,
+}
+
+/// Running score of a race against a single opponent
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct RaceScore {
+    pub wins: usize,
+    pub losses: usize,
+    pub ties: usize,
+}
+
+struct RaceOperation {
+    request: RaceRequest,
+    response: Option<Result<RaceResponse>>,
+}
+
+impl Race {
+    /// Creates a new race with a freshly generated race key. The key should
+    /// be shared with the other players so that they can join the race.
+    pub fn new(player: String, solve_type: SolveType) -> Self {
+        Self::with_key(SyncRequest::new_sync_key(), player, solve_type)
+    }
+
+    /// Joins an existing race using the race key from another player
+    pub fn join(key: &str, player: String, solve_type: SolveType) -> crate::Result<Self> {
+        let key = SyncRequest::validate_sync_key(key)
+            .ok_or_else(|| Error::Parse(anyhow!("Invalid race key")))?;
+        Ok(Self::with_key(key, player, solve_type))
+    }
+
+    fn with_key(key: String, player: String, solve_type: SolveType) -> Self {
+        let rng = SeededRandomSource::new(RaceRequest::seed(&key));
+        Self {
+            key,
+            player,
+            solve_type,
+            rng,
+            scrambles: Vec::new(),
+            results: BTreeMap::new(),
+            opponents: BTreeMap::new(),
+            current_update: None,
+            last_error: None,
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn player(&self) -> &str {
+        &self.player
+    }
+
+    pub fn solve_type(&self) -> SolveType {
+        self.solve_type
+    }
+
+    /// Returns the scramble for the solve at `index`. Scrambles are generated
+    /// in order from the shared seed, so all players see the same sequence.
+    #[cfg(not(feature = "no_solver"))]
+    pub fn scramble(&mut self, index: usize) -> &[Move] {
+        while self.scrambles.len() <= index {
+            let scramble = match self.solve_type {
+                SolveType::Standard3x3x3
+                | SolveType::OneHanded3x3x3
+                | SolveType::Blind3x3x3
+                | SolveType::Void3x3x3 => scramble_3x3x3_sourced(&mut self.rng),
+                SolveType::Bandaged3x3x3 => {
+                    Variant3x3x3::for_solve_type(self.solve_type).scramble_sourced(&mut self.rng)
+                }
+                SolveType::Standard2x2x2 => scramble_2x2x2_sourced(&mut self.rng),
+                SolveType::Standard5x5x5 | SolveType::Standard6x6x6 | SolveType::Standard7x7x7 => {
+                    scramble_big_cube_moves_sourced(self.solve_type.cube_size(), &mut self.rng)
+                }
+            };
+            self.scrambles.push(scramble);
+        }
+        &self.scrambles[index]
+    }
+
+    /// Index of the next solve that this player has not yet completed
+    pub fn next_index(&self) -> usize {
+        match self.results.keys().next_back() {
+            Some(index) => index + 1,
+            None => 0,
+        }
+    }
+
+    /// Records the result of a solve. A time of `None` is a DNF. The result is
+    /// sent to the other players on the next update.
+    pub fn submit_result(&mut self, index: usize, time: Option<u32>) {
+        self.results.insert(index, time);
+        self.start_update();
+    }
+
+    pub fn result(&self, index: usize) -> Option<Option<u32>> {
+        self.results.get(&index).cloned()
+    }
+
+    pub fn opponents(&self) -> Vec<&str> {
+        self.opponents.keys().map(|name| name.as_str()).collect()
+    }
+
+    pub fn opponent_result(&self, opponent: &str, index: usize) -> Option<Option<u32>> {
+        self.opponents
+            .get(opponent)
+            .and_then(|results| results.get(&index).cloned())
+    }
+
+    /// Computes the running match score against an opponent. Only solves that
+    /// both players have completed are counted. A DNF loses to any time, and
+    /// two DNFs are a tie.
+    pub fn score(&self, opponent: &str) -> RaceScore {
+        let mut score = RaceScore::default();
+        if let Some(opponent_results) = self.opponents.get(opponent) {
+            for (index, time) in &self.results {
+                if let Some(opponent_time) = opponent_results.get(index) {
+                    match (time, opponent_time) {
+                        (Some(time), Some(opponent_time)) => {
+                            if time < opponent_time {
+                                score.wins += 1;
+                            } else if time > opponent_time {
+                                score.losses += 1;
+                            } else {
+                                score.ties += 1;
+                            }
+                        }
+                        (Some(_), None) => score.wins += 1,
+                        (None, Some(_)) => score.losses += 1,
+                        (None, None) => score.ties += 1,
+                    }
+                }
+            }
+        }
+        score
+    }
+
+    /// Starts exchanging results with the server. This should be called
+    /// periodically while a race is active to receive the other players'
+    /// results. Returns false if an update is already in progress.
+    pub fn start_update(&mut self) -> bool {
+        if self.current_update.is_some() {
+            return false;
+        }
+
+        let request = RaceRequest {
+            race_key: self.key.clone(),
+            player: self.player.clone(),
+            results: self
+                .results
+                .iter()
+                .map(|(index, time)| RaceResult {
+                    index: *index,
+                    time: *time,
+                })
+                .collect(),
+        };
+        self.current_update = Some(RaceOperation::new(request));
+        true
+    }
+
+    /// Checks for completion of an update. Returns true if new results from
+    /// the other players were received.
+    pub fn check_update(&mut self) -> bool {
+        let operation = match self.current_update.clone() {
+            Some(operation) => operation,
+            None => return false,
+        };
+        let operation = operation.lock().unwrap();
+        if !operation.done() {
+            return false;
+        }
+
+        let mut changed = false;
+        match operation.response().as_ref().unwrap() {
+            Ok(response) => {
+                self.last_error = None;
+                for player in &response.players {
+                    if player.player == self.player {
+                        continue;
+                    }
+                    let results = self
+                        .opponents
+                        .entry(player.player.clone())
+                        .or_insert_with(BTreeMap::new);
+                    for result in &player.results {
+                        if results.insert(result.index, result.time) != Some(result.time) {
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            Err(error) => {
+                self.last_error = Some(error.to_string());
+            }
+        }
+        self.current_update = None;
+        changed
+    }
+
+    pub fn update_in_progress(&self) -> bool {
+        self.current_update.is_some()
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+}
+
+impl RaceOperation {
+    fn new(request: RaceRequest) -> Arc<Mutex<Self>> {
+        let operation = Arc::new(Mutex::new(Self {
+            request,
+            response: None,
+        }));
+
+        let operation_copy = operation.clone();
+
+        #[cfg(feature = "native-storage")]
+        std::thread::spawn(move || {
+            let result = Self::execute(&operation_copy);
+            operation_copy.lock().unwrap().response = Some(result);
+        });
+
+        #[cfg(feature = "web-storage")]
+        spawn_future(async move {
+            let result = Self::execute(&operation_copy).await;
+            operation_copy.lock().unwrap().response = Some(result);
+        });
+
+        operation
+    }
+
+    #[cfg(feature = "native-storage")]
+    fn execute(operation: &Arc<Mutex<Self>>) -> Result<RaceResponse> {
+        let request = operation.lock().unwrap().request.serialize()?.to_string();
+        Ok(RaceResponse::deserialize(post_native(request)?)?)
+    }
+
+    #[cfg(feature = "web-storage")]
+    async fn execute(operation: &Arc<Mutex<Self>>) -> Result<RaceResponse> {
+        let request = operation.lock().unwrap().request.serialize()?.to_string();
+        Ok(RaceResponse::deserialize(post_web(request).await?)?)
+    }
+
+    fn done(&self) -> bool {
+        self.response.is_some()
+    }
+
+    fn response(&self) -> &Option<Result<RaceResponse>> {
+        &self.response
+    }
+}
@@ -14,6 +14,15 @@ pub struct SimpleSeededRandomSource {
 /// Random source using the `rand` crate
 pub struct StandardRandomSource;
 
+/// Repeatable random source with a fixed seed. This is used when multiple
+/// clients must generate the same sequence of scrambles, such as in a race
+/// between two players. The generator is SplitMix64 with rejection sampling,
+/// which is implemented here instead of using the `rand` crate so that the
+/// output can never change between versions or platforms.
+pub struct SeededRandomSource {
+    state: u64,
+}
+
 impl SimpleSeededRandomSource {
     /// Creates a new random source. This always starts at the same seed, and is intended
     /// for use in repeatable testing. Do not use for generating scrambles for a user.
@@ -30,6 +39,35 @@ impl RandomSource for SimpleSeededRandomSource {
     }
 }
 
+impl SeededRandomSource {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+}
+
+impl RandomSource for SeededRandomSource {
+    fn next(&mut self, range: u32) -> u32 {
+        // Reject values from the incomplete final block so that every number in
+        // the range is equally likely
+        let range = range as u64;
+        let limit = u64::MAX - u64::MAX % range;
+        loop {
+            let value = self.next_u64();
+            if value < limit {
+                return (value % range) as u32;
+            }
+        }
+    }
+}
+
 impl RandomSource for StandardRandomSource {
     fn next(&mut self, range: u32) -> u32 {
         thread_rng().gen_range(0..range)
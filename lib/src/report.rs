@@ -0,0 +1,481 @@
+use crate::{
+    Analysis, BlindAnalysis, BlindPieceType, CubeWithSolution, History, ListAverage, MoveNotation,
+    MoveSequence, SessionAnalysis, SkippedStep, Solve, SolveList, SolveType,
+};
+use chrono::{DateTime, Local};
+
+/// Sizes of averages included in the statistics table
+const REPORT_AVERAGE_SIZES: [usize; 4] = [5, 12, 50, 100];
+
+/// Names of the major steps of the analysis, indexed by major step index
+const MAJOR_STEP_NAMES: [&str; 4] = ["Cross", "F2L", "OLL", "PLL"];
+
+/// Column headers of the PLL case table
+const PLL_CASE_HEADERS: [&str; 5] = ["Case", "Count", "Recognition", "Execution", "Worst"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// Formatted summary of a session for sharing, containing a statistics table,
+/// personal bests set during the session, step breakdown aggregates for
+/// solves with move data, and reconstructions of the best and worst solves.
+pub struct SessionReport {
+    title: String,
+    solve_type: SolveType,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    stats: Vec<(String, String)>,
+    personal_bests: Vec<String>,
+    steps: Vec<(String, String)>,
+    pll_cases: Vec<[String; 5]>,
+    reconstructions: Vec<Reconstruction>,
+}
+
+struct Reconstruction {
+    title: String,
+    time: String,
+    scramble: String,
+    steps: Vec<(String, String)>,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+impl SessionReport {
+    /// Generates a report for the given session. Returns `None` if the session
+    /// does not exist or has no solves.
+    pub fn generate(history: &History, session_id: &str) -> Option<Self> {
+        let session = history.sessions().get(session_id)?;
+        let solves = session.to_vec(history);
+        let first = solves.first()?;
+        let last = solves.last()?;
+
+        let title = match session.name() {
+            Some(name) => name.clone(),
+            None => format!("Session on {}", first.created.format("%B %e, %Y")),
+        };
+
+        // Basic statistics for the session
+        let mut stats = Vec::new();
+        let dnf_count = solves
+            .iter()
+            .filter(|solve| solve.final_time().is_none())
+            .count();
+        stats.push(("Solves".to_string(), format!("{}", solves.len())));
+        stats.push(("DNFs".to_string(), format!("{}", dnf_count)));
+        let repeated: usize = history
+            .reused_scrambles(session_id)
+            .iter()
+            .map(|group| group.len() - 1)
+            .sum();
+        if repeated != 0 {
+            stats.push(("Repeated scrambles".to_string(), format!("{}", repeated)));
+        }
+        stats.push((
+            "Session average".to_string(),
+            optional_time_string(solves.as_slice().average()),
+        ));
+        let best = solves.as_slice().best();
+        stats.push((
+            "Best single".to_string(),
+            optional_time_string(best.as_ref().map(|best| best.time)),
+        ));
+        let worst = solves
+            .iter()
+            .filter(|solve| solve.final_time().is_some())
+            .max_by_key(|solve| solve.final_time());
+        stats.push((
+            "Worst single".to_string(),
+            optional_time_string(worst.and_then(|solve| solve.final_time())),
+        ));
+        for size in REPORT_AVERAGE_SIZES.iter() {
+            if solves.len() < *size {
+                break;
+            }
+            stats.push((
+                format!("Best ao{}", size),
+                optional_time_string(
+                    solves
+                        .as_slice()
+                        .best_average(*size)
+                        .map(|average| average.time),
+                ),
+            ));
+            stats.push((
+                format!("Last ao{}", size),
+                optional_time_string(
+                    solves
+                        .as_slice()
+                        .last_average(*size)
+                        .map(|average| average.time),
+                ),
+            ));
+        }
+
+        // Compare session bests against solves from before the session
+        let previous: Vec<Solve> = history
+            .iter()
+            .filter(|solve| {
+                solve.solve_type == session.solve_type()
+                    && solve.created < first.created
+                    && solve.session != session_id
+            })
+            .cloned()
+            .collect();
+        let mut personal_bests = Vec::new();
+        if let Some(best) = &best {
+            if previous.as_slice().best().map(|prev| best.time < prev.time) != Some(false) {
+                personal_bests.push(format!("Single: {}", time_string(best.time)));
+            }
+        }
+        for size in REPORT_AVERAGE_SIZES.iter() {
+            if let Some(average) = solves.as_slice().best_average(*size) {
+                let prev = previous.as_slice().best_average(*size);
+                if prev.map(|prev| average.time < prev.time) != Some(false) {
+                    personal_bests.push(format!("ao{}: {}", size, time_string(average.time)));
+                }
+            }
+        }
+
+        // Aggregate step breakdown for solves with move data
+        let analysis = SessionAnalysis::analyze(&solves);
+        let mut steps = Vec::new();
+        if analysis.analyzed_count > 0 {
+            steps.push((
+                "Analyzed solves".to_string(),
+                format!("{}", analysis.analyzed_count),
+            ));
+            for (idx, name) in MAJOR_STEP_NAMES.iter().enumerate() {
+                if let Some(time) = analysis.average_step_time(idx) {
+                    steps.push((format!("Average {}", name), time_string(time)));
+                }
+            }
+            if let Some(rate) = analysis.xcross_rate() {
+                steps.push(("XCross rate".to_string(), format!("{:.0}%", rate * 100.0)));
+            }
+            for step in &[SkippedStep::OLL, SkippedStep::PLL] {
+                if let Some(rate) = analysis.skip_rate(*step) {
+                    steps.push((
+                        format!("{} rate", step.to_str()),
+                        format!("{:.0}%", rate * 100.0),
+                    ));
+                }
+            }
+            if let Some(aufs) = analysis.average_aufs() {
+                steps.push(("AUFs per algorithm".to_string(), format!("{:.2}", aufs)));
+            }
+            if let Some(score) = analysis.lookahead_score() {
+                steps.push(("Lookahead score".to_string(), format!("{:.0}", score)));
+            }
+        }
+
+        // Time spent on each PLL case, most costly first
+        let pll_cases = analysis
+            .pll_case_breakdown()
+            .iter()
+            .map(|(algorithm, stats)| {
+                [
+                    algorithm.to_str().to_string(),
+                    format!("{}", stats.count),
+                    time_string(stats.average_recognition_time()),
+                    time_string(stats.average_execution_time()),
+                    time_string(stats.worst_time),
+                ]
+            })
+            .collect();
+
+        // Reconstructions of the best and worst solves, if they have move data
+        let notation = history.preferences().move_notation();
+        let mut reconstructions = Vec::new();
+        if let Some(best) = &best {
+            if let Some(reconstruction) = Reconstruction::new("Best solve", &best.solve, notation) {
+                reconstructions.push(reconstruction);
+            }
+        }
+        if let Some(worst) = worst {
+            if best.as_ref().map(|best| best.solve.id != worst.id) != Some(false) {
+                if let Some(reconstruction) = Reconstruction::new("Worst solve", worst, notation) {
+                    reconstructions.push(reconstruction);
+                }
+            }
+        }
+
+        Some(Self {
+            title,
+            solve_type: session.solve_type(),
+            start: first.created,
+            end: last.created,
+            stats,
+            personal_bests,
+            steps,
+            pll_cases,
+            reconstructions,
+        })
+    }
+
+    pub fn export(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    fn date_range(&self) -> String {
+        if self.start.date() == self.end.date() {
+            format!(
+                "{}, {} - {}",
+                self.start.format("%B %e, %Y"),
+                self.start.format("%l:%M %P").to_string().trim(),
+                self.end.format("%l:%M %P").to_string().trim()
+            )
+        } else {
+            format!(
+                "{} - {}",
+                self.start.format("%B %e, %Y"),
+                self.end.format("%B %e, %Y")
+            )
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut result = format!("# {}\n\n", self.title);
+        result += &format!(
+            "*{}* — {}\n\n",
+            self.solve_type.to_string(),
+            self.date_range()
+        );
+
+        result += "## Statistics\n\n| Statistic | Value |\n|---|---|\n";
+        for (name, value) in &self.stats {
+            result += &format!("| {} | {} |\n", name, value);
+        }
+
+        if self.personal_bests.len() > 0 {
+            result += "\n## Personal Bests\n\n";
+            for pb in &self.personal_bests {
+                result += &format!("- {}\n", pb);
+            }
+        }
+
+        if self.steps.len() > 0 {
+            result += "\n## Step Breakdown\n\n| Step | Value |\n|---|---|\n";
+            for (name, value) in &self.steps {
+                result += &format!("| {} | {} |\n", name, value);
+            }
+        }
+
+        if self.pll_cases.len() > 0 {
+            result += &format!(
+                "\n## PLL Cases\n\n| {} |\n|---|---|---|---|---|\n",
+                PLL_CASE_HEADERS.join(" | ")
+            );
+            for row in &self.pll_cases {
+                result += &format!("| {} |\n", row.join(" | "));
+            }
+        }
+
+        for reconstruction in &self.reconstructions {
+            result += &format!(
+                "\n## {} ({})\n\n```\n{} // scramble\n\n",
+                reconstruction.title, reconstruction.time, reconstruction.scramble
+            );
+            for (name, moves) in &reconstruction.steps {
+                result += &format!("{} // {}\n", moves, name);
+            }
+            result += "```\n";
+        }
+        result
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut body = format!("<h1>{}</h1>\n", escape_html(&self.title));
+        body += &format!(
+            "<p><em>{}</em> &mdash; {}</p>\n",
+            escape_html(&self.solve_type.to_string()),
+            escape_html(&self.date_range())
+        );
+
+        body += "<h2>Statistics</h2>\n";
+        body += &html_table(&self.stats);
+
+        if self.personal_bests.len() > 0 {
+            body += "<h2>Personal Bests</h2>\n<ul>\n";
+            for pb in &self.personal_bests {
+                body += &format!("<li>{}</li>\n", escape_html(pb));
+            }
+            body += "</ul>\n";
+        }
+
+        if self.steps.len() > 0 {
+            body += "<h2>Step Breakdown</h2>\n";
+            body += &html_table(&self.steps);
+        }
+
+        if self.pll_cases.len() > 0 {
+            body += "<h2>PLL Cases</h2>\n<table>\n<tr>";
+            for header in PLL_CASE_HEADERS.iter() {
+                body += &format!("<th>{}</th>", header);
+            }
+            body += "</tr>\n";
+            for row in &self.pll_cases {
+                body += "<tr>";
+                for value in row {
+                    body += &format!("<td>{}</td>", escape_html(value));
+                }
+                body += "</tr>\n";
+            }
+            body += "</table>\n";
+        }
+
+        for reconstruction in &self.reconstructions {
+            body += &format!(
+                "<h2>{} ({})</h2>\n<pre>{} // scramble\n\n",
+                escape_html(&reconstruction.title),
+                escape_html(&reconstruction.time),
+                escape_html(&reconstruction.scramble)
+            );
+            for (name, moves) in &reconstruction.steps {
+                body += &format!("{} // {}\n", escape_html(moves), escape_html(name));
+            }
+            body += "</pre>\n";
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+            <style>\nbody {{ font-family: sans-serif; max-width: 48em; margin: 2em auto; }}\n\
+            table {{ border-collapse: collapse; }}\n\
+            td, th {{ border: 1px solid #ccc; padding: 0.25em 1em; }}\n\
+            pre {{ background: #f4f4f4; padding: 1em; }}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            escape_html(&self.title),
+            body
+        )
+    }
+}
+
+impl Reconstruction {
+    fn new(title: &str, solve: &Solve, notation: MoveNotation) -> Option<Self> {
+        let cube: Option<CubeWithSolution> = solve.into();
+        let cube = cube?;
+        let cfop = match Analysis::analyze_with_solve_type(&cube, solve.solve_type) {
+            Analysis::CFOP(cfop) => cfop,
+            Analysis::Blind(blind) => {
+                return Some(Self::new_blind(title, solve, &blind, notation));
+            }
+            Analysis::Unsuccessful => return None,
+        };
+
+        let mut steps = Vec::new();
+        steps.push((
+            cfop.cross.to_str().to_string(),
+            cfop.cross.moves.to_string(),
+        ));
+        for (idx, pair) in cfop.f2l_pairs.iter().enumerate() {
+            steps.push((format!("F2L Pair {}", idx + 1), pair.moves.to_string()));
+        }
+        for oll in &cfop.oll {
+            steps.push((
+                format!(
+                    "OLL ({})",
+                    oll.execution.label(&oll.performed_algorithm.to_string())
+                ),
+                oll.execution.to_string(),
+            ));
+        }
+        for pll in &cfop.pll {
+            steps.push((
+                format!(
+                    "PLL ({})",
+                    pll.execution.label(pll.performed_algorithm.to_str())
+                ),
+                pll.execution.to_string(),
+            ));
+        }
+        if cfop.alignment.moves.len() > 0 {
+            steps.push(("AUF".to_string(), cfop.alignment.moves.to_string()));
+        }
+
+        Some(Self {
+            title: title.to_string(),
+            time: optional_time_string(solve.final_time()),
+            scramble: solve.scramble.to_string_with(notation),
+            steps,
+        })
+    }
+
+    fn new_blind(
+        title: &str,
+        solve: &Solve,
+        blind: &BlindAnalysis,
+        notation: MoveNotation,
+    ) -> Self {
+        let mut steps = Vec::new();
+        let mut targets = [0, 0];
+        for cycle in &blind.cycles {
+            let (name, count) = match cycle.piece {
+                BlindPieceType::Edge => ("Edge", &mut targets[0]),
+                BlindPieceType::Corner => ("Corner", &mut targets[1]),
+            };
+            *count += 1;
+            steps.push((
+                format!("{} {} ({})", name, count, cycle.algorithm.to_str()),
+                cycle.moves.to_string(),
+            ));
+        }
+
+        Self {
+            title: title.to_string(),
+            time: optional_time_string(solve.final_time()),
+            scramble: solve.scramble.to_string_with(notation),
+            steps,
+        }
+    }
+}
+
+fn time_string(time: u32) -> String {
+    let time = (time + 5) / 10;
+    if time > 6000 {
+        format!(
+            "{}:{:02}.{:02}",
+            time / 6000,
+            (time % 6000) / 100,
+            time % 100
+        )
+    } else {
+        format!("{}.{:02}", time / 100, time % 100)
+    }
+}
+
+pub(crate) fn optional_time_string(time: Option<u32>) -> String {
+    match time {
+        Some(time) => time_string(time),
+        None => "DNF".into(),
+    }
+}
+
+fn html_table(rows: &[(String, String)]) -> String {
+    let mut result = "<table>\n".to_string();
+    for (name, value) in rows {
+        result += &format!(
+            "<tr><td>{}</td><td>{}</td></tr>\n",
+            escape_html(name),
+            escape_html(value)
+        );
+    }
+    result + "</table>\n"
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
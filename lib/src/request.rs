@@ -1,8 +1,11 @@
-use crate::action::StoredAction;
-use anyhow::{anyhow, Result};
+use crate::action::{Action, StoredAction};
+use crate::error::{Error, ErrorKind, Result};
+use anyhow::anyhow;
 use rand::{thread_rng, Rng};
 use serde_json::{json, Value};
+use std::collections::HashSet;
 use std::convert::TryInto;
+use std::fmt;
 
 pub const SYNC_API_VERSION: u64 = 1;
 
@@ -14,6 +17,18 @@ const SYNC_KEY_LENGTH: usize = 25;
 const SYNC_KEY_GROUPING: usize = 5;
 const SYNC_KEY_VALIDATION_BITS: usize = 15;
 
+/// Largest number of actions uploaded in a single sync request. Larger
+/// uploads, such as after an import, are split across several requests.
+pub const MAX_UPLOAD_ACTIONS_PER_REQUEST: usize = 1024;
+/// Minimum time between the requests of an upload that was split into
+/// several requests, so that bulk uploads do not flood the server
+pub const BULK_UPLOAD_INTERVAL_MS: u64 = 1000;
+/// Delay used when the server asks the client to back off without saying
+/// for how long
+const DEFAULT_RETRY_AFTER_MS: u64 = 30_000;
+/// Longest delay accepted from the server before retrying
+const MAX_RETRY_AFTER_MS: u64 = 60 * 60 * 1000;
+
 #[derive(Clone, Debug)]
 pub struct SyncRequest {
     pub sync_key: String,
@@ -29,6 +44,79 @@ pub struct SyncResponse {
     pub uploaded: usize,
 }
 
+/// Error for a request rejected by the server because the client is sending
+/// requests too quickly. The request should be retried after the delay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryAfter {
+    pub delay_ms: u64,
+}
+
+/// Result of a single solve in a race. A time of `None` is a DNF.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RaceResult {
+    pub index: usize,
+    pub time: Option<u32>,
+}
+
+/// Uploads a player's results in a race and requests the results of the
+/// other players. Races are identified by a race key, which uses the same
+/// format as sync keys.
+#[derive(Clone, Debug)]
+pub struct RaceRequest {
+    pub race_key: String,
+    pub player: String,
+    pub results: Vec<RaceResult>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RacePlayerResults {
+    pub player: String,
+    pub results: Vec<RaceResult>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RaceResponse {
+    pub players: Vec<RacePlayerResults>,
+}
+
+/// Account management operation. Accounts are identified by their sync key,
+/// and every device that has logged in to an account receives its own token.
+/// Operations other than creating an account or logging in require a token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountOperation {
+    Create,
+    Login,
+    ListDevices,
+    RevokeDevice(String),
+    WipeData,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountRequest {
+    pub sync_key: String,
+    pub device_id: String,
+    pub device_name: String,
+    pub token: Option<String>,
+    pub operation: AccountOperation,
+}
+
+/// Device that has logged in to an account
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountDevice {
+    pub id: String,
+    pub name: String,
+    /// Time of the device's last login, in milliseconds since the epoch
+    pub last_login: i64,
+}
+
+/// Response to an account request. The token is only sent when creating an
+/// account or logging in. The device list is empty after wiping data.
+#[derive(Clone, Debug)]
+pub struct AccountResponse {
+    pub token: Option<String>,
+    pub devices: Vec<AccountDevice>,
+}
+
 impl SyncRequest {
     pub fn new_sync_key() -> String {
         // Generate a random sync identifier
@@ -125,6 +213,82 @@ impl SyncRequest {
         }
     }
 
+    /// Creates a sync request that uploads `actions`, leaving out the actions that
+    /// apply to any of the sessions in `excluded_sessions`. Actions that refer to
+    /// solves are checked against the session the solve is currently in, as given
+    /// by `solve_session`. If nothing is left to upload, only fetches. At most
+    /// `MAX_UPLOAD_ACTIONS_PER_REQUEST` actions are uploaded, the rest are left
+    /// for the following requests.
+    pub fn upload_excluding<'a, I, F>(
+        sync_key: String,
+        sync_id: u32,
+        actions: I,
+        excluded_sessions: &HashSet<String>,
+        solve_session: F,
+    ) -> Self
+    where
+        I: IntoIterator<Item = &'a StoredAction>,
+        F: Fn(&str) -> Option<String>,
+    {
+        let actions: Vec<StoredAction> = actions
+            .into_iter()
+            .filter(|action| !Self::excluded(&action.action, excluded_sessions, &solve_session))
+            .take(MAX_UPLOAD_ACTIONS_PER_REQUEST)
+            .cloned()
+            .collect();
+        if actions.len() == 0 {
+            Self::fetch(sync_key, sync_id)
+        } else {
+            Self::upload(sync_key, sync_id, actions)
+        }
+    }
+
+    /// Returns true if this request uploads as many actions as a single request
+    /// allows, meaning that there may be more actions left to upload
+    pub fn is_bulk_upload(&self) -> bool {
+        self.upload
+            .as_ref()
+            .map(|upload| upload.len() >= MAX_UPLOAD_ACTIONS_PER_REQUEST)
+            .unwrap_or(false)
+    }
+
+    fn excluded<F>(action: &Action, excluded_sessions: &HashSet<String>, solve_session: &F) -> bool
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if excluded_sessions.len() == 0 {
+            return false;
+        }
+
+        let solve_excluded = |solve_id: &str| match solve_session(solve_id) {
+            Some(session) => excluded_sessions.contains(&session),
+            None => false,
+        };
+        match action {
+            Action::NewSolve(solve) => match solve_session(&solve.id) {
+                Some(session) => excluded_sessions.contains(&session),
+                None => excluded_sessions.contains(&solve.session),
+            },
+            Action::Penalty(solve_id, _)
+            | Action::DeleteSolve(solve_id)
+            | Action::ChangeParticipant(solve_id, _)
+            | Action::TagSolve(solve_id, _)
+            | Action::UntagSolve(solve_id, _) => solve_excluded(solve_id),
+            Action::ChangeSession(solve_id, session_id) => {
+                excluded_sessions.contains(session_id) || solve_excluded(solve_id)
+            }
+            Action::MergeSessions(first, second) => {
+                excluded_sessions.contains(first) || excluded_sessions.contains(second)
+            }
+            Action::RenameSession(session_id, _)
+            | Action::AddParticipant(session_id, _, _)
+            | Action::RemoveParticipant(session_id, _)
+            | Action::SetSessionGoal(session_id, _, _) => excluded_sessions.contains(session_id),
+            Action::AttemptGroup(_, _, solves) => solves.iter().any(|solve| solve_excluded(solve)),
+            Action::SetPreference(_, _) => false,
+        }
+    }
+
     pub fn serialize(&self) -> Result<Value> {
         Ok(match &self.upload {
             Some(upload) => {
@@ -150,26 +314,30 @@ impl SyncRequest {
         let sync_key = Self::validate_sync_key(
             request
                 .get("sync_key")
-                .ok_or_else(|| anyhow!("Missing sync key"))?
+                .ok_or_else(|| Error::Parse(anyhow!("Missing sync key")))?
                 .as_str()
-                .ok_or_else(|| anyhow!("Sync key is not a string"))?,
+                .ok_or_else(|| Error::Parse(anyhow!("Sync key is not a string")))?,
         )
-        .ok_or_else(|| anyhow!("Invalid sync key"))?;
+        .ok_or_else(|| Error::Parse(anyhow!("Invalid sync key")))?;
 
         let sync_id: u32 = request
             .get("sync_id")
-            .ok_or_else(|| anyhow!("Missing sync ID"))?
+            .ok_or_else(|| Error::Parse(anyhow!("Missing sync ID")))?
             .as_u64()
-            .ok_or_else(|| anyhow!("Sync ID is not an integer"))?
-            .try_into()?;
-
-        let upload = match request.get("upload") {
-            Some(data) => Some(StoredAction::deserialize_list(&base64::decode(
-                data.as_str()
-                    .ok_or_else(|| anyhow!("Upload data is not a base64 string"))?,
-            )?)?),
-            None => None,
-        };
+            .ok_or_else(|| Error::Parse(anyhow!("Sync ID is not an integer")))?
+            .try_into()
+            .parse_error()?;
+
+        let upload =
+            match request.get("upload") {
+                Some(data) => Some(StoredAction::deserialize_list(
+                    &base64::decode(data.as_str().ok_or_else(|| {
+                        Error::Parse(anyhow!("Upload data is not a base64 string"))
+                    })?)
+                    .parse_error()?,
+                )?),
+                None => None,
+            };
 
         Ok(Self {
             sync_key,
@@ -200,29 +368,34 @@ impl SyncResponse {
     pub fn deserialize(response: Value) -> Result<Self> {
         let new_sync_id: u32 = response
             .get("sync_id")
-            .ok_or_else(|| anyhow!("Missing sync ID"))?
+            .ok_or_else(|| Error::Parse(anyhow!("Missing sync ID")))?
             .as_u64()
-            .ok_or_else(|| anyhow!("Sync ID is not an integer"))?
-            .try_into()?;
+            .ok_or_else(|| Error::Parse(anyhow!("Sync ID is not an integer")))?
+            .try_into()
+            .parse_error()?;
         let new_actions = match response.get("data") {
-            Some(data) => StoredAction::deserialize_list(&base64::decode(
-                data.as_str()
-                    .ok_or_else(|| anyhow!("Data is not a base64 string"))?,
-            )?)?,
+            Some(data) => StoredAction::deserialize_list(
+                &base64::decode(
+                    data.as_str()
+                        .ok_or_else(|| Error::Parse(anyhow!("Data is not a base64 string")))?,
+                )
+                .parse_error()?,
+            )?,
             None => Vec::new(),
         };
         let more_actions = match response.get("more") {
             Some(more) => more
                 .as_bool()
-                .ok_or_else(|| anyhow!("More actions flag is not a bool"))?,
+                .ok_or_else(|| Error::Parse(anyhow!("More actions flag is not a bool")))?,
             None => false,
         };
         let uploaded = response
             .get("uploaded")
-            .ok_or_else(|| anyhow!("Missing upload count"))?
+            .ok_or_else(|| Error::Parse(anyhow!("Missing upload count")))?
             .as_u64()
-            .ok_or_else(|| anyhow!("Upload count is not an integer"))?
-            .try_into()?;
+            .ok_or_else(|| Error::Parse(anyhow!("Upload count is not an integer")))?
+            .try_into()
+            .parse_error()?;
 
         Ok(Self {
             new_sync_id,
@@ -232,3 +405,296 @@ impl SyncResponse {
         })
     }
 }
+
+impl RetryAfter {
+    /// Parses the value of a Retry-After header. Only the delay in seconds
+    /// form is understood, other values use a default delay.
+    pub fn from_header(value: Option<&str>) -> Self {
+        let delay_ms = value
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(|seconds| seconds.saturating_mul(1000))
+            .unwrap_or(DEFAULT_RETRY_AFTER_MS);
+        Self {
+            delay_ms: delay_ms.min(MAX_RETRY_AFTER_MS),
+        }
+    }
+}
+
+impl fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Server is busy, retrying in {} seconds",
+            (self.delay_ms + 999) / 1000
+        )
+    }
+}
+
+impl std::error::Error for RetryAfter {}
+
+impl RaceResult {
+    fn serialize_list(results: &[RaceResult]) -> Value {
+        Value::Array(
+            results
+                .iter()
+                .map(|result| json!([result.index, result.time]))
+                .collect(),
+        )
+    }
+
+    fn deserialize_list(value: &Value) -> Result<Vec<RaceResult>> {
+        let mut results = Vec::new();
+        for result in value
+            .as_array()
+            .ok_or_else(|| Error::Parse(anyhow!("Race results are not an array")))?
+        {
+            let index = result
+                .get(0)
+                .ok_or_else(|| Error::Parse(anyhow!("Missing race result index")))?
+                .as_u64()
+                .ok_or_else(|| Error::Parse(anyhow!("Race result index is not an integer")))?
+                .try_into()
+                .parse_error()?;
+            let time = match result.get(1) {
+                Some(Value::Null) | None => None,
+                Some(time) => Some(
+                    time.as_u64()
+                        .ok_or_else(|| Error::Parse(anyhow!("Race result time is not an integer")))?
+                        .try_into()
+                        .parse_error()?,
+                ),
+            };
+            results.push(RaceResult { index, time });
+        }
+        Ok(results)
+    }
+}
+
+impl RaceRequest {
+    pub fn serialize(&self) -> Result<Value> {
+        Ok(json!({
+            "api_version": SYNC_API_VERSION,
+            "race_key": self.race_key,
+            "player": self.player,
+            "results": RaceResult::serialize_list(&self.results)
+        }))
+    }
+
+    pub fn deserialize(request: Value) -> Result<Self> {
+        let race_key = SyncRequest::validate_sync_key(
+            request
+                .get("race_key")
+                .ok_or_else(|| Error::Parse(anyhow!("Missing race key")))?
+                .as_str()
+                .ok_or_else(|| Error::Parse(anyhow!("Race key is not a string")))?,
+        )
+        .ok_or_else(|| Error::Parse(anyhow!("Invalid race key")))?;
+
+        let player = request
+            .get("player")
+            .ok_or_else(|| Error::Parse(anyhow!("Missing player name")))?
+            .as_str()
+            .ok_or_else(|| Error::Parse(anyhow!("Player name is not a string")))?
+            .to_string();
+
+        let results = RaceResult::deserialize_list(
+            request
+                .get("results")
+                .ok_or_else(|| Error::Parse(anyhow!("Missing race results")))?,
+        )?;
+
+        Ok(Self {
+            race_key,
+            player,
+            results,
+        })
+    }
+
+    /// Computes the seed used for scramble generation from the race key. All
+    /// clients in a race use this seed so that they receive identical scrambles.
+    pub fn seed(race_key: &str) -> u64 {
+        let mut value: u64 = 0xcbf29ce484222325;
+        for ch in race_key.chars().filter(|ch| *ch != '-') {
+            value ^= ch as u64;
+            value = value.wrapping_mul(0x100000001b3);
+        }
+        value
+    }
+}
+
+impl RaceResponse {
+    pub fn serialize(&self) -> Result<Value> {
+        let players: Vec<Value> = self
+            .players
+            .iter()
+            .map(|player| {
+                json!({
+                    "player": player.player,
+                    "results": RaceResult::serialize_list(&player.results)
+                })
+            })
+            .collect();
+        Ok(json!({ "players": players }))
+    }
+
+    pub fn deserialize(response: Value) -> Result<Self> {
+        let mut players = Vec::new();
+        for player in response
+            .get("players")
+            .ok_or_else(|| Error::Parse(anyhow!("Missing player list")))?
+            .as_array()
+            .ok_or_else(|| Error::Parse(anyhow!("Player list is not an array")))?
+        {
+            players.push(RacePlayerResults {
+                player: player
+                    .get("player")
+                    .ok_or_else(|| Error::Parse(anyhow!("Missing player name")))?
+                    .as_str()
+                    .ok_or_else(|| Error::Parse(anyhow!("Player name is not a string")))?
+                    .to_string(),
+                results: RaceResult::deserialize_list(
+                    player
+                        .get("results")
+                        .ok_or_else(|| Error::Parse(anyhow!("Missing race results")))?,
+                )?,
+            });
+        }
+        Ok(Self { players })
+    }
+}
+
+impl AccountOperation {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            AccountOperation::Create => "create",
+            AccountOperation::Login => "login",
+            AccountOperation::ListDevices => "devices",
+            AccountOperation::RevokeDevice(_) => "revoke",
+            AccountOperation::WipeData => "wipe",
+        }
+    }
+}
+
+impl AccountRequest {
+    pub fn serialize(&self) -> Result<Value> {
+        let mut request = json!({
+            "api_version": SYNC_API_VERSION,
+            "account": self.operation.to_str(),
+            "sync_key": self.sync_key,
+            "device_id": self.device_id,
+            "device_name": self.device_name
+        });
+        if let Some(token) = &self.token {
+            request["token"] = json!(token);
+        }
+        if let AccountOperation::RevokeDevice(device) = &self.operation {
+            request["revoke_device"] = json!(device);
+        }
+        Ok(request)
+    }
+
+    pub fn deserialize(request: Value) -> Result<Self> {
+        let sync_key = SyncRequest::validate_sync_key(
+            request
+                .get("sync_key")
+                .ok_or_else(|| Error::Parse(anyhow!("Missing sync key")))?
+                .as_str()
+                .ok_or_else(|| Error::Parse(anyhow!("Sync key is not a string")))?,
+        )
+        .ok_or_else(|| Error::Parse(anyhow!("Invalid sync key")))?;
+
+        let string_field = |name: &str| -> Result<String> {
+            Ok(request
+                .get(name)
+                .ok_or_else(|| Error::Parse(anyhow!("Missing {}", name.replace('_', " "))))?
+                .as_str()
+                .ok_or_else(|| Error::Parse(anyhow!("{} is not a string", name.replace('_', " "))))?
+                .to_string())
+        };
+        let device_id = string_field("device_id")?;
+        let device_name = string_field("device_name")?;
+        let token = match request.get("token") {
+            Some(_) => Some(string_field("token")?),
+            None => None,
+        };
+
+        let operation = match string_field("account")?.as_str() {
+            "create" => AccountOperation::Create,
+            "login" => AccountOperation::Login,
+            "devices" => AccountOperation::ListDevices,
+            "revoke" => AccountOperation::RevokeDevice(string_field("revoke_device")?),
+            "wipe" => AccountOperation::WipeData,
+            _ => return Err(Error::Parse(anyhow!("Unknown account operation"))),
+        };
+
+        Ok(Self {
+            sync_key,
+            device_id,
+            device_name,
+            token,
+            operation,
+        })
+    }
+}
+
+impl AccountResponse {
+    pub fn serialize(&self) -> Result<Value> {
+        let devices: Vec<Value> = self
+            .devices
+            .iter()
+            .map(|device| {
+                json!({
+                    "id": device.id,
+                    "name": device.name,
+                    "last_login": device.last_login
+                })
+            })
+            .collect();
+        Ok(match &self.token {
+            Some(token) => json!({ "token": token, "devices": devices }),
+            None => json!({ "devices": devices }),
+        })
+    }
+
+    pub fn deserialize(response: Value) -> Result<Self> {
+        let token = match response.get("token") {
+            Some(token) => Some(
+                token
+                    .as_str()
+                    .ok_or_else(|| Error::Parse(anyhow!("Token is not a string")))?
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let mut devices = Vec::new();
+        for device in response
+            .get("devices")
+            .ok_or_else(|| Error::Parse(anyhow!("Missing device list")))?
+            .as_array()
+            .ok_or_else(|| Error::Parse(anyhow!("Device list is not an array")))?
+        {
+            devices.push(AccountDevice {
+                id: device
+                    .get("id")
+                    .ok_or_else(|| Error::Parse(anyhow!("Missing device ID")))?
+                    .as_str()
+                    .ok_or_else(|| Error::Parse(anyhow!("Device ID is not a string")))?
+                    .to_string(),
+                name: device
+                    .get("name")
+                    .ok_or_else(|| Error::Parse(anyhow!("Missing device name")))?
+                    .as_str()
+                    .ok_or_else(|| Error::Parse(anyhow!("Device name is not a string")))?
+                    .to_string(),
+                last_login: device
+                    .get("last_login")
+                    .ok_or_else(|| Error::Parse(anyhow!("Missing device login time")))?
+                    .as_i64()
+                    .ok_or_else(|| Error::Parse(anyhow!("Device login time is not an integer")))?,
+            });
+        }
+
+        Ok(Self { token, devices })
+    }
+}
@@ -0,0 +1,166 @@
+use crate::error::{ErrorKind, Result};
+use crate::{Color, Cube, CubeFace, Move, SolveType};
+use image::codecs::png::PngEncoder;
+use image::{ColorType, Rgb, RgbImage};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+/// Position of each face in the flattened cube diagram, in units of faces.
+/// Indexed by `CubeFace`.
+const FACE_X: [u32; 6] = [1, 1, 2, 3, 0, 1];
+const FACE_Y: [u32; 6] = [0, 1, 1, 1, 1, 2];
+
+const BORDER_COLOR: [u8; 3] = [0x20, 0x20, 0x20];
+
+/// Image of the state of a cube after a scramble, shown as a flattened cube
+/// diagram with the top face above the front face and the bottom face below it.
+pub struct ScrambleImage {
+    size: usize,
+    colors: BTreeMap<CubeFace, Vec<Vec<Color>>>,
+}
+
+impl ScrambleImage {
+    /// Creates an image of the given cube state
+    pub fn from_cube(cube: &dyn Cube) -> Self {
+        Self {
+            size: cube.size(),
+            colors: cube.colors(),
+        }
+    }
+
+    /// Creates an image of the state after applying `scramble` to a solved
+    /// cube of the puzzle used by `solve_type`.
+    pub fn from_scramble(solve_type: SolveType, scramble: &[Move]) -> Self {
+        let mut cube = solve_type.new_cube();
+        cube.do_moves(scramble);
+        Self::from_cube(cube.as_ref())
+    }
+
+    /// Width of the image in pixels for a given sticker size
+    pub fn width(&self, sticker_size: u32) -> u32 {
+        self.face_size(sticker_size) * 4 + sticker_size / 4
+    }
+
+    /// Height of the image in pixels for a given sticker size
+    pub fn height(&self, sticker_size: u32) -> u32 {
+        self.face_size(sticker_size) * 3 + sticker_size / 4
+    }
+
+    fn face_size(&self, sticker_size: u32) -> u32 {
+        sticker_size * self.size as u32 + sticker_size / 4
+    }
+
+    /// Calls `func` with the position and color of each sticker in the diagram.
+    /// Positions are the top left corner of the sticker in pixels.
+    pub fn for_each_sticker<F: FnMut(u32, u32, Color)>(&self, sticker_size: u32, mut func: F) {
+        let gap = sticker_size / 4;
+        for face_idx in 0..6 {
+            let face = CubeFace::try_from(face_idx as u8).unwrap();
+            let face_x = FACE_X[face_idx] * self.face_size(sticker_size) + gap;
+            let face_y = FACE_Y[face_idx] * self.face_size(sticker_size) + gap;
+            if let Some(rows) = self.colors.get(&face) {
+                for (row, colors) in rows.iter().enumerate() {
+                    for (col, color) in colors.iter().enumerate() {
+                        func(
+                            face_x + col as u32 * sticker_size,
+                            face_y + row as u32 * sticker_size,
+                            *color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Renders the diagram as an SVG document
+    pub fn to_svg(&self, sticker_size: u32) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n{}</svg>\n",
+            self.width(sticker_size),
+            self.height(sticker_size),
+            self.svg_stickers(sticker_size, 0, 0)
+        )
+    }
+
+    /// Renders the stickers of the diagram as SVG elements with the top left
+    /// corner of the diagram at the given position, for embedding the diagram
+    /// into a larger document.
+    pub fn svg_stickers(&self, sticker_size: u32, x: u32, y: u32) -> String {
+        let mut result = String::new();
+        self.for_each_sticker(sticker_size, |sticker_x, sticker_y, color| {
+            let rgb = sticker_rgb(color);
+            result += &format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+                fill=\"#{:02x}{:02x}{:02x}\" stroke=\"#{:02x}{:02x}{:02x}\" stroke-width=\"{}\"/>\n",
+                x + sticker_x,
+                y + sticker_y,
+                sticker_size,
+                sticker_size,
+                rgb[0],
+                rgb[1],
+                rgb[2],
+                BORDER_COLOR[0],
+                BORDER_COLOR[1],
+                BORDER_COLOR[2],
+                (sticker_size / 16).max(1)
+            );
+        });
+        result
+    }
+
+    /// Renders the diagram as an RGB image. Areas outside of the cube faces
+    /// are white.
+    pub fn to_image(&self, sticker_size: u32) -> RgbImage {
+        let mut image = RgbImage::from_pixel(
+            self.width(sticker_size),
+            self.height(sticker_size),
+            Rgb([0xff, 0xff, 0xff]),
+        );
+        let border = (sticker_size / 16).max(1);
+        self.for_each_sticker(sticker_size, |x, y, color| {
+            for py in 0..=sticker_size {
+                for px in 0..=sticker_size {
+                    let edge = px < border
+                        || py < border
+                        || px + border > sticker_size
+                        || py + border > sticker_size;
+                    let pixel = if edge {
+                        BORDER_COLOR
+                    } else {
+                        sticker_rgb(color)
+                    };
+                    if x + px < image.width() && y + py < image.height() {
+                        image.put_pixel(x + px, y + py, Rgb(pixel));
+                    }
+                }
+            }
+        });
+        image
+    }
+
+    /// Renders the diagram as a PNG file
+    pub fn to_png(&self, sticker_size: u32) -> Result<Vec<u8>> {
+        let image = self.to_image(sticker_size);
+        let mut result = Vec::new();
+        PngEncoder::new(&mut result)
+            .encode(
+                image.as_raw(),
+                image.width(),
+                image.height(),
+                ColorType::Rgb8,
+            )
+            .parse_error()?;
+        Ok(result)
+    }
+}
+
+fn sticker_rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::White => [0xff, 0xff, 0xff],
+        Color::Green => [0x00, 0xb0, 0x40],
+        Color::Red => [0xd0, 0x10, 0x10],
+        Color::Blue => [0x10, 0x40, 0xe0],
+        Color::Orange => [0xff, 0x80, 0x00],
+        Color::Yellow => [0xff, 0xe0, 0x00],
+    }
+}
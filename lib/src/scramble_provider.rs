@@ -0,0 +1,115 @@
+use crate::error::Error;
+use crate::{
+    parse_move_string, scramble_2x2x2, scramble_3x3x3, scramble_big_cube_moves, Move, SolveType,
+    Variant3x3x3,
+};
+use anyhow::anyhow;
+use std::collections::VecDeque;
+
+/// Source of scrambles for the timer. The internal random state generators
+/// are used by default, and other sources, such as a file of scrambles
+/// prepared for a competition or a TNoodle compatible scramble server, can
+/// be used in their place by implementing this trait.
+pub trait ScrambleProvider: Send {
+    /// Name of the source for display
+    fn name(&self) -> String;
+
+    /// Gets the next scramble for a puzzle. Returns `None` if the source has
+    /// no scrambles for the puzzle, in which case the timer falls back to the
+    /// internal generators.
+    fn next_scramble(&mut self, solve_type: SolveType) -> Option<Vec<Move>>;
+
+    /// Number of scrambles left for a puzzle, if the source is finite
+    fn remaining(&self, _solve_type: SolveType) -> Option<usize> {
+        None
+    }
+}
+
+/// Random state scrambles from the internal solver
+pub struct GeneratedScrambles;
+
+/// Scrambles read from a text file, such as scrambles prepared ahead of time
+/// for a competition. Each line holds one scramble and may be numbered, as in
+/// the text files exported by TNoodle. Blank lines and lines starting with
+/// `#` are ignored. Scrambles are served in order for a single puzzle.
+pub struct ScrambleFile {
+    name: String,
+    solve_type: SolveType,
+    scrambles: VecDeque<Vec<Move>>,
+}
+
+impl ScrambleProvider for GeneratedScrambles {
+    fn name(&self) -> String {
+        "Random state".into()
+    }
+
+    fn next_scramble(&mut self, solve_type: SolveType) -> Option<Vec<Move>> {
+        Some(match solve_type {
+            SolveType::Standard2x2x2 => scramble_2x2x2(),
+            SolveType::Standard3x3x3
+            | SolveType::OneHanded3x3x3
+            | SolveType::Blind3x3x3
+            | SolveType::Void3x3x3 => scramble_3x3x3(),
+            SolveType::Bandaged3x3x3 => Variant3x3x3::for_solve_type(solve_type).scramble(),
+            SolveType::Standard5x5x5 | SolveType::Standard6x6x6 | SolveType::Standard7x7x7 => {
+                scramble_big_cube_moves(solve_type.cube_size())
+            }
+        })
+    }
+}
+
+impl ScrambleFile {
+    pub fn parse(name: String, solve_type: SolveType, contents: &str) -> crate::Result<Self> {
+        let mut scrambles = VecDeque::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.len() == 0 || line.starts_with('#') {
+                continue;
+            }
+
+            // Remove numbering such as "1." or "1)" from the start of the line
+            let line = match line.split_once(|ch: char| ch == '.' || ch == ')') {
+                Some((number, rest)) if number.trim().parse::<usize>().is_ok() => rest,
+                _ => line,
+            };
+
+            let scramble = parse_move_string(line).map_err(|error| {
+                Error::Parse(anyhow!("Invalid scramble on line {}: {}", idx + 1, error))
+            })?;
+            if scramble.len() != 0 {
+                scrambles.push_back(scramble);
+            }
+        }
+
+        if scrambles.len() == 0 {
+            return Err(Error::Parse(anyhow!("No scrambles found")));
+        }
+
+        Ok(Self {
+            name,
+            solve_type,
+            scrambles,
+        })
+    }
+}
+
+impl ScrambleProvider for ScrambleFile {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn next_scramble(&mut self, solve_type: SolveType) -> Option<Vec<Move>> {
+        if solve_type != self.solve_type {
+            return None;
+        }
+        self.scrambles.pop_front()
+    }
+
+    fn remaining(&self, solve_type: SolveType) -> Option<usize> {
+        if solve_type == self.solve_type {
+            Some(self.scrambles.len())
+        } else {
+            Some(0)
+        }
+    }
+}
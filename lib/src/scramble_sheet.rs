@@ -0,0 +1,233 @@
+use crate::{
+    scramble_2x2x2_sourced, scramble_3x3x3_batch_sourced, scramble_big_cube_moves_sourced, Move,
+    MoveSequence, RandomSource, ScrambleImage, SolveType, Variant3x3x3,
+};
+
+/// Number of scrambles in a standard WCA average of 5 round
+pub const STANDARD_SCRAMBLE_COUNT: usize = 5;
+
+/// Number of extra scrambles generated for each round, used when a scramble
+/// must be replaced during the round
+pub const STANDARD_EXTRA_SCRAMBLE_COUNT: usize = 2;
+
+const PAGE_WIDTH: u32 = 800;
+const MARGIN: u32 = 40;
+const HEADER_HEIGHT: u32 = 40;
+const ROUND_HEADER_HEIGHT: u32 = 36;
+/// Height of a scramble row. Rows are taller when the scramble or the diagram
+/// of a big cube does not fit.
+const ROW_HEIGHT: u32 = 136;
+const ROW_PADDING: u32 = 16;
+const LINE_HEIGHT: u32 = 22;
+const STICKER_SIZE: u32 = 12;
+const MOVES_PER_LINE: usize = 10;
+
+/// Scrambles for a single round of an event
+#[derive(Clone)]
+pub struct ScrambleSet {
+    pub scrambles: Vec<Vec<Move>>,
+    pub extras: Vec<Vec<Move>>,
+}
+
+/// Set of scrambles for all rounds of an event at a competition, which can be
+/// rendered into a printable document with a state diagram for each scramble.
+#[derive(Clone)]
+pub struct ScrambleSheet {
+    pub title: String,
+    pub solve_type: SolveType,
+    pub rounds: Vec<ScrambleSet>,
+}
+
+impl ScrambleSet {
+    pub fn generate<T: RandomSource>(
+        solve_type: SolveType,
+        scramble_count: usize,
+        extra_count: usize,
+        rng: &mut T,
+    ) -> Self {
+        let mut scrambles = generate_scrambles(solve_type, scramble_count + extra_count, rng);
+        let extras = scrambles.split_off(scramble_count);
+        Self { scrambles, extras }
+    }
+}
+
+impl ScrambleSheet {
+    /// Generates scrambles for `round_count` rounds, each with the standard
+    /// number of scrambles and extras.
+    pub fn generate<T: RandomSource>(
+        title: String,
+        solve_type: SolveType,
+        round_count: usize,
+        rng: &mut T,
+    ) -> Self {
+        Self::generate_with_counts(
+            title,
+            solve_type,
+            round_count,
+            STANDARD_SCRAMBLE_COUNT,
+            STANDARD_EXTRA_SCRAMBLE_COUNT,
+            rng,
+        )
+    }
+
+    pub fn generate_with_counts<T: RandomSource>(
+        title: String,
+        solve_type: SolveType,
+        round_count: usize,
+        scramble_count: usize,
+        extra_count: usize,
+        rng: &mut T,
+    ) -> Self {
+        // Generate scrambles for every round at once so that they can all be
+        // solved in parallel
+        let round_size = scramble_count + extra_count;
+        let mut remaining = generate_scrambles(solve_type, round_count * round_size, rng);
+        let rounds = (0..round_count)
+            .map(|_| {
+                let rest = remaining.split_off(round_size);
+                let mut scrambles = std::mem::replace(&mut remaining, rest);
+                let extras = scrambles.split_off(scramble_count);
+                ScrambleSet { scrambles, extras }
+            })
+            .collect();
+        Self {
+            title,
+            solve_type,
+            rounds,
+        }
+    }
+
+    /// Renders all rounds into a single SVG document. Each round starts with
+    /// a header, followed by one row per scramble containing the scramble
+    /// number, the scramble moves, and the state diagram.
+    pub fn to_svg(&self) -> String {
+        let mut body = String::new();
+        let mut y = MARGIN;
+
+        body += &text_element(
+            MARGIN,
+            y + 24,
+            24,
+            "bold",
+            &format!("{} - {}", self.title, self.solve_type.to_string()),
+        );
+        y += HEADER_HEIGHT;
+
+        for (round_idx, round) in self.rounds.iter().enumerate() {
+            body += &text_element(
+                MARGIN,
+                y + 24,
+                18,
+                "bold",
+                &format!("Round {}", round_idx + 1),
+            );
+            y += ROUND_HEADER_HEIGHT;
+
+            let rows = round
+                .scrambles
+                .iter()
+                .enumerate()
+                .map(|(idx, scramble)| (format!("{}", idx + 1), scramble))
+                .chain(
+                    round
+                        .extras
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, scramble)| (format!("E{}", idx + 1), scramble)),
+                );
+            for (label, scramble) in rows {
+                let (row, height) = self.scramble_row(&label, scramble, y);
+                body += &row;
+                y += height;
+            }
+        }
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n\
+            <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n{}</svg>\n",
+            PAGE_WIDTH,
+            y + MARGIN,
+            body
+        )
+    }
+
+    /// Renders the row for a scramble, returning the SVG elements and the
+    /// height of the row
+    fn scramble_row(&self, label: &str, scramble: &[Move], y: u32) -> (String, u32) {
+        let image = ScrambleImage::from_scramble(self.solve_type, scramble);
+
+        // Split long scrambles into multiple lines so they fit beside the diagram
+        let lines: Vec<&[Move]> = scramble.chunks(MOVES_PER_LINE).collect();
+        let row_height = ROW_HEIGHT
+            .max(image.height(STICKER_SIZE) + ROW_PADDING)
+            .max(lines.len() as u32 * LINE_HEIGHT + ROW_PADDING);
+
+        let image_x = PAGE_WIDTH - MARGIN - image.width(STICKER_SIZE);
+        let image_y = y + (row_height - image.height(STICKER_SIZE)) / 2;
+
+        let mut result = format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+            fill=\"none\" stroke=\"#000000\" stroke-width=\"1\"/>\n",
+            MARGIN,
+            y,
+            PAGE_WIDTH - MARGIN * 2,
+            row_height
+        );
+        result += &text_element(MARGIN + 12, y + row_height / 2 + 8, 20, "bold", label);
+
+        let text_y = y + row_height / 2 + 17 - (lines.len() as u32 * LINE_HEIGHT) / 2;
+        for (idx, line) in lines.iter().enumerate() {
+            result += &text_element(
+                MARGIN + 64,
+                text_y + idx as u32 * LINE_HEIGHT,
+                16,
+                "normal",
+                &line.to_string(),
+            );
+        }
+
+        result += &image.svg_stickers(STICKER_SIZE, image_x, image_y);
+        (result, row_height)
+    }
+}
+
+/// Generates scrambles in the order they are drawn from the random source
+pub(crate) fn generate_scrambles<T: RandomSource>(
+    solve_type: SolveType,
+    count: usize,
+    rng: &mut T,
+) -> Vec<Vec<Move>> {
+    match solve_type {
+        SolveType::Standard2x2x2 => (0..count).map(|_| scramble_2x2x2_sourced(rng)).collect(),
+        SolveType::Standard5x5x5 | SolveType::Standard6x6x6 | SolveType::Standard7x7x7 => {
+            let size = solve_type.cube_size();
+            (0..count)
+                .map(|_| scramble_big_cube_moves_sourced(size, rng))
+                .collect()
+        }
+        SolveType::Bandaged3x3x3 => {
+            let variant = Variant3x3x3::for_solve_type(solve_type);
+            (0..count).map(|_| variant.scramble_sourced(rng)).collect()
+        }
+        _ => scramble_3x3x3_batch_sourced(rng, count),
+    }
+}
+
+fn text_element(x: u32, y: u32, size: u32, weight: &str, text: &str) -> String {
+    format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" \
+        font-weight=\"{}\">{}</text>\n",
+        x,
+        y,
+        size,
+        weight,
+        escape_xml(text)
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
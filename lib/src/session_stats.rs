@@ -0,0 +1,124 @@
+use crate::common::{Average, BestSolve, ListAverage, Penalty, Solve};
+use crate::stats::RollingAverage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Sizes of the best averages kept in session statistics
+pub const SESSION_STATS_AVERAGE_SIZES: [usize; 4] = [5, 12, 50, 100];
+
+/// Statistics for a session. These are cached in storage so that the history
+/// does not need to recompute them from every solve each time it is opened.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SessionStats {
+    fingerprint: u64,
+    solve_count: usize,
+    average: Option<u32>,
+    best: Option<CachedTime>,
+    best_averages: BTreeMap<usize, CachedTime>,
+}
+
+/// Time with the index of the solve it starts at, so that the solves can be
+/// found again without storing them in the cache
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct CachedTime {
+    time: u32,
+    start: usize,
+}
+
+impl SessionStats {
+    /// Computes statistics for the solves of a session. The fingerprint is
+    /// used to check that the cached statistics are still up to date.
+    pub(crate) fn compute(fingerprint: u64, solves: &[Solve]) -> Self {
+        let mut best: Option<CachedTime> = None;
+        let mut averages: Vec<RollingAverage> = SESSION_STATS_AVERAGE_SIZES
+            .iter()
+            .map(|size| RollingAverage::new(*size))
+            .collect();
+        for (idx, solve) in solves.iter().enumerate() {
+            let time = solve.final_time();
+            if let Some(time) = time {
+                if best.map(|best| time < best.time).unwrap_or(true) {
+                    best = Some(CachedTime { time, start: idx });
+                }
+            }
+            for average in &mut averages {
+                average.push(time);
+            }
+        }
+
+        let best_averages = averages
+            .iter()
+            .filter_map(|average| {
+                let time = average.best()?;
+                let end = average.best_end()?;
+                Some((
+                    average.size(),
+                    CachedTime {
+                        time,
+                        start: end + 1 - average.size(),
+                    },
+                ))
+            })
+            .collect();
+
+        Self {
+            fingerprint,
+            solve_count: solves.len(),
+            average: solves.average(),
+            best,
+            best_averages,
+        }
+    }
+
+    pub(crate) fn valid_for(&self, fingerprint: u64, solve_count: usize) -> bool {
+        self.fingerprint == fingerprint && self.solve_count == solve_count
+    }
+
+    pub fn solve_count(&self) -> usize {
+        self.solve_count
+    }
+
+    pub fn average(&self) -> Option<u32> {
+        self.average
+    }
+
+    /// Best solve of the session. The solves must be the solves of the session
+    /// in chronological order, as returned by `Session::to_vec`.
+    pub fn best(&self, solves: &[Solve]) -> Option<BestSolve> {
+        let best = self.best?;
+        Some(BestSolve {
+            solve: solves.get(best.start)?.clone(),
+            time: best.time,
+        })
+    }
+
+    /// Best average of the given size, which must be one of
+    /// `SESSION_STATS_AVERAGE_SIZES`. The solves must be the solves of the
+    /// session in chronological order.
+    pub fn best_average(&self, size: usize, solves: &[Solve]) -> Option<Average> {
+        let best = self.best_averages.get(&size)?;
+        Some(Average {
+            solves: solves.get(best.start..best.start + size)?.to_vec(),
+            time: best.time,
+        })
+    }
+}
+
+/// Hash of a solve and its final time. The fingerprint of a session is the
+/// exclusive or of the hashes of its solves, so that it can be updated as
+/// solves are added, removed or penalized, and does not depend on the order
+/// that actions were resolved in.
+pub(crate) fn solve_fingerprint(id: &str, penalty: &Penalty, time: u32) -> u64 {
+    // 64-bit FNV-1a, which is stable across builds unlike the standard hasher
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let final_time = match penalty {
+        Penalty::None => time as u64,
+        Penalty::Time(penalty) => (time + penalty) as u64,
+        Penalty::DNF => u64::MAX,
+    };
+    for byte in id.bytes().chain(final_time.to_le_bytes().iter().cloned()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
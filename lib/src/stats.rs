@@ -0,0 +1,525 @@
+use crate::common::average_trim_count;
+use chrono::{DateTime, Duration, Local};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Minimum number of averages required to estimate improvement
+const MIN_ESTIMATE_AVERAGES: usize = 50;
+
+/// Size of the rolling average that progression is fitted to
+const ESTIMATE_AVERAGE_SIZE: usize = 12;
+
+/// Number of days before the most recent solve used to estimate how often the
+/// user practices
+const PRACTICE_RATE_DAYS: i64 = 30;
+
+/// Forecasts further than this many days in the future are not reported, as
+/// the model is not meaningful that far out
+const MAX_FORECAST_DAYS: f64 = 3650.0;
+
+/// Minimum number of completed solves needed before anomalies are detected
+const MIN_ANOMALY_SOLVES: usize = 5;
+
+/// Robust z-score beyond which a solve is considered anomalous
+const ANOMALY_SCORE_THRESHOLD: f64 = 3.5;
+
+/// Anomalous solves must also be at least this many times slower, or this many
+/// times faster, than the median so that tightly grouped sessions do not flag
+/// ordinary bad solves
+const ANOMALY_MEDIAN_RATIO: f64 = 2.5;
+
+/// Scale factor that makes the median absolute deviation consistent with the
+/// standard deviation for normally distributed values
+const MAD_SCALE: f64 = 1.4826;
+
+/// Most solves at the start of a session that can be counted as warmup
+const MAX_WARMUP_SOLVES: usize = 5;
+
+/// Minimum number of completed solves after the possible warmup solves needed
+/// to judge the pace of a session
+const MIN_WARMUP_BASELINE_SOLVES: usize = 7;
+
+/// Warmup solves are at least this much slower than the median of the rest
+/// of the session
+const WARMUP_SLOWDOWN_RATIO: f64 = 1.1;
+
+/// Incrementally computes the average of the most recent values in a stream,
+/// using the same trimming rules as `ListAverage`. A value of `None` is a
+/// DNF and counts as the worst possible time.
+///
+/// The window is kept split into three sorted multisets: the trimmed fastest
+/// values, the values counted in the average, and the trimmed slowest values.
+/// Each new value takes `O(log size)` time, so that averages over large
+/// windows and long histories stay fast.
+pub struct RollingAverage {
+    size: usize,
+    trim: usize,
+    window: VecDeque<Option<u32>>,
+    low: SortedMultiset,
+    mid: SortedMultiset,
+    high: SortedMultiset,
+    count: usize,
+    best: Option<(u32, usize)>,
+}
+
+/// Multiset of window values. DNFs are stored as a key greater than any time
+/// so that they sort as the slowest values.
+struct SortedMultiset {
+    values: BTreeMap<u64, usize>,
+    len: usize,
+    sum: u64,
+    dnf_count: usize,
+}
+
+const DNF_KEY: u64 = u64::MAX;
+
+impl RollingAverage {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            trim: average_trim_count(size),
+            window: VecDeque::with_capacity(size + 1),
+            low: SortedMultiset::new(),
+            mid: SortedMultiset::new(),
+            high: SortedMultiset::new(),
+            count: 0,
+            best: None,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Adds a value to the stream and returns the average of the last `size`
+    /// values, or `None` if there are not yet enough values or the average
+    /// is a DNF.
+    pub fn push(&mut self, value: Option<u32>) -> Option<u32> {
+        if self.size == 0 {
+            return None;
+        }
+
+        self.window.push_back(value);
+        self.insert(value.map(|value| value as u64).unwrap_or(DNF_KEY));
+        if self.window.len() > self.size {
+            let removed = self.window.pop_front().unwrap();
+            self.remove(removed.map(|value| value as u64).unwrap_or(DNF_KEY));
+        }
+        self.rebalance();
+        self.count += 1;
+
+        let current = self.current();
+        if let Some(time) = current {
+            let improved = match self.best {
+                Some((best, _)) => time < best,
+                None => true,
+            };
+            if improved {
+                self.best = Some((time, self.count - 1));
+            }
+        }
+        current
+    }
+
+    /// Average of the last `size` values pushed into the stream
+    pub fn current(&self) -> Option<u32> {
+        if self.size == 0 || self.window.len() < self.size || self.mid.dnf_count > 0 {
+            return None;
+        }
+        let len = self.mid.len as u64;
+        Some(((self.mid.sum + len / 2) / len) as u32)
+    }
+
+    /// Best average seen so far in the stream, or `None` if every average
+    /// was a DNF
+    pub fn best(&self) -> Option<u32> {
+        self.best.map(|(time, _)| time)
+    }
+
+    /// Index of the last value in the window of the best average, counting
+    /// from the first value pushed into the stream. When several windows have
+    /// the same average, the earliest is used.
+    pub fn best_end(&self) -> Option<usize> {
+        self.best.map(|(_, end)| end)
+    }
+
+    fn insert(&mut self, key: u64) {
+        if self.low.max().map(|max| key < max).unwrap_or(false) {
+            self.low.insert(key);
+        } else if self.high.min().map(|min| key > min).unwrap_or(false) {
+            self.high.insert(key);
+        } else {
+            self.mid.insert(key);
+        }
+    }
+
+    fn remove(&mut self, key: u64) {
+        if !self.low.remove(key) && !self.high.remove(key) {
+            self.mid.remove(key);
+        }
+    }
+
+    /// Moves values between the sets so that the trimmed sets hold exactly
+    /// the `trim` fastest and slowest values, when there are enough values
+    fn rebalance(&mut self) {
+        while self.low.len > self.trim {
+            let key = self.low.max().unwrap();
+            self.low.remove(key);
+            self.mid.insert(key);
+        }
+        while self.high.len > self.trim {
+            let key = self.high.min().unwrap();
+            self.high.remove(key);
+            self.mid.insert(key);
+        }
+        while self.low.len < self.trim && self.mid.len > 0 {
+            let key = self.mid.min().unwrap();
+            self.mid.remove(key);
+            self.low.insert(key);
+        }
+        while self.high.len < self.trim && self.mid.len > 0 {
+            let key = self.mid.max().unwrap();
+            self.mid.remove(key);
+            self.high.insert(key);
+        }
+    }
+}
+
+impl SortedMultiset {
+    fn new() -> Self {
+        Self {
+            values: BTreeMap::new(),
+            len: 0,
+            sum: 0,
+            dnf_count: 0,
+        }
+    }
+
+    fn min(&self) -> Option<u64> {
+        self.values.keys().next().cloned()
+    }
+
+    fn max(&self) -> Option<u64> {
+        self.values.keys().next_back().cloned()
+    }
+
+    fn insert(&mut self, key: u64) {
+        *self.values.entry(key).or_insert(0) += 1;
+        self.len += 1;
+        if key == DNF_KEY {
+            self.dnf_count += 1;
+        } else {
+            self.sum += key;
+        }
+    }
+
+    /// Removes one instance of the key. Returns false if the key is not in
+    /// the set.
+    fn remove(&mut self, key: u64) -> bool {
+        match self.values.get_mut(&key) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.values.remove(&key);
+                }
+            }
+            None => return false,
+        }
+        self.len -= 1;
+        if key == DNF_KEY {
+            self.dnf_count -= 1;
+        } else {
+            self.sum -= key;
+        }
+        true
+    }
+}
+
+/// Computes the rolling average of `size` values ending at each entry of
+/// `values`. The result has the same length as `values`.
+pub fn rolling_averages(values: &[Option<u32>], size: usize) -> Vec<Option<u32>> {
+    let mut average = RollingAverage::new(size);
+    values.iter().map(|value| average.push(*value)).collect()
+}
+
+/// Reason a solve looks anomalous compared to the rest of its session
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnomalyKind {
+    /// Much faster than the other solves, likely a timer misfire. These
+    /// should usually be deleted.
+    Misfire,
+    /// Much slower than the other solves, likely a timer that was not stopped
+    /// in time. These should usually be marked as DNF.
+    Unstopped,
+}
+
+/// Solve that is statistically anomalous within its session
+#[derive(Clone, Copy, Debug)]
+pub struct SolveAnomaly {
+    /// Index of the solve in the list of values passed to `detect_anomalies`
+    pub index: usize,
+    pub kind: AnomalyKind,
+    /// Robust z-score of the solve, measured in scaled median absolute
+    /// deviations from the median
+    pub score: f64,
+}
+
+/// Estimate of a user's improvement over time, fitted to the rolling average
+/// of 12 of their solve history for a single puzzle. Times are modeled with a
+/// power law learning curve, where the average after `n` solves is
+/// `scale * n ^ -exponent`.
+pub struct ImprovementEstimate {
+    scale: f64,
+    exponent: f64,
+    solve_count: usize,
+    solves_per_day: f64,
+    last_solve: DateTime<Local>,
+}
+
+impl ImprovementEstimate {
+    /// Fits the model to a solve history. `times` and `values` are the creation
+    /// time and final time of each solve in chronological order. Returns `None`
+    /// if there is not enough history to make an estimate.
+    pub fn fit(times: &[DateTime<Local>], values: &[Option<u32>]) -> Option<Self> {
+        let last_solve = *times.last()?;
+        let averages = rolling_averages(values, ESTIMATE_AVERAGE_SIZE);
+
+        // Least squares fit of a line in log-log space
+        let points: Vec<(f64, f64)> = averages
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, average)| {
+                average.map(|average| (((idx + 1) as f64).ln(), (average.max(1) as f64).ln()))
+            })
+            .collect();
+        if points.len() < MIN_ESTIMATE_AVERAGES {
+            return None;
+        }
+        let count = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+        let covariance = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum::<f64>();
+        let variance = points
+            .iter()
+            .map(|(x, _)| (x - mean_x) * (x - mean_x))
+            .sum::<f64>();
+        if variance <= 0.0 {
+            return None;
+        }
+        let slope = covariance / variance;
+        let intercept = mean_y - slope * mean_x;
+
+        // Estimate practice rate from recent solves, falling back to the entire
+        // history if there were no recent solves
+        let recent_start = last_solve - Duration::days(PRACTICE_RATE_DAYS);
+        let recent = times.iter().filter(|time| **time > recent_start).count();
+        let solves_per_day = if recent > 1 {
+            recent as f64 / PRACTICE_RATE_DAYS as f64
+        } else {
+            let days = (last_solve - times[0]).num_seconds() as f64 / 86400.0;
+            times.len() as f64 / days.max(1.0)
+        };
+
+        Some(Self {
+            scale: intercept.exp(),
+            exponent: -slope,
+            solve_count: values.len(),
+            solves_per_day,
+            last_solve,
+        })
+    }
+
+    /// Predicted average of 12 after the given number of solves
+    pub fn predicted_average(&self, solve_count: usize) -> u32 {
+        (self.scale * (solve_count.max(1) as f64).powf(-self.exponent)) as u32
+    }
+
+    /// Predicted average of 12 at the current point in the history
+    pub fn current_average(&self) -> u32 {
+        self.predicted_average(self.solve_count)
+    }
+
+    /// Returns true if the history shows times getting faster
+    pub fn improving(&self) -> bool {
+        self.exponent > 0.0
+    }
+
+    /// Expected improvement of the average in milliseconds over the next `solves`
+    /// solves. Negative if times are getting slower.
+    pub fn improvement_over_solves(&self, solves: usize) -> i64 {
+        self.current_average() as i64 - self.predicted_average(self.solve_count + solves) as i64
+    }
+
+    /// Expected improvement of the average in milliseconds over the next 30
+    /// days at the current practice rate
+    pub fn monthly_improvement(&self) -> i64 {
+        self.improvement_over_solves((self.solves_per_day * 30.0) as usize)
+    }
+
+    /// Number of additional solves expected before the average reaches the
+    /// target time in milliseconds. Returns `None` if the target is not
+    /// expected to be reached.
+    pub fn solves_to_reach(&self, target: u32) -> Option<usize> {
+        if self.current_average() <= target {
+            return Some(0);
+        }
+        if !self.improving() || target == 0 {
+            return None;
+        }
+        let needed = (self.scale / target as f64).powf(1.0 / self.exponent);
+        if !needed.is_finite() || needed > self.solve_count as f64 + self.max_forecast_solves() {
+            return None;
+        }
+        Some((needed.ceil() as usize).saturating_sub(self.solve_count))
+    }
+
+    /// Date at which the average is expected to reach the target time in
+    /// milliseconds, assuming the user keeps practicing at their recent rate
+    pub fn forecast(&self, target: u32) -> Option<DateTime<Local>> {
+        let solves = self.solves_to_reach(target)?;
+        if solves == 0 {
+            return Some(self.last_solve);
+        }
+        if self.solves_per_day <= 0.0 {
+            return None;
+        }
+        let days = solves as f64 / self.solves_per_day;
+        Some(self.last_solve + Duration::seconds((days * 86400.0) as i64))
+    }
+
+    fn max_forecast_solves(&self) -> f64 {
+        self.solves_per_day * MAX_FORECAST_DAYS
+    }
+}
+
+/// Finds solves that are statistically anomalous, such as timer misfires or
+/// timers that were left running. Solves are compared against the median and
+/// median absolute deviation of the list so that the anomalies themselves do
+/// not skew the detection. DNFs are ignored.
+pub fn detect_anomalies(values: &[Option<u32>]) -> Vec<SolveAnomaly> {
+    let mut sorted: Vec<f64> = values.iter().filter_map(|v| v.map(|v| v as f64)).collect();
+    if sorted.len() < MIN_ANOMALY_SOLVES {
+        return Vec::new();
+    }
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let median = median_of_sorted(&sorted);
+    if median <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut deviations: Vec<f64> = sorted.iter().map(|v| (v - median).abs()).collect();
+    deviations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    // Guard against a zero deviation when many solves have nearly the same time
+    let deviation = (median_of_sorted(&deviations) * MAD_SCALE).max(median * 0.01);
+
+    values
+        .iter()
+        .enumerate()
+        .filter_map(|(index, value)| {
+            let value = (*value)? as f64;
+            let score = (value - median) / deviation;
+            let kind = if score > ANOMALY_SCORE_THRESHOLD && value > median * ANOMALY_MEDIAN_RATIO {
+                AnomalyKind::Unstopped
+            } else if score < -ANOMALY_SCORE_THRESHOLD && value < median / ANOMALY_MEDIAN_RATIO {
+                AnomalyKind::Misfire
+            } else {
+                return None;
+            };
+            Some(SolveAnomaly { index, kind, score })
+        })
+        .collect()
+}
+
+/// Counts the warmup solves at the start of a session. These are the leading
+/// solves that are DNFs or significantly slower than the median of the solves
+/// after them. At most the first few solves are considered, and sessions that
+/// are too short to establish a pace have no warmup.
+pub fn detect_warmup(values: &[Option<u32>]) -> usize {
+    if values.len() <= MAX_WARMUP_SOLVES {
+        return 0;
+    }
+    let mut sorted: Vec<f64> = values[MAX_WARMUP_SOLVES..]
+        .iter()
+        .filter_map(|v| v.map(|v| v as f64))
+        .collect();
+    if sorted.len() < MIN_WARMUP_BASELINE_SOLVES {
+        return 0;
+    }
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let threshold = median_of_sorted(&sorted) * WARMUP_SLOWDOWN_RATIO;
+
+    values[..MAX_WARMUP_SOLVES]
+        .iter()
+        .take_while(|value| match value {
+            Some(value) => *value as f64 > threshold,
+            None => true,
+        })
+        .count()
+}
+
+fn median_of_sorted(values: &[f64]) -> f64 {
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Computes the standard deviation of the values in the list, ignoring DNFs.
+/// Returns `None` if there are fewer than two valid values.
+pub fn standard_deviation(values: &[Option<u32>]) -> Option<u32> {
+    let values: Vec<f64> = values
+        .iter()
+        .filter_map(|value| value.map(|value| value as f64))
+        .collect();
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean) * (value - mean))
+        .sum::<f64>()
+        / (values.len() - 1) as f64;
+    Some((variance.sqrt() + 0.5) as u32)
+}
+
+/// Computes the standard deviation of the `size` values ending at each entry
+/// of `values`. The result has the same length as `values`.
+pub fn rolling_standard_deviations(values: &[Option<u32>], size: usize) -> Vec<Option<u32>> {
+    (0..values.len())
+        .map(|idx| {
+            if size == 0 || idx + 1 < size {
+                None
+            } else {
+                standard_deviation(&values[idx + 1 - size..=idx])
+            }
+        })
+        .collect()
+}
+
+/// Counts the number of valid values in each bucket of width `bucket_size`.
+/// Returns the start of each bucket with its count, starting at the bucket
+/// of the smallest value and ending at the bucket of the largest value.
+/// Empty buckets between these are included.
+pub fn histogram(values: &[Option<u32>], bucket_size: u32) -> Vec<(u32, usize)> {
+    let bucket_size = bucket_size.max(1);
+    let valid: Vec<u32> = values.iter().filter_map(|value| *value).collect();
+    let min_bucket = match valid.iter().min() {
+        Some(min) => min / bucket_size,
+        None => return Vec::new(),
+    };
+    let max_bucket = valid.iter().max().unwrap() / bucket_size;
+    let mut counts = vec![0; (max_bucket - min_bucket) as usize + 1];
+    for value in valid {
+        counts[(value / bucket_size - min_bucket) as usize] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(idx, count)| ((min_bucket + idx as u32) * bucket_size, count))
+        .collect()
+}
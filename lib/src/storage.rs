@@ -1,6 +1,6 @@
 use crate::future::spawn_future;
 use anyhow::Result;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 
 #[cfg(feature = "native-storage")]
@@ -19,13 +19,20 @@ use wasm_bindgen_futures::JsFuture;
 #[cfg(feature = "web-storage")]
 use web_sys::{IdbDatabase, IdbTransactionMode};
 
+pub(crate) enum Storage {
+    Database(Database),
+    /// Keeps everything in memory and discards it when the history is
+    /// closed. Used for guest sessions that must not leave anything behind.
+    Memory(HashMap<String, Vec<u8>>),
+}
+
 #[cfg(feature = "native-storage")]
-pub(crate) struct Storage {
+pub(crate) struct Database {
     db: DB,
 }
 
 #[cfg(feature = "web-storage")]
-pub(crate) struct Storage {
+pub(crate) struct Database {
     db: IdbDatabase,
 }
 
@@ -38,6 +45,7 @@ enum StorageQueueItem {
     Put(String, Vec<u8>),
     Delete(String),
     Flush,
+    Compact,
 }
 
 struct StorageQueue {
@@ -45,14 +53,71 @@ struct StorageQueue {
     items: VecDeque<StorageQueueItem>,
 }
 
-#[cfg(feature = "native-storage")]
 impl Storage {
+    #[cfg(feature = "native-storage")]
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Storage::Database(Database::open(path)?))
+    }
+
+    #[cfg(feature = "web-storage")]
+    pub async fn new() -> Result<Self> {
+        Ok(Storage::Database(Database::new().await?))
+    }
+
+    pub fn in_memory() -> Self {
+        diagnostic!(info, "Using in-memory storage");
+        Storage::Memory(HashMap::new())
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Storage::Database(db) => db.get(key).await,
+            Storage::Memory(values) => Ok(values.get(key).cloned()),
+        }
+    }
+
+    pub async fn put(&mut self, key: &str, value: &[u8]) -> Result<()> {
+        match self {
+            Storage::Database(db) => db.put(key, value).await,
+            Storage::Memory(values) => {
+                values.insert(key.to_string(), value.to_vec());
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn delete(&mut self, key: &str) -> Result<()> {
+        match self {
+            Storage::Database(db) => db.delete(key).await,
+            Storage::Memory(values) => {
+                values.remove(key);
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn flush(&self) {
+        if let Storage::Database(db) = self {
+            db.flush().await;
+        }
+    }
+
+    pub async fn compact(&self) {
+        if let Storage::Database(db) = self {
+            db.compact().await;
+        }
+    }
+}
+
+#[cfg(feature = "native-storage")]
+impl Database {
     pub fn open(path: &Path) -> Result<Self> {
         let mut opts = Options::default();
         opts.create_if_missing(true);
         opts.set_compression_type(DBCompressionType::Zstd);
         opts.set_keep_log_file_num(8);
         let db = DB::open(&opts, path)?;
+        diagnostic!(info, path = %path.display(), "Opened database");
         Ok(Self { db })
     }
 
@@ -71,10 +136,16 @@ impl Storage {
     pub async fn flush(&self) {
         let _ = self.db.flush();
     }
+
+    /// Compacts the database files so that space used by deleted keys is
+    /// reclaimed
+    pub async fn compact(&self) {
+        self.db.compact_range::<&[u8], &[u8]>(None, None);
+    }
 }
 
 #[cfg(feature = "web-storage")]
-impl Storage {
+impl Database {
     pub async fn new() -> Result<Self> {
         // Create database open request
         let open_request = web_sys::window()
@@ -139,6 +210,7 @@ impl Storage {
             .dyn_into()
             .unwrap();
 
+        diagnostic!(info, "Opened IndexedDB database");
         Ok(Self { db })
     }
 
@@ -285,6 +357,10 @@ impl Storage {
     }
 
     pub async fn flush(&self) {}
+
+    /// The browser manages the space used by IndexedDB, so there is nothing
+    /// to compact
+    pub async fn compact(&self) {}
 }
 
 impl DeferredStorage {
@@ -318,6 +394,7 @@ impl DeferredStorage {
             let queue = self.queue.clone();
             let error_message = self.error.clone();
             spawn_future(async move {
+                diagnostic!(trace, "Processing storage queue");
                 loop {
                     let item = {
                         // Lock the queue lock only while checking for new items
@@ -343,8 +420,16 @@ impl DeferredStorage {
                     match item {
                         StorageQueueItem::Put(key, value) => {
                             match storage.put(&key, &value).await {
-                                Ok(_) => (),
+                                Ok(_) => {
+                                    diagnostic!(trace, key = %key, bytes = value.len(), "Put");
+                                }
                                 Err(error) => {
+                                    diagnostic!(
+                                        error,
+                                        key = %key,
+                                        "Storage write failed: {}",
+                                        error
+                                    );
                                     // On error set the error string and abort all future work,
                                     // as it may corrupt the database. Simply not handing the
                                     // storage ownership back to the `DeferredStorage` instance
@@ -356,8 +441,16 @@ impl DeferredStorage {
                         }
                         StorageQueueItem::Delete(key) => {
                             match storage.delete(&key).await {
-                                Ok(_) => (),
+                                Ok(_) => {
+                                    diagnostic!(trace, key = %key, "Delete");
+                                }
                                 Err(error) => {
+                                    diagnostic!(
+                                        error,
+                                        key = %key,
+                                        "Storage delete failed: {}",
+                                        error
+                                    );
                                     // On error set the error string and abort all future work,
                                     // as it may corrupt the database. Simply not handing the
                                     // storage ownership back to the `DeferredStorage` instance
@@ -367,7 +460,14 @@ impl DeferredStorage {
                                 }
                             }
                         }
-                        StorageQueueItem::Flush => storage.flush().await,
+                        StorageQueueItem::Flush => {
+                            storage.flush().await;
+                            diagnostic!(trace, "Flush");
+                        }
+                        StorageQueueItem::Compact => {
+                            storage.compact().await;
+                            diagnostic!(info, "Compacted database");
+                        }
                     }
                 }
             });
@@ -386,6 +486,10 @@ impl DeferredStorage {
         self.push(StorageQueueItem::Flush);
     }
 
+    pub fn compact(&self) {
+        self.push(StorageQueueItem::Compact);
+    }
+
     pub fn check_for_error(&self) -> Option<String> {
         self.error.lock().unwrap().clone()
     }
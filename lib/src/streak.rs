@@ -0,0 +1,185 @@
+use crate::history::History;
+use chrono::{Local, NaiveDate, Timelike};
+use std::collections::BTreeMap;
+
+/// Local setting holding the longest streak reached on this device
+const LONGEST_STREAK_SETTING: &str = "longest_practice_streak";
+
+/// Hour of the day, in local time, after which a streak that has not been
+/// extended yet today is reported as at risk
+pub const STREAK_REMINDER_HOUR: u32 = 18;
+
+/// Consecutive days of practice. A day counts toward the streak if it has at
+/// least the daily solve goal in solves, or at least one solve if there is no
+/// daily goal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PracticeStreak {
+    /// Solves needed for a day to count toward the streak
+    pub daily_goal: usize,
+    pub today_solves: usize,
+    /// Length of the current streak in days. Today is included once its goal
+    /// is met. Until then the streak ending yesterday is still current.
+    pub current: u32,
+    pub longest: u32,
+}
+
+/// Event emitted by `StreakTracker` for the GUI to nudge the user
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreakEvent {
+    /// The current streak will end if today's goal is not met
+    AtRisk {
+        streak: u32,
+        remaining_solves: usize,
+    },
+    /// Today's goal was met, extending the streak to the given length
+    Extended(u32),
+}
+
+/// Watches the history for changes to the practice streak and reports
+/// events as they happen. Each event is reported at most once per day.
+pub struct StreakTracker {
+    update_id: Option<u64>,
+    date: Option<NaiveDate>,
+    reminded: Option<NaiveDate>,
+    extended: Option<NaiveDate>,
+    streak: Option<PracticeStreak>,
+}
+
+impl PracticeStreak {
+    /// Computes the streak from the dates of all solves. Dates do not need
+    /// to be sorted.
+    pub fn compute<I: Iterator<Item = NaiveDate>>(
+        dates: I,
+        daily_goal: usize,
+        today: NaiveDate,
+    ) -> Self {
+        let daily_goal = daily_goal.max(1);
+        let mut days: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+        for date in dates {
+            *days.entry(date).or_insert(0) += 1;
+        }
+
+        // Find the longest run of consecutive days that met the goal, and the
+        // run that ends today or yesterday
+        let mut longest = 0;
+        let mut run = 0;
+        let mut last_day: Option<NaiveDate> = None;
+        for (day, count) in &days {
+            if *count < daily_goal || *day > today {
+                continue;
+            }
+            run = match last_day {
+                Some(last_day) if last_day.succ() == *day => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+            last_day = Some(*day);
+        }
+        let current = match last_day {
+            Some(day) if day == today || day.succ() == today => run,
+            _ => 0,
+        };
+
+        Self {
+            daily_goal,
+            today_solves: days.get(&today).cloned().unwrap_or(0),
+            current,
+            longest,
+        }
+    }
+
+    /// Computes the streak for the solves of every session. The longest
+    /// streak is also kept in local storage, so that it is not lost when old
+    /// solves are deleted.
+    pub fn for_history(history: &mut History) -> Self {
+        let mut streak = Self::compute(
+            history
+                .iter()
+                .map(|solve| solve.created.date().naive_local()),
+            history.preferences().daily_solve_goal() as usize,
+            Local::now().date().naive_local(),
+        );
+        let stored = history
+            .setting_as_i64(LONGEST_STREAK_SETTING)
+            .unwrap_or(0)
+            .max(0) as u32;
+        if streak.longest > stored {
+            let _ = history.set_i64_setting(LONGEST_STREAK_SETTING, streak.longest as i64);
+        } else {
+            streak.longest = stored;
+        }
+        streak
+    }
+
+    pub fn goal_met_today(&self) -> bool {
+        self.today_solves >= self.daily_goal
+    }
+
+    /// Solves still needed today to extend the streak
+    pub fn remaining_today(&self) -> usize {
+        self.daily_goal.saturating_sub(self.today_solves)
+    }
+
+    /// Returns true if there is a streak that ends unless the goal is met
+    /// today
+    pub fn at_risk(&self) -> bool {
+        self.current > 0 && !self.goal_met_today()
+    }
+}
+
+impl StreakTracker {
+    pub fn new() -> Self {
+        Self {
+            update_id: None,
+            date: None,
+            reminded: None,
+            extended: None,
+            streak: None,
+        }
+    }
+
+    /// Current streak, as of the last call to `update`
+    pub fn streak(&self) -> Option<&PracticeStreak> {
+        self.streak.as_ref()
+    }
+
+    /// Checks the history for streak events. This should be called
+    /// periodically, and is cheap when the history has not changed.
+    pub fn update(&mut self, history: &mut History) -> Vec<StreakEvent> {
+        let now = Local::now();
+        let today = now.date().naive_local();
+        let update_id = history.update_id();
+        let first_update = self.streak.is_none();
+        if self.update_id != Some(update_id) || self.date != Some(today) {
+            self.update_id = Some(update_id);
+            self.date = Some(today);
+            self.streak = Some(PracticeStreak::for_history(history));
+        }
+        let streak = match &self.streak {
+            Some(streak) => *streak,
+            None => return Vec::new(),
+        };
+
+        let mut events = Vec::new();
+        if streak.goal_met_today() {
+            // Don't report a streak that was already extended before the
+            // tracker was created
+            if first_update {
+                self.extended = Some(today);
+            } else if self.extended != Some(today) {
+                self.extended = Some(today);
+                events.push(StreakEvent::Extended(streak.current));
+            }
+        } else if streak.at_risk()
+            && now.hour() >= STREAK_REMINDER_HOUR
+            && self.reminded != Some(today)
+        {
+            self.reminded = Some(today);
+            events.push(StreakEvent::AtRisk {
+                streak: streak.current,
+                remaining_solves: streak.remaining_today(),
+            });
+        }
+        events
+    }
+}
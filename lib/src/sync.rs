@@ -1,14 +1,20 @@
-use crate::request::{SyncRequest, SyncResponse};
+use crate::request::{RetryAfter, SyncRequest, SyncResponse};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
 use serde_json::Value;
 use std::sync::{Arc, Mutex};
 
+#[cfg(feature = "native-storage")]
+use crate::lan_sync::post_lan;
+#[cfg(feature = "native-storage")]
+use std::net::SocketAddr;
+
 #[cfg(feature = "native-storage")]
 use http::StatusCode;
 #[cfg(feature = "native-storage")]
 use reqwest::{
     blocking::Client,
-    header::{HeaderValue, CONTENT_TYPE, USER_AGENT},
+    header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER, USER_AGENT},
 };
 
 #[cfg(feature = "web-storage")]
@@ -16,9 +22,23 @@ use wasm_bindgen::JsValue;
 
 const ENDPOINT: &'static str = "https://api.tpscube.xyz/sync";
 
+/// Where sync requests are sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncEndpoint {
+    Cloud,
+    /// A device hosting sync on the local network
+    #[cfg(feature = "native-storage")]
+    Lan(SocketAddr),
+}
+
 pub(crate) struct SyncOperation {
     request: SyncRequest,
+    #[cfg_attr(not(feature = "native-storage"), allow(dead_code))]
+    endpoint: SyncEndpoint,
     response: Option<Result<SyncResponse>>,
+    request_bytes: usize,
+    response_bytes: usize,
+    round_trip_ms: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -29,19 +49,206 @@ pub enum SyncStatus {
     SyncComplete,
 }
 
+/// Diagnostic details about the current or most recent sync. A sync can take
+/// several requests when the server has more actions than fit in a single
+/// response, so the transfer counts cover every request made by the sync.
+#[derive(Clone, Debug, Default)]
+pub struct SyncDetails {
+    /// Number of requests completed by the sync
+    pub requests: usize,
+    pub uploaded_bytes: usize,
+    pub downloaded_bytes: usize,
+    pub uploaded_actions: usize,
+    pub downloaded_actions: usize,
+    /// Local actions that have not been uploaded yet. These are kept in
+    /// storage until a sync uploads them.
+    pub pending_actions: usize,
+    /// Failed syncs since the last successful sync
+    pub retries: u32,
+    /// Time until the next automatic retry, if one is scheduled
+    pub next_retry_ms: Option<u64>,
+    /// Round trip time of the most recent request
+    pub round_trip_ms: Option<u64>,
+    /// Error from the most recent sync, cleared when a sync completes
+    pub last_error: Option<String>,
+    pub last_complete: Option<DateTime<Local>>,
+}
+
 #[cfg(feature = "web-storage")]
-fn spawn_future<F>(future: F)
+pub(crate) fn spawn_future<F>(future: F)
 where
     F: std::future::Future<Output = ()> + 'static,
 {
     wasm_bindgen_futures::spawn_local(future);
 }
 
+#[cfg(feature = "native-storage")]
+pub(crate) fn post_native(request: String) -> Result<Value> {
+    diagnostic!(debug, bytes = request.len(), "Sending request");
+    #[cfg(feature = "diagnostics")]
+    let start = instant::Instant::now();
+
+    match post_native_request(request) {
+        Ok(response) => {
+            diagnostic!(
+                debug,
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Request complete"
+            );
+            Ok(response)
+        }
+        Err(error) => {
+            diagnostic!(
+                warn,
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Request failed: {}",
+                error
+            );
+            Err(error)
+        }
+    }
+}
+
+#[cfg(feature = "native-storage")]
+fn post_native_request(request: String) -> Result<Value> {
+    let client = Client::new();
+    let result = client
+        .post(ENDPOINT)
+        .header(USER_AGENT, HeaderValue::from_static("tpscube"))
+        .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .body(request)
+        .send()?;
+
+    // Check status code
+    if result.status().is_success() {
+        // Request success, return response for deserialization by the caller
+        Ok(result.json()?)
+    } else if result.status() == StatusCode::BAD_REQUEST {
+        // Bad request status contains a message from the server, decode it and pass
+        // it along as the error.
+        let message_json: Value = result.json()?;
+        let message = message_json
+            .get("message")
+            .ok_or_else(|| anyhow!("Bad request"))?
+            .as_str()
+            .ok_or_else(|| anyhow!("Bad request"))?;
+        Err(anyhow!("{}", message))
+    } else if result.status() == StatusCode::TOO_MANY_REQUESTS
+        || result.status() == StatusCode::SERVICE_UNAVAILABLE
+    {
+        // Server is asking the client to back off, pass along how long to wait
+        let retry_after = result
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok());
+        Err(RetryAfter::from_header(retry_after).into())
+    } else {
+        // For other status codes, use the standard HTTP reasons as the error
+        Err(anyhow!(
+            "{}",
+            result
+                .status()
+                .canonical_reason()
+                .ok_or_else(|| anyhow!("Request failed"))?
+        ))
+    }
+}
+
+#[cfg(feature = "web-storage")]
+pub(crate) async fn post_web(request: String) -> Result<Value> {
+    diagnostic!(debug, bytes = request.len(), "Sending request");
+    #[cfg(feature = "diagnostics")]
+    let start = instant::Instant::now();
+
+    match post_web_request(request).await {
+        Ok(response) => {
+            diagnostic!(
+                debug,
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Request complete"
+            );
+            Ok(response)
+        }
+        Err(error) => {
+            diagnostic!(
+                warn,
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Request failed: {}",
+                error
+            );
+            Err(error)
+        }
+    }
+}
+
+#[cfg(feature = "web-storage")]
+async fn post_web_request(request: String) -> Result<Value> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    let mut init = web_sys::RequestInit::new();
+    init.method("POST");
+    init.mode(web_sys::RequestMode::Cors);
+    init.body(Some(&JsValue::from_str(&request)));
+
+    let request = web_sys::Request::new_with_str_and_init(ENDPOINT, &init)
+        .map_err(|_| anyhow!("Request init failed"))?;
+    request
+        .headers()
+        .set("Content-Type", "text/plain")
+        .map_err(|_| anyhow!("Failed to set headers"))?;
+
+    let window = web_sys::window().unwrap();
+    let response = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|_| anyhow!("Fetch failed"))?;
+    assert!(response.is_instance_of::<web_sys::Response>());
+
+    let response: web_sys::Response = response.dyn_into().unwrap();
+    let array_buffer = JsFuture::from(
+        response
+            .array_buffer()
+            .map_err(|_| anyhow!("Response could not be fetched as an array buffer"))?,
+    )
+    .await
+    .map_err(|_| anyhow!("Response could not be fetched as an array buffer"))?;
+    let uint8_array = js_sys::Uint8Array::new(&array_buffer);
+    let bytes = uint8_array.to_vec();
+    let result = String::from_utf8(bytes.clone()).map_err(|_| anyhow!("Bad response body"))?;
+
+    // Check status code
+    if response.status() >= 200 && response.status() <= 299 {
+        // Request success, return response for deserialization by the caller
+        Ok(serde_json::from_str(&result)?)
+    } else if response.status() == 400 {
+        // Bad request status contains a message from the server, decode it and pass
+        // it along as the error.
+        let message_json: Value = serde_json::from_str(&result)?;
+        let message = message_json
+            .get("message")
+            .ok_or_else(|| anyhow!("Bad request"))?
+            .as_str()
+            .ok_or_else(|| anyhow!("Bad request"))?;
+        Err(anyhow!("{}", message))
+    } else if response.status() == 429 || response.status() == 503 {
+        // Server is asking the client to back off, pass along how long to wait
+        let retry_after = response.headers().get("Retry-After").ok().flatten();
+        Err(RetryAfter::from_header(retry_after.as_deref()).into())
+    } else {
+        // For other status codes, use the standard HTTP reasons as the error
+        Err(anyhow!("{}", response.status_text()))
+    }
+}
+
 impl SyncOperation {
-    pub fn new(request: SyncRequest) -> Arc<Mutex<Self>> {
+    pub fn new(request: SyncRequest, endpoint: SyncEndpoint) -> Arc<Mutex<Self>> {
         let operation = Arc::new(Mutex::new(Self {
             request,
+            endpoint,
             response: None,
+            request_bytes: 0,
+            response_bytes: 0,
+            round_trip_ms: None,
         }));
 
         let operation_copy = operation.clone();
@@ -61,116 +268,75 @@ impl SyncOperation {
         operation
     }
 
-    #[cfg(feature = "native-storage")]
-    fn execute_native(request: String) -> Result<SyncResponse> {
-        let client = Client::new();
-        let result = client
-            .post(ENDPOINT)
-            .header(USER_AGENT, HeaderValue::from_static("tpscube"))
-            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
-            .body(request)
-            .send()?;
-
-        // Check status code
-        if result.status().is_success() {
-            // Request success, deserialize as sync response
-            SyncResponse::deserialize(result.json()?)
-        } else if result.status() == StatusCode::BAD_REQUEST {
-            // Bad request status contains a message from the server, decode it and pass
-            // it along as the error.
-            let message_json: Value = result.json()?;
-            let message = message_json
-                .get("message")
-                .ok_or_else(|| anyhow!("Bad request"))?
-                .as_str()
-                .ok_or_else(|| anyhow!("Bad request"))?;
-            Err(anyhow!("{}", message))
-        } else {
-            // For other status codes, use the standard HTTP reasons as the error
-            Err(anyhow!(
-                "{}",
-                result
-                    .status()
-                    .canonical_reason()
-                    .ok_or_else(|| anyhow!("Request failed"))?
-            ))
-        }
-    }
-
-    #[cfg(feature = "web-storage")]
-    async fn execute_web(request: String) -> Result<SyncResponse> {
-        use wasm_bindgen::JsCast;
-        use wasm_bindgen_futures::JsFuture;
-
-        let mut init = web_sys::RequestInit::new();
-        init.method("POST");
-        init.mode(web_sys::RequestMode::Cors);
-        init.body(Some(&JsValue::from_str(&request)));
-
-        let request = web_sys::Request::new_with_str_and_init(ENDPOINT, &init)
-            .map_err(|_| anyhow!("Request init failed"))?;
-        request
-            .headers()
-            .set("Content-Type", "text/plain")
-            .map_err(|_| anyhow!("Failed to set headers"))?;
-
-        let window = web_sys::window().unwrap();
-        let response = JsFuture::from(window.fetch_with_request(&request))
-            .await
-            .map_err(|_| anyhow!("Fetch failed"))?;
-        assert!(response.is_instance_of::<web_sys::Response>());
-
-        let response: web_sys::Response = response.dyn_into().unwrap();
-        let array_buffer = JsFuture::from(
-            response
-                .array_buffer()
-                .map_err(|_| anyhow!("Response could not be fetched as an array buffer"))?,
-        )
-        .await
-        .map_err(|_| anyhow!("Response could not be fetched as an array buffer"))?;
-        let uint8_array = js_sys::Uint8Array::new(&array_buffer);
-        let bytes = uint8_array.to_vec();
-        let result = String::from_utf8(bytes.clone()).map_err(|_| anyhow!("Bad response body"))?;
-
-        // Check status code
-        if response.status() >= 200 && response.status() <= 299 {
-            // Request success, deserialize as sync response
-            let result: Value = serde_json::from_str(&result)?;
-            SyncResponse::deserialize(result)
-        } else if response.status() == 400 {
-            // Bad request status contains a message from the server, decode it and pass
-            // it along as the error.
-            let message_json: Value = serde_json::from_str(&result)?;
-            let message = message_json
-                .get("message")
-                .ok_or_else(|| anyhow!("Bad request"))?
-                .as_str()
-                .ok_or_else(|| anyhow!("Bad request"))?;
-            Err(anyhow!("{}", message))
-        } else {
-            // For other status codes, use the standard HTTP reasons as the error
-            Err(anyhow!("{}", response.status_text()))
-        }
-    }
-
     #[cfg(feature = "native-storage")]
     fn execute(operation: &Arc<Mutex<Self>>) -> Result<SyncResponse> {
         // Serialize request and send response
-        let request = operation.lock().unwrap().request.serialize()?.to_string();
-        Self::execute_native(request)
+        let (request, sync_key, endpoint) = {
+            let operation = operation.lock().unwrap();
+            (
+                operation.request.serialize()?.to_string(),
+                operation.request.sync_key.clone(),
+                operation.endpoint,
+            )
+        };
+        let start = Self::start_request(operation, &request);
+        let response = match endpoint {
+            SyncEndpoint::Cloud => post_native(request),
+            SyncEndpoint::Lan(address) => post_lan(address, &sync_key, request),
+        };
+        Self::finish_request(operation, start, &response);
+        Ok(SyncResponse::deserialize(response?)?)
     }
 
     #[cfg(feature = "web-storage")]
     async fn execute(operation: &Arc<Mutex<Self>>) -> Result<SyncResponse> {
         // Serialize request and send response
         let request = operation.lock().unwrap().request.serialize()?.to_string();
-        Self::execute_web(request).await
+        let start = Self::start_request(operation, &request);
+        let response = post_web(request).await;
+        Self::finish_request(operation, start, &response);
+        Ok(SyncResponse::deserialize(response?)?)
+    }
+
+    fn start_request(operation: &Arc<Mutex<Self>>, request: &str) -> instant::Instant {
+        operation.lock().unwrap().request_bytes = request.len();
+        instant::Instant::now()
+    }
+
+    fn finish_request(
+        operation: &Arc<Mutex<Self>>,
+        start: instant::Instant,
+        response: &Result<Value>,
+    ) {
+        let mut operation = operation.lock().unwrap();
+        operation.round_trip_ms = Some(start.elapsed().as_millis() as u64);
+        if let Ok(response) = response {
+            // The response body has already been parsed, so measure its serialized
+            // form. This matches the transferred size apart from whitespace.
+            operation.response_bytes = response.to_string().len();
+        }
     }
 
     pub fn done(&self) -> bool {
         self.response.is_some()
     }
 
+    pub fn request(&self) -> &SyncRequest {
+        &self.request
+    }
+
+    pub fn request_bytes(&self) -> usize {
+        self.request_bytes
+    }
+
+    pub fn response_bytes(&self) -> usize {
+        self.response_bytes
+    }
+
+    pub fn round_trip_ms(&self) -> Option<u64> {
+        self.round_trip_ms
+    }
+
     pub fn response(&self) -> &Option<Result<SyncResponse>> {
         &self.response
     }
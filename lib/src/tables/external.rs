@@ -0,0 +1,212 @@
+use crate::error::{Error, Result};
+use anyhow::anyhow;
+use once_cell::sync::OnceCell;
+use std::ops::Deref;
+
+#[cfg(not(target_arch = "wasm32"))]
+use memmap2::Mmap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+/// File names of the solver tables, in the order of the table indexes used by
+/// `SolverTable`. These are the files generated by the `gentables` tool.
+pub const SOLVER_TABLE_FILES: [&str; 14] = [
+    "corner_orientation_move_table.bin",
+    "corner_permutation_move_table.bin",
+    "3x3x3_edge_orientation_move_table.bin",
+    "3x3x3_equatorial_edge_slice_move_table.bin",
+    "3x3x3_phase_2_edge_permutation_move_table.bin",
+    "3x3x3_phase_2_equatorial_edge_permutation_move_table.bin",
+    "corner_orientation_prune_table.bin",
+    "corner_permutation_prune_table.bin",
+    "3x3x3_corner_orientation_edge_slice_prune_table.bin",
+    "3x3x3_edge_orientation_prune_table.bin",
+    "3x3x3_combined_orientation_prune_table.bin",
+    "3x3x3_corner_edge_permutation_prune_table.bin",
+    "3x3x3_phase_1_corner_permutation_prune_table.bin",
+    "3x3x3_phase_2_edge_permutation_prune_table.bin",
+];
+
+/// Expected size of each solver table in bytes, in the same order as
+/// `SOLVER_TABLE_FILES`. Move tables hold a 16-bit index for each of the 18
+/// moves from every coordinate, prune tables hold one byte per coordinate.
+const SOLVER_TABLE_SIZES: [usize; 14] = [
+    2187 * 18 * 2,
+    40320 * 18 * 2,
+    2048 * 18 * 2,
+    495 * 18 * 2,
+    40320 * 18 * 2,
+    24 * 18 * 2,
+    2187,
+    40320,
+    2187 * 495,
+    2048 * 495,
+    2187 * 2048,
+    40320 * 24,
+    40320,
+    40320 * 24,
+];
+
+/// Environment variable naming the directory to load solver tables from when
+/// they are first used
+#[cfg(not(target_arch = "wasm32"))]
+const TABLE_PATH_VAR: &str = "TPSCUBE_TABLES";
+
+static TABLES: [OnceCell<&'static [u8]>; 14] = [
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+    OnceCell::new(),
+];
+
+/// Solver table that is loaded at runtime instead of being embedded in the
+/// binary. The solver calls `ensure_solver_tables` before searching, so the
+/// tables it dereferences are always loaded and have been checked.
+pub(crate) struct SolverTable {
+    index: usize,
+}
+
+impl SolverTable {
+    pub(crate) const fn new(index: usize) -> Self {
+        Self { index }
+    }
+
+    /// Gets the contents of the table, loading all tables from the default
+    /// location on first use if they have not been loaded yet
+    pub(crate) fn get(&self) -> Result<&'static [u8]> {
+        if let Some(table) = TABLES[self.index].get() {
+            return Ok(table);
+        }
+
+        load_default_tables()?;
+        TABLES[self.index].get().copied().ok_or_else(|| {
+            Error::Solver(anyhow!(
+                "Solver table {} has not been loaded",
+                SOLVER_TABLE_FILES[self.index]
+            ))
+        })
+    }
+}
+
+impl Deref for SolverTable {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // Only reachable from the solver after `ensure_solver_tables` has
+        // succeeded, and loaded tables are never removed
+        TABLES[self.index]
+            .get()
+            .expect("solver tables are checked before solving")
+    }
+}
+
+/// Loads the solver tables if they are not loaded yet. Returns an error if any
+/// table is missing or invalid, in which case the solver must not be used.
+pub(crate) fn ensure_solver_tables() -> Result<()> {
+    for index in 0..SOLVER_TABLE_FILES.len() {
+        SolverTable::new(index).get()?;
+    }
+    Ok(())
+}
+
+fn check_table_size(index: usize, size: usize) -> Result<()> {
+    if size != SOLVER_TABLE_SIZES[index] {
+        return Err(Error::Solver(anyhow!(
+            "Solver table {} is {} bytes, expected {} bytes",
+            SOLVER_TABLE_FILES[index],
+            size,
+            SOLVER_TABLE_SIZES[index]
+        )));
+    }
+    Ok(())
+}
+
+/// Provides the contents of a solver table, for targets where the tables
+/// can't be memory mapped, such as the web. Tables that have already been
+/// loaded are not replaced.
+pub fn set_solver_table(name: &str, contents: Vec<u8>) -> Result<()> {
+    let index = SOLVER_TABLE_FILES
+        .iter()
+        .position(|file| *file == name)
+        .ok_or_else(|| Error::Solver(anyhow!("Unknown solver table {}", name)))?;
+    check_table_size(index, contents.len())?;
+    // Tables live for the rest of the program
+    let contents: &'static [u8] = Box::leak(contents.into_boxed_slice());
+    let _ = TABLES[index].set(contents);
+    Ok(())
+}
+
+/// Memory maps all solver tables from the given directory. Only the parts
+/// of the tables that are used by the solver are read into memory.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_solver_tables(path: &Path) -> Result<()> {
+    for (index, name) in SOLVER_TABLE_FILES.iter().enumerate() {
+        if TABLES[index].get().is_some() {
+            continue;
+        }
+        let file = File::open(path.join(name)).map_err(|error| {
+            Error::Solver(anyhow!(
+                "Could not open {}: {}",
+                path.join(name).display(),
+                error
+            ))
+        })?;
+        // Safety: the mapping is only valid while the file is not modified or
+        // truncated by another process, which this program can't prevent. The
+        // tables are installed read-only with the program and are never written
+        // by it. The size is checked so that a file truncated before loading is
+        // rejected instead of being read out of bounds.
+        let map = unsafe { Mmap::map(&file).map_err(|error| Error::Solver(error.into()))? };
+        check_table_size(index, map.len())?;
+        let map: &'static Mmap = Box::leak(Box::new(map));
+        let _ = TABLES[index].set(&map[..]);
+    }
+    Ok(())
+}
+
+/// Returns true if every solver table has been loaded
+pub fn solver_tables_loaded() -> bool {
+    TABLES.iter().all(|table| table.get().is_some())
+}
+
+/// Size of a solver table, or `None` if it has not been loaded yet
+pub(crate) fn loaded_table_size(index: usize) -> Option<usize> {
+    TABLES[index].get().map(|table| table.len())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_default_tables() -> Result<()> {
+    load_solver_tables(&default_table_path())
+}
+
+/// There is no file system on the web, tables must be provided with
+/// `set_solver_table` before the solver is used
+#[cfg(target_arch = "wasm32")]
+fn load_default_tables() -> Result<()> {
+    Ok(())
+}
+
+/// Tables are loaded from the directory in the `TPSCUBE_TABLES` environment
+/// variable, or from a `tables` directory next to the executable.
+#[cfg(not(target_arch = "wasm32"))]
+fn default_table_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(TABLE_PATH_VAR) {
+        return PathBuf::from(path);
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("tables")))
+        .unwrap_or_else(|| PathBuf::from("tables"))
+}
@@ -0,0 +1,418 @@
+use crate::common::CornerPermutationMoveTable;
+use crate::cube3x3x3::PackedEdges3x3x3;
+use crate::tables::solve::CUBE3_POSSIBLE_PHASE_2_MOVES;
+use crate::{Cube3x3x3, InitialCubeState, Move};
+use once_cell::sync::OnceCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Number of arrangements of the four U layer edges within the eight U and D
+/// layer edge positions (8P4)
+const U_EDGE_INDEX_COUNT: usize = 1680;
+
+const UNVISITED: u8 = 0xff;
+
+/// Number of symmetries used to reduce the table. These are the four rotations
+/// about the U-D axis, each with or without a left-right mirror. They keep the
+/// U layer edges in the U layer and map phase 2 moves to phase 2 moves, so a
+/// state and its conjugate by one of them are the same distance from solved.
+/// Symmetries that swap the U and D layers would not preserve the U layer
+/// edge coordinate.
+const SYMMETRY_COUNT: usize = 8;
+
+/// Corner and U/D edge positions that each position moves to when rotating
+/// the cube a quarter turn about the U-D axis
+const ROTATION_CORNERS: [usize; 8] = [1, 2, 3, 0, 5, 6, 7, 4];
+const ROTATION_EDGES: [usize; 8] = [1, 2, 3, 0, 5, 6, 7, 4];
+
+/// Corner and U/D edge positions that each position moves to when mirroring
+/// the cube from left to right
+const MIRROR_CORNERS: [usize; 8] = [1, 0, 3, 2, 5, 4, 7, 6];
+const MIRROR_EDGES: [usize; 8] = [2, 1, 0, 3, 6, 5, 4, 7];
+
+/// Set of pruning tables used by the 3x3x3 solver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolverTableSize {
+    /// Tables embedded in the library
+    Standard,
+    /// Adds a phase 2 pruning table over the corner permutation and the
+    /// positions of the U layer edges. This table is generated the first time
+    /// it is used, which takes a few seconds and about 9 MB of memory, and
+    /// gives shorter phase 2 searches when looking for short solutions.
+    Large,
+}
+
+static USE_LARGE_TABLES: AtomicBool = AtomicBool::new(false);
+static U_EDGE_MOVE_TABLE: OnceCell<Vec<u16>> = OnceCell::new();
+static LARGE_PRUNE_TABLE: OnceCell<LargePhase2PruneTable> = OnceCell::new();
+
+/// Phase 2 pruning table indexed by corner permutation and U layer edge
+/// positions. Corner permutations are reduced by symmetry, so that only one
+/// corner permutation of each symmetry class is stored, which makes the table
+/// about eight times smaller.
+pub(crate) struct LargePhase2PruneTable {
+    /// Symmetry class of each corner permutation
+    corner_class: Vec<u16>,
+    /// Symmetry that maps each corner permutation to the representative of
+    /// its class
+    corner_symmetry: Vec<u8>,
+    /// U layer edge index conjugated by each symmetry
+    u_edge_conjugate: Vec<u16>,
+    /// Distances indexed by corner permutation class and U layer edge index
+    distances: Vec<u8>,
+}
+
+/// Symmetry of the cube as the mapping of corner and U/D edge positions
+#[derive(Clone, Copy)]
+struct Symmetry {
+    corners: [usize; 8],
+    edges: [usize; 8],
+}
+
+/// Selects the pruning tables used by the solver for future solves
+pub fn set_solver_table_size(size: SolverTableSize) {
+    USE_LARGE_TABLES.store(size == SolverTableSize::Large, Ordering::Relaxed);
+}
+
+pub fn solver_table_size() -> SolverTableSize {
+    if USE_LARGE_TABLES.load(Ordering::Relaxed) {
+        SolverTableSize::Large
+    } else {
+        SolverTableSize::Standard
+    }
+}
+
+/// Generates the large tables now instead of on the first solve that uses
+/// them. This can be called from a background thread at startup. The large
+/// tables are generated from the standard tables, so this fails if those are
+/// not available.
+pub fn prepare_large_solver_tables() -> crate::Result<()> {
+    crate::tables::ensure_solver_tables()?;
+    LargePhase2PruneTable::get();
+    Ok(())
+}
+
+/// Memory used by the large tables in bytes, or `None` for tables that have
+/// not been generated yet
+pub(crate) fn large_table_memory_usage() -> [(&'static str, Option<usize>); 2] {
+    [
+        (
+            "3x3x3_u_edge_move_table",
+            U_EDGE_MOVE_TABLE
+                .get()
+                .map(|table| table.len() * std::mem::size_of::<u16>()),
+        ),
+        (
+            "3x3x3_large_phase_2_prune_table",
+            LARGE_PRUNE_TABLE.get().map(|table| table.memory_usage()),
+        ),
+    ]
+}
+
+/// Large table to use for a new solve, if enabled
+pub(crate) fn active_large_tables() -> Option<&'static LargePhase2PruneTable> {
+    if USE_LARGE_TABLES.load(Ordering::Relaxed) {
+        Some(LargePhase2PruneTable::get())
+    } else {
+        None
+    }
+}
+
+/// Index of the positions of the U layer edges. Only valid in phase 2, when
+/// all U and D layer edges are within the U and D layers. Each edge's position
+/// is ranked among the positions not taken by the previous edges, so that
+/// the solved state has index zero.
+pub(crate) fn u_edge_index(edges: &PackedEdges3x3x3) -> u16 {
+    let mut positions = [0; 4];
+    for pos in 0..8 {
+        let piece = edges.piece(pos) as usize;
+        if piece < 4 {
+            positions[piece] = pos;
+        }
+    }
+    u_edge_positions_index(&positions)
+}
+
+/// U layer edge index from the position of each U layer edge
+fn u_edge_positions_index(positions: &[usize; 4]) -> u16 {
+    let mut result = 0;
+    for i in 0..4 {
+        let taken = positions[..i]
+            .iter()
+            .filter(|pos| **pos < positions[i])
+            .count();
+        result = result * (8 - i) + positions[i] - taken;
+    }
+    result as u16
+}
+
+/// Position of each U layer edge from the U layer edge index
+fn u_edge_positions_from_index(idx: u16) -> [usize; 4] {
+    let mut idx = idx as usize;
+    let mut ranks = [0; 4];
+    for i in (0..4).rev() {
+        ranks[i] = idx % (8 - i);
+        idx /= 8 - i;
+    }
+
+    let mut positions = [0; 4];
+    for i in 0..4 {
+        positions[i] = (0..8)
+            .filter(|pos| !positions[..i].contains(pos))
+            .nth(ranks[i])
+            .unwrap();
+    }
+    positions
+}
+
+/// Corner permutation index of a permutation, using the same factorial number
+/// system as `Cube3x3x3::corner_permutation_index`
+fn corner_permutation_index(pieces: &[usize; 8]) -> u16 {
+    let mut result = 0;
+    for i in 0..7 {
+        let cur = pieces[i + 1..]
+            .iter()
+            .filter(|piece| **piece < pieces[i])
+            .count() as u16;
+        result = (result + cur) * (7 - i as u16);
+    }
+    result
+}
+
+/// Piece at each corner position from the corner permutation index
+fn corner_permutation_from_index(idx: u16) -> [usize; 8] {
+    let mut idx = idx as usize;
+    let mut pieces = [0; 8];
+    let mut factorial = 5040; // 7!
+    for i in 0..8 {
+        let digit = idx / factorial;
+        idx %= factorial;
+        if i < 7 {
+            factorial /= 7 - i;
+        }
+        pieces[i] = (0..8)
+            .filter(|piece| !pieces[..i].contains(piece))
+            .nth(digit)
+            .unwrap();
+    }
+    pieces
+}
+
+impl Symmetry {
+    const IDENTITY: Self = Self {
+        corners: [0, 1, 2, 3, 4, 5, 6, 7],
+        edges: [0, 1, 2, 3, 4, 5, 6, 7],
+    };
+
+    /// All symmetries used for the table, starting with the identity
+    fn all() -> [Symmetry; SYMMETRY_COUNT] {
+        let mut result = [Self::IDENTITY; SYMMETRY_COUNT];
+        for mirror in 0..2 {
+            for rotation in 0..4 {
+                let mut sym = Self::IDENTITY;
+                for _ in 0..rotation {
+                    sym = sym.then(&ROTATION_CORNERS, &ROTATION_EDGES);
+                }
+                if mirror == 1 {
+                    sym = sym.then(&MIRROR_CORNERS, &MIRROR_EDGES);
+                }
+                result[mirror * 4 + rotation] = sym;
+            }
+        }
+        result
+    }
+
+    fn then(&self, corners: &[usize; 8], edges: &[usize; 8]) -> Self {
+        let mut result = *self;
+        for i in 0..8 {
+            result.corners[i] = corners[self.corners[i]];
+            result.edges[i] = edges[self.edges[i]];
+        }
+        result
+    }
+
+    /// Corner permutation of the state seen through this symmetry
+    fn conjugate_corner_permutation(&self, idx: u16) -> u16 {
+        let pieces = corner_permutation_from_index(idx);
+        let mut result = [0; 8];
+        for i in 0..8 {
+            result[self.corners[i]] = self.corners[pieces[i]];
+        }
+        corner_permutation_index(&result)
+    }
+
+    /// U layer edge index of the state seen through this symmetry
+    fn conjugate_u_edges(&self, idx: u16) -> u16 {
+        let positions = u_edge_positions_from_index(idx);
+        let mut result = [0; 4];
+        for i in 0..4 {
+            result[self.edges[i]] = self.edges[positions[i]];
+        }
+        u_edge_positions_index(&result)
+    }
+}
+
+/// Moves the U layer edge index. Only phase 2 moves are valid.
+pub(crate) fn u_edge_move(idx: u16, mv: Move) -> u16 {
+    let table = U_EDGE_MOVE_TABLE.get_or_init(generate_u_edge_move_table);
+    table[idx as usize * Move::count_3x3x3() + mv as u8 as usize]
+}
+
+fn generate_u_edge_move_table() -> Vec<u16> {
+    // Walk the reachable arrangements from the solved state, keeping the edge
+    // permutation of each arrangement to apply moves to
+    let mut table = vec![0; U_EDGE_INDEX_COUNT * Move::count_3x3x3()];
+    let mut visited = vec![false; U_EDGE_INDEX_COUNT];
+    let mut queue = vec![PackedEdges3x3x3::new(&Cube3x3x3::new())];
+    visited[0] = true;
+    while let Some(edges) = queue.pop() {
+        let idx = u_edge_index(&edges);
+        for mv in CUBE3_POSSIBLE_PHASE_2_MOVES {
+            let mut next = edges;
+            next.do_move(*mv);
+            let next_idx = u_edge_index(&next);
+            table[idx as usize * Move::count_3x3x3() + *mv as u8 as usize] = next_idx;
+            if !visited[next_idx as usize] {
+                visited[next_idx as usize] = true;
+                queue.push(next);
+            }
+        }
+    }
+    table
+}
+
+impl LargePhase2PruneTable {
+    fn get() -> &'static Self {
+        LARGE_PRUNE_TABLE.get_or_init(Self::generate)
+    }
+
+    fn generate() -> Self {
+        let symmetries = Symmetry::all();
+        let corner_count = Cube3x3x3::CORNER_PERMUTATION_INDEX_COUNT;
+
+        let mut corner_conjugate = vec![0; SYMMETRY_COUNT * corner_count];
+        for (sym_idx, sym) in symmetries.iter().enumerate() {
+            for idx in 0..corner_count {
+                corner_conjugate[sym_idx * corner_count + idx] =
+                    sym.conjugate_corner_permutation(idx as u16);
+            }
+        }
+        let mut u_edge_conjugate = vec![0; SYMMETRY_COUNT * U_EDGE_INDEX_COUNT];
+        for (sym_idx, sym) in symmetries.iter().enumerate() {
+            for idx in 0..U_EDGE_INDEX_COUNT {
+                u_edge_conjugate[sym_idx * U_EDGE_INDEX_COUNT + idx] =
+                    sym.conjugate_u_edges(idx as u16);
+            }
+        }
+
+        // Group the corner permutations into symmetry classes, using the first
+        // permutation found in each class as its representative
+        let mut corner_class = vec![u16::MAX; corner_count];
+        let mut representatives = Vec::new();
+        for idx in 0..corner_count {
+            if corner_class[idx] != u16::MAX {
+                continue;
+            }
+            let class = representatives.len() as u16;
+            representatives.push(idx as u16);
+            for sym_idx in 0..SYMMETRY_COUNT {
+                corner_class[corner_conjugate[sym_idx * corner_count + idx] as usize] = class;
+            }
+        }
+        let mut corner_symmetry = vec![0; corner_count];
+        for idx in 0..corner_count {
+            let representative = representatives[corner_class[idx] as usize];
+            corner_symmetry[idx] = (0..SYMMETRY_COUNT)
+                .find(|sym_idx| corner_conjugate[sym_idx * corner_count + idx] == representative)
+                .unwrap() as u8;
+        }
+
+        // Symmetries that leave each representative unchanged. A state with the
+        // representative's corner permutation is the same distance from solved
+        // as its conjugates by these, so they must all be given a distance at
+        // the same time.
+        let stabilizers: Vec<Vec<usize>> = representatives
+            .iter()
+            .map(|representative| {
+                (0..SYMMETRY_COUNT)
+                    .filter(|sym_idx| {
+                        corner_conjugate[sym_idx * corner_count + *representative as usize]
+                            == *representative
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut result = Self {
+            corner_class,
+            corner_symmetry,
+            u_edge_conjugate,
+            distances: vec![UNVISITED; representatives.len() * U_EDGE_INDEX_COUNT],
+        };
+        result.distances[0] = 0;
+
+        // Breadth first search one depth at a time, expanding the states found
+        // at the previous depth
+        let mut depth = 0;
+        loop {
+            let mut found = false;
+            for idx in 0..result.distances.len() {
+                if result.distances[idx] != depth {
+                    continue;
+                }
+                let corner_permutation = representatives[idx / U_EDGE_INDEX_COUNT];
+                let u_edges = (idx % U_EDGE_INDEX_COUNT) as u16;
+                for mv in CUBE3_POSSIBLE_PHASE_2_MOVES {
+                    let next_corners = CornerPermutationMoveTable::get(corner_permutation, *mv);
+                    let next_edges = u_edge_move(u_edges, *mv);
+                    let class = result.corner_class[next_corners as usize] as usize;
+                    let next_edges = result.conjugate_u_edges(
+                        result.corner_symmetry[next_corners as usize] as usize,
+                        next_edges,
+                    );
+                    for sym_idx in &stabilizers[class] {
+                        let next = class * U_EDGE_INDEX_COUNT
+                            + result.conjugate_u_edges(*sym_idx, next_edges) as usize;
+                        if result.distances[next] == UNVISITED {
+                            result.distances[next] = depth + 1;
+                            found = true;
+                        }
+                    }
+                }
+            }
+            if !found {
+                break;
+            }
+            depth += 1;
+        }
+
+        // Every state should be reachable, but never overestimate the distance
+        for distance in result.distances.iter_mut() {
+            if *distance == UNVISITED {
+                *distance = 0;
+            }
+        }
+
+        result
+    }
+
+    fn conjugate_u_edges(&self, sym_idx: usize, u_edges: u16) -> u16 {
+        self.u_edge_conjugate[sym_idx * U_EDGE_INDEX_COUNT + u_edges as usize]
+    }
+
+    /// Memory used by the table in bytes
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.corner_class.len() * std::mem::size_of::<u16>()
+            + self.corner_symmetry.len()
+            + self.u_edge_conjugate.len() * std::mem::size_of::<u16>()
+            + self.distances.len()
+    }
+
+    /// Minimum number of phase 2 moves needed to solve the corner permutation
+    /// and the U layer edges
+    pub(crate) fn distance(&self, corner_permutation: u16, u_edges: u16) -> usize {
+        let class = self.corner_class[corner_permutation as usize] as usize;
+        let sym_idx = self.corner_symmetry[corner_permutation as usize] as usize;
+        let u_edges = self.conjugate_u_edges(sym_idx, u_edges);
+        self.distances[class * U_EDGE_INDEX_COUNT + u_edges as usize] as usize
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(not(feature = "external-tables"))]
+use crate::tables::solve::*;
+
+/// Memory used by one of the 3x3x3 solver tables
+#[derive(Clone, Debug)]
+pub struct SolverTableMemory {
+    pub name: &'static str,
+    /// Size of the table in bytes. This is zero for tables that are loaded or
+    /// generated at runtime and are not available yet.
+    pub bytes: usize,
+    /// True if the table is available to the solver
+    pub loaded: bool,
+}
+
+const SOLVER_TABLE_NAMES: [&str; 14] = [
+    "corner_orientation_move_table",
+    "corner_permutation_move_table",
+    "3x3x3_edge_orientation_move_table",
+    "3x3x3_equatorial_edge_slice_move_table",
+    "3x3x3_phase_2_edge_permutation_move_table",
+    "3x3x3_phase_2_equatorial_edge_permutation_move_table",
+    "corner_orientation_prune_table",
+    "corner_permutation_prune_table",
+    "3x3x3_corner_orientation_edge_slice_prune_table",
+    "3x3x3_edge_orientation_prune_table",
+    "3x3x3_combined_orientation_prune_table",
+    "3x3x3_corner_edge_permutation_prune_table",
+    "3x3x3_phase_1_corner_permutation_prune_table",
+    "3x3x3_phase_2_edge_permutation_prune_table",
+];
+
+/// Gets the memory used by each of the tables used by the 3x3x3 solver. Tables
+/// embedded in the library are always loaded. Tables loaded at runtime are only
+/// counted once they have been loaded, so that checking memory usage does not
+/// cause them to be loaded.
+pub fn solver_table_memory_usage() -> Vec<SolverTableMemory> {
+    SOLVER_TABLE_NAMES
+        .iter()
+        .enumerate()
+        .map(|(index, name)| table_memory(name, standard_table_size(index)))
+        .chain(large_table_memory())
+        .collect()
+}
+
+/// Total memory used by the 3x3x3 solver tables that are loaded, in bytes
+pub fn solver_table_memory_total() -> usize {
+    solver_table_memory_usage()
+        .iter()
+        .map(|table| table.bytes)
+        .sum()
+}
+
+fn table_memory(name: &'static str, size: Option<usize>) -> SolverTableMemory {
+    SolverTableMemory {
+        name,
+        bytes: size.unwrap_or(0),
+        loaded: size.is_some(),
+    }
+}
+
+#[cfg(feature = "large-tables")]
+fn large_table_memory() -> Vec<SolverTableMemory> {
+    crate::tables::large::large_table_memory_usage()
+        .iter()
+        .map(|(name, size)| table_memory(name, *size))
+        .collect()
+}
+
+#[cfg(not(feature = "large-tables"))]
+fn large_table_memory() -> Vec<SolverTableMemory> {
+    Vec::new()
+}
+
+#[cfg(feature = "external-tables")]
+fn standard_table_size(index: usize) -> Option<usize> {
+    crate::tables::external::loaded_table_size(index)
+}
+
+#[cfg(not(feature = "external-tables"))]
+fn standard_table_size(index: usize) -> Option<usize> {
+    let tables: [&[u8]; 14] = [
+        CUBE_CORNER_ORIENTATION_MOVE_TABLE,
+        CUBE_CORNER_PERMUTATION_MOVE_TABLE,
+        CUBE3_EDGE_ORIENTATION_MOVE_TABLE,
+        CUBE3_EQUATORIAL_EDGE_SLICE_MOVE_TABLE,
+        CUBE3_PHASE_2_EDGE_PERMUTATION_MOVE_TABLE,
+        CUBE3_PHASE_2_EQUATORIAL_EDGE_PERMUTATION_MOVE_TABLE,
+        CUBE_CORNER_ORIENTATION_PRUNE_TABLE,
+        CUBE_CORNER_PERMUTATION_PRUNE_TABLE,
+        CUBE3_CORNER_ORIENTATION_EDGE_SLICE_PRUNE_TABLE,
+        CUBE3_EDGE_ORIENTATION_PRUNE_TABLE,
+        CUBE3_COMBINED_ORIENTATION_PRUNE_TABLE,
+        CUBE3_CORNER_EDGE_PERMUTATION_PRUNE_TABLE,
+        CUBE3_PHASE_1_CORNER_PERMUTATION_PRUNE_TABLE,
+        CUBE3_PHASE_2_EDGE_PERMUTATION_PRUNE_TABLE,
+    ];
+    Some(tables[index].len())
+}
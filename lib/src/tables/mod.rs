@@ -4,5 +4,25 @@ pub(crate) mod table2x2x2;
 pub(crate) mod table3x3x3;
 pub(crate) mod table4x4x4;
 
+#[cfg(all(not(feature = "no_solver"), feature = "external-tables"))]
+pub(crate) mod external;
+#[cfg(all(not(feature = "no_solver"), feature = "large-tables"))]
+pub(crate) mod large;
+#[cfg(not(feature = "no_solver"))]
+pub(crate) mod memory;
 #[cfg(not(feature = "no_solver"))]
 pub(crate) mod solve;
+
+/// Makes sure that the solver tables are available before the solver uses
+/// them. Tables loaded at runtime are loaded and checked on first use, and a
+/// missing or invalid table is reported as `Error::Solver`.
+#[cfg(all(not(feature = "no_solver"), feature = "external-tables"))]
+pub fn ensure_solver_tables() -> crate::Result<()> {
+    external::ensure_solver_tables()
+}
+
+/// Embedded tables are always available
+#[cfg(all(not(feature = "no_solver"), not(feature = "external-tables")))]
+pub fn ensure_solver_tables() -> crate::Result<()> {
+    Ok(())
+}
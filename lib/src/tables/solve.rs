@@ -1,34 +1,81 @@
 use crate::common::Move;
 
+#[cfg(feature = "external-tables")]
+use crate::tables::external::SolverTable;
+
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE_CORNER_ORIENTATION_MOVE_TABLE: &'static [u8] =
     include_bytes!("corner_orientation_move_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE_CORNER_PERMUTATION_MOVE_TABLE: &'static [u8] =
     include_bytes!("corner_permutation_move_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_EDGE_ORIENTATION_MOVE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_edge_orientation_move_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_EQUATORIAL_EDGE_SLICE_MOVE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_equatorial_edge_slice_move_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_PHASE_2_EDGE_PERMUTATION_MOVE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_phase_2_edge_permutation_move_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_PHASE_2_EQUATORIAL_EDGE_PERMUTATION_MOVE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_phase_2_equatorial_edge_permutation_move_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE_CORNER_ORIENTATION_PRUNE_TABLE: &'static [u8] =
     include_bytes!("corner_orientation_prune_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE_CORNER_PERMUTATION_PRUNE_TABLE: &'static [u8] =
     include_bytes!("corner_permutation_prune_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_CORNER_ORIENTATION_EDGE_SLICE_PRUNE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_corner_orientation_edge_slice_prune_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_EDGE_ORIENTATION_PRUNE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_edge_orientation_prune_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_COMBINED_ORIENTATION_PRUNE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_combined_orientation_prune_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_CORNER_EDGE_PERMUTATION_PRUNE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_corner_edge_permutation_prune_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_PHASE_1_CORNER_PERMUTATION_PRUNE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_phase_1_corner_permutation_prune_table.bin");
+#[cfg(not(feature = "external-tables"))]
 pub(crate) const CUBE3_PHASE_2_EDGE_PERMUTATION_PRUNE_TABLE: &'static [u8] =
     include_bytes!("3x3x3_phase_2_edge_permutation_prune_table.bin");
 
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE_CORNER_ORIENTATION_MOVE_TABLE: SolverTable = SolverTable::new(0);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE_CORNER_PERMUTATION_MOVE_TABLE: SolverTable = SolverTable::new(1);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_EDGE_ORIENTATION_MOVE_TABLE: SolverTable = SolverTable::new(2);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_EQUATORIAL_EDGE_SLICE_MOVE_TABLE: SolverTable = SolverTable::new(3);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_PHASE_2_EDGE_PERMUTATION_MOVE_TABLE: SolverTable = SolverTable::new(4);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_PHASE_2_EQUATORIAL_EDGE_PERMUTATION_MOVE_TABLE: SolverTable =
+    SolverTable::new(5);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE_CORNER_ORIENTATION_PRUNE_TABLE: SolverTable = SolverTable::new(6);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE_CORNER_PERMUTATION_PRUNE_TABLE: SolverTable = SolverTable::new(7);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_CORNER_ORIENTATION_EDGE_SLICE_PRUNE_TABLE: SolverTable = SolverTable::new(8);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_EDGE_ORIENTATION_PRUNE_TABLE: SolverTable = SolverTable::new(9);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_COMBINED_ORIENTATION_PRUNE_TABLE: SolverTable = SolverTable::new(10);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_CORNER_EDGE_PERMUTATION_PRUNE_TABLE: SolverTable = SolverTable::new(11);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_PHASE_1_CORNER_PERMUTATION_PRUNE_TABLE: SolverTable = SolverTable::new(12);
+#[cfg(feature = "external-tables")]
+pub(crate) const CUBE3_PHASE_2_EDGE_PERMUTATION_PRUNE_TABLE: SolverTable = SolverTable::new(13);
+
 pub(crate) const CUBE2_POSSIBLE_MOVES: &'static [Move] = CUBE3_POSSIBLE_PHASE_1_MOVES;
 pub(crate) const CUBE2_POSSIBLE_FOLLOWUP_MOVES: [&'static [Move]; Move::count_2x2x2()] =
     CUBE3_POSSIBLE_PHASE_1_FOLLOWUP_MOVES;
@@ -1,5 +1,6 @@
-use crate::common::{Color, CubeFace};
+use crate::common::{Color, CubeFace, Move, RotationDirection};
 use crate::cube3x3x3::{Cube3x3x3Faces, Edge3x3x3, EdgePiece3x3x3};
+use crate::tables::corner::CUBE_CORNER_PIECE_ROTATION;
 
 // Table for rotating the edges in piece format. Rotations are organized by
 // the face being rotated. Each entry is where the piece goes, where it comes
@@ -385,6 +386,86 @@ pub(crate) const CUBE3_EDGE_PIECE_ROTATION: [[[(Edge3x3x3, EdgePiece3x3x3); 4];
     ],
 ];
 
+/// Movement of every piece for a 3x3x3 move in piece format, combining the
+/// face rotations above so that a move can be applied in a single pass. Each
+/// entry is the position the piece comes from and the adjustment to its
+/// orientation.
+pub(crate) struct Cube3x3x3PieceMove {
+    pub corners: [(u8, u8); 8],
+    pub edges: [(u8, u8); 12],
+}
+
+impl Cube3x3x3PieceMove {
+    const fn new(mv: Move) -> Self {
+        let face = mv.face() as u8 as usize;
+        let rotation = mv.rotation();
+        let (dir, count) = if rotation < 0 {
+            (RotationDirection::CCW as u8 as usize, -rotation)
+        } else {
+            (RotationDirection::CW as u8 as usize, rotation)
+        };
+
+        let mut corners = [(0, 0); 8];
+        let mut i = 0;
+        while i < 8 {
+            corners[i] = (i as u8, 0);
+            i += 1;
+        }
+        let mut edges = [(0, 0); 12];
+        let mut i = 0;
+        while i < 12 {
+            edges[i] = (i as u8, 0);
+            i += 1;
+        }
+
+        let mut n = 0;
+        while n < count {
+            let old_corners = corners;
+            let mut i = 0;
+            while i < 4 {
+                let (dest, src) = CUBE_CORNER_PIECE_ROTATION[dir][face][i];
+                let (from, orientation) = old_corners[src.piece as u8 as usize];
+                corners[dest as u8 as usize] = (from, (orientation + src.orientation) % 3);
+                i += 1;
+            }
+
+            let old_edges = edges;
+            let mut i = 0;
+            while i < 4 {
+                let (dest, src) = CUBE3_EDGE_PIECE_ROTATION[dir][face][i];
+                let (from, orientation) = old_edges[src.piece as u8 as usize];
+                edges[dest as u8 as usize] = (from, orientation ^ src.orientation);
+                i += 1;
+            }
+            n += 1;
+        }
+
+        Self { corners, edges }
+    }
+}
+
+// Piece movement for each 3x3x3 move, in the order of the `Move` enumeration
+pub(crate) const CUBE3_PIECE_MOVES: [Cube3x3x3PieceMove; Move::count_3x3x3()] = [
+    Cube3x3x3PieceMove::new(Move::U),
+    Cube3x3x3PieceMove::new(Move::Up),
+    Cube3x3x3PieceMove::new(Move::U2),
+    Cube3x3x3PieceMove::new(Move::F),
+    Cube3x3x3PieceMove::new(Move::Fp),
+    Cube3x3x3PieceMove::new(Move::F2),
+    Cube3x3x3PieceMove::new(Move::R),
+    Cube3x3x3PieceMove::new(Move::Rp),
+    Cube3x3x3PieceMove::new(Move::R2),
+    Cube3x3x3PieceMove::new(Move::B),
+    Cube3x3x3PieceMove::new(Move::Bp),
+    Cube3x3x3PieceMove::new(Move::B2),
+    Cube3x3x3PieceMove::new(Move::L),
+    Cube3x3x3PieceMove::new(Move::Lp),
+    Cube3x3x3PieceMove::new(Move::L2),
+    Cube3x3x3PieceMove::new(Move::D),
+    Cube3x3x3PieceMove::new(Move::Dp),
+    Cube3x3x3PieceMove::new(Move::D2),
+];
+
 // Table of adjacent faces on edges for cubes in face color format
 pub(crate) const CUBE3_EDGE_ADJACENCY: [[usize; 4]; 6] = [
     // Top
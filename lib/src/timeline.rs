@@ -0,0 +1,168 @@
+use crate::common::TimedMove;
+use std::time::{Duration, Instant};
+
+/// Time in seconds between moves after which the clock ratio is no longer
+/// updated. Some cubes have a limited range for move timing, so long gaps are
+/// measured with the host clock instead.
+const CALIBRATION_RESET_TIME: u64 = 30;
+
+/// Converts timestamps from a device's free running clock into milliseconds
+/// elapsed between events. Device clocks are counters of a limited width that
+/// wrap around, and notifications can arrive out of order. A timestamp that
+/// is behind the latest one is treated as happening at the latest time
+/// instead of a full wraparound later.
+pub struct DeviceClock {
+    mask: u64,
+    ticks_per_second: u64,
+    last: Option<u64>,
+    /// Time not yet reported, in thousandths of a tick
+    remainder: u64,
+}
+
+impl DeviceClock {
+    pub fn new(bits: u32, ticks_per_second: u64) -> Self {
+        Self {
+            mask: if bits >= 64 {
+                u64::MAX
+            } else {
+                (1 << bits) - 1
+            },
+            ticks_per_second,
+            last: None,
+            remainder: 0,
+        }
+    }
+
+    /// Clock for host timestamps in milliseconds, for devices that do not
+    /// report their own timing
+    pub fn host() -> Self {
+        Self::new(64, 1000)
+    }
+
+    /// Returns the milliseconds elapsed since the previous timestamp. The
+    /// first timestamp is at zero. Fractions of a millisecond are carried over
+    /// to the next call so that rounding does not accumulate.
+    pub fn elapsed(&mut self, timestamp: u64) -> u32 {
+        let timestamp = timestamp & self.mask;
+        let last = match self.last {
+            Some(last) => last,
+            None => {
+                self.last = Some(timestamp);
+                return 0;
+            }
+        };
+
+        let delta = timestamp.wrapping_sub(last) & self.mask;
+        if delta > self.mask / 2 {
+            // Timestamp is behind the latest one, the packet arrived out of order
+            diagnostic!(debug, timestamp, last, "Out of order device timestamp");
+            return 0;
+        }
+        self.last = Some(timestamp);
+
+        let total = self.remainder + delta * 1000;
+        self.remainder = total % self.ticks_per_second;
+        (total / self.ticks_per_second) as u32
+    }
+}
+
+/// Maps move times reported by a device onto the host's timeline. Some cubes
+/// have very uncalibrated clocks, so the ratio between the device clock and
+/// real time is learned as moves arrive, with the host as the reference.
+pub struct MoveTimeline {
+    start_time: Option<Instant>,
+    last_move_time: Option<Instant>,
+    current_duration: Duration,
+    total_raw_ticks: u64,
+    total_real_ticks: u64,
+    clock_ratio: f64,
+    clock_ratio_range: (f64, f64),
+}
+
+impl MoveTimeline {
+    pub fn new(clock_ratio: f64, clock_ratio_range: (f64, f64)) -> Self {
+        Self {
+            start_time: None,
+            last_move_time: None,
+            current_duration: Duration::from_secs(0),
+            total_raw_ticks: 0,
+            total_real_ticks: 0,
+            clock_ratio: clock_ratio
+                .max(clock_ratio_range.0)
+                .min(clock_ratio_range.1),
+            clock_ratio_range,
+        }
+    }
+
+    /// Current ratio between device clock ticks and real milliseconds
+    pub fn clock_ratio(&self) -> f64 {
+        self.clock_ratio
+    }
+
+    /// Adjusts the timing of moves received at `now`, with times relative to
+    /// the previous move in device clock ticks. Returns the moves with times
+    /// relative to the previous move in real milliseconds.
+    pub fn add_moves(&mut self, moves: &[TimedMove], now: Instant) -> Vec<TimedMove> {
+        let mut last_duration = self.current_duration;
+
+        // Check length of time since last move
+        let mut calibration_reset = false;
+        if let Some(last_move_time) = self.last_move_time {
+            let delta = now.saturating_duration_since(last_move_time);
+            if delta.as_secs() > CALIBRATION_RESET_TIME {
+                // Too long between moves, don't adjust clock ratio to avoid issues with
+                // the range of the encodings of some cubes. Adjust timestamp using real time.
+                calibration_reset = true;
+                self.current_duration += delta;
+            }
+        }
+
+        // Go through the move list and adjust the timing information
+        let mut adjusted_moves = Vec::with_capacity(moves.len());
+        let mut new_raw_ticks = 0;
+        for raw_move in moves {
+            if !calibration_reset {
+                new_raw_ticks += raw_move.time() as u64;
+
+                // Adjust delta using clock ratio. This will be adjusted over time to be
+                // calibrated to real time.
+                self.current_duration += Duration::from_nanos(
+                    ((raw_move.time() as u64 * 1_000_000) as f64 / self.clock_ratio) as u64,
+                );
+            }
+
+            let adjusted_time = self.current_duration.as_millis() - last_duration.as_millis();
+            last_duration = self.current_duration;
+            adjusted_moves.push(TimedMove::new(raw_move.move_(), adjusted_time as u32));
+        }
+
+        // Update calibration state
+        if let Some(start_time) = self.start_time {
+            if calibration_reset {
+                // Measure from this move forward
+                self.start_time = Some(now);
+                self.total_raw_ticks = 0;
+                self.total_real_ticks = 0;
+            } else {
+                // Update the calibration with the number of milliseconds reported in the
+                // raw data and the number of milliseconds that have actually passed.
+                self.total_raw_ticks += new_raw_ticks;
+                self.total_real_ticks =
+                    now.saturating_duration_since(start_time).as_millis() as u64;
+                if self.total_real_ticks != 0 {
+                    let computed_clock_ratio =
+                        self.total_raw_ticks as f64 / self.total_real_ticks as f64;
+                    self.clock_ratio = computed_clock_ratio
+                        .max(self.clock_ratio_range.0)
+                        .min(self.clock_ratio_range.1);
+                }
+            }
+        } else {
+            // First move, record start time
+            self.start_time = Some(now);
+        }
+
+        self.last_move_time = Some(now);
+        adjusted_moves
+    }
+}
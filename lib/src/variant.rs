@@ -0,0 +1,322 @@
+use crate::{Corner, Cube, Cube3x3x3, CubeFace, Edge3x3x3, InitialCubeState, Move, SolveType};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+#[cfg(not(feature = "no_solver"))]
+use crate::{scramble_3x3x3_sourced, RandomSource, StandardRandomSource};
+
+/// Number of moves in a random move scramble for bandaged cubes
+const BANDAGED_SCRAMBLE_LENGTH: usize = 25;
+
+/// A piece of a 3x3x3 cube, identified by its position on a solved cube
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BandagedPiece {
+    Corner(Corner),
+    Edge(Edge3x3x3),
+    Center(CubeFace),
+}
+
+/// Two adjacent pieces that are fused together and always move as one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bandage {
+    a: BandagedPiece,
+    b: BandagedPiece,
+}
+
+/// Variation of the 3x3x3 that uses the standard cube state, but has different
+/// rules for which moves are allowed or when the cube is solved
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Variant3x3x3 {
+    Standard,
+    /// Cube with fused pieces. Moves that would separate a bandage are illegal.
+    Bandaged(Vec<Bandage>),
+    /// Cube without centers. The cube is solved when it is solved in any
+    /// orientation.
+    ///
+    /// Slice moves can't be represented in the cube state, so the corner and
+    /// edge permutation parity always match. A physical void cube can reach
+    /// states where they don't, which look like a single swapped edge pair.
+    /// These must be fixed with a slice quarter turn before entering the state.
+    Void,
+}
+
+/// Puzzle that is a 3x3x3 internally, but has pieces of different shapes.
+/// These use the standard 3x3x3 logic, and only differ in how they are
+/// displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeMod {
+    /// Single color cube where each layer has a different thickness, so the
+    /// pieces are told apart by their height instead of their color
+    MirrorBlocks,
+}
+
+/// Display data for a shape mod. Contains the distance of each sticker from
+/// the core, in units of a standard 3x3x3 piece. The faces of a standard
+/// cube are at 1.5.
+#[derive(Clone, Debug)]
+pub struct ShapeModDisplay {
+    pub heights: BTreeMap<CubeFace, Vec<Vec<f32>>>,
+}
+
+impl BandagedPiece {
+    /// Faces of the layers this piece is currently in
+    fn layers(&self, cube: &Cube3x3x3) -> Vec<CubeFace> {
+        match self {
+            BandagedPiece::Corner(corner) => {
+                let idx = (0..8)
+                    .find(|i| {
+                        cube.corner_piece(Corner::try_from(*i as u8).unwrap()).piece == *corner
+                    })
+                    .unwrap();
+                crate::tables::corner::CUBE3_CORNER_INDICIES[idx]
+                    .iter()
+                    .map(|sticker| CubeFace::try_from((sticker / 9) as u8).unwrap())
+                    .collect()
+            }
+            BandagedPiece::Edge(edge) => {
+                let idx = (0..12)
+                    .find(|i| {
+                        cube.edge_piece(Edge3x3x3::try_from(*i as u8).unwrap())
+                            .piece
+                            == *edge
+                    })
+                    .unwrap();
+                crate::tables::table3x3x3::CUBE3_EDGE_INDICIES[idx]
+                    .iter()
+                    .map(|sticker| CubeFace::try_from((sticker / 9) as u8).unwrap())
+                    .collect()
+            }
+            BandagedPiece::Center(face) => vec![*face],
+        }
+    }
+}
+
+impl Bandage {
+    /// Creates a bandage between two pieces. Returns `None` if the pieces are
+    /// not next to each other on a solved cube.
+    pub fn new(a: BandagedPiece, b: BandagedPiece) -> Option<Self> {
+        let cube = Cube3x3x3::new();
+        let (a_layers, b_layers) = (a.layers(&cube), b.layers(&cube));
+        let (smaller, larger) = if a_layers.len() < b_layers.len() {
+            (a_layers, b_layers)
+        } else {
+            (b_layers, a_layers)
+        };
+
+        // Adjacent pieces differ by one layer, and the piece closer to the
+        // center is in a subset of the layers of the other piece
+        if larger.len() != smaller.len() + 1 || !smaller.iter().all(|face| larger.contains(face)) {
+            return None;
+        }
+        Some(Self { a, b })
+    }
+
+    pub fn pieces(&self) -> (BandagedPiece, BandagedPiece) {
+        (self.a, self.b)
+    }
+
+    /// Checks if a move keeps this bandage together. A move is only legal if
+    /// both pieces are inside the turning layer, or both are outside of it.
+    pub fn allows_move(&self, cube: &Cube3x3x3, mv: Move) -> bool {
+        let face = mv.face();
+        self.a.layers(cube).contains(&face) == self.b.layers(cube).contains(&face)
+    }
+}
+
+impl Variant3x3x3 {
+    /// Variant used for solves of the given type
+    pub fn for_solve_type(solve_type: SolveType) -> Self {
+        match solve_type {
+            SolveType::Void3x3x3 => Variant3x3x3::Void,
+            SolveType::Bandaged3x3x3 => Self::bandaged_block(),
+            _ => Variant3x3x3::Standard,
+        }
+    }
+
+    /// Bandaged cube used by the bandaged solve type. The top and front edges
+    /// of the right face are fused with the corner between them and the right
+    /// center, forming a 1x2x2 block.
+    pub fn bandaged_block() -> Self {
+        let corner = BandagedPiece::Corner(Corner::URF);
+        let top = BandagedPiece::Edge(Edge3x3x3::UR);
+        let front = BandagedPiece::Edge(Edge3x3x3::FR);
+        let center = BandagedPiece::Center(CubeFace::Right);
+        Variant3x3x3::Bandaged(
+            [
+                (corner, top),
+                (corner, front),
+                (top, center),
+                (front, center),
+            ]
+            .iter()
+            .map(|(a, b)| Bandage::new(*a, *b).unwrap())
+            .collect(),
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant3x3x3::Standard => "Standard",
+            Variant3x3x3::Bandaged(_) => "Bandaged",
+            Variant3x3x3::Void => "Void cube",
+        }
+    }
+
+    /// Checks if a move can be performed on the given cube state
+    pub fn is_move_legal(&self, cube: &Cube3x3x3, mv: Move) -> bool {
+        match self {
+            Variant3x3x3::Bandaged(bandages) => {
+                bandages.iter().all(|bandage| bandage.allows_move(cube, mv))
+            }
+            _ => true,
+        }
+    }
+
+    /// Gets all outer layer moves that can be performed on the given cube state
+    pub fn legal_moves(&self, cube: &Cube3x3x3) -> Vec<Move> {
+        (0..Move::count_3x3x3())
+            .map(|i| Move::try_from(i as u8).unwrap())
+            .filter(|mv| self.is_move_legal(cube, *mv))
+            .collect()
+    }
+
+    pub fn is_solved(&self, cube: &Cube3x3x3) -> bool {
+        match self {
+            Variant3x3x3::Void => {
+                // Centers are ignored, so every other sticker on a face must
+                // match the color of a reference sticker on that face
+                let faces = cube.as_faces();
+                (0..6).all(|face| {
+                    let face = CubeFace::try_from(face as u8).unwrap();
+                    let color = faces.color(face, 0, 0);
+                    (0..3).all(|row| {
+                        (0..3).all(|col| {
+                            (row == 1 && col == 1) || faces.color(face, row, col) == color
+                        })
+                    })
+                })
+            }
+            _ => cube.is_solved(),
+        }
+    }
+
+    /// Generates a random scramble using the given source of randomness.
+    /// Bandaged cubes can't reach every state, so they are scrambled with a
+    /// sequence of random legal moves instead of a random state.
+    #[cfg(not(feature = "no_solver"))]
+    pub fn scramble_sourced<T: RandomSource>(&self, rng: &mut T) -> Vec<Move> {
+        match self {
+            Variant3x3x3::Bandaged(_) => {
+                let mut cube = Cube3x3x3::new();
+                let mut result: Vec<Move> = Vec::new();
+                // Faces turned since the last move on a different axis. Moves on
+                // the same axis commute, so turning any of these faces again
+                // could cancel.
+                let mut axis_faces: Vec<CubeFace> = Vec::new();
+                while result.len() < BANDAGED_SCRAMBLE_LENGTH {
+                    let moves: Vec<Move> = self
+                        .legal_moves(&cube)
+                        .into_iter()
+                        .filter(|mv| !axis_faces.contains(&mv.face()))
+                        .collect();
+                    if moves.len() == 0 {
+                        break;
+                    }
+                    let mv = moves[rng.next(moves.len() as u32) as usize];
+                    if let Some(last) = result.last() {
+                        if last.face() != mv.face() && last.face() != mv.face().opposite() {
+                            axis_faces.clear();
+                        }
+                    }
+                    axis_faces.push(mv.face());
+                    cube.do_move(mv);
+                    result.push(mv);
+                }
+                result
+            }
+            _ => scramble_3x3x3_sourced(rng),
+        }
+    }
+
+    /// Generates a random scramble
+    #[cfg(not(feature = "no_solver"))]
+    pub fn scramble(&self) -> Vec<Move> {
+        self.scramble_sourced(&mut StandardRandomSource)
+    }
+
+    /// Finds a solution to the cube state. Bandaged cubes are not solved, as
+    /// the 3x3x3 solver can't be restricted to legal moves. Their scrambles
+    /// are made of legal moves, so the inverse of the scramble solves them.
+    #[cfg(not(feature = "no_solver"))]
+    pub fn solve(&self, cube: &Cube3x3x3) -> Option<Vec<Move>> {
+        match self {
+            Variant3x3x3::Bandaged(_) => None,
+            // The void cube is also solved by the standard solution, as face
+            // turns move the same pieces
+            _ => cube.solve(),
+        }
+    }
+}
+
+impl ShapeMod {
+    pub const ALL: &'static [ShapeMod] = &[ShapeMod::MirrorBlocks];
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "mirror_blocks" => Some(ShapeMod::MirrorBlocks),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            ShapeMod::MirrorBlocks => "mirror_blocks",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShapeMod::MirrorBlocks => "Mirror blocks",
+        }
+    }
+
+    /// Distance from the core to a face of the solved puzzle
+    pub fn face_distance(&self, face: CubeFace) -> f32 {
+        match self {
+            // Layers are 0.5, 1 and 1.5 units thick on each axis, with the
+            // thin layers on the top, back and right
+            ShapeMod::MirrorBlocks => match face {
+                CubeFace::Top | CubeFace::Back | CubeFace::Right => 1.0,
+                CubeFace::Bottom | CubeFace::Front | CubeFace::Left => 2.0,
+            },
+        }
+    }
+
+    /// Gets the display data for a cube state. Every sticker stays the same
+    /// distance from the core as it moves, so the height of a sticker only
+    /// depends on the face it belongs to on the solved puzzle.
+    pub fn display(&self, cube: &Cube3x3x3) -> ShapeModDisplay {
+        let heights = cube
+            .colors()
+            .into_iter()
+            .map(|(face, rows)| {
+                let rows = rows
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|color| self.face_distance(color.face()))
+                            .collect()
+                    })
+                    .collect();
+                (face, rows)
+            })
+            .collect();
+        ShapeModDisplay { heights }
+    }
+}
+
+impl Default for Variant3x3x3 {
+    fn default() -> Self {
+        Variant3x3x3::Standard
+    }
+}
@@ -7,18 +7,38 @@ use crate::widgets::CustomWidgets;
 use egui::{
     Align, CentralPanel, CtxRef, Direction, Layout, SidePanel, Stroke, TopBottomPanel, Ui, Vec2,
 };
+use list::AlgorithmList;
 use report::TPSReport;
 use std::collections::HashMap;
 use tpscube_core::{
-    Analysis, Cube, Cube3x3x3, CubeWithSolution, History, InitialCubeState, OLLAlgorithm,
-    PLLAlgorithm,
+    AlgorithmComparison, AlgorithmDatabase, Analysis, Cube, Cube3x3x3, CubeWithSolution, History,
+    InitialCubeState, OLLAlgorithm, PLLAlgorithm,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::font::FontSize;
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use egui::{Label, Sense};
+#[cfg(not(target_arch = "wasm32"))]
+use tpscube_core::{AlgorithmConflict, AlgorithmImportResult, AlgorithmSheetFormat};
+
+/// Number of import errors listed after importing an algorithm sheet
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_IMPORT_ERRORS_SHOWN: usize = 5;
+
 pub struct AlgorithmsWidget {
     cached_update_id: Option<u64>,
     algorithm_stats: AlgorithmStats,
+    comparison: AlgorithmComparison,
     mode: AlgorithmMode,
     sort: Sort,
+    database: Option<AlgorithmDatabase>,
+    #[cfg(not(target_arch = "wasm32"))]
+    replace_existing: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    import_result: Option<Result<AlgorithmImportResult>>,
 }
 
 struct AlgorithmStats {
@@ -30,6 +50,7 @@ struct AlgorithmStats {
 struct AlgorithmCounts {
     perform_count: usize,
     total_moves: usize,
+    total_aufs: usize,
     total_recognition_time: u64,
     total_execution_time: u64,
 }
@@ -74,28 +95,44 @@ impl AlgorithmsWidget {
                 oll: HashMap::new(),
                 pll: HashMap::new(),
             },
+            comparison: AlgorithmComparison::new(),
             mode: AlgorithmMode::TPSReport(AlgorithmType::PLL),
             sort: Sort {
                 column: SortColumn::TPS,
                 order: SortOrder::Descending,
             },
+            database: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            replace_existing: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            import_result: None,
         }
     }
 
     fn analyze(&mut self, history: &History) {
         self.algorithm_stats.oll.clear();
         self.algorithm_stats.pll.clear();
+        self.comparison = AlgorithmComparison::new();
 
         for solve in history.iter() {
             if let Some(moves) = &solve.moves {
                 let mut unsolved_state = Cube3x3x3::new();
                 unsolved_state.do_moves(&solve.scramble);
-                let analysis = Analysis::analyze(&CubeWithSolution {
-                    initial_state: unsolved_state.clone(),
-                    solution: moves.clone(),
-                });
+                let analysis = Analysis::analyze_with_solve_type(
+                    &CubeWithSolution {
+                        initial_state: unsolved_state.clone(),
+                        solution: moves.clone(),
+                    },
+                    solve.solve_type,
+                );
 
                 if let Analysis::CFOP(cfop) = analysis {
+                    // Match the algorithms executed against the user's
+                    // algorithms to compare algorithms for the same case
+                    if let Some(database) = &self.database {
+                        self.comparison.add_analysis(database, &cfop);
+                    }
+
                     for oll in cfop.oll {
                         let oll_entry = self
                             .algorithm_stats
@@ -104,11 +141,16 @@ impl AlgorithmsWidget {
                             .or_insert(AlgorithmCounts::default());
                         oll_entry.perform_count += 1;
                         oll_entry.total_moves += oll.moves.len();
+                        oll_entry.total_aufs += oll.execution.auf_count();
                         oll_entry.total_recognition_time += oll.recognition_time as u64;
                         oll_entry.total_execution_time += oll.execution_time as u64;
                     }
 
-                    for pll in cfop.pll {
+                    // The final alignment of the last layer is the adjustment after the
+                    // last PLL algorithm
+                    let pll_count = cfop.pll.len();
+                    let final_alignment = cfop.alignment.moves.len() != 0;
+                    for (idx, pll) in cfop.pll.into_iter().enumerate() {
                         let pll_entry = self
                             .algorithm_stats
                             .pll
@@ -116,6 +158,10 @@ impl AlgorithmsWidget {
                             .or_insert(AlgorithmCounts::default());
                         pll_entry.perform_count += 1;
                         pll_entry.total_moves += pll.moves.len();
+                        pll_entry.total_aufs += pll.execution.auf_count();
+                        if idx + 1 == pll_count && final_alignment {
+                            pll_entry.total_aufs += 1;
+                        }
                         pll_entry.total_recognition_time += pll.recognition_time as u64;
                         pll_entry.total_execution_time += pll.execution_time as u64;
                     }
@@ -168,7 +214,91 @@ impl AlgorithmsWidget {
         }
     }
 
-    fn landscape_sidebar(&mut self, ctxt: &CtxRef) {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_sheet(&mut self, history: &mut History) {
+        if let Some(path) = tinyfiledialogs::open_file_dialog(
+            "Import Algorithm Sheet",
+            ".",
+            Some((&["*.csv", "*.txt", "*.tsv"], "Algorithm sheets")),
+        ) {
+            let conflict = if self.replace_existing {
+                AlgorithmConflict::Replace
+            } else {
+                AlgorithmConflict::AddAlternative
+            };
+            let database = self
+                .database
+                .get_or_insert_with(|| AlgorithmDatabase::load(history));
+            self.import_result = Some(
+                std::fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|contents| {
+                        let result = database.import(
+                            &contents,
+                            AlgorithmSheetFormat::detect(&contents),
+                            conflict,
+                        );
+                        database.save(history)?;
+                        Ok(result)
+                    }),
+            );
+
+            // Algorithm statistics depend on which algorithms are stored
+            self.cached_update_id = None;
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_options(&mut self, ui: &mut Ui, history: &mut History) {
+        if ui
+            .add(
+                Label::new("📥  Import")
+                    .text_style(FontSize::Normal.into())
+                    .sense(Sense::click()),
+            )
+            .on_hover_text("Import algorithms from a CSV file, an Anki export or a text list")
+            .clicked()
+        {
+            self.import_sheet(history);
+        }
+        ui.checkbox(&mut self.replace_existing, "Replace existing");
+
+        match &self.import_result {
+            Some(Ok(result)) => {
+                let mut message = format!("Added {} algorithms", result.added);
+                if result.replaced_cases > 0 {
+                    message += &format!(", replaced {} cases", result.replaced_cases);
+                }
+                if result.duplicates > 0 {
+                    message += &format!(", {} already added", result.duplicates);
+                }
+                ui.add(Label::new(message).wrap(true).text_color(Theme::Green));
+                for error in result.errors.iter().take(MAX_IMPORT_ERRORS_SHOWN) {
+                    ui.add(Label::new(error).wrap(true).text_color(Theme::Red));
+                }
+                if result.errors.len() > MAX_IMPORT_ERRORS_SHOWN {
+                    ui.add(
+                        Label::new(format!(
+                            "{} more lines could not be imported",
+                            result.errors.len() - MAX_IMPORT_ERRORS_SHOWN
+                        ))
+                        .wrap(true)
+                        .text_color(Theme::Red),
+                    );
+                }
+            }
+            Some(Err(error)) => {
+                ui.add(
+                    Label::new(format!("Error: {}", error))
+                        .wrap(true)
+                        .text_color(Theme::Red),
+                );
+            }
+            None => (),
+        }
+    }
+
+    fn landscape_sidebar(&mut self, ctxt: &CtxRef, _history: &mut History) {
         SidePanel::left("left_algorithm_options")
             .default_width(160.0)
             .resizable(false)
@@ -180,6 +310,13 @@ impl AlgorithmsWidget {
                     ui.add_space(8.0);
                     ui.section("TPS Reports");
                     self.report_options(ui);
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        ui.add_space(8.0);
+                        ui.section("Algorithm Sheets");
+                        self.import_options(ui, _history);
+                    }
                 });
             });
     }
@@ -227,6 +364,9 @@ impl AlgorithmsWidget {
     }
 
     pub fn update(&mut self, ctxt: &CtxRef, _frame: &mut epi::Frame<'_>, history: &mut History) {
+        if self.database.is_none() {
+            self.database = Some(AlgorithmDatabase::load(history));
+        }
         if self.cached_update_id != Some(history.update_id()) {
             self.cached_update_id = Some(history.update_id());
             self.analyze(history);
@@ -236,19 +376,37 @@ impl AlgorithmsWidget {
         let aspect = ctxt.available_rect().width() / ctxt.available_rect().height();
         if aspect >= 1.0 {
             // Landscape mode. Report options to the left.
-            self.landscape_sidebar(ctxt);
+            self.landscape_sidebar(ctxt, history);
         } else {
             // Portrait mode. Report options at the top.
             self.portrait_top_bar(ctxt);
         }
 
+        let database = self
+            .database
+            .get_or_insert_with(|| AlgorithmDatabase::load(history));
+        let comparison = &self.comparison;
+        let algorithm_stats = &self.algorithm_stats;
+        let sort = &mut self.sort;
+
         ctxt.set_visuals(content_visuals());
-        CentralPanel::default().show(ctxt, |ui| match self.mode {
+        let mode = self.mode;
+        let mut changed = false;
+        CentralPanel::default().show(ctxt, |ui| match mode {
+            AlgorithmMode::Algorithms(alg_type) => {
+                changed = AlgorithmList::new(database, comparison, alg_type).update(ui);
+            }
             AlgorithmMode::TPSReport(alg_type) => {
-                let report = TPSReport::new(&self.algorithm_stats, alg_type, &mut self.sort);
+                let report = TPSReport::new(algorithm_stats, alg_type, sort);
                 report.update(ui);
             }
-            _ => (),
         });
+
+        if changed {
+            let _ = database.save(history);
+
+            // Algorithm statistics depend on which algorithms are stored
+            self.cached_update_id = None;
+        }
     }
 }
@@ -0,0 +1,126 @@
+use super::AlgorithmType;
+use crate::font::FontSize;
+use crate::theme::Theme;
+use crate::widgets::solve_time_string;
+use egui::{Label, ScrollArea, Sense, Ui};
+use tpscube_core::{AlgorithmComparison, AlgorithmDatabase, LastLayerCase};
+
+/// Number of executions of each algorithm needed before the fastest
+/// algorithm of a case is highlighted
+const MIN_COMPARISON_COUNT: usize = 5;
+
+/// List of the algorithms stored for each case of a set, with how fast each
+/// algorithm has been executed in solves
+pub(super) struct AlgorithmList<'a> {
+    database: &'a mut AlgorithmDatabase,
+    comparison: &'a AlgorithmComparison,
+    cases: Vec<LastLayerCase>,
+}
+
+impl<'a> AlgorithmList<'a> {
+    pub fn new(
+        database: &'a mut AlgorithmDatabase,
+        comparison: &'a AlgorithmComparison,
+        alg_type: AlgorithmType,
+    ) -> Self {
+        let cases = match alg_type {
+            AlgorithmType::OLL => LastLayerCase::all_oll(),
+            AlgorithmType::PLL => LastLayerCase::all_pll(),
+        };
+        Self {
+            database,
+            comparison,
+            cases,
+        }
+    }
+
+    /// Shows the list. Returns true if an algorithm was removed, in which
+    /// case the database should be saved.
+    pub fn update(&mut self, ui: &mut Ui) -> bool {
+        let mut removed = None;
+        let database = &self.database;
+        let comparison = self.comparison;
+        let cases: Vec<LastLayerCase> = self
+            .cases
+            .iter()
+            .cloned()
+            .filter(|case| database.algorithms(*case).len() != 0)
+            .collect();
+
+        if cases.len() == 0 {
+            ui.add(
+                Label::new(
+                    "No algorithms have been added for these cases. Import an algorithm \
+                    sheet to add your algorithms.",
+                )
+                .wrap(true),
+            );
+            return false;
+        }
+
+        ScrollArea::auto_sized().show(ui, |ui| {
+            ui.vertical(|ui| {
+                for case in cases {
+                    ui.add(
+                        Label::new(case.to_string())
+                            .text_style(FontSize::Section.into())
+                            .text_color(Theme::Blue),
+                    );
+                    let fastest = comparison.fastest(case, MIN_COMPARISON_COUNT);
+                    for (idx, algorithm) in database.algorithms(case).iter().enumerate() {
+                        let stats = comparison.stats(case, idx);
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add(
+                                    Label::new("🗑")
+                                        .text_color(Theme::Disabled)
+                                        .sense(Sense::click()),
+                                )
+                                .on_hover_text("Remove algorithm")
+                                .clicked()
+                            {
+                                removed = Some((case, idx));
+                            }
+                            ui.label(&algorithm.moves);
+                            if let Some(execution_time) = stats.average_execution_time() {
+                                let text = format!(
+                                    "{}×  avg {}  best {}",
+                                    stats.count,
+                                    solve_time_string(execution_time),
+                                    solve_time_string(stats.best_execution_time.unwrap_or(0))
+                                );
+                                if fastest == Some(idx) {
+                                    ui.add(
+                                        Label::new(format!("⚡ {}", text)).text_color(Theme::Green),
+                                    )
+                                    .on_hover_text("Fastest algorithm for this case");
+                                } else {
+                                    ui.add(Label::new(text).text_color(Theme::Disabled));
+                                }
+                            }
+                        });
+                    }
+                    let unmatched = comparison.unmatched_count(case);
+                    if unmatched > 0 {
+                        ui.add(
+                            Label::new(format!(
+                                "{} {} with another algorithm",
+                                unmatched,
+                                if unmatched == 1 { "solve" } else { "solves" }
+                            ))
+                            .text_color(Theme::Disabled),
+                        );
+                    }
+                    ui.add_space(4.0);
+                }
+            });
+        });
+
+        if let Some((case, idx)) = removed {
+            self.database.remove(case, idx);
+            true
+        } else {
+            false
+        }
+    }
+}
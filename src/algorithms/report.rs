@@ -13,6 +13,7 @@ struct AlgorithmRow {
     algorithm: Algorithm,
     count: usize,
     moves: f32,
+    aufs: f32,
     recognition_time: f32,
     execution_time: f32,
     tps: f32,
@@ -76,13 +77,14 @@ impl<'a> TPSReport<'a> {
         ui.vertical(|ui| {
             for row in &self.rows {
                 ui.label(format!(
-                    "{}: count {} recog {:.2} exec {:.2} total {:.2} moves {:.1} tps {:.2} etps {:.2}",
+                    "{}: count {} recog {:.2} exec {:.2} total {:.2} moves {:.1} aufs {:.2} tps {:.2} etps {:.2}",
                     row.algorithm.to_string(),
                     row.count,
                     row.recognition_time,
                     row.execution_time,
                     row.recognition_time + row.execution_time,
                     row.moves,
+                    row.aufs,
                     row.tps,
                     row.execution_tps
                 ));
@@ -114,6 +116,7 @@ impl AlgorithmRow {
 
         // Compute average stats for this algorithm
         let moves = counts.total_moves as f32 / counts.perform_count as f32;
+        let aufs = counts.total_aufs as f32 / counts.perform_count as f32;
         let recognition_time =
             counts.total_recognition_time as f32 / 1000.0 / counts.perform_count as f32;
         let execution_time =
@@ -123,6 +126,7 @@ impl AlgorithmRow {
             algorithm,
             count: counts.perform_count,
             moves,
+            aufs,
             recognition_time,
             execution_time,
             tps: moves / total_time,
@@ -1,18 +1,20 @@
 use crate::algorithms::AlgorithmsWidget;
 use crate::details::average::AverageDetailsWindow;
 use crate::details::solve::SolveDetailsWindow;
-use crate::font::{font_definitions, ScreenSize};
+use crate::editor::CubeEditorWindow;
+use crate::font::{font_definitions, ScreenSize, LARGE_DISPLAY_SCALE};
 use crate::framerate::Framerate;
 use crate::future::spawn_future;
 use crate::gl::GlContext;
 use crate::graph::GraphWidget;
 use crate::history::HistoryWidget;
+use crate::input::GamepadInput;
 use crate::mode::SolveTypeSelectWindow;
 use crate::settings::Settings;
 use crate::style::{base_visuals, content_visuals, header_visuals};
 use crate::theme::Theme;
 use crate::timer::TimerWidget;
-use crate::widgets::CustomWidgets;
+use crate::widgets::{set_time_rounding, CustomWidgets};
 use anyhow::Result;
 use egui::{
     widgets::Label, CentralPanel, Color32, CtxRef, Event, Key, Layout, Rect, Rgba, Sense, Stroke,
@@ -22,7 +24,9 @@ use epi::RepaintSignal;
 use image::GenericImageView;
 use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, Mutex};
-use tpscube_core::{History, HistoryLoadProgress, Solve, SolveType, SyncStatus};
+use tpscube_core::{
+    History, HistoryChange, HistoryLoadProgress, ScrambleFontSize, Solve, SolveType, SyncStatus,
+};
 
 #[cfg(target_arch = "wasm32")]
 use crate::is_safari;
@@ -34,6 +38,16 @@ use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::bluetooth::BluetoothState;
 
+#[cfg(all(feature = "audio-trigger", not(target_arch = "wasm32")))]
+use crate::audio::AudioTrigger;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::feedback::MoveFeedback;
+#[cfg(all(feature = "move-sound", not(target_arch = "wasm32")))]
+use crate::feedback::TickSound;
+#[cfg(not(target_arch = "wasm32"))]
+use tpscube_core::SolveInput;
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum Mode {
     Timer,
@@ -50,9 +64,11 @@ pub struct Application {
     graph_widget: GraphWidget,
     algorithms_widget: AlgorithmsWidget,
     settings_widget: Settings,
+    gamepad: GamepadInput,
     history: Option<History>,
     history_load_progress: Arc<Mutex<HistoryLoadProgress>>,
-    loading_history: Arc<Mutex<Option<Result<Option<History>>>>>,
+    loading_history: Arc<Mutex<Option<tpscube_core::Result<Option<History>>>>>,
+    history_changes: Arc<Mutex<Vec<HistoryChange>>>,
     repaint_signal: Arc<Mutex<Option<Arc<dyn RepaintSignal>>>>,
     framerate: Option<Framerate>,
     timer_cube_rect: Option<Rect>,
@@ -61,8 +77,11 @@ pub struct Application {
     solve_details_cube_rect: Option<Rect>,
     average_details: Option<AverageDetailsWindow>,
     solve_type_select: Option<SolveTypeSelectWindow>,
+    cube_editor: Option<CubeEditorWindow>,
     first_frame: bool,
     screen_size: ScreenSize,
+    scramble_font_size: ScrambleFontSize,
+    large_display: bool,
     solve_type: SolveType,
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -71,6 +90,21 @@ pub struct Application {
     bluetooth_icon: Icon,
     bluetooth_dialog_open: bool,
 
+    /// Smart timer connected alongside a smart cube. The timer provides the
+    /// solve time while the cube provides the moves.
+    #[cfg(not(target_arch = "wasm32"))]
+    bluetooth_timer: BluetoothState,
+    #[cfg(not(target_arch = "wasm32"))]
+    bluetooth_timer_dialog_open: bool,
+
+    #[cfg(all(feature = "audio-trigger", not(target_arch = "wasm32")))]
+    audio_trigger: Option<AudioTrigger>,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    move_feedback: MoveFeedback,
+    #[cfg(all(feature = "move-sound", not(target_arch = "wasm32")))]
+    move_sound_opened: bool,
+
     #[cfg(target_arch = "wasm32")]
     start_time: Instant,
 }
@@ -134,18 +168,23 @@ pub trait App {
 
 impl Application {
     pub fn new() -> Result<Self> {
+        // Create the settings first so that the diagnostic log captures opening the history
+        let settings_widget = Settings::new();
+
         let history_load_progress = Arc::new(Mutex::new(HistoryLoadProgress::default()));
         let history_load_progress_copy = history_load_progress.clone();
         let loading_history = Arc::new(Mutex::new(None));
         let loading_history_copy = loading_history.clone();
         let repaint_signal = Arc::new(Mutex::new(None));
         let repaint_signal_copy = repaint_signal.clone();
+        let guest = guest_mode_requested();
         spawn_future(async move {
-            *loading_history_copy.lock().unwrap() = Some(
-                History::open_with_progress(history_load_progress_copy)
-                    .await
-                    .map(|history| Some(history)),
-            );
+            let history = if guest {
+                History::open_in_memory().await
+            } else {
+                History::open_with_progress(history_load_progress_copy).await
+            };
+            *loading_history_copy.lock().unwrap() = Some(history.map(|history| Some(history)));
 
             // Wake up UI thread now that history is loaded. If we beat the UI initialization, the
             // first frame will immediately recognize that it was complete.
@@ -174,10 +213,12 @@ impl Application {
             history_widget: HistoryWidget::new(),
             graph_widget: GraphWidget::new(),
             algorithms_widget: AlgorithmsWidget::new(),
-            settings_widget: Settings::new(),
+            settings_widget,
+            gamepad: GamepadInput::new(),
             history: None,
             history_load_progress,
             loading_history,
+            history_changes: Arc::new(Mutex::new(Vec::new())),
             repaint_signal,
             framerate: None,
             timer_cube_rect: None,
@@ -186,8 +227,11 @@ impl Application {
             solve_details_cube_rect: None,
             average_details: None,
             solve_type_select: None,
+            cube_editor: None,
             first_frame: true,
             screen_size: ScreenSize::Normal,
+            scramble_font_size: ScrambleFontSize::default(),
+            large_display: false,
             solve_type: SolveType::Standard3x3x3,
 
             #[cfg(not(target_arch = "wasm32"))]
@@ -196,6 +240,19 @@ impl Application {
             bluetooth_icon,
             bluetooth_dialog_open: false,
 
+            #[cfg(not(target_arch = "wasm32"))]
+            bluetooth_timer: BluetoothState::new_timer(),
+            #[cfg(not(target_arch = "wasm32"))]
+            bluetooth_timer_dialog_open: false,
+
+            #[cfg(all(feature = "audio-trigger", not(target_arch = "wasm32")))]
+            audio_trigger: None,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            move_feedback: MoveFeedback::new(),
+            #[cfg(all(feature = "move-sound", not(target_arch = "wasm32")))]
+            move_sound_opened: false,
+
             #[cfg(target_arch = "wasm32")]
             start_time: Instant::now(),
         })
@@ -208,11 +265,50 @@ impl Application {
             *repaint_signal = Some(frame.repaint_signal());
         }
     }
+
+    fn handle_history_changes(&mut self, ctxt: &CtxRef) {
+        let changes: Vec<HistoryChange> = self.history_changes.lock().unwrap().drain(..).collect();
+        for change in changes {
+            match change {
+                HistoryChange::SolveDeleted { solve, .. } => {
+                    // Close the details of a solve that no longer exists
+                    if let Some(solve_details) = &self.solve_details {
+                        if solve_details.solve_id() == solve {
+                            self.solve_details = None;
+                        }
+                    }
+                }
+                HistoryChange::Synced(_) => {
+                    // Solves may have been deleted on another device
+                    if let Some(solve_details) = &self.solve_details {
+                        if self
+                            .history
+                            .as_ref()
+                            .unwrap()
+                            .solve(solve_details.solve_id())
+                            .is_none()
+                        {
+                            self.solve_details = None;
+                        }
+                    }
+
+                    // The sync may have completed after parts of this frame were drawn,
+                    // draw again with the new solves
+                    ctxt.request_repaint();
+                }
+                _ => (),
+            }
+        }
+    }
 }
 
 impl App for Application {
     fn setup(&mut self, ctxt: &CtxRef) {
-        ctxt.set_fonts(font_definitions(self.screen_size));
+        ctxt.set_fonts(font_definitions(
+            self.screen_size,
+            self.scramble_font_size.scale(),
+            1.0,
+        ));
         ctxt.set_visuals(base_visuals());
     }
 
@@ -238,9 +334,41 @@ impl App for Application {
             ScreenSize::VeryLarge
         };
 
-        if self.screen_size != new_screen_size {
+        // Apply color scheme before any visuals are generated for this frame
+        if let Some(history) = &self.history {
+            let preferences = history.preferences();
+            Theme::set_color_scheme(preferences.color_scheme(), preferences.accent_color());
+            set_time_rounding(preferences.time_rounding());
+        }
+
+        let new_scramble_font_size = match &self.history {
+            Some(history) => history.preferences().scramble_font_size(),
+            None => ScrambleFontSize::default(),
+        };
+
+        // Large display mode only applies to the timer
+        let new_large_display = self.mode == Mode::Timer
+            && match &self.history {
+                Some(history) => TimerWidget::large_display(history),
+                None => false,
+            };
+
+        if self.screen_size != new_screen_size
+            || self.scramble_font_size != new_scramble_font_size
+            || self.large_display != new_large_display
+        {
             self.screen_size = new_screen_size;
-            ctxt.set_fonts(font_definitions(self.screen_size));
+            self.scramble_font_size = new_scramble_font_size;
+            self.large_display = new_large_display;
+            ctxt.set_fonts(font_definitions(
+                self.screen_size,
+                self.scramble_font_size.scale(),
+                if self.large_display {
+                    LARGE_DISPLAY_SCALE
+                } else {
+                    1.0
+                },
+            ));
         }
 
         if self.history.is_some() {
@@ -293,7 +421,15 @@ impl App for Application {
                             self.mode = Mode::Settings;
                         }
 
-                        // Check status of sync and create tooltip text for sync button
+                        // Check status of sync and create tooltip text for sync button. Failed
+                        // syncs are retried automatically once their backoff delay has passed.
+                        #[cfg(not(target_arch = "wasm32"))]
+                        self.settings_widget
+                            .update_lan_sync_host(self.history.as_mut().unwrap());
+                        #[cfg(not(target_arch = "wasm32"))]
+                        self.settings_widget
+                            .run_solve_commands(self.history.as_ref().unwrap());
+                        self.history.as_mut().unwrap().retry_sync_if_due();
                         let sync_status = self.history.as_mut().unwrap().check_sync_status();
                         let local_count = self.history.as_ref().unwrap().local_action_count();
                         let local_status = match local_count {
@@ -310,7 +446,17 @@ impl App for Application {
                                 }
                             }
                             SyncStatus::SyncFailed(message) => {
-                                format!("{}\nSync failed: {}", local_status, message)
+                                match self.history.as_ref().unwrap().sync_details().next_retry_ms {
+                                    Some(retry) => format!(
+                                        "{}\nSync failed: {}\nRetrying in {} seconds.",
+                                        local_status,
+                                        message,
+                                        (retry + 999) / 1000
+                                    ),
+                                    None => {
+                                        format!("{}\nSync failed: {}", local_status, message)
+                                    }
+                                }
                             }
                             SyncStatus::SyncComplete => {
                                 if local_count != 0 {
@@ -324,8 +470,15 @@ impl App for Application {
                         // Show icons on the right of the header
                         ui.style_mut().spacing.item_spacing.x = 12.0;
                         ui.with_layout(Layout::right_to_left(), |ui| {
-                            // Show sync button
-                            if self.history.as_ref().unwrap().sync_in_progress() {
+                            // Show sync button, or a guest indicator when the history is
+                            // only kept in memory and is never synced
+                            if self.history.as_ref().unwrap().is_in_memory() {
+                                ui.add(Label::new("👤 Guest").text_color(Theme::Yellow))
+                                    .on_hover_text(
+                                        "Guest mode. Solves are not saved or synced and are \
+                                        discarded when the app is closed.",
+                                    );
+                            } else if self.history.as_ref().unwrap().sync_in_progress() {
                                 ui.style_mut().visuals.widgets.inactive.fg_stroke = Stroke {
                                     width: 1.0,
                                     color: Theme::Blue.into(),
@@ -339,17 +492,18 @@ impl App for Application {
                                     color: Theme::Blue.into(),
                                 };
                             }
-                            if ui
-                                .add(
-                                    Label::new(if local_count == 0 {
-                                        "🔃".into()
-                                    } else {
-                                        format!("🔃 {}", local_count)
-                                    })
-                                    .sense(Sense::click()),
-                                )
-                                .on_hover_text(sync_status)
-                                .clicked()
+                            if !self.history.as_ref().unwrap().is_in_memory()
+                                && ui
+                                    .add(
+                                        Label::new(if local_count == 0 {
+                                            "🔃".into()
+                                        } else {
+                                            format!("🔃 {}", local_count)
+                                        })
+                                        .sense(Sense::click()),
+                                    )
+                                    .on_hover_text(sync_status)
+                                    .clicked()
                             {
                                 self.history.as_mut().unwrap().start_sync();
                             }
@@ -381,6 +535,43 @@ impl App for Application {
                                         Label::new(self.bluetooth.status())
                                             .text_color(self.bluetooth.status_color()),
                                     );
+                                    if let Some(details) = self.bluetooth.device_details() {
+                                        ui.add(
+                                            Label::new(details).small().text_color(Theme::Disabled),
+                                        );
+                                    }
+                                });
+                            }
+
+                            // Show smart timer button when a smart cube is connected
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if self.bluetooth.ready() && !self.bluetooth.timer_only() {
+                                let response = ui.add(
+                                    Label::new("⏱")
+                                        .text_color(if self.bluetooth_timer.active() {
+                                            Theme::Blue
+                                        } else {
+                                            Theme::Disabled
+                                        })
+                                        .sense(Sense::click()),
+                                );
+                                if response.clicked() {
+                                    if self.bluetooth_timer.active() {
+                                        self.bluetooth_timer.disconnect();
+                                    } else {
+                                        self.bluetooth_timer_dialog_open = true;
+                                        self.bluetooth_timer.start_connect_flow(frame);
+                                    }
+                                }
+                                response.on_hover_ui(|ui| {
+                                    ui.add(
+                                        Label::new(if self.bluetooth_timer.active() {
+                                            self.bluetooth_timer.status()
+                                        } else {
+                                            "Connect smart timer".into()
+                                        })
+                                        .text_color(self.bluetooth_timer.status_color()),
+                                    );
                                 });
                             }
 
@@ -397,6 +588,15 @@ impl App for Application {
                             #[cfg(not(target_arch = "wasm32"))]
                             let allow_change_solve_type = !self.bluetooth.active();
 
+                            // Show cube state editor button
+                            if ui
+                                .add(Label::new("✏").sense(Sense::click()))
+                                .on_hover_text("Cube state editor")
+                                .clicked()
+                            {
+                                self.cube_editor = Some(CubeEditorWindow::new());
+                            }
+
                             // Show solve type
                             ui.style_mut().visuals.widgets = base_visuals().widgets;
                             if ui
@@ -429,46 +629,120 @@ impl App for Application {
                 framerate.request(Some(10));
             }
 
+            // Gamepads do not generate window events, so keep polling while
+            // one is connected
+            self.gamepad.update();
+            if self.gamepad.connected() {
+                framerate.request(Some(30));
+            }
+
             let mut details = None;
+            let mut show_day = None;
             match self.mode {
                 Mode::Timer => {
                     #[cfg(target_arch = "wasm32")]
-                    let (bluetooth_state, bluetooth_events, bluetooth_name) =
-                        (None, Vec::new(), None);
+                    let (bluetooth_state, bluetooth_events, bluetooth_input, external_timer) =
+                        (None, Vec::new(), None, false);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    let external_timer = !self.bluetooth_timer_dialog_open
+                        && self.bluetooth_timer.ready()
+                        && self.bluetooth.ready()
+                        && !self.bluetooth.timer_only();
                     #[cfg(not(target_arch = "wasm32"))]
-                    let (bluetooth_state, bluetooth_events, bluetooth_name) =
-                        if !self.bluetooth_dialog_open && self.bluetooth.ready() {
+                    if !external_timer {
+                        // Discard events from a timer that is not paired with a cube
+                        self.bluetooth_timer.new_events();
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    let (bluetooth_state, bluetooth_events, bluetooth_input) =
+                        if !self.bluetooth_dialog_open && external_timer {
+                            // Cube moves are listed first so that they are recorded
+                            // before a timer stop in the same frame. The timer gives
+                            // the official time, so solves are attributed to it.
+                            let mut events = self.bluetooth.new_events();
+                            events.extend(self.bluetooth_timer.new_events());
+                            let state = self.bluetooth.cube_state();
+                            let input = SolveInput::ExternalTimer {
+                                id: self.bluetooth_timer.connected_id().unwrap_or_default(),
+                                name: self
+                                    .bluetooth_timer
+                                    .name()
+                                    .unwrap_or("Bluetooth Smart Timer".to_string()),
+                            };
+                            (Some(state), events, Some(input))
+                        } else if !self.bluetooth_dialog_open && self.bluetooth.ready() {
                             if self.bluetooth.timer_only() {
                                 let events = self.bluetooth.new_events();
-                                let name = self
-                                    .bluetooth
-                                    .name()
-                                    .unwrap_or("Bluetooth Smart Timer".to_string());
-                                (None, events, Some(name))
+                                let input = SolveInput::ExternalTimer {
+                                    id: self.bluetooth.connected_id().unwrap_or_default(),
+                                    name: self
+                                        .bluetooth
+                                        .name()
+                                        .unwrap_or("Bluetooth Smart Timer".to_string()),
+                                };
+                                (None, events, Some(input))
                             } else {
                                 let events = self.bluetooth.new_events();
                                 let state = self.bluetooth.cube_state();
-                                let name = self
-                                    .bluetooth
-                                    .name()
-                                    .unwrap_or("Bluetooth Cube".to_string());
-                                (Some(state), events, Some(name))
+                                let input = SolveInput::SmartCube {
+                                    id: self.bluetooth.connected_id().unwrap_or_default(),
+                                    name: self
+                                        .bluetooth
+                                        .name()
+                                        .unwrap_or("Bluetooth Cube".to_string()),
+                                };
+                                (Some(state), events, Some(input))
                             }
                         } else {
                             (None, Vec::new(), None)
                         };
 
+                    #[cfg(all(feature = "move-sound", not(target_arch = "wasm32")))]
+                    if !self.move_sound_opened
+                        && MoveFeedback::enabled(self.history.as_ref().unwrap())
+                    {
+                        // Open the audio output the first time move feedback is used
+                        self.move_sound_opened = true;
+                        if let Ok(sound) = TickSound::new() {
+                            self.move_feedback
+                                .add_hook(move |_, style| sound.play(style));
+                        }
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    self.move_feedback
+                        .update(self.history.as_ref().unwrap(), &bluetooth_events);
+
+                    #[cfg(all(feature = "audio-trigger", not(target_arch = "wasm32")))]
+                    {
+                        // Open the microphone only while the sound trigger is enabled
+                        if Settings::audio_trigger_enabled(self.history.as_ref().unwrap()) {
+                            if self.audio_trigger.is_none() {
+                                self.audio_trigger = AudioTrigger::new().ok();
+                            }
+                            if let Some(audio_trigger) = &mut self.audio_trigger {
+                                if audio_trigger.triggered() {
+                                    self.timer_widget.external_trigger();
+                                }
+                            }
+                            framerate.request(Some(30));
+                        } else {
+                            self.audio_trigger = None;
+                        }
+                    }
+
                     self.timer_widget.update(
                         ctxt,
                         frame,
                         self.history.as_mut().unwrap(),
                         bluetooth_state,
                         bluetooth_events,
-                        bluetooth_name,
+                        bluetooth_input,
+                        external_timer,
                         framerate,
                         &mut self.timer_cube_rect,
                         &mut details,
                         self.solve_details.is_none(),
+                        &self.gamepad,
                         &mut self.solve_type,
                     )
                 }
@@ -483,21 +757,28 @@ impl App for Application {
                     ctxt,
                     frame,
                     self.history.as_mut().unwrap(),
+                    &mut details,
+                    &mut show_day,
                     self.solve_type,
                 ),
                 Mode::Algorithms => {
                     self.algorithms_widget
                         .update(ctxt, frame, self.history.as_mut().unwrap())
                 }
-                Mode::Settings => {
-                    self.settings_widget
-                        .update(ctxt, frame, self.history.as_mut().unwrap())
-                }
+                Mode::Settings => self.settings_widget.update(
+                    ctxt,
+                    frame,
+                    self.history.as_mut().unwrap(),
+                    &self.gamepad,
+                ),
             }
 
             match details {
                 Some(SolveDetails::IndividualSolve(solve)) => {
-                    self.solve_details = Some(SolveDetailsWindow::new(solve));
+                    self.solve_details = Some(SolveDetailsWindow::new(
+                        solve,
+                        self.history.as_ref().unwrap().preferences().move_notation(),
+                    ));
                 }
                 Some(SolveDetails::AverageOfSolves(solves)) => {
                     self.average_details = Some(AverageDetailsWindow::new(solves));
@@ -505,6 +786,12 @@ impl App for Application {
                 None => (),
             }
 
+            if let Some(date) = show_day {
+                // Clicking a day in the calendar shows the solves from that day
+                self.history_widget.scroll_to_date(date);
+                self.mode = Mode::History;
+            }
+
             let mut escape_down = false;
             for event in &ctxt.input().events {
                 match event {
@@ -520,6 +807,8 @@ impl App for Application {
                 }
             }
 
+            self.handle_history_changes(ctxt);
+
             if let Some(solve_details) = &mut self.solve_details {
                 let mut open = true;
                 solve_details.update(
@@ -541,14 +830,22 @@ impl App for Application {
 
                 match details {
                     Some(SolveDetails::IndividualSolve(solve)) => {
-                        self.solve_details = Some(SolveDetailsWindow::new(solve));
+                        self.solve_details = Some(SolveDetailsWindow::new(
+                            solve,
+                            self.history.as_ref().unwrap().preferences().move_notation(),
+                        ));
                     }
                     _ => (),
                 }
             } else if let Some(solve_type_select) = &self.solve_type_select {
                 let mut open = true;
                 let mut selection = None;
-                solve_type_select.update(ctxt, &mut open, &mut selection);
+                solve_type_select.update(
+                    ctxt,
+                    self.history.as_ref().unwrap(),
+                    &mut open,
+                    &mut selection,
+                );
                 if !open || escape_down || selection.is_some() {
                     self.solve_type_select = None;
                 }
@@ -564,6 +861,29 @@ impl App for Application {
                     }
                     _ => (),
                 }
+            } else if let Some(cube_editor) = &mut self.cube_editor {
+                // A connected smart cube's state can be loaded into the editor
+                #[cfg(target_arch = "wasm32")]
+                let cube_state = None;
+                #[cfg(not(target_arch = "wasm32"))]
+                let cube_state = if self.bluetooth.ready() && !self.bluetooth.timer_only() {
+                    Some(self.bluetooth.cube_state())
+                } else {
+                    None
+                };
+
+                let mut open = true;
+                cube_editor.update(ctxt, &mut open, cube_state.as_ref());
+                if !open || escape_down {
+                    self.cube_editor = None;
+                }
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.bluetooth.record_device(self.history.as_mut().unwrap());
+                self.bluetooth_timer
+                    .record_device(self.history.as_mut().unwrap());
             }
 
             #[cfg(not(target_arch = "wasm32"))]
@@ -572,6 +892,7 @@ impl App for Application {
                 self.bluetooth.update(
                     ctxt,
                     frame,
+                    self.history.as_ref().unwrap(),
                     framerate,
                     &mut self.bluetooth_cube_rect,
                     &mut open,
@@ -586,6 +907,27 @@ impl App for Application {
                 }
             }
 
+            #[cfg(not(target_arch = "wasm32"))]
+            if self.bluetooth_timer_dialog_open {
+                let mut open = true;
+                self.bluetooth_timer.update(
+                    ctxt,
+                    frame,
+                    self.history.as_ref().unwrap(),
+                    framerate,
+                    &mut self.bluetooth_cube_rect,
+                    &mut open,
+                );
+                if !open || escape_down {
+                    self.bluetooth_timer_dialog_open = false;
+                    self.bluetooth_timer.close();
+                }
+
+                if self.bluetooth_timer.finished() {
+                    self.bluetooth_timer_dialog_open = false;
+                }
+            }
+
             framerate.commit();
 
             if self.first_frame {
@@ -612,20 +954,41 @@ impl App for Application {
                     std::mem::swap(&mut self.history, history);
                     *loading_history = None;
 
-                    // Load initial solve type from settings
-                    self.solve_type = SolveType::from_str(
-                        &self
-                            .history
-                            .as_ref()
-                            .unwrap()
-                            .setting_as_string("solve_type")
-                            .unwrap_or("3x3x3".into()),
-                    )
-                    .unwrap_or(SolveType::Standard3x3x3);
+                    // Collect changes to the history so that open windows can react to them
+                    let history_changes = self.history_changes.clone();
+                    self.history
+                        .as_mut()
+                        .unwrap()
+                        .register_change_listener(move |change| {
+                            history_changes.lock().unwrap().push(change);
+                        });
+
+                    // Run the user's solve command after each solve
+                    #[cfg(not(target_arch = "wasm32"))]
+                    self.settings_widget
+                        .register_solve_command(self.history.as_mut().unwrap());
+
+                    // Load initial solve type from preferences, or the last used solve
+                    // type on this device if there is no default
+                    let history = self.history.as_ref().unwrap();
+                    self.solve_type = match history.preferences().default_puzzle() {
+                        Some(solve_type) => solve_type,
+                        None => SolveType::from_str(
+                            &history
+                                .setting_as_string("solve_type")
+                                .unwrap_or("3x3x3".into()),
+                        )
+                        .unwrap_or(SolveType::Standard3x3x3),
+                    };
 
                     ctxt.request_repaint();
                 } else if let Err(load_error) = result {
-                    error = Some(load_error.to_string());
+                    error = Some(match load_error {
+                        tpscube_core::Error::Storage(_) => {
+                            format!("Unable to open the solve database: {}", load_error)
+                        }
+                        _ => load_error.to_string(),
+                    });
                 }
             }
 
@@ -759,7 +1122,7 @@ impl ErrorApplication {
 
 impl App for ErrorApplication {
     fn setup(&mut self, ctxt: &CtxRef) {
-        ctxt.set_fonts(font_definitions(ScreenSize::Normal));
+        ctxt.set_fonts(font_definitions(ScreenSize::Normal, 1.0, 1.0));
         ctxt.set_visuals(base_visuals());
     }
 
@@ -819,3 +1182,24 @@ impl Icon {
         }
     }
 }
+
+/// Guest mode keeps the solve history in memory only, for demo machines or
+/// borrowed devices. It is enabled with the `--guest` command line option, or
+/// with `?guest` in the page URL on the web.
+#[cfg(not(target_arch = "wasm32"))]
+fn guest_mode_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--guest")
+}
+
+#[cfg(target_arch = "wasm32")]
+fn guest_mode_requested() -> bool {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .map(|search| {
+            search
+                .trim_start_matches('?')
+                .split('&')
+                .any(|param| param == "guest")
+        })
+        .unwrap_or(false)
+}
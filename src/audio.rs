@@ -0,0 +1,144 @@
+use anyhow::{anyhow, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{InputCallbackInfo, SampleFormat, Stream, StreamConfig};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Length of each analysis window in seconds
+const WINDOW_LENGTH: f32 = 0.01;
+
+/// A window must be this many times louder than the ambient level to trigger
+const TRIGGER_RATIO: f32 = 8.0;
+
+/// Minimum absolute peak level to trigger, so that a silent room does not
+/// cause every small noise to trigger
+const MIN_TRIGGER_LEVEL: f32 = 0.2;
+
+/// Time after a trigger during which other sounds are ignored, so that echoes
+/// or a clap during a start do not immediately stop the timer
+const REFRACTORY_TIME: f32 = 1.0;
+
+/// Starts and stops the timer on a sharp sound from the microphone, such as
+/// a clap, a tap on the table, or a short spoken command. Triggers act like
+/// pressing and releasing the spacebar.
+pub struct AudioTrigger {
+    _stream: Stream,
+    trigger_count: Arc<AtomicUsize>,
+    last_trigger_count: usize,
+}
+
+struct OnsetDetector {
+    window_size: usize,
+    refractory_samples: usize,
+    window_peak: f32,
+    window_energy: f32,
+    window_pos: usize,
+    ambient_level: f32,
+    samples_since_trigger: usize,
+    trigger_count: Arc<AtomicUsize>,
+}
+
+impl AudioTrigger {
+    pub fn new() -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_input_device()
+            .ok_or_else(|| anyhow!("No audio input device found"))?;
+        let config = device.default_input_config()?;
+        let sample_format = config.sample_format();
+        let channels = config.channels() as usize;
+        let config: StreamConfig = config.into();
+
+        let trigger_count = Arc::new(AtomicUsize::new(0));
+        let mut detector = OnsetDetector::new(config.sample_rate.0, trigger_count.clone());
+        let error_callback = |_| ();
+        let stream = match sample_format {
+            SampleFormat::F32 => device.build_input_stream(
+                &config,
+                move |data: &[f32], _: &InputCallbackInfo| {
+                    for frame in data.chunks(channels) {
+                        detector.sample(frame[0]);
+                    }
+                },
+                error_callback,
+            )?,
+            SampleFormat::I16 => device.build_input_stream(
+                &config,
+                move |data: &[i16], _: &InputCallbackInfo| {
+                    for frame in data.chunks(channels) {
+                        detector.sample(frame[0] as f32 / i16::MAX as f32);
+                    }
+                },
+                error_callback,
+            )?,
+            SampleFormat::U16 => device.build_input_stream(
+                &config,
+                move |data: &[u16], _: &InputCallbackInfo| {
+                    for frame in data.chunks(channels) {
+                        detector.sample((frame[0] as f32 - 32768.0) / 32768.0);
+                    }
+                },
+                error_callback,
+            )?,
+        };
+        stream.play()?;
+
+        Ok(Self {
+            _stream: stream,
+            trigger_count,
+            last_trigger_count: 0,
+        })
+    }
+
+    /// Returns true if there has been a trigger since the last call
+    pub fn triggered(&mut self) -> bool {
+        let count = self.trigger_count.load(Ordering::SeqCst);
+        if count != self.last_trigger_count {
+            self.last_trigger_count = count;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl OnsetDetector {
+    fn new(sample_rate: u32, trigger_count: Arc<AtomicUsize>) -> Self {
+        let refractory_samples = (sample_rate as f32 * REFRACTORY_TIME) as usize;
+        Self {
+            window_size: ((sample_rate as f32 * WINDOW_LENGTH) as usize).max(1),
+            refractory_samples,
+            window_peak: 0.0,
+            window_energy: 0.0,
+            window_pos: 0,
+            ambient_level: 0.0,
+            samples_since_trigger: refractory_samples,
+            trigger_count,
+        }
+    }
+
+    fn sample(&mut self, value: f32) {
+        self.window_peak = self.window_peak.max(value.abs());
+        self.window_energy += value * value;
+        self.window_pos += 1;
+        self.samples_since_trigger = self.samples_since_trigger.saturating_add(1);
+        if self.window_pos < self.window_size {
+            return;
+        }
+
+        // A trigger is a window with a peak well above the ambient level
+        let threshold = (self.ambient_level * TRIGGER_RATIO).max(MIN_TRIGGER_LEVEL);
+        if self.window_peak > threshold && self.samples_since_trigger >= self.refractory_samples {
+            self.trigger_count.fetch_add(1, Ordering::SeqCst);
+            self.samples_since_trigger = 0;
+        }
+
+        // Track the ambient level slowly so that sharp sounds stand out from it
+        let rms = (self.window_energy / self.window_size as f32).sqrt();
+        self.ambient_level = self.ambient_level * 0.95 + rms * 0.05;
+
+        self.window_peak = 0.0;
+        self.window_energy = 0.0;
+        self.window_pos = 0;
+    }
+}
@@ -2,19 +2,26 @@ use crate::cube::CubeRenderer;
 use crate::font::FontSize;
 use crate::framerate::Framerate;
 use crate::gl::GlContext;
+use crate::settings::Settings;
 use crate::style::dialog_visuals;
 use crate::theme::Theme;
 use crate::timer::BluetoothEvent;
+use crate::widgets::date_string;
 use anyhow::{anyhow, Result};
 use egui::{
     Color32, CtxRef, Direction, Label, Layout, Rect, ScrollArea, Sense, Stroke, Ui, Vec2, Window,
 };
+use instant::Instant;
 use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, Mutex};
 use tpscube_core::{
-    BluetoothCube, BluetoothCubeEvent, BluetoothCubeState, Cube3x3x3, InitialCubeState,
+    BluetoothCube, BluetoothCubeEvent, BluetoothCubeState, Cube3x3x3, History, InitialCubeState,
+    KnownDevice,
 };
 
+/// Time in milliseconds between saves of the connected device's information
+const DEVICE_RECORD_INTERVAL: u128 = 60000;
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum BluetoothMode {
     DiscoverDevices,
@@ -29,10 +36,13 @@ pub struct BluetoothState {
     mode: BluetoothMode,
     cube: Option<BluetoothCube>,
     error: Option<String>,
+    device_error: Option<String>,
     renderer: CubeRenderer,
     move_queue: Arc<Mutex<Vec<BluetoothCubeEvent>>>,
     cube_state: Cube3x3x3,
     timer_only: bool,
+    timer_required: bool,
+    last_recorded: Option<Instant>,
 }
 
 impl BluetoothState {
@@ -41,10 +51,22 @@ impl BluetoothState {
             mode: BluetoothMode::DiscoverDevices,
             cube: None,
             error: None,
+            device_error: None,
             renderer: CubeRenderer::new(Box::new(Cube3x3x3::new())),
             move_queue: Arc::new(Mutex::new(Vec::new())),
             cube_state: Cube3x3x3::new(),
             timer_only: false,
+            timer_required: false,
+            last_recorded: None,
+        }
+    }
+
+    /// Creates a connection that only accepts smart timers. This is used to
+    /// connect a timer alongside a smart cube.
+    pub fn new_timer() -> Self {
+        Self {
+            timer_required: true,
+            ..Self::new()
         }
     }
 
@@ -85,7 +107,10 @@ impl BluetoothState {
                 }
                 Ok(BluetoothCubeState::Connecting) => "Connecting...".into(),
                 Ok(BluetoothCubeState::Discovering) => "Disconnected".into(),
-                Ok(BluetoothCubeState::Desynced) => "Cube state desynced".into(),
+                Ok(BluetoothCubeState::Desynced) => match &self.device_error {
+                    Some(error) => format!("Cube state desynced: {}", error),
+                    None => "Cube state desynced".into(),
+                },
                 Ok(BluetoothCubeState::Error) => "Internal error".into(),
                 Err(error) => format!("Connection error: {}", error),
             }
@@ -94,6 +119,38 @@ impl BluetoothState {
         }
     }
 
+    /// Hardware details of the connected device, one item per line
+    pub fn device_details(&self) -> Option<String> {
+        let info = self.cube.as_ref()?.device_info().ok()?;
+        let mut lines = vec![format!("Protocol: {}", info.protocol)];
+        if let Some(firmware) = &info.firmware_version {
+            lines.push(format!("Firmware: {}", firmware));
+        }
+        if let Some(hardware) = &info.hardware_version {
+            lines.push(format!("Hardware: {}", hardware));
+        }
+        if let Some(id) = &info.device_id {
+            lines.push(format!("Device ID: {}", id));
+        }
+
+        let features = info.features;
+        let supported: Vec<&str> = [
+            (features.cube_state, "cube state"),
+            (features.reset_cube_state, "reset"),
+            (features.battery, "battery"),
+            (features.orientation, "gyroscope"),
+            (features.timer, "timer"),
+        ]
+        .iter()
+        .filter(|(supported, _)| *supported)
+        .map(|(_, name)| *name)
+        .collect();
+        if supported.len() != 0 {
+            lines.push(format!("Supports: {}", supported.join(", ")));
+        }
+        Some(lines.join("\n"))
+    }
+
     pub fn status_color(&self) -> Color32 {
         if let Some(cube) = &self.cube {
             match cube.state() {
@@ -131,6 +188,14 @@ impl BluetoothState {
         }
     }
 
+    /// Identifier of the connected device, which is its Bluetooth address
+    pub fn connected_id(&self) -> Option<String> {
+        match self.cube.as_ref()?.connected_address() {
+            Ok(Some(address)) => Some(address.to_string()),
+            _ => None,
+        }
+    }
+
     pub fn new_events(&mut self) -> Vec<BluetoothEvent> {
         let mut move_queue = self.move_queue.lock().unwrap();
         let mut result = Vec::new();
@@ -142,21 +207,66 @@ impl BluetoothState {
                     }
                     self.cube_state = state.clone();
                 }
+                BluetoothCubeEvent::Orientation(orientation) => {
+                    result.push(BluetoothEvent::Orientation(*orientation))
+                }
                 BluetoothCubeEvent::HandsOnTimer => result.push(BluetoothEvent::HandsOnTimer),
                 BluetoothCubeEvent::TimerStartCancel => {
                     result.push(BluetoothEvent::TimerStartCancel)
                 }
                 BluetoothCubeEvent::TimerReady => result.push(BluetoothEvent::TimerReady),
                 BluetoothCubeEvent::TimerStarted => result.push(BluetoothEvent::TimerStarted),
+                BluetoothCubeEvent::TimerRunning(time) => {
+                    result.push(BluetoothEvent::TimerRunning(*time))
+                }
                 BluetoothCubeEvent::TimerFinished(time) => {
                     result.push(BluetoothEvent::TimerFinished(*time))
                 }
+                BluetoothCubeEvent::Error(error) => self.device_error = Some(error.clone()),
             }
         }
         move_queue.clear();
         result
     }
 
+    /// Saves the name, battery level and clock calibration of the connected
+    /// device so that they can be shown in the device list and restored on
+    /// the next connection. Call every frame, saves are done periodically.
+    pub fn record_device(&mut self, history: &mut History) {
+        if !self.active() {
+            self.last_recorded = None;
+            return;
+        }
+        if let Some(last_recorded) = self.last_recorded {
+            if (Instant::now() - last_recorded).as_millis() < DEVICE_RECORD_INTERVAL {
+                return;
+            }
+        }
+
+        let id = match self.connected_id() {
+            Some(id) => id,
+            None => return,
+        };
+        let cube = self.cube.as_ref().unwrap();
+        let name = self.name().unwrap_or_default();
+        let mut device = history
+            .known_device(&id)
+            .cloned()
+            .unwrap_or_else(|| KnownDevice::new(id, name.clone()));
+        if !name.is_empty() {
+            device.name = name;
+        }
+        device.seen();
+        if let Ok(Some(battery)) = cube.battery_percentage() {
+            device.battery = Some(battery);
+        }
+        if let Ok(Some(clock_ratio)) = cube.clock_ratio() {
+            device.clock_ratio = Some(clock_ratio);
+        }
+        history.update_known_device(device);
+        self.last_recorded = Some(Instant::now());
+    }
+
     pub fn disconnect(&mut self) {
         if let Some(cube) = &self.cube {
             cube.disconnect();
@@ -165,6 +275,7 @@ impl BluetoothState {
 
     pub fn start_connect_flow(&mut self, frame: &epi::Frame<'_>) {
         self.disconnect();
+        self.device_error = None;
         self.mode = BluetoothMode::DiscoverDevices;
         if self.cube.is_none() {
             let cube = BluetoothCube::new();
@@ -172,8 +283,19 @@ impl BluetoothState {
             let repaint_signal = frame.repaint_signal();
             let move_queue = self.move_queue.clone();
             cube.register_move_listener(move |event| {
-                move_queue.lock().unwrap().push(event);
-                repaint_signal.request_repaint();
+                let mut move_queue = move_queue.lock().unwrap();
+                if let BluetoothCubeEvent::Orientation(_) = &event {
+                    // Orientation updates arrive continuously. Only the latest one is
+                    // needed, and it does not need an immediate repaint.
+                    move_queue.retain(|event| match event {
+                        BluetoothCubeEvent::Orientation(_) => false,
+                        _ => true,
+                    });
+                    move_queue.push(event);
+                } else {
+                    move_queue.push(event);
+                    repaint_signal.request_repaint();
+                }
             });
 
             self.cube = Some(cube);
@@ -186,7 +308,7 @@ impl BluetoothState {
         }
     }
 
-    fn discover_devices(&mut self, ui: &mut Ui) {
+    fn discover_devices(&mut self, ui: &mut Ui, history: &History) {
         ui.vertical(|ui| {
             ui.add(Label::new(
                 "Searching for supported Bluetooth cubes. Click a \
@@ -214,6 +336,7 @@ impl BluetoothState {
                     if let Ok(available_devices) = cube.available_devices() {
                         let mut at_least_one = false;
                         for device in available_devices {
+                            let known = history.known_device(&device.address.to_string());
                             if ui
                                 .add(
                                     Label::new(format!("⮊  {}", device.name))
@@ -222,6 +345,9 @@ impl BluetoothState {
                                 )
                                 .clicked()
                             {
+                                // Restore the clock calibration from the last time this
+                                // device was connected
+                                cube.set_clock_ratio(known.and_then(|known| known.clock_ratio));
                                 match cube.connect(device.address) {
                                     Ok(_) => self.mode = BluetoothMode::WaitForConnection,
                                     Err(error) => {
@@ -230,6 +356,18 @@ impl BluetoothState {
                                     }
                                 }
                             }
+                            if let Some(known) = known {
+                                let mut info =
+                                    format!("Last seen {}", date_string(&known.last_seen()));
+                                if let Some(battery) = known.battery {
+                                    info += &format!("  🔋 {}%", battery);
+                                }
+                                ui.add(
+                                    Label::new(info)
+                                        .text_style(FontSize::Small.into())
+                                        .text_color(Theme::Disabled),
+                                );
+                            }
                             at_least_one = true;
                         }
                         if !at_least_one {
@@ -240,7 +378,7 @@ impl BluetoothState {
         });
     }
 
-    fn waiting_for_connection(&mut self, ui: &mut Ui) -> Result<()> {
+    fn waiting_for_connection(&mut self, ui: &mut Ui, history: &History) -> Result<()> {
         ui.with_layout(Layout::centered_and_justified(Direction::TopDown), |ui| {
             ui.with_layout(
                 Layout::centered_and_justified(Direction::LeftToRight),
@@ -259,10 +397,15 @@ impl BluetoothState {
             BluetoothCubeState::Connected => {
                 let state = cube.cube_state()?;
                 let timer_only = cube.timer_only()?;
+                cube.set_timer_start_latency(Settings::timer_start_latency(history))?;
                 self.cube_state = state.clone();
                 self.timer_only = timer_only;
                 self.renderer.set_cube_state(Box::new(state));
-                if timer_only {
+                if self.timer_required && !timer_only {
+                    cube.disconnect();
+                    self.mode = BluetoothMode::Error;
+                    self.error = Some("Connected device is not a smart timer".into());
+                } else if timer_only {
                     self.mode = BluetoothMode::Finished;
                 } else {
                     self.mode = BluetoothMode::CheckState;
@@ -456,12 +599,21 @@ impl BluetoothState {
         &mut self,
         ctxt: &CtxRef,
         _frame: &mut epi::Frame<'_>,
+        history: &History,
         framerate: &mut Framerate,
         cube_rect: &mut Option<Rect>,
         open: &mut bool,
     ) {
+        // Cube and timer connection dialogs can be open at the same time, and
+        // windows are identified by their title
+        let title = if self.timer_required {
+            "Connect Timer"
+        } else {
+            "Connect"
+        };
+
         ctxt.set_visuals(dialog_visuals());
-        Window::new("Connect")
+        Window::new(title)
             .fixed_size(Vec2::new(250.0, 300.0))
             .collapsible(false)
             .open(open)
@@ -469,14 +621,16 @@ impl BluetoothState {
                 ui.set_min_size(Vec2::new(250.0, 300.0));
                 ui.set_max_size(Vec2::new(250.0, 300.0));
                 match self.mode {
-                    BluetoothMode::DiscoverDevices => self.discover_devices(ui),
-                    BluetoothMode::WaitForConnection => match self.waiting_for_connection(ui) {
-                        Ok(_) => (),
-                        Err(error) => {
-                            self.mode = BluetoothMode::Error;
-                            self.error = Some(error.to_string());
+                    BluetoothMode::DiscoverDevices => self.discover_devices(ui, history),
+                    BluetoothMode::WaitForConnection => {
+                        match self.waiting_for_connection(ui, history) {
+                            Ok(_) => (),
+                            Err(error) => {
+                                self.mode = BluetoothMode::Error;
+                                self.error = Some(error.to_string());
+                            }
                         }
-                    },
+                    }
                     BluetoothMode::CheckState => {
                         match self.check_state(ctxt, ui, framerate, cube_rect) {
                             Ok(_) => (),
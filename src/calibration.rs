@@ -0,0 +1,163 @@
+use crate::font::FontSize;
+use crate::settings::Settings;
+use crate::theme::Theme;
+use egui::{widgets::Label, Key, Sense, Stroke, Ui, Vec2};
+use instant::Instant;
+use tpscube_core::History;
+
+/// Time between flashes of the calibration box in milliseconds
+const BEAT_INTERVAL: u128 = 1000;
+/// Time that the calibration box stays lit in milliseconds
+const FLASH_DURATION: u128 = 100;
+/// Number of taps collected before the calibration result is shown
+const CALIBRATION_TAPS: usize = 10;
+/// Largest timer offset that can be set, in either direction
+const MAX_TIMER_OFFSET: i64 = 500;
+const OFFSET_STEP: i64 = 10;
+const FLASH_BOX_SIZE: f32 = 48.0;
+
+/// Measures the combined keyboard and display latency by having the user tap
+/// along with a flashing box. The median difference between the taps and the
+/// flashes is suggested as the timer offset.
+pub struct TimerCalibration {
+    start: Option<Instant>,
+    taps: Vec<i64>,
+}
+
+impl TimerCalibration {
+    pub fn new() -> Self {
+        Self {
+            start: None,
+            taps: Vec::new(),
+        }
+    }
+
+    fn set_offset(history: &mut History, offset: i64) {
+        let _ = history.set_i64_setting(
+            "timer_offset",
+            offset.max(-MAX_TIMER_OFFSET).min(MAX_TIMER_OFFSET),
+        );
+    }
+
+    /// Median of the tap offsets, or `None` if not enough taps were made
+    fn result(&self) -> Option<i64> {
+        if self.taps.len() < CALIBRATION_TAPS {
+            return None;
+        }
+        let mut taps = self.taps.clone();
+        taps.sort_unstable();
+        Some(taps[taps.len() / 2])
+    }
+
+    pub fn update(&mut self, ui: &mut Ui, history: &mut History) {
+        let offset = Settings::timer_offset(history);
+        ui.add(
+            Label::new(format!("⏲  Timer Offset: {:+} ms", offset))
+                .text_style(FontSize::Section.into()),
+        );
+        ui.horizontal(|ui| {
+            if ui
+                .add(Label::new("➖").sense(Sense::click()))
+                .on_hover_text(format!("-{} ms", OFFSET_STEP))
+                .clicked()
+            {
+                Self::set_offset(history, offset - OFFSET_STEP);
+            }
+            if ui
+                .add(Label::new("➕").sense(Sense::click()))
+                .on_hover_text(format!("+{} ms", OFFSET_STEP))
+                .clicked()
+            {
+                Self::set_offset(history, offset + OFFSET_STEP);
+            }
+            if ui.add(Label::new("Reset").sense(Sense::click())).clicked() {
+                Self::set_offset(history, 0);
+            }
+            if self.start.is_none()
+                && ui
+                    .add(Label::new("Calibrate").sense(Sense::click()))
+                    .clicked()
+            {
+                self.start = Some(Instant::now());
+                self.taps.clear();
+            }
+        });
+        ui.add(
+            Label::new(
+                "Added to every time recorded with the keyboard timer, to correct for \
+                    keyboard and display latency. Calibrate to measure the latency of this \
+                    device.",
+            )
+            .wrap(true),
+        );
+
+        let start = match self.start {
+            Some(start) => start,
+            None => return,
+        };
+
+        ui.add_space(8.0);
+        if let Some(result) = self.result() {
+            ui.add(Label::new(format!(
+                "Taps were {} ms {} the flash on average.",
+                result.abs(),
+                if result >= 0 { "after" } else { "before" }
+            )));
+            ui.horizontal(|ui| {
+                if ui
+                    .add(
+                        Label::new(format!("Apply {:+} ms", -result))
+                            .text_color(Theme::Blue)
+                            .sense(Sense::click()),
+                    )
+                    .clicked()
+                {
+                    Self::set_offset(history, -result);
+                    self.start = None;
+                }
+                if ui.add(Label::new("Cancel").sense(Sense::click())).clicked() {
+                    self.start = None;
+                }
+            });
+            return;
+        }
+
+        // Flash the box at the start of each beat
+        let elapsed = start.elapsed().as_millis();
+        let lit = elapsed % BEAT_INTERVAL < FLASH_DURATION;
+        let (response, painter) =
+            ui.allocate_painter(Vec2::new(FLASH_BOX_SIZE, FLASH_BOX_SIZE), Sense::hover());
+        painter.rect(
+            response.rect,
+            4.0,
+            if lit {
+                Theme::Green.into()
+            } else {
+                Theme::BackgroundHighlight.into()
+            },
+            Stroke::new(1.0, Theme::Disabled),
+        );
+        ui.add(
+            Label::new(format!(
+                "Press space each time the box lights up ({}/{}). Escape to cancel.",
+                self.taps.len(),
+                CALIBRATION_TAPS
+            ))
+            .wrap(true),
+        );
+
+        // Taps are measured against the nearest flash, so taps slightly before
+        // a flash are negative
+        if ui.input().key_pressed(Key::Space) && elapsed >= BEAT_INTERVAL / 2 {
+            let mut tap = (elapsed % BEAT_INTERVAL) as i64;
+            if tap > BEAT_INTERVAL as i64 / 2 {
+                tap -= BEAT_INTERVAL as i64;
+            }
+            self.taps.push(tap);
+        }
+        if ui.input().key_pressed(Key::Escape) {
+            self.start = None;
+        }
+        ui.ctx().request_repaint();
+    }
+}
@@ -1,3 +1,4 @@
 pub mod average;
 pub mod bar;
 pub mod solve;
+pub mod tps;
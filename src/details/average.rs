@@ -3,20 +3,23 @@ use crate::details::bar::SolveBar;
 use crate::font::FontSize;
 use crate::style::dialog_visuals;
 use crate::theme::Theme;
-use crate::widgets::{date_string, solve_time_string};
-use egui::{CtxRef, CursorIcon, Label, Pos2, Rect, Sense, Vec2, Window};
+use crate::widgets::{date_string, solve_time_string, CustomWidgets};
+use egui::{Color32, CtxRef, CursorIcon, Label, Pos2, Rect, Sense, Ui, Vec2, Window};
 use std::cmp::Ordering;
 use tpscube_core::{
-    Analysis, AnalysisStepSummary, AnalysisSummary, Cube, Cube3x3x3, CubeWithSolution,
-    InitialCubeState, ListAverage, Solve,
+    average_trim_count, Analysis, AnalysisStepSummary, AnalysisSummary, Cube, Cube3x3x3,
+    CubeWithSolution, InitialCubeState, ListAverage, PLLAlgorithm, PLLCaseStats, SessionAnalysis,
+    Solve,
 };
 
 const TARGET_MAX_WIDTH: f32 = 300.0;
 const BAR_PADDING: f32 = 16.0;
+const PLL_CASE_COLUMN_WIDTH: f32 = 0.25;
 
 pub struct AverageDetailsWindow {
     average: Option<u32>,
     solves: Vec<SolveWithAnalysis>,
+    pll_cases: Vec<(PLLAlgorithm, PLLCaseStats)>,
 }
 
 struct SolveWithAnalysis {
@@ -31,14 +34,20 @@ impl AverageDetailsWindow {
 
         // Analyze solves
         let mut solves_with_analysis = Vec::new();
+        let mut session_analysis = SessionAnalysis::default();
         for solve in solves {
             let mut unsolved_state = Cube3x3x3::new();
             unsolved_state.do_moves(&solve.scramble);
             let analysis = if let Some(solution) = &solve.moves {
-                Analysis::analyze(&CubeWithSolution {
-                    initial_state: unsolved_state.clone(),
-                    solution: solution.clone(),
-                })
+                let analysis = Analysis::analyze_with_solve_type(
+                    &CubeWithSolution {
+                        initial_state: unsolved_state.clone(),
+                        solution: solution.clone(),
+                    },
+                    solve.solve_type,
+                );
+                session_analysis.add_analysis(&analysis, solution);
+                analysis
             } else {
                 Analysis::default()
             };
@@ -70,13 +79,7 @@ impl AverageDetailsWindow {
         });
 
         // Remove the best and worst time(s) as appropriate for the size of the set.
-        // If there are less than 5 values, use an arithmetic mean and do not
-        // eliminate any values.
-        let to_remove = if sorted.len() >= 5 {
-            (sorted.len() + 39) / 40
-        } else {
-            0
-        };
+        let to_remove = average_trim_count(sorted.len());
         let len = sorted.len();
         for i in 0..to_remove {
             solves_with_analysis[sorted[i]].included_in_average = false;
@@ -86,6 +89,7 @@ impl AverageDetailsWindow {
         Self {
             average,
             solves: solves_with_analysis,
+            pll_cases: session_analysis.pll_case_breakdown(),
         }
     }
 
@@ -202,7 +206,63 @@ impl AverageDetailsWindow {
                         *details = Some(SolveDetails::IndividualSolve(solve.solve.clone()));
                     }
                 }
+
+                // Show time spent on each PLL case, most costly first
+                if self.pll_cases.len() != 0 {
+                    ui.add_space(8.0);
+                    ui.section("PLL Cases");
+                    let headers = ["Case", "Count", "Recognize", "Execute", "Worst"];
+                    let header: Vec<String> = headers.iter().map(|name| name.to_string()).collect();
+                    self.pll_case_row(
+                        ui,
+                        target_width,
+                        &header,
+                        FontSize::Small,
+                        Theme::Disabled.into(),
+                    );
+                    for (algorithm, stats) in &self.pll_cases {
+                        let row = [
+                            algorithm.to_str().to_string(),
+                            format!("{}", stats.count),
+                            solve_time_string(stats.average_recognition_time()),
+                            solve_time_string(stats.average_execution_time()),
+                            solve_time_string(stats.worst_time),
+                        ];
+                        self.pll_case_row(
+                            ui,
+                            target_width,
+                            &row,
+                            FontSize::Normal,
+                            Theme::Content.into(),
+                        );
+                    }
+                }
             });
         });
     }
+
+    /// Draws a row of the PLL case table. The case name is left aligned and
+    /// the remaining columns are right aligned.
+    fn pll_case_row(
+        &self,
+        ui: &mut Ui,
+        target_width: f32,
+        columns: &[String],
+        font: FontSize,
+        color: Color32,
+    ) {
+        let (_, rect) =
+            ui.allocate_space(Vec2::new(target_width, ui.fonts().row_height(font.into())));
+        let value_width = target_width * (1.0 - PLL_CASE_COLUMN_WIDTH) / (columns.len() - 1) as f32;
+        for (idx, column) in columns.iter().enumerate() {
+            let galley = ui.fonts().layout_single_line(font.into(), column.clone());
+            let x = if idx == 0 {
+                rect.left()
+            } else {
+                rect.left() + target_width * PLL_CASE_COLUMN_WIDTH + value_width * idx as f32
+                    - galley.size.x
+            };
+            ui.painter().galley(Pos2::new(x, rect.top()), galley, color);
+        }
+    }
 }
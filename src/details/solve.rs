@@ -1,5 +1,6 @@
 use crate::cube::CubeRenderer;
 use crate::details::bar::SolveBar;
+use crate::details::tps::TPSGraph;
 use crate::font::FontSize;
 use crate::framerate::Framerate;
 use crate::gl::GlContext;
@@ -16,25 +17,32 @@ use egui::{
 };
 use instant::Instant;
 use tpscube_core::{
-    Analysis, AnalysisStepSummary, AnalysisSummary, Cube, Cube2x2x2, Cube3x3x3, CubeWithSolution,
-    InitialCubeState, Solve, SolveType,
+    Analysis, AnalysisStepSummary, AnalysisSummary, AnalysisWarning, Cube, Cube3x3x3,
+    CubeWithSolution, FingertrickAnalysis, InitialCubeState, MoveNotation, Solve, SolveType,
+    TPSCurve,
 };
 
 const TARGET_MIN_WIDTH: f32 = 280.0;
 const TARGET_MAX_WIDTH: f32 = 400.0;
 const STEP_COLUMN_PADDING: f32 = 8.0;
+const TPS_GRAPH_HEIGHT: f32 = 80.0;
 
 pub struct SolveDetailsWindow {
     solve: Solve,
     unsolved_state: Box<dyn Cube>,
     analysis: Analysis,
+    warnings: Vec<AnalysisWarning>,
+    fingertricks: Vec<FingertrickAnalysis>,
     summary: Vec<AnalysisStepSummary>,
+    tps: Option<TPSCurve>,
     renderer: CubeRenderer,
     replay_time: f32,
     replay_move_idx: usize,
+    step_boundaries: Vec<usize>,
     playing: bool,
     last_frame: Instant,
     mode: SolveDetailsMode,
+    notation: MoveNotation,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -44,10 +52,15 @@ enum SolveDetailsMode {
 }
 
 impl SolveDetailsWindow {
-    pub fn new(solve: Solve) -> Self {
+    pub fn new(solve: Solve, notation: MoveNotation) -> Self {
         match solve.solve_type {
-            SolveType::Standard2x2x2 => {
-                let mut unsolved_state = Box::new(Cube2x2x2::new());
+            SolveType::Standard2x2x2
+            | SolveType::Standard5x5x5
+            | SolveType::Standard6x6x6
+            | SolveType::Standard7x7x7
+            | SolveType::Void3x3x3
+            | SolveType::Bandaged3x3x3 => {
+                let mut unsolved_state = solve.solve_type.new_cube();
                 unsolved_state.do_moves(&solve.scramble);
                 let renderer = CubeRenderer::new(unsolved_state.dyn_clone());
 
@@ -55,13 +68,18 @@ impl SolveDetailsWindow {
                     solve,
                     unsolved_state,
                     analysis: Analysis::default(),
+                    warnings: Vec::new(),
+                    fingertricks: Vec::new(),
                     summary: Vec::new(),
+                    tps: None,
                     renderer,
                     replay_time: 0.0,
                     replay_move_idx: 0,
+                    step_boundaries: Vec::new(),
                     playing: false,
                     last_frame: Instant::now(),
                     mode: SolveDetailsMode::Replay,
+                    notation,
                 }
             }
             SolveType::Standard3x3x3 | SolveType::OneHanded3x3x3 | SolveType::Blind3x3x3 => {
@@ -69,27 +87,55 @@ impl SolveDetailsWindow {
                 unsolved_state.do_moves(&solve.scramble);
                 let renderer = CubeRenderer::new(Box::new(unsolved_state.clone()));
 
-                let analysis = if let Some(solution) = &solve.moves {
-                    Analysis::analyze(&CubeWithSolution {
+                let (analysis, warnings) = if let Some(solution) = &solve.moves {
+                    let solution = CubeWithSolution {
                         initial_state: unsolved_state.clone(),
                         solution: solution.clone(),
-                    })
+                    };
+                    let analysis = Analysis::analyze_with_solve_type(&solution, solve.solve_type);
+                    let warnings = analysis.warnings(&solution);
+                    (analysis, warnings)
                 } else {
-                    Analysis::default()
+                    (Analysis::default(), Vec::new())
                 };
                 let summary = analysis.detailed_step_summary();
+                let step_boundaries = match &analysis {
+                    Analysis::CFOP(cfop) => cfop
+                        .step_boundaries(&unsolved_state)
+                        .iter()
+                        .map(|boundary| boundary.move_index)
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                let tps = match &solve.moves {
+                    Some(solution) if solution.len() != 0 => {
+                        Some(TPSCurve::new(solution, &summary))
+                    }
+                    _ => None,
+                };
+                let fingertricks = match (&analysis, &solve.moves) {
+                    (Analysis::CFOP(cfop), Some(solution)) => {
+                        cfop.last_layer_fingertricks(solution)
+                    }
+                    _ => Vec::new(),
+                };
 
                 Self {
                     solve,
                     unsolved_state: Box::new(unsolved_state),
                     analysis,
+                    warnings,
+                    fingertricks,
                     summary,
+                    tps,
                     renderer,
                     replay_time: 0.0,
                     replay_move_idx: 0,
+                    step_boundaries,
                     playing: false,
                     last_frame: Instant::now(),
                     mode: SolveDetailsMode::Replay,
+                    notation,
                 }
             }
         }
@@ -118,6 +164,18 @@ impl SolveDetailsWindow {
         }
     }
 
+    /// Moves the replay to the end of a step, with the replay time at the
+    /// last move of the step
+    fn go_to_step_boundary(&mut self, move_idx: usize) {
+        self.go_to_move_idx(move_idx);
+        self.replay_time = if self.replay_move_idx > 0 {
+            self.solve.moves.as_ref().unwrap()[self.replay_move_idx - 1].time() as f32 / 1000.0
+        } else {
+            0.0
+        };
+        self.playing = false;
+    }
+
     fn go_to_time(&mut self, time: f32) {
         if let Some(solution) = &self.solve.moves {
             if solution.len() > 0 {
@@ -179,6 +237,8 @@ impl SolveDetailsWindow {
         let mut right_down = false;
         let mut home_down = false;
         let mut end_down = false;
+        let mut page_up_down = false;
+        let mut page_down_down = false;
         for event in &ctxt.input().events {
             match event {
                 Event::Key { key, pressed, .. } => {
@@ -189,6 +249,8 @@ impl SolveDetailsWindow {
                             Key::ArrowRight => right_down = true,
                             Key::Home => home_down = true,
                             Key::End => end_down = true,
+                            Key::PageUp => page_up_down = true,
+                            Key::PageDown => page_down_down = true,
                             _ => (),
                         }
                     }
@@ -230,6 +292,25 @@ impl SolveDetailsWindow {
                 ui.add(Label::new("⏮"));
             }
 
+            let prev_boundary = self
+                .step_boundaries
+                .iter()
+                .rev()
+                .find(|idx| **idx < self.replay_move_idx)
+                .cloned();
+            if self.step_boundaries.len() != 0 && self.replay_move_idx > 0 {
+                if ui
+                    .add(Label::new("⏪").sense(Sense::click()))
+                    .on_hover_text("Go to previous step")
+                    .clicked()
+                    || page_up_down
+                {
+                    self.go_to_step_boundary(prev_boundary.unwrap_or(0));
+                }
+            } else if self.step_boundaries.len() != 0 {
+                ui.add(Label::new("⏪"));
+            }
+
             if self.replay_move_idx > 0 {
                 if ui
                     .add(Label::new("⬅").sense(Sense::click()))
@@ -296,6 +377,34 @@ impl SolveDetailsWindow {
                 ui.add(Label::new("➡"));
             }
 
+            let next_boundary = self
+                .step_boundaries
+                .iter()
+                .find(|idx| **idx > self.replay_move_idx)
+                .cloned();
+            if let Some(next_boundary) = next_boundary {
+                if ui
+                    .add(Label::new("⏩").sense(Sense::click()))
+                    .on_hover_text("Go to next step")
+                    .clicked()
+                    || page_down_down
+                {
+                    self.go_to_step_boundary(next_boundary);
+                }
+            } else if self.step_boundaries.len() != 0 {
+                ui.add(Label::new("⏩"));
+            }
+
+            if ui
+                .add(Label::new("🗐").sense(Sense::click()))
+                .on_hover_text("Copy reconstruction")
+                .clicked()
+            {
+                if let Some(text) = self.solve.reconstruction_text() {
+                    ui.output().copied_text = text;
+                }
+            }
+
             if end_down {
                 self.replay_time =
                     self.solve.moves.as_ref().unwrap().last().unwrap().time() as f32 / 1000.0;
@@ -364,12 +473,20 @@ impl SolveDetailsWindow {
     ) {
         ui.vertical_centered(|ui| {
             // Fit scramble to desired window size
-            let scramble_lines =
-                fit_scramble(ui, FontSize::Section, &self.solve.scramble, target_width);
+            let scramble_lines = fit_scramble(
+                ui,
+                FontSize::Section,
+                &self.solve.scramble,
+                self.notation,
+                target_width,
+            );
 
             // Add scramble at top
             for line in scramble_lines {
-                let line: Vec<String> = line.iter().map(|mv| mv.to_string()).collect();
+                let line: Vec<String> = line
+                    .iter()
+                    .map(|mv| mv.to_string_with(self.notation))
+                    .collect();
                 let line = line.join(" ");
                 ui.add(
                     Label::new(line)
@@ -389,6 +506,15 @@ impl SolveDetailsWindow {
                 );
             }
 
+            // Explain any problems found while analyzing the solve
+            for warning in &self.warnings {
+                ui.add(
+                    Label::new(format!("⚠  {}", warning))
+                        .text_style(FontSize::Small.into())
+                        .text_color(Theme::Orange),
+                );
+            }
+
             // Allocate space for the cube rendering, this will be rendered using
             // OpenGL later.
             let (id, rect) = ui.allocate_space(Vec2::new(cube_size, cube_size));
@@ -586,7 +712,19 @@ impl SolveDetailsWindow {
                     target_width,
                     ui.fonts().row_height(FontSize::Small.into()),
                 ));
-                let response = ui.interact(rect, id, Sense::click());
+                let mut response = ui.interact(rect, id, Sense::click());
+
+                // Show fingertrick hints when hovering over last layer algorithms
+                if let Some(fingertricks) = self
+                    .fingertricks
+                    .iter()
+                    .find(|fingertricks| fingertricks.start_move_index == cur_move_index)
+                {
+                    let description = fingertricks.description();
+                    if description.len() != 0 {
+                        response = response.on_hover_text(description);
+                    }
+                }
 
                 if response.clicked() {
                     // If a step row is clicked, navigate to the beginning of the
@@ -736,10 +874,23 @@ impl SolveDetailsWindow {
                 Theme::Content.into(),
             );
 
+            // Show TPS over the course of the solve
+            if let Some(tps) = &self.tps {
+                ui.add_space(8.0);
+                ui.section("Turns per Second");
+                let (_, rect) = ui.allocate_space(Vec2::new(target_width, TPS_GRAPH_HEIGHT));
+                let max_time = self.solve.moves.as_ref().unwrap().last().unwrap().time();
+                TPSGraph::new(tps, max_time, Some(self.replay_time)).draw(ui, rect);
+            }
+
             ui.add_space(8.0);
         });
     }
 
+    pub fn solve_id(&self) -> &str {
+        &self.solve.id
+    }
+
     pub fn update(
         &mut self,
         ctxt: &CtxRef,
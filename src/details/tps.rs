@@ -0,0 +1,120 @@
+use crate::font::FontSize;
+use crate::theme::Theme;
+use crate::widgets::color_for_step_index;
+use egui::{Pos2, Rect, Stroke, Ui};
+use tpscube_core::TPSCurve;
+
+const AXIS_LABEL_PADDING: f32 = 4.0;
+
+/// Plot of the TPS curve of a single solve. The rolling TPS is drawn as a
+/// line over the instantaneous TPS of each move, with the average TPS of
+/// each step overlaid as a horizontal segment in the color of the step.
+pub struct TPSGraph<'a> {
+    curve: &'a TPSCurve,
+    max_time: u32,
+    cursor: Option<f32>,
+}
+
+impl<'a> TPSGraph<'a> {
+    pub fn new(curve: &'a TPSCurve, max_time: u32, cursor: Option<f32>) -> Self {
+        Self {
+            curve,
+            max_time,
+            cursor,
+        }
+    }
+
+    pub fn draw(&self, ui: &mut Ui, rect: Rect) {
+        if self.curve.samples.len() == 0 || self.max_time == 0 {
+            return;
+        }
+
+        // Round the TPS axis up to a whole number so that the label is useful
+        let max_tps = self.curve.max_tps().ceil().max(1.0);
+        let label = ui
+            .fonts()
+            .layout_single_line(FontSize::Small.into(), format!("{} TPS", max_tps));
+        let plot_rect = Rect::from_min_max(
+            Pos2::new(rect.left(), rect.top() + label.size.y + AXIS_LABEL_PADDING),
+            rect.max,
+        );
+        ui.painter()
+            .galley(rect.left_top(), label, Theme::Disabled.into());
+
+        let x =
+            |time: u32| plot_rect.left() + plot_rect.width() * time as f32 / self.max_time as f32;
+        let y = |tps: f32| plot_rect.bottom() - plot_rect.height() * tps.min(max_tps) / max_tps;
+
+        // Draw axes
+        let axis = Stroke {
+            width: 1.0,
+            color: Theme::Light.into(),
+        };
+        ui.painter()
+            .line_segment([plot_rect.left_top(), plot_rect.left_bottom()], axis);
+        ui.painter()
+            .line_segment([plot_rect.left_bottom(), plot_rect.right_bottom()], axis);
+
+        // Draw step overlays and the boundaries between steps
+        for step in &self.curve.steps {
+            let color = color_for_step_index(step.major_step_index);
+            ui.painter().line_segment(
+                [
+                    Pos2::new(x(step.end_time), plot_rect.top()),
+                    Pos2::new(x(step.end_time), plot_rect.bottom()),
+                ],
+                axis,
+            );
+            ui.painter().line_segment(
+                [
+                    Pos2::new(x(step.start_time), y(step.tps)),
+                    Pos2::new(x(step.end_time), y(step.tps)),
+                ],
+                Stroke { width: 3.0, color },
+            );
+        }
+
+        // Draw instantaneous TPS of each move
+        for sample in &self.curve.samples {
+            ui.painter().line_segment(
+                [
+                    Pos2::new(x(sample.time), y(0.0)),
+                    Pos2::new(x(sample.time), y(sample.instantaneous)),
+                ],
+                Stroke {
+                    width: 1.0,
+                    color: Theme::Disabled.into(),
+                },
+            );
+        }
+
+        // Draw rolling TPS curve
+        for pair in self.curve.samples.windows(2) {
+            ui.painter().line_segment(
+                [
+                    Pos2::new(x(pair[0].time), y(pair[0].rolling)),
+                    Pos2::new(x(pair[1].time), y(pair[1].rolling)),
+                ],
+                Stroke {
+                    width: 2.0,
+                    color: Theme::Blue.into(),
+                },
+            );
+        }
+
+        if let Some(cursor) = self.cursor {
+            // Draw indicator of replay position
+            let cursor_x = x((cursor * 1000.0) as u32);
+            ui.painter().line_segment(
+                [
+                    Pos2::new(cursor_x, plot_rect.top()),
+                    Pos2::new(cursor_x, plot_rect.bottom()),
+                ],
+                Stroke {
+                    width: 1.0,
+                    color: Theme::Content.into(),
+                },
+            );
+        }
+    }
+}
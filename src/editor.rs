@@ -0,0 +1,261 @@
+use crate::font::FontSize;
+use crate::style::dialog_visuals;
+use crate::theme::Theme;
+use crate::widgets::CustomWidgets;
+use egui::{CtxRef, Label, Rect, Sense, Stroke, Ui, Vec2, Window};
+use std::convert::TryFrom;
+use tpscube_core::{
+    CFOPPartialAnalysis, CFOPProgress, Color, Cube, Cube3x3x3, Cube3x3x3Faces, CubeFace,
+    CubeWithSolution, InitialCubeState, MoveSequence,
+};
+
+const STICKER_SIZE: f32 = 22.0;
+const STICKER_GAP: f32 = 2.0;
+const FACE_GAP: f32 = 6.0;
+
+// Position of each face in the unfolded cube, in units of faces
+const FACE_X: [f32; 6] = [1.0, 1.0, 2.0, 3.0, 0.0, 1.0];
+const FACE_Y: [f32; 6] = [0.0, 1.0, 1.0, 1.0, 1.0, 2.0];
+
+const COLORS: [Color; 6] = [
+    Color::White,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+    Color::Orange,
+    Color::Yellow,
+];
+
+/// Window for entering an arbitrary 3x3x3 cube state by painting stickers on an
+/// unfolded cube. Once the state is valid it can be solved or analyzed, which
+/// is useful for returning a cube to the solved state after a smart cube loses
+/// sync, or for looking at a specific case.
+pub struct CubeEditorWindow {
+    faces: Cube3x3x3Faces,
+    paint: Color,
+    result: Option<EditorResult>,
+}
+
+enum EditorResult {
+    Invalid(String),
+    Solution(String),
+    Analysis(String),
+}
+
+impl CubeEditorWindow {
+    pub fn new() -> Self {
+        Self {
+            faces: Cube3x3x3Faces::new(),
+            paint: Color::White,
+            result: None,
+        }
+    }
+
+    fn face_size() -> f32 {
+        STICKER_SIZE * 3.0 + STICKER_GAP * 2.0 + FACE_GAP
+    }
+
+    fn stickers(&mut self, ui: &mut Ui) {
+        let (rect, _) = ui.allocate_exact_size(
+            Vec2::new(Self::face_size() * 4.0, Self::face_size() * 3.0),
+            Sense::hover(),
+        );
+
+        for face_idx in 0..6 {
+            let face = CubeFace::try_from(face_idx as u8).unwrap();
+            let face_pos =
+                rect.left_top() + Vec2::new(FACE_X[face_idx], FACE_Y[face_idx]) * Self::face_size();
+            for row in 0..3 {
+                for col in 0..3 {
+                    let sticker_rect = Rect::from_min_size(
+                        face_pos
+                            + Vec2::new(
+                                col as f32 * (STICKER_SIZE + STICKER_GAP),
+                                row as f32 * (STICKER_SIZE + STICKER_GAP),
+                            ),
+                        Vec2::new(STICKER_SIZE, STICKER_SIZE),
+                    );
+
+                    // Centers define the orientation of the cube and can't be changed
+                    let center = row == 1 && col == 1;
+                    let interact = ui.allocate_rect(
+                        sticker_rect,
+                        if center {
+                            Sense::hover()
+                        } else {
+                            Sense::click()
+                        },
+                    );
+                    if interact.clicked() {
+                        self.faces.set_color(face, row, col, self.paint);
+                        self.result = None;
+                    }
+
+                    ui.painter().rect_filled(
+                        sticker_rect,
+                        2.0,
+                        Theme::sticker(self.faces.color(face, row, col)),
+                    );
+                    if interact.hovered() && !center {
+                        ui.painter().rect_stroke(
+                            sticker_rect,
+                            2.0,
+                            Stroke {
+                                width: 2.0,
+                                color: Theme::Content.into(),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn palette(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            for color in &COLORS {
+                let (rect, interact) =
+                    ui.allocate_exact_size(Vec2::new(STICKER_SIZE, STICKER_SIZE), Sense::click());
+                if interact.clicked() {
+                    self.paint = *color;
+                }
+                ui.painter().rect_filled(rect, 2.0, Theme::sticker(*color));
+                if self.paint == *color {
+                    ui.painter().rect_stroke(
+                        rect.expand(2.0),
+                        2.0,
+                        Stroke {
+                            width: 2.0,
+                            color: Theme::Blue.into(),
+                        },
+                    );
+                }
+                interact.on_hover_text(color.to_str());
+            }
+        });
+    }
+
+    fn solve(&mut self) {
+        self.result = Some(match self.faces.try_as_pieces() {
+            Ok(cube) => match cube.solve() {
+                Some(solution) if solution.len() == 0 => {
+                    EditorResult::Solution("Cube is already solved".into())
+                }
+                Some(solution) => EditorResult::Solution(format!(
+                    "{} ({} moves)",
+                    solution.to_string(),
+                    solution.len()
+                )),
+                None => EditorResult::Invalid("No solution found".into()),
+            },
+            Err(error) => EditorResult::Invalid(error.to_string()),
+        });
+    }
+
+    fn analyze(&mut self) {
+        self.result = Some(match self.faces.try_as_pieces() {
+            Ok(cube) => EditorResult::Analysis(Self::describe_state(cube)),
+            Err(error) => EditorResult::Invalid(error.to_string()),
+        });
+    }
+
+    /// Describes the CFOP progress of a cube state, using the cross color with
+    /// the most progress
+    fn describe_state(cube: Cube3x3x3) -> String {
+        let analysis = CFOPPartialAnalysis::analyze(&CubeWithSolution {
+            initial_state: cube,
+            solution: Vec::new(),
+        });
+        let cross = match &analysis.cross {
+            Some(cross) => format!("{} {}", cross.color.to_str(), cross.to_str().to_lowercase()),
+            None => "Cross".into(),
+        };
+        match analysis.progress {
+            CFOPProgress::Initial => "Cross is not solved".into(),
+            CFOPProgress::F2LPair(count) => {
+                format!("{} solved with {} of 4 F2L pairs", cross, count)
+            }
+            CFOPProgress::OLL(algorithm) => {
+                format!("F2L solved, OLL case is {}", algorithm.to_string())
+            }
+            CFOPProgress::PLL(algorithm) => {
+                format!("Last layer oriented, PLL case is {}", algorithm.to_str())
+            }
+            CFOPProgress::FinalAlignment => "Last layer needs alignment".into(),
+            CFOPProgress::Solved => "Cube is solved".into(),
+        }
+    }
+
+    /// Shows the editor. `cube_state` is the state of a connected smart cube,
+    /// if there is one, which can be loaded as a starting point.
+    pub fn update(&mut self, ctxt: &CtxRef, open: &mut bool, cube_state: Option<&Cube3x3x3>) {
+        ctxt.set_visuals(dialog_visuals());
+
+        Window::new("Cube State Editor")
+            .collapsible(false)
+            .resizable(false)
+            .open(open)
+            .show(ctxt, |ui| {
+                ui.vertical(|ui| {
+                    ui.add(
+                        Label::new("Choose a color, then click stickers to paint them")
+                            .text_color(Theme::Disabled),
+                    );
+                    ui.add_space(4.0);
+                    self.palette(ui);
+                    ui.add_space(8.0);
+                    self.stickers(ui);
+
+                    ui.section_separator();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(Label::new("🔍  Solve").sense(Sense::click()))
+                            .clicked()
+                        {
+                            self.solve();
+                        }
+                        if ui
+                            .add(Label::new("📊  Analyze").sense(Sense::click()))
+                            .clicked()
+                        {
+                            self.analyze();
+                        }
+                        if ui
+                            .add(Label::new("↺  Reset").sense(Sense::click()))
+                            .clicked()
+                        {
+                            self.faces = Cube3x3x3Faces::new();
+                            self.result = None;
+                        }
+                        if let Some(cube_state) = cube_state {
+                            if ui
+                                .add(Label::new("🔗  Load from cube").sense(Sense::click()))
+                                .on_hover_text("Start from the state reported by the smart cube")
+                                .clicked()
+                            {
+                                self.faces = cube_state.as_faces();
+                                self.result = None;
+                            }
+                        }
+                    });
+
+                    match &self.result {
+                        Some(EditorResult::Invalid(message)) => {
+                            ui.add(Label::new(message).text_color(Theme::Red));
+                        }
+                        Some(EditorResult::Solution(solution)) => {
+                            ui.add(
+                                Label::new(solution)
+                                    .text_style(FontSize::Scramble.into())
+                                    .wrap(true),
+                            );
+                        }
+                        Some(EditorResult::Analysis(description)) => {
+                            ui.add(Label::new(description).text_color(Theme::Green));
+                        }
+                        None => (),
+                    }
+                });
+            });
+    }
+}
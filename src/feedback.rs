@@ -0,0 +1,264 @@
+use crate::settings::Settings;
+use crate::timer::BluetoothEvent;
+use instant::Instant;
+use std::time::Duration;
+use tpscube_core::{History, Move};
+
+#[cfg(feature = "move-sound")]
+use anyhow::{anyhow, Result};
+#[cfg(feature = "move-sound")]
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+#[cfg(feature = "move-sound")]
+use cpal::{OutputCallbackInfo, SampleFormat, Stream, StreamConfig};
+#[cfg(feature = "move-sound")]
+use std::sync::{Arc, Mutex};
+
+/// Moves that arrive closer together than this only trigger feedback once,
+/// so that fast turning does not turn into a continuous buzz
+const FEEDBACK_DEBOUNCE: Duration = Duration::from_millis(40);
+
+/// Kinds of moves that can have different feedback
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MoveFeedbackType {
+    QuarterTurn,
+    HalfTurn,
+}
+
+/// Feedback given when a smart cube move is confirmed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MoveFeedbackStyle {
+    Off,
+    LowTick,
+    HighTick,
+}
+
+/// Dispatches confirmed smart cube moves to registered feedback hooks, such
+/// as a sound tick. Each kind of move can be configured separately in the
+/// settings, and hooks are only called for moves that have feedback enabled.
+pub struct MoveFeedback {
+    hooks: Vec<Box<dyn FnMut(Move, MoveFeedbackStyle)>>,
+    last_feedback: Option<Instant>,
+}
+
+impl MoveFeedbackType {
+    pub const ALL: &'static [MoveFeedbackType] =
+        &[MoveFeedbackType::QuarterTurn, MoveFeedbackType::HalfTurn];
+
+    pub fn for_move(mv: Move) -> Self {
+        if mv.rotation() == 2 {
+            MoveFeedbackType::HalfTurn
+        } else {
+            MoveFeedbackType::QuarterTurn
+        }
+    }
+
+    pub fn setting_name(&self) -> &'static str {
+        match self {
+            MoveFeedbackType::QuarterTurn => "move_feedback_quarter",
+            MoveFeedbackType::HalfTurn => "move_feedback_half",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoveFeedbackType::QuarterTurn => "Quarter turns",
+            MoveFeedbackType::HalfTurn => "Half turns",
+        }
+    }
+}
+
+impl MoveFeedbackStyle {
+    pub const ALL: &'static [MoveFeedbackStyle] = &[
+        MoveFeedbackStyle::Off,
+        MoveFeedbackStyle::LowTick,
+        MoveFeedbackStyle::HighTick,
+    ];
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "off" => Some(MoveFeedbackStyle::Off),
+            "low_tick" => Some(MoveFeedbackStyle::LowTick),
+            "high_tick" => Some(MoveFeedbackStyle::HighTick),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            MoveFeedbackStyle::Off => "off",
+            MoveFeedbackStyle::LowTick => "low_tick",
+            MoveFeedbackStyle::HighTick => "high_tick",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoveFeedbackStyle::Off => "Off",
+            MoveFeedbackStyle::LowTick => "Low tick",
+            MoveFeedbackStyle::HighTick => "High tick",
+        }
+    }
+}
+
+impl MoveFeedback {
+    pub fn new() -> Self {
+        Self {
+            hooks: Vec::new(),
+            last_feedback: None,
+        }
+    }
+
+    /// Adds a hook that is called with each move that has feedback enabled
+    pub fn add_hook<F: FnMut(Move, MoveFeedbackStyle) + 'static>(&mut self, hook: F) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Returns true if any kind of move has feedback enabled
+    pub fn enabled(history: &History) -> bool {
+        MoveFeedbackType::ALL
+            .iter()
+            .any(|move_type| Settings::move_feedback(history, *move_type) != MoveFeedbackStyle::Off)
+    }
+
+    /// Gives feedback for the moves in a set of Bluetooth events
+    pub fn update(&mut self, history: &History, events: &[BluetoothEvent]) {
+        for event in events {
+            if let BluetoothEvent::Move(mv) = event {
+                let mv = mv.move_();
+                let style = Settings::move_feedback(history, MoveFeedbackType::for_move(mv));
+                if style == MoveFeedbackStyle::Off {
+                    continue;
+                }
+
+                let now = Instant::now();
+                if let Some(last) = self.last_feedback {
+                    if now - last < FEEDBACK_DEBOUNCE {
+                        continue;
+                    }
+                }
+                self.last_feedback = Some(now);
+
+                for hook in &mut self.hooks {
+                    hook(mv, style);
+                }
+            }
+        }
+    }
+}
+
+/// Plays a short tick on the default audio output for each move
+#[cfg(feature = "move-sound")]
+pub struct TickSound {
+    _stream: Stream,
+    pending: Arc<Mutex<Option<MoveFeedbackStyle>>>,
+}
+
+#[cfg(feature = "move-sound")]
+struct TickGenerator {
+    sample_rate: f32,
+    pending: Arc<Mutex<Option<MoveFeedbackStyle>>>,
+    frequency: f32,
+    position: usize,
+    length: usize,
+}
+
+#[cfg(feature = "move-sound")]
+impl TickSound {
+    /// Length of each tick in seconds
+    const TICK_LENGTH: f32 = 0.015;
+
+    pub fn new() -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or_else(|| anyhow!("No audio output device found"))?;
+        let config = device.default_output_config()?;
+        let sample_format = config.sample_format();
+        let channels = config.channels() as usize;
+        let config: StreamConfig = config.into();
+
+        let pending = Arc::new(Mutex::new(None));
+        let mut generator = TickGenerator {
+            sample_rate: config.sample_rate.0 as f32,
+            pending: pending.clone(),
+            frequency: 0.0,
+            position: 0,
+            length: 0,
+        };
+        let error_callback = |_| ();
+        let stream = match sample_format {
+            SampleFormat::F32 => device.build_output_stream(
+                &config,
+                move |data: &mut [f32], _: &OutputCallbackInfo| {
+                    generator.start_pending();
+                    for frame in data.chunks_mut(channels) {
+                        let value = generator.sample();
+                        frame.iter_mut().for_each(|sample| *sample = value);
+                    }
+                },
+                error_callback,
+            )?,
+            SampleFormat::I16 => device.build_output_stream(
+                &config,
+                move |data: &mut [i16], _: &OutputCallbackInfo| {
+                    generator.start_pending();
+                    for frame in data.chunks_mut(channels) {
+                        let value = (generator.sample() * i16::MAX as f32) as i16;
+                        frame.iter_mut().for_each(|sample| *sample = value);
+                    }
+                },
+                error_callback,
+            )?,
+            SampleFormat::U16 => device.build_output_stream(
+                &config,
+                move |data: &mut [u16], _: &OutputCallbackInfo| {
+                    generator.start_pending();
+                    for frame in data.chunks_mut(channels) {
+                        let value = (generator.sample() * 32767.0 + 32768.0) as u16;
+                        frame.iter_mut().for_each(|sample| *sample = value);
+                    }
+                },
+                error_callback,
+            )?,
+        };
+        stream.play()?;
+
+        Ok(Self {
+            _stream: stream,
+            pending,
+        })
+    }
+
+    pub fn play(&self, style: MoveFeedbackStyle) {
+        *self.pending.lock().unwrap() = Some(style);
+    }
+}
+
+#[cfg(feature = "move-sound")]
+impl TickGenerator {
+    /// Starts a new tick if one was requested. This is checked once per
+    /// buffer to avoid locking for every sample.
+    fn start_pending(&mut self) {
+        if let Some(style) = self.pending.lock().unwrap().take() {
+            self.frequency = match style {
+                MoveFeedbackStyle::Off => 0.0,
+                MoveFeedbackStyle::LowTick => 1000.0,
+                MoveFeedbackStyle::HighTick => 2500.0,
+            };
+            self.position = 0;
+            self.length = (self.sample_rate * TickSound::TICK_LENGTH) as usize;
+        }
+    }
+
+    fn sample(&mut self) -> f32 {
+        if self.position >= self.length || self.frequency == 0.0 {
+            return 0.0;
+        }
+
+        // Sine burst with an exponential decay so that it sounds like a click
+        let t = self.position as f32 / self.sample_rate;
+        let envelope = (-(self.position as f32) * 5.0 / self.length as f32).exp();
+        self.position += 1;
+        (t * self.frequency * std::f32::consts::TAU).sin() * envelope * 0.3
+    }
+}
@@ -17,6 +17,9 @@ pub enum FontSize {
     Timer,
 }
 
+/// Scale of the timer and scramble fonts in large display mode
+pub const LARGE_DISPLAY_SCALE: f32 = 2.0;
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ScreenSize {
     Small,
@@ -48,7 +51,14 @@ impl LabelFontSize for Label {
     }
 }
 
-pub fn font_definitions(screen_size: ScreenSize) -> FontDefinitions {
+/// Creates fonts for the given screen size. The scramble font is scaled by
+/// `scramble_scale`, and both the scramble and timer fonts are scaled by
+/// `display_scale`.
+pub fn font_definitions(
+    screen_size: ScreenSize,
+    scramble_scale: f32,
+    display_scale: f32,
+) -> FontDefinitions {
     let mut fonts = FontDefinitions {
         font_data: BTreeMap::new(),
         fonts_for_family: BTreeMap::new(),
@@ -102,24 +112,27 @@ pub fn font_definitions(screen_size: ScreenSize) -> FontDefinitions {
         FontSize::Scramble.into(),
         (
             FontFamily::Monospace,
-            match screen_size {
-                ScreenSize::Small => 32.0,
-                ScreenSize::Normal => 40.0,
-                ScreenSize::Large => 48.0,
-                ScreenSize::VeryLarge => 64.0,
-            },
+            scramble_scale
+                * display_scale
+                * match screen_size {
+                    ScreenSize::Small => 32.0,
+                    ScreenSize::Normal => 40.0,
+                    ScreenSize::Large => 48.0,
+                    ScreenSize::VeryLarge => 64.0,
+                },
         ),
     );
     fonts.family_and_size.insert(
         FontSize::Timer.into(),
         (
             FontFamily::Monospace,
-            match screen_size {
-                ScreenSize::Small => 80.0,
-                ScreenSize::Normal => 128.0,
-                ScreenSize::Large => 144.0,
-                ScreenSize::VeryLarge => 192.0,
-            },
+            display_scale
+                * match screen_size {
+                    ScreenSize::Small => 80.0,
+                    ScreenSize::Normal => 128.0,
+                    ScreenSize::Large => 144.0,
+                    ScreenSize::VeryLarge => 192.0,
+                },
         ),
     );
 
@@ -1,44 +1,174 @@
 mod data;
 mod plot;
 
+use crate::app::SolveDetails;
 use crate::font::FontSize;
 use crate::style::{content_visuals, side_visuals};
 use crate::theme::Theme;
 use crate::widgets::CustomWidgets;
 use anyhow::Result;
-use data::{CFOPPhase, GraphData, Phase, Statistic};
+use chrono::{Date, Local};
+use data::{CFOPPhase, DateRange, GraphData, GraphKind, Phase, Statistic};
 use egui::{
     Align, CentralPanel, CtxRef, Direction, Label, Layout, Pos2, Rect, ScrollArea, Sense,
     SidePanel, Stroke, TopBottomPanel, Ui, Vec2,
 };
-use plot::Plot;
-use tpscube_core::{History, SolveType};
+use plot::{Plot, PlotAction};
+use tpscube_core::{History, SessionGoal, SolveInput, SolveType};
 
 const GRAPH_PADDING: f32 = 16.0;
 
+/// Histogram bucket sizes available, in thousandths of the statistic's unit
+const BUCKET_SIZES: [u32; 5] = [250, 500, 1000, 2000, 5000];
+
 pub struct GraphWidget {
+    kind: GraphKind,
+    range: DateRange,
+    bucket_size: u32,
     statistic: Statistic,
     phase: Phase,
     average_size: usize,
     plot: Option<Plot>,
     update_id: Option<u64>,
     solve_type: SolveType,
+    input: Option<SolveInput>,
+    inputs: Vec<SolveInput>,
     settings_restored: bool,
 }
 
 impl GraphWidget {
     pub fn new() -> Self {
         Self {
+            kind: GraphKind::TimeSeries,
+            range: DateRange::AllTime,
+            bucket_size: 1000,
             statistic: Statistic::TotalTime,
             phase: Phase::EntireSolve,
             average_size: 5,
             plot: None,
             update_id: None,
             solve_type: SolveType::Standard3x3x3,
+            input: None,
+            inputs: Vec::new(),
             settings_restored: false,
         }
     }
 
+    fn kind_options(
+        &mut self,
+        ui: &mut Ui,
+        history: &mut History,
+        solve_type: SolveType,
+        compact: bool,
+    ) {
+        for (kind, name, compact_name) in [
+            (GraphKind::TimeSeries, "Time series", "Times"),
+            (GraphKind::Histogram, "Histogram", "Histogram"),
+            (GraphKind::Consistency, "Consistency", "Consistency"),
+            (GraphKind::Phases, "Phase breakdown", "Phases"),
+            (GraphKind::Calendar, "Calendar", "Calendar"),
+        ]
+        .iter()
+        {
+            if *kind == GraphKind::Phases && !solve_type.is_3x3x3() {
+                // Phase analysis is only available for 3x3x3 solves
+                if self.kind == GraphKind::Phases {
+                    self.kind = GraphKind::TimeSeries;
+                    self.plot = None;
+                }
+                continue;
+            }
+
+            if ui
+                .mode_label(
+                    if compact { compact_name } else { name },
+                    self.kind == *kind,
+                )
+                .clicked()
+            {
+                self.kind = *kind;
+                self.plot = None;
+                let _ = self.save_settings(history);
+            }
+        }
+    }
+
+    fn range_options(&mut self, ui: &mut Ui, history: &mut History, compact: bool) {
+        for (range, name, compact_name) in [
+            (DateRange::AllTime, "All time", "All"),
+            (DateRange::CurrentSession, "Current session", "Session"),
+            (DateRange::LastWeek, "Last 7 days", "7d"),
+            (DateRange::LastMonth, "Last 30 days", "30d"),
+            (DateRange::LastYear, "Last year", "1y"),
+        ]
+        .iter()
+        {
+            if ui
+                .mode_label(
+                    if compact { compact_name } else { name },
+                    self.range == *range,
+                )
+                .clicked()
+            {
+                self.range = *range;
+                self.plot = None;
+                let _ = self.save_settings(history);
+            }
+        }
+    }
+
+    fn input_options(&mut self, ui: &mut Ui) {
+        if ui.mode_label("All inputs", self.input.is_none()).clicked() {
+            self.input = None;
+            self.plot = None;
+        }
+        for input in &self.inputs {
+            let selected = match &self.input {
+                Some(selected) => selected.same_source(input),
+                None => false,
+            };
+            if ui.mode_label(input.name(), selected).clicked() {
+                self.input = Some(input.clone());
+                self.plot = None;
+            }
+        }
+    }
+
+    fn bucket_options(&mut self, ui: &mut Ui, history: &mut History) {
+        for size in BUCKET_SIZES.iter() {
+            let name = if size % 1000 == 0 {
+                format!("{}", size / 1000)
+            } else {
+                format!("{}.{:02}", size / 1000, (size % 1000) / 10)
+            };
+            if ui.mode_label(&name, self.bucket_size == *size).clicked() {
+                self.bucket_size = *size;
+                self.plot = None;
+                let _ = self.save_settings(history);
+            }
+        }
+    }
+
+    /// Shows either the average size or the histogram bucket size, depending
+    /// on the type of graph
+    fn size_options(&mut self, ui: &mut Ui, history: &mut History, compact: bool) {
+        match self.kind {
+            GraphKind::Histogram => {
+                ui.section(if compact { "Bucket" } else { "Bucket size" });
+                self.bucket_options(ui, history);
+            }
+            GraphKind::TimeSeries | GraphKind::Phases => {
+                ui.section(if compact { "Avg" } else { "Average" });
+                self.average_options(ui, history, compact);
+            }
+            GraphKind::Consistency => {
+                ui.section("Window");
+                self.average_options(ui, history, compact);
+            }
+            GraphKind::Calendar => (),
+        }
+    }
+
     fn statistic_options(&mut self, ui: &mut Ui, history: &mut History) {
         if ui
             .mode_label("Total time", self.statistic == Statistic::TotalTime)
@@ -226,21 +356,46 @@ impl GraphWidget {
                     .id_source("left_graph_options_scroll")
                     .show(ui, |ui| {
                         ui.vertical(|ui| {
-                            if solve_type.is_3x3x3() {
-                                ui.section("Statistic");
-                                self.statistic_options(ui, history);
+                            ui.section("Graph");
+                            self.kind_options(ui, history, solve_type, false);
 
+                            if self.kind != GraphKind::Calendar {
+                                // Calendar always shows the past year
                                 ui.add_space(8.0);
-                                ui.section("Phase");
-                                self.phase_options(ui, history);
+                                ui.section("Range");
+                                self.range_options(ui, history, false);
+                            }
+
+                            if solve_type.is_3x3x3() {
+                                // Phase breakdown always shows total time of every phase, and
+                                // calendar only shows solve counts
+                                if self.kind != GraphKind::Phases
+                                    && self.kind != GraphKind::Calendar
+                                {
+                                    ui.add_space(8.0);
+                                    ui.section("Statistic");
+                                    self.statistic_options(ui, history);
+
+                                    ui.add_space(8.0);
+                                    ui.section("Phase");
+                                    self.phase_options(ui, history);
+                                }
                             } else {
                                 self.statistic = Statistic::TotalTime;
                                 self.phase = Phase::EntireSolve;
                             }
 
-                            ui.add_space(8.0);
-                            ui.section("Average");
-                            self.average_options(ui, history, false);
+                            if self.kind != GraphKind::Calendar {
+                                ui.add_space(8.0);
+                                self.size_options(ui, history, false);
+                            }
+
+                            // Only offer an input filter when there is a choice
+                            if self.inputs.len() > 1 {
+                                ui.add_space(8.0);
+                                ui.section("Input");
+                                self.input_options(ui);
+                            }
                         });
                     });
             });
@@ -249,10 +404,21 @@ impl GraphWidget {
     fn portrait_top_bar(&mut self, ctxt: &CtxRef, history: &mut History, solve_type: SolveType) {
         TopBottomPanel::top("top_graph_options").show(ctxt, |ui| {
             ui.vertical(|ui| {
+                ui.horizontal_wrapped(|ui| {
+                    self.kind_options(ui, history, solve_type, true);
+                    if self.kind != GraphKind::Calendar {
+                        ui.add_space(8.0);
+                        self.range_options(ui, history, true);
+                    }
+                });
+                ui.add_space(4.0);
                 ui.with_layout(
                     Layout::from_main_dir_and_cross_align(Direction::LeftToRight, Align::TOP),
                     |ui| {
-                        if solve_type.is_3x3x3() {
+                        if solve_type.is_3x3x3()
+                            && self.kind != GraphKind::Phases
+                            && self.kind != GraphKind::Calendar
+                        {
                             ui.allocate_ui(
                                 Vec2::new(
                                     (ui.max_rect().width() - 48.0) / 2.0,
@@ -304,8 +470,7 @@ impl GraphWidget {
                             Vec2::new((ui.max_rect().width() - 48.0) / 6.0, ui.max_rect().height()),
                             |ui| {
                                 ui.vertical(|ui| {
-                                    ui.section("Avg");
-                                    self.average_options(ui, history, true);
+                                    self.size_options(ui, history, true);
                                     ui.add_space(4.0);
                                 });
                             },
@@ -318,6 +483,29 @@ impl GraphWidget {
 
     fn restore_settings(&mut self, history: &History) {
         self.average_size = history.setting_as_i64("graph_average").unwrap_or(5) as usize;
+        self.bucket_size = history.setting_as_i64("graph_bucket").unwrap_or(1000) as u32;
+        self.kind = match history
+            .setting_as_string("graph_kind")
+            .as_ref()
+            .map(|s| s.as_str())
+        {
+            Some("histogram") => GraphKind::Histogram,
+            Some("consistency") => GraphKind::Consistency,
+            Some("phases") => GraphKind::Phases,
+            Some("calendar") => GraphKind::Calendar,
+            Some(_) | None => GraphKind::TimeSeries,
+        };
+        self.range = match history
+            .setting_as_string("graph_range")
+            .as_ref()
+            .map(|s| s.as_str())
+        {
+            Some("session") => DateRange::CurrentSession,
+            Some("week") => DateRange::LastWeek,
+            Some("month") => DateRange::LastMonth,
+            Some("year") => DateRange::LastYear,
+            Some(_) | None => DateRange::AllTime,
+        };
         self.statistic = match history
             .setting_as_string("graph_stat")
             .as_ref()
@@ -348,6 +536,27 @@ impl GraphWidget {
 
     fn save_settings(&self, history: &mut History) -> Result<()> {
         history.set_i64_setting("graph_average", self.average_size as i64)?;
+        history.set_i64_setting("graph_bucket", self.bucket_size as i64)?;
+        history.set_string_setting(
+            "graph_kind",
+            match self.kind {
+                GraphKind::TimeSeries => "time",
+                GraphKind::Histogram => "histogram",
+                GraphKind::Consistency => "consistency",
+                GraphKind::Phases => "phases",
+                GraphKind::Calendar => "calendar",
+            },
+        )?;
+        history.set_string_setting(
+            "graph_range",
+            match self.range {
+                DateRange::AllTime => "all",
+                DateRange::CurrentSession => "session",
+                DateRange::LastWeek => "week",
+                DateRange::LastMonth => "month",
+                DateRange::LastYear => "year",
+            },
+        )?;
         history.set_string_setting(
             "graph_stat",
             match self.statistic {
@@ -372,7 +581,24 @@ impl GraphWidget {
         Ok(())
     }
 
+    /// Uses the average goal of the current session as the forecast target,
+    /// if there is one
+    fn forecast_target(history: &History) -> Option<u32> {
+        history
+            .session_goals(history.current_session())
+            .iter()
+            .find_map(|goal| match goal {
+                SessionGoal::Average12(time) => Some(*time),
+                _ => None,
+            })
+    }
+
     fn requires_analysis(&self) -> bool {
+        match self.kind {
+            GraphKind::Phases => return true,
+            GraphKind::Calendar => return false,
+            _ => (),
+        }
         match self.phase {
             Phase::CFOP(_) => true,
             _ => match self.statistic {
@@ -387,6 +613,8 @@ impl GraphWidget {
         ctxt: &CtxRef,
         _frame: &mut epi::Frame<'_>,
         history: &mut History,
+        details: &mut Option<SolveDetails>,
+        show_day: &mut Option<Date<Local>>,
         solve_type: SolveType,
     ) {
         if !self.settings_restored {
@@ -409,6 +637,15 @@ impl GraphWidget {
                 // If history has been updated, regenerate plot
                 self.plot = None;
                 self.solve_type = solve_type;
+
+                // Keep the input filter only if the input is used for this solve type
+                self.inputs = history.solve_inputs(solve_type);
+                let inputs = &self.inputs;
+                if let Some(input) = &self.input {
+                    if !inputs.iter().any(|other| other.same_source(input)) {
+                        self.input = None;
+                    }
+                }
             }
 
             // Get plot data
@@ -418,9 +655,14 @@ impl GraphWidget {
                 // No plot data cached, regenerate now
                 self.plot = Some(
                     GraphData::new()
+                        .kind(self.kind)
+                        .range(self.range)
+                        .bucket_size(self.bucket_size)
                         .statistic(self.statistic)
                         .phase(self.phase)
                         .average_size(self.average_size)
+                        .input(self.input.clone())
+                        .forecast_target(Self::forecast_target(history))
                         .build(history, solve_type),
                 );
                 self.update_id = Some(history.update_id());
@@ -436,13 +678,28 @@ impl GraphWidget {
                     .fonts()
                     .layout_single_line(FontSize::Section.into(), plot.title().to_string());
                 let title_width = title_galley.size.x;
-                let title_height = title_galley.size.y;
+                let mut title_height = title_galley.size.y;
                 painter.galley(
                     Pos2::new(rect.center().x - title_width / 2.0, rect.top()),
                     title_galley,
                     Theme::Blue.into(),
                 );
 
+                // Draw notes, such as the improvement forecast, below the title
+                for note in plot.notes() {
+                    let galley = ui
+                        .fonts()
+                        .layout_single_line(FontSize::Small.into(), note.clone());
+                    let width = galley.size.x;
+                    let height = galley.size.y;
+                    painter.galley(
+                        Pos2::new(rect.center().x - width / 2.0, rect.top() + title_height),
+                        galley,
+                        Theme::Disabled.into(),
+                    );
+                    title_height += height;
+                }
+
                 // Draw plot
                 let rect = Rect::from_min_size(
                     Pos2::new(
@@ -456,7 +713,16 @@ impl GraphWidget {
                 );
 
                 let interact = ui.allocate_rect(rect, Sense::click_and_drag());
-                plot.update(ctxt, ui, rect, interact);
+                match plot.update(ctxt, ui, rect, interact) {
+                    Some(PlotAction::OpenSolve(solve_id)) => {
+                        // Clicking on a point opens the details for that solve
+                        if let Some(solve) = history.solve(&solve_id) {
+                            *details = Some(SolveDetails::IndividualSolve(solve.clone()));
+                        }
+                    }
+                    Some(PlotAction::ShowDay(date)) => *show_day = Some(date),
+                    None => (),
+                }
             } else {
                 ui.centered_and_justified(|ui| {
                     ui.add(
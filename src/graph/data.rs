@@ -1,14 +1,50 @@
-use crate::graph::plot::{Plot, SinglePlot, YAxis};
+use crate::graph::plot::{CalendarDay, CalendarPlot, HistogramPlot, Plot, SinglePlot, YAxis};
 use crate::theme::Theme;
+use crate::widgets::{short_day_string, solve_time_string};
+use chrono::{Date, Duration, Local};
+use egui::Color32;
+use std::collections::BTreeMap;
 use tpscube_core::{
-    Analysis, Cube, Cube3x3x3, CubeWithSolution, History, InitialCubeState, ListAverage, Solve,
-    SolveType,
+    histogram, rolling_averages, rolling_standard_deviations, standard_deviation, Analysis, Cube,
+    Cube3x3x3, CubeWithSolution, History, ImprovementEstimate, InitialCubeState, ListAverage,
+    Solve, SolveInput, SolveType,
 };
 
+/// Sizes of the moving averages drawn on top of the main series
+const OVERLAY_AVERAGE_SIZES: [usize; 2] = [12, 100];
+
+/// Values further than this many standard deviations above the mean are left
+/// out of histograms so that a single very slow solve does not flatten the
+/// rest of the chart.
+const HISTOGRAM_OUTLIER_DEVIATIONS: f64 = 3.0;
+
 pub struct GraphData {
+    kind: GraphKind,
+    range: DateRange,
     statistic: Statistic,
     phase: Phase,
     average_size: usize,
+    bucket_size: u32,
+    input: Option<SolveInput>,
+    forecast_target: Option<u32>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GraphKind {
+    TimeSeries,
+    Histogram,
+    Consistency,
+    Phases,
+    Calendar,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DateRange {
+    AllTime,
+    CurrentSession,
+    LastWeek,
+    LastMonth,
+    LastYear,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -47,15 +83,43 @@ impl Statistic {
     }
 }
 
+impl DateRange {
+    fn includes(&self, solve: &Solve, history: &History) -> bool {
+        let days = match self {
+            DateRange::AllTime => return true,
+            DateRange::CurrentSession => return solve.session == history.current_session(),
+            DateRange::LastWeek => 7,
+            DateRange::LastMonth => 30,
+            DateRange::LastYear => 365,
+        };
+        solve.created >= Local::now() - Duration::days(days)
+    }
+}
+
 impl GraphData {
     pub fn new() -> Self {
         Self {
+            kind: GraphKind::TimeSeries,
+            range: DateRange::AllTime,
             statistic: Statistic::TotalTime,
             phase: Phase::EntireSolve,
             average_size: 5,
+            bucket_size: 1000,
+            input: None,
+            forecast_target: None,
         }
     }
 
+    pub fn kind(mut self, kind: GraphKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn range(mut self, range: DateRange) -> Self {
+        self.range = range;
+        self
+    }
+
     pub fn statistic(mut self, statistic: Statistic) -> Self {
         self.statistic = statistic;
         self
@@ -71,14 +135,46 @@ impl GraphData {
         self
     }
 
+    /// Width of each histogram bucket, in thousandths of the statistic's unit
+    pub fn bucket_size(mut self, size: u32) -> Self {
+        self.bucket_size = size;
+        self
+    }
+
+    /// Only include solves recorded with the given input
+    pub fn input(mut self, input: Option<SolveInput>) -> Self {
+        self.input = input;
+        self
+    }
+
+    fn includes_input(&self, solve: &Solve) -> bool {
+        match &self.input {
+            Some(input) => match solve.input() {
+                Some(solve_input) => solve_input.same_source(input),
+                None => false,
+            },
+            None => true,
+        }
+    }
+
+    /// Target average for the improvement forecast, in milliseconds. If not
+    /// set, a target somewhat faster than the current average is chosen.
+    pub fn forecast_target(mut self, target: Option<u32>) -> Self {
+        self.forecast_target = target;
+        self
+    }
+
     fn analyze(solve: &Solve) -> Option<Analysis> {
         if let Some(solution) = &solve.moves {
             let mut initial_state = Cube3x3x3::new();
             initial_state.do_moves(&solve.scramble);
-            Some(Analysis::analyze(&CubeWithSolution {
-                initial_state,
-                solution: solution.clone(),
-            }))
+            Some(Analysis::analyze_with_solve_type(
+                &CubeWithSolution {
+                    initial_state,
+                    solution: solution.clone(),
+                },
+                solve.solve_type,
+            ))
         } else {
             None
         }
@@ -283,8 +379,21 @@ impl GraphData {
     }
 
     pub fn build(self, history: &History, solve_type: SolveType) -> Plot {
+        if self.kind == GraphKind::Calendar {
+            let mut plot = CalendarPlot::new("Daily Practice".into(), Theme::Green.into());
+            self.calendar(&mut plot, history, solve_type);
+            return plot.into();
+        }
+
         let title = format!(
-            "{} for {}",
+            "{}{} for {}",
+            match self.kind {
+                GraphKind::TimeSeries => "",
+                GraphKind::Histogram => "Distribution of ",
+                GraphKind::Consistency => "Consistency of ",
+                GraphKind::Phases => "Phase Breakdown of ",
+                GraphKind::Calendar => unreachable!(),
+            },
             match self.statistic {
                 Statistic::TotalTime => "Time",
                 Statistic::RecognitionTime => "Recognition Time",
@@ -302,41 +411,278 @@ impl GraphData {
             }
         );
 
-        let mut plot = SinglePlot::new(
-            title,
-            self.statistic.y_axis(),
-            match self.phase {
-                Phase::CFOP(CFOPPhase::Cross) => Theme::Red.into(),
-                Phase::CFOP(CFOPPhase::F2L) => Theme::Blue.into(),
-                Phase::CFOP(CFOPPhase::OLL) => Theme::Yellow.into(),
-                Phase::CFOP(CFOPPhase::PLL) => Theme::Green.into(),
-                _ => Theme::Blue.into(),
-            },
-        );
+        let color = match self.phase {
+            Phase::CFOP(CFOPPhase::Cross) => Theme::Red.into(),
+            Phase::CFOP(CFOPPhase::F2L) => Theme::Blue.into(),
+            Phase::CFOP(CFOPPhase::OLL) => Theme::Yellow.into(),
+            Phase::CFOP(CFOPPhase::PLL) => Theme::Green.into(),
+            _ => Theme::Blue.into(),
+        };
 
-        let mut window = Vec::new();
+        if self.kind == GraphKind::Phases {
+            let mut plot = SinglePlot::new(title, YAxis::Time, Theme::Content.into());
+            self.phases(&mut plot, history, solve_type);
+            return plot.into();
+        }
+
+        // Collect data points for each solve of the current solve type. The entire
+        // history is also kept for estimating improvement, along with the index of
+        // each plotted solve within it.
+        let mut solves = Vec::new();
+        let mut values = Vec::new();
+        let mut history_times = Vec::new();
+        let mut history_values = Vec::new();
+        let mut history_indexes = Vec::new();
         for solve in history.iter() {
-            if solve.solve_type != solve_type {
-                // Only include solves with the current solve type
+            if solve.solve_type != solve_type || !self.includes_input(solve) {
+                // Only include solves with the current solve type and input
+                continue;
+            }
+            history_times.push(solve.created);
+            history_values.push(solve.final_time());
+            if !self.range.includes(solve, history) {
                 continue;
             }
 
-            let data_point = match Self::data_point(solve, self.statistic, self.phase) {
-                Some(value) => Some(value),
-                None => continue,
-            };
+            if let Some(value) = Self::data_point(solve, self.statistic, self.phase) {
+                solves.push(solve);
+                values.push(Some(value));
+                history_indexes.push(history_values.len());
+            }
+        }
 
-            window.push(data_point);
-            if window.len() > self.average_size {
-                window.remove(0);
+        match self.kind {
+            GraphKind::TimeSeries => {
+                let mut plot = SinglePlot::new(title, self.statistic.y_axis(), color);
+                let estimate =
+                    if self.statistic == Statistic::TotalTime && self.phase == Phase::EntireSolve {
+                        ImprovementEstimate::fit(&history_times, &history_values)
+                    } else {
+                        None
+                    };
+                if let Some(estimate) = &estimate {
+                    self.forecast_notes(&mut plot, estimate);
+                }
+                self.time_series(
+                    &mut plot,
+                    &solves,
+                    &values,
+                    estimate
+                        .as_ref()
+                        .map(|estimate| (estimate, history_indexes.as_slice())),
+                );
+                plot.into()
             }
-            if window.len() >= self.average_size {
-                if let Some(average) = window.as_slice().average() {
-                    plot.push(solve.created, average as f32 / 1000.0);
+            GraphKind::Histogram => {
+                let mut plot = HistogramPlot::new(title, self.statistic.y_axis(), color);
+                self.histogram(&mut plot, &values);
+                plot.into()
+            }
+            GraphKind::Consistency => {
+                let mut plot = SinglePlot::new(title, self.statistic.y_axis(), color);
+                self.consistency(&mut plot, &solves, &values);
+                plot.into()
+            }
+            GraphKind::Phases | GraphKind::Calendar => unreachable!(),
+        }
+    }
+
+    /// Draws the main series along with the longer term averages. If an
+    /// improvement estimate is given, the fitted trend is drawn as well, using
+    /// the index of each solve within the entire history of the solve type.
+    fn time_series(
+        &self,
+        plot: &mut SinglePlot,
+        solves: &[&Solve],
+        values: &[Option<u32>],
+        estimate: Option<(&ImprovementEstimate, &[usize])>,
+    ) {
+        // Compute the main series and the longer term average overlays
+        let averages = rolling_averages(values, self.average_size);
+        let overlays: Vec<(usize, Vec<Option<u32>>)> = OVERLAY_AVERAGE_SIZES
+            .iter()
+            .filter(|size| **size != self.average_size)
+            .map(|size| (*size, rolling_averages(values, *size)))
+            .collect();
+        for (size, _) in &overlays {
+            plot.add_overlay(format!("ao{}", size), overlay_color(*size));
+        }
+        if estimate.is_some() {
+            plot.add_overlay("Trend".into(), Theme::Green.into());
+        }
+
+        for (idx, solve) in solves.iter().enumerate() {
+            if let Some(average) = averages[idx] {
+                let mut overlay_values: Vec<Option<f32>> = overlays
+                    .iter()
+                    .map(|(_, values)| values[idx].map(|value| value as f32 / 1000.0))
+                    .collect();
+                if let Some((estimate, indexes)) = estimate {
+                    overlay_values.push(Some(
+                        estimate.predicted_average(indexes[idx]) as f32 / 1000.0,
+                    ));
                 }
+                plot.push(
+                    solve.created,
+                    average as f32 / 1000.0,
+                    solve.id.clone(),
+                    overlay_values,
+                );
+            }
+        }
+    }
+
+    /// Adds notes describing the rate of improvement and when the target
+    /// average is expected to be reached
+    fn forecast_notes(&self, plot: &mut SinglePlot, estimate: &ImprovementEstimate) {
+        if !estimate.improving() || estimate.monthly_improvement() <= 0 {
+            plot.add_note("No improvement trend in recent solves".into());
+            return;
+        }
+
+        plot.add_note(format!(
+            "Improving about {} per 100 solves, {} per month at current practice rate",
+            solve_time_string(estimate.improvement_over_solves(100) as u32),
+            solve_time_string(estimate.monthly_improvement() as u32)
+        ));
+
+        let current = estimate.current_average();
+        let target = match self.forecast_target {
+            Some(target) if target < current => target,
+            _ => default_forecast_target(current),
+        };
+        let target_name = if target % 1000 == 0 {
+            format!("Sub-{} ao12", target / 1000)
+        } else {
+            format!("{} ao12", solve_time_string(target))
+        };
+        match (estimate.forecast(target), estimate.solves_to_reach(target)) {
+            (Some(date), Some(solves)) => plot.add_note(format!(
+                "{} projected by {} (about {} more solves)",
+                target_name,
+                short_day_string(&date),
+                solves
+            )),
+            _ => plot.add_note(format!("{} is not projected to be reached", target_name)),
+        }
+    }
+
+    fn histogram(&self, plot: &mut HistogramPlot, values: &[Option<u32>]) {
+        let mut values = values.to_vec();
+        if let Some(deviation) = standard_deviation(&values) {
+            let valid: Vec<f64> = values.iter().filter_map(|v| v.map(|v| v as f64)).collect();
+            let mean = valid.iter().sum::<f64>() / valid.len() as f64;
+            let limit = mean + deviation as f64 * HISTOGRAM_OUTLIER_DEVIATIONS;
+            values.retain(|value| value.map(|value| value as f64 <= limit).unwrap_or(false));
+        }
+
+        plot.set_bucket_size(self.bucket_size as f32 / 1000.0);
+        for (start, count) in histogram(&values, self.bucket_size) {
+            plot.push(start as f32 / 1000.0, count);
+        }
+    }
+
+    fn phases(&self, plot: &mut SinglePlot, history: &History, solve_type: SolveType) {
+        let phases: [(CFOPPhase, &str, Color32); 4] = [
+            (CFOPPhase::Cross, "Cross", Theme::Red.into()),
+            (CFOPPhase::F2L, "F2L", Theme::Blue.into()),
+            (CFOPPhase::OLL, "OLL", Theme::Yellow.into()),
+            (CFOPPhase::PLL, "PLL", Theme::Green.into()),
+        ];
+        for (_, name, color) in phases.iter() {
+            plot.add_layer(name.to_string(), *color);
+        }
+
+        // Collect the time of each phase for solves with analysis data
+        let mut solves = Vec::new();
+        let mut values: Vec<Vec<Option<u32>>> = vec![Vec::new(); phases.len()];
+        for solve in history.iter() {
+            if solve.solve_type != solve_type
+                || !self.range.includes(solve, history)
+                || !self.includes_input(solve)
+            {
+                continue;
+            }
+            if Self::data_point(solve, Statistic::TotalTime, Phase::CFOP(CFOPPhase::Cross))
+                .is_none()
+            {
+                // No analysis available for this solve
+                continue;
+            }
+
+            solves.push(solve);
+            for (idx, (phase, _, _)) in phases.iter().enumerate() {
+                // Skipped last layer cases have no time for that phase
+                values[idx].push(Some(
+                    Self::data_point(solve, Statistic::TotalTime, Phase::CFOP(*phase)).unwrap_or(0),
+                ));
+            }
+        }
+
+        // Smooth each phase with the selected average so that trends are visible
+        let averages: Vec<Vec<Option<u32>>> = values
+            .iter()
+            .map(|values| rolling_averages(values, self.average_size))
+            .collect();
+        for (idx, solve) in solves.iter().enumerate() {
+            let layers: Option<Vec<f32>> = averages
+                .iter()
+                .map(|averages| averages[idx].map(|value| value as f32 / 1000.0))
+                .collect();
+            if let Some(layers) = layers {
+                plot.push_stacked(solve.created, solve.id.clone(), layers);
+            }
+        }
+    }
+
+    fn calendar(&self, plot: &mut CalendarPlot, history: &History, solve_type: SolveType) {
+        // Group solves by the day they were performed
+        let mut days: BTreeMap<Date<Local>, Vec<Solve>> = BTreeMap::new();
+        for solve in history.iter() {
+            if solve.solve_type == solve_type && self.includes_input(solve) {
+                days.entry(solve.created.date())
+                    .or_insert_with(Vec::new)
+                    .push(solve.clone());
+            }
+        }
+
+        for (date, solves) in days {
+            plot.set_day(
+                date,
+                CalendarDay {
+                    count: solves.len(),
+                    average: solves.as_slice().average(),
+                },
+            );
+        }
+    }
+
+    fn consistency(&self, plot: &mut SinglePlot, solves: &[&Solve], values: &[Option<u32>]) {
+        // Standard deviation needs at least a few values to be meaningful
+        let size = self.average_size.max(5);
+        let deviations = rolling_standard_deviations(values, size);
+        for (idx, solve) in solves.iter().enumerate() {
+            if let Some(deviation) = deviations[idx] {
+                plot.push(
+                    solve.created,
+                    deviation as f32 / 1000.0,
+                    solve.id.clone(),
+                    Vec::new(),
+                );
             }
         }
+    }
+}
+
+/// Chooses a forecast target about 10% faster than the current average,
+/// rounded down to a whole second
+fn default_forecast_target(current: u32) -> u32 {
+    (((current as u64 * 9 / 10) / 1000) as u32 * 1000).max(1000)
+}
 
-        plot.into()
+fn overlay_color(size: usize) -> Color32 {
+    match size {
+        12 => Theme::Orange.into(),
+        _ => Theme::Magenta.into(),
     }
 }
@@ -1,11 +1,12 @@
 use crate::font::FontSize;
 use crate::theme::Theme;
-use crate::widgets::short_day_string;
-use chrono::{DateTime, Local};
+use crate::widgets::{short_day_string, solve_time_string};
+use chrono::{Date, DateTime, Datelike, Duration, Local};
 use egui::{
     epaint::{Mesh, TextureId, Vertex, WHITE_UV},
     Color32, CtxRef, Pos2, Rect, Response, Shape, Stroke, Ui, Vec2,
 };
+use std::collections::BTreeMap;
 
 const AXIS_PADDING: f32 = 16.0;
 const AXIS_LABEL_PADDING_FACTOR: f32 = 2.0;
@@ -17,6 +18,9 @@ const TARGET_MIN_POINTS: usize = 64;
 const EXP_ZOOM_DIVISOR: f32 = 256.0;
 const EPSILON: f32 = 0.0001;
 
+const CALENDAR_WEEKS: usize = 53;
+const MAX_CALENDAR_CELL_SIZE: f32 = 24.0;
+
 #[cfg(target_arch = "wasm32")]
 const MIN_ALPHA: u8 = 128;
 #[cfg(target_arch = "wasm32")]
@@ -29,16 +33,63 @@ const MAX_ALPHA: u8 = 128;
 
 pub enum Plot {
     Single(SinglePlot),
+    Histogram(HistogramPlot),
+    Calendar(CalendarPlot),
+}
+
+/// Action requested by clicking on a plot
+pub enum PlotAction {
+    OpenSolve(String),
+    ShowDay(Date<Local>),
 }
 
 pub struct SinglePlot {
     title: String,
     y_axis: YAxis,
-    points: Vec<(DateTime<Local>, f32)>,
+    points: Vec<PlotPoint>,
     color: Color32,
+    overlays: Vec<PlotOverlay>,
+    layers: Vec<PlotOverlay>,
+    notes: Vec<String>,
     zoom: PlotZoom,
 }
 
+struct PlotPoint {
+    time: DateTime<Local>,
+    value: f32,
+    solve_id: String,
+    overlays: Vec<Option<f32>>,
+    layers: Vec<f32>,
+}
+
+/// Bar chart showing the number of values that fall in each bucket
+pub struct HistogramPlot {
+    title: String,
+    y_axis: YAxis,
+    bucket_size: f32,
+    buckets: Vec<(f32, usize)>,
+    color: Color32,
+}
+
+/// Calendar of the past year showing the number of solves on each day
+pub struct CalendarPlot {
+    title: String,
+    days: BTreeMap<Date<Local>, CalendarDay>,
+    color: Color32,
+}
+
+pub struct CalendarDay {
+    pub count: usize,
+    pub average: Option<u32>,
+}
+
+/// Additional line drawn over the main series, such as a longer term average,
+/// or a band of a stacked plot
+struct PlotOverlay {
+    label: String,
+    color: Color32,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum YAxis {
     Time,
@@ -55,18 +106,45 @@ impl Plot {
     pub fn title(&self) -> &str {
         match self {
             Plot::Single(plot) => plot.title(),
+            Plot::Histogram(plot) => plot.title(),
+            Plot::Calendar(plot) => plot.title(),
+        }
+    }
+
+    /// Additional lines of text to show below the title
+    pub fn notes(&self) -> &[String] {
+        match self {
+            Plot::Single(plot) => &plot.notes,
+            Plot::Histogram(_) | Plot::Calendar(_) => &[],
         }
     }
 
     pub fn valid(&self) -> bool {
         match self {
             Plot::Single(plot) => plot.valid(),
+            Plot::Histogram(plot) => plot.valid(),
+            Plot::Calendar(plot) => plot.valid(),
         }
     }
 
-    pub fn update(&mut self, ctxt: &CtxRef, ui: &mut Ui, rect: Rect, interact: Response) {
+    /// Draws the plot. Returns the action for the part of the plot that was
+    /// clicked, if any.
+    pub fn update(
+        &mut self,
+        ctxt: &CtxRef,
+        ui: &mut Ui,
+        rect: Rect,
+        interact: Response,
+    ) -> Option<PlotAction> {
         match self {
-            Plot::Single(plot) => plot.update(ctxt, ui, rect, interact),
+            Plot::Single(plot) => plot
+                .update(ctxt, ui, rect, interact)
+                .map(PlotAction::OpenSolve),
+            Plot::Histogram(plot) => {
+                plot.update(ui, rect, interact);
+                None
+            }
+            Plot::Calendar(plot) => plot.update(ui, rect, interact).map(PlotAction::ShowDay),
         }
     }
 }
@@ -78,6 +156,9 @@ impl SinglePlot {
             y_axis,
             points: Vec::new(),
             color,
+            overlays: Vec::new(),
+            layers: Vec::new(),
+            notes: Vec::new(),
             zoom: PlotZoom {
                 zoom: 1.0,
                 start: 0.0,
@@ -85,9 +166,48 @@ impl SinglePlot {
         }
     }
 
-    /// Assumes the points are added in chronological order
-    pub fn push(&mut self, time: DateTime<Local>, value: f32) {
-        self.points.push((time, value));
+    pub fn add_overlay(&mut self, label: String, color: Color32) {
+        self.overlays.push(PlotOverlay { label, color });
+    }
+
+    pub fn add_note(&mut self, note: String) {
+        self.notes.push(note);
+    }
+
+    /// Assumes the points are added in chronological order. There must be one
+    /// overlay value for each overlay added with `add_overlay`.
+    pub fn push(
+        &mut self,
+        time: DateTime<Local>,
+        value: f32,
+        solve_id: String,
+        overlays: Vec<Option<f32>>,
+    ) {
+        self.points.push(PlotPoint {
+            time,
+            value,
+            solve_id,
+            overlays,
+            layers: Vec::new(),
+        });
+    }
+
+    /// Adds a band to a stacked plot. Layers are stacked from the bottom of
+    /// the plot in the order they are added.
+    pub fn add_layer(&mut self, label: String, color: Color32) {
+        self.layers.push(PlotOverlay { label, color });
+    }
+
+    /// Adds a point to a stacked plot, with one value for each layer added
+    /// with `add_layer`. The value of the point is the total of the layers.
+    pub fn push_stacked(&mut self, time: DateTime<Local>, solve_id: String, layers: Vec<f32>) {
+        self.points.push(PlotPoint {
+            time,
+            value: layers.iter().sum(),
+            solve_id,
+            overlays: vec![None; self.overlays.len()],
+            layers,
+        });
     }
 
     pub fn title(&self) -> &str {
@@ -133,9 +253,52 @@ impl SinglePlot {
         ctxt.request_repaint();
     }
 
-    pub fn update(&mut self, ctxt: &CtxRef, ui: &mut Ui, rect: Rect, interact: Response) {
+    fn tooltip(&self, points: &[PlotPoint]) -> String {
+        let first = points.first().unwrap();
+        let last = points.last().unwrap();
+        let mut result = if points.len() > 1 {
+            format!(
+                "{} - {}",
+                first.time.format("%b %e, %Y %l:%M %P"),
+                last.time.format("%b %e, %Y %l:%M %P")
+            )
+        } else {
+            format!("{}", last.time.format("%b %e, %Y %l:%M %P"))
+        };
+        let value = points.iter().fold(0.0, |sum, point| sum + point.value) / points.len() as f32;
+        result += &format!("\n{}", self.y_axis.value_string(value));
+        for (idx, layer) in self.layers.iter().enumerate() {
+            let value = points
+                .iter()
+                .fold(0.0, |sum, point| sum + point.layers[idx])
+                / points.len() as f32;
+            result += &format!("\n{}: {}", layer.label, self.y_axis.value_string(value));
+        }
+        for (idx, overlay) in self.overlays.iter().enumerate() {
+            if let Some(value) = last.overlays[idx] {
+                result += &format!("\n{}: {}", overlay.label, self.y_axis.value_string(value));
+            }
+        }
+        result
+    }
+
+    pub fn update(
+        &mut self,
+        ctxt: &CtxRef,
+        ui: &mut Ui,
+        rect: Rect,
+        interact: Response,
+    ) -> Option<String> {
         let painter = ui.painter();
-        let max_value = self.points.iter().fold(0.0, |max, value| value.1.max(max));
+        let max_value = self.points.iter().fold(0.0, |max, point| {
+            point
+                .overlays
+                .iter()
+                .fold(point.value.max(max), |max, value| match value {
+                    Some(value) => value.max(max),
+                    None => max,
+                })
+        });
 
         let points_to_show = (self.points.len() as f32 * self.zoom.zoom) as usize;
 
@@ -152,7 +315,7 @@ impl SinglePlot {
             as usize
             * combined_points;
         if first_point >= self.points.len() {
-            return;
+            return None;
         }
 
         // Get number of points to plot based on zoom
@@ -247,7 +410,7 @@ impl SinglePlot {
             .enumerate()
         {
             let value = value.last().unwrap();
-            let day = short_day_string(&value.0);
+            let day = short_day_string(&value.time);
             if Some(day.clone()) != last_day {
                 let galley = ui
                     .fonts()
@@ -286,6 +449,8 @@ impl SinglePlot {
 
         // Compute locations of each plot point
         let mut points = Vec::new();
+        let mut overlay_points: Vec<Vec<Option<Pos2>>> = vec![Vec::new(); self.overlays.len()];
+        let mut layer_tops: Vec<Vec<f32>> = Vec::new();
         for (idx, value) in (&self.points[first_point..end_point])
             .chunks(combined_points)
             .enumerate()
@@ -296,16 +461,45 @@ impl SinglePlot {
                 continue;
             }
 
-            let sum = value.iter().fold(0.0, |sum, value| sum + value.1);
+            let x = plot_area.left()
+                + plot_area.width() * idx as f32 / (points_to_show / combined_points) as f32;
+            for (overlay_idx, overlay) in overlay_points.iter_mut().enumerate() {
+                // Overlays are moving averages already, so use the value at the end
+                // of each combined group of points.
+                overlay.push(
+                    value.last().unwrap().overlays[overlay_idx].map(|overlay_value| {
+                        Pos2::new(
+                            x,
+                            plot_area.top()
+                                + plot_area.height() * (1.0 - overlay_value / max_value),
+                        )
+                    }),
+                );
+            }
+
+            // Compute the top of each band of a stacked plot
+            let mut layer_total = 0.0;
+            layer_tops.push(
+                (0..self.layers.len())
+                    .map(|layer_idx| {
+                        layer_total += value
+                            .iter()
+                            .fold(0.0, |sum, value| sum + value.layers[layer_idx])
+                            / value.len() as f32;
+                        plot_area.top() + plot_area.height() * (1.0 - layer_total / max_value)
+                    })
+                    .collect(),
+            );
+
+            let sum = value.iter().fold(0.0, |sum, value| sum + value.value);
             let value = sum / value.len() as f32;
             points.push((
                 Pos2::new(
-                    plot_area.left()
-                        + plot_area.width() * idx as f32
-                            / (points_to_show / combined_points) as f32,
+                    x,
                     plot_area.top() + plot_area.height() * (1.0 - value / max_value),
                 ),
                 value / max_value,
+                first_point + idx * combined_points,
             ));
         }
 
@@ -376,47 +570,92 @@ impl SinglePlot {
             },
         );
 
-        // Create mesh for plot fill
-        let mut verts = Vec::new();
-        let mut idx = Vec::new();
-        for pt in &points {
-            let start = verts.len() as u32;
-            verts.push(Vertex {
-                pos: Pos2::new(pt.0.x, plot_area.bottom()),
-                uv: WHITE_UV,
-                color: Color32::from_rgba_unmultiplied(
-                    self.color.r(),
-                    self.color.g(),
-                    self.color.b(),
-                    MIN_ALPHA,
-                ),
-            });
-            verts.push(Vertex {
-                pos: Pos2::new(pt.0.x, pt.0.y),
-                uv: WHITE_UV,
-                color: Color32::from_rgba_unmultiplied(
-                    self.color.r(),
-                    self.color.g(),
-                    self.color.b(),
-                    MIN_ALPHA + ((MAX_ALPHA - MIN_ALPHA) as f32 * pt.1) as u8,
-                ),
-            });
-            if start >= 2 {
-                idx.push(start - 2);
-                idx.push(start - 1);
-                idx.push(start + 1);
-                idx.push(start - 2);
-                idx.push(start + 1);
-                idx.push(start);
+        if self.layers.len() != 0 {
+            // Stacked plot, fill each band with its own color
+            for (layer_idx, layer) in self.layers.iter().enumerate() {
+                let mut verts = Vec::new();
+                let mut idx = Vec::new();
+                for (pt, tops) in points.iter().zip(layer_tops.iter()) {
+                    let start = verts.len() as u32;
+                    let bottom = if layer_idx == 0 {
+                        plot_area.bottom()
+                    } else {
+                        tops[layer_idx - 1]
+                    };
+                    let color = Color32::from_rgba_unmultiplied(
+                        layer.color.r(),
+                        layer.color.g(),
+                        layer.color.b(),
+                        MAX_ALPHA,
+                    );
+                    verts.push(Vertex {
+                        pos: Pos2::new(pt.0.x, bottom),
+                        uv: WHITE_UV,
+                        color,
+                    });
+                    verts.push(Vertex {
+                        pos: Pos2::new(pt.0.x, tops[layer_idx]),
+                        uv: WHITE_UV,
+                        color,
+                    });
+                    if start >= 2 {
+                        idx.push(start - 2);
+                        idx.push(start - 1);
+                        idx.push(start + 1);
+                        idx.push(start - 2);
+                        idx.push(start + 1);
+                        idx.push(start);
+                    }
+                }
+                painter.add(Shape::mesh(Mesh {
+                    indices: idx,
+                    vertices: verts,
+                    texture_id: TextureId::Egui,
+                }));
+            }
+        } else {
+            // Create mesh for plot fill
+            let mut verts = Vec::new();
+            let mut idx = Vec::new();
+            for pt in &points {
+                let start = verts.len() as u32;
+                verts.push(Vertex {
+                    pos: Pos2::new(pt.0.x, plot_area.bottom()),
+                    uv: WHITE_UV,
+                    color: Color32::from_rgba_unmultiplied(
+                        self.color.r(),
+                        self.color.g(),
+                        self.color.b(),
+                        MIN_ALPHA,
+                    ),
+                });
+                verts.push(Vertex {
+                    pos: Pos2::new(pt.0.x, pt.0.y),
+                    uv: WHITE_UV,
+                    color: Color32::from_rgba_unmultiplied(
+                        self.color.r(),
+                        self.color.g(),
+                        self.color.b(),
+                        MIN_ALPHA + ((MAX_ALPHA - MIN_ALPHA) as f32 * pt.1) as u8,
+                    ),
+                });
+                if start >= 2 {
+                    idx.push(start - 2);
+                    idx.push(start - 1);
+                    idx.push(start + 1);
+                    idx.push(start - 2);
+                    idx.push(start + 1);
+                    idx.push(start);
+                }
             }
-        }
 
-        // Draw background fill
-        painter.add(Shape::mesh(Mesh {
-            indices: idx,
-            vertices: verts,
-            texture_id: TextureId::Egui,
-        }));
+            // Draw background fill
+            painter.add(Shape::mesh(Mesh {
+                indices: idx,
+                vertices: verts,
+                texture_id: TextureId::Egui,
+            }));
+        }
 
         // Draw line in graph
         for segment in points.as_slice().windows(2) {
@@ -429,6 +668,66 @@ impl SinglePlot {
             )
         }
 
+        // Draw overlay lines and the legend
+        let mut legend_x = plot_area.right();
+        for layer in self.layers.iter().rev() {
+            let galley = ui
+                .fonts()
+                .layout_single_line(FontSize::Small.into(), layer.label.clone());
+            legend_x -= galley.size.x;
+            painter.galley(Pos2::new(legend_x, plot_area.top()), galley, layer.color);
+            legend_x -= AXIS_PADDING;
+        }
+        for (overlay, overlay_points) in self.overlays.iter().zip(overlay_points.iter()).rev() {
+            for segment in overlay_points.as_slice().windows(2) {
+                let (start, end) = match (segment[0], segment[1]) {
+                    (Some(start), Some(end)) => (start, end),
+                    _ => continue,
+                };
+                painter.line_segment(
+                    [start, end],
+                    Stroke {
+                        width: 1.5,
+                        color: overlay.color,
+                    },
+                )
+            }
+
+            let galley = ui
+                .fonts()
+                .layout_single_line(FontSize::Small.into(), overlay.label.clone());
+            legend_x -= galley.size.x;
+            painter.galley(Pos2::new(legend_x, plot_area.top()), galley, overlay.color);
+            legend_x -= AXIS_PADDING;
+        }
+
+        // Highlight the point under the cursor and show its details
+        let mut clicked = None;
+        let hovered = if interact.dragged() || points.is_empty() {
+            None
+        } else if let Some(pos) = interact.hover_pos() {
+            if plot_area.contains(pos) {
+                points.iter().min_by(|a, b| {
+                    (a.0.x - pos.x)
+                        .abs()
+                        .partial_cmp(&(b.0.x - pos.x).abs())
+                        .unwrap()
+                })
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        if let Some((pos, _, start)) = hovered {
+            painter.circle_filled(*pos, 4.0, self.color);
+            let group = &self.points[*start..*start + combined_points];
+            if interact.clicked() {
+                clicked = Some(group.last().unwrap().solve_id.clone());
+            }
+            interact.clone().on_hover_text(self.tooltip(group));
+        }
+
         if interact.dragged() {
             self.update_zoom(
                 ctxt,
@@ -441,6 +740,411 @@ impl SinglePlot {
             let scroll_delta = ctxt.input().scroll_delta;
             self.update_zoom(ctxt, ui, scroll_delta.x, scroll_delta.y, &plot_area);
         }
+
+        clicked
+    }
+}
+
+impl YAxis {
+    fn value_string(&self, value: f32) -> String {
+        match self {
+            YAxis::Time => solve_time_string((value * 1000.0) as u32),
+            YAxis::MoveCount => format!("{:.1}", value),
+            YAxis::TurnsPerSecond => format!("{:.2}", value),
+        }
+    }
+}
+
+impl HistogramPlot {
+    pub fn new(title: String, y_axis: YAxis, color: Color32) -> Self {
+        Self {
+            title,
+            y_axis,
+            bucket_size: 1.0,
+            buckets: Vec::new(),
+            color,
+        }
+    }
+
+    pub fn set_bucket_size(&mut self, size: f32) {
+        self.bucket_size = size;
+    }
+
+    /// Assumes the buckets are added in increasing order with no gaps
+    pub fn push(&mut self, start: f32, count: usize) {
+        self.buckets.push((start, count));
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn valid(&self) -> bool {
+        self.buckets.iter().fold(0, |sum, bucket| sum + bucket.1) >= 2
+    }
+
+    pub fn update(&mut self, ui: &mut Ui, rect: Rect, interact: Response) {
+        let painter = ui.painter();
+        let max_count = self.buckets.iter().fold(1, |max, bucket| bucket.1.max(max));
+
+        // Subtract out x axis labels from plot area
+        let axis_label_height = ui.fonts().row_height(FontSize::Normal.into());
+        let plot_area = Rect::from_min_size(
+            rect.left_top(),
+            Vec2::new(
+                rect.width(),
+                rect.height() - axis_label_height - AXIS_PADDING,
+            ),
+        );
+
+        // Pick a step for the count labels on the y axis that fits
+        let max_labels = ((plot_area.height() / (axis_label_height * AXIS_LABEL_PADDING_FACTOR))
+            as usize)
+            .max(2);
+        let mut step = 1;
+        for candidate in [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000].iter() {
+            step = *candidate;
+            if max_count / step <= max_labels {
+                break;
+            }
+        }
+
+        let mut y_axis_labels = Vec::new();
+        let mut max_width = 0.0;
+        let mut value = step;
+        while value <= max_count {
+            let galley = ui
+                .fonts()
+                .layout_single_line(FontSize::Normal.into(), format!("{}", value));
+            max_width = galley.size.x.max(max_width);
+            y_axis_labels.push((galley, value));
+            value += step;
+        }
+
+        // Subtract out y axis labels from plot area
+        let plot_area = Rect::from_min_size(
+            Pos2::new(plot_area.left() + max_width + AXIS_PADDING, plot_area.top()),
+            Vec2::new(
+                plot_area.width() - max_width - AXIS_PADDING,
+                plot_area.height(),
+            ),
+        );
+        let bar_width = plot_area.width() / self.buckets.len() as f32;
+        let count_y = |count: usize| {
+            plot_area.top() + plot_area.height() * (1.0 - count as f32 / max_count as f32)
+        };
+
+        // Draw y axis labels and grid lines
+        for (galley, value) in y_axis_labels {
+            let y = count_y(value);
+            painter.line_segment(
+                [
+                    Pos2::new(plot_area.left(), y),
+                    Pos2::new(plot_area.right(), y),
+                ],
+                Stroke {
+                    width: 1.0,
+                    color: Theme::BackgroundHighlight.into(),
+                },
+            );
+            painter.galley(
+                Pos2::new(
+                    plot_area.left() - AXIS_PADDING - galley.size.x,
+                    y - galley.size.y / 2.0,
+                ),
+                galley,
+                Theme::Content.into(),
+            );
+        }
+
+        // Draw x axis labels at bucket boundaries, skipping labels that would overlap
+        let mut min_x = plot_area.left() - max_width;
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            let x = plot_area.left() + bar_width * idx as f32;
+            let galley = ui
+                .fonts()
+                .layout_single_line(FontSize::Normal.into(), self.y_axis.value_string(bucket.0));
+            let left = x - galley.size.x / 2.0;
+            if left > min_x {
+                min_x = x + galley.size.x / 2.0 + AXIS_LABEL_PADDING_WIDTH;
+                painter.galley(
+                    Pos2::new(left, plot_area.bottom() + AXIS_PADDING),
+                    galley,
+                    Theme::Content.into(),
+                );
+                painter.line_segment(
+                    [
+                        Pos2::new(x, plot_area.bottom()),
+                        Pos2::new(x, plot_area.bottom() + AXIS_TICK_SIZE),
+                    ],
+                    Stroke {
+                        width: 2.0,
+                        color: Theme::Content.into(),
+                    },
+                );
+            }
+        }
+
+        // Find the bar under the cursor
+        let hovered = interact.hover_pos().and_then(|pos| {
+            if plot_area.contains(pos) {
+                Some(((pos.x - plot_area.left()) / bar_width) as usize)
+            } else {
+                None
+            }
+        });
+
+        // Draw bars
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            if bucket.1 == 0 {
+                continue;
+            }
+            let alpha = if hovered == Some(idx) {
+                MAX_ALPHA
+            } else {
+                MIN_ALPHA
+                    + ((MAX_ALPHA - MIN_ALPHA) as f32 * bucket.1 as f32 / max_count as f32) as u8
+                        / 2
+            };
+            let bar = Rect::from_min_max(
+                Pos2::new(
+                    plot_area.left() + bar_width * idx as f32 + 1.0,
+                    count_y(bucket.1),
+                ),
+                Pos2::new(
+                    plot_area.left() + bar_width * (idx + 1) as f32 - 1.0,
+                    plot_area.bottom(),
+                ),
+            );
+            painter.rect_filled(
+                bar,
+                0.0,
+                Color32::from_rgba_unmultiplied(
+                    self.color.r(),
+                    self.color.g(),
+                    self.color.b(),
+                    alpha,
+                ),
+            );
+            painter.line_segment(
+                [bar.left_top(), bar.right_top()],
+                Stroke {
+                    width: 2.0,
+                    color: self.color,
+                },
+            );
+        }
+
+        // Draw axis lines
+        painter.line_segment(
+            [
+                Pos2::new(plot_area.left(), plot_area.top() - 1.0),
+                plot_area.left_bottom(),
+            ],
+            Stroke {
+                width: 2.0,
+                color: Theme::Content.into(),
+            },
+        );
+        painter.line_segment(
+            [
+                plot_area.right_bottom(),
+                Pos2::new(plot_area.left() - 1.0, plot_area.bottom()),
+            ],
+            Stroke {
+                width: 2.0,
+                color: Theme::Content.into(),
+            },
+        );
+
+        if let Some(bucket) = hovered.and_then(|idx| self.buckets.get(idx)) {
+            interact.on_hover_text(format!(
+                "{} - {}\n{} solve{}",
+                self.y_axis.value_string(bucket.0),
+                self.y_axis.value_string(bucket.0 + self.bucket_size),
+                bucket.1,
+                if bucket.1 == 1 { "" } else { "s" }
+            ));
+        }
+    }
+}
+
+impl CalendarPlot {
+    pub fn new(title: String, color: Color32) -> Self {
+        Self {
+            title,
+            days: BTreeMap::new(),
+            color,
+        }
+    }
+
+    pub fn set_day(&mut self, date: Date<Local>, day: CalendarDay) {
+        self.days.insert(date, day);
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn valid(&self) -> bool {
+        true
+    }
+
+    fn day_tooltip(date: &Date<Local>, day: Option<&CalendarDay>) -> String {
+        let mut result = format!("{}", date.format("%a, %b %e, %Y"));
+        match day {
+            Some(day) => {
+                result += &format!(
+                    "\n{} solve{}",
+                    day.count,
+                    if day.count == 1 { "" } else { "s" }
+                );
+                if let Some(average) = day.average {
+                    result += &format!("\nAverage: {}", solve_time_string(average));
+                }
+            }
+            None => result += "\nNo solves",
+        }
+        result
+    }
+
+    /// Draws the calendar. Returns the day that was clicked, if any.
+    pub fn update(&mut self, ui: &mut Ui, rect: Rect, interact: Response) -> Option<Date<Local>> {
+        let painter = ui.painter();
+        let label_height = ui.fonts().row_height(FontSize::Small.into());
+        let label_width = ["Mon", "Wed", "Fri"].iter().fold(0.0, |max: f32, day| {
+            ui.fonts()
+                .layout_single_line(FontSize::Small.into(), day.to_string())
+                .size
+                .x
+                .max(max)
+        });
+
+        // Size cells so that the whole year fits in the available space
+        let cell_size = ((rect.width() - label_width - AXIS_PADDING) / CALENDAR_WEEKS as f32)
+            .min((rect.height() - label_height - AXIS_PADDING) / 7.0)
+            .min(MAX_CALENDAR_CELL_SIZE)
+            .floor();
+        if cell_size < 2.0 {
+            return None;
+        }
+        let grid_width = cell_size * CALENDAR_WEEKS as f32;
+        let grid = Rect::from_min_size(
+            Pos2::new(
+                rect.center().x - (grid_width - label_width - AXIS_PADDING) / 2.0,
+                rect.top() + label_height + AXIS_PADDING,
+            ),
+            Vec2::new(grid_width, cell_size * 7.0),
+        );
+
+        // Weeks start on Sunday, with the last column containing today
+        let today = Local::today();
+        let first_day = today
+            - Duration::days(today.weekday().num_days_from_sunday() as i64)
+            - Duration::weeks(CALENDAR_WEEKS as i64 - 1);
+        let max_count = self
+            .days
+            .range(first_day..)
+            .fold(1, |max, (_, day)| day.count.max(max));
+
+        // Draw day of week labels
+        for (row, day) in [(1, "Mon"), (3, "Wed"), (5, "Fri")].iter() {
+            let galley = ui
+                .fonts()
+                .layout_single_line(FontSize::Small.into(), day.to_string());
+            painter.galley(
+                Pos2::new(
+                    grid.left() - AXIS_PADDING - galley.size.x,
+                    grid.top() + cell_size * (*row as f32 + 0.5) - galley.size.y / 2.0,
+                ),
+                galley,
+                Theme::Disabled.into(),
+            );
+        }
+
+        let hover_pos = interact.hover_pos();
+        let mut hovered = None;
+        let mut last_month = None;
+        for week in 0..CALENDAR_WEEKS {
+            let week_start = first_day + Duration::weeks(week as i64);
+            let x = grid.left() + cell_size * week as f32;
+
+            // Label the first week of each month
+            if last_month != Some(week_start.month()) {
+                if last_month.is_some() || week_start.day() <= 7 {
+                    painter.galley(
+                        Pos2::new(x, rect.top()),
+                        ui.fonts().layout_single_line(
+                            FontSize::Small.into(),
+                            week_start.format("%b").to_string(),
+                        ),
+                        Theme::Disabled.into(),
+                    );
+                }
+                last_month = Some(week_start.month());
+            }
+
+            for weekday in 0..7 {
+                let date = week_start + Duration::days(weekday);
+                if date > today {
+                    break;
+                }
+
+                let cell = Rect::from_min_size(
+                    Pos2::new(x, grid.top() + cell_size * weekday as f32),
+                    Vec2::new(cell_size, cell_size),
+                )
+                .shrink(1.0);
+                let color = match self.days.get(&date) {
+                    Some(day) if day.count > 0 => Color32::from_rgba_unmultiplied(
+                        self.color.r(),
+                        self.color.g(),
+                        self.color.b(),
+                        MIN_ALPHA
+                            + ((255 - MIN_ALPHA) as f32 * day.count as f32 / max_count as f32)
+                                as u8,
+                    ),
+                    _ => Theme::BackgroundHighlight.into(),
+                };
+                painter.rect_filled(cell, 2.0, color);
+
+                if let Some(pos) = hover_pos {
+                    if cell.expand(1.0).contains(pos) {
+                        painter.rect_stroke(
+                            cell,
+                            2.0,
+                            Stroke {
+                                width: 1.0,
+                                color: Theme::Content.into(),
+                            },
+                        );
+                        hovered = Some(date);
+                    }
+                }
+            }
+        }
+
+        if let Some(date) = hovered {
+            let day = self.days.get(&date);
+            let clicked = interact.clicked() && day.is_some();
+            interact.on_hover_text(Self::day_tooltip(&date, day));
+            if clicked {
+                return Some(date);
+            }
+        }
+        None
+    }
+}
+
+impl From<CalendarPlot> for Plot {
+    fn from(plot: CalendarPlot) -> Self {
+        Self::Calendar(plot)
+    }
+}
+
+impl From<HistogramPlot> for Plot {
+    fn from(plot: HistogramPlot) -> Self {
+        Self::Histogram(plot)
     }
 }
 
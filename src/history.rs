@@ -1,16 +1,26 @@
 use crate::app::SolveDetails;
 use crate::font::FontSize;
-use crate::style::content_visuals;
+use crate::style::{content_visuals, side_visuals};
 use crate::theme::Theme;
-use crate::widgets::{date_string, solve_time_string};
+use crate::widgets::{date_string, solve_time_string, CustomWidgets};
+use chrono::{Date, Local};
 use egui::{
-    containers::ScrollArea, popup_below_widget, Align2, CentralPanel, CtxRef, CursorIcon, Pos2,
-    Rect, SelectableLabel, Sense, Stroke, Ui, Vec2,
+    containers::ScrollArea, popup_below_widget, Align, Align2, CentralPanel, CtxRef, CursorIcon,
+    Label, Pos2, Rect, SelectableLabel, Sense, Stroke, TextEdit, TopBottomPanel, Ui, Vec2,
 };
+use std::collections::HashSet;
 use tpscube_core::{
-    Average, BestSolve, History, ListAverage, Penalty, Solve, SolveList, SolveType,
+    AttemptFormat, AttemptGroup, Average, BestSolve, History, ListAverage, Penalty, SkippedStep,
+    Solve, SolveList, SolveQuery, SolveType,
 };
 
+use anyhow::Result;
+
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::anyhow;
+#[cfg(not(target_arch = "wasm32"))]
+use tpscube_core::{ReportFormat, SessionReport};
+
 const REGION_PADDING: f32 = 16.0;
 const SESSION_REGION_BORDER: f32 = 8.0;
 const SESSION_SEPARATOR_SIZE: f32 = 16.0;
@@ -18,6 +28,17 @@ const SESSION_BEST_PADDING: f32 = 32.0;
 const BEST_TIME_COL_PADDING: f32 = 32.0;
 const BEST_TIME_ROW_PADDING: f32 = 8.0;
 
+/// Number of solves fetched from the history at a time when grouping by
+/// something other than session
+const QUERY_PAGE_SIZE: usize = 1000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HistoryGrouping {
+    Session,
+    Day,
+    Puzzle,
+}
+
 trait HistoryRegion {
     fn height(&self, ui: &Ui, layout_metrics: &SolveLayoutMetrics) -> f32;
     fn paint(
@@ -28,7 +49,13 @@ trait HistoryRegion {
         history: &mut History,
         all_time_best: &Option<AllTimeBestRegion>,
         details: &mut Option<SolveDetails>,
+        selection: &mut HashSet<String>,
     );
+
+    /// Returns true if the region shows solves performed on the given day
+    fn contains_date(&self, _date: &Date<Local>) -> bool {
+        false
+    }
 }
 
 struct NoSolvesRegion;
@@ -39,11 +66,16 @@ struct AllTimeBestRegion {
     best_ao12: Option<Average>,
     best_ao50: Option<Average>,
     best_ao100: Option<Average>,
+    /// Official WCA records for the puzzle, if they have been imported
+    official: Option<String>,
 }
 
+/// Group of solves shown together in the history. When grouping by session,
+/// `session_id` is the session the solves belong to.
 struct SessionRegion {
-    session_id: String,
+    session_id: Option<String>,
     name: String,
+    average_label: &'static str,
     solves: Vec<Solve>,
     last_solve: Solve,
     rows: usize,
@@ -53,6 +85,8 @@ struct SessionRegion {
     best_ao50: Option<Average>,
     best_ao100: Option<Average>,
     average: Option<u32>,
+    /// Formal attempts made in the session, only shown when grouping by session
+    attempts: Vec<AttemptGroup>,
 }
 
 struct HistoryRegionLayout {
@@ -69,6 +103,15 @@ pub struct HistoryWidget {
     cached_best_columns: usize,
     cached_solve_columns: usize,
     cached_solve_type: SolveType,
+    cached_grouping: HistoryGrouping,
+    cached_skip_filter: Option<SkippedStep>,
+    scroll_target: Option<Date<Local>>,
+    grouping: HistoryGrouping,
+    /// Only show solves where this step was skipped
+    skip_filter: Option<SkippedStep>,
+    selection: HashSet<String>,
+    tag_text: String,
+    settings_restored: bool,
 }
 
 struct SolveLayoutMetrics {
@@ -96,6 +139,7 @@ impl HistoryRegion for NoSolvesRegion {
         _history: &mut History,
         _all_time_best: &Option<AllTimeBestRegion>,
         _details: &mut Option<SolveDetails>,
+        _selection: &mut HashSet<String>,
     ) {
         let galley = ui.fonts().layout_multiline(
             FontSize::Section.into(),
@@ -140,11 +184,17 @@ impl AllTimeBestRegion {
 impl HistoryRegion for AllTimeBestRegion {
     fn height(&self, ui: &Ui, layout_metrics: &SolveLayoutMetrics) -> f32 {
         let rows = (self.columns() + layout_metrics.best_columns - 1) / layout_metrics.best_columns;
+        let official_height = if self.official.is_some() {
+            ui.fonts().row_height(FontSize::Normal.into()) + BEST_TIME_ROW_PADDING
+        } else {
+            0.0
+        };
         rows as f32
             * (ui.fonts().row_height(FontSize::Normal.into())
                 + ui.fonts().row_height(FontSize::BestTime.into())
                 + BEST_TIME_ROW_PADDING)
             - BEST_TIME_ROW_PADDING
+            + official_height
     }
 
     fn paint(
@@ -155,6 +205,7 @@ impl HistoryRegion for AllTimeBestRegion {
         _history: &mut History,
         _all_time_best: &Option<AllTimeBestRegion>,
         details: &mut Option<SolveDetails>,
+        _selection: &mut HashSet<String>,
     ) {
         let mut best_count = self.columns();
         let mut row_columns_left = if best_count <= layout_metrics.best_columns {
@@ -441,10 +492,135 @@ impl HistoryRegion for AllTimeBestRegion {
                 *details = Some(SolveDetails::AverageOfSolves(average.solves.clone()));
             }
         }
+
+        // Draw official records below the practice bests
+        if let Some(official) = &self.official {
+            let galley = ui
+                .fonts()
+                .layout_single_line(FontSize::Normal.into(), official.clone());
+            ui.painter().galley(
+                Pos2::new(
+                    rect.center().x - galley.size.x / 2.0,
+                    rect.bottom() - galley.size.y,
+                ),
+                galley,
+                Theme::Disabled.into(),
+            );
+        }
     }
 }
 
+impl SessionRegion {
+    /// Returns the index of the formal attempt that a solve is part of
+    fn attempt_index(&self, solve_id: &str) -> Option<usize> {
+        self.attempts
+            .iter()
+            .position(|attempt| attempt.solves.iter().any(|solve| solve.id == solve_id))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn session_menu(
+        &self,
+        ui: &mut Ui,
+        content_area: Rect,
+        layout_metrics: &SolveLayoutMetrics,
+        history: &mut History,
+        session_id: &str,
+        details: &mut Option<SolveDetails>,
+    ) {
+        let menu_rect = Rect::from_min_size(
+            Pos2::new(
+                content_area.right() - layout_metrics.solve_menu_width,
+                content_area.top(),
+            ),
+            Vec2::new(
+                layout_metrics.solve_menu_width,
+                ui.fonts().row_height(FontSize::Section.into()),
+            ),
+        );
+        let interact = ui.allocate_rect(menu_rect, Sense::click());
+        ui.painter().text(
+            Pos2::new(menu_rect.left(), menu_rect.center().y),
+            Align2::LEFT_CENTER,
+            " ☰",
+            FontSize::Normal.into(),
+            if interact.hovered() {
+                Theme::Content.into()
+            } else {
+                Theme::Disabled.into()
+            },
+        );
+
+        let popup_id = ui.make_persistent_id(format!("session-{}", session_id));
+        if interact.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        let old_visuals = ui.ctx().style().visuals.clone();
+        ui.ctx().set_visuals(crate::style::popup_visuals());
+        popup_below_widget(ui, popup_id, &interact, |ui| {
+            ui.set_min_width(220.0);
+            for (format, name) in [
+                (ReportFormat::Markdown, "Export report as Markdown"),
+                (ReportFormat::Html, "Export report as HTML"),
+            ]
+            .iter()
+            {
+                if ui
+                    .add(SelectableLabel::new(false, *name).text_style(FontSize::Normal.into()))
+                    .clicked()
+                {
+                    let _ = export_session_report(history, session_id, *format);
+                }
+            }
+
+            if ui
+                .add(
+                    SelectableLabel::new(false, "Show PLL case breakdown")
+                        .text_style(FontSize::Normal.into()),
+                )
+                .clicked()
+            {
+                *details = Some(SolveDetails::AverageOfSolves(self.solves.clone()));
+            }
+
+            let excluded = history.is_session_sync_excluded(session_id);
+            if ui
+                .add(
+                    SelectableLabel::new(excluded, "Keep on this device only")
+                        .text_style(FontSize::Normal.into()),
+                )
+                .clicked()
+            {
+                history.set_session_sync_excluded(session_id, !excluded);
+            }
+        });
+        ui.ctx().set_visuals(old_visuals);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export_session_report(history: &History, session_id: &str, format: ReportFormat) -> Result<()> {
+    let report = SessionReport::generate(history, session_id)
+        .ok_or_else(|| anyhow!("Session has no solves"))?;
+    let extension = format.extension();
+    if let Some(path) = tinyfiledialogs::save_file_dialog_with_filter(
+        "Export Session Report",
+        &format!("session.{}", extension),
+        &[&format!("*.{}", extension)],
+        "Session reports",
+    ) {
+        std::fs::write(path, report.export(format).as_bytes())?;
+    }
+    Ok(())
+}
+
 impl HistoryRegion for SessionRegion {
+    fn contains_date(&self, date: &Date<Local>) -> bool {
+        self.solves
+            .iter()
+            .any(|solve| solve.created.date() == *date)
+    }
+
     fn height(&self, ui: &Ui, layout_metrics: &SolveLayoutMetrics) -> f32 {
         // Layout best solve and average region to determine line wrapping
         let mut x = 0.0;
@@ -538,7 +714,7 @@ impl HistoryRegion for SessionRegion {
         if let Some(average) = &self.average {
             let width = ui
                 .fonts()
-                .layout_single_line(FontSize::Normal.into(), "Session avg: ".into())
+                .layout_single_line(FontSize::Normal.into(), self.average_label.into())
                 .size
                 .x
                 + ui.fonts()
@@ -550,6 +726,9 @@ impl HistoryRegion for SessionRegion {
             }
         }
 
+        // Each formal attempt is shown on its own line
+        lines += self.attempts.len();
+
         ui.fonts().row_height(FontSize::Normal.into()) * ((self.rows + lines) as f32)
             + ui.fonts().row_height(FontSize::Section.into())
             + SESSION_REGION_BORDER
@@ -564,6 +743,7 @@ impl HistoryRegion for SessionRegion {
         history: &mut History,
         all_time_best: &Option<AllTimeBestRegion>,
         details: &mut Option<SolveDetails>,
+        selection: &mut HashSet<String>,
     ) {
         let (
             all_time_best_solve,
@@ -599,6 +779,15 @@ impl HistoryRegion for SessionRegion {
         // Draw session name
         let content_area = shaded_area.shrink2(Vec2::new(SESSION_REGION_BORDER, 0.0));
 
+        // Leave room for the session menu on platforms that can save files
+        #[cfg(not(target_arch = "wasm32"))]
+        let name_width = match self.session_id {
+            Some(_) => content_area.width() - layout_metrics.solve_menu_width,
+            None => content_area.width(),
+        };
+        #[cfg(target_arch = "wasm32")]
+        let name_width = content_area.width();
+
         let mut name = self.name.clone();
         let mut truncated = false;
         while name.len() > 0 {
@@ -611,7 +800,7 @@ impl HistoryRegion for SessionRegion {
                 .fonts()
                 .layout_single_line(FontSize::Section.into(), string);
 
-            if name_galley.size.x > content_area.width() {
+            if name_galley.size.x > name_width {
                 name.pop();
                 truncated = true;
                 continue;
@@ -622,6 +811,18 @@ impl HistoryRegion for SessionRegion {
             break;
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(session_id) = &self.session_id {
+            self.session_menu(
+                ui,
+                content_area,
+                layout_metrics,
+                history,
+                session_id,
+                details,
+            );
+        }
+
         // Draw separator between name and solve list
         let mut y = content_area.top() + ui.fonts().row_height(FontSize::Section.into());
         ui.painter().line_segment(
@@ -646,6 +847,30 @@ impl HistoryRegion for SessionRegion {
             }
 
             for row in 0..self.rows {
+                // Only paint rows that are visible, as sessions can contain thousands of solves
+                let row_top = y + row as f32 * row_height;
+                if row_top + row_height < ui.clip_rect().top() || row_top > ui.clip_rect().bottom()
+                {
+                    i += 1;
+                    if i >= self.solves.len() {
+                        break;
+                    }
+                    continue;
+                }
+
+                // Highlight selected solves
+                let selected = selection.contains(&self.solves[i].id);
+                if selected {
+                    ui.painter().rect_filled(
+                        Rect::from_min_size(
+                            Pos2::new(content_area.left() + col as f32 * col_width, row_top),
+                            Vec2::new(layout_metrics.total_solve_width, row_height),
+                        ),
+                        0.0,
+                        Theme::BackgroundHighlight,
+                    );
+                }
+
                 // Draw solve number
                 ui.painter().text(
                     Pos2::new(
@@ -655,7 +880,11 @@ impl HistoryRegion for SessionRegion {
                     Align2::LEFT_TOP,
                     format!("{}.", i + 1),
                     FontSize::Normal.into(),
-                    Theme::Disabled.into(),
+                    if self.attempt_index(&self.solves[i].id).is_some() {
+                        Theme::Magenta.into()
+                    } else {
+                        Theme::Disabled.into()
+                    },
                 );
 
                 // Layout solve time for right alignment
@@ -687,6 +916,8 @@ impl HistoryRegion for SessionRegion {
                     galley,
                     if interact.hovered() {
                         Theme::Blue.into()
+                    } else if selected {
+                        Theme::Yellow.into()
                     } else {
                         match time {
                             Some(_) => {
@@ -703,9 +934,17 @@ impl HistoryRegion for SessionRegion {
                     },
                 );
 
-                // Check for click on solve time
+                // Check for click on solve time. Holding command or shift, or clicking
+                // while there is already a selection, toggles selection of the solve.
                 if interact.on_hover_cursor(CursorIcon::PointingHand).clicked() {
-                    *details = Some(SolveDetails::IndividualSolve(self.solves[i].clone()));
+                    let modifiers = ui.input().modifiers;
+                    if modifiers.command || modifiers.shift || selection.len() != 0 {
+                        if !selection.remove(&self.solves[i].id) {
+                            selection.insert(self.solves[i].id.clone());
+                        }
+                    } else {
+                        *details = Some(SolveDetails::IndividualSolve(self.solves[i].clone()));
+                    }
                 }
 
                 if let Penalty::Time(penalty) = self.solves[i].penalty {
@@ -791,6 +1030,17 @@ impl HistoryRegion for SessionRegion {
                 ui.ctx().set_visuals(crate::style::popup_visuals());
                 popup_below_widget(ui, popup_id, &interact, |ui| {
                     ui.set_min_width(180.0);
+
+                    // Show which input recorded the solve, if known
+                    if let Some(input) = self.solves[i].input() {
+                        ui.add(
+                            Label::new(format!("Recorded with {}", input.name()))
+                                .text_style(FontSize::Small.into())
+                                .text_color(Theme::Disabled),
+                        );
+                        ui.separator();
+                    }
+
                     if ui
                         .add(
                             SelectableLabel::new(
@@ -842,6 +1092,25 @@ impl HistoryRegion for SessionRegion {
                         let _ = history.local_commit();
                     }
 
+                    // Show tags, clicking on a tag removes it
+                    let tags = history.solve_tags(&self.solves[i].id);
+                    if tags.len() != 0 {
+                        ui.separator();
+                        for tag in tags {
+                            if ui
+                                .add(
+                                    SelectableLabel::new(true, format!("🏷 {}", tag))
+                                        .text_style(FontSize::Normal.into()),
+                                )
+                                .on_hover_text("Click to remove tag")
+                                .clicked()
+                            {
+                                history.untag_solve(self.solves[i].id.clone(), tag);
+                                let _ = history.local_commit();
+                            }
+                        }
+                    }
+
                     ui.separator();
 
                     if ui
@@ -1095,7 +1364,7 @@ impl HistoryRegion for SessionRegion {
         if let Some(average) = &self.average {
             let label_galley = ui
                 .fonts()
-                .layout_single_line(FontSize::Normal.into(), "Session avg: ".into());
+                .layout_single_line(FontSize::Normal.into(), self.average_label.into());
             let time_galley = ui
                 .fonts()
                 .layout_single_line(FontSize::Normal.into(), solve_time_string(*average));
@@ -1116,6 +1385,62 @@ impl HistoryRegion for SessionRegion {
                 Theme::Content.into(),
             );
         }
+
+        // Draw formal attempts
+        for (i, attempt) in self.attempts.iter().enumerate() {
+            let result = match attempt.result() {
+                Some(result) => result,
+                None => continue,
+            };
+            y += ui.fonts().row_height(FontSize::Normal.into());
+            let x = content_area.left();
+
+            let label_galley = ui.fonts().layout_single_line(
+                FontSize::Normal.into(),
+                format!("{} #{}: ", attempt.format.to_string(), i + 1),
+            );
+            let label_width = label_galley.size.x;
+            ui.painter()
+                .galley(Pos2::new(x, y), label_galley, Theme::Magenta.into());
+
+            let time_galley = ui.fonts().layout_single_line(
+                FontSize::Normal.into(),
+                match result.ranked() {
+                    Some(time) => solve_time_string(time),
+                    None => "DNF".into(),
+                },
+            );
+            let time_width = time_galley.size.x;
+            let rect = Rect::from_min_size(Pos2::new(x + label_width, y), time_galley.size);
+            let interact = ui.allocate_rect(rect, Sense::click());
+            ui.painter().galley(
+                rect.left_top(),
+                time_galley,
+                if interact.hovered() {
+                    Theme::Blue.into()
+                } else if result.ranked().is_none() {
+                    Theme::Red.into()
+                } else {
+                    Theme::Content.into()
+                },
+            );
+
+            // Averages also show the best single, as in competition results
+            if let Some(best) = result.best.filter(|_| attempt.format.has_average()) {
+                ui.painter().text(
+                    Pos2::new(x + label_width + time_width, y),
+                    Align2::LEFT_TOP,
+                    format!("  (best {})", solve_time_string(best)),
+                    FontSize::Normal.into(),
+                    Theme::Disabled.into(),
+                );
+            }
+
+            // Check for click on attempt result
+            if interact.on_hover_cursor(CursorIcon::PointingHand).clicked() {
+                *details = Some(SolveDetails::AverageOfSolves(attempt.solves.clone()));
+            }
+        }
     }
 }
 
@@ -1129,6 +1454,309 @@ impl HistoryWidget {
             cached_best_columns: 0,
             cached_solve_columns: 0,
             cached_solve_type: SolveType::Standard3x3x3,
+            cached_grouping: HistoryGrouping::Session,
+            cached_skip_filter: None,
+            scroll_target: None,
+            grouping: HistoryGrouping::Session,
+            skip_filter: None,
+            selection: HashSet::new(),
+            tag_text: String::new(),
+            settings_restored: false,
+        }
+    }
+
+    /// Scrolls to the session containing solves from the given day on the
+    /// next update
+    pub fn scroll_to_date(&mut self, date: Date<Local>) {
+        self.scroll_target = Some(date);
+    }
+
+    /// Skips are only analyzed for 3x3x3 solves, so the skip filter is only
+    /// available when those solves are shown
+    fn can_filter_skips(&self, solve_type: SolveType) -> bool {
+        solve_type.is_3x3x3() || self.grouping == HistoryGrouping::Puzzle
+    }
+
+    /// Skip filter that applies to the solves currently shown
+    fn active_skip_filter(&self, solve_type: SolveType) -> Option<SkippedStep> {
+        if self.can_filter_skips(solve_type) {
+            self.skip_filter
+        } else {
+            None
+        }
+    }
+
+    /// Gathers the solves to show in the history according to the current
+    /// grouping and skip filter. Solves in each group are in chronological order.
+    fn solve_groups(
+        &self,
+        history: &History,
+        solve_type: SolveType,
+    ) -> Vec<(Option<String>, Vec<Solve>)> {
+        let mut query = match self.grouping {
+            HistoryGrouping::Puzzle => SolveQuery::new(),
+            _ => SolveQuery::new().solve_type(solve_type),
+        };
+        if let Some(step) = self.active_skip_filter(solve_type) {
+            query = query.skipped(step);
+        }
+
+        if self.grouping == HistoryGrouping::Session {
+            return history
+                .sessions()
+                .values()
+                .filter(|session| session.solve_type() == solve_type)
+                .map(|session| {
+                    let solves: Vec<Solve> = session
+                        .to_vec(history)
+                        .into_iter()
+                        .filter(|solve| query.matches(solve, history))
+                        .collect();
+                    (Some(session.id().to_string()), solves)
+                })
+                .filter(|(_, solves)| solves.len() != 0)
+                .collect();
+        }
+
+        let mut days: Vec<Vec<Solve>> = Vec::new();
+        let mut puzzles: Vec<(SolveType, Vec<Solve>)> = Vec::new();
+        let mut offset = 0;
+        loop {
+            // Results are most recent first, so solves from the same day are adjacent
+            let page = history.query(&query, offset, QUERY_PAGE_SIZE);
+            if page.len() == 0 {
+                break;
+            }
+            offset += page.len();
+
+            for solve in page {
+                match self.grouping {
+                    HistoryGrouping::Puzzle => {
+                        match puzzles
+                            .iter_mut()
+                            .find(|(solve_type, _)| *solve_type == solve.solve_type)
+                        {
+                            Some((_, solves)) => solves.push(solve.clone()),
+                            None => puzzles.push((solve.solve_type, vec![solve.clone()])),
+                        }
+                    }
+                    _ => match days.last_mut() {
+                        Some(solves) if solves[0].created.date() == solve.created.date() => {
+                            solves.push(solve.clone())
+                        }
+                        _ => days.push(vec![solve.clone()]),
+                    },
+                }
+            }
+        }
+
+        days.into_iter()
+            .chain(puzzles.into_iter().map(|(_, solves)| solves))
+            .map(|mut solves| {
+                solves.reverse();
+                (None, solves)
+            })
+            .collect()
+    }
+
+    fn restore_settings(&mut self, history: &History) {
+        self.grouping = match history
+            .setting_as_string("history_group")
+            .as_ref()
+            .map(|s| s.as_str())
+        {
+            Some("day") => HistoryGrouping::Day,
+            Some("puzzle") => HistoryGrouping::Puzzle,
+            Some(_) | None => HistoryGrouping::Session,
+        };
+        self.settings_restored = true;
+    }
+
+    fn save_settings(&self, history: &mut History) -> Result<()> {
+        history.set_string_setting(
+            "history_group",
+            match self.grouping {
+                HistoryGrouping::Session => "session",
+                HistoryGrouping::Day => "day",
+                HistoryGrouping::Puzzle => "puzzle",
+            },
+        )
+    }
+
+    fn options_bar(&mut self, ctxt: &CtxRef, history: &mut History, solve_type: SolveType) {
+        TopBottomPanel::top("history_options").show(ctxt, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.add(Label::new("Group by").text_color(Theme::Disabled));
+                for (grouping, name) in [
+                    (HistoryGrouping::Session, "Session"),
+                    (HistoryGrouping::Day, "Day"),
+                    (HistoryGrouping::Puzzle, "Puzzle"),
+                ]
+                .iter()
+                {
+                    if ui.mode_label(name, self.grouping == *grouping).clicked() {
+                        self.grouping = *grouping;
+                        let _ = self.save_settings(history);
+                    }
+                }
+
+                if self.can_filter_skips(solve_type) {
+                    ui.add_space(16.0);
+                    ui.add(Label::new("Show").text_color(Theme::Disabled));
+                    for (filter, name) in [
+                        (None, "All solves"),
+                        (Some(SkippedStep::Any), SkippedStep::Any.to_str()),
+                        (Some(SkippedStep::F2LPair), SkippedStep::F2LPair.to_str()),
+                        (Some(SkippedStep::OLL), SkippedStep::OLL.to_str()),
+                        (Some(SkippedStep::PLL), SkippedStep::PLL.to_str()),
+                    ]
+                    .iter()
+                    {
+                        if ui.mode_label(name, self.skip_filter == *filter).clicked() {
+                            self.skip_filter = *filter;
+                        }
+                    }
+                }
+
+                if self.selection.len() != 0 {
+                    ui.add_space(16.0);
+                    self.selection_options(ui, history);
+                }
+            });
+        });
+    }
+
+    /// Shows batch operations that apply to every selected solve
+    fn selection_options(&mut self, ui: &mut Ui, history: &mut History) {
+        ui.add(Label::new(format!("{} selected", self.selection.len())).text_color(Theme::Yellow));
+
+        // Solves can only be moved into a session of the same solve type
+        let mut solve_types = self
+            .selection
+            .iter()
+            .filter_map(|id| history.solve(id).map(|solve| solve.solve_type));
+        let solve_type = solve_types.next();
+        let single_type = solve_types.all(|other| Some(other) == solve_type);
+
+        if single_type {
+            let move_label = ui.add(Label::new("➡  Move to session").sense(Sense::click()));
+            let popup_id = ui.make_persistent_id("history-move-selection");
+            if move_label.clicked() {
+                ui.memory().toggle_popup(popup_id);
+            }
+            let mut target_session = None;
+            let old_visuals = ui.ctx().style().visuals.clone();
+            ui.ctx().set_visuals(crate::style::popup_visuals());
+            popup_below_widget(ui, popup_id, &move_label, |ui| {
+                ui.set_min_width(220.0);
+                let mut sessions: Vec<_> = history
+                    .sessions()
+                    .values()
+                    .filter(|session| Some(session.solve_type()) == solve_type)
+                    .filter_map(|session| {
+                        session
+                            .last_solve_time()
+                            .map(|time| (time, session.id().to_string(), session.name().clone()))
+                    })
+                    .collect();
+                sessions.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+                ScrollArea::from_max_height(300.0).show(ui, |ui| {
+                    for (time, id, name) in sessions {
+                        let label = match name {
+                            Some(name) => format!("{} - {}", name, date_string(&time)),
+                            None => date_string(&time),
+                        };
+                        if ui
+                            .add(
+                                SelectableLabel::new(false, label)
+                                    .text_style(FontSize::Normal.into()),
+                            )
+                            .clicked()
+                        {
+                            target_session = Some(id);
+                        }
+                    }
+                });
+            });
+            ui.ctx().set_visuals(old_visuals);
+
+            if let Some(session_id) = target_session {
+                for solve_id in self.selection.drain() {
+                    history.change_session(solve_id, session_id.clone());
+                }
+                let _ = history.local_commit();
+            }
+        } else {
+            ui.add(Label::new("➡  Move to session").text_color(Theme::Disabled))
+                .on_hover_text("Selected solves must all be the same puzzle type");
+        }
+
+        // Solves can be grouped into a formal attempt in any format with a
+        // matching number of solves
+        let mut group_format = None;
+        for format in AttemptFormat::ALL {
+            if format.solve_count() == self.selection.len()
+                && ui
+                    .add(Label::new(format!("🏆  {}", format.to_string())).sense(Sense::click()))
+                    .on_hover_text("Group consecutive solves as a formal attempt")
+                    .clicked()
+            {
+                group_format = Some(*format);
+            }
+        }
+        if let Some(format) = group_format {
+            let solve_ids: Vec<String> = self.selection.iter().cloned().collect();
+            if history.group_attempt(format, &solve_ids).is_some() {
+                let _ = history.local_commit();
+                self.selection.clear();
+            }
+        }
+
+        let groups: HashSet<String> = self
+            .selection
+            .iter()
+            .filter_map(|id| history.solve_attempt_group(id).map(|group| group.id))
+            .collect();
+        if groups.len() != 0
+            && ui
+                .add(Label::new("✂  Ungroup").sense(Sense::click()))
+                .clicked()
+        {
+            for group_id in groups {
+                history.ungroup_attempt(group_id);
+            }
+            let _ = history.local_commit();
+        }
+
+        ui.add(TextEdit::singleline(&mut self.tag_text).desired_width(100.0));
+        if ui.add(Label::new("🏷  Tag").sense(Sense::click())).clicked()
+            && self.tag_text.trim().len() != 0
+        {
+            let tag = self.tag_text.trim().to_string();
+            for solve_id in &self.selection {
+                if !history.solve_tags(solve_id).contains(&tag) {
+                    history.tag_solve(solve_id.clone(), tag.clone());
+                }
+            }
+            let _ = history.local_commit();
+            self.tag_text.clear();
+        }
+
+        if ui
+            .add(Label::new("🗑  Delete").sense(Sense::click()))
+            .clicked()
+        {
+            for solve_id in self.selection.drain() {
+                history.delete_solve(solve_id);
+            }
+            let _ = history.local_commit();
+        }
+
+        if ui
+            .add(Label::new("✖  Clear").sense(Sense::click()))
+            .clicked()
+        {
+            self.selection.clear();
         }
     }
 
@@ -1145,28 +1773,38 @@ impl HistoryWidget {
         let mut all_time_best_ao50: Option<Average> = None;
         let mut all_time_best_ao100: Option<Average> = None;
 
-        // Go through sessions, gather data about them, and create regions for them
+        // Go through groups of solves, gather data about them, and create regions for them
+        let skip_filter = self.active_skip_filter(solve_type);
         let mut session_regions = Vec::new();
-        for session in history.sessions().values() {
-            let solves: Vec<Solve> = session.to_vec(history);
-            if solves.len() == 0 {
-                // Skip empty sessions
-                continue;
-            }
-            if session.solve_type() != solve_type {
-                // Skip sessions that aren't the active solve type
-                continue;
-            }
-
+        for (session_id, solves) in self.solve_groups(history, solve_type) {
             let last_solve = solves.last().unwrap().clone();
 
-            // Get averages and bests
-            let average = solves.as_slice().average();
-            let best_solve = solves.as_slice().best();
-            let best_ao5 = solves.as_slice().best_average(5);
-            let best_ao12 = solves.as_slice().best_average(12);
-            let best_ao50 = solves.as_slice().best_average(50);
-            let best_ao100 = solves.as_slice().best_average(100);
+            // Get averages and bests. Sessions use the statistics cached by the
+            // history, other groupings are computed from the solves.
+            // Cached statistics cover the whole session, so they can't be used
+            // when only some of its solves are shown.
+            let stats = session_id
+                .as_ref()
+                .filter(|_| skip_filter.is_none())
+                .and_then(|id| history.session_stats(id));
+            let (average, best_solve, best_ao5, best_ao12, best_ao50, best_ao100) = match &stats {
+                Some(stats) => (
+                    stats.average(),
+                    stats.best(&solves),
+                    stats.best_average(5, &solves),
+                    stats.best_average(12, &solves),
+                    stats.best_average(50, &solves),
+                    stats.best_average(100, &solves),
+                ),
+                None => (
+                    solves.as_slice().average(),
+                    solves.as_slice().best(),
+                    solves.as_slice().best_average(5),
+                    solves.as_slice().best_average(12),
+                    solves.as_slice().best_average(50),
+                    solves.as_slice().best_average(100),
+                ),
+            };
 
             // Check for all time best solve
             if let Some(current_best) = &all_time_best_solve {
@@ -1227,16 +1865,35 @@ impl HistoryWidget {
             let rows =
                 (solves.len() + layout_metrics.solve_columns - 1) / layout_metrics.solve_columns;
 
-            // Construct session title
-            let name = match session.name() {
-                Some(name) => format!("{} - {}", &name, date_string(&last_solve.created)),
-                None => date_string(&last_solve.created),
+            // Construct region title
+            let (name, average_label) = match self.grouping {
+                HistoryGrouping::Session => (
+                    match session_id
+                        .as_ref()
+                        .and_then(|id| history.sessions().get(id))
+                        .and_then(|session| session.name().clone())
+                    {
+                        Some(name) => format!("{} - {}", &name, date_string(&last_solve.created)),
+                        None => date_string(&last_solve.created),
+                    },
+                    "Session avg: ",
+                ),
+                HistoryGrouping::Day => (
+                    last_solve.created.format("%A, %B %e, %Y").to_string(),
+                    "Daily avg: ",
+                ),
+                HistoryGrouping::Puzzle => (last_solve.solve_type.to_string(), "Average: "),
+            };
+            let attempts = match (self.grouping, &session_id) {
+                (HistoryGrouping::Session, Some(session_id)) => history.attempt_groups(session_id),
+                _ => Vec::new(),
             };
 
-            // Add the session to the region list
+            // Add the group to the region list
             session_regions.push(SessionRegion {
-                session_id: session.id().into(),
+                session_id,
                 name,
+                average_label,
                 solves,
                 last_solve,
                 rows,
@@ -1246,9 +1903,12 @@ impl HistoryWidget {
                 best_ao50,
                 best_ao100,
                 average,
+                attempts,
             });
         }
 
+        let _ = history.save_session_stats();
+
         // Sort regions by solve time in descending order
         session_regions.sort_unstable_by(|a, b| b.last_solve.cmp(&a.last_solve));
 
@@ -1265,6 +1925,9 @@ impl HistoryWidget {
             // There are no sessions
             regions.push(Box::new(NoSolvesRegion));
             self.all_time_best_region = None;
+        } else if self.grouping == HistoryGrouping::Puzzle {
+            // Bests are not comparable between puzzles, so each region shows its own
+            self.all_time_best_region = None;
         } else {
             // Add an all-time best region at the top
             self.all_time_best_region = Some(AllTimeBestRegion {
@@ -1273,6 +1936,19 @@ impl HistoryWidget {
                 best_ao12: all_time_best_ao12,
                 best_ao50: all_time_best_ao50,
                 best_ao100: all_time_best_ao100,
+                official: history
+                    .official_results()
+                    .and_then(|results| results.record(solve_type))
+                    .map(|record| {
+                        let mut parts = Vec::new();
+                        if let Some(single) = record.single {
+                            parts.push(format!("single {}", solve_time_string(single)));
+                        }
+                        if let Some(average) = record.average {
+                            parts.push(format!("average {}", solve_time_string(average)));
+                        }
+                        format!("Official WCA records: {}", parts.join(", "))
+                    }),
             });
         }
 
@@ -1299,6 +1975,13 @@ impl HistoryWidget {
         details: &mut Option<SolveDetails>,
         solve_type: SolveType,
     ) {
+        if !self.settings_restored {
+            self.restore_settings(history);
+        }
+
+        ctxt.set_visuals(side_visuals());
+        self.options_bar(ctxt, history, solve_type);
+
         ctxt.set_visuals(content_visuals());
         CentralPanel::default().show(ctxt, |ui| {
             let number_galley = ui
@@ -1348,11 +2031,19 @@ impl HistoryWidget {
                 || self.cached_solve_columns != solve_columns
                 || self.cached_best_columns != best_columns
                 || self.cached_solve_type != solve_type
+                || self.cached_grouping != self.grouping
+                || self.cached_skip_filter != self.skip_filter
             {
                 self.cached_update_id = Some(history.update_id());
                 self.cached_solve_columns = solve_columns;
                 self.cached_best_columns = best_columns;
                 self.cached_solve_type = solve_type;
+                self.cached_grouping = self.grouping;
+                self.cached_skip_filter = self.skip_filter;
+
+                // Drop solves from the selection that no longer exist
+                self.selection.retain(|id| history.solve(id).is_some());
+
                 self.generate_regions(ui, &solve_layout_metrics, history, solve_type);
             }
 
@@ -1366,6 +2057,21 @@ impl HistoryWidget {
                         Vec2::new(ui.max_rect().width(), self.total_height),
                         Sense::hover(),
                     );
+                    if let Some(date) = self.scroll_target.take() {
+                        if let Some(region) = self
+                            .regions
+                            .iter()
+                            .find(|region| region.region.contains_date(&date))
+                        {
+                            ui.scroll_to_rect(
+                                Rect::from_min_size(
+                                    Pos2::new(rect.left(), rect.top() + region.y),
+                                    Vec2::new(rect.width(), region.height),
+                                ),
+                                Align::TOP,
+                            );
+                        }
+                    }
                     if let Some(region) = &self.all_time_best_region {
                         let height = region.height(ui, &solve_layout_metrics);
                         if height >= viewport.top() {
@@ -1379,6 +2085,7 @@ impl HistoryWidget {
                                 history,
                                 &None,
                                 details,
+                                &mut self.selection,
                             );
                         }
                     }
@@ -1400,6 +2107,7 @@ impl HistoryWidget {
                             history,
                             &self.all_time_best_region,
                             details,
+                            &mut self.selection,
                         );
                     }
                 });
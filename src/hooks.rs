@@ -0,0 +1,99 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tpscube_core::History;
+
+/// Name of the local setting holding the command to run after each solve
+pub const SOLVE_COMMAND_SETTING: &str = "solve_command";
+
+/// Runs a user provided shell command after each completed solve, so that
+/// results can be piped into stream overlays, chat bots or spreadsheets. The
+/// solve is passed as a line of JSON on standard input and in the
+/// `TPSCUBE_SOLVE` environment variable. The command is a local setting and
+/// is never synced, as it runs with the permissions of the user.
+pub struct SolveCommandRunner {
+    pending: Arc<Mutex<Vec<String>>>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl SolveCommandRunner {
+    pub fn new() -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(Vec::new())),
+            last_error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Registers the solve hook that collects completed solves. Commands are
+    /// run from `update` so that the hook itself stays cheap.
+    pub fn register(&self, history: &mut History) {
+        let pending = self.pending.clone();
+        history.register_solve_hook(move |completion| {
+            pending.lock().unwrap().push(completion.to_json());
+        });
+    }
+
+    /// Runs the configured command for each solve completed since the last
+    /// call. Commands run in the background so that a slow command does not
+    /// stall the timer.
+    pub fn update(&self, history: &History) {
+        let solves: Vec<String> = self.pending.lock().unwrap().drain(..).collect();
+        if solves.len() == 0 {
+            return;
+        }
+        let command = match history.setting_as_string(SOLVE_COMMAND_SETTING) {
+            Some(command) if command.trim().len() != 0 => command,
+            _ => return,
+        };
+
+        let last_error = self.last_error.clone();
+        std::thread::spawn(move || {
+            for solve in solves {
+                let result = Self::run(&command, &solve);
+                *last_error.lock().unwrap() = result.err();
+            }
+        });
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    fn run(command: &str, solve: &str) -> Result<(), String> {
+        #[cfg(target_os = "windows")]
+        let mut process = {
+            let mut process = Command::new("cmd");
+            process.arg("/C").arg(command);
+            process
+        };
+        #[cfg(not(target_os = "windows"))]
+        let mut process = {
+            let mut process = Command::new("sh");
+            process.arg("-c").arg(command);
+            process
+        };
+
+        let mut child = process
+            .env("TPSCUBE_SOLVE", solve)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("Failed to run solve command: {}", error))?;
+
+        // The command may not read its input, so ignore write errors
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(solve.as_bytes());
+            let _ = stdin.write_all(b"\n");
+        }
+
+        let status = child
+            .wait()
+            .map_err(|error| format!("Failed to run solve command: {}", error))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("Solve command failed ({})", status))
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use egui::{CtxRef, Event, Key, PointerButton};
+use std::collections::HashSet;
+use tpscube_core::History;
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+use gilrs::{Button, EventType, Gilrs};
+
+/// Action that can be performed on the timer with a bound input
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimerAction {
+    StartStop,
+    Abort,
+    PlusTwo,
+    DNF,
+    DeleteLast,
+    LargeDisplay,
+}
+
+/// Input that can be bound to a timer action
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputBinding {
+    Key(Key),
+    Mouse(PointerButton),
+    Gamepad(GamepadButton),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    RightTrigger,
+    LeftTrigger2,
+    RightTrigger2,
+    Select,
+    Start,
+}
+
+/// Bindings of inputs to timer actions, stored in the settings
+#[derive(Default)]
+pub struct InputBindings {
+    bindings: Vec<(TimerAction, Vec<InputBinding>)>,
+}
+
+/// State of connected gamepads. Gamepads are only supported when built with
+/// the `gamepad` feature, otherwise no buttons are ever pressed.
+pub struct GamepadInput {
+    #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+    gilrs: Option<Gilrs>,
+    down: HashSet<GamepadButton>,
+    pressed: HashSet<GamepadButton>,
+}
+
+const KEY_NAMES: &[(Key, &str)] = &[
+    (Key::ArrowDown, "Down"),
+    (Key::ArrowLeft, "Left"),
+    (Key::ArrowRight, "Right"),
+    (Key::ArrowUp, "Up"),
+    (Key::Escape, "Escape"),
+    (Key::Tab, "Tab"),
+    (Key::Backspace, "Backspace"),
+    (Key::Enter, "Enter"),
+    (Key::Space, "Space"),
+    (Key::Insert, "Insert"),
+    (Key::Delete, "Delete"),
+    (Key::Home, "Home"),
+    (Key::End, "End"),
+    (Key::PageUp, "PageUp"),
+    (Key::PageDown, "PageDown"),
+    (Key::Num0, "0"),
+    (Key::Num1, "1"),
+    (Key::Num2, "2"),
+    (Key::Num3, "3"),
+    (Key::Num4, "4"),
+    (Key::Num5, "5"),
+    (Key::Num6, "6"),
+    (Key::Num7, "7"),
+    (Key::Num8, "8"),
+    (Key::Num9, "9"),
+    (Key::A, "A"),
+    (Key::B, "B"),
+    (Key::C, "C"),
+    (Key::D, "D"),
+    (Key::E, "E"),
+    (Key::F, "F"),
+    (Key::G, "G"),
+    (Key::H, "H"),
+    (Key::I, "I"),
+    (Key::J, "J"),
+    (Key::K, "K"),
+    (Key::L, "L"),
+    (Key::M, "M"),
+    (Key::N, "N"),
+    (Key::O, "O"),
+    (Key::P, "P"),
+    (Key::Q, "Q"),
+    (Key::R, "R"),
+    (Key::S, "S"),
+    (Key::T, "T"),
+    (Key::U, "U"),
+    (Key::V, "V"),
+    (Key::W, "W"),
+    (Key::X, "X"),
+    (Key::Y, "Y"),
+    (Key::Z, "Z"),
+];
+
+const MOUSE_NAMES: &[(PointerButton, &str)] = &[
+    (PointerButton::Primary, "Left Click"),
+    (PointerButton::Secondary, "Right Click"),
+    (PointerButton::Middle, "Middle Click"),
+];
+
+const GAMEPAD_NAMES: &[(GamepadButton, &str)] = &[
+    (GamepadButton::South, "Pad South"),
+    (GamepadButton::East, "Pad East"),
+    (GamepadButton::North, "Pad North"),
+    (GamepadButton::West, "Pad West"),
+    (GamepadButton::LeftTrigger, "Pad LB"),
+    (GamepadButton::RightTrigger, "Pad RB"),
+    (GamepadButton::LeftTrigger2, "Pad LT"),
+    (GamepadButton::RightTrigger2, "Pad RT"),
+    (GamepadButton::Select, "Pad Select"),
+    (GamepadButton::Start, "Pad Start"),
+];
+
+impl TimerAction {
+    pub const ALL: &'static [TimerAction] = &[
+        TimerAction::StartStop,
+        TimerAction::Abort,
+        TimerAction::PlusTwo,
+        TimerAction::DNF,
+        TimerAction::DeleteLast,
+        TimerAction::LargeDisplay,
+    ];
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            TimerAction::StartStop => "Start / stop",
+            TimerAction::Abort => "Abort solve",
+            TimerAction::PlusTwo => "+2 on last solve",
+            TimerAction::DNF => "DNF last solve",
+            TimerAction::DeleteLast => "Delete last solve",
+            TimerAction::LargeDisplay => "Toggle large display",
+        }
+    }
+
+    fn setting_name(&self) -> &'static str {
+        match self {
+            TimerAction::StartStop => "binding_start_stop",
+            TimerAction::Abort => "binding_abort",
+            TimerAction::PlusTwo => "binding_plus_two",
+            TimerAction::DNF => "binding_dnf",
+            TimerAction::DeleteLast => "binding_delete_last",
+            TimerAction::LargeDisplay => "binding_large_display",
+        }
+    }
+
+    fn default_bindings(&self) -> Vec<InputBinding> {
+        match self {
+            TimerAction::StartStop => vec![InputBinding::Key(Key::Space)],
+            TimerAction::Abort => vec![InputBinding::Key(Key::Escape)],
+            TimerAction::LargeDisplay => vec![InputBinding::Key(Key::L)],
+            TimerAction::PlusTwo | TimerAction::DNF | TimerAction::DeleteLast => Vec::new(),
+        }
+    }
+}
+
+impl InputBinding {
+    pub fn to_string(&self) -> String {
+        match self {
+            InputBinding::Key(key) => KEY_NAMES
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_default(),
+            InputBinding::Mouse(button) => MOUSE_NAMES
+                .iter()
+                .find(|(b, _)| b == button)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_default(),
+            InputBinding::Gamepad(button) => GAMEPAD_NAMES
+                .iter()
+                .find(|(b, _)| b == button)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        if let Some((key, _)) = KEY_NAMES.iter().find(|(_, name)| *name == string) {
+            Some(InputBinding::Key(*key))
+        } else if let Some((button, _)) = MOUSE_NAMES.iter().find(|(_, name)| *name == string) {
+            Some(InputBinding::Mouse(*button))
+        } else if let Some((button, _)) = GAMEPAD_NAMES.iter().find(|(_, name)| *name == string) {
+            Some(InputBinding::Gamepad(*button))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the first newly pressed input in this frame, used when capturing a
+    /// new binding from the user.
+    pub fn capture(ctxt: &CtxRef, gamepad: &GamepadInput) -> Option<Self> {
+        for event in &ctxt.input().events {
+            match event {
+                Event::Key {
+                    key, pressed: true, ..
+                } => return Some(InputBinding::Key(*key)),
+                Event::PointerButton {
+                    button,
+                    pressed: true,
+                    ..
+                } => return Some(InputBinding::Mouse(*button)),
+                _ => (),
+            }
+        }
+        gamepad
+            .pressed
+            .iter()
+            .next()
+            .map(|button| InputBinding::Gamepad(*button))
+    }
+
+    fn is_down(&self, ctxt: &CtxRef, pointer_in_timer: bool, gamepad: &GamepadInput) -> bool {
+        match self {
+            InputBinding::Key(key) => ctxt.input().keys_down.contains(key),
+            InputBinding::Mouse(button) => {
+                pointer_in_timer && ctxt.input().pointer.button_down(*button)
+            }
+            InputBinding::Gamepad(button) => gamepad.down.contains(button),
+        }
+    }
+
+    fn is_pressed(&self, ctxt: &CtxRef, pointer_in_timer: bool, gamepad: &GamepadInput) -> bool {
+        match self {
+            InputBinding::Key(key) => ctxt.input().events.iter().any(|event| match event {
+                Event::Key {
+                    key: event_key,
+                    pressed: true,
+                    ..
+                } => event_key == key,
+                _ => false,
+            }),
+            InputBinding::Mouse(button) => {
+                pointer_in_timer
+                    && ctxt.input().events.iter().any(|event| match event {
+                        Event::PointerButton {
+                            button: event_button,
+                            pressed: true,
+                            ..
+                        } => event_button == button,
+                        _ => false,
+                    })
+            }
+            InputBinding::Gamepad(button) => gamepad.pressed.contains(button),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn load(history: &History) -> Self {
+        let bindings = TimerAction::ALL
+            .iter()
+            .map(|action| {
+                let bindings = match history.setting_as_string(action.setting_name()) {
+                    Some(string) => string
+                        .split(',')
+                        .filter_map(|name| InputBinding::from_str(name))
+                        .collect(),
+                    None => action.default_bindings(),
+                };
+                (*action, bindings)
+            })
+            .collect();
+        Self { bindings }
+    }
+
+    pub fn bindings(&self, action: TimerAction) -> &[InputBinding] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, bindings)| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn set_bindings(history: &mut History, action: TimerAction, bindings: &[InputBinding]) {
+        let names: Vec<String> = bindings.iter().map(|binding| binding.to_string()).collect();
+        let _ = history.set_string_setting(action.setting_name(), &names.join(","));
+    }
+
+    pub fn reset(history: &mut History) {
+        for action in TimerAction::ALL {
+            Self::set_bindings(history, *action, &action.default_bindings());
+        }
+    }
+
+    /// Checks if any input bound to the action is currently held down. Mouse
+    /// bindings only apply while the pointer is over the timer.
+    pub fn is_down(
+        &self,
+        action: TimerAction,
+        ctxt: &CtxRef,
+        pointer_in_timer: bool,
+        gamepad: &GamepadInput,
+    ) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.is_down(ctxt, pointer_in_timer, gamepad))
+    }
+
+    /// Checks if any input bound to the action was pressed in this frame
+    pub fn is_pressed(
+        &self,
+        action: TimerAction,
+        ctxt: &CtxRef,
+        pointer_in_timer: bool,
+        gamepad: &GamepadInput,
+    ) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.is_pressed(ctxt, pointer_in_timer, gamepad))
+    }
+}
+
+impl GamepadInput {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+            gilrs: Gilrs::new().ok(),
+            down: HashSet::new(),
+            pressed: HashSet::new(),
+        }
+    }
+
+    /// Processes pending gamepad events. This should be called once per frame.
+    pub fn update(&mut self) {
+        self.pressed.clear();
+
+        #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+        if let Some(gilrs) = &mut self.gilrs {
+            while let Some(event) = gilrs.next_event() {
+                match event.event {
+                    EventType::ButtonPressed(button, _) => {
+                        if let Some(button) = GamepadButton::from_gilrs(button) {
+                            self.down.insert(button);
+                            self.pressed.insert(button);
+                        }
+                    }
+                    EventType::ButtonReleased(button, _) => {
+                        if let Some(button) = GamepadButton::from_gilrs(button) {
+                            self.down.remove(&button);
+                        }
+                    }
+                    EventType::Disconnected => self.down.clear(),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    pub fn any_down(&self) -> bool {
+        self.down.len() != 0
+    }
+
+    pub fn connected(&self) -> bool {
+        #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+        if let Some(gilrs) = &self.gilrs {
+            return gilrs.gamepads().next().is_some();
+        }
+        false
+    }
+}
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+impl GamepadButton {
+    fn from_gilrs(button: Button) -> Option<Self> {
+        match button {
+            Button::South => Some(GamepadButton::South),
+            Button::East => Some(GamepadButton::East),
+            Button::North => Some(GamepadButton::North),
+            Button::West => Some(GamepadButton::West),
+            Button::LeftTrigger => Some(GamepadButton::LeftTrigger),
+            Button::RightTrigger => Some(GamepadButton::RightTrigger),
+            Button::LeftTrigger2 => Some(GamepadButton::LeftTrigger2),
+            Button::RightTrigger2 => Some(GamepadButton::RightTrigger2),
+            Button::Select => Some(GamepadButton::Select),
+            Button::Start => Some(GamepadButton::Start),
+            _ => None,
+        }
+    }
+}
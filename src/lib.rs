@@ -1,16 +1,19 @@
 mod algorithms;
 pub mod app;
+mod calibration;
 mod center_generated;
 mod corner_generated;
 mod cube;
 mod details;
 mod edge_generated;
+mod editor;
 mod font;
 mod framerate;
 mod future;
 mod gl;
 mod graph;
 mod history;
+mod input;
 mod mode;
 mod settings;
 mod style;
@@ -21,6 +24,18 @@ mod widgets;
 #[cfg(not(target_arch = "wasm32"))]
 mod bluetooth;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod feedback;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod hooks;
+
+#[cfg(all(feature = "audio-trigger", not(target_arch = "wasm32")))]
+mod audio;
+
+#[cfg(all(feature = "speech", not(target_arch = "wasm32")))]
+mod speech;
+
 // This code is from backend.rs and lib.rs in egui_web, but modified to allow for
 // rendering of 3D elements.
 
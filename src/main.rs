@@ -1,16 +1,19 @@
 mod algorithms;
 mod app;
+mod calibration;
 mod center_generated;
 mod corner_generated;
 mod cube;
 mod details;
 mod edge_generated;
+mod editor;
 mod font;
 mod framerate;
 mod future;
 mod gl;
 mod graph;
 mod history;
+mod input;
 mod mode;
 mod settings;
 mod style;
@@ -21,6 +24,18 @@ mod widgets;
 #[cfg(not(target_arch = "wasm32"))]
 mod bluetooth;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod feedback;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod hooks;
+
+#[cfg(all(feature = "audio-trigger", not(target_arch = "wasm32")))]
+mod audio;
+
+#[cfg(all(feature = "speech", not(target_arch = "wasm32")))]
+mod speech;
+
 // This code is from backend.rs in egui_glium, but modified to allow for rendering
 // of 3D elements.
 
@@ -29,7 +44,9 @@ use gl::GlContext;
 use glium::glutin;
 #[cfg(target_os = "windows")]
 use glium::glutin::platform::windows::WindowBuilderExtWindows;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Instant;
+use tpscube_core::{ApiServer, History, API_SERVER_PORT};
 
 struct RequestRepaintEvent;
 
@@ -302,8 +319,52 @@ pub fn run(
     });
 }
 
+/// Address to serve the HTTP API on when started with `--serve [ADDRESS]`.
+/// The API is only served on the loopback address unless another address is
+/// given, as it has no authentication.
+fn serve_address() -> Option<Result<SocketAddr, String>> {
+    let args: Vec<String> = std::env::args().collect();
+    let idx = args.iter().position(|arg| arg == "--serve")?;
+    Some(
+        match args.get(idx + 1).filter(|arg| !arg.starts_with("--")) {
+            Some(address) => address
+                .parse()
+                .map_err(|_| format!("Invalid address to serve on: {}", address)),
+            None => Ok(SocketAddr::new(
+                IpAddr::from([127, 0, 0, 1]),
+                API_SERVER_PORT,
+            )),
+        },
+    )
+}
+
+/// Runs without the user interface, serving the solve history over HTTP
+async fn serve(address: SocketAddr) -> Result<(), String> {
+    let mut history = History::open()
+        .await
+        .map_err(|error| format!("Unable to open the solve database: {}", error))?;
+    let server = ApiServer::bind(address).map_err(|error| error.to_string())?;
+    println!(
+        "Serving API on http://{}",
+        server.local_address().map_err(|error| error.to_string())?
+    );
+    server.run(&mut history).map_err(|error| error.to_string())
+}
+
 #[tokio::main]
 async fn main() {
+    if let Some(address) = serve_address() {
+        let result = match address {
+            Ok(address) => serve(address).await,
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            eprintln!("Error: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Initialize SDL2 just for stopping the screensaver. There are no other crates for this and
     // its a giant pile of platform dependent code.
     let sdl_context = sdl2::init().unwrap();
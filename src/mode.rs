@@ -1,7 +1,7 @@
 use crate::theme::Theme;
 use crate::widgets::CustomWidgets;
 use egui::{CtxRef, Label, Sense, Ui, Window};
-use tpscube_core::SolveType;
+use tpscube_core::{History, SolveType};
 
 pub struct SolveTypeSelectWindow {
     solve_type: SolveType,
@@ -15,10 +15,16 @@ impl SolveTypeSelectWindow {
     fn option(
         &self,
         ui: &mut Ui,
+        history: &History,
         selected: &mut Option<SolveType>,
         solve_type: SolveType,
         name: &str,
     ) {
+        // Show the size of the session that will be resumed for this puzzle
+        let name = match history.solve_type_session(solve_type) {
+            Some(session) => format!("{}  ({} solves)", name, session.len()),
+            None => name.into(),
+        };
         let mut label = Label::new(name).sense(Sense::click());
         if self.solve_type == solve_type {
             label = label.text_color(Theme::Green);
@@ -28,7 +34,13 @@ impl SolveTypeSelectWindow {
         }
     }
 
-    pub fn update(&self, ctxt: &CtxRef, open: &mut bool, selected: &mut Option<SolveType>) {
+    pub fn update(
+        &self,
+        ctxt: &CtxRef,
+        history: &History,
+        open: &mut bool,
+        selected: &mut Option<SolveType>,
+    ) {
         Window::new("Select Puzzle")
             .collapsible(false)
             .resizable(false)
@@ -37,12 +49,39 @@ impl SolveTypeSelectWindow {
             .show(ctxt, |ui| {
                 ui.vertical(|ui| {
                     ui.section("Standard Cubes");
-                    self.option(ui, selected, SolveType::Standard2x2x2, "2x2x2");
-                    self.option(ui, selected, SolveType::Standard3x3x3, "3x3x3");
-                    self.option(ui, selected, SolveType::OneHanded3x3x3, "3x3x3 One Handed");
+                    self.option(ui, history, selected, SolveType::Standard2x2x2, "2x2x2");
+                    self.option(ui, history, selected, SolveType::Standard3x3x3, "3x3x3");
+                    self.option(
+                        ui,
+                        history,
+                        selected,
+                        SolveType::OneHanded3x3x3,
+                        "3x3x3 One Handed",
+                    );
+
+                    ui.section("Big Cubes");
+                    self.option(ui, history, selected, SolveType::Standard5x5x5, "5x5x5");
+                    self.option(ui, history, selected, SolveType::Standard6x6x6, "6x6x6");
+                    self.option(ui, history, selected, SolveType::Standard7x7x7, "7x7x7");
+
+                    ui.section("Variants");
+                    self.option(ui, history, selected, SolveType::Void3x3x3, "3x3x3 Void");
+                    self.option(
+                        ui,
+                        history,
+                        selected,
+                        SolveType::Bandaged3x3x3,
+                        "3x3x3 Bandaged",
+                    );
 
                     ui.section("Blindfolded");
-                    self.option(ui, selected, SolveType::Blind3x3x3, "3x3x3 Blindfolded");
+                    self.option(
+                        ui,
+                        history,
+                        selected,
+                        SolveType::Blind3x3x3,
+                        "3x3x3 Blindfolded",
+                    );
                 });
             });
     }
@@ -1,13 +1,49 @@
+use crate::calibration::TimerCalibration;
 use crate::font::FontSize;
+use crate::input::{GamepadInput, InputBinding, InputBindings, TimerAction};
 use crate::style::settings_visuals;
 use crate::theme::Theme;
 use crate::widgets::CustomWidgets;
 use anyhow::Result;
+use chrono::{Local, TimeZone};
 use egui::{
     containers::ScrollArea, popup_below_widget, widgets::Label, CentralPanel, CtxRef,
-    SelectableLabel, Sense, Stroke,
+    SelectableLabel, Sense, Stroke, Ui,
 };
-use tpscube_core::{History, SyncRequest};
+use tpscube_core::{
+    AccentColor, Account, AccountOperation, ColorScheme, History, Preference, PrimeNotation,
+    ScrambleFontSize, ScrambleOrientation, SolveType, SolvingTimeDisplay, StorageCategory,
+    StorageUsage, SyncRequest, TimeRounding, WideMoveNotation, DAILY_SOLVE_GOAL_CHOICES,
+    HOLD_DURATION_CHOICES,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::feedback::{MoveFeedbackStyle, MoveFeedbackType};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::future::spawn_future;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::hooks::{SolveCommandRunner, SOLVE_COMMAND_SETTING};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use tpscube_core::{HistoryImport, LanSyncBrowser, LanSyncHost, MergeSource};
+
+#[cfg(feature = "diagnostics")]
+use tpscube_core::{install_diagnostic_log, DiagnosticLevel, DiagnosticLog};
+
+/// Number of diagnostic events kept for the diagnostic log view
+#[cfg(feature = "diagnostics")]
+const DIAGNOSTIC_LOG_SIZE: usize = 2000;
+
+/// Number of solves merged per frame while importing
+#[cfg(not(target_arch = "wasm32"))]
+const IMPORT_CHUNK_SIZE: usize = 250;
+
+/// Largest smart timer start latency that can be set, in milliseconds
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_TIMER_START_LATENCY: i64 = 500;
+#[cfg(not(target_arch = "wasm32"))]
+const TIMER_START_LATENCY_STEP: i64 = 10;
 
 pub struct Settings {
     sync_key_visible: bool,
@@ -15,7 +51,37 @@ pub struct Settings {
     new_sync_key: String,
     organize_result: Option<String>,
     import_result: Option<Result<String>>,
+    official_import_result: Option<Result<String>>,
     export_result: Option<Result<()>>,
+    storage_usage: Option<StorageUsage>,
+    compact_result: Option<Result<usize>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    import_source: Option<Arc<Mutex<Option<Result<HistoryImport>>>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    import: Option<HistoryImport>,
+    #[cfg(not(target_arch = "wasm32"))]
+    merge_source: Option<Arc<Mutex<Option<tpscube_core::Result<MergeSource>>>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    merge_result: Option<Result<String>>,
+    capturing_binding: Option<TimerAction>,
+    calibration: TimerCalibration,
+    account: Option<Account>,
+    confirm_wipe: bool,
+    account_status: Option<&'static str>,
+    #[cfg(not(target_arch = "wasm32"))]
+    lan_sync_host: Option<LanSyncHost>,
+    #[cfg(not(target_arch = "wasm32"))]
+    lan_sync_browser: Option<LanSyncBrowser>,
+    #[cfg(not(target_arch = "wasm32"))]
+    lan_sync_error: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    solve_commands: SolveCommandRunner,
+    #[cfg(not(target_arch = "wasm32"))]
+    solve_command_edit: Option<String>,
+    #[cfg(feature = "diagnostics")]
+    diagnostic_log: Option<DiagnosticLog>,
+    #[cfg(feature = "diagnostics")]
+    diagnostic_level: DiagnosticLevel,
 }
 
 impl Settings {
@@ -26,7 +92,37 @@ impl Settings {
             new_sync_key: "".into(),
             organize_result: None,
             import_result: None,
+            official_import_result: None,
             export_result: None,
+            storage_usage: None,
+            compact_result: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            import_source: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            import: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            merge_source: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            merge_result: None,
+            capturing_binding: None,
+            calibration: TimerCalibration::new(),
+            account: None,
+            confirm_wipe: false,
+            account_status: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            lan_sync_host: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            lan_sync_browser: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            lan_sync_error: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            solve_commands: SolveCommandRunner::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            solve_command_edit: None,
+            #[cfg(feature = "diagnostics")]
+            diagnostic_log: install_diagnostic_log(DIAGNOSTIC_LOG_SIZE, DiagnosticLevel::Trace),
+            #[cfg(feature = "diagnostics")]
+            diagnostic_level: DiagnosticLevel::Info,
         }
     }
 
@@ -38,20 +134,376 @@ impl Settings {
         history.setting_as_i64("auto_session_time").unwrap_or(3600)
     }
 
+    pub fn exclude_warmup_enabled(history: &History) -> bool {
+        history.setting_as_bool("exclude_warmup").unwrap_or(false)
+    }
+
+    pub fn audio_trigger_enabled(history: &History) -> bool {
+        history.setting_as_bool("audio_trigger").unwrap_or(false)
+    }
+
+    pub fn orientation_arming_enabled(history: &History) -> bool {
+        history
+            .setting_as_bool("orientation_arming")
+            .unwrap_or(true)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn move_feedback(history: &History, move_type: MoveFeedbackType) -> MoveFeedbackStyle {
+        history
+            .setting_as_string(move_type.setting_name())
+            .and_then(|style| MoveFeedbackStyle::from_str(&style))
+            .unwrap_or(MoveFeedbackStyle::Off)
+    }
+
+    /// Returns true if this device hosts LAN sync for the other devices
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn lan_sync_host_enabled(history: &History) -> bool {
+        history.setting_as_bool("lan_sync_host").unwrap_or(false)
+    }
+
+    /// Registers the hook that runs the solve command, if one is configured.
+    /// Call once after the history is loaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn register_solve_command(&self, history: &mut History) {
+        self.solve_commands.register(history);
+    }
+
+    /// Runs the solve command for solves completed since the last frame
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run_solve_commands(&self, history: &History) {
+        self.solve_commands.update(history);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn solve_command(&mut self, ui: &mut Ui, history: &mut History) {
+        let saved = history
+            .setting_as_string(SOLVE_COMMAND_SETTING)
+            .unwrap_or_default();
+        let edit = self.solve_command_edit.get_or_insert_with(|| saved.clone());
+
+        ui.add(Label::new("Command to run after each solve:").text_color(Theme::Yellow));
+        ui.style_mut().visuals.widgets.inactive.bg_stroke = Stroke {
+            width: 1.0,
+            color: Theme::Disabled.into(),
+        };
+        ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke {
+            width: 1.0,
+            color: Theme::Disabled.into(),
+        };
+        ui.style_mut().visuals.widgets.active.bg_stroke = Stroke {
+            width: 1.0,
+            color: Theme::Content.into(),
+        };
+        ui.text_edit_singleline(edit);
+
+        if *edit != saved {
+            if ui
+                .add(
+                    Label::new("✔  Save")
+                        .text_style(FontSize::Section.into())
+                        .sense(Sense::click()),
+                )
+                .clicked()
+            {
+                let _ = history.set_string_setting(SOLVE_COMMAND_SETTING, edit.trim());
+                self.solve_command_edit = None;
+            }
+        } else if let Some(error) = self.solve_commands.last_error() {
+            ui.add(Label::new(error).wrap(true).text_color(Theme::Red));
+        }
+
+        ui.add(
+            Label::new(
+                "Runs a shell command after each solve, such as a script that updates \
+                a stream overlay or a spreadsheet. The solve is passed as JSON on \
+                standard input and in the TPSCUBE_SOLVE environment variable. The \
+                command is only stored on this device.",
+            )
+            .wrap(true),
+        );
+    }
+
+    /// Starts or stops hosting LAN sync to match the setting. This is called
+    /// every frame so that the host keeps running while other pages are shown.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn update_lan_sync_host(&mut self, history: &mut History) {
+        // Guest sessions must not share their solves with other devices
+        if !Self::lan_sync_host_enabled(history) || history.is_in_memory() {
+            if self.lan_sync_host.take().is_some() {
+                history.set_lan_sync_peer(None);
+            }
+            return;
+        }
+        if self.lan_sync_error.is_some() {
+            return;
+        }
+        if let Some(host) = &mut self.lan_sync_host {
+            // Devices are synced by sync key, so the host follows changes to it
+            if host.sync_key() != history.sync_key() {
+                if let Err(error) = host.set_sync_key(history.sync_key()) {
+                    self.lan_sync_host = None;
+                    self.lan_sync_error = Some(error.to_string());
+                }
+            }
+            return;
+        }
+        match LanSyncHost::start(history.sync_key(), &device_name()) {
+            Ok(host) => {
+                self.lan_sync_host = Some(host);
+                history.set_lan_sync_peer(Some(LanSyncHost::local_address()));
+            }
+            Err(error) => self.lan_sync_error = Some(error.to_string()),
+        }
+    }
+
+    /// Offset in milliseconds added to times recorded with the keyboard timer
+    pub fn timer_offset(history: &History) -> i64 {
+        history.setting_as_i64("timer_offset").unwrap_or(0)
+    }
+
+    /// Latency in milliseconds between a smart timer starting and the start
+    /// being reported, applied to smart timers when they connect
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn timer_start_latency(history: &History) -> u32 {
+        history
+            .setting_as_i64("timer_start_latency")
+            .unwrap_or(0)
+            .max(0)
+            .min(MAX_TIMER_START_LATENCY) as u32
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn timer_start_latency_preference(ui: &mut Ui, history: &mut History) {
+        let latency = Self::timer_start_latency(history) as i64;
+        ui.add(
+            Label::new(format!("⏲  Smart Timer Latency: {} ms", latency))
+                .text_style(FontSize::Section.into()),
+        );
+        let new_latency = ui
+            .horizontal(|ui| {
+                if ui
+                    .add(Label::new("➖").sense(Sense::click()))
+                    .on_hover_text(format!("-{} ms", TIMER_START_LATENCY_STEP))
+                    .clicked()
+                {
+                    return Some(latency - TIMER_START_LATENCY_STEP);
+                }
+                if ui
+                    .add(Label::new("➕").sense(Sense::click()))
+                    .on_hover_text(format!("+{} ms", TIMER_START_LATENCY_STEP))
+                    .clicked()
+                {
+                    return Some(latency + TIMER_START_LATENCY_STEP);
+                }
+                if ui.add(Label::new("Reset").sense(Sense::click())).clicked() {
+                    return Some(0);
+                }
+                None
+            })
+            .inner;
+        if let Some(new_latency) = new_latency {
+            let _ = history.set_i64_setting(
+                "timer_start_latency",
+                new_latency.max(0).min(MAX_TIMER_START_LATENCY),
+            );
+        }
+        ui.add(
+            Label::new(
+                "Added to the running time shown while a smart timer is running, to make up \
+                    for the delay in receiving the start from the timer. Applied the next time \
+                    a smart timer connects. Final times come from the timer and are not changed.",
+            )
+            .wrap(true),
+        );
+    }
+
+    pub fn speak_scramble_enabled(history: &History) -> bool {
+        history.setting_as_bool("speak_scramble").unwrap_or(false)
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
-    fn import_solves_from_path(path: &str, history: &mut History) -> Result<String> {
+    fn parse_import(path: &str, dry_run: bool) -> Result<HistoryImport> {
         let contents = String::from_utf8(std::fs::read(path)?)?;
-        history.import(contents)
+        HistoryImport::new(contents, dry_run).map_err(|error| match error {
+            tpscube_core::Error::Parse(_) => {
+                anyhow::anyhow!("File is not a supported solve backup ({})", error)
+            }
+            _ => error.into(),
+        })
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    fn import_solves(&mut self, history: &mut History) {
+    fn import_solves(&mut self, dry_run: bool) {
         if let Some(path) = tinyfiledialogs::open_file_dialog(
             "Import Solves",
             ".",
             Some((&["*.json", "*.csv", "*.txt"], "Solve backups")),
         ) {
-            self.import_result = Some(Self::import_solves_from_path(&path, history));
+            // Parsing a large backup can take a while, so do it in the
+            // background and merge the solves in chunks once it is parsed
+            let source = Arc::new(Mutex::new(None));
+            let source_copy = source.clone();
+            spawn_future(async move {
+                *source_copy.lock().unwrap() = Some(Self::parse_import(&path, dry_run));
+            });
+            self.import_source = Some(source);
+            self.import_result = None;
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn check_import(&mut self, history: &mut History) {
+        let parsed = match &self.import_source {
+            Some(source) => source.lock().unwrap().take(),
+            None => None,
+        };
+        match parsed {
+            Some(Ok(import)) => {
+                self.import = Some(import);
+                self.import_source = None;
+            }
+            Some(Err(error)) => {
+                self.import_result = Some(Err(error));
+                self.import_source = None;
+            }
+            None => (),
+        }
+
+        if let Some(import) = &mut self.import {
+            if history.import_chunk(import, IMPORT_CHUNK_SIZE) {
+                self.import_result = Some(Ok(if import.is_dry_run() {
+                    format!("Import preview.\n{}", import.summary())
+                } else {
+                    format!("Import complete.\n{}", import.summary())
+                }));
+                self.import = None;
+            }
+        }
+    }
+
+    fn storage(&mut self, ui: &mut Ui, history: &mut History) {
+        if ui
+            .add(
+                Label::new("📊  Show database size")
+                    .text_style(FontSize::Section.into())
+                    .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            self.storage_usage = Some(history.storage_usage());
+        }
+        if let Some(usage) = &self.storage_usage {
+            let category = |ui: &mut Ui, name: &str, category: &StorageCategory| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}:", name));
+                    ui.add(
+                        Label::new(format!(
+                            "{} ({} actions)",
+                            byte_count_string(category.bytes),
+                            category.actions
+                        ))
+                        .text_color(Theme::Disabled),
+                    );
+                });
+            };
+            category(ui, "Solves", &usage.solves);
+            category(ui, "Solve changes", &usage.solve_edits);
+            category(ui, "Sessions", &usage.sessions);
+            category(ui, "Preferences", &usage.preferences);
+            category(ui, "Total", &usage.total());
+            category(ui, "Removable by compacting", &usage.superseded);
+        }
+
+        if ui
+            .add(
+                Label::new("🧹  Compact solve database")
+                    .text_style(FontSize::Section.into())
+                    .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            self.compact_result = Some(history.compact_storage().map_err(anyhow::Error::from));
+            if self.storage_usage.is_some() {
+                self.storage_usage = Some(history.storage_usage());
+            }
+        }
+        if let Some(result) = &self.compact_result {
+            match result {
+                Ok(0) => {
+                    ui.add(Label::new("Nothing to compact").text_color(Theme::Green));
+                }
+                Ok(count) => {
+                    ui.add(
+                        Label::new(format!("Removed {} superseded actions", count))
+                            .text_color(Theme::Green),
+                    );
+                }
+                Err(error) => {
+                    ui.add(
+                        Label::new(format!("Error: {}", error))
+                            .wrap(true)
+                            .text_color(Theme::Red),
+                    );
+                }
+            }
+        }
+        ui.add(
+            Label::new(
+                "Compacting removes changes that no longer affect your history, such \
+                as deleted solves and earlier values of settings. Your solves and \
+                statistics are not changed.",
+            )
+            .wrap(true),
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_official_results(&mut self, history: &mut History) {
+        if let Some(path) = tinyfiledialogs::open_file_dialog(
+            "Import WCA Results",
+            ".",
+            Some((&["*.json"], "WCA results")),
+        ) {
+            self.official_import_result = Some(
+                std::fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|contents| Ok(history.import_official_results(&contents)?)),
+            );
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn merge_database(&mut self) {
+        if let Some(path) = tinyfiledialogs::select_folder_dialog("Merge Solve Database", ".") {
+            // Reading the other database can take a while, so do it in the
+            // background and merge once it is loaded
+            let source = Arc::new(Mutex::new(None));
+            let source_copy = source.clone();
+            spawn_future(async move {
+                *source_copy.lock().unwrap() = Some(MergeSource::open(path).await);
+            });
+            self.merge_source = Some(source);
+            self.merge_result = None;
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn check_merge(&mut self, history: &mut History) {
+        let source = match &self.merge_source {
+            Some(source) => source.lock().unwrap().take(),
+            None => return,
+        };
+        match source {
+            Some(Ok(source)) => {
+                self.merge_result = Some(Ok(history.merge_database(source)));
+                self.merge_source = None;
+            }
+            Some(Err(error)) => {
+                self.merge_result = Some(Err(error.into()));
+                self.merge_source = None;
+            }
+            None => (),
         }
     }
 
@@ -73,7 +525,1104 @@ impl Settings {
         }
     }
 
-    pub fn update(&mut self, ctxt: &CtxRef, _frame: &mut epi::Frame<'_>, history: &mut History) {
+    fn input_bindings(&mut self, ui: &mut Ui, history: &mut History, gamepad: &GamepadInput) {
+        // If waiting for an input to bind, take the next input the user presses
+        if let Some(action) = self.capturing_binding {
+            if let Some(binding) = InputBinding::capture(ui.ctx(), gamepad) {
+                let mut bindings = InputBindings::load(history).bindings(action).to_vec();
+                if !bindings.contains(&binding) {
+                    bindings.push(binding);
+                }
+                InputBindings::set_bindings(history, action, &bindings);
+                self.capturing_binding = None;
+            }
+        }
+
+        let bindings = InputBindings::load(history);
+        for action in TimerAction::ALL {
+            ui.horizontal(|ui| {
+                ui.add(
+                    Label::new(action.to_str())
+                        .text_style(FontSize::Section.into())
+                        .text_color(Theme::Content),
+                );
+
+                let names: Vec<String> = bindings
+                    .bindings(*action)
+                    .iter()
+                    .map(|binding| binding.to_string())
+                    .collect();
+                ui.add(Label::new(if names.len() == 0 {
+                    "(None)".to_string()
+                } else {
+                    names.join(", ")
+                }));
+
+                if self.capturing_binding == Some(*action) {
+                    ui.add(Label::new("Press an input...").text_color(Theme::Yellow));
+                } else {
+                    if ui
+                        .add(Label::new("➕  Add").sense(Sense::click()))
+                        .clicked()
+                    {
+                        self.capturing_binding = Some(*action);
+                    }
+                    if names.len() != 0
+                        && ui
+                            .add(Label::new("✖  Clear").sense(Sense::click()))
+                            .clicked()
+                    {
+                        InputBindings::set_bindings(history, *action, &[]);
+                    }
+                }
+            });
+        }
+
+        ui.add_space(8.0);
+        if ui
+            .add(
+                Label::new("↺  Reset to defaults")
+                    .text_style(FontSize::Section.into())
+                    .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            InputBindings::reset(history);
+            self.capturing_binding = None;
+        }
+        ui.add(
+            Label::new(
+                "Choose which keys, mouse buttons, or gamepad buttons control the timer. \
+                    Mouse buttons only apply when clicking in the timer area.",
+            )
+            .wrap(true),
+        );
+    }
+
+    fn timer_preferences(&mut self, ui: &mut Ui, history: &mut History) {
+        let hold_duration = history.preferences().hold_duration();
+        let popup_id = ui.make_persistent_id("hold-duration");
+        let response = ui.add(
+            Label::new(format!(
+                "⏱  Hold Time: {} ⏷",
+                hold_duration_string(hold_duration)
+            ))
+            .text_style(FontSize::Section.into())
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            for duration in &HOLD_DURATION_CHOICES {
+                if ui
+                    .add(
+                        SelectableLabel::new(
+                            hold_duration == *duration,
+                            hold_duration_string(*duration),
+                        )
+                        .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::HoldDuration(*duration));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+        ui.add(
+            Label::new(
+                "How long the start control must be held before the timer is ready to start.",
+            )
+            .wrap(true),
+        );
+
+        ui.add_space(8.0);
+
+        let time_rounding = history.preferences().time_rounding();
+        let popup_id = ui.make_persistent_id("time-rounding");
+        let response = ui.add(
+            Label::new(format!("🔢  Time Display: {} ⏷", time_rounding.name()))
+                .text_style(FontSize::Section.into())
+                .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            for rounding in TimeRounding::ALL {
+                if ui
+                    .add(
+                        SelectableLabel::new(time_rounding == *rounding, rounding.name())
+                            .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::TimeRounding(*rounding));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+        ui.add(
+            Label::new(
+                "How times are shown. Times are always recorded to the millisecond. WCA \
+                    regulations truncate times to the hundredth of a second.",
+            )
+            .wrap(true),
+        );
+
+        ui.add_space(8.0);
+
+        let solving_display = history.preferences().solving_time_display();
+        let popup_id = ui.make_persistent_id("solving-time-display");
+        let response = ui.add(
+            Label::new(format!(
+                "⏱  Time While Solving: {} ⏷",
+                solving_display.name()
+            ))
+            .text_style(FontSize::Section.into())
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            for display in SolvingTimeDisplay::ALL {
+                if ui
+                    .add(
+                        SelectableLabel::new(solving_display == *display, display.name())
+                            .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::SolvingTimeDisplay(*display));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+        ui.add(
+            Label::new(
+                "How much of the running time is shown during a solve. Hiding the time can \
+                    make it less distracting. The full time is shown once the solve is complete.",
+            )
+            .wrap(true),
+        );
+
+        ui.add_space(8.0);
+
+        let inspection = history.preferences().inspection();
+        if ui
+            .add(
+                Label::new(format!(
+                    "{}  Inspection",
+                    if inspection { "☑" } else { "☐" }
+                ))
+                .text_style(FontSize::Section.into())
+                .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            history.set_preference(Preference::Inspection(!inspection));
+            let _ = history.local_commit();
+        }
+        ui.add(
+            Label::new(
+                "Count down 15 seconds of inspection before each solve. Starting the solve late \
+                    adds a +2 penalty, or a DNF if more than 17 seconds have passed.",
+            )
+            .wrap(true),
+        );
+
+        ui.add_space(8.0);
+
+        let daily_goal = history.preferences().daily_solve_goal();
+        let popup_id = ui.make_persistent_id("daily-solve-goal");
+        let response = ui.add(
+            Label::new(format!(
+                "🔥  Daily Goal: {} ⏷",
+                daily_solve_goal_string(daily_goal)
+            ))
+            .text_style(FontSize::Section.into())
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            for goal in &DAILY_SOLVE_GOAL_CHOICES {
+                if ui
+                    .add(
+                        SelectableLabel::new(daily_goal == *goal, daily_solve_goal_string(*goal))
+                            .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::DailySolveGoal(*goal));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+        ui.add(
+            Label::new(
+                "Number of solves per day that keeps your practice streak going. You are \
+                    reminded in the evening if your streak is at risk.",
+            )
+            .wrap(true),
+        );
+    }
+
+    #[cfg(all(feature = "move-sound", not(target_arch = "wasm32")))]
+    fn move_feedback_preferences(&mut self, ui: &mut Ui, history: &mut History) {
+        for move_type in MoveFeedbackType::ALL {
+            let style = Self::move_feedback(history, *move_type);
+            let popup_id = ui.make_persistent_id(move_type.setting_name());
+            let response = ui.add(
+                Label::new(format!(
+                    "🔊  {} Feedback: {} ⏷",
+                    move_type.name(),
+                    style.name()
+                ))
+                .text_style(FontSize::Section.into())
+                .sense(Sense::click()),
+            );
+            if response.clicked() {
+                ui.memory().toggle_popup(popup_id);
+            }
+            popup_below_widget(ui, popup_id, &response, |ui| {
+                ui.set_min_width(180.0);
+                for choice in MoveFeedbackStyle::ALL {
+                    if ui
+                        .add(
+                            SelectableLabel::new(style == *choice, choice.name())
+                                .text_style(FontSize::Normal.into()),
+                        )
+                        .clicked()
+                    {
+                        let _ =
+                            history.set_string_setting(move_type.setting_name(), choice.to_str());
+                    }
+                }
+            });
+        }
+        ui.add(
+            Label::new(
+                "Play a tick for each smart cube move. Moves in quick succession only tick \
+                    once. This setting is not synced to other devices.",
+            )
+            .wrap(true),
+        );
+    }
+
+    fn display_preferences(&mut self, ui: &mut Ui, history: &mut History) {
+        let color_scheme = history.preferences().color_scheme();
+        let popup_id = ui.make_persistent_id("color-scheme");
+        let response = ui.add(
+            Label::new(format!("🎨  Color Scheme: {} ⏷", color_scheme.name()))
+                .text_style(FontSize::Section.into())
+                .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            for scheme in ColorScheme::ALL {
+                if ui
+                    .add(
+                        SelectableLabel::new(color_scheme == *scheme, scheme.name())
+                            .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::ColorScheme(*scheme));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+
+        ui.add_space(8.0);
+
+        let accent_color = history.preferences().accent_color();
+        ui.horizontal_wrapped(|ui| {
+            ui.add(
+                Label::new("Accent color:")
+                    .text_style(FontSize::Section.into())
+                    .text_color(Theme::Content),
+            );
+            for color in AccentColor::ALL {
+                let response = ui
+                    .add(
+                        Label::new(if accent_color == *color { "⏺" } else { "⚫" })
+                            .text_style(FontSize::Section.into())
+                            .text_color(Theme::accent(*color))
+                            .sense(Sense::click()),
+                    )
+                    .on_hover_text(color.name());
+                if response.clicked() {
+                    history.set_preference(Preference::AccentColor(*color));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+
+        ui.add_space(8.0);
+
+        let scramble_font_size = history.preferences().scramble_font_size();
+        let popup_id = ui.make_persistent_id("scramble-font-size");
+        let response = ui.add(
+            Label::new(format!(
+                "🔠  Scramble Size: {} ⏷",
+                scramble_font_size.name()
+            ))
+            .text_style(FontSize::Section.into())
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            for size in &[
+                ScrambleFontSize::Small,
+                ScrambleFontSize::Medium,
+                ScrambleFontSize::Large,
+                ScrambleFontSize::ExtraLarge,
+            ] {
+                if ui
+                    .add(
+                        SelectableLabel::new(scramble_font_size == *size, size.name())
+                            .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::ScrambleFontSize(*size));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+
+        ui.add_space(8.0);
+
+        let scramble_colors = history.preferences().scramble_colors();
+        if ui
+            .add(
+                Label::new(format!(
+                    "{}  Color-coded scramble",
+                    if scramble_colors { "☑" } else { "☐" }
+                ))
+                .text_style(FontSize::Section.into())
+                .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            history.set_preference(Preference::ScrambleColors(!scramble_colors));
+            let _ = history.local_commit();
+        }
+        ui.add(
+            Label::new(
+                "Color each move of the scramble with the color of the face it turns, using \
+                    the standard color scheme with white on top and green in front.",
+            )
+            .wrap(true),
+        );
+
+        ui.add_space(8.0);
+
+        let scramble_preview = history.preferences().scramble_preview();
+        if ui
+            .add(
+                Label::new(format!(
+                    "{}  Scramble preview",
+                    if scramble_preview { "☑" } else { "☐" }
+                ))
+                .text_style(FontSize::Section.into())
+                .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            history.set_preference(Preference::ScramblePreview(!scramble_preview));
+            let _ = history.local_commit();
+        }
+        ui.add(
+            Label::new(
+                "Show a diagram of the scrambled cube next to the scramble, to check that the \
+                    scramble was applied correctly.",
+            )
+            .wrap(true),
+        );
+
+        ui.add_space(8.0);
+
+        let scramble_orientation = history.preferences().scramble_orientation();
+        let popup_id = ui.make_persistent_id("scramble-orientation");
+        let response = ui.add(
+            Label::new(format!(
+                "🔄  Scramble Orientation: {} ⏷",
+                scramble_orientation.name()
+            ))
+            .text_style(FontSize::Section.into())
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            ScrollArea::from_max_height(300.0).show(ui, |ui| {
+                for orientation in ScrambleOrientation::all() {
+                    if ui
+                        .add(
+                            SelectableLabel::new(
+                                scramble_orientation == orientation,
+                                orientation.name(),
+                            )
+                            .text_style(FontSize::Normal.into()),
+                        )
+                        .clicked()
+                    {
+                        history.set_preference(Preference::ScrambleOrientation(orientation));
+                        let _ = history.local_commit();
+                    }
+                }
+            });
+        });
+        ui.add(
+            Label::new(
+                "Orientation you hold the cube in while scrambling. Scrambles are rewritten \
+                    so that they turn the same colors as they would with white on top and \
+                    green in front.",
+            )
+            .wrap(true),
+        );
+
+        let notation = history.preferences().move_notation();
+        let popup_id = ui.make_persistent_id("move-notation");
+        let response = ui.add(
+            Label::new(format!(
+                "✏  Notation: {}, {} ⏷",
+                notation.wide.name(),
+                notation.prime.name()
+            ))
+            .text_style(FontSize::Section.into())
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            for wide in WideMoveNotation::ALL {
+                if ui
+                    .add(
+                        SelectableLabel::new(
+                            notation.wide == *wide,
+                            format!("Wide moves as {}", wide.name()),
+                        )
+                        .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::WideMoveNotation(*wide));
+                    let _ = history.local_commit();
+                }
+            }
+            ui.separator();
+            for prime in PrimeNotation::ALL {
+                if ui
+                    .add(
+                        SelectableLabel::new(
+                            notation.prime == *prime,
+                            format!("Counterclockwise as {}", prime.name()),
+                        )
+                        .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::PrimeNotation(*prime));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+        ui.add(
+            Label::new(
+                "Notation used for scrambles and exported solves. Moves in any of these \
+                    notations are accepted when importing.",
+            )
+            .wrap(true),
+        );
+
+        #[cfg(all(feature = "speech", not(target_arch = "wasm32")))]
+        {
+            ui.add_space(8.0);
+
+            if ui
+                .add(
+                    Label::new(format!(
+                        "{}  Read scramble aloud",
+                        if Self::speak_scramble_enabled(history) {
+                            "☑"
+                        } else {
+                            "☐"
+                        }
+                    ))
+                    .text_style(FontSize::Section.into())
+                    .sense(Sense::click()),
+                )
+                .clicked()
+            {
+                let new_speak_scramble_enabled = !Self::speak_scramble_enabled(history);
+                let _ = history.set_bool_setting("speak_scramble", new_speak_scramble_enabled);
+            }
+            ui.add(
+                Label::new(
+                    "Speak each new scramble using the system text to speech voice. This \
+                        setting is not synced to other devices.",
+                )
+                .wrap(true),
+            );
+        }
+
+        ui.add_space(8.0);
+
+        let default_puzzle = history.preferences().default_puzzle();
+        let popup_id = ui.make_persistent_id("default-puzzle");
+        let response = ui.add(
+            Label::new(format!(
+                "🎲  Default Puzzle: {} ⏷",
+                match default_puzzle {
+                    Some(solve_type) => solve_type.to_string(),
+                    None => "Last used".into(),
+                }
+            ))
+            .text_style(FontSize::Section.into())
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(180.0);
+            for solve_type in &[
+                None,
+                Some(SolveType::Standard3x3x3),
+                Some(SolveType::OneHanded3x3x3),
+                Some(SolveType::Blind3x3x3),
+                Some(SolveType::Standard2x2x2),
+                Some(SolveType::Standard5x5x5),
+                Some(SolveType::Standard6x6x6),
+                Some(SolveType::Standard7x7x7),
+                Some(SolveType::Void3x3x3),
+                Some(SolveType::Bandaged3x3x3),
+            ] {
+                if ui
+                    .add(
+                        SelectableLabel::new(
+                            default_puzzle == *solve_type,
+                            match solve_type {
+                                Some(solve_type) => solve_type.to_string(),
+                                None => "Last used".into(),
+                            },
+                        )
+                        .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_preference(Preference::DefaultPuzzle(*solve_type));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+        ui.add(
+            Label::new(
+                "Puzzle to select when the app starts. Display preferences are synced across \
+                    all of your devices.",
+            )
+            .wrap(true),
+        );
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn diagnostics(&mut self, ui: &mut Ui) {
+        let log = match self.diagnostic_log.clone() {
+            Some(log) => log,
+            None => {
+                ui.add(Label::new("Diagnostic log is not available.").text_color(Theme::Red));
+                return;
+            }
+        };
+
+        ui.horizontal(|ui| {
+            for level in &[
+                DiagnosticLevel::Trace,
+                DiagnosticLevel::Debug,
+                DiagnosticLevel::Info,
+                DiagnosticLevel::Warning,
+                DiagnosticLevel::Error,
+            ] {
+                if ui
+                    .add(
+                        SelectableLabel::new(self.diagnostic_level == *level, level.name())
+                            .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    self.diagnostic_level = *level;
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui
+                .add(
+                    Label::new("🗐  Copy log")
+                        .text_style(FontSize::Section.into())
+                        .sense(Sense::click()),
+                )
+                .clicked()
+            {
+                ui.output().copied_text = log.to_text();
+            }
+            ui.add_space(16.0);
+            if ui
+                .add(
+                    Label::new("🗑  Clear log")
+                        .text_style(FontSize::Section.into())
+                        .sense(Sense::click()),
+                )
+                .clicked()
+            {
+                log.clear();
+            }
+        });
+
+        ScrollArea::from_max_height(300.0).show(ui, |ui| {
+            // Show the newest entries first
+            for entry in log.entries().iter().rev() {
+                if entry.level < self.diagnostic_level {
+                    continue;
+                }
+                ui.add(
+                    Label::new(entry.to_string())
+                        .text_style(FontSize::Small.into())
+                        .text_color(match entry.level {
+                            DiagnosticLevel::Error => Theme::Red,
+                            DiagnosticLevel::Warning => Theme::Orange,
+                            DiagnosticLevel::Info => Theme::Content,
+                            _ => Theme::Disabled,
+                        })
+                        .wrap(true),
+                );
+            }
+        });
+
+        ui.add(
+            Label::new(
+                "Recent Bluetooth, sync and storage activity. Include a copy of this log \
+                    when reporting a problem.",
+            )
+            .wrap(true),
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn lan_sync(&mut self, ui: &mut Ui, history: &mut History) {
+        let mut host_enabled = Self::lan_sync_host_enabled(history);
+        if ui
+            .checkbox(&mut host_enabled, "📡  Host sync on this device")
+            .changed()
+        {
+            self.lan_sync_error = None;
+            let _ = history.set_bool_setting("lan_sync_host", host_enabled);
+        }
+        ui.add(
+            Label::new(
+                "Devices on the local network with the same sync key can sync with this \
+                    device instead of the cloud. This device must be running for the others \
+                    to sync.",
+            )
+            .wrap(true),
+        );
+        if let Some(error) = &self.lan_sync_error {
+            ui.add(
+                Label::new(format!("Error: {}", error))
+                    .wrap(true)
+                    .text_color(Theme::Red),
+            );
+        }
+        if host_enabled {
+            return;
+        }
+
+        // Look for hosts while the settings page is shown
+        if self.lan_sync_browser.is_none() {
+            self.lan_sync_browser = LanSyncBrowser::new().ok();
+        }
+        let peers = match &mut self.lan_sync_browser {
+            Some(browser) => browser.peers(),
+            None => Vec::new(),
+        };
+
+        ui.add_space(8.0);
+        let current = history.lan_sync_peer();
+        if ui
+            .add(SelectableLabel::new(
+                current.is_none(),
+                "☁  Sync with the cloud",
+            ))
+            .clicked()
+        {
+            history.set_lan_sync_peer(None);
+        }
+        for peer in peers {
+            if ui
+                .add(SelectableLabel::new(
+                    current == Some(peer.address),
+                    format!("🖧  Sync with {}", peer.name),
+                ))
+                .clicked()
+            {
+                history.set_lan_sync_peer(Some(peer.address));
+            }
+        }
+        if let Some(address) = current {
+            ui.add(Label::new(format!("Syncing with {}", address)).text_color(Theme::Disabled));
+        }
+    }
+
+    fn account(&mut self, ui: &mut Ui, history: &mut History) {
+        // Account state is tied to the sync key, reload it if the key changes
+        if self.account.as_ref().map(|account| account.sync_key()) != Some(history.sync_key()) {
+            self.account = Some(Account::load(history, &device_name()));
+            self.confirm_wipe = false;
+            self.account_status = None;
+        }
+        let account = self.account.as_mut().unwrap();
+
+        if let Some(operation) = account.check_operation(history) {
+            self.account_status = match operation {
+                AccountOperation::RevokeDevice(_) => Some("Device revoked"),
+                AccountOperation::WipeData => {
+                    Some("Cloud data wiped. This device now has a new sync key.")
+                }
+                _ => None,
+            };
+        }
+        if account.operation_in_progress() {
+            ui.ctx().request_repaint();
+        }
+
+        if !account.logged_in() {
+            ui.horizontal(|ui| {
+                if ui
+                    .add(
+                        Label::new("👤  Create account")
+                            .text_style(FontSize::Section.into())
+                            .sense(Sense::click()),
+                    )
+                    .clicked()
+                {
+                    account.create();
+                }
+                if ui
+                    .add(
+                        Label::new("🔑  Log in")
+                            .text_style(FontSize::Section.into())
+                            .sense(Sense::click()),
+                    )
+                    .clicked()
+                {
+                    account.login();
+                }
+            });
+            ui.add(
+                Label::new(
+                    "An account lets you see which devices are syncing with your sync key, \
+                        revoke devices you no longer use, and delete your data from the \
+                        cloud. Create an account on your first device, then log in on the \
+                        others.",
+                )
+                .wrap(true),
+            );
+        } else {
+            if ui
+                .add(
+                    Label::new("🔃  Refresh devices")
+                        .text_style(FontSize::Section.into())
+                        .sense(Sense::click()),
+                )
+                .clicked()
+            {
+                account.list_devices();
+            }
+
+            let mut revoke = None;
+            for device in account.devices() {
+                ui.horizontal(|ui| {
+                    let current = device.id == account.device_id();
+                    ui.add(Label::new(if current {
+                        format!("{} (this device)", device.name)
+                    } else {
+                        device.name.clone()
+                    }));
+                    ui.add(
+                        Label::new(
+                            Local
+                                .timestamp_millis(device.last_login)
+                                .format("%b %e, %Y")
+                                .to_string(),
+                        )
+                        .text_color(Theme::Disabled),
+                    );
+                    if ui
+                        .add(
+                            Label::new(if current { "Log out" } else { "✖ Revoke" })
+                                .text_color(Theme::Red)
+                                .sense(Sense::click()),
+                        )
+                        .clicked()
+                    {
+                        revoke = Some(device.id.clone());
+                    }
+                });
+            }
+            if let Some(device) = revoke {
+                account.revoke_device(&device);
+            }
+
+            ui.add_space(8.0);
+            if self.confirm_wipe {
+                ui.add(
+                    Label::new(
+                        "Delete all synced solves and devices from the cloud? Solves on this \
+                            device are kept, and it will start syncing with a new sync key.",
+                    )
+                    .text_color(Theme::Red)
+                    .wrap(true),
+                );
+                let (wipe, cancel) = ui
+                    .horizontal(|ui| {
+                        let wipe = ui
+                            .add(
+                                Label::new("🗑  Wipe")
+                                    .text_color(Theme::Red)
+                                    .sense(Sense::click()),
+                            )
+                            .clicked();
+                        let cancel = ui.add(Label::new("Cancel").sense(Sense::click())).clicked();
+                        (wipe, cancel)
+                    })
+                    .inner;
+                if wipe {
+                    account.wipe_data();
+                }
+                if wipe || cancel {
+                    self.confirm_wipe = false;
+                }
+            } else if ui
+                .add(
+                    Label::new("🗑  Wipe cloud data")
+                        .text_style(FontSize::Section.into())
+                        .sense(Sense::click()),
+                )
+                .clicked()
+            {
+                self.confirm_wipe = true;
+            }
+        }
+
+        if account.operation_in_progress() {
+            ui.add(Label::new("Contacting server...").text_color(Theme::Disabled));
+        } else if let Some(error) = account.last_error() {
+            ui.add(
+                Label::new(format!("Error: {}", error))
+                    .wrap(true)
+                    .text_color(Theme::Red),
+            );
+        } else if let Some(status) = self.account_status {
+            ui.add(Label::new(status).text_color(Theme::Green));
+        }
+    }
+
+    /// Sync key and sync endpoint settings. These are hidden for guest
+    /// sessions, which are never synced.
+    fn cloud_sync(&mut self, ui: &mut Ui, history: &mut History) {
+        // Show sync key option
+        if ui
+            .add(
+                Label::new(format!(
+                    "👁  {} sync key",
+                    if self.sync_key_visible {
+                        "Hide"
+                    } else {
+                        "Show"
+                    }
+                ))
+                .text_style(FontSize::Section.into())
+                .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            self.sync_key_visible = !self.sync_key_visible
+        }
+        if self.sync_key_visible {
+            ui.add(
+                Label::new(history.sync_key())
+                    .text_style(FontSize::Scramble.into())
+                    .text_color(Theme::Yellow),
+            );
+            if ui
+                .add(
+                    Label::new("🗐  Copy")
+                        .text_style(FontSize::Section.into())
+                        .sense(Sense::click()),
+                )
+                .clicked()
+            {
+                ui.output().copied_text = history.sync_key().into();
+            }
+        }
+        ui.add(
+            Label::new(format!(
+                "{} your active sync key. This key acts like a password to sync your \
+                    solve information across devices. Never share your sync key with \
+                    anyone.",
+                if self.sync_key_visible {
+                    "This is"
+                } else {
+                    "Show"
+                }
+            ))
+            .wrap(true),
+        );
+
+        ui.add_space(8.0);
+
+        // Set sync key option
+        if ui
+            .add(
+                Label::new("🗝  Set sync key")
+                    .text_style(FontSize::Section.into())
+                    .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            self.set_key_visible = !self.set_key_visible;
+        }
+        ui.add(
+            Label::new(
+                "If you already have other devices with solve information, you can \
+                    set your sync key here to sync with them. You can view \
+                    your sync key on any device that is already being synced.",
+            )
+            .wrap(true),
+        );
+
+        if self.set_key_visible {
+            // If set sync key is active, show edit box
+            ui.add_space(8.0);
+            ui.add(Label::new("New sync key: ").text_color(Theme::Yellow));
+            ui.style_mut().visuals.widgets.inactive.bg_stroke = Stroke {
+                width: 1.0,
+                color: Theme::Disabled.into(),
+            };
+            ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke {
+                width: 1.0,
+                color: Theme::Disabled.into(),
+            };
+            ui.style_mut().visuals.widgets.active.bg_stroke = Stroke {
+                width: 1.0,
+                color: Theme::Content.into(),
+            };
+            ui.text_edit_singleline(&mut self.new_sync_key);
+
+            // Validate the sync key being entered
+            if let Some(key) = SyncRequest::validate_sync_key(&self.new_sync_key) {
+                if key == history.sync_key() {
+                    ui.add(Label::new("Key set").text_color(Theme::Green));
+                } else {
+                    // Sync key is valid and different, allow the user to set it
+                    if ui
+                        .add(
+                            Label::new("✔  Save")
+                                .text_style(FontSize::Section.into())
+                                .sense(Sense::click()),
+                        )
+                        .clicked()
+                    {
+                        let _ = history.set_sync_key(&key);
+                    }
+                }
+            } else {
+                // Sync key is not valid, show error
+                ui.add(Label::new("(Not valid)").text_color(Theme::Red));
+            }
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.add_space(8.0);
+            self.lan_sync(ui, history);
+        }
+
+        ui.add_space(8.0);
+        self.sync_status(ui, history);
+    }
+
+    fn sync_status(&self, ui: &mut Ui, history: &History) {
+        let details = history.sync_details();
+        ui.add(Label::new(match details.last_complete {
+            Some(time) => format!("Last synced {}", time.format("%b %e, %Y %l:%M %P")),
+            None => "Not synced on this device yet".into(),
+        }));
+        ui.add(Label::new(match details.pending_actions {
+            0 => "No actions waiting to upload".into(),
+            1 => "1 action waiting to upload".into(),
+            count => format!("{} actions waiting to upload", count),
+        }));
+        if details.requests != 0 {
+            ui.add(
+                Label::new(format!(
+                    "{} actions uploaded ({}), {} downloaded ({}) in {} request{}",
+                    details.uploaded_actions,
+                    byte_count_string(details.uploaded_bytes),
+                    details.downloaded_actions,
+                    byte_count_string(details.downloaded_bytes),
+                    details.requests,
+                    if details.requests == 1 { "" } else { "s" }
+                ))
+                .wrap(true),
+            );
+        }
+        if let Some(round_trip) = details.round_trip_ms {
+            ui.add(Label::new(format!("Round trip time {} ms", round_trip)));
+        }
+        if let Some(error) = &details.last_error {
+            ui.add(
+                Label::new(format!("Last error: {}", error))
+                    .wrap(true)
+                    .text_color(Theme::Red),
+            );
+        }
+        if let Some(retry) = details.next_retry_ms {
+            ui.add(Label::new(format!(
+                "Retry {} in {} seconds",
+                details.retries + 1,
+                (retry + 999) / 1000
+            )));
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        ctxt: &CtxRef,
+        _frame: &mut epi::Frame<'_>,
+        history: &mut History,
+        gamepad: &GamepadInput,
+    ) {
         ctxt.set_visuals(settings_visuals());
         CentralPanel::default().show(ctxt, |ui| {
             ui.visuals_mut().widgets.inactive.bg_fill = Theme::BackgroundHighlight.into();
@@ -195,18 +1744,16 @@ impl Settings {
                         );
                     }
 
-                    ui.add_space(16.0);
-                    ui.section("Cloud Sync");
+                    ui.add_space(8.0);
 
-                    // Show sync key option
                     if ui
                         .add(
                             Label::new(format!(
-                                "👁  {} sync key",
-                                if self.sync_key_visible {
-                                    "Hide"
+                                "{}  Exclude warmup solves",
+                                if Self::exclude_warmup_enabled(history) {
+                                    "☑"
                                 } else {
-                                    "Show"
+                                    "☐"
                                 }
                             ))
                             .text_style(FontSize::Section.into())
@@ -214,123 +1761,236 @@ impl Settings {
                         )
                         .clicked()
                     {
-                        self.sync_key_visible = !self.sync_key_visible
-                    }
-                    if self.sync_key_visible {
-                        ui.add(
-                            Label::new(history.sync_key())
-                                .text_style(FontSize::Scramble.into())
-                                .text_color(Theme::Yellow),
-                        );
-                        if ui
-                            .add(
-                                Label::new("🗐  Copy")
-                                    .text_style(FontSize::Section.into())
-                                    .sense(Sense::click()),
-                            )
-                            .clicked()
-                        {
-                            ui.output().copied_text = history.sync_key().into();
-                        }
+                        let new_exclude_warmup = !Self::exclude_warmup_enabled(history);
+                        let _ = history.set_bool_setting("exclude_warmup", new_exclude_warmup);
                     }
                     ui.add(
-                        Label::new(format!(
-                            "{} your active sync key. This key acts like a password to sync your \
-                                solve information across devices. Never share your sync key with \
-                                anyone.",
-                            if self.sync_key_visible {
-                                "This is"
-                            } else {
-                                "Show"
-                            }
-                        ))
+                        Label::new(
+                            "Leave slow solves at the start of a session out of the session \
+                                averages and bests. Up to five solves are considered warmup.",
+                        )
                         .wrap(true),
                     );
 
+                    ui.add_space(16.0);
+                    ui.section("Timer Controls");
+                    self.input_bindings(ui, history, gamepad);
+
                     ui.add_space(8.0);
+                    self.timer_preferences(ui, history);
 
-                    // Set sync key option
-                    if ui
-                        .add(
-                            Label::new("🗝  Set sync key")
+                    ui.add_space(8.0);
+                    self.calibration.update(ui, history);
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        ui.add_space(8.0);
+
+                        if ui
+                            .add(
+                                Label::new(format!(
+                                    "{}  Arm when cube is set down",
+                                    if Self::orientation_arming_enabled(history) {
+                                        "☑"
+                                    } else {
+                                        "☐"
+                                    }
+                                ))
                                 .text_style(FontSize::Section.into())
                                 .sense(Sense::click()),
-                        )
-                        .clicked()
+                            )
+                            .clicked()
+                        {
+                            let new_orientation_arming_enabled =
+                                !Self::orientation_arming_enabled(history);
+                            let _ = history.set_bool_setting(
+                                "orientation_arming",
+                                new_orientation_arming_enabled,
+                            );
+                        }
+                        ui.add(
+                            Label::new(
+                                "For smart cubes with a gyroscope, wait until the scrambled cube \
+                                    is resting flat before arming the timer. The first turn \
+                                    starts the solve.",
+                            )
+                            .wrap(true),
+                        );
+
+                        ui.add_space(8.0);
+                        Self::timer_start_latency_preference(ui, history);
+                    }
+
+                    #[cfg(all(feature = "move-sound", not(target_arch = "wasm32")))]
                     {
-                        self.set_key_visible = !self.set_key_visible;
+                        ui.add_space(8.0);
+                        self.move_feedback_preferences(ui, history);
                     }
-                    ui.add(
-                        Label::new(
-                            "If you already have other devices with solve information, you can \
-                                set your sync key here to sync with them. You can view \
-                                your sync key on any device that is already being synced.",
-                        )
-                        .wrap(true),
-                    );
 
-                    if self.set_key_visible {
-                        // If set sync key is active, show edit box
+                    #[cfg(all(feature = "audio-trigger", not(target_arch = "wasm32")))]
+                    {
                         ui.add_space(8.0);
-                        ui.add(Label::new("New sync key: ").text_color(Theme::Yellow));
-                        ui.style_mut().visuals.widgets.inactive.bg_stroke = Stroke {
-                            width: 1.0,
-                            color: Theme::Disabled.into(),
-                        };
-                        ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke {
-                            width: 1.0,
-                            color: Theme::Disabled.into(),
-                        };
-                        ui.style_mut().visuals.widgets.active.bg_stroke = Stroke {
-                            width: 1.0,
-                            color: Theme::Content.into(),
-                        };
-                        ui.text_edit_singleline(&mut self.new_sync_key);
-
-                        // Validate the sync key being entered
-                        if let Some(key) = SyncRequest::validate_sync_key(&self.new_sync_key) {
-                            if key == history.sync_key() {
-                                ui.add(Label::new("Key set").text_color(Theme::Green));
-                            } else {
-                                // Sync key is valid and different, allow the user to set it
-                                if ui
-                                    .add(
-                                        Label::new("✔  Save")
-                                            .text_style(FontSize::Section.into())
-                                            .sense(Sense::click()),
-                                    )
-                                    .clicked()
-                                {
-                                    let _ = history.set_sync_key(&key);
-                                }
-                            }
-                        } else {
-                            // Sync key is not valid, show error
-                            ui.add(Label::new("(Not valid)").text_color(Theme::Red));
+
+                        if ui
+                            .add(
+                                Label::new(format!(
+                                    "{}  Sound trigger",
+                                    if Self::audio_trigger_enabled(history) {
+                                        "☑"
+                                    } else {
+                                        "☐"
+                                    }
+                                ))
+                                .text_style(FontSize::Section.into())
+                                .sense(Sense::click()),
+                            )
+                            .clicked()
+                        {
+                            let new_audio_trigger_enabled = !Self::audio_trigger_enabled(history);
+                            let _ = history
+                                .set_bool_setting("audio_trigger", new_audio_trigger_enabled);
                         }
+                        ui.add(
+                            Label::new(
+                                "Start and stop the timer with a sharp sound, such as a clap or \
+                                    a short spoken command, instead of the keyboard.",
+                            )
+                            .wrap(true),
+                        );
                     }
 
+                    ui.add_space(16.0);
+                    ui.section("Display");
+                    self.display_preferences(ui, history);
+
+                    ui.add_space(16.0);
+                    ui.section("Cloud Sync");
+
+                    if history.is_in_memory() {
+                        ui.add(
+                            Label::new(
+                                "Guest mode is active. Solves are kept in memory only, are \
+                                never synced, and are discarded when the app is closed.",
+                            )
+                            .wrap(true)
+                            .text_color(Theme::Yellow),
+                        );
+                    } else {
+                        self.cloud_sync(ui, history);
+                    }
+
+                    ui.add_space(16.0);
+                    ui.section("Account");
+                    self.account(ui, history);
+
                     #[cfg(not(target_arch = "wasm32"))]
                     {
                         ui.add_space(16.0);
                         ui.section("Import / Export");
 
                         // Import solves option
+                        self.check_import(history);
+                        let importing = self.import_source.is_some() || self.import.is_some();
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add(
+                                    Label::new("🗁  Import solves")
+                                        .text_style(FontSize::Section.into())
+                                        .sense(Sense::click()),
+                                )
+                                .clicked()
+                                && !importing
+                            {
+                                self.import_solves(false);
+                            }
+                            ui.add_space(16.0);
+                            if ui
+                                .add(
+                                    Label::new("🔍  Preview")
+                                        .text_style(FontSize::Section.into())
+                                        .sense(Sense::click()),
+                                )
+                                .on_hover_text("Show what an import would change without importing")
+                                .clicked()
+                                && !importing
+                            {
+                                self.import_solves(true);
+                            }
+                        });
+                        if self.import_source.is_some() {
+                            ui.add(Label::new("Reading backup...").text_color(Theme::Disabled));
+                            ui.ctx().request_repaint();
+                        }
+                        if let Some(import) = &mut self.import {
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    Label::new(format!(
+                                        "{} {:.0}%",
+                                        if import.is_dry_run() {
+                                            "Checking..."
+                                        } else {
+                                            "Importing..."
+                                        },
+                                        import.progress().approximate_percent_done()
+                                    ))
+                                    .text_color(Theme::Disabled),
+                                );
+                                if ui
+                                    .add(Label::new("✖  Cancel").sense(Sense::click()))
+                                    .clicked()
+                                {
+                                    import.cancel();
+                                }
+                            });
+                            ui.ctx().request_repaint();
+                        }
+                        if let Some(result) = &self.import_result {
+                            match result {
+                                Ok(message) => {
+                                    ui.add(Label::new(message).text_color(Theme::Green));
+                                }
+                                Err(error) => {
+                                    ui.add(
+                                        Label::new(format!("Error: {}", error))
+                                            .wrap(true)
+                                            .text_color(Theme::Red),
+                                    );
+                                }
+                            }
+                        }
+                        ui.add(
+                            Label::new(
+                                "Import solves from a backup. Supports backups from \
+                               TPS Cube, csTimer, and Cubeast. Preview shows how many \
+                               solves and sessions would be added without importing.",
+                            )
+                            .wrap(true),
+                        );
+
+                        ui.add_space(8.0);
+
+                        // Merge database option
+                        self.check_merge(history);
                         if ui
                             .add(
-                                Label::new("🗁  Import solves")
+                                Label::new("🗄  Merge solve database")
                                     .text_style(FontSize::Section.into())
                                     .sense(Sense::click()),
                             )
                             .clicked()
+                            && self.merge_source.is_none()
                         {
-                            self.import_solves(history);
+                            self.merge_database();
                         }
-                        if let Some(result) = &self.import_result {
+                        if self.merge_source.is_some() {
+                            ui.add(Label::new("Reading database...").text_color(Theme::Disabled));
+                            ui.ctx().request_repaint();
+                        }
+                        if let Some(result) = &self.merge_result {
                             match result {
                                 Ok(message) => {
                                     ui.add(
-                                        Label::new(format!("Import complete.\n{}", message))
+                                        Label::new(format!("Merge complete.\n{}", message))
                                             .text_color(Theme::Green),
                                     );
                                 }
@@ -345,8 +2005,9 @@ impl Settings {
                         }
                         ui.add(
                             Label::new(
-                                "Import solves from a backup. Supports backups from \
-                               TPS Cube, csTimer, and Cubeast.",
+                                "Merge the solves from the database folder of another \
+                                    installation, such as a computer used before sync was \
+                                    enabled. Solves already in this history are skipped.",
                             )
                             .wrap(true),
                         );
@@ -378,10 +2039,116 @@ impl Settings {
                                 }
                             }
                         }
-                        ui.label("Export all solve information to a file for backup.")
+                        ui.label("Export all solve information to a file for backup.");
+
+                        ui.add_space(8.0);
+
+                        // Import official results option
+                        if ui
+                            .add(
+                                Label::new("🏆  Import WCA results")
+                                    .text_style(FontSize::Section.into())
+                                    .sense(Sense::click()),
+                            )
+                            .clicked()
+                        {
+                            self.import_official_results(history);
+                        }
+                        if let Some(result) = &self.official_import_result {
+                            match result {
+                                Ok(message) => {
+                                    ui.add(Label::new(message).text_color(Theme::Green));
+                                }
+                                Err(error) => {
+                                    ui.add(
+                                        Label::new(format!("Error: {}", error))
+                                            .wrap(true)
+                                            .text_color(Theme::Red),
+                                    );
+                                }
+                            }
+                        }
+                        if history.official_results().is_some()
+                            && ui
+                                .add(
+                                    Label::new("🗑  Remove WCA results")
+                                        .text_style(FontSize::Section.into())
+                                        .sense(Sense::click()),
+                                )
+                                .clicked()
+                        {
+                            history.clear_official_results();
+                            self.official_import_result = None;
+                        }
+                        ui.add(
+                            Label::new(
+                                "Import your official records from the WCA API \
+                                (worldcubeassociation.org/api/v0/persons/<WCA ID>) to show \
+                                them alongside your practice bests. These are kept on this \
+                                device and are not synced.",
+                            )
+                            .wrap(true),
+                        );
+                    }
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        ui.add_space(16.0);
+                        ui.section("Solve Command");
+                        self.solve_command(ui, history);
+                    }
+
+                    ui.add_space(16.0);
+                    ui.section("Storage");
+                    self.storage(ui, history);
+
+                    #[cfg(feature = "diagnostics")]
+                    {
+                        ui.add_space(16.0);
+                        ui.section("Diagnostics");
+                        self.diagnostics(ui);
                     }
                 });
             });
         });
     }
 }
+
+fn hold_duration_string(duration: u32) -> String {
+    if duration == 0 {
+        "None".into()
+    } else {
+        format!("{:.2} seconds", duration as f32 / 1000.0)
+    }
+}
+
+fn daily_solve_goal_string(goal: u32) -> String {
+    match goal {
+        0 => "Any solve".into(),
+        1 => "1 solve".into(),
+        _ => format!("{} solves", goal),
+    }
+}
+
+fn byte_count_string(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} bytes", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f32 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f32 / (1024.0 * 1024.0))
+    }
+}
+
+/// Name shown to other devices for LAN sync hosting and account device lists
+#[cfg(not(target_arch = "wasm32"))]
+fn device_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "TPS Cube".into())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn device_name() -> String {
+    "Web browser".into()
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use tpscube_core::{CubeFace, Move};
+use tts::Tts;
+
+/// Reads scrambles aloud using the platform's text to speech engine. Moves
+/// are spoken using full face names so that they can be followed without
+/// looking at the screen.
+pub struct ScrambleSpeech {
+    tts: Tts,
+    last_spoken: Vec<Move>,
+}
+
+impl ScrambleSpeech {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            tts: Tts::default()?,
+            last_spoken: Vec::new(),
+        })
+    }
+
+    /// Speaks the scramble, interrupting anything currently being spoken.
+    pub fn speak(&mut self, scramble: &[Move]) -> Result<()> {
+        self.last_spoken = scramble.to_vec();
+        self.tts.speak(spoken_scramble(scramble), true)?;
+        Ok(())
+    }
+
+    /// Speaks the scramble if it is different from the last one spoken.
+    pub fn speak_if_changed(&mut self, scramble: &[Move]) -> Result<()> {
+        if self.last_spoken.as_slice() != scramble {
+            self.speak(scramble)?;
+        }
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if self.last_spoken.len() > 0 {
+            let _ = self.tts.stop();
+            self.last_spoken.clear();
+        }
+    }
+}
+
+fn spoken_move(mv: Move) -> String {
+    let face = match mv.face() {
+        CubeFace::Top => "Up",
+        CubeFace::Front => "Front",
+        CubeFace::Right => "Right",
+        CubeFace::Back => "Back",
+        CubeFace::Left => "Left",
+        CubeFace::Bottom => "Down",
+    };
+    let wide = if mv.to_string().contains('w') {
+        " wide"
+    } else {
+        ""
+    };
+    match mv.rotation() {
+        -1 => format!("{}{} prime", face, wide),
+        2 => format!("{}{} two", face, wide),
+        _ => format!("{}{}", face, wide),
+    }
+}
+
+/// Text to speak for a scramble. Moves are separated with commas so that
+/// speech engines pause between them.
+fn spoken_scramble(scramble: &[Move]) -> String {
+    scramble
+        .iter()
+        .map(|mv| spoken_move(*mv))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
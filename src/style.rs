@@ -7,7 +7,7 @@ use egui::{
 
 pub fn base_visuals() -> Visuals {
     Visuals {
-        dark_mode: true,
+        dark_mode: Theme::is_dark(),
         widgets: Widgets {
             noninteractive: WidgetVisuals {
                 bg_fill: Theme::BackgroundWindow.into(),
@@ -88,7 +88,12 @@ pub fn base_visuals() -> Visuals {
         window_corner_radius: 0.0,
         window_shadow: Shadow {
             extrusion: 12.0,
-            color: Color32::from_rgba_premultiplied(0, 0, 0, 160),
+            color: Color32::from_rgba_premultiplied(
+                0,
+                0,
+                0,
+                if Theme::is_dark() { 160 } else { 64 },
+            ),
         },
         ..Default::default()
     }
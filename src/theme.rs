@@ -1,4 +1,6 @@
 use egui::Color32;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tpscube_core::{AccentColor, Color, ColorScheme, CubeFace};
 
 pub enum Theme {
     Background,
@@ -19,9 +21,101 @@ pub enum Theme {
     Magenta,
 }
 
+// Active color scheme and accent color. These are global so that theme colors
+// can continue to be converted anywhere without passing the user's preferences.
+static COLOR_SCHEME: AtomicU8 = AtomicU8::new(0);
+static ACCENT_COLOR: AtomicU8 = AtomicU8::new(0);
+
+impl Theme {
+    /// Sets the color scheme and accent color used for all theme colors
+    pub fn set_color_scheme(scheme: ColorScheme, accent: AccentColor) {
+        COLOR_SCHEME.store(
+            ColorScheme::ALL
+                .iter()
+                .position(|value| *value == scheme)
+                .unwrap_or(0) as u8,
+            Ordering::Relaxed,
+        );
+        ACCENT_COLOR.store(
+            AccentColor::ALL
+                .iter()
+                .position(|value| *value == accent)
+                .unwrap_or(0) as u8,
+            Ordering::Relaxed,
+        );
+    }
+
+    pub fn color_scheme() -> ColorScheme {
+        ColorScheme::ALL
+            .get(COLOR_SCHEME.load(Ordering::Relaxed) as usize)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn accent_color() -> AccentColor {
+        AccentColor::ALL
+            .get(ACCENT_COLOR.load(Ordering::Relaxed) as usize)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn is_dark() -> bool {
+        Self::color_scheme() != ColorScheme::Light
+    }
+
+    /// Color used for an accent color in the active color scheme
+    pub fn accent(accent: AccentColor) -> Color32 {
+        match accent {
+            AccentColor::Blue => scheme_color(Self::color_scheme(), Theme::Blue),
+            AccentColor::Cyan => Theme::Cyan.into(),
+            AccentColor::Green => Theme::Green.into(),
+            AccentColor::Orange => Theme::Orange.into(),
+            AccentColor::Yellow => Theme::Yellow.into(),
+            AccentColor::Magenta => Theme::Magenta.into(),
+        }
+    }
+
+    /// Color used to draw moves of the given face when scramble colors are
+    /// enabled. This is independent of the accent color so that back face
+    /// moves are always blue.
+    pub fn face(face: CubeFace) -> Color32 {
+        match face {
+            CubeFace::Top => Theme::Content.into(),
+            CubeFace::Front => Theme::Green.into(),
+            CubeFace::Right => Theme::Red.into(),
+            CubeFace::Back => Theme::accent(AccentColor::Blue),
+            CubeFace::Left => Theme::Orange.into(),
+            CubeFace::Bottom => Theme::Yellow.into(),
+        }
+    }
+
+    /// Color of a cube sticker in cube diagrams. These are the real sticker
+    /// colors, which don't change with the color scheme.
+    pub fn sticker(color: Color) -> Color32 {
+        match color {
+            Color::White => Color32::from_rgb(0xff, 0xff, 0xff),
+            Color::Green => Color32::from_rgb(0x00, 0xb0, 0x40),
+            Color::Red => Color32::from_rgb(0xd0, 0x10, 0x10),
+            Color::Blue => Color32::from_rgb(0x10, 0x40, 0xe0),
+            Color::Orange => Color32::from_rgb(0xff, 0x80, 0x00),
+            Color::Yellow => Color32::from_rgb(0xff, 0xe0, 0x00),
+        }
+    }
+}
+
 impl Into<Color32> for Theme {
     fn into(self) -> Color32 {
         match self {
+            // The accent color replaces blue, which is used for titles and highlights
+            Theme::Blue => Theme::accent(Theme::accent_color()),
+            _ => scheme_color(Theme::color_scheme(), self),
+        }
+    }
+}
+
+fn scheme_color(scheme: ColorScheme, theme: Theme) -> Color32 {
+    match scheme {
+        ColorScheme::Dark => match theme {
             Theme::Background => Color32::from_rgb(44, 44, 44),
             Theme::BackgroundDark => Color32::from_rgb(34, 34, 34),
             Theme::BackgroundWindow => Color32::from_rgb(56, 56, 56),
@@ -38,6 +132,42 @@ impl Into<Color32> for Theme {
             Theme::Orange => Color32::from_rgb(237, 189, 129),
             Theme::Yellow => Color32::from_rgb(237, 223, 179),
             Theme::Magenta => Color32::from_rgb(218, 196, 209),
-        }
+        },
+        ColorScheme::Light => match theme {
+            Theme::Background => Color32::from_rgb(236, 236, 236),
+            Theme::BackgroundDark => Color32::from_rgb(250, 250, 250),
+            Theme::BackgroundWindow => Color32::from_rgb(224, 224, 224),
+            Theme::BackgroundHighlight => Color32::from_rgb(212, 212, 212),
+            Theme::Content => Color32::from_rgb(32, 32, 32),
+            Theme::Light => Color32::from_rgb(192, 192, 192),
+            Theme::Disabled => Color32::from_rgb(110, 110, 110),
+            Theme::Selection => Color32::from_rgb(196, 196, 196),
+            Theme::Green => Color32::from_rgb(36, 128, 72),
+            Theme::Red => Color32::from_rgb(186, 52, 64),
+            Theme::Blue => Color32::from_rgb(24, 108, 168),
+            Theme::DarkBlue => Color32::from_rgb(150, 182, 204),
+            Theme::Cyan => Color32::from_rgb(0, 128, 140),
+            Theme::Orange => Color32::from_rgb(196, 104, 16),
+            Theme::Yellow => Color32::from_rgb(140, 112, 0),
+            Theme::Magenta => Color32::from_rgb(152, 64, 124),
+        },
+        ColorScheme::HighContrast => match theme {
+            Theme::Background => Color32::from_rgb(0, 0, 0),
+            Theme::BackgroundDark => Color32::from_rgb(0, 0, 0),
+            Theme::BackgroundWindow => Color32::from_rgb(24, 24, 24),
+            Theme::BackgroundHighlight => Color32::from_rgb(48, 48, 48),
+            Theme::Content => Color32::from_rgb(255, 255, 255),
+            Theme::Light => Color32::from_rgb(128, 128, 128),
+            Theme::Disabled => Color32::from_rgb(208, 208, 208),
+            Theme::Selection => Color32::from_rgb(96, 96, 96),
+            Theme::Green => Color32::from_rgb(64, 255, 128),
+            Theme::Red => Color32::from_rgb(255, 88, 88),
+            Theme::Blue => Color32::from_rgb(80, 200, 255),
+            Theme::DarkBlue => Color32::from_rgb(120, 168, 255),
+            Theme::Cyan => Color32::from_rgb(0, 255, 255),
+            Theme::Orange => Color32::from_rgb(255, 168, 0),
+            Theme::Yellow => Color32::from_rgb(255, 255, 0),
+            Theme::Magenta => Color32::from_rgb(255, 128, 255),
+        },
     }
 }
@@ -1,39 +1,88 @@
 mod analysis;
+mod bulk;
+mod correlation;
+mod orientation;
 mod scramble;
 mod session;
 mod solve;
 mod state;
 
 use crate::app::SolveDetails;
+use crate::font::FontSize;
 use crate::framerate::Framerate;
 use crate::gl::GlContext;
+use crate::input::{GamepadInput, InputBindings, TimerAction};
 use crate::settings::Settings;
+#[cfg(all(feature = "speech", not(target_arch = "wasm32")))]
+use crate::speech::ScrambleSpeech;
 use crate::style::{content_visuals, side_visuals};
+use crate::theme::Theme;
 use anyhow::Result;
+use bulk::BulkTimeEntryWindow;
 use chrono::Local;
-use egui::{Align, CentralPanel, CtxRef, Event, Key, Layout, Rect, Response, Sense, Ui, Vec2};
+use correlation::align_moves_to_timer;
+use egui::{
+    Align, Align2, CentralPanel, CtxRef, Event, Key, Layout, Pos2, Rect, Response, Sense, Ui, Vec2,
+};
 use instant::Instant;
+use orientation::OrientationTracker;
 use scramble::TimerCube;
-use session::TimerSession;
+use session::{goal_milestone_description, streak_event_description, TimerSession};
 use solve::{bluetooth_timer_ui, timer_ui};
 use state::TimerState;
+use std::rc::Rc;
+use std::time::Duration;
 use tpscube_core::{
-    Analysis, Cube, Cube3x3x3, CubeWithSolution, History, InitialCubeState, PartialAnalysis,
-    Penalty, Solve, SolveType, TimedMove,
+    Analysis, Cube, Cube3x3x3, CubeOrientation, CubeWithSolution, GoalMilestone, GoalTracker,
+    History, InitialCubeState, PartialAnalysis, Penalty, Solve, SolveInput, SolveType, StreakEvent,
+    StreakTracker, TimedMove,
 };
 
 pub struct TimerWidget {
     state: TimerState,
     session: TimerSession,
     cube: TimerCube,
+    external_trigger: bool,
+    touch_seen: bool,
+    swipe_origin: Option<Pos2>,
+    swipe_pos: Option<Pos2>,
+    inspection_penalty: Penalty,
+    orientation: OrientationTracker,
+    goals: GoalTracker,
+    goal_alert: Option<(GoalMilestone, Instant)>,
+    streak: StreakTracker,
+    streak_alert: Option<(StreakEvent, Instant)>,
+    /// Moves from a smart cube received while a smart timer is timing the
+    /// solve, with the time each move was received
+    timer_moves: Vec<(Instant, TimedMove)>,
+    bulk_entry: Option<BulkTimeEntryWindow>,
+    /// Input bindings loaded from the settings, reloaded when the settings
+    /// change
+    bindings: Rc<InputBindings>,
+    bindings_update_id: Option<u64>,
+    #[cfg(all(feature = "speech", not(target_arch = "wasm32")))]
+    speech: Option<ScrambleSpeech>,
 }
 
+/// Minimum distance in points that a touch must move to be treated as a swipe
+/// instead of a hold to start the timer
+const SWIPE_DISTANCE: f32 = 64.0;
+
+/// Time in milliseconds that a goal milestone alert is shown
+const GOAL_ALERT_TIME: u128 = 5000;
+
+/// Time in milliseconds that a practice streak reminder is shown. Reminders
+/// are also removed once the streak is no longer at risk.
+const STREAK_ALERT_TIME: u128 = 60000;
+
 pub enum BluetoothEvent {
     Move(TimedMove),
+    Orientation(CubeOrientation),
     HandsOnTimer,
     TimerStartCancel,
     TimerReady,
     TimerStarted,
+    TimerRunning(u32),
     TimerFinished(u32),
 }
 
@@ -43,92 +92,225 @@ impl TimerWidget {
             state: TimerState::Inactive(0, None),
             cube: TimerCube::new(),
             session: TimerSession::new(),
+            external_trigger: false,
+            touch_seen: false,
+            swipe_origin: None,
+            swipe_pos: None,
+            inspection_penalty: Penalty::None,
+            orientation: OrientationTracker::new(),
+            goals: GoalTracker::new(),
+            goal_alert: None,
+            streak: StreakTracker::new(),
+            streak_alert: None,
+            timer_moves: Vec::new(),
+            bulk_entry: None,
+            bindings: Rc::new(InputBindings::default()),
+            bindings_update_id: None,
+            #[cfg(all(feature = "speech", not(target_arch = "wasm32")))]
+            speech: None,
         }
     }
 
+    fn bindings(&mut self, history: &History) -> Rc<InputBindings> {
+        if self.bindings_update_id != Some(history.settings_update_id()) {
+            self.bindings = Rc::new(InputBindings::load(history));
+            self.bindings_update_id = Some(history.settings_update_id());
+        }
+        self.bindings.clone()
+    }
+
     pub fn is_solving(&self) -> bool {
         self.state.is_solving()
     }
 
-    fn finish_solve(&mut self, time: u32, history: &mut History, solve_type: SolveType) {
+    /// Large display mode shows the timer and scramble in much larger text
+    /// for visibility from a distance, such as when using a projector
+    pub fn large_display(history: &History) -> bool {
+        history.setting_as_bool("large_display").unwrap_or(false)
+    }
+
+    /// Signals a trigger from an external input source, such as a sound. This
+    /// acts like pressing and releasing the spacebar.
+    pub fn external_trigger(&mut self) {
+        self.external_trigger = true;
+    }
+
+    /// Time of a solve timed with the keyboard, with the calibrated timer
+    /// offset applied
+    fn keyboard_solve_time(start: Instant, history: &History) -> u32 {
+        let time = (Instant::now() - start).as_millis() as i64 + Settings::timer_offset(history);
+        time.max(0) as u32
+    }
+
+    /// Moves on to the next scramble after a solve is recorded. During a
+    /// competition round the scramble comes from the round, and the round is
+    /// recorded as an attempt once all of its solves are done.
+    fn solve_recorded(&mut self, history: &mut History, solve_id: String) {
+        if let Some(round) = self.session.competition_mut() {
+            round.add_solve(solve_id);
+            if let Some(scramble) = round.current_scramble() {
+                self.cube.use_scramble(scramble.to_vec());
+                return;
+            }
+            round.finish(history);
+            let _ = history.local_commit();
+            *self.session.competition_mut() = None;
+        }
+        self.cube.new_scramble();
+    }
+
+    fn finish_solve(
+        &mut self,
+        time: u32,
+        history: &mut History,
+        solve_type: SolveType,
+        input: Option<SolveInput>,
+    ) {
+        let id = Solve::new_id();
         history.new_solve(Solve {
-            id: Solve::new_id(),
+            id: id.clone(),
             solve_type,
             session: history.current_session().into(),
             scramble: self.cube.scramble().to_vec(),
             created: Local::now(),
             time,
-            penalty: Penalty::None,
-            device: None,
+            penalty: std::mem::replace(&mut self.inspection_penalty, Penalty::None),
+            device: input.map(|input| input.to_device_string()),
+            participant: history.next_participant(),
             moves: None,
+            skips: None,
         });
         let _ = history.local_commit();
         self.state = TimerState::SolveComplete(time, None);
-        self.cube.new_scramble();
+        self.solve_recorded(history, id);
     }
 
     fn finish_bluetooth_solve(
         &mut self,
         history: &mut History,
         moves: Vec<TimedMove>,
-        name: Option<String>,
+        input: Option<SolveInput>,
         solve_type: SolveType,
     ) {
-        // Sanity check move data and modify move timing to be relative to the start
-        // instead of relative to the prior move
-        let mut cube = Cube3x3x3::new();
-        cube.do_moves(self.cube.scramble());
-        let initial_state = cube.clone();
+        // Modify move timing to be relative to the start instead of relative to
+        // the prior move
         let mut final_moves = Vec::new();
         let mut time = 0;
         for mv in &moves {
-            cube.do_move(mv.move_());
             time += mv.time();
             final_moves.push(TimedMove::new(mv.move_(), time));
         }
-        let (moves, analysis) = if cube.is_solved() {
-            let analysis = Analysis::analyze(&CubeWithSolution {
-                initial_state,
-                solution: final_moves.clone(),
-            });
-            (Some(final_moves), Some(analysis))
-        } else {
-            (None, None)
+        let (moves, analysis) = self.reconstruct_solve(final_moves, solve_type);
+
+        let id = Solve::new_id();
+        history.new_solve(Solve {
+            id: id.clone(),
+            solve_type,
+            session: history.current_session().into(),
+            scramble: self.cube.scramble().to_vec(),
+            created: Local::now(),
+            time,
+            penalty: Penalty::None,
+            device: input.map(|input| input.to_device_string()),
+            participant: history.next_participant(),
+            moves,
+            skips: None,
+        });
+        let _ = history.local_commit();
+        self.state = TimerState::SolveComplete(time, analysis);
+        self.solve_recorded(history, id);
+    }
+
+    /// Finishes a solve timed by a smart timer while a smart cube is also
+    /// connected. The timer provides the official time, and the moves from the
+    /// cube are aligned to the timer to reconstruct the solve.
+    fn finish_combined_solve(
+        &mut self,
+        time: u32,
+        history: &mut History,
+        input: Option<SolveInput>,
+        solve_type: SolveType,
+    ) {
+        let start = match self.state {
+            TimerState::ExternalTimerSolving(start) => Some(start),
+            _ => None,
         };
+        let moves = align_moves_to_timer(&self.timer_moves, start, time);
+        self.timer_moves.clear();
+        let (moves, analysis) = self.reconstruct_solve(moves, solve_type);
 
+        let id = Solve::new_id();
         history.new_solve(Solve {
-            id: Solve::new_id(),
+            id: id.clone(),
             solve_type,
             session: history.current_session().into(),
             scramble: self.cube.scramble().to_vec(),
             created: Local::now(),
             time,
             penalty: Penalty::None,
-            device: name,
+            device: input.map(|input| input.to_device_string()),
+            participant: history.next_participant(),
             moves,
+            skips: None,
         });
         let _ = history.local_commit();
         self.state = TimerState::SolveComplete(time, analysis);
-        self.cube.new_scramble();
+        self.solve_recorded(history, id);
+    }
+
+    /// Sanity checks that moves, with times relative to the start of the solve,
+    /// solve the current scramble. Returns the moves and their analysis if so.
+    fn reconstruct_solve(
+        &self,
+        moves: Vec<TimedMove>,
+        solve_type: SolveType,
+    ) -> (Option<Vec<TimedMove>>, Option<Analysis>) {
+        let mut cube = Cube3x3x3::new();
+        cube.do_moves(self.cube.scramble());
+        let initial_state = cube.clone();
+        for mv in &moves {
+            cube.do_move(mv.move_());
+        }
+        if cube.is_solved() {
+            let analysis = Analysis::analyze_with_solve_type(
+                &CubeWithSolution {
+                    initial_state,
+                    solution: moves.clone(),
+                },
+                solve_type,
+            );
+            (Some(moves), Some(analysis))
+        } else {
+            (None, None)
+        }
     }
 
-    fn abort_solve(&mut self, time: u32, history: &mut History, solve_type: SolveType) {
+    fn abort_solve(
+        &mut self,
+        time: u32,
+        history: &mut History,
+        solve_type: SolveType,
+        input: Option<SolveInput>,
+    ) {
         if time > 2000 {
             // If some solve progress was made, add a DNF. Otherwise,
             // treat it as an accidental start.
+            let id = Solve::new_id();
             history.new_solve(Solve {
-                id: Solve::new_id(),
+                id: id.clone(),
                 solve_type,
                 session: history.current_session().into(),
                 scramble: self.cube.scramble().to_vec(),
                 created: Local::now(),
                 time,
                 penalty: Penalty::DNF,
-                device: None,
+                device: input.map(|input| input.to_device_string()),
+                participant: history.next_participant(),
                 moves: None,
+                skips: None,
             });
             let _ = history.local_commit();
-            self.cube.new_scramble();
+            self.solve_recorded(history, id);
         }
         self.state = TimerState::SolveComplete(0, None);
     }
@@ -140,8 +322,10 @@ impl TimerWidget {
         rect: &Rect,
         history: &mut History,
         bluetooth_events: Vec<BluetoothEvent>,
-        bluetooth_name: Option<String>,
+        bluetooth_input: Option<SolveInput>,
+        external_timer: bool,
         accept_keyboard: bool,
+        gamepad: &GamepadInput,
         solve_type: SolveType,
     ) -> Response {
         let id = ui.make_persistent_id("timer_input");
@@ -160,6 +344,7 @@ impl TimerWidget {
         for event in &bluetooth_events {
             match event {
                 BluetoothEvent::HandsOnTimer => {
+                    self.timer_moves.clear();
                     match self.state.clone() {
                         TimerState::Inactive(time, analysis) => {
                             self.state = TimerState::ExternalTimerPreparing(time, analysis);
@@ -187,26 +372,104 @@ impl TimerWidget {
                     self.state = TimerState::ExternalTimerSolving(Instant::now());
                     ctxt.request_repaint();
                 }
+                BluetoothEvent::TimerRunning(time) => {
+                    // Keep the displayed time in sync with the hardware timer
+                    if let TimerState::ExternalTimerSolving(_) = self.state {
+                        self.state = TimerState::ExternalTimerSolving(
+                            Instant::now() - Duration::from_millis(*time as u64),
+                        );
+                        ctxt.request_repaint();
+                    }
+                }
                 BluetoothEvent::TimerFinished(time) => {
-                    self.finish_solve(*time, history, solve_type);
-                    self.state = TimerState::SolveComplete(*time, None);
+                    if external_timer && self.cube.is_bluetooth_active() {
+                        self.finish_combined_solve(
+                            *time,
+                            history,
+                            bluetooth_input.clone(),
+                            solve_type,
+                        );
+                    } else {
+                        self.finish_solve(*time, history, solve_type, bluetooth_input.clone());
+                        self.state = TimerState::SolveComplete(*time, None);
+                    }
                     ctxt.request_repaint();
                 }
+                BluetoothEvent::Move(mv) if external_timer => match self.state {
+                    TimerState::ExternalTimerPreparing(_, _)
+                    | TimerState::ExternalTimerReady
+                    | TimerState::ExternalTimerSolving(_) => {
+                        self.timer_moves.push((Instant::now(), mv.clone()));
+                    }
+                    _ => (),
+                },
+                BluetoothEvent::Orientation(orientation) => self.orientation.update(*orientation),
                 _ => (),
             }
         }
 
-        // Check for user input to interact with the timer
-        let touching = crate::is_mobile() == Some(true)
-            && (interact.is_pointer_button_down_on() || interact.dragged());
+        // Check for user input to interact with the timer. Touch input is always
+        // enabled on mobile, and is enabled on other devices once a touch or pen
+        // event has been seen.
+        for event in &ctxt.input().events {
+            if let Event::Touch { .. } = event {
+                self.touch_seen = true;
+            }
+        }
+        let touch_enabled = crate::is_mobile() == Some(true) || self.touch_seen;
+        let touching =
+            touch_enabled && (interact.is_pointer_button_down_on() || interact.dragged());
+        let swipe = self.check_for_swipe(ctxt, &interact, touch_enabled);
+        let swiping = self.swiping();
+        let triggered = std::mem::replace(&mut self.external_trigger, false);
+        let bindings = self.bindings(history);
+        let pointer_in_timer = interact.hovered();
+        let start_down = accept_keyboard
+            && bindings.is_down(TimerAction::StartStop, ctxt, pointer_in_timer, gamepad);
+        let abort_down = bindings.is_down(TimerAction::Abort, ctxt, pointer_in_timer, gamepad);
+        let any_down =
+            ctxt.input().keys_down.len() != 0 || touching || start_down || gamepad.any_down();
+
+        if accept_keyboard && !self.state.is_solving() {
+            if bindings.is_pressed(TimerAction::LargeDisplay, ctxt, pointer_in_timer, gamepad) {
+                let _ = history.set_bool_setting("large_display", !Self::large_display(history));
+            }
+            self.check_for_last_solve_actions(ctxt, history, &bindings, pointer_in_timer, gamepad);
+            if let Some(action) = swipe {
+                self.last_solve_action(ctxt, history, action);
+            }
+        }
+
+        let preferences = history.preferences();
+        let hold_duration = preferences.hold_duration() as u128;
+        // Inspection is always used during competition rounds
+        let inspection = preferences.inspection() || self.session.competition().is_some();
+
         match self.state.clone() {
             TimerState::Inactive(time, analysis) => {
-                if accept_keyboard && (ctxt.input().keys_down.contains(&Key::Space) || touching) {
-                    self.state = TimerState::Preparing(Instant::now(), time, analysis);
+                if start_down || (accept_keyboard && touching && !swiping) {
+                    self.inspection_penalty = Penalty::None;
+                    if inspection {
+                        self.state = TimerState::Inspection(Instant::now(), false);
+                    } else {
+                        self.state = TimerState::Preparing(Instant::now(), time, analysis);
+                    }
+                } else if accept_keyboard && triggered {
+                    self.inspection_penalty = Penalty::None;
+                    if inspection {
+                        self.state = TimerState::Inspection(Instant::now(), true);
+                    } else {
+                        // External triggers are instantaneous, so there is no need to hold
+                        // before the timer is ready.
+                        self.state = TimerState::Ready;
+                    }
                 } else if self.cube.is_bluetooth_active() {
+                    // With a smart timer connected, the timer starts the solve
+                    // instead of the first move after the scramble
                     if self
                         .cube
                         .update_bluetooth_scramble_and_check_finish(&bluetooth_events)
+                        && !external_timer
                     {
                         if solve_type == SolveType::Blind3x3x3 {
                             // When solving in blind mode, start timer immediately when the
@@ -235,15 +498,42 @@ impl TimerWidget {
                 }
             }
             TimerState::Preparing(start, time, analysis) => {
-                if ctxt.input().keys_down.len() == 0 && !touching {
+                if !any_down || swiping {
                     self.state = TimerState::Inactive(time, analysis);
-                } else if (Instant::now() - start).as_millis() > 300 {
+                } else if (Instant::now() - start).as_millis() > hold_duration {
+                    self.state = TimerState::Ready;
+                }
+            }
+            TimerState::Inspection(start, released) => {
+                if abort_down {
+                    self.state = TimerState::Inactive(0, None);
+                } else if released && triggered {
+                    self.inspection_penalty = TimerState::inspection_penalty(start);
+                    self.state = TimerState::Ready;
+                } else if !any_down {
+                    self.state = TimerState::Inspection(start, true);
+                } else if released && (start_down || (touching && !swiping)) {
+                    self.state = TimerState::InspectionPreparing(start, Instant::now());
+                }
+            }
+            TimerState::InspectionPreparing(start, hold_start) => {
+                if !any_down || swiping {
+                    self.state = TimerState::Inspection(start, true);
+                } else if (Instant::now() - hold_start).as_millis() > hold_duration {
+                    self.inspection_penalty = TimerState::inspection_penalty(start);
                     self.state = TimerState::Ready;
                 }
             }
             TimerState::BluetoothPreparing(start, time, analysis) => {
                 if self.cube.is_bluetooth_active() {
-                    if bluetooth_events.len() != 0 {
+                    let moved = bluetooth_events.iter().any(|event| match event {
+                        BluetoothEvent::Move(_) => true,
+                        _ => false,
+                    });
+                    let waiting_for_rest = Settings::orientation_arming_enabled(history)
+                        && self.orientation.available()
+                        && !self.orientation.at_rest();
+                    if moved {
                         // For the first second after finishing a Bluetooth scramble,
                         // cause any extra moves to transition to the fix bad
                         // scramble state. This means that extra accidental turns
@@ -255,7 +545,10 @@ impl TimerWidget {
                         {
                             self.state = TimerState::Inactive(time, analysis);
                         }
-                    } else if (Instant::now() - start).as_millis() >= 1000 {
+                    } else if (Instant::now() - start).as_millis() >= 1000 && !waiting_for_rest {
+                        // If the cube reports its orientation, wait for it to be set
+                        // down flat before arming so that the first turn after
+                        // picking it up starts the timer.
                         self.state = TimerState::BluetoothReady;
                     }
                 } else {
@@ -263,12 +556,15 @@ impl TimerWidget {
                 }
             }
             TimerState::ExternalTimerPreparing(time, analysis) => {
-                if bluetooth_name.is_none() {
+                if bluetooth_input.is_none() {
                     self.state = TimerState::Inactive(time, analysis);
                 }
             }
             TimerState::Ready => {
-                if ctxt.input().keys_down.len() == 0 && !touching {
+                if swiping {
+                    // Touch moved too far to be a hold, treat it as a swipe instead
+                    self.state = TimerState::Inactive(0, None);
+                } else if !any_down {
                     self.state = TimerState::Solving(Instant::now());
                 }
             }
@@ -294,23 +590,25 @@ impl TimerWidget {
                 }
             }
             TimerState::ExternalTimerReady => {
-                if bluetooth_name.is_none() {
+                if bluetooth_input.is_none() {
                     self.state = TimerState::Inactive(0, None);
                 }
             }
             TimerState::Solving(start) => {
-                if ctxt.input().keys_down.contains(&Key::Escape) {
+                if abort_down {
                     self.abort_solve(
-                        (Instant::now() - start).as_millis() as u32,
+                        Self::keyboard_solve_time(start, history),
                         history,
                         solve_type,
+                        Some(SolveInput::Keyboard),
                     );
                     ctxt.request_repaint();
-                } else if ctxt.input().keys_down.len() != 0 || touching {
+                } else if any_down || triggered {
                     self.finish_solve(
-                        (Instant::now() - start).as_millis() as u32,
+                        Self::keyboard_solve_time(start, history),
                         history,
                         solve_type,
+                        Some(SolveInput::Keyboard),
                     );
                     ctxt.request_repaint();
                 }
@@ -323,25 +621,27 @@ impl TimerWidget {
                     }
                 }
 
-                if ctxt.input().keys_down.contains(&Key::Escape) {
+                if abort_down {
                     self.abort_solve(
                         (Instant::now() - start).as_millis() as u32,
                         history,
                         solve_type,
+                        bluetooth_input.clone(),
                     );
                     ctxt.request_repaint();
-                } else if ctxt.input().keys_down.len() != 0 || touching {
+                } else if any_down {
                     self.finish_solve(
                         (Instant::now() - start).as_millis() as u32,
                         history,
                         solve_type,
+                        bluetooth_input.clone(),
                     );
                     ctxt.request_repaint();
                 } else if bluetooth_moves.len() != 0 {
                     let mut moves = moves.clone();
                     moves.extend(bluetooth_moves);
                     if self.cube.is_solved() {
-                        self.finish_bluetooth_solve(history, moves, bluetooth_name, solve_type);
+                        self.finish_bluetooth_solve(history, moves, bluetooth_input, solve_type);
                         ctxt.request_repaint();
                     } else {
                         let mut cube = Cube3x3x3::new();
@@ -363,7 +663,7 @@ impl TimerWidget {
                 }
             }
             TimerState::ExternalTimerSolving(_) => {
-                if bluetooth_name.is_none() {
+                if bluetooth_input.is_none() {
                     self.state = TimerState::Inactive(0, None);
                 }
                 ctxt.request_repaint();
@@ -376,7 +676,7 @@ impl TimerWidget {
                         TimerState::ManualTimeEntryDelay(digits / 10, Some(Key::Backspace));
                 } else if ctxt.input().key_down(Key::Enter) {
                     let time = TimerState::digits_to_time(digits);
-                    self.finish_solve(time, history, solve_type);
+                    self.finish_solve(time, history, solve_type, Some(SolveInput::Manual));
                     self.state = TimerState::SolveComplete(time, None);
                 } else {
                     for event in &ctxt.input().events {
@@ -404,7 +704,7 @@ impl TimerWidget {
                 }
             }
             TimerState::SolveComplete(time, analysis) => {
-                if ctxt.input().keys_down.len() == 0 && !touching {
+                if !any_down {
                     self.state = TimerState::Inactive(time, analysis);
                     ctxt.request_repaint();
                 }
@@ -414,6 +714,127 @@ impl TimerWidget {
         interact
     }
 
+    fn check_for_last_solve_actions(
+        &mut self,
+        ctxt: &CtxRef,
+        history: &mut History,
+        bindings: &InputBindings,
+        pointer_in_timer: bool,
+        gamepad: &GamepadInput,
+    ) {
+        for action in &[
+            TimerAction::PlusTwo,
+            TimerAction::DNF,
+            TimerAction::DeleteLast,
+        ] {
+            if bindings.is_pressed(*action, ctxt, pointer_in_timer, gamepad) {
+                self.last_solve_action(ctxt, history, *action);
+                break;
+            }
+        }
+    }
+
+    fn last_solve_action(&mut self, ctxt: &CtxRef, history: &mut History, action: TimerAction) {
+        let last_solve = match history
+            .sessions()
+            .get(history.current_session())
+            .and_then(|session| session.iter(history).next_back())
+        {
+            Some(solve) => solve.id.clone(),
+            None => return,
+        };
+
+        match action {
+            TimerAction::PlusTwo => history.penalty(last_solve, Penalty::Time(2000)),
+            TimerAction::DNF => history.penalty(last_solve, Penalty::DNF),
+            TimerAction::DeleteLast => {
+                history.delete_solve(last_solve);
+                self.state = TimerState::Inactive(0, None);
+            }
+            _ => return,
+        }
+        let _ = history.local_commit();
+        ctxt.request_repaint();
+    }
+
+    /// Tracks touch drags on the timer and returns the action for a completed
+    /// swipe. Swiping right adds a +2 penalty to the last solve, swiping up
+    /// marks it as a DNF, and swiping left deletes it.
+    fn check_for_swipe(
+        &mut self,
+        ctxt: &CtxRef,
+        interact: &Response,
+        touch_enabled: bool,
+    ) -> Option<TimerAction> {
+        if !touch_enabled || self.state.is_solving() {
+            self.swipe_origin = None;
+            self.swipe_pos = None;
+            return None;
+        }
+
+        let pos = ctxt.input().pointer.interact_pos();
+        if interact.drag_started() {
+            self.swipe_origin = pos;
+            self.swipe_pos = pos;
+        } else if interact.dragged() {
+            if pos.is_some() {
+                self.swipe_pos = pos;
+            }
+        } else if interact.drag_released() {
+            let swiping = self.swiping();
+            let delta = match (self.swipe_origin.take(), self.swipe_pos.take()) {
+                (Some(origin), Some(pos)) => pos - origin,
+                _ => return None,
+            };
+            if !swiping {
+                return None;
+            }
+            return if delta.x.abs() > delta.y.abs() {
+                if delta.x > 0.0 {
+                    Some(TimerAction::PlusTwo)
+                } else {
+                    Some(TimerAction::DeleteLast)
+                }
+            } else if delta.y < 0.0 {
+                Some(TimerAction::DNF)
+            } else {
+                None
+            };
+        }
+        None
+    }
+
+    fn swiping(&self) -> bool {
+        match (self.swipe_origin, self.swipe_pos) {
+            (Some(origin), Some(pos)) => (pos - origin).length() >= SWIPE_DISTANCE,
+            _ => false,
+        }
+    }
+
+    #[cfg(all(feature = "speech", not(target_arch = "wasm32")))]
+    fn update_speech(&mut self, history: &History, is_solving: bool) {
+        if !Settings::speak_scramble_enabled(history) {
+            self.speech = None;
+            return;
+        }
+
+        if self.speech.is_none() {
+            self.speech = ScrambleSpeech::new().ok();
+        }
+        if let Some(speech) = &mut self.speech {
+            if is_solving {
+                // Don't keep reading the scramble once the solve has started
+                speech.stop();
+            } else {
+                let scramble = history
+                    .preferences()
+                    .scramble_orientation()
+                    .apply(self.cube.displayed_scramble());
+                let _ = speech.speak_if_changed(&scramble);
+            }
+        }
+    }
+
     fn check_for_expired_session(&mut self, history: &mut History, solve_type: SolveType) {
         self.session.check_solve_type(history, solve_type);
 
@@ -434,16 +855,21 @@ impl TimerWidget {
         history: &mut History,
         bluetooth_state: Option<Cube3x3x3>,
         bluetooth_events: Vec<BluetoothEvent>,
-        bluetooth_name: Option<String>,
+        bluetooth_input: Option<SolveInput>,
+        external_timer: bool,
         framerate: &mut Framerate,
         cube_rect: &mut Option<Rect>,
         details: &mut Option<SolveDetails>,
         accept_keyboard: bool,
+        gamepad: &GamepadInput,
         solve_type: &mut SolveType,
     ) {
         self.cube.check_for_new_scramble();
         self.cube
             .update_bluetooth_state(&bluetooth_state, &bluetooth_events);
+        if bluetooth_state.is_none() {
+            self.orientation.reset();
+        }
 
         if bluetooth_state.is_some() {
             // If Bluetooth cube is connected, force 3x3x3 solve type
@@ -455,14 +881,68 @@ impl TimerWidget {
         self.cube.check_solve_type(*solve_type);
         self.check_for_expired_session(history, *solve_type);
 
+        // Alert the user when a session goal milestone is reached
+        if let Some(milestone) = self.goals.update(history).pop() {
+            self.goal_alert = Some((milestone, Instant::now()));
+        }
+        if let Some((_, shown)) = &self.goal_alert {
+            if (Instant::now() - *shown).as_millis() > GOAL_ALERT_TIME {
+                self.goal_alert = None;
+            }
+        }
+
+        // Nudge the user when the practice streak is at risk or was extended
+        if let Some(event) = self.streak.update(history).pop() {
+            self.streak_alert = Some((event, Instant::now()));
+        }
+        if let Some((event, shown)) = &self.streak_alert {
+            let alert_time = match event {
+                StreakEvent::AtRisk { .. } => STREAK_ALERT_TIME,
+                StreakEvent::Extended(_) => GOAL_ALERT_TIME,
+            };
+            let resolved = match event {
+                StreakEvent::AtRisk { .. } => !self
+                    .streak
+                    .streak()
+                    .map(|streak| streak.at_risk())
+                    .unwrap_or(false),
+                StreakEvent::Extended(_) => false,
+            };
+            if resolved || (Instant::now() - *shown).as_millis() > alert_time {
+                self.streak_alert = None;
+            }
+        }
+
         ctxt.set_visuals(side_visuals());
         let aspect = ctxt.available_rect().width() / ctxt.available_rect().height();
-        if aspect >= 1.0 {
-            // Landscape mode. Session details to the left.
-            self.session.landscape_sidebar(ctxt, history, details);
-        } else {
-            // Portrait mode. Session details at the top.
-            self.session.portrait_top_bar(ctxt, history, details);
+        if !Self::large_display(history) {
+            // Session details are hidden in large display mode to give the timer
+            // the entire area
+            if aspect >= 1.0 {
+                // Landscape mode. Session details to the left.
+                self.session.landscape_sidebar(ctxt, history, details);
+            } else {
+                // Portrait mode. Session details at the top.
+                self.session.portrait_top_bar(ctxt, history, details);
+            }
+        }
+        if self.session.take_bulk_entry_request() {
+            self.bulk_entry = Some(BulkTimeEntryWindow::new());
+        }
+
+        // Typing in the time list or session note must not control the timer
+        let accept_keyboard =
+            accept_keyboard && self.bulk_entry.is_none() && !self.session.editing_note();
+
+        // Competition rounds use the scrambles that were fixed for the round
+        if let Some(scramble) = self
+            .session
+            .competition()
+            .and_then(|round| round.current_scramble())
+        {
+            if !self.state.is_solving() && self.cube.scramble() != scramble {
+                self.cube.use_scramble(scramble.to_vec());
+            }
         }
 
         ctxt.set_visuals(content_visuals());
@@ -474,7 +954,8 @@ impl TimerWidget {
                     let mut center = rect.center();
                     let is_solving = self.state.is_solving();
 
-                    if !is_solving {
+                    // Scrambles can't be replaced during a competition round
+                    if !is_solving && self.session.competition().is_none() {
                         // Draw new scramble button at top
                         self.cube.new_scramble_button(
                             if let TimerState::Inactive(_, _) = &self.state {
@@ -485,6 +966,7 @@ impl TimerWidget {
                             ui,
                             &mut rect,
                             &mut center,
+                            history,
                         );
                     }
 
@@ -496,8 +978,10 @@ impl TimerWidget {
                         &rect,
                         history,
                         bluetooth_events,
-                        bluetooth_name,
+                        bluetooth_input,
+                        external_timer,
                         accept_keyboard,
+                        gamepad,
                         *solve_type,
                     );
 
@@ -511,9 +995,15 @@ impl TimerWidget {
                                 &self.state,
                                 cube_rect,
                                 framerate,
+                                history.preferences().solving_time_display(),
                             );
                         } else {
-                            timer_ui(ui, &center, &self.state);
+                            timer_ui(
+                                ui,
+                                &center,
+                                &self.state,
+                                history.preferences().solving_time_display(),
+                            );
                         }
                     } else {
                         self.cube.scramble_ui(
@@ -524,8 +1014,44 @@ impl TimerWidget {
                             &self.state,
                             cube_rect,
                             framerate,
+                            history.preferences().scramble_colors(),
+                            // Competitors only see the scrambled cube, not a
+                            // diagram of it
+                            history.preferences().scramble_preview()
+                                && self.session.competition().is_none(),
+                            history.preferences().move_notation(),
+                            history.preferences().scramble_orientation(),
+                        );
+                    }
+
+                    let mut alert_offset = 4.0;
+                    if let Some((milestone, _)) = &self.goal_alert {
+                        let alert_rect = ui.painter().text(
+                            rect.left_top() + Vec2::new(8.0, alert_offset),
+                            Align2::LEFT_TOP,
+                            goal_milestone_description(milestone),
+                            FontSize::Section.into(),
+                            Theme::Green.into(),
                         );
+                        alert_offset += alert_rect.height() + 4.0;
                     }
+                    if let Some((event, _)) = &self.streak_alert {
+                        if !is_solving {
+                            ui.painter().text(
+                                rect.left_top() + Vec2::new(8.0, alert_offset),
+                                Align2::LEFT_TOP,
+                                streak_event_description(event),
+                                FontSize::Section.into(),
+                                match event {
+                                    StreakEvent::AtRisk { .. } => Theme::Yellow.into(),
+                                    StreakEvent::Extended(_) => Theme::Green.into(),
+                                },
+                            );
+                        }
+                    }
+
+                    #[cfg(all(feature = "speech", not(target_arch = "wasm32")))]
+                    self.update_speech(history, is_solving);
 
                     self.cube
                         .rotate_cube_with_input(ctxt, ui, cube_rect, interact);
@@ -533,14 +1059,29 @@ impl TimerWidget {
             });
         });
 
+        if let Some(bulk_entry) = &mut self.bulk_entry {
+            let mut open = true;
+            bulk_entry.update(ctxt, history, *solve_type, &mut open);
+            if !open {
+                self.bulk_entry = None;
+            }
+        }
+
         // Run at 10 FPS when solving (to update counting timer), or only when
         // updates occur otherwise
         match self.state {
             TimerState::Preparing(_, _, _)
+            | TimerState::Inspection(_, _)
+            | TimerState::InspectionPreparing(_, _)
             | TimerState::BluetoothPreparing(_, _, _)
             | TimerState::Solving(_)
             | TimerState::BluetoothSolving(_, _, _) => framerate.request(Some(10)),
-            _ => (),
+            _ => {
+                if self.goal_alert.is_some() {
+                    // Keep updating so that the alert is hidden when it expires
+                    framerate.request(Some(2));
+                }
+            }
         }
     }
 
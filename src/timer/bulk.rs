@@ -0,0 +1,99 @@
+use crate::style::dialog_visuals;
+use crate::theme::Theme;
+use crate::widgets::CustomWidgets;
+use egui::{CtxRef, Label, Sense, TextEdit, Window};
+use tpscube_core::{parse_time_list, History, SolveType};
+
+/// Window for entering many times at once, such as handwritten results. Each
+/// time is recorded into the current session with a generated scramble.
+pub struct BulkTimeEntryWindow {
+    text: String,
+    error: Option<String>,
+}
+
+impl BulkTimeEntryWindow {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        ctxt: &CtxRef,
+        history: &mut History,
+        solve_type: SolveType,
+        open: &mut bool,
+    ) {
+        ctxt.set_visuals(dialog_visuals());
+
+        let mut window_open = *open;
+        Window::new("Add Times")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut window_open)
+            .show(ctxt, |ui| {
+                ui.vertical(|ui| {
+                    ui.add(
+                        Label::new(
+                            "Enter or paste times separated by spaces, commas, or new lines. \
+                                Add + for a +2 penalty, or enter DNF.",
+                        )
+                        .text_color(Theme::Disabled)
+                        .wrap(true),
+                    );
+                    ui.add_space(4.0);
+                    if ui
+                        .add(TextEdit::multiline(&mut self.text).desired_rows(8))
+                        .changed()
+                    {
+                        self.error = None;
+                    }
+
+                    let parsed = parse_time_list(&self.text);
+                    match &parsed {
+                        Ok(times) => {
+                            ui.add(Label::new(format!(
+                                "{} time(s) will be added to the current {} session",
+                                times.len(),
+                                solve_type.to_string()
+                            )));
+                        }
+                        Err(error) => {
+                            ui.add(Label::new(error.to_string()).text_color(Theme::Red));
+                        }
+                    }
+                    if let Some(error) = &self.error {
+                        ui.add(Label::new(error).text_color(Theme::Red));
+                    }
+
+                    ui.section_separator();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(Label::new("➕  Add").sense(Sense::click()))
+                            .clicked()
+                        {
+                            match parsed {
+                                Ok(times) if times.len() != 0 => {
+                                    history.new_manual_solves(solve_type, &times);
+                                    match history.local_commit() {
+                                        Ok(_) => *open = false,
+                                        Err(error) => self.error = Some(error.to_string()),
+                                    }
+                                }
+                                Ok(_) => self.error = Some("No times entered".into()),
+                                Err(_) => (),
+                            }
+                        }
+                        if ui.add(Label::new("Cancel").sense(Sense::click())).clicked() {
+                            *open = false;
+                        }
+                    });
+                });
+            });
+        if !window_open {
+            *open = false;
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use instant::Instant;
+use tpscube_core::TimedMove;
+
+/// Aligns moves reported by a smart cube to the solve window measured by a
+/// smart timer, returning moves with times relative to the start of the solve.
+///
+/// Move times from the cube are relative to the previous move and use the
+/// cube's clock, while the only link between the two devices is when their
+/// notifications were received. The first move is placed at the time it was
+/// received relative to the timer start, and later moves keep the cube's
+/// relative timing. Times are clamped to the solve window so that moves made
+/// just before the timer started or stopped do not fall outside of it.
+pub fn align_moves_to_timer(
+    moves: &[(Instant, TimedMove)],
+    timer_start: Option<Instant>,
+    final_time: u32,
+) -> Vec<TimedMove> {
+    let mut time = match (moves.first(), timer_start) {
+        (Some((received, _)), Some(start)) if *received > start => {
+            (*received - start).as_millis() as u32
+        }
+        _ => 0,
+    };
+
+    let mut result = Vec::with_capacity(moves.len());
+    for (idx, (_, mv)) in moves.iter().enumerate() {
+        if idx != 0 {
+            time += mv.time();
+        }
+        result.push(TimedMove::new(mv.move_(), time.min(final_time)));
+    }
+    result
+}
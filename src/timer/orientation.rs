@@ -0,0 +1,68 @@
+use instant::Instant;
+use tpscube_core::CubeOrientation;
+
+/// Maximum angle in degrees between the cube's axes and vertical for the cube
+/// to be considered flat
+const FLAT_ANGLE: f32 = 12.0;
+
+/// Maximum angle in degrees the cube can move and still be stationary, to allow
+/// for sensor noise
+const STATIONARY_ANGLE: f32 = 3.0;
+
+/// Time in milliseconds the cube must be flat and stationary to be at rest
+const REST_TIME: u128 = 500;
+
+/// Tracks gyroscope reports from a smart cube to detect when it has been set
+/// down flat and is no longer moving.
+pub struct OrientationTracker {
+    reference: Option<CubeOrientation>,
+    rest_start: Option<Instant>,
+}
+
+impl OrientationTracker {
+    pub fn new() -> Self {
+        Self {
+            reference: None,
+            rest_start: None,
+        }
+    }
+
+    /// Returns true if the connected cube has reported its orientation. Cubes
+    /// without a gyroscope never do.
+    pub fn available(&self) -> bool {
+        self.reference.is_some()
+    }
+
+    pub fn update(&mut self, orientation: CubeOrientation) {
+        if orientation.tilt() > FLAT_ANGLE {
+            self.rest_start = None;
+            self.reference = Some(orientation);
+            return;
+        }
+
+        match (&self.reference, &self.rest_start) {
+            (Some(reference), Some(_)) if reference.angle_to(&orientation) <= STATIONARY_ANGLE => {
+                // Still resting at the same orientation. Don't update the reference
+                // so that slow drift is still detected as movement.
+            }
+            _ => {
+                self.reference = Some(orientation);
+                self.rest_start = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Returns true if the cube has been flat and stationary long enough to
+    /// arm the timer
+    pub fn at_rest(&self) -> bool {
+        match self.rest_start {
+            Some(start) => (Instant::now() - start).as_millis() >= REST_TIME,
+            None => false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.reference = None;
+        self.rest_start = None;
+    }
+}
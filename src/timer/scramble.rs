@@ -9,21 +9,33 @@ use crate::timer::BluetoothEvent;
 use crate::widgets::fit_scramble;
 use anyhow::Result;
 use egui::{CtxRef, Pos2, Rect, Response, Sense, Ui, Vec2};
+use std::collections::HashMap;
 use tpscube_core::{
-    scramble_2x2x2, scramble_3x3x3, Cube, Cube2x2x2, Cube3x3x3, InitialCubeState, Move,
-    MoveSequence, SolveType,
+    scramble_3x3x3, Cube, Cube3x3x3, GeneratedScrambles, GuidedSolve, GuidedSolveProgress, History,
+    InitialCubeState, Move, MoveNotation, MoveSequence, ScrambleImage, ScrambleOrientation,
+    ScrambleProvider, SolveType, SolvingTimeDisplay,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use tpscube_core::ScrambleFile;
+
 const TARGET_SCRAMBLE_FRACTION: f32 = 0.2;
 const TARGET_ANALYSIS_SCRAMBLE_FRACTION: f32 = 0.15;
 const TARGET_TIMER_FRACTION: f32 = 0.2;
 
 const NEW_SCRAMBLE_PADDING: f32 = 4.0;
+const GUIDE_BUTTON_SPACING: f32 = 24.0;
 
 const ANALYSIS_MIN_PADDING: f32 = 24.0;
 const ANALYSIS_MAX_PADDING: f32 = 64.0;
 const MAX_ANALYSIS_WIDTH: f32 = 360.0;
 
+/// Sticker size used when laying out the scramble preview. The diagram is
+/// scaled from this size to fit next to the scramble.
+const PREVIEW_STICKER_SIZE: u32 = 16;
+const PREVIEW_PADDING: f32 = 16.0;
+const MAX_PREVIEW_WIDTH_FRACTION: f32 = 0.25;
+
 pub struct TimerCube {
     current_scramble: Vec<Move>,
     current_scramble_displayed: bool,
@@ -34,7 +46,22 @@ pub struct TimerCube {
     scramble_move_index: Option<usize>,
     scramble_pending_move: Option<Move>,
     scramble_fix_moves: Vec<Move>,
+    guided: Option<GuidedSolve>,
+    guided_recomputed: bool,
     solve_type: SolveType,
+    saved_scrambles: HashMap<SolveType, SavedScrambles>,
+    provider: Box<dyn ScrambleProvider>,
+    /// Set when the scramble source is a file, which can be unloaded to go
+    /// back to generated scrambles
+    file_provider_active: bool,
+    scramble_file_error: Option<String>,
+}
+
+/// Scrambles of a puzzle that is not currently selected, kept so that switching
+/// back to the puzzle resumes the same scramble
+struct SavedScrambles {
+    current: Vec<Move>,
+    next: Option<Vec<Move>>,
 }
 
 enum ScrambleMoveResult {
@@ -62,7 +89,13 @@ impl TimerCube {
             scramble_move_index: None,
             scramble_pending_move: None,
             scramble_fix_moves: Vec::new(),
+            guided: None,
+            guided_recomputed: false,
             solve_type: SolveType::Standard3x3x3,
+            saved_scrambles: HashMap::new(),
+            provider: Box::new(GeneratedScrambles),
+            file_provider_active: false,
+            scramble_file_error: None,
         }
     }
 
@@ -70,16 +103,74 @@ impl TimerCube {
         &self.current_scramble
     }
 
+    /// Scramble as currently shown to the user. This differs from the
+    /// generated scramble when a Bluetooth cube starts out unsolved.
+    pub fn displayed_scramble(&self) -> &[Move] {
+        &self.displayed_scramble
+    }
+
     pub fn is_bluetooth_active(&self) -> bool {
         self.bluetooth_active
     }
 
-    fn generate_scramble(&self) -> Vec<Move> {
-        match self.solve_type {
-            SolveType::Standard2x2x2 => scramble_2x2x2(),
-            SolveType::Standard3x3x3 | SolveType::OneHanded3x3x3 | SolveType::Blind3x3x3 => {
-                scramble_3x3x3()
+    fn generate_scramble(&mut self) -> Vec<Move> {
+        // Fall back to generated scrambles when the provider runs out
+        match self.provider.next_scramble(self.solve_type) {
+            Some(scramble) => scramble,
+            None => GeneratedScrambles.next_scramble(self.solve_type).unwrap(),
+        }
+    }
+
+    /// Replaces the source of scrambles. Scrambles that were already taken
+    /// from the previous source are discarded.
+    pub fn set_scramble_provider(&mut self, provider: Box<dyn ScrambleProvider>) {
+        self.provider = provider;
+        self.next_scramble = None;
+        self.saved_scrambles.clear();
+        self.new_scramble();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_scramble_file(&mut self) {
+        let path = match tinyfiledialogs::open_file_dialog(
+            "Load Scrambles",
+            ".",
+            Some((&["*.txt"], "Scramble files")),
+        ) {
+            Some(path) => path,
+            None => return,
+        };
+        let name = std::path::Path::new(&path)
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Scramble file".into());
+        let file = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(ScrambleFile::parse(name, self.solve_type, &contents)?));
+        match file {
+            Ok(file) => {
+                self.scramble_file_error = None;
+                self.file_provider_active = true;
+                self.set_scramble_provider(Box::new(file));
             }
+            Err(error) => self.scramble_file_error = Some(error.to_string()),
+        }
+    }
+
+    /// Label of the button for choosing the scramble source
+    fn scramble_source_label(&self) -> Option<String> {
+        if cfg!(target_arch = "wasm32") {
+            None
+        } else if self.file_provider_active {
+            Some(format!(
+                "📄  {} ({} left)",
+                self.provider.name(),
+                self.provider.remaining(self.solve_type).unwrap_or(0)
+            ))
+        } else if self.scramble_file_error.is_some() {
+            Some("📄  Invalid scramble file".into())
+        } else {
+            Some("📄  Load scrambles".into())
         }
     }
 
@@ -102,6 +193,15 @@ impl TimerCube {
         }
     }
 
+    /// Replaces the current scramble with a given one, such as a scramble that
+    /// was skipped earlier. The upcoming scramble is kept for afterwards.
+    pub fn use_scramble(&mut self, scramble: Vec<Move>) {
+        let next = self.next_scramble.take();
+        self.next_scramble = Some(scramble);
+        self.new_scramble();
+        self.next_scramble = next;
+    }
+
     pub fn display_scramble_from_current_state(&mut self) {
         if self.bluetooth_active {
             let state = self.renderer.cube_state();
@@ -137,6 +237,7 @@ impl TimerCube {
 
     pub fn bluetooth_lost(&mut self) {
         self.bluetooth_active = false;
+        self.guided = None;
         self.scramble_move_index = None;
         self.scramble_pending_move = None;
         self.scramble_fix_moves.clear();
@@ -147,6 +248,51 @@ impl TimerCube {
         self.renderer.reset_angle();
     }
 
+    /// Returns true if a guided solve back to the solved state can be started or
+    /// is in progress. This is only offered when a Bluetooth cube is connected.
+    fn guided_solve_available(&self) -> bool {
+        self.bluetooth_active && (self.guided.is_some() || !self.renderer.is_solved())
+    }
+
+    fn toggle_guided_solve(&mut self) {
+        if self.guided.is_some() {
+            self.guided = None;
+            self.display_scramble_from_current_state();
+            return;
+        }
+
+        // The renderer only exposes the generic cube state, so reconstruct the
+        // 3x3x3 state from the inverse of its solution
+        let solution = match self.renderer.cube_state().solve_fast() {
+            Some(solution) => solution,
+            None => return,
+        };
+        let mut cube = Cube3x3x3::new();
+        cube.do_moves(&solution.inverse());
+        self.guided = Some(GuidedSolve::new(cube));
+        self.guided_recomputed = false;
+    }
+
+    /// Verifies Bluetooth moves against the guided solve. Once the cube is solved
+    /// the guide is closed and the scramble is shown again.
+    fn apply_bluetooth_moves_for_guided_solve(&mut self, events: &[BluetoothEvent]) {
+        if let Some(guided) = &mut self.guided {
+            for event in events {
+                if let BluetoothEvent::Move(mv) = event {
+                    match guided.do_move(mv.move_()) {
+                        GuidedSolveProgress::OnTrack => (),
+                        GuidedSolveProgress::Recomputed => self.guided_recomputed = true,
+                        GuidedSolveProgress::Solved => {
+                            self.guided = None;
+                            self.display_scramble_from_current_state();
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     fn apply_bluetooth_move_for_expected_move(
         &mut self,
         mv: Move,
@@ -244,13 +390,53 @@ impl TimerCube {
             }
         }
 
-        // If the fix path gets long, redo the scramble to get a correct result from
-        // whatever the current state is.
+        // If the fix path gets long, use the solver to find a shorter path back to
+        // the expected state for the current point in the scramble.
         if self.scramble_fix_moves.len() > 3 {
-            self.display_scramble_from_current_state();
+            self.correct_scramble_from_current_state();
+        }
+    }
+
+    /// Computes the moves needed to bring the cube from its current state to the
+    /// state expected after the first `index` moves of the displayed scramble.
+    fn correction_moves(&self, index: usize) -> Option<Vec<Move>> {
+        // Moves act on the cube the same way regardless of the starting state, so
+        // undoing the current state and then performing the partial scramble gives
+        // the required correction as a single relative state.
+        let to_solved = self.renderer.cube_state().solve_fast()?;
+        let mut relative = Cube3x3x3::new();
+        relative.do_moves(&to_solved);
+        relative.do_moves(&self.displayed_scramble[0..index]);
+        Some(relative.solve()?.inverse())
+    }
+
+    /// Replaces the pending fix moves with a solver computed correction, keeping
+    /// the progress made in the scramble so far.
+    fn correct_scramble_from_current_state(&mut self) {
+        let index = match self.scramble_move_index {
+            Some(index) => index.min(self.displayed_scramble.len()),
+            None => return,
+        };
+        match self.correction_moves(index) {
+            Some(moves) => {
+                // Fix moves are stored in reverse, with the next expected move last
+                self.scramble_fix_moves = moves.iter().rev().cloned().collect();
+                self.scramble_pending_move = None;
+                self.scramble_move_index = Some(index);
+            }
+            None => self.display_scramble_from_current_state(),
         }
     }
 
+    /// Checks that the cube is in the scrambled state. Move tracking can miss a
+    /// move if the connection drops events, so the final state is compared before
+    /// the timer is armed.
+    fn verify_scrambled_state(&self) -> bool {
+        let mut expected = Cube3x3x3::new();
+        expected.do_moves(&self.displayed_scramble);
+        self.renderer.cube_state().colors() == expected.colors()
+    }
+
     pub fn check_for_new_scramble(&mut self) {
         // Generate a scramble when the current one is onscreen. The slight delay will
         // not be noticed as much when performing a new scramble.
@@ -285,13 +471,20 @@ impl TimerCube {
         &mut self,
         bluetooth_events: &[BluetoothEvent],
     ) -> bool {
-        if self.bluetooth_active {
+        if self.bluetooth_active && self.guided.is_some() {
+            self.apply_bluetooth_moves_for_guided_solve(bluetooth_events);
+        } else if self.bluetooth_active {
             self.apply_bluetooth_moves_for_scramble(bluetooth_events);
             if let Some(move_index) = self.scramble_move_index {
                 if move_index >= self.displayed_scramble.len() && self.scramble_fix_moves.len() == 0
                 {
-                    // Scramble complete, get ready to transition to solving
-                    return true;
+                    if self.verify_scrambled_state() {
+                        // Scramble complete, get ready to transition to solving
+                        return true;
+                    }
+
+                    // Cube doesn't match the scramble, show the moves to correct it
+                    self.correct_scramble_from_current_state();
                 }
             }
         }
@@ -308,14 +501,53 @@ impl TimerCube {
         ui: &mut Ui,
         rect: &mut Rect,
         center: &mut Pos2,
+        history: &mut History,
     ) {
         let scramble_galley = ui
             .fonts()
             .layout_single_line(FontSize::Small.into(), "↺  New scramble".into());
-        let new_scramble_rect = Rect::from_center_size(
+
+        // When scrambles were skipped earlier, offer to go back to them
+        let skipped_count = history.skipped_scramble_count(self.solve_type);
+        let skipped_galley = if skipped_count != 0 {
+            Some(ui.fonts().layout_single_line(
+                FontSize::Small.into(),
+                format!("⤺  Skipped ({})", skipped_count),
+            ))
+        } else {
+            None
+        };
+
+        // When a Bluetooth cube is in an unsolved state, offer to guide the user
+        // back to the solved state next to the new scramble button
+        let guide_galley = if self.guided_solve_available() {
+            Some(ui.fonts().layout_single_line(
+                FontSize::Small.into(),
+                if self.guided.is_some() {
+                    "✖  Stop guide".into()
+                } else {
+                    "🧭  Guide to solved".into()
+                },
+            ))
+        } else {
+            None
+        };
+        let source_galley = self
+            .scramble_source_label()
+            .map(|label| ui.fonts().layout_single_line(FontSize::Small.into(), label));
+        let mut total_width = scramble_galley.size.x;
+        for galley in skipped_galley
+            .iter()
+            .chain(guide_galley.iter())
+            .chain(source_galley.iter())
+        {
+            total_width += GUIDE_BUTTON_SPACING + galley.size.x;
+        }
+
+        let new_scramble_rect = Rect::from_min_size(
             Pos2::new(
-                rect.center().x,
-                rect.top() + NEW_SCRAMBLE_PADDING + scramble_galley.size.y / 2.0,
+                rect.center().x - total_width / 2.0,
+                rect.top() + NEW_SCRAMBLE_PADDING,
             ),
             scramble_galley.size,
         );
@@ -334,11 +566,108 @@ impl TimerCube {
             },
         );
 
-        // Check for new scramble clicks
+        // Check for new scramble clicks. The current scramble is recorded as
+        // skipped so that it can be served again later.
         if interact.clicked() && active {
+            history.record_skipped_scramble(self.solve_type, &self.current_scramble);
             self.new_scramble();
         }
 
+        let mut next_left = new_scramble_rect.right() + GUIDE_BUTTON_SPACING;
+        if let Some(skipped_galley) = skipped_galley {
+            let skipped_rect = Rect::from_min_size(
+                Pos2::new(next_left, new_scramble_rect.top()),
+                skipped_galley.size,
+            );
+            next_left = skipped_rect.right() + GUIDE_BUTTON_SPACING;
+            let interact = ui.allocate_rect(skipped_rect, Sense::click());
+            ui.painter().galley(
+                skipped_rect.left_top(),
+                skipped_galley,
+                if active {
+                    if interact.hovered() {
+                        Theme::Red.into()
+                    } else {
+                        Theme::Disabled.into()
+                    }
+                } else {
+                    Theme::Light.into()
+                },
+            );
+
+            if interact.clicked() && active {
+                if let Some(scramble) = history.take_skipped_scramble(self.solve_type) {
+                    history.record_skipped_scramble(self.solve_type, &self.current_scramble);
+                    self.use_scramble(scramble);
+                }
+            }
+        }
+
+        if let Some(guide_galley) = guide_galley {
+            let guide_rect = Rect::from_min_size(
+                Pos2::new(next_left, new_scramble_rect.top()),
+                guide_galley.size,
+            );
+            let interact = ui.allocate_rect(guide_rect, Sense::click());
+            ui.painter().galley(
+                guide_rect.left_top(),
+                guide_galley,
+                if active {
+                    if interact.hovered() {
+                        Theme::Red.into()
+                    } else {
+                        Theme::Disabled.into()
+                    }
+                } else {
+                    Theme::Light.into()
+                },
+            );
+
+            next_left = guide_rect.right() + GUIDE_BUTTON_SPACING;
+            if interact.clicked() && active {
+                self.toggle_guided_solve();
+            }
+        }
+
+        if let Some(source_galley) = source_galley {
+            let source_rect = Rect::from_min_size(
+                Pos2::new(next_left, new_scramble_rect.top()),
+                source_galley.size,
+            );
+            let mut interact = ui.allocate_rect(source_rect, Sense::click());
+            ui.painter().galley(
+                source_rect.left_top(),
+                source_galley,
+                if active {
+                    if interact.hovered() {
+                        Theme::Red.into()
+                    } else if self.scramble_file_error.is_some() {
+                        Theme::Orange.into()
+                    } else {
+                        Theme::Disabled.into()
+                    }
+                } else {
+                    Theme::Light.into()
+                },
+            );
+            if let Some(error) = &self.scramble_file_error {
+                interact = interact.on_hover_text(error);
+            } else if self.file_provider_active {
+                interact = interact.on_hover_text("Go back to generated scrambles");
+            }
+
+            // Clicking unloads the current scramble file, or loads a new one
+            if interact.clicked() && active {
+                if self.file_provider_active {
+                    self.file_provider_active = false;
+                    self.set_scramble_provider(Box::new(GeneratedScrambles));
+                } else {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    self.load_scramble_file();
+                }
+            }
+        }
+
         // Adjust remaining rectangle to remove new scramble button area
         let top_left = Pos2::new(
             rect.left(),
@@ -360,6 +689,10 @@ impl TimerCube {
         state: &TimerState,
         cube_rect: &mut Option<Rect>,
         framerate: &mut Framerate,
+        colors: bool,
+        preview: bool,
+        notation: MoveNotation,
+        orientation: ScrambleOrientation,
     ) {
         let analysis = if let Some(analysis) = state.analysis() {
             if aspect >= 1.0 {
@@ -382,7 +715,47 @@ impl TimerCube {
 
         let scramble_padding = 8.0;
 
-        let (fix, scramble) = if self.bluetooth_active && self.scramble_fix_moves.len() > 0 {
+        // Show a diagram of the scrambled state to the right of the scramble. This
+        // is only shown in landscape layouts, where there is room beside it.
+        let fixing = self.bluetooth_active && self.scramble_fix_moves.len() > 0;
+        let preview = if preview && aspect >= 1.0 && self.guided.is_none() && !fixing {
+            Some(ScrambleImage::from_scramble(
+                self.solve_type,
+                &self.displayed_scramble,
+            ))
+        } else {
+            None
+        };
+        let (preview_scale, preview_size) = match &preview {
+            Some(image) => {
+                let width = image.width(PREVIEW_STICKER_SIZE) as f32;
+                let height = image.height(PREVIEW_STICKER_SIZE) as f32;
+                let scale = (target_scramble_height / height)
+                    .min(rect.width() * MAX_PREVIEW_WIDTH_FRACTION / width);
+                (scale, Vec2::new(width * scale, height * scale))
+            }
+            None => (0.0, Vec2::new(0.0, 0.0)),
+        };
+        let (scramble_width, scramble_center_x) = if preview.is_some() {
+            let width = rect.width() - preview_size.x - PREVIEW_PADDING * 2.0;
+            (width, rect.left() + width / 2.0)
+        } else {
+            (rect.width(), center.x)
+        };
+
+        let guided = self.guided.is_some();
+        let (fix, scramble) = if let Some(guided) = &self.guided {
+            // Guided solve shows a header line followed by the remaining solution
+            let mut lines = vec![vec![]];
+            lines.extend(fit_scramble(
+                ui,
+                FontSize::Scramble,
+                guided.remaining_moves(),
+                notation,
+                rect.width(),
+            ));
+            (false, lines)
+        } else if self.bluetooth_active && self.scramble_fix_moves.len() > 0 {
             (
                 true,
                 vec![
@@ -400,8 +773,9 @@ impl TimerCube {
                 fit_scramble(
                     ui,
                     FontSize::Scramble,
-                    &self.displayed_scramble,
-                    rect.width(),
+                    &orientation.apply(&self.displayed_scramble),
+                    notation,
+                    scramble_width,
                 ),
             )
         };
@@ -433,38 +807,72 @@ impl TimerCube {
             // Layout individual moves in the scramble
             let mut tokens = Vec::new();
             if fix && line_idx == 0 {
-                tokens.push(ui.fonts().layout_single_line(
-                    FontSize::Scramble.into(),
-                    "Scramble incorrect, fix with".into(),
+                tokens.push((
+                    ui.fonts().layout_single_line(
+                        FontSize::Scramble.into(),
+                        "Scramble incorrect, fix with".into(),
+                    ),
+                    None,
                 ));
-            } else {
-                for (idx, mv) in line.iter().enumerate() {
-                    tokens.push(ui.fonts().layout_single_line(
+            } else if guided && line_idx == 0 {
+                tokens.push((
+                    ui.fonts().layout_single_line(
                         FontSize::Scramble.into(),
-                        if idx == 0 {
-                            mv.to_string()
+                        if self.guided_recomputed {
+                            "Off track, solve with".into()
                         } else {
-                            format!("  {}", mv.to_string())
+                            "Return to solved with".into()
                         },
+                    ),
+                    None,
+                ));
+            } else {
+                for (idx, mv) in line.iter().enumerate() {
+                    tokens.push((
+                        ui.fonts().layout_single_line(
+                            FontSize::Scramble.into(),
+                            if idx == 0 {
+                                mv.to_string_with(notation)
+                            } else {
+                                format!("  {}", mv.to_string_with(notation))
+                            },
+                        ),
+                        Some(*mv),
                     ));
                 }
             }
 
             // Determine line width and center on screen
-            let line_width = tokens.iter().fold(0.0, |sum, token| sum + token.size.x);
-            let mut x = center.x - line_width / 2.0;
+            let line_width = tokens
+                .iter()
+                .fold(0.0, |sum, (token, _)| sum + token.size.x);
+            let mut x = scramble_center_x - line_width / 2.0;
 
             // Render individual moves
-            for token in tokens {
+            for (token, mv) in tokens {
                 let width = token.size.x;
                 ui.painter().galley(
                     Pos2::new(x, y),
                     token,
-                    if !fix
+                    if guided {
+                        // Highlight the next move of the guided solve
+                        match mv {
+                            Some(mv) if move_idx == 0 && colors => Theme::face(mv.face()),
+                            Some(_) if move_idx == 0 => Theme::Blue.into(),
+                            Some(_) => Theme::Light.into(),
+                            None if self.guided_recomputed => Theme::Red.into(),
+                            None => Theme::Disabled.into(),
+                        }
+                    } else if !fix
                         && (self.scramble_move_index.is_none()
                             || Some(move_idx) == self.scramble_move_index)
                     {
-                        Theme::Blue.into()
+                        // Scramble moves are rewritten for the orientation, so
+                        // color them by the center of the face that is turned
+                        match mv {
+                            Some(mv) if colors => Theme::face(orientation.color(mv.face()).face()),
+                            _ => Theme::Blue.into(),
+                        }
                     } else if fix && (line_idx == 0 || move_idx == 0) {
                         Theme::Red.into()
                     } else {
@@ -473,7 +881,7 @@ impl TimerCube {
                 );
 
                 x += width;
-                if !fix || line_idx != 0 {
+                if (!fix && !guided) || line_idx != 0 {
                     move_idx += 1;
                 }
             }
@@ -482,6 +890,26 @@ impl TimerCube {
         }
         self.current_scramble_displayed = true;
 
+        // Render scramble preview
+        if let Some(image) = &preview {
+            let origin = Pos2::new(
+                rect.right() - PREVIEW_PADDING - preview_size.x,
+                rect.top() + scramble_padding + (scramble_height - preview_size.y) / 2.0,
+            );
+            let sticker_size = PREVIEW_STICKER_SIZE as f32 * preview_scale;
+            image.for_each_sticker(PREVIEW_STICKER_SIZE, |x, y, color| {
+                let sticker = Rect::from_min_size(
+                    origin + Vec2::new(x as f32, y as f32) * preview_scale,
+                    Vec2::new(sticker_size, sticker_size),
+                );
+                ui.painter().rect_filled(
+                    sticker.shrink(preview_scale),
+                    preview_scale,
+                    Theme::sticker(color),
+                );
+            });
+        }
+
         // Allocate space for the cube rendering. This is 3D so it will be rendered
         // with OpenGL after egui is done painting.
         let computed_cube_rect = Rect::from_min_size(
@@ -496,9 +924,11 @@ impl TimerCube {
         }
 
         // Layout timer
-        let timer_galley = ui
-            .fonts()
-            .layout_single_line(FontSize::Timer.into(), state.current_time_string());
+        let timer_galley = ui.fonts().layout_single_line(
+            FontSize::Timer.into(),
+            // The scramble is only shown while not solving
+            state.current_time_string(SolvingTimeDisplay::default()),
+        );
         let timer_width = timer_galley.size.x;
 
         // Determine target width of analysis region
@@ -608,15 +1038,25 @@ impl TimerCube {
             return;
         }
 
+        self.saved_scrambles.insert(
+            self.solve_type,
+            SavedScrambles {
+                current: std::mem::take(&mut self.current_scramble),
+                next: self.next_scramble.take(),
+            },
+        );
         self.solve_type = solve_type;
 
-        self.renderer = match solve_type {
-            SolveType::Standard2x2x2 => CubeRenderer::new(Box::new(Cube2x2x2::new())),
-            SolveType::Standard3x3x3 | SolveType::OneHanded3x3x3 | SolveType::Blind3x3x3 => {
-                CubeRenderer::new(Box::new(Cube3x3x3::new()))
+        self.renderer = CubeRenderer::new(solve_type.new_cube());
+
+        // Resume the scramble this puzzle had when it was last selected
+        match self.saved_scrambles.remove(&solve_type) {
+            Some(saved) => {
+                self.next_scramble = Some(saved.current);
+                self.new_scramble();
+                self.next_scramble = saved.next;
             }
-        };
-        self.next_scramble = None;
-        self.new_scramble();
+            None => self.new_scramble(),
+        }
     }
 }
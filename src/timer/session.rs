@@ -1,18 +1,36 @@
 use crate::app::SolveDetails;
 use crate::font::{FontSize, LabelFontSize};
+use crate::settings::Settings;
 use crate::theme::Theme;
 use crate::widgets::{solve_time_string, CustomWidgets};
 use chrono::{DateTime, Local};
 use egui::{
-    popup_below_widget, Align2, CtxRef, CursorIcon, Label, Layout, ScrollArea, SelectableLabel,
-    Sense, SidePanel, Stroke, TopBottomPanel, Ui, Vec2,
+    popup_below_widget, Align2, CtxRef, CursorIcon, Label, Layout, Rect, ScrollArea,
+    SelectableLabel, Sense, SidePanel, Stroke, TextEdit, TopBottomPanel, Ui, Vec2,
 };
+use std::collections::HashMap;
 use tpscube_core::{
-    Average, BestSolve, History, ListAverage, Penalty, Solve, SolveList, SolveType,
+    detect_anomalies, detect_warmup, goal_progress, AnomalyKind, Average, BestSolve,
+    CompetitionRound, GoalMilestone, GoalMilestoneKind, History, ListAverage, Penalty, SessionGoal,
+    ShapeMod, Solve, SolveList, SolveType, StreakEvent,
 };
 
+/// Target average of 12 times offered as goals, in milliseconds
+const AVERAGE_GOAL_CHOICES: [u32; 10] = [
+    8000, 10000, 12000, 15000, 20000, 25000, 30000, 40000, 60000, 90000,
+];
+
+/// Solve counts offered as goals
+const SOLVE_COUNT_GOAL_CHOICES: [usize; 5] = [12, 25, 50, 100, 200];
+
+/// Practice durations offered as goals, in minutes
+const DURATION_GOAL_CHOICES: [u32; 5] = [10, 15, 30, 60, 90];
+
+const GOAL_BAR_HEIGHT: f32 = 4.0;
+
 pub struct TimerSession {
     update_id: Option<u64>,
+    solve_type: Option<SolveType>,
     solves: Vec<Solve>,
     last_ao5: Option<Average>,
     last_ao12: Option<Average>,
@@ -20,6 +38,13 @@ pub struct TimerSession {
     best_solve: Option<BestSolve>,
     best_ao5: Option<Average>,
     best_ao12: Option<Average>,
+    anomalies: HashMap<String, AnomalyKind>,
+    warmup_count: usize,
+    warmup_excluded: bool,
+    bulk_entry_requested: bool,
+    competition: Option<CompetitionRound>,
+    note_edit: Option<String>,
+    participant_edit: Option<String>,
 }
 
 enum SessionTime {
@@ -32,6 +57,7 @@ impl TimerSession {
     pub fn new() -> Self {
         Self {
             update_id: None,
+            solve_type: None,
             solves: Vec::new(),
             last_ao5: None,
             last_ao12: None,
@@ -39,29 +65,64 @@ impl TimerSession {
             best_solve: None,
             best_ao5: None,
             best_ao12: None,
+            anomalies: HashMap::new(),
+            warmup_count: 0,
+            warmup_excluded: false,
+            bulk_entry_requested: false,
+            competition: None,
+            note_edit: None,
+            participant_edit: None,
         }
     }
 
     pub fn check_solve_type(&mut self, history: &mut History, solve_type: SolveType) {
-        // If solve type no longer matches session's, create a new session to hold the
-        // new type of solves
-        if let Some(session) = history.sessions().get(history.current_session()) {
-            if session.solve_type() != solve_type {
-                self.new_session(history);
-            }
+        // When the puzzle changes, or the session no longer matches it, resume the last
+        // session of the puzzle or create a new session to hold the new type of solves
+        let mismatched = match history.sessions().get(history.current_session()) {
+            Some(session) => session.solve_type() != solve_type,
+            None => false,
+        };
+        if mismatched || self.solve_type != Some(solve_type) {
+            self.solve_type = Some(solve_type);
+            self.competition = None;
+            history.select_solve_type(solve_type);
+            self.update(history);
         }
     }
 
-    fn from_solves(update_id: Option<u64>, solves: Vec<Solve>) -> Self {
-        let last_ao5 = solves.as_slice().last_average(5);
-        let last_ao12 = solves.as_slice().last_average(12);
-        let session_avg = solves.as_slice().average();
-        let best_solve = solves.as_slice().best();
-        let best_ao5 = solves.as_slice().best_average(5);
-        let best_ao12 = solves.as_slice().best_average(12);
+    fn from_solves(
+        update_id: Option<u64>,
+        solve_type: Option<SolveType>,
+        solves: Vec<Solve>,
+        exclude_warmup: bool,
+    ) -> Self {
+        let times: Vec<Option<u32>> = solves.iter().map(|solve| solve.final_time()).collect();
+        let warmup_count = detect_warmup(&times);
+
+        // Warmup solves are left out of the averages and bests if requested,
+        // but are still shown in the solve list
+        let counted = if exclude_warmup {
+            &solves[warmup_count..]
+        } else {
+            solves.as_slice()
+        };
+        let last_ao5 = counted.last_average(5);
+        let last_ao12 = counted.last_average(12);
+        let session_avg = counted.average();
+        let best_solve = counted.best();
+        let best_ao5 = counted.best_average(5);
+        let best_ao12 = counted.best_average(12);
+
+        // Flag solves that look like misfires or timers left running so that
+        // they can be reviewed
+        let anomalies = detect_anomalies(&times)
+            .iter()
+            .map(|anomaly| (solves[anomaly.index].id.clone(), anomaly.kind))
+            .collect();
 
         Self {
             update_id,
+            solve_type,
             solves,
             last_ao5,
             last_ao12,
@@ -69,27 +130,58 @@ impl TimerSession {
             best_solve,
             best_ao5,
             best_ao12,
+            anomalies,
+            warmup_count,
+            warmup_excluded: exclude_warmup,
+            bulk_entry_requested: false,
+            competition: None,
+            note_edit: None,
+            participant_edit: None,
         }
     }
 
     fn update(&mut self, history: &History) {
+        let exclude_warmup = Settings::exclude_warmup_enabled(history);
         if let Some(session) = history.sessions().get(history.current_session()) {
             // Check for updates
             if let Some(update_id) = self.update_id {
-                if update_id == session.update_id() {
+                if update_id == session.update_id() && exclude_warmup == self.warmup_excluded {
                     // Already cached and up to date
                     return;
                 }
             }
 
             // Cache solve information
-            *self = Self::from_solves(Some(session.update_id()), session.to_vec(history));
+            let cached = Self::from_solves(
+                Some(session.update_id()),
+                self.solve_type,
+                session.to_vec(history),
+                exclude_warmup,
+            );
+            self.replace_cache(cached);
         } else {
             // New session, invalidate cache
-            *self = Self::from_solves(None, Vec::new());
+            let cached = Self::from_solves(None, self.solve_type, Vec::new(), exclude_warmup);
+            self.replace_cache(cached);
         }
     }
 
+    /// Replaces the cached solve information while keeping the state of the
+    /// session controls, such as a competition round in progress
+    fn replace_cache(&mut self, cached: Self) {
+        let bulk_entry_requested = self.bulk_entry_requested;
+        let competition = self.competition.take();
+        let note_edit = self.note_edit.take();
+        let participant_edit = self.participant_edit.take();
+        *self = Self {
+            bulk_entry_requested,
+            competition,
+            note_edit,
+            participant_edit,
+            ..cached
+        };
+    }
+
     fn session_time(
         ui: &mut Ui,
         name: &str,
@@ -161,6 +253,7 @@ impl TimerSession {
         ui: &mut Ui,
         idx: usize,
         solve: &Solve,
+        anomaly: Option<AnomalyKind>,
         history: &mut History,
         details: &mut Option<SolveDetails>,
     ) {
@@ -171,6 +264,15 @@ impl TimerSession {
         ui.style_mut().spacing.item_spacing.x = 0.0;
         ui.horizontal(|ui| {
             ui.add(Label::new(format!("{}.", idx + 1)).text_color(Theme::Disabled));
+            if let Some(anomaly) = anomaly {
+                ui.add(
+                    Label::new(" ⚠")
+                        .small()
+                        .text_color(Theme::Orange)
+                        .sense(Sense::hover()),
+                )
+                .on_hover_text(anomaly_description(anomaly));
+            }
             ui.with_layout(Layout::right_to_left(), |ui| {
                 let popup_id = ui.make_persistent_id(format!("timer-{}", solve.id));
                 let response = ui.add(Label::new("  ☰").small().sense(Sense::click()));
@@ -179,6 +281,16 @@ impl TimerSession {
                 }
                 popup_below_widget(ui, popup_id, &response, |ui| {
                     ui.set_min_width(180.0);
+                    if let Some(anomaly) = anomaly {
+                        ui.add(
+                            Label::new(anomaly_description(anomaly))
+                                .small()
+                                .text_color(Theme::Orange)
+                                .wrap(true),
+                        );
+                        ui.separator();
+                    }
+
                     if ui
                         .add(
                             SelectableLabel::new(
@@ -359,9 +471,16 @@ impl TimerSession {
                             .map(|avg| SessionTime::AverageOfN(avg)),
                         details,
                     );
+                    self.warmup_note(ui);
 
                     ui.add_space(8.0);
                     ui.horizontal(|ui| {
+                        if ui
+                            .add(Label::new("📋  Add times").sense(Sense::click()))
+                            .clicked()
+                        {
+                            self.bulk_entry_requested = true;
+                        }
                         ui.style_mut().visuals.widgets.hovered.fg_stroke = Stroke {
                             width: 1.0,
                             color: Theme::Red.into(),
@@ -380,6 +499,15 @@ impl TimerSession {
                         })
                     });
                 });
+                ui.add_space(4.0);
+                Self::shape_mod_choice(ui, history);
+                self.competition_choice(ui);
+                self.note(ui, history);
+                self.participants(ui, history);
+                ui.add_space(8.0);
+                ui.section("Goals");
+                self.goals(ui, history);
+
                 ui.add_space(8.0);
                 ui.section("Solves");
 
@@ -391,7 +519,14 @@ impl TimerSession {
                     .show(ui, |ui| {
                         let mut has_solves = false;
                         for (idx, solve) in self.solves.iter().enumerate().rev() {
-                            Self::add_solve(ui, idx, solve, history, details);
+                            Self::add_solve(
+                                ui,
+                                idx,
+                                solve,
+                                self.anomalies.get(&solve.id).cloned(),
+                                history,
+                                details,
+                            );
                             has_solves = true;
                         }
                         if !has_solves {
@@ -424,6 +559,12 @@ impl TimerSession {
                     {
                         let _ = history.new_session();
                     }
+                    if ui
+                        .add(Label::new("📋  Add times").sense(Sense::click()))
+                        .clicked()
+                    {
+                        self.bulk_entry_requested = true;
+                    }
                 })
             });
             ui.section_separator();
@@ -527,11 +668,409 @@ impl TimerSession {
                     );
                 }
             });
+            self.warmup_note(ui);
+
+            ui.add_space(4.0);
+        });
+    }
+
+    /// Notes how many warmup solves were left out of the session statistics
+    fn warmup_note(&self, ui: &mut Ui) {
+        if self.warmup_excluded && self.warmup_count != 0 {
+            ui.add(
+                Label::new(format!(
+                    "{} warmup solve{} excluded",
+                    self.warmup_count,
+                    if self.warmup_count == 1 { "" } else { "s" }
+                ))
+                .small()
+                .text_color(Theme::Disabled),
+            );
+        }
+    }
+
+    fn goal_choice(
+        ui: &mut Ui,
+        history: &mut History,
+        session_id: &str,
+        id: &str,
+        current: Option<SessionGoal>,
+        empty_text: &str,
+        choices: Vec<SessionGoal>,
+    ) {
+        let popup_id = ui.make_persistent_id(id);
+        let response = ui.add(
+            Label::new(match current {
+                Some(goal) => format!("{} ⏷", goal_description(&goal)),
+                None => format!("{} ⏷", empty_text),
+            })
+            .small()
+            .text_color(if current.is_some() {
+                Theme::Content
+            } else {
+                Theme::Disabled
+            })
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(160.0);
+            if let Some(goal) = current {
+                if ui
+                    .add(SelectableLabel::new(false, "No goal").text_style(FontSize::Normal.into()))
+                    .clicked()
+                {
+                    history.clear_session_goal(session_id.to_string(), goal.kind());
+                    let _ = history.local_commit();
+                }
+            }
+            for choice in choices {
+                if ui
+                    .add(
+                        SelectableLabel::new(current == Some(choice), goal_description(&choice))
+                            .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_session_goal(session_id.to_string(), choice);
+                    let _ = history.local_commit();
+                }
+            }
+        });
+    }
+
+    /// Shows the shape mod the current session is labeled with, with an option
+    /// to change it
+    fn shape_mod_choice(ui: &mut Ui, history: &mut History) {
+        let session_id = history.current_session().to_string();
+        let current = history.session_shape_mod(&session_id);
+
+        let popup_id = ui.make_persistent_id("session-shape-mod");
+        let response = ui.add(
+            Label::new(format!(
+                "🧩  {} ⏷",
+                match current {
+                    Some(shape_mod) => shape_mod.name(),
+                    None => "Standard cube",
+                }
+            ))
+            .small()
+            .text_color(if current.is_some() {
+                Theme::Content
+            } else {
+                Theme::Disabled
+            })
+            .sense(Sense::click()),
+        );
+        if response.clicked() {
+            ui.memory().toggle_popup(popup_id);
+        }
+        popup_below_widget(ui, popup_id, &response, |ui| {
+            ui.set_min_width(160.0);
+            if ui
+                .add(
+                    SelectableLabel::new(current.is_none(), "Standard cube")
+                        .text_style(FontSize::Normal.into()),
+                )
+                .clicked()
+            {
+                history.set_session_shape_mod(session_id.clone(), None);
+                let _ = history.local_commit();
+            }
+            for choice in ShapeMod::ALL {
+                if ui
+                    .add(
+                        SelectableLabel::new(current == Some(*choice), choice.name())
+                            .text_style(FontSize::Normal.into()),
+                    )
+                    .clicked()
+                {
+                    history.set_session_shape_mod(session_id.clone(), Some(*choice));
+                    let _ = history.local_commit();
+                }
+            }
+        });
+    }
+
+    /// Shows the progress of the current competition round, or an option to
+    /// start one
+    fn competition_choice(&mut self, ui: &mut Ui) {
+        if let Some(round) = &self.competition {
+            let cancel = ui
+                .horizontal(|ui| {
+                    ui.add(
+                        Label::new(format!(
+                            "🏆  {}: solve {} of {}",
+                            round.format().to_string(),
+                            round.solve_count() + 1,
+                            round.format().solve_count()
+                        ))
+                        .small(),
+                    );
+                    ui.add(
+                        Label::new("✖")
+                            .small()
+                            .text_color(Theme::Red)
+                            .sense(Sense::click()),
+                    )
+                    .on_hover_text("Cancel round")
+                    .clicked()
+                })
+                .inner;
+            if cancel {
+                self.competition = None;
+            }
+        } else if let Some(solve_type) = self.solve_type {
+            if ui
+                .add(
+                    Label::new("🏆  Start competition round")
+                        .small()
+                        .text_color(Theme::Disabled)
+                        .sense(Sense::click()),
+                )
+                .on_hover_text(
+                    "Solve a round with fixed scrambles and enforced inspection. The round \
+                        result is added to the session note.",
+                )
+                .clicked()
+            {
+                self.competition = Some(CompetitionRound::new(solve_type));
+            }
+        }
+    }
+
+    /// Shows the note of the current session, with an option to edit it
+    fn note(&mut self, ui: &mut Ui, history: &mut History) {
+        let session_id = history.current_session().to_string();
+        if let Some(text) = &mut self.note_edit {
+            ui.add(TextEdit::multiline(text).desired_rows(3));
+            let (save, cancel) = ui
+                .horizontal(|ui| {
+                    (
+                        ui.add(Label::new("Save").sense(Sense::click())).clicked(),
+                        ui.add(Label::new("Cancel").sense(Sense::click())).clicked(),
+                    )
+                })
+                .inner;
+            if save {
+                history.set_session_note(session_id, text.trim().to_string());
+                let _ = history.local_commit();
+            }
+            if save || cancel {
+                self.note_edit = None;
+            }
+            return;
+        }
+
+        let note = history.session_note(&session_id);
+        if let Some(note) = &note {
+            ui.add(Label::new(note).small().wrap(true));
+        }
+        if ui
+            .add(
+                Label::new(if note.is_some() {
+                    "📝  Edit note"
+                } else {
+                    "📝  Add note"
+                })
+                .small()
+                .text_color(Theme::Disabled)
+                .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            self.note_edit = Some(note.unwrap_or_default());
+        }
+    }
+
+    /// Shows the people taking turns in the current session along with their
+    /// averages, with options to add and remove participants
+    fn participants(&mut self, ui: &mut Ui, history: &mut History) {
+        let session_id = history.current_session().to_string();
+        let next = history.next_participant();
+        let mut removed = None;
+        for participant in history.participants(&session_id) {
+            let solves = history.participant_solves(&session_id, participant.id());
+            let average = solves.as_slice().average();
+            ui.horizontal(|ui| {
+                let name = if next.as_deref() == Some(participant.id()) {
+                    format!("▶  {}", participant.name())
+                } else {
+                    participant.name().to_string()
+                };
+                ui.add(Label::new(name).small());
+                ui.with_layout(Layout::right_to_left(), |ui| {
+                    if ui
+                        .add(
+                            Label::new("✖")
+                                .small()
+                                .text_color(Theme::Disabled)
+                                .sense(Sense::click()),
+                        )
+                        .clicked()
+                    {
+                        removed = Some(participant.id().to_string());
+                    }
+                    ui.add(
+                        Label::new(match average {
+                            Some(time) => solve_time_string(time),
+                            None => "-".into(),
+                        })
+                        .small(),
+                    );
+                });
+            });
+        }
+        if let Some(participant_id) = removed {
+            history.remove_participant(session_id.clone(), participant_id);
+            let _ = history.local_commit();
+        }
 
+        if let Some(name) = &mut self.participant_edit {
+            ui.add(TextEdit::singleline(name));
+            let (add, cancel) = ui
+                .horizontal(|ui| {
+                    (
+                        ui.add(Label::new("Add").sense(Sense::click())).clicked(),
+                        ui.add(Label::new("Cancel").sense(Sense::click())).clicked(),
+                    )
+                })
+                .inner;
+            if add && !name.trim().is_empty() {
+                history.add_participant(session_id, name.trim().to_string());
+                let _ = history.local_commit();
+            }
+            if add || cancel {
+                self.participant_edit = None;
+            }
+            return;
+        }
+
+        if ui
+            .add(
+                Label::new("👥  Add participant")
+                    .small()
+                    .text_color(Theme::Disabled)
+                    .sense(Sense::click()),
+            )
+            .clicked()
+        {
+            self.participant_edit = Some(String::new());
+        }
+    }
+
+    /// Competition round in progress, if any
+    pub fn competition(&self) -> Option<&CompetitionRound> {
+        self.competition.as_ref()
+    }
+
+    pub fn competition_mut(&mut self) -> &mut Option<CompetitionRound> {
+        &mut self.competition
+    }
+
+    /// Checks if the session note or a participant name is being edited,
+    /// which uses the keyboard
+    pub fn editing_note(&self) -> bool {
+        self.note_edit.is_some() || self.participant_edit.is_some()
+    }
+
+    /// Shows progress toward the goals of the current session, with options to
+    /// change each goal
+    fn goals(&self, ui: &mut Ui, history: &mut History) {
+        let session_id = history.current_session().to_string();
+        let progress = goal_progress(&history.session_goals(&session_id), &self.solves);
+
+        for progress in &progress {
+            ui.horizontal(|ui| {
+                ui.add(Label::new(goal_description(&progress.goal)).small());
+                ui.with_layout(Layout::right_to_left(), |ui| {
+                    if progress.complete() {
+                        ui.add(Label::new("✔").small().text_color(Theme::Green));
+                    } else {
+                        ui.add(
+                            Label::new(format!("{:.0}%", progress.fraction * 100.0))
+                                .small()
+                                .text_color(Theme::Disabled),
+                        );
+                    }
+                });
+            });
+
+            // Progress bar
+            let (response, painter) = ui.allocate_painter(
+                Vec2::new(ui.max_rect().width(), GOAL_BAR_HEIGHT),
+                Sense::hover(),
+            );
+            painter.rect_filled(response.rect, 0.0, Theme::BackgroundHighlight);
+            painter.rect_filled(
+                Rect::from_min_size(
+                    response.rect.left_top(),
+                    Vec2::new(
+                        response.rect.width() * progress.fraction,
+                        response.rect.height(),
+                    ),
+                ),
+                0.0,
+                if progress.complete() {
+                    Theme::Green
+                } else {
+                    Theme::Blue
+                },
+            );
             ui.add_space(4.0);
+        }
+
+        let goals: Vec<SessionGoal> = progress.iter().map(|progress| progress.goal).collect();
+        let current = |kind: &str| goals.iter().find(|goal| goal.kind() == kind).cloned();
+        ui.horizontal_wrapped(|ui| {
+            ui.add(Label::new("🎯").small());
+            Self::goal_choice(
+                ui,
+                history,
+                &session_id,
+                "goal-ao12",
+                current("ao12"),
+                "ao12",
+                AVERAGE_GOAL_CHOICES
+                    .iter()
+                    .map(|time| SessionGoal::Average12(*time))
+                    .collect(),
+            );
+            Self::goal_choice(
+                ui,
+                history,
+                &session_id,
+                "goal-solve-count",
+                current("solve_count"),
+                "Solves",
+                SOLVE_COUNT_GOAL_CHOICES
+                    .iter()
+                    .map(|count| SessionGoal::SolveCount(*count))
+                    .collect(),
+            );
+            Self::goal_choice(
+                ui,
+                history,
+                &session_id,
+                "goal-duration",
+                current("duration"),
+                "Time",
+                DURATION_GOAL_CHOICES
+                    .iter()
+                    .map(|minutes| SessionGoal::Duration(*minutes * 60))
+                    .collect(),
+            );
         });
     }
 
+    /// Checks if the user asked to enter a list of times. The request is
+    /// cleared once it has been checked.
+    pub fn take_bulk_entry_request(&mut self) -> bool {
+        std::mem::replace(&mut self.bulk_entry_requested, false)
+    }
+
     pub fn last_solve_time(&self) -> Option<DateTime<Local>> {
         if let Some(solve) = self.solves.last() {
             Some(solve.created)
@@ -542,6 +1081,58 @@ impl TimerSession {
 
     pub fn new_session(&mut self, history: &mut History) {
         history.new_session();
+        self.competition = None;
         self.update(history);
     }
 }
+
+pub fn goal_description(goal: &SessionGoal) -> String {
+    match goal {
+        SessionGoal::Average12(time) => format!("ao12 under {}", solve_time_string(*time)),
+        SessionGoal::SolveCount(count) => format!("{} solves", count),
+        SessionGoal::Duration(seconds) => format!("{} minutes", seconds / 60),
+    }
+}
+
+/// Text of the alert shown when a goal milestone is reached
+pub fn goal_milestone_description(milestone: &GoalMilestone) -> String {
+    match milestone.kind {
+        GoalMilestoneKind::Halfway => {
+            format!("🎯  Halfway to {}", goal_description(&milestone.goal))
+        }
+        GoalMilestoneKind::Complete => {
+            format!("🎯  Goal reached: {}", goal_description(&milestone.goal))
+        }
+    }
+}
+
+/// Text of the alert shown for a practice streak event
+pub fn streak_event_description(event: &StreakEvent) -> String {
+    match event {
+        StreakEvent::AtRisk {
+            streak,
+            remaining_solves,
+        } => format!(
+            "🔥  {} day streak at risk, {} more {} today",
+            streak,
+            remaining_solves,
+            if *remaining_solves == 1 {
+                "solve"
+            } else {
+                "solves"
+            }
+        ),
+        StreakEvent::Extended(streak) => format!(
+            "🔥  {} day streak{}",
+            streak,
+            if *streak == 1 { " started" } else { "" }
+        ),
+    }
+}
+
+fn anomaly_description(anomaly: AnomalyKind) -> &'static str {
+    match anomaly {
+        AnomalyKind::Misfire => "Possible timer misfire, consider deleting this solve",
+        AnomalyKind::Unstopped => "Timer may have been left running, consider a DNF",
+    }
+}
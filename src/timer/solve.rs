@@ -3,15 +3,16 @@ use crate::framerate::Framerate;
 use crate::timer::scramble::TimerCube;
 use crate::timer::state::TimerState;
 use egui::{Pos2, Rect, Ui, Vec2};
+use tpscube_core::SolvingTimeDisplay;
 
 const TARGET_CUBE_FRACTION: f32 = 0.75;
 
-pub fn timer_ui(ui: &mut Ui, center: &Pos2, state: &TimerState) {
+pub fn timer_ui(ui: &mut Ui, center: &Pos2, state: &TimerState, display: SolvingTimeDisplay) {
     // Render timer only in center of screen
     let timer_height = ui.fonts().row_height(FontSize::Timer.into());
     let galley = ui
         .fonts()
-        .layout_single_line(FontSize::Timer.into(), state.current_time_string());
+        .layout_single_line(FontSize::Timer.into(), state.current_time_string(display));
     let timer_width = galley.size.x;
     ui.painter().galley(
         Pos2::new(center.x - timer_width / 2.0, center.y - timer_height / 2.0),
@@ -28,6 +29,7 @@ pub fn bluetooth_timer_ui(
     state: &TimerState,
     cube_rect: &mut Option<Rect>,
     framerate: &mut Framerate,
+    display: SolvingTimeDisplay,
 ) {
     // In Bluetooth mode, render cube as well as timer
     let timer_height = ui.fonts().row_height(FontSize::Timer.into());
@@ -52,7 +54,7 @@ pub fn bluetooth_timer_ui(
     // Draw timer
     let galley = ui
         .fonts()
-        .layout_single_line(FontSize::Timer.into(), state.current_time_string());
+        .layout_single_line(FontSize::Timer.into(), state.current_time_string(display));
     let timer_width = galley.size.x;
     ui.painter().galley(
         Pos2::new(
@@ -2,12 +2,25 @@ use crate::theme::Theme;
 use crate::widgets::{solve_time_short_string, solve_time_string, solve_time_string_ms};
 use egui::{Color32, Key};
 use instant::Instant;
-use tpscube_core::{Analysis, AnalysisSummary, PartialAnalysis, TimedMove};
+use tpscube_core::{
+    Analysis, AnalysisSummary, PartialAnalysis, Penalty, SolvingTimeDisplay, TimedMove,
+    INSPECTION_TIME,
+};
+
+/// Time in milliseconds after the end of inspection when a solve start is
+/// still allowed with a +2 penalty
+const INSPECTION_PLUS_TWO_TIME: u32 = 2000;
 
 #[derive(Clone)]
 pub enum TimerState {
     Inactive(u32, Option<Analysis>),
     Preparing(Instant, u32, Option<Analysis>),
+    /// Inspection in progress with the time inspection started. The flag is
+    /// set once the key that started inspection has been released.
+    Inspection(Instant, bool),
+    /// Start key is being held during inspection, with the time inspection
+    /// started and the time the key was pressed
+    InspectionPreparing(Instant, Instant),
     BluetoothPreparing(Instant, u32, Option<Analysis>),
     ExternalTimerPreparing(u32, Option<Analysis>),
     Ready,
@@ -37,8 +50,51 @@ impl TimerState {
         min * 60000 + sec * 1000 + msec
     }
 
-    pub fn current_time_string(&self) -> String {
+    /// Penalty for starting a solve now, based on when inspection started
+    pub fn inspection_penalty(start: Instant) -> Penalty {
+        let elapsed = (Instant::now() - start).as_millis() as u32;
+        if elapsed <= INSPECTION_TIME {
+            Penalty::None
+        } else if elapsed <= INSPECTION_TIME + INSPECTION_PLUS_TWO_TIME {
+            Penalty::Time(2000)
+        } else {
+            Penalty::DNF
+        }
+    }
+
+    fn inspection_string(start: Instant) -> String {
+        match Self::inspection_penalty(start) {
+            Penalty::None => {
+                let elapsed = (Instant::now() - start).as_millis() as u32;
+                format!("{}", (INSPECTION_TIME - elapsed + 999) / 1000)
+            }
+            Penalty::Time(_) => "+2".into(),
+            Penalty::DNF => "DNF".into(),
+        }
+    }
+
+    /// Running time shown while solving, which may be reduced or hidden to
+    /// avoid distracting the solver
+    fn solving_time_string(time: u32, display: SolvingTimeDisplay) -> String {
+        match display {
+            SolvingTimeDisplay::Tenths => solve_time_short_string(time),
+            SolvingTimeDisplay::Seconds => {
+                let time = time / 1000;
+                if time >= 60 {
+                    format!("{}:{:02}", time / 60, time % 60)
+                } else {
+                    format!("{}", time)
+                }
+            }
+            SolvingTimeDisplay::Hidden => "Solving".into(),
+        }
+    }
+
+    pub fn current_time_string(&self, display: SolvingTimeDisplay) -> String {
         match self {
+            TimerState::Inspection(start, _) | TimerState::InspectionPreparing(start, _) => {
+                Self::inspection_string(*start)
+            }
             TimerState::Inactive(time, _) | TimerState::SolveComplete(time, _) => {
                 solve_time_string(*time)
             }
@@ -47,7 +103,7 @@ impl TimerState {
             }
             TimerState::Preparing(_, time, _) => {
                 if self.is_solving() {
-                    solve_time_short_string(0)
+                    Self::solving_time_string(0, display)
                 } else {
                     solve_time_string(*time)
                 }
@@ -60,13 +116,18 @@ impl TimerState {
             TimerState::Solving(start)
             | TimerState::BluetoothSolving(start, _, _)
             | TimerState::ExternalTimerSolving(start) => {
-                solve_time_short_string((Instant::now() - *start).as_millis() as u32)
+                Self::solving_time_string((Instant::now() - *start).as_millis() as u32, display)
             }
         }
     }
 
     pub fn current_time_color(&self) -> Color32 {
         match self {
+            TimerState::Inspection(start, _) => match Self::inspection_penalty(*start) {
+                Penalty::None => Theme::Yellow.into(),
+                _ => Theme::Red.into(),
+            },
+            TimerState::InspectionPreparing(_, _) => Theme::BackgroundHighlight.into(),
             TimerState::Inactive(_, _)
             | TimerState::BluetoothPreparing(_, _, _)
             | TimerState::ExternalTimerPreparing(_, _)
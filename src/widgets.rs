@@ -2,7 +2,8 @@ use crate::font::{FontSize, LabelFontSize};
 use crate::theme::Theme;
 use chrono::{DateTime, Local};
 use egui::{widgets::Label, Color32, Pos2, Response, Sense, Stroke, Ui, Vec2};
-use tpscube_core::Move;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tpscube_core::{Move, MoveNotation, TimeRounding};
 
 const MIN_SCRAMBLE_LINES: usize = 2;
 const MAX_SCRAMBLE_LINES: usize = 5;
@@ -14,8 +15,34 @@ pub trait CustomWidgets {
     fn section_separator(&mut self);
 }
 
+// Active time rounding mode. This is global so that times can be formatted
+// anywhere without passing the user's preferences.
+static TIME_ROUNDING: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the rounding used when formatting solve times
+pub fn set_time_rounding(rounding: TimeRounding) {
+    TIME_ROUNDING.store(
+        TimeRounding::ALL
+            .iter()
+            .position(|value| *value == rounding)
+            .unwrap_or(0) as u8,
+        Ordering::Relaxed,
+    );
+}
+
+pub fn time_rounding() -> TimeRounding {
+    TimeRounding::ALL
+        .get(TIME_ROUNDING.load(Ordering::Relaxed) as usize)
+        .cloned()
+        .unwrap_or_default()
+}
+
 pub fn solve_time_string(time: u32) -> String {
-    let time = (time + 5) / 10;
+    let rounding = time_rounding();
+    if rounding == TimeRounding::Milliseconds {
+        return solve_time_string_ms(time);
+    }
+    let time = rounding.apply(time);
     if time > 6000 {
         format!(
             "{}:{:02}.{:02}",
@@ -112,15 +139,24 @@ fn scramble_lines(scramble: &[Move], line_count: usize) -> Vec<Vec<Move>> {
     lines
 }
 
-pub fn fit_scramble(ui: &Ui, font: FontSize, scramble: &[Move], width: f32) -> Vec<Vec<Move>> {
-    for line_count in MIN_SCRAMBLE_LINES..MAX_SCRAMBLE_LINES {
+pub fn fit_scramble(
+    ui: &Ui,
+    font: FontSize,
+    scramble: &[Move],
+    notation: MoveNotation,
+    width: f32,
+) -> Vec<Vec<Move>> {
+    // Use more than the maximum number of lines only when the font is too large
+    // for the scramble to fit otherwise, such as in large display mode
+    let max_lines = MAX_SCRAMBLE_LINES.max(scramble.len());
+    for line_count in MIN_SCRAMBLE_LINES..max_lines {
         let lines = scramble_lines(scramble, line_count);
         if !lines.iter().any(|line| {
             ui.fonts()
                 .layout_single_line(
                     font.into(),
                     line.iter()
-                        .map(|mv| mv.to_string())
+                        .map(|mv| mv.to_string_with(notation))
                         .collect::<Vec<String>>()
                         .join("  "),
                 )
@@ -131,7 +167,7 @@ pub fn fit_scramble(ui: &Ui, font: FontSize, scramble: &[Move], width: f32) -> V
             return lines;
         }
     }
-    scramble_lines(scramble, MAX_SCRAMBLE_LINES)
+    scramble_lines(scramble, max_lines)
 }
 
 pub fn color_for_step_index(idx: usize) -> Color32 {
@@ -0,0 +1,213 @@
+use crate::sync::TABLE_NAME;
+use anyhow::{anyhow, Result};
+use rusoto_dynamodb::{
+    AttributeValue, DeleteItemInput, DynamoDb, DynamoDbClient, PutItemInput, QueryInput,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tpscube_core::{AccountDevice, AccountOperation, AccountRequest, AccountResponse, SyncRequest};
+
+pub const ACCOUNT_TABLE_NAME: &'static str = "tpscube_account";
+
+struct DeviceItem {
+    device: AccountDevice,
+    token: String,
+}
+
+pub async fn perform_account(client: &DynamoDbClient, request: Value) -> Result<Value> {
+    let request = AccountRequest::deserialize(request)?;
+    let mut devices = query_devices(client, &request.sync_key).await?;
+
+    let token = match &request.operation {
+        AccountOperation::Create => {
+            if devices.len() != 0 {
+                return Err(anyhow!(
+                    "An account already exists for this sync key, log in instead"
+                ));
+            }
+            Some(login_device(client, &request, &mut devices).await?)
+        }
+        AccountOperation::Login => {
+            if devices.len() == 0 {
+                return Err(anyhow!("No account exists for this sync key"));
+            }
+            Some(login_device(client, &request, &mut devices).await?)
+        }
+        operation => {
+            // All other operations require a valid token for the device
+            let authorized = devices.iter().any(|item| {
+                item.device.id == request.device_id && Some(&item.token) == request.token.as_ref()
+            });
+            if !authorized {
+                return Err(anyhow!("This device is not logged in to the account"));
+            }
+
+            match operation {
+                AccountOperation::RevokeDevice(device) => {
+                    delete_device(client, &request.sync_key, device).await?;
+                    devices.retain(|item| &item.device.id != device);
+                }
+                AccountOperation::WipeData => {
+                    delete_actions(client, &request.sync_key).await?;
+                    for item in &devices {
+                        delete_device(client, &request.sync_key, &item.device.id).await?;
+                    }
+                    devices.clear();
+                }
+                _ => (),
+            }
+            None
+        }
+    };
+
+    AccountResponse {
+        token,
+        devices: devices.into_iter().map(|item| item.device).collect(),
+    }
+    .serialize()
+}
+
+fn string_value(value: &str) -> AttributeValue {
+    AttributeValue {
+        s: Some(value.into()),
+        ..Default::default()
+    }
+}
+
+fn device_key(sync_key: &str, device: &str) -> HashMap<String, AttributeValue> {
+    let mut key = HashMap::new();
+    key.insert("sync_key".into(), string_value(sync_key));
+    key.insert("device".into(), string_value(device));
+    key
+}
+
+async fn query_devices(client: &DynamoDbClient, sync_key: &str) -> Result<Vec<DeviceItem>> {
+    let mut values = HashMap::new();
+    values.insert(":key".into(), string_value(sync_key));
+    let query = QueryInput {
+        table_name: ACCOUNT_TABLE_NAME.into(),
+        key_condition_expression: Some("sync_key = :key".into()),
+        expression_attribute_values: Some(values),
+        ..Default::default()
+    };
+    let result = client.query(query).await?;
+
+    let mut devices = Vec::new();
+    if let Some(items) = result.items {
+        for item in items {
+            let field = |name: &str| -> Result<String> {
+                item.get(name)
+                    .ok_or_else(|| anyhow!("Missing {} in account query result", name))?
+                    .s
+                    .clone()
+                    .ok_or_else(|| anyhow!("Account {} is not a string", name))
+            };
+            devices.push(DeviceItem {
+                device: AccountDevice {
+                    id: field("device")?,
+                    name: field("name")?,
+                    last_login: item
+                        .get("last_login")
+                        .and_then(|value| value.n.as_ref())
+                        .ok_or_else(|| anyhow!("Missing login time in account query result"))?
+                        .parse()?,
+                },
+                token: field("token")?,
+            });
+        }
+    }
+    Ok(devices)
+}
+
+/// Issues a new token for the requesting device, replacing any previous
+/// token of the device
+async fn login_device(
+    client: &DynamoDbClient,
+    request: &AccountRequest,
+    devices: &mut Vec<DeviceItem>,
+) -> Result<String> {
+    // Tokens use the same generator as sync keys, which gives around 110 bits
+    // of randomness
+    let token = SyncRequest::new_sync_key();
+    let last_login = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+
+    let mut item = device_key(&request.sync_key, &request.device_id);
+    item.insert("name".into(), string_value(&request.device_name));
+    item.insert("token".into(), string_value(&token));
+    item.insert(
+        "last_login".into(),
+        AttributeValue {
+            n: Some(format!("{}", last_login)),
+            ..Default::default()
+        },
+    );
+    let put = PutItemInput {
+        table_name: ACCOUNT_TABLE_NAME.into(),
+        item,
+        ..Default::default()
+    };
+    client.put_item(put).await?;
+
+    devices.retain(|item| item.device.id != request.device_id);
+    devices.push(DeviceItem {
+        device: AccountDevice {
+            id: request.device_id.clone(),
+            name: request.device_name.clone(),
+            last_login,
+        },
+        token: token.clone(),
+    });
+    Ok(token)
+}
+
+async fn delete_device(client: &DynamoDbClient, sync_key: &str, device: &str) -> Result<()> {
+    let delete = DeleteItemInput {
+        table_name: ACCOUNT_TABLE_NAME.into(),
+        key: device_key(sync_key, device),
+        ..Default::default()
+    };
+    client.delete_item(delete).await?;
+    Ok(())
+}
+
+/// Deletes every synced action stored for the sync key
+async fn delete_actions(client: &DynamoDbClient, sync_key: &str) -> Result<()> {
+    let mut start_key = None;
+    loop {
+        let mut values = HashMap::new();
+        values.insert(":key".into(), string_value(sync_key));
+        let query = QueryInput {
+            table_name: TABLE_NAME.into(),
+            key_condition_expression: Some("sync_key = :key".into()),
+            expression_attribute_values: Some(values),
+            projection_expression: Some("sync_key, sync_id".into()),
+            exclusive_start_key: start_key,
+            ..Default::default()
+        };
+        let result = client.query(query).await?;
+
+        if let Some(items) = result.items {
+            for item in items {
+                let sync_id = item
+                    .get("sync_id")
+                    .ok_or_else(|| anyhow!("Missing sync ID in query result"))?
+                    .clone();
+                let mut key = HashMap::new();
+                key.insert("sync_key".into(), string_value(sync_key));
+                key.insert("sync_id".into(), sync_id);
+                let delete = DeleteItemInput {
+                    table_name: TABLE_NAME.into(),
+                    key,
+                    ..Default::default()
+                };
+                client.delete_item(delete).await?;
+            }
+        }
+
+        start_key = result.last_evaluated_key;
+        if start_key.is_none() {
+            return Ok(());
+        }
+    }
+}
@@ -1,4 +1,6 @@
+mod account;
 mod query;
+mod race;
 mod store;
 mod sync;
 
@@ -0,0 +1,128 @@
+use anyhow::{anyhow, Result};
+use rusoto_dynamodb::{AttributeValue, DynamoDb, DynamoDbClient, PutItemInput, QueryInput};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tpscube_core::{RacePlayerResults, RaceRequest, RaceResponse};
+
+pub const RACE_TABLE_NAME: &'static str = "tpscube_race";
+
+// Races are short lived, so race records are expired by the database after a day. The table
+// must have time to live enabled on the "expires" attribute.
+const RACE_EXPIRATION_SECS: u64 = 24 * 60 * 60;
+
+const MAX_PLAYER_NAME_LENGTH: usize = 32;
+const MAX_RACE_RESULTS: usize = 1000;
+
+fn validate_race_request(request: &RaceRequest) -> Result<()> {
+    if request.player.trim().is_empty() {
+        return Err(anyhow!("Player name is empty"));
+    }
+    if request.player.chars().count() > MAX_PLAYER_NAME_LENGTH {
+        return Err(anyhow!("Player name is too long"));
+    }
+    if request.results.len() > MAX_RACE_RESULTS {
+        return Err(anyhow!("Too many race results"));
+    }
+
+    let mut indices = HashSet::new();
+    for result in &request.results {
+        if result.index >= MAX_RACE_RESULTS {
+            return Err(anyhow!("Race result index is out of range"));
+        }
+        if !indices.insert(result.index) {
+            return Err(anyhow!("Duplicate race result index"));
+        }
+    }
+    Ok(())
+}
+
+pub async fn perform_race(client: &DynamoDbClient, request: Value) -> Result<Value> {
+    let request = RaceRequest::deserialize(request)?;
+    validate_race_request(&request)?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+    // Store this player's results. Each player has a single item in the race that
+    // is replaced with the full result list on every request.
+    let mut item = HashMap::new();
+    item.insert(
+        "race_key".into(),
+        AttributeValue {
+            s: Some(request.race_key.clone()),
+            ..Default::default()
+        },
+    );
+    item.insert(
+        "player".into(),
+        AttributeValue {
+            s: Some(request.player.clone()),
+            ..Default::default()
+        },
+    );
+    item.insert(
+        "data".into(),
+        AttributeValue {
+            s: Some(request.serialize()?.to_string()),
+            ..Default::default()
+        },
+    );
+    item.insert(
+        "expires".into(),
+        AttributeValue {
+            n: Some(format!("{}", now + RACE_EXPIRATION_SECS)),
+            ..Default::default()
+        },
+    );
+    let put = PutItemInput {
+        table_name: RACE_TABLE_NAME.into(),
+        item,
+        ..Default::default()
+    };
+    client.put_item(put).await?;
+
+    // Query the results of all players in the race. Expired records are not removed by the
+    // database right away, so filter them out here.
+    let mut values = HashMap::new();
+    values.insert(
+        ":key".into(),
+        AttributeValue {
+            s: Some(request.race_key.clone()),
+            ..Default::default()
+        },
+    );
+    values.insert(
+        ":now".into(),
+        AttributeValue {
+            n: Some(format!("{}", now)),
+            ..Default::default()
+        },
+    );
+    let query = QueryInput {
+        table_name: RACE_TABLE_NAME.into(),
+        key_condition_expression: Some("race_key = :key".into()),
+        filter_expression: Some("expires > :now".into()),
+        expression_attribute_values: Some(values),
+        ..Default::default()
+    };
+    let result = client.query(query).await?;
+
+    let mut players = Vec::new();
+    if let Some(items) = result.items {
+        for item in items {
+            let data = item
+                .get("data")
+                .ok_or_else(|| anyhow!("Missing race data in query result"))?
+                .s
+                .as_ref()
+                .ok_or_else(|| anyhow!("Race data is not a string in query result"))?;
+            let player = RaceRequest::deserialize(serde_json::from_str(data)?)?;
+            players.push(RacePlayerResults {
+                player: player.player,
+                results: player.results,
+            });
+        }
+    }
+
+    RaceResponse { players }.serialize()
+}
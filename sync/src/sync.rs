@@ -1,4 +1,6 @@
+use crate::account::perform_account;
 use crate::query::query_updates;
+use crate::race::perform_race;
 use crate::store::store_actions;
 use anyhow::{anyhow, Result};
 use lambda_http::{http::StatusCode, lambda_runtime::Error, Body, Request, Response};
@@ -34,9 +36,20 @@ pub async fn perform_sync(request: Request) -> Result<Value> {
         return Err(anyhow!("API version mismatch, please update the client"));
     }
 
-    let request = SyncRequest::deserialize(request)?;
     let client = DynamoDbClient::new(Region::UsEast1);
 
+    // Race requests share the endpoint with sync requests
+    if request.get("race_key").is_some() {
+        return perform_race(&client, request).await;
+    }
+
+    // Account management requests also share the endpoint
+    if request.get("account").is_some() {
+        return perform_account(&client, request).await;
+    }
+
+    let request = SyncRequest::deserialize(request)?;
+
     // Get any new updates based on client's last sync
     let updates = query_updates(&client, &request.sync_key, request.sync_id).await?;
 
@@ -32,6 +32,7 @@ async fn main() {
                 time: (solution.len() * 1000) as u32,
                 penalty: Penalty::None,
                 device: Some("test".to_string()),
+                participant: None,
                 moves: Some(moves),
             };
             history.new_solve(solve);